@@ -0,0 +1,613 @@
+//! 应用注册表
+//!
+//! Claude / Codex / Gemini 由 [`AppType`] 及各自的专用逻辑处理；其他 CLI 工具
+//! 以 [`AppDefinition`] 描述：配置文件路径、格式、写入方式与必填字段。
+//! 注册表由内置定义与用户定义（`~/.cc-switch/apps/*.toml`）组成，
+//! 供应商数据与内置应用共用同一张表，按应用 id 区分。
+//!
+//! 用户定义示例（`~/.cc-switch/apps/crush.toml`）：
+//!
+//! ```toml
+//! id = "crush"
+//! name = "Crush"
+//! config_path = "~/.config/crush/crush.json"
+//! format = "json"
+//! merge_keys = ["providers"]
+//! required_fields = ["/providers"]
+//! ```
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::app_config::AppType;
+use crate::config::{get_app_config_dir, write_json_file, write_text_file};
+use crate::error::AppError;
+use crate::provider::LiveWriteMode;
+
+/// live 配置文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LiveFormat {
+    Json,
+    Toml,
+    /// `KEY=VALUE` 形式的 .env 文件；供应商配置须为字符串值的对象
+    Env,
+}
+
+/// 新建供应商时可选的配置模板
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppPreset {
+    pub name: String,
+    pub settings: Value,
+}
+
+/// 切换时一并深度合并到另一个 JSON 文件中的固定内容（如认证方式）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonFilePatch {
+    /// 目标文件路径，支持 `~` 开头
+    pub path: String,
+    pub patch: Value,
+}
+
+/// 一个可切换供应商的应用
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppDefinition {
+    /// 应用 id（小写字母、数字、`-`、`_`），同时作为数据库中的 app_type
+    pub id: String,
+    pub name: String,
+    /// live 配置文件路径，支持 `~` 开头
+    #[serde(alias = "config_path")]
+    pub config_path: String,
+    pub format: LiveFormat,
+    /// 写入方式：合并（默认，仅替换供应商配置中出现的顶层键）或整体覆盖
+    #[serde(default, alias = "write_mode")]
+    pub write_mode: LiveWriteMode,
+    /// 供应商配置中必须存在且非空的字段（JSON Pointer，如 `/env/API_KEY`）
+    #[serde(default, alias = "required_fields")]
+    pub required_fields: Vec<String>,
+    /// 合并写入时按条目合并（而非整体替换）的顶层对象，如 OpenCode 的 `provider`
+    #[serde(default, alias = "merge_keys")]
+    pub merge_keys: Vec<String>,
+    /// 新建供应商时可选的配置模板
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub presets: Vec<AppPreset>,
+    /// 切换时额外合并到其他 JSON 文件的内容
+    #[serde(default, alias = "json_patch", skip_serializing_if = "Option::is_none")]
+    pub json_patch: Option<JsonFilePatch>,
+    /// 内置应用（Claude / Codex / Gemini）由专用逻辑处理
+    #[serde(default, skip_deserializing)]
+    pub native: bool,
+}
+
+impl AppDefinition {
+    /// 展开 `~` 后的配置文件路径
+    pub fn config_file(&self) -> PathBuf {
+        expand_home(&self.config_path)
+    }
+
+    /// 对应的内置 [`AppType`]（仅 native 应用）
+    pub fn app_type(&self) -> Option<AppType> {
+        self.native
+            .then(|| AppType::from_str(&self.id).ok())
+            .flatten()
+    }
+
+    /// 校验供应商配置，返回缺失的必填字段
+    pub fn missing_fields(&self, settings: &Value) -> Vec<String> {
+        self.required_fields
+            .iter()
+            .filter(|pointer| match settings.pointer(pointer) {
+                None | Some(Value::Null) => true,
+                Some(Value::String(s)) => s.trim().is_empty(),
+                Some(_) => false,
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// 将供应商配置写入 live 配置文件（非 native 应用），并应用 `json_patch`
+    pub fn write_live(&self, settings: &Value) -> Result<(), AppError> {
+        self.write_config_file(settings)?;
+        if let Some(extra) = &self.json_patch {
+            let path = expand_home(&extra.path);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
+            }
+            let mut target = if path.exists() {
+                crate::config::read_json_file::<Value>(&path)?
+            } else {
+                Value::Object(Map::new())
+            };
+            deep_merge(&mut target, &extra.patch);
+            write_json_file(&path, &target)?;
+        }
+        Ok(())
+    }
+
+    fn write_config_file(&self, settings: &Value) -> Result<(), AppError> {
+        let Value::Object(incoming) = settings else {
+            return Err(AppError::InvalidInput(format!(
+                "{} 的供应商配置必须是 JSON 对象",
+                self.name
+            )));
+        };
+        let path = self.config_file();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
+        }
+        let merge = self.write_mode == LiveWriteMode::Merge && path.exists();
+
+        match self.format {
+            LiveFormat::Json => {
+                let mut target = if merge {
+                    match crate::config::read_json_file::<Value>(&path)? {
+                        Value::Object(map) => map,
+                        _ => Map::new(),
+                    }
+                } else {
+                    Map::new()
+                };
+                for (key, value) in incoming {
+                    match (target.get_mut(key), value) {
+                        (Some(Value::Object(existing)), Value::Object(entries))
+                            if self.merge_keys.contains(key) =>
+                        {
+                            for (name, entry) in entries {
+                                existing.insert(name.clone(), entry.clone());
+                            }
+                        }
+                        _ => {
+                            target.insert(key.clone(), value.clone());
+                        }
+                    }
+                }
+                write_json_file(&path, &Value::Object(target))
+            }
+            LiveFormat::Toml => {
+                let mut target = if merge {
+                    let text =
+                        std::fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+                    toml::from_str::<toml::Table>(&text).map_err(|e| {
+                        AppError::Config(format!("解析 {} 失败: {e}", path.display()))
+                    })?
+                } else {
+                    toml::Table::new()
+                };
+                let incoming: toml::Table = serde_json::from_value(settings.clone())
+                    .map_err(|e| AppError::Config(format!("供应商配置无法转换为 TOML: {e}")))?;
+                target.extend(incoming);
+                let text = toml::to_string_pretty(&target)
+                    .map_err(|e| AppError::Config(format!("序列化 TOML 失败: {e}")))?;
+                write_text_file(&path, &text)
+            }
+            LiveFormat::Env => {
+                use crate::gemini_config::{parse_env_file, serialize_env_file};
+
+                let mut target = if merge {
+                    let text =
+                        std::fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+                    parse_env_file(&text)
+                } else {
+                    HashMap::new()
+                };
+                for (key, value) in incoming {
+                    let value = value.as_str().ok_or_else(|| {
+                        AppError::InvalidInput(format!("环境变量 {key} 的值必须是字符串"))
+                    })?;
+                    target.insert(key.clone(), value.to_string());
+                }
+                write_text_file(&path, &serialize_env_file(&target))
+            }
+        }
+    }
+}
+
+/// 内置应用定义
+pub fn builtin_definitions() -> Vec<AppDefinition> {
+    let native = |id: &str, name: &str, path: PathBuf, format: LiveFormat| AppDefinition {
+        id: id.to_string(),
+        name: name.to_string(),
+        config_path: path.to_string_lossy().to_string(),
+        format,
+        write_mode: LiveWriteMode::Merge,
+        required_fields: Vec::new(),
+        merge_keys: Vec::new(),
+        presets: Vec::new(),
+        json_patch: None,
+        native: true,
+    };
+    vec![
+        native(
+            "claude",
+            "Claude Code",
+            crate::config::get_claude_settings_path(),
+            LiveFormat::Json,
+        ),
+        native(
+            "codex",
+            "Codex",
+            crate::codex_config::get_codex_config_path(),
+            LiveFormat::Toml,
+        ),
+        native(
+            "gemini",
+            "Gemini CLI",
+            crate::gemini_config::get_gemini_env_path(),
+            LiveFormat::Env,
+        ),
+        opencode_definition(),
+        qwen_definition(),
+        iflow_definition(),
+    ]
+}
+
+/// OpenCode（`~/.config/opencode/opencode.json`）
+///
+/// 供应商配置写入 `provider` 映射中的同名条目（保留用户的其他条目），
+/// 并通过顶层 `model`（`<provider>/<model>`）选中该供应商。
+fn opencode_definition() -> AppDefinition {
+    AppDefinition {
+        id: "opencode".to_string(),
+        name: "OpenCode".to_string(),
+        config_path: "~/.config/opencode/opencode.json".to_string(),
+        format: LiveFormat::Json,
+        write_mode: LiveWriteMode::Merge,
+        required_fields: vec!["/provider".to_string(), "/model".to_string()],
+        merge_keys: vec!["provider".to_string()],
+        presets: vec![AppPreset {
+            name: "OpenAI Compatible".to_string(),
+            settings: serde_json::json!({
+            "$schema": "https://opencode.ai/config.json",
+            "provider": {
+                "custom": {
+                    "npm": "@ai-sdk/openai-compatible",
+                    "name": "Custom",
+                    "options": {
+                        "baseURL": "https://api.example.com/v1",
+                        "apiKey": ""
+                    },
+                    "models": {
+                        "model-id": { "name": "model-id" }
+                    }
+                }
+            },
+            "model": "custom/model-id"
+            }),
+        }],
+        json_patch: None,
+        native: false,
+    }
+}
+
+/// Qwen Code（`~/.qwen/.env` + `~/.qwen/settings.json`）
+///
+/// 供应商配置为 OpenAI 兼容的环境变量，写入 `.env`；同时将 settings.json 的
+/// 认证方式设为 `openai`，使 Qwen Code 读取这些变量而非走 Qwen OAuth 登录。
+fn qwen_definition() -> AppDefinition {
+    let preset = |name: &str, base_url: &str, model: &str| AppPreset {
+        name: name.to_string(),
+        settings: serde_json::json!({
+            "OPENAI_API_KEY": "",
+            "OPENAI_BASE_URL": base_url,
+            "OPENAI_MODEL": model
+        }),
+    };
+    AppDefinition {
+        id: "qwen".to_string(),
+        name: "Qwen Code".to_string(),
+        config_path: "~/.qwen/.env".to_string(),
+        format: LiveFormat::Env,
+        write_mode: LiveWriteMode::Merge,
+        required_fields: vec![
+            "/OPENAI_API_KEY".to_string(),
+            "/OPENAI_BASE_URL".to_string(),
+        ],
+        merge_keys: Vec::new(),
+        presets: vec![
+            preset(
+                "DashScope",
+                "https://dashscope.aliyuncs.com/compatible-mode/v1",
+                "qwen3-coder-plus",
+            ),
+            preset(
+                "DashScope International",
+                "https://dashscope-intl.aliyuncs.com/compatible-mode/v1",
+                "qwen3-coder-plus",
+            ),
+            preset(
+                "ModelScope",
+                "https://api-inference.modelscope.cn/v1",
+                "Qwen/Qwen3-Coder-480B-A35B-Instruct",
+            ),
+        ],
+        json_patch: Some(JsonFilePatch {
+            path: "~/.qwen/settings.json".to_string(),
+            patch: serde_json::json!({ "security": { "auth": { "selectedType": "openai" } } }),
+        }),
+        native: false,
+    }
+}
+
+/// iFlow CLI（`~/.iflow/settings.json`）
+fn iflow_definition() -> AppDefinition {
+    AppDefinition {
+        id: "iflow".to_string(),
+        name: "iFlow CLI".to_string(),
+        config_path: "~/.iflow/settings.json".to_string(),
+        format: LiveFormat::Json,
+        write_mode: LiveWriteMode::Merge,
+        required_fields: vec!["/apiKey".to_string(), "/baseUrl".to_string()],
+        merge_keys: Vec::new(),
+        presets: vec![AppPreset {
+            name: "iFlow".to_string(),
+            settings: serde_json::json!({
+                "selectedAuthType": "openai-compatible",
+                "apiKey": "",
+                "baseUrl": "https://apis.iflow.cn/v1",
+                "modelName": "qwen3-coder-plus"
+            }),
+        }],
+        json_patch: None,
+        native: false,
+    }
+}
+
+/// 递归合并对象；非对象值直接覆盖
+fn deep_merge(target: &mut Value, patch: &Value) {
+    match (target, patch) {
+        (Value::Object(target), Value::Object(patch)) => {
+            for (key, value) in patch {
+                match target.get_mut(key) {
+                    Some(existing) if existing.is_object() && value.is_object() => {
+                        deep_merge(existing, value)
+                    }
+                    _ => {
+                        target.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (target, patch) => *target = patch.clone(),
+    }
+}
+
+/// 用户自定义应用目录（`~/.cc-switch/apps`）
+pub fn get_user_apps_dir() -> PathBuf {
+    get_app_config_dir().join("apps")
+}
+
+/// 应用注册表：内置定义 + 用户定义
+pub struct AppRegistry {
+    apps: Vec<AppDefinition>,
+}
+
+impl AppRegistry {
+    /// 加载内置定义与 `~/.cc-switch/apps/*.toml`
+    ///
+    /// 无法解析或与已有 id 冲突的用户定义会被跳过并记录警告。
+    pub fn load() -> Self {
+        let mut registry = Self {
+            apps: builtin_definitions(),
+        };
+        for definition in load_user_definitions(&get_user_apps_dir()) {
+            if let Err(e) = registry.register(definition) {
+                log::warn!("忽略应用定义: {e}");
+            }
+        }
+        registry
+    }
+
+    /// 注册一个应用定义
+    pub fn register(&mut self, definition: AppDefinition) -> Result<(), AppError> {
+        validate_app_id(&definition.id)?;
+        if self.get(&definition.id).is_some() {
+            return Err(AppError::InvalidInput(format!(
+                "应用 id 已存在: {}",
+                definition.id
+            )));
+        }
+        self.apps.push(definition);
+        Ok(())
+    }
+
+    pub fn all(&self) -> &[AppDefinition] {
+        &self.apps
+    }
+
+    pub fn get(&self, id: &str) -> Option<&AppDefinition> {
+        let id = id.trim().to_lowercase();
+        self.apps.iter().find(|app| app.id == id)
+    }
+
+    /// 查找已注册应用，未注册时返回错误
+    pub fn require(&self, id: &str) -> Result<&AppDefinition, AppError> {
+        self.get(id).ok_or_else(|| {
+            let known: Vec<&str> = self.apps.iter().map(|app| app.id.as_str()).collect();
+            AppError::localized(
+                "unsupported_app",
+                format!("不支持的应用标识: '{id}'。可选值: {}。", known.join(", ")),
+                format!("Unsupported app id: '{id}'. Allowed: {}.", known.join(", ")),
+            )
+        })
+    }
+}
+
+fn load_user_definitions(dir: &Path) -> Vec<AppDefinition> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .collect();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let parsed = std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| toml::from_str::<AppDefinition>(&text).map_err(|e| e.to_string()));
+            match parsed {
+                Ok(definition) => Some(definition),
+                Err(e) => {
+                    log::warn!("解析应用定义 {} 失败: {e}", path.display());
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+fn validate_app_id(id: &str) -> Result<(), AppError> {
+    let valid = !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::InvalidInput(format!(
+            "无效的应用 id: '{id}'（仅允许小写字母、数字、- 和 _）"
+        )))
+    }
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/").or_else(|| path.strip_prefix("~\\")) {
+        Some(rest) => crate::config::home_dir().unwrap_or_default().join(rest),
+        None if path == "~" => crate::config::home_dir().unwrap_or_default(),
+        None => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn definition(format: LiveFormat, path: &Path) -> AppDefinition {
+        AppDefinition {
+            id: "tool".to_string(),
+            name: "Tool".to_string(),
+            config_path: path.to_string_lossy().to_string(),
+            format,
+            write_mode: LiveWriteMode::Merge,
+            required_fields: vec!["/env/API_KEY".to_string()],
+            merge_keys: Vec::new(),
+            presets: Vec::new(),
+            json_patch: None,
+            native: false,
+        }
+    }
+
+    #[test]
+    fn user_definitions_are_parsed_and_cannot_shadow_builtins() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        std::fs::write(
+            dir.path().join("tool.toml"),
+            "id = \"tool\"\nname = \"Tool\"\nconfig_path = \"~/.tool/config.json\"\nformat = \"json\"\n",
+        )
+        .expect("write definition");
+        std::fs::write(dir.path().join("broken.toml"), "id = ").expect("write broken");
+
+        let definitions = load_user_definitions(dir.path());
+        assert_eq!(definitions.len(), 1);
+        assert_eq!(definitions[0].format, LiveFormat::Json);
+        assert!(!definitions[0].native);
+
+        let mut registry = AppRegistry {
+            apps: builtin_definitions(),
+        };
+        registry
+            .register(definitions[0].clone())
+            .expect("register tool");
+        assert!(registry.get("TOOL").is_some());
+        assert!(registry.get("claude").unwrap().app_type().is_some());
+
+        let mut shadow = definitions[0].clone();
+        shadow.id = "codex".to_string();
+        assert!(registry.register(shadow).is_err());
+        assert!(registry.require("nope").is_err());
+    }
+
+    #[test]
+    fn json_write_merges_top_level_keys_and_checks_required_fields() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let path = dir.path().join("config.json");
+        std::fs::write(&path, r#"{"theme":"dark","env":{"OLD":"1"}}"#).expect("seed");
+
+        let app = definition(LiveFormat::Json, &path);
+        let settings = json!({ "env": { "API_KEY": "" } });
+        assert_eq!(app.missing_fields(&settings), vec!["/env/API_KEY"]);
+
+        app.write_live(&json!({ "env": { "API_KEY": "k" } }))
+            .expect("write live");
+        let written: Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(
+            written,
+            json!({ "theme": "dark", "env": { "API_KEY": "k" } })
+        );
+    }
+
+    #[test]
+    fn opencode_merges_provider_entries() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let path = dir.path().join("opencode.json");
+        std::fs::write(
+            &path,
+            r#"{"theme":"opencode","provider":{"mine":{"name":"Mine"}},"model":"mine/a"}"#,
+        )
+        .expect("seed");
+
+        let mut app = opencode_definition();
+        app.config_path = path.to_string_lossy().to_string();
+        let preset = app.presets[0].settings.clone();
+        assert!(app.missing_fields(&preset).is_empty());
+
+        app.write_live(&preset).expect("write live");
+        let written: Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(written["theme"], "opencode");
+        assert_eq!(written["provider"]["mine"]["name"], "Mine");
+        assert_eq!(
+            written["provider"]["custom"]["options"]["baseURL"],
+            "https://api.example.com/v1"
+        );
+        assert_eq!(written["model"], "custom/model-id");
+    }
+
+    #[test]
+    fn qwen_writes_env_and_selects_openai_auth() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let settings_path = dir.path().join("settings.json");
+        std::fs::write(
+            &settings_path,
+            r#"{"ui":{"theme":"dark"},"security":{"auth":{"selectedType":"qwen-oauth"}}}"#,
+        )
+        .expect("seed settings");
+
+        let mut app = qwen_definition();
+        app.config_path = dir.path().join(".env").to_string_lossy().to_string();
+        app.json_patch.as_mut().unwrap().path = settings_path.to_string_lossy().to_string();
+
+        let mut settings = app.presets[2].settings.clone();
+        assert_eq!(app.missing_fields(&settings), vec!["/OPENAI_API_KEY"]);
+        settings["OPENAI_API_KEY"] = json!("ms-key");
+        app.write_live(&settings).expect("write live");
+
+        let env = std::fs::read_to_string(dir.path().join(".env")).unwrap();
+        assert!(env.contains("OPENAI_BASE_URL=https://api-inference.modelscope.cn/v1"));
+        assert!(env.contains("OPENAI_API_KEY=ms-key"));
+        let written: Value =
+            serde_json::from_str(&std::fs::read_to_string(&settings_path).unwrap()).unwrap();
+        assert_eq!(written["security"]["auth"]["selectedType"], "openai");
+        assert_eq!(written["ui"]["theme"], "dark");
+    }
+}
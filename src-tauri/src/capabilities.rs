@@ -0,0 +1,98 @@
+//! 能力报告
+//!
+//! 列出当前构建中可用的子系统、命令行子命令与 RPC 方法，供脚本和前端在调用前
+//! 判断功能是否存在，而不是在遇到缺失的子命令时直接失败。
+
+use serde::Serialize;
+
+use crate::cli::SUBCOMMANDS;
+use crate::cli_error::{ExitCode, EXIT_CODES};
+use crate::database::SCHEMA_VERSION;
+use crate::rpc::METHODS;
+
+/// 一个可选子系统
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Capability {
+    /// 稳定标识（如 `proxy`）
+    pub name: &'static str,
+    pub available: bool,
+    /// 不可用时的原因，或可用时的补充说明
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<&'static str>,
+}
+
+/// 当前构建的能力报告
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapabilityReport {
+    pub version: &'static str,
+    pub schema_version: i32,
+    pub capabilities: Vec<Capability>,
+    pub subcommands: Vec<&'static str>,
+    pub rpc_methods: Vec<&'static str>,
+    /// 命令行退出码约定
+    pub exit_codes: Vec<ExitCode>,
+}
+
+impl CapabilityReport {
+    /// 某个子系统是否可用
+    pub fn has(&self, name: &str) -> bool {
+        self.capabilities
+            .iter()
+            .any(|c| c.name == name && c.available)
+    }
+}
+
+/// 生成能力报告
+pub fn capabilities() -> CapabilityReport {
+    CapabilityReport {
+        version: env!("CARGO_PKG_VERSION"),
+        schema_version: SCHEMA_VERSION,
+        capabilities: vec![
+            Capability {
+                name: "proxy",
+                available: true,
+                detail: Some("本地代理、故障转移与热切换"),
+            },
+            Capability {
+                name: "usage",
+                available: true,
+                detail: Some("请求日志、费用统计与用量计数器"),
+            },
+            Capability {
+                name: "sync",
+                available: true,
+                detail: Some("WebDAV / S3 兼容存储上的加密数据库快照同步"),
+            },
+            Capability {
+                name: "keyring",
+                available: false,
+                detail: Some("此构建未集成系统钥匙串，密钥保存在本地数据库中"),
+            },
+            Capability {
+                name: "tui",
+                available: true,
+                detail: Some("终端界面（cc-switch tui）"),
+            },
+        ],
+        subcommands: SUBCOMMANDS.to_vec(),
+        rpc_methods: METHODS.to_vec(),
+        exit_codes: EXIT_CODES.to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_lists_subsystems_and_entry_points() {
+        let report = capabilities();
+        assert!(report.has("proxy"));
+        assert!(!report.has("keyring"));
+        assert!(!report.has("unknown"));
+        assert!(report.subcommands.contains(&"capabilities"));
+        assert!(report.rpc_methods.contains(&"capabilities"));
+    }
+}
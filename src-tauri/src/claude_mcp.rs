@@ -154,6 +154,66 @@ pub fn clear_has_completed_onboarding() -> Result<bool, AppError> {
     Ok(true)
 }
 
+/// Claude Code 在 customApiKeyResponses 中记录的 Key 后缀长度
+const APPROVED_KEY_SUFFIX_LEN: usize = 20;
+
+/// 把 API Key 记入 ~/.claude.json 的 `customApiKeyResponses.approved`，Claude Code 启动时便不再
+/// 询问是否使用 `ANTHROPIC_API_KEY`；返回是否有修改
+///
+/// 路径与 MCP 配置相同（见 [`get_claude_mcp_path`]），在 Windows 上同样位于用户主目录下。
+pub fn approve_api_key_in_claude_json(api_key: &str) -> Result<bool, AppError> {
+    let api_key = api_key.trim();
+    if api_key.is_empty() {
+        return Ok(false);
+    }
+    let path = user_config_path();
+    let mut root = read_json_value(&path)?;
+    if !approve_api_key(&mut root, api_key)? {
+        return Ok(false);
+    }
+    write_json_value(&path, &root)?;
+    Ok(true)
+}
+
+fn approve_api_key(root: &mut Value, api_key: &str) -> Result<bool, AppError> {
+    let chars: Vec<char> = api_key.chars().collect();
+    let suffix: String = chars[chars.len().saturating_sub(APPROVED_KEY_SUFFIX_LEN)..]
+        .iter()
+        .collect();
+
+    let obj = root
+        .as_object_mut()
+        .ok_or_else(|| AppError::Config("~/.claude.json 根必须是对象".into()))?;
+    let responses = obj
+        .entry("customApiKeyResponses")
+        .or_insert_with(|| serde_json::json!({}));
+    if !responses.is_object() {
+        *responses = serde_json::json!({});
+    }
+    let responses = responses.as_object_mut().expect("checked above");
+
+    let is_suffix = |entry: &Value| entry.as_str() == Some(suffix.as_str());
+
+    let mut changed = false;
+    if let Some(Value::Array(rejected)) = responses.get_mut("rejected") {
+        let before = rejected.len();
+        rejected.retain(|entry| !is_suffix(entry));
+        changed = rejected.len() != before;
+    }
+    let approved = responses
+        .entry("approved")
+        .or_insert_with(|| Value::Array(Vec::new()));
+    if !approved.is_array() {
+        *approved = Value::Array(Vec::new());
+    }
+    let approved = approved.as_array_mut().expect("checked above");
+    if !approved.iter().any(is_suffix) {
+        approved.push(Value::String(suffix.clone()));
+        changed = true;
+    }
+    Ok(changed)
+}
+
 pub fn upsert_mcp_server(id: &str, spec: Value) -> Result<bool, AppError> {
     if id.trim().is_empty() {
         return Err(AppError::InvalidInput("MCP 服务器 ID 不能为空".into()));
@@ -352,3 +412,27 @@ pub fn set_mcp_servers_map(
     write_json_value(&path, &root)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn approving_api_key_records_suffix_once_and_clears_rejection() {
+        let key = "sk-ant-REDACTED";
+        let suffix = "0123456789abcdefghijKLMNOPQRST"[10..].to_string();
+        let mut root = json!({
+            "numStartups": 3,
+            "customApiKeyResponses": { "approved": [], "rejected": [suffix] }
+        });
+
+        assert!(approve_api_key(&mut root, key).unwrap());
+        assert_eq!(root["customApiKeyResponses"]["approved"], json!([suffix]));
+        assert_eq!(root["customApiKeyResponses"]["rejected"], json!([]));
+        assert_eq!(root["numStartups"], 3);
+
+        assert!(!approve_api_key(&mut root, key).unwrap());
+        assert!(approve_api_key(&mut json!({}), "short").unwrap());
+    }
+}
@@ -0,0 +1,185 @@
+//! 子命令参数解析
+//!
+//! 全局选项（`--config-dir`、`--db-path` 等）在分发前取出，其余参数由各子命令用 [`ParsedArgs`]
+//! 按自己的选项表解析；`--app` 经应用注册表解析（见 [`crate::app_registry`]）。
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::app_config::AppType;
+use crate::app_registry::AppDefinition;
+use crate::error::AppError;
+use crate::services::provider::set_auto_adopt;
+use crate::services::RegisteredAppService;
+
+/// 子命令失败的原因
+#[derive(Debug)]
+pub(crate) enum CliError {
+    /// 用法错误（打印用法说明，退出码 2）
+    Usage(String),
+    /// 参数值无效（退出码 2）
+    Argument(AppError),
+    /// 执行失败（退出码见 [`crate::cli_error::exit_code`]）
+    Failed(AppError),
+}
+
+impl From<AppError> for CliError {
+    fn from(err: AppError) -> Self {
+        CliError::Failed(err)
+    }
+}
+
+/// 取出全局的 `--config-dir <dir>`、`--db-path <file>`、`--auto-adopt` 与 `--force-schema`
+/// （可出现在任意位置），设置进程内的对应选项后返回其余参数（`--` 之后的参数原样保留）；不带子命令时同样作用于 GUI
+pub(super) fn apply_global_flags(args: &[String]) -> Result<Vec<String>, AppError> {
+    let mut rest = Vec::with_capacity(args.len());
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--" {
+            rest.push(arg.clone());
+            rest.extend(iter.cloned());
+            break;
+        }
+        if arg == "--auto-adopt" {
+            set_auto_adopt(true);
+            continue;
+        }
+        if arg == "--force-schema" {
+            crate::database::set_force_schema(true);
+            continue;
+        }
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) => (flag, Some(value.to_string())),
+            None => (arg.as_str(), None),
+        };
+        if flag != "--config-dir" && flag != "--db-path" {
+            rest.push(arg.clone());
+            continue;
+        }
+        let value = match inline {
+            Some(value) => value,
+            None => iter
+                .next()
+                .cloned()
+                .ok_or_else(|| AppError::InvalidInput(format!("{flag} 需要一个参数")))?,
+        };
+        if value.trim().is_empty() {
+            return Err(AppError::InvalidInput(format!("{flag} 不能为空")));
+        }
+        let path = PathBuf::from(value);
+        if flag == "--config-dir" {
+            crate::config::set_config_dir_override(Some(path));
+        } else {
+            crate::config::set_db_path_override(Some(path));
+        }
+    }
+    Ok(rest)
+}
+
+/// 取出子命令的 `--db <path>` / `--db=<path>`（`--` 之后的参数原样保留）
+pub(super) fn extract_db_flag(args: &[String]) -> Result<(Option<PathBuf>, Vec<String>), AppError> {
+    let mut db = None;
+    let mut rest = Vec::with_capacity(args.len());
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--" {
+            rest.push(arg.clone());
+            rest.extend(iter.cloned());
+            break;
+        }
+        let path = if let Some(path) = arg.strip_prefix("--db=") {
+            path.to_string()
+        } else if arg == "--db" {
+            iter.next()
+                .cloned()
+                .ok_or_else(|| AppError::InvalidInput("--db 需要一个参数".to_string()))?
+        } else {
+            rest.push(arg.clone());
+            continue;
+        };
+        if path.trim().is_empty() {
+            return Err(AppError::InvalidInput("--db 不能为空".to_string()));
+        }
+        db = Some(PathBuf::from(path));
+    }
+    Ok((db, rest))
+}
+
+/// 解析后的子命令参数
+pub(super) struct ParsedArgs {
+    pub(super) positional: Vec<String>,
+    options: HashMap<String, Option<String>>,
+}
+
+impl ParsedArgs {
+    /// 拆分位置参数与选项：`value_flags` 必须带值，`switch_flags` 不带值；
+    /// 其他以 `--` 开头的参数视为未知选项
+    pub(super) fn parse(
+        args: &[String],
+        value_flags: &[&str],
+        switch_flags: &[&str],
+        usage: &str,
+    ) -> Result<Self, CliError> {
+        let mut positional = Vec::new();
+        let mut options = HashMap::new();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let flag = arg.as_str();
+            if value_flags.contains(&flag) {
+                let value = iter
+                    .next()
+                    .ok_or_else(|| CliError::Usage(usage.to_string()))?;
+                options.insert(arg.clone(), Some(value.clone()));
+            } else if switch_flags.contains(&flag) {
+                options.insert(arg.clone(), None);
+            } else if flag.starts_with("--") {
+                return Err(CliError::Usage(format!("未知参数: {flag}\n{usage}")));
+            } else {
+                positional.push(arg.clone());
+            }
+        }
+        Ok(Self {
+            positional,
+            options,
+        })
+    }
+
+    pub(super) fn value(&self, flag: &str) -> Option<&str> {
+        self.options.get(flag).and_then(|v| v.as_deref())
+    }
+
+    pub(super) fn has(&self, flag: &str) -> bool {
+        self.options.contains_key(flag)
+    }
+
+    /// `--app`，默认 claude
+    pub(super) fn app(&self) -> &str {
+        self.value("--app").unwrap_or("claude")
+    }
+
+    /// `--app` 对应的注册表应用定义
+    pub(super) fn app_definition(&self) -> Result<AppDefinition, CliError> {
+        RegisteredAppService::definition(self.app()).map_err(CliError::Argument)
+    }
+
+    /// `--app` 对应的内置应用；注册表中的其他应用不支持当前子命令
+    pub(super) fn app_type(&self) -> Result<AppType, CliError> {
+        let app = self.app_definition()?;
+        app.app_type().ok_or_else(|| unsupported_app(&app))
+    }
+}
+
+/// 注册表中的其他应用（非内置应用）不支持的子命令或选项
+pub(super) fn unsupported_app(app: &AppDefinition) -> CliError {
+    CliError::Argument(AppError::localized(
+        "unsupported_app",
+        format!(
+            "{} 不支持此命令或选项（仅支持 claude、codex、gemini）",
+            app.id
+        ),
+        format!(
+            "{} is not supported by this command or option (claude, codex and gemini only)",
+            app.id
+        ),
+    ))
+}
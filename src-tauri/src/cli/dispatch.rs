@@ -0,0 +1,201 @@
+//! 子命令分发的公共部分
+//!
+//! 输出上下文、只读模式与 `--db` 的判定、主目录检查、打开数据库与错误报告，供各子命令共用。
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use super::args::CliError;
+use super::output::{CommandOutput, OutputFormat};
+use crate::app_config::AppType;
+use crate::app_registry::AppRegistry;
+use crate::cli_error;
+use crate::database::{ChangeSource, Database};
+use crate::error::AppError;
+use crate::services::provider::TableStyle;
+use crate::services::ProviderService;
+use crate::store::AppState;
+
+/// 子命令的输出上下文
+pub(crate) struct Output {
+    pub(super) format: OutputFormat,
+}
+
+impl Output {
+    /// 按选定格式打印一个结果
+    pub(crate) fn emit(&self, output: &CommandOutput) {
+        if let Some(text) = self
+            .format
+            .renderer(TableStyle::for_stdout())
+            .render(output)
+        {
+            println!("{text}");
+        }
+    }
+}
+
+pub(super) type Handler = fn(&[String], &Output) -> Result<CommandOutput, CliError>;
+
+/// 可以用 `--db <path>` 查看其他数据库文件的子命令
+pub(super) const INSPECT_COMMANDS: &[&str] = &[
+    "list",
+    "provider list",
+    "show",
+    "provider show",
+    "provider export",
+    "provider search",
+    "provider validate",
+];
+
+/// 本次执行的 `--db <path>`：设置时 [`open_state`] 以只读方式打开该文件（见
+/// [`Database::open_read_only`]），不使用也不备份当前数据库
+pub(super) static INSPECT_DB: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// 运行前检查主目录（数据库与各应用配置都位于其下）
+pub(super) fn guarded(command: &str, run: impl FnOnce() -> i32) -> i32 {
+    if crate::config::home_dir().is_none() && crate::config::get_home_override().is_none() {
+        report_error(
+            command,
+            &AppError::localized(
+                "home.not_found",
+                "无法获取用户主目录",
+                "Unable to determine the home directory",
+            ),
+        );
+        return cli_error::FAILURE;
+    }
+    run()
+}
+
+/// 数据库为空时先进入首次运行引导的子命令
+pub(super) const ONBOARDING_COMMANDS: &[&str] = &["list", "provider list", "switch", "tui"];
+
+/// 只读模式下拒绝的子命令
+const MUTATING_COMMANDS: &[&str] = &[
+    "switch",
+    "init",
+    "provider set-model",
+    "provider edit",
+    "provider rotate-key",
+    "provider restore",
+    "provider dedupe",
+    "provider pin",
+    "provider unpin",
+    "provider alias",
+    "provider enrich",
+    "provider clone",
+    "provider delete",
+    "provider tag",
+    "provider move",
+    "provider reorder",
+    "provider import",
+    "tui",
+];
+
+/// 带动作参数的子命令中会修改配置的动作（`key add`、`backup restore` 等）
+const MUTATING_ACTIONS: &[&str] = &[
+    "add", "remove", "edit", "assign", "use", "strategy", "setup", "push", "pull", "prune",
+    "restore", "start", "revoke",
+];
+
+/// 支持注册表应用（OpenCode、Qwen Code 等非内置应用）的子命令
+const REGISTRY_COMMANDS: &[&str] = &[
+    "list",
+    "provider list",
+    "show",
+    "provider show",
+    "current",
+    "switch",
+    "provider delete",
+];
+
+/// `--app` 指向非内置的注册表应用、而子命令只支持内置应用时，返回点名该子命令的错误
+///
+/// 在执行子命令之前检查，避免各子命令各自报出含糊的"不支持此命令或选项"。
+pub(super) fn unsupported_registry_app(name: &str, args: &[String]) -> Option<AppError> {
+    if REGISTRY_COMMANDS.contains(&name) {
+        return None;
+    }
+    let app = args
+        .iter()
+        .position(|arg| arg == "--app")
+        .and_then(|index| args.get(index + 1))?;
+    let definition = AppRegistry::load().get(app).cloned()?;
+    if definition.native {
+        return None;
+    }
+    Some(AppError::localized(
+        "cli.registry_app_unsupported",
+        format!(
+            "`cc-switch {name}` 不支持 {}；该应用可用的命令: {}",
+            definition.id,
+            REGISTRY_COMMANDS.join(", ")
+        ),
+        format!(
+            "`cc-switch {name}` does not support {}; commands available for it: {}",
+            definition.id,
+            REGISTRY_COMMANDS.join(", ")
+        ),
+    ))
+}
+
+/// 子命令是否会修改配置（只读模式下拒绝执行）
+pub(super) fn is_mutating(name: &str, args: &[String]) -> bool {
+    match name {
+        "endpoint" | "key" | "keys" | "hook" | "vendor" | "sync" | "backup" | "app" | "proxy" => {
+            args.iter()
+                .any(|arg| MUTATING_ACTIONS.contains(&arg.as_str()))
+        }
+        "bundle" => args.first().is_some_and(|action| action == "import"),
+        "failover" => args.first().is_some_and(|action| action == "run"),
+        "provider env" => args
+            .first()
+            .is_some_and(|action| action == "set" || action == "unset"),
+        // 写入 ~/.claude/settings.json；其他目标只在 `--dir` 中生成文件
+        "integrate" => args
+            .first()
+            .is_some_and(|target| target == "claude-statusline"),
+        _ => MUTATING_COMMANDS.contains(&name),
+    }
+}
+
+/// 把别名解析为供应商 ID；既不是 ID 也不是别名时原样返回，由后续查找报告不存在
+pub(super) fn resolve_id(
+    state: &AppState,
+    app_type: &AppType,
+    id: &str,
+) -> Result<String, CliError> {
+    Ok(ProviderService::resolve_id(state, app_type, id)?)
+}
+
+pub(super) fn open_state() -> Result<AppState, CliError> {
+    let inspect_db = INSPECT_DB.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    if let Some(path) = inspect_db {
+        return Ok(AppState::new(Arc::new(Database::open_read_only(&path)?)));
+    }
+    let db = Database::init()?;
+    db.set_change_source(ChangeSource::Cli);
+    if let Err(e) = db.auto_backup() {
+        log::warn!("自动备份数据库失败: {e}");
+    }
+    Ok(AppState::new(Arc::new(db)))
+}
+
+pub(super) fn runtime() -> Result<tokio::runtime::Runtime, CliError> {
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| CliError::Failed(AppError::Message(format!("创建运行时失败: {e}"))))
+}
+
+/// 打印错误及其恢复建议
+pub(super) fn report_error(command: &str, err: &AppError) {
+    eprintln!("cc-switch {command}: {err}");
+    print_hint(err);
+}
+
+fn print_hint(err: &AppError) {
+    if let Some(hint) = err.hint() {
+        eprintln!("  提示: {hint}");
+    }
+}
@@ -0,0 +1,4019 @@
+//! 无界面命令行入口
+//!
+//! `cc-switch <子命令>` 不启动 GUI，执行完毕后直接退出；没有参数或第一个参数不是
+//! 已知子命令（例如深链接 URL）时返回 None，照常启动 GUI。
+//!
+//! 子命令只返回 [`CommandOutput`]，输出格式由全局选项决定（见 [`output`]）：
+//! `--output human|json|ndjson|table|quiet`（`-o`），`--json` 与 `--quiet`（`-q`）为简写。
+//! 退出码遵循 [`crate::cli_error`] 中的约定。
+//!
+//! 切换、导入、备份与恢复在 `~/.cc-switch/cc-switch.lock` 上互斥（见 [`crate::operation_lock`]），
+//! 并发执行时后来者最多等待 10 秒，超时以退出码 4 失败。
+//!
+//! 只读模式（设置 readOnly 或 `CC_SWITCH_READ_ONLY=1`，见 [`crate::settings::is_read_only`]）下
+//! 查看类子命令照常执行，切换、添加、删除等修改配置的子命令以退出码 8 拒绝；
+//! `CC_SWITCH_ADMIN=1` 覆盖只读模式。
+//!
+//! 全局选项 `--config-dir <dir>` 与 `--db-path <file>` 可出现在任意位置，分别替换应用配置目录
+//! （默认 `~/.cc-switch`，也可用环境变量 `CC_SWITCH_HOME` 指定）与数据库文件路径，
+//! 便于便携安装、维护多套配置或隔离测试。`--auto-adopt` 在 live 配置使用了未保存的凭据时
+//! 直接把它保存为新的当前供应商，不再询问（GUI 启动时同样生效）。
+//!
+//! 查看类子命令（`list`、`show`、`provider export|search|validate`）接受 `--db <path>`，以只读方式
+//! 查看另一个数据库文件（备份或他人导出的 .db），不修改该文件，也不使用当前数据库。
+//!
+//! `--app` 可以是应用注册表中的任意应用（见 [`crate::app_registry`]）。内置应用之外的应用
+//! （如 OpenCode、Qwen Code）支持 `list`、`show`、`current`、`switch <id>` 与
+//! `provider delete <id>...`，其他子命令与只对内置应用有意义的选项以退出码 2 拒绝。
+//!
+//! 数据库由更新的版本写入且声明与当前构建兼容时，以兼容模式只读打开（见
+//! [`crate::database::schema_compat`]）：查看类子命令与 `switch` 照常执行，其他修改以退出码 8
+//! 失败；不兼容时退出码为 6，全局选项 `--force-schema` 强制以兼容模式打开。
+//!
+//! - `init [--app <app>] [--yes]`：检测 Claude Code / Codex / Gemini CLI 的现有配置，确认后导入为
+//!   default 供应商并设为当前（见 [`crate::services::onboarding`]）；数据库为空时 `list` / `switch` /
+//!   `tui` 会先在终端中进入同样的引导
+//! - `rpc [--db <path>]`：JSON-RPC 模式，见 [`crate::rpc`]
+//! - `capabilities`：当前构建可用的子系统、子命令与 RPC 方法
+//! - `list [--app <app>] [--columns <cols>] [--style bordered|plain]`（也可写作 `provider list`）：
+//!   打印供应商列表；stdout 不是终端时默认输出无边框、每行一个供应商的纯文本表格；
+//!   `--group-by vendor` 按服务商账号分组显示；`--mine` 只列出当前系统用户新建的供应商
+//!   （新建供应商时记录系统用户名为所有者，JSON 输出中为 `owner`）；`--tag <tag>` 只列出带该标签的
+//!   供应商
+//! - `provider set-model <id> <model> [--app <app>] [--small-fast <model>]`：设置供应商的默认模型，
+//!   model 为空字符串时清除；若为当前供应商会立即写入 live 配置
+//! - `provider export [--format ccr|opencode|env] [id... | --ids <id,id...> | --select] [--app <app>]
+//!   [--out <file>]`：把供应商（默认全部）导出为 cc-switch 导入文件（export bundle），或转换为
+//!   claude-code-router / OpenCode 配置或 `.env` 片段，包含 API Key；`--select` 在终端中多选
+//! - `provider delete [<id>...] [--app <app>] [--yes]` / `provider tag <tag> [<id>...] [--app <app>]`：
+//!   在一个事务中删除多个供应商（包含当前供应商时全部不删除），或为多个供应商添加同一标签；
+//!   省略 id 时在终端中多选（如 `1,3,5-7`），删除前确认
+//! - `provider import <file> [--app <app>]`：导入 export bundle，先按 JSON Schema 校验并指出出错
+//!   字段的路径，已存在的供应商 ID 跳过。旧格式的文件先升级（没有版本的 `{app, providers}`、
+//!   `{id: provider}` 映射），后者没有记录应用，需要 `--app`；版本更新的文件拒绝导入
+//! - `schema provider|export-bundle`：打印由核心类型生成的 JSON Schema，供其他工具生成导入文件
+//! - `bundle export [--include <sections>] [--exclude <sections>] [--out <file>]`：导出配置包，
+//!   分区（逗号分隔）为 providers、settings、tags、mcp，默认全部；本机状态（代理接管、同步进度）
+//!   不导出
+//! - `bundle manifest <file>`：列出配置包中各分区包含的内容，供导入前确认
+//! - `bundle import <file> [--include <sections>] [--exclude <sections>]`：导入配置包中选中的分区；
+//!   已存在的供应商与 MCP 服务器跳过，设置覆盖同名项，标签只加到已存在的供应商上
+//! - `provider history <id> [--app <app>]`：供应商的变更记录（修订号、时间、来源与字段差异，
+//!   密钥已遮蔽）
+//! - `provider diff <id1> <id2>` / `provider diff <id> --live [--app <app>] [--show-secrets]`：
+//!   逐字段比较两个供应商的配置，或切换到该供应商会对 live 配置做出的修改；Codex 的
+//!   config.toml 按 TOML 键比较，密钥默认遮蔽
+//! - `provider dedupe [--app <app>] [--dry-run]`：按配置指纹找出设置相同的供应商并合并，保留当前
+//!   供应商（否则为列表中的第一个），备注、标签与自定义端点取并集；`--dry-run` 只列出分组
+//! - `provider restore <id> --to <revision|time> [--app <app>]`：把供应商的 settings_config 恢复到
+//!   某个修订或时间点（Unix 时间戳、RFC 3339 或本地 `YYYY-MM-DD HH:MM`），恢复本身记为新的修订
+//! - `provider pin|unpin <id> [--app <app>]`：置顶或取消置顶供应商；置顶的供应商在交互式选择
+//!   （不带 id 的 `switch`、`tui`）中排在最前，并按列表顺序占用快捷切换槽位，
+//!   `cc-switch 1`..`cc-switch 9 [--app <app>]` 直接切换到对应的置顶供应商
+//! - `provider alias <id> <alias>|--clear [--app <app>]`：设置或清除供应商的短别名（如 `work`、
+//!   `cheap`），同一应用内唯一；凡是接受供应商 ID 的地方都可以改用别名
+//! - `provider enrich <id> [--app <app>] [--dry-run]`：按 Base URL 匹配已知服务商，补全为空的网站、
+//!   分类、图标、备注与用量查询方式；`--dry-run` 只列出将要补全的字段
+//! - `provider search <query> [--app <app>]`：按名称、备注、分类与端点全文搜索供应商
+//! - `provider clone <id> [--name <name>] [--api-key <key>] [--app <app>]`：以新 ID 复制供应商，
+//!   可同时改名并换用另一个 API Key
+//! - `provider move <id> --to <position>|--up|--down [--app <app>]`：调整供应商在列表中的位置
+//!   （从 1 开始）；`provider reorder [--app <app>]` 在终端中按输入的序号重排整个列表
+//! - `provider snippet <id> --lang python|node [--app <app>]`：打印用该供应商的地址与 Key 初始化
+//!   官方 SDK 客户端的代码片段
+//! - `provider validate [--app <app> | --all]`：按应用的规则校验已保存的供应商（必填字段、URL 格式、
+//!   Key 变量冲突等），`--all` 校验所有应用；发现错误时退出码为 1
+//! - `current [--app <app>|all] [--porcelain]`：当前供应商的名称、Base URL、遮蔽后的 Key，以及
+//!   live 配置是否与之一致；`--porcelain` 输出单行 `app:name`（live 配置不一致时名称后加 `*`），
+//!   便于嵌入 shell 提示符与状态栏
+//! - `prompt-segment [--format powerline|starship|plain] [--app <app>]`：供 starship / p10k 等提示符
+//!   嵌入的彩色片段（如 `⚡ deepseek-r1 via openrouter`）；结果缓存在 `~/.cc-switch/cache`，
+//!   数据库与 settings.json 未修改时不会打开数据库；`--model <name>` 替换显示的模型
+//! - `integrate claude-statusline`：为 Claude Code 安装 statusLine 脚本（显示当前供应商与模型），
+//!   切换供应商时 settings.json 中的 statusLine 会被保留
+//! - `integrations generate vscode|nvim|raycast [--dir <dir>]`：在目录（默认当前目录）中生成调用
+//!   RPC 模式的 VS Code 任务与 Neovim 模块，或列出、切换供应商的 Raycast 脚本命令
+//!   （`integrate vscode|nvim|raycast` 为旧写法）
+//! - `env <id> [--app <app>] [--shell bash|fish|powershell]`：打印设置该供应商环境变量的语句
+//!   （`ANTHROPIC_BASE_URL` 等，Codex / Gemini 为对应变量），配合 `eval "$(cc-switch env <id>)"`
+//!   只在当前 shell 会话中使用该供应商，不修改 live 配置；默认按 `$SHELL` 选择语法
+//! - `run [--app <app>] [--provider <id>] -- <command> [args...]`：把供应商（默认为当前供应商）的
+//!   环境变量只注入到启动的子进程中运行命令，不修改 live 配置；供应商配置了 Key 池时每次运行
+//!   从池中取下一个 Key；转发 SIGTERM / SIGHUP，退出码与子进程一致
+//! - `stats [--app <app>] [--by-vendor]`：各供应商经代理转发的累计请求数与 token 数；
+//!   `--by-vendor` 按服务商账号汇总所有应用
+//! - `usage <id> [--app <app>]`：查询供应商余额/用量（`usage --all` 汇总所有配置了用量查询的
+//!   供应商，每个套餐一行）；金额按 numberLocale 格式化，并可按
+//!   displayCurrency 在 USD 与 CNY 之间换算（汇率见 usdToCnyRate）。shell / node / python
+//!   用量脚本只在本机开启 allowExternalUsageScripts（或 `CC_SWITCH_ALLOW_EXTERNAL_SCRIPTS=1`）
+//!   后运行；导入或同步得到的这类脚本会被停用
+//! - `show <id> [--app <app>]`（也可写作 `provider show`）：供应商详情及累计用量
+//! - `provider env list|set|unset <id> [KEY=VALUE | KEY ...] [--app <app>]`：管理供应商的额外
+//!   环境变量（写入 settings_config.env，切换时应用到各应用的配置）
+//! - `provider edit <id> [--http-proxy <url>] [--https-proxy <url>] [--no-proxy <hosts>] [--app <app>]`：
+//!   修改供应商的代理设置（空字符串清除），切换时导出为 HTTP_PROXY / HTTPS_PROXY / NO_PROXY；
+//!   `--key-expires <YYYY-MM-DD>` / `--rotate-every <days>` 设置密钥到期日与轮换周期，`list` 的
+//!   Key 列会标出 14 天内到期的密钥
+//! - `provider rotate-key <id> --api-key <key> [--expires <YYYY-MM-DD>] [--app <app>]`：更换 API Key，
+//!   旧密钥的指纹记入供应商元数据与变更记录；若为当前供应商会立即写入 live 配置
+//! - `proxy start [--port <port>] [--address <addr>]`：前台运行本地 API 代理，按当前供应商转发
+//!   Anthropic / OpenAI / Gemini 格式的请求；`proxy status` 检查代理端口是否在监听
+//! - `bench [--app <app>] [--all]`：向每个供应商发送一个很小的流式请求，按首字节时间排序显示
+//!   TTFB 与输出速度；`--all` 测试所有应用。结果会保存，`list --columns ...,latency` 显示近期延迟
+//! - `switch [<id>] [--app <app>]` / `switch --fastest [--app <app>]`：切换供应商，在终端中省略 id 时
+//!   列出供应商（置顶的在最前）供选择；`--tag <tag>` 切换到带该标签的第一个供应商（按列表顺序），
+//!   `--category <cat>` 切换到该分类的默认供应商；`--fastest` 选择最近 24 小时基准测试中最快的
+//!   供应商；`--best-endpoint` 先测试该供应商的全部端点，把最快的写入 live 配置的 Base URL；
+//!   输出列出每个 live 配置文件的写入结果，多个文件一并写入，任一失败时已写入的文件恢复原内容
+//! - `switch <id> --temporary [--for <duration>] [-- <command> [args...]]`：限时切换，记录之前的
+//!   供应商，`--for 2h` 到期（由后台计时进程或运行中的应用负责）或包装的命令退出后自动切回；
+//!   带 `--for` 时可省略 `--temporary`；`switch --revert [--app <app>]` 立即切回所有已到期的限时切换
+//! - `endpoint test <id> [--app <app>]`：同时测试供应商的基础地址与自定义端点（TCP 建连与
+//!   TCP + TLS + HTTP HEAD 耗时），按速度排序显示并记录测速结果
+//! - `endpoint list|add|remove <id> [url] [--app <app>]`：管理供应商的自定义端点；
+//!   `endpoint use <id> [url | --reset]` 选择写入 live 配置的端点，省略 url 时在终端中交互选择
+//! - `key list|add|remove <id> [key] [--label <text>] [--weight <n>] [--app <app>]`：管理供应商的
+//!   Key 池（第一次添加时供应商原有的 Key 也会加入池中，Gemini 供应商默认由代理按请求轮换、
+//!   遇到 429 换用下一个 Key）；`key strategy <id> [round-robin|lru|weighted] [--rotate switch|proxy]`
+//!   设置轮换策略，以及在切换时还是由代理按请求轮换
+//! - `keys revoke <key|provider-id> [--archive]`：在所有应用的供应商（含 Key 池）中清除该密钥
+//!   （参数为供应商 ID 时为其当前使用的密钥），当前供应商立即写回 live 配置，并记入审计日志；
+//!   `--archive` 为受影响的供应商加上 archived 标签
+//! - `hook list|add|remove [pre|post] [--command <cmd> | --webhook <url> | <index>] [--provider <id>]
+//!   [--app <app>]`：管理切换前后执行的钩子（全局，或 `--provider` 指定的供应商）；命令通过
+//!   `CC_SWITCH_APP` / `CC_SWITCH_FROM` / `CC_SWITCH_TO` 等环境变量获得事件信息，webhook 收到
+//!   JSON；pre-switch 命令失败会取消切换
+//! - `vendor list|add|edit|remove|assign`：管理服务商账号（同一服务商的多个供应商归为一组，见
+//!   [`crate::services::vendor`]）；`vendor assign <provider-id> <vendor-id|--none> [--app <app>]`
+//!   设置供应商所属账号，`vendor remove <id> [--archive|--cascade]` 删除账号时默认只解除关联，
+//!   也可归档或一并删除其供应商
+//! - `backup list|prune|restore <backup-id|file.db>`：查看、按保留策略清理 `~/.cc-switch/backups`
+//!   中的数据库备份，或用某个备份 / 任意 .db 文件替换当前数据库（先做完整性与版本检查，
+//!   替换前会先备份）；启动时按 backup.intervalHours 自动备份
+//! - `app snapshot [--app <app>] [--label <text>]`：把应用的整个配置目录（如 `~/.codex`，不含会话
+//!   记录与缓存）打包到 `~/.cc-switch/snapshots`；`app snapshot list [--app <app>]` 列出快照，
+//!   `app snapshot restore <id>` 用快照替换配置目录（替换前会先生成快照），见
+//!   [`crate::services::snapshot`]
+//! - `db doctor [--check-only]`：检查数据库完整性，通过后执行 ANALYZE 与 VACUUM，并显示各表
+//!   行数、文件大小、Schema 版本与日志模式；发现损坏时退出码为 1
+//! - `db migrate [--dry-run]`：执行尚未执行的 Schema 迁移；`--dry-run` 只列出将要执行的迁移，
+//!   不修改数据库。已执行的迁移在发布后被改动过（校验和不一致）时迁移失败，`--dry-run` 列出
+//!   这些迁移并以退出码 1 结束
+//! - `sync setup --backend webdav|s3|git ...`：保存云同步配置（设备级，写入 settings.json）；
+//!   `sync push [--force]` / `sync pull` / `sync status`：上传、下载加密的数据库快照或比较两端状态，
+//!   远端在上次同步后被其他设备修改时按供应商的修改时间合并（见 [`crate::services::sync`]）；
+//!   git 后端把每个供应商写成仓库中的一个 JSON 文件，有变化时提交并推送
+//! - `lint [--app <app>|all]`：检查应用的 live 配置文件（settings.json、auth.json / config.toml、
+//!   .env）：无法解析的文件、未知字段、格式错误的 Base URL、为空或互相冲突的 API Key 变量；
+//!   发现错误时退出码为 1。同样的检查在每次写入 live 配置前执行，错误会阻止写入
+//! - `limits status [--app <app>]`：设置了消费限额的供应商及今日 / 本月估算花费；有供应商超限时
+//!   退出码为 1
+//! - `tui`：终端界面，按应用浏览供应商及其用量与延迟，可直接切换、编辑备注、归档与测速
+//!   （见 [`tui`]）
+//! - `debug replay <id> --request <file> [--app <app>]`：用供应商的地址与凭据重放保存的请求
+//!   （完整描述或只有请求体，见 [`crate::services::debug`]），打印原始状态码、响应头与响应体；
+//!   `debug headers <id> [--app <app>]` 发送最小请求，列出识别网关 / CDN 的响应头、限流响应头与延迟
+//! - `failover run [--app <app>]`：检查当前供应商，不可用时按故障转移组的优先级切换到下一个
+//!   健康的供应商（见 [`crate::services::failover`]），列出每个被检查供应商的结果
+//! - `find --key <key> | --url <url>`：在所有应用的供应商中查找使用该 Key（区分大小写）或端点 / 主机
+//!   （不区分大小写）的供应商，列出所在字段及是否为当前供应商
+//! - `history [--app <app>] [--cwd [path]] [--limit <n>]`：切换历史（按时间倒序），
+//!   `--cwd` 只显示在该目录及其子目录下发起的切换，省略路径时为当前目录
+
+mod args;
+mod dispatch;
+mod output;
+mod tui;
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use indexmap::IndexMap;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::app_config::AppType;
+use crate::app_registry::AppDefinition;
+use crate::cli_error;
+use crate::config::FileWriteStatus;
+use crate::database::{Database, JsonChange, MigrationMismatch, ProviderKey};
+use crate::error::AppError;
+use crate::operation_lock::OperationLock;
+use crate::provider::{
+    is_secret_env_name, mask_secret, KeyRotation, KeyStrategy, Provider, UsageData,
+};
+use crate::rpc::run_rpc;
+use crate::services::bundle::ConfigBundle;
+use crate::services::debug::{DebugService, ReplayRequest};
+use crate::services::failover::FailoverService;
+use crate::services::integrations::{IntegrationService, IntegrationTarget};
+use crate::services::provider::{
+    app_version_warning, auto_adopt, parse_columns, parse_env_assignment, CurrentStatus,
+    DiffTarget, ExportFormat, HookStage, KeyPolicy, LookupTarget, ProviderLabel, ProviderMove,
+    ProviderProxy, ProviderReference, ProviderService, RestoreTarget, SchemaKind, SegmentFormat,
+    Severity, ShellKind, SnippetLang, TableStyle, DEFAULT_COLUMNS,
+};
+use crate::services::sync::SyncOutcome;
+use crate::services::{
+    BenchService, BundleSection, BundleService, DetectedConfig, OnboardingService,
+    RegisteredAppService, SnapshotService, SyncService, TemporarySwitchService, VendorRemoval,
+    VendorService,
+};
+use crate::settings::{SwitchHook, SwitchHooks, SyncSettings};
+use crate::store::AppState;
+use crate::usage_format::{Currency, UsageFormatter};
+
+use args::{apply_global_flags, extract_db_flag, unsupported_app, CliError, ParsedArgs};
+use dispatch::{
+    guarded, is_mutating, open_state, report_error, resolve_id, runtime, unsupported_registry_app,
+    Handler, Output, INSPECT_COMMANDS, INSPECT_DB, ONBOARDING_COMMANDS,
+};
+use output::{CommandOutput, OutputFormat};
+
+/// 支持的子命令（见能力报告）
+pub(crate) const SUBCOMMANDS: &[&str] = &[
+    "rpc",
+    "capabilities",
+    "init",
+    "list",
+    "provider list",
+    "provider show",
+    "provider set-model",
+    "provider env",
+    "provider edit",
+    "provider rotate-key",
+    "provider export",
+    "provider import",
+    "provider history",
+    "provider restore",
+    "provider diff",
+    "provider dedupe",
+    "provider pin",
+    "provider unpin",
+    "provider alias",
+    "provider enrich",
+    "provider search",
+    "provider clone",
+    "provider delete",
+    "provider tag",
+    "provider move",
+    "provider reorder",
+    "provider snippet",
+    "provider validate",
+    "show",
+    "current",
+    "prompt-segment",
+    "integrate",
+    "integrations generate",
+    "env",
+    "run",
+    "stats",
+    "usage",
+    "history",
+    "bench",
+    "switch",
+    "1..9",
+    "endpoint test",
+    "endpoint list",
+    "endpoint add",
+    "endpoint remove",
+    "endpoint use",
+    "key list",
+    "key add",
+    "key remove",
+    "key strategy",
+    "keys revoke",
+    "hook list",
+    "hook add",
+    "hook remove",
+    "vendor list",
+    "vendor add",
+    "vendor edit",
+    "vendor remove",
+    "vendor assign",
+    "limits status",
+    "lint",
+    "debug replay",
+    "debug headers",
+    "failover run",
+    "find",
+    "schema",
+    "bundle export",
+    "bundle manifest",
+    "bundle import",
+    "sync setup",
+    "sync push",
+    "sync pull",
+    "sync status",
+    "backup list",
+    "backup prune",
+    "backup restore",
+    "app snapshot",
+    "app snapshot list",
+    "app snapshot restore",
+    "db doctor",
+    "db migrate",
+    "proxy start",
+    "proxy status",
+    "tui",
+];
+
+/// 执行子命令并返回进程退出码；不是子命令时返回 None
+pub fn run_cli(args: Vec<String>) -> Option<i32> {
+    let args = match apply_global_flags(&args) {
+        Ok(args) => args,
+        Err(e) => {
+            report_error("cc-switch", &e);
+            return Some(cli_error::USAGE);
+        }
+    };
+    let (command, rest) = args.split_first()?;
+    if command == "rpc" {
+        return Some(guarded(command, || run_rpc(rest.to_vec())));
+    }
+
+    let (name, handler, rest): (&str, Handler, &[String]) = match command.as_str() {
+        "capabilities" => ("capabilities", capabilities, rest),
+        "list" => ("list", list, rest),
+        "show" => ("show", show, rest),
+        "current" => ("current", current, rest),
+        "prompt-segment" => ("prompt-segment", prompt_segment, rest),
+        "integrate" => ("integrate", integrate, rest),
+        "integrations" => ("integrations", integrations, rest),
+        "env" => ("env", shell_env, rest),
+        "run" => ("run", run_command, rest),
+        "stats" => ("stats", stats, rest),
+        "usage" => ("usage", usage, rest),
+        "history" => ("history", history, rest),
+        "limits" => ("limits", limits, rest),
+        "lint" => ("lint", lint, rest),
+        "debug" => ("debug", debug, rest),
+        "failover" => ("failover", failover, rest),
+        "find" => ("find", find, rest),
+        "schema" => ("schema", schema, rest),
+        "bundle" => ("bundle", bundle, rest),
+        "sync" => ("sync", sync, rest),
+        "backup" => ("backup", backup, rest),
+        "app" => ("app", app_snapshot, rest),
+        "db" => ("db", db, rest),
+        "init" => ("init", init, rest),
+        "bench" => ("bench", bench, rest),
+        "switch" => ("switch", switch, rest),
+        "endpoint" => ("endpoint", endpoint, rest),
+        "key" => ("key", key, rest),
+        "keys" => ("keys", keys, rest),
+        "hook" => ("hook", hook, rest),
+        "vendor" => ("vendor", vendor, rest),
+        "1" | "2" | "3" | "4" | "5" | "6" | "7" | "8" | "9" => ("switch", quick_switch, &args[..]),
+        "proxy" => ("proxy", proxy, rest),
+        "tui" => ("tui", tui, rest),
+        "provider" => {
+            let (name, handler): (&str, Handler) = match rest.first().map(String::as_str) {
+                Some("list") => ("provider list", list),
+                Some("show") => ("provider show", show),
+                Some("set-model") => ("provider set-model", set_model),
+                Some("env") => ("provider env", env),
+                Some("edit") => ("provider edit", edit),
+                Some("rotate-key") => ("provider rotate-key", rotate_key),
+                Some("export") => ("provider export", export),
+                Some("import") => ("provider import", import),
+                Some("history") => ("provider history", provider_history),
+                Some("restore") => ("provider restore", provider_restore),
+                Some("diff") => ("provider diff", provider_diff),
+                Some("dedupe") => ("provider dedupe", provider_dedupe),
+                Some("pin") => ("provider pin", provider_pin),
+                Some("unpin") => ("provider unpin", provider_unpin),
+                Some("alias") => ("provider alias", provider_alias),
+                Some("enrich") => ("provider enrich", provider_enrich),
+                Some("search") => ("provider search", provider_search),
+                Some("clone") => ("provider clone", provider_clone),
+                Some("delete") => ("provider delete", provider_delete),
+                Some("tag") => ("provider tag", provider_tag),
+                Some("move") => ("provider move", provider_move),
+                Some("reorder") => ("provider reorder", provider_reorder),
+                Some("snippet") => ("provider snippet", provider_snippet),
+                Some("validate") => ("provider validate", provider_validate),
+                _ => return None,
+            };
+            (name, handler, &rest[1..])
+        }
+        _ => return None,
+    };
+
+    Some(guarded(name, || {
+        let (format, args) = match OutputFormat::extract(rest) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                report_error(name, &e);
+                return cli_error::USAGE;
+            }
+        };
+        let output = Output { format };
+        let (inspect_db, args) = match extract_db_flag(&args) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                report_error(name, &e);
+                return cli_error::USAGE;
+            }
+        };
+        if inspect_db.is_some() && !INSPECT_COMMANDS.contains(&name) {
+            report_error(
+                name,
+                &AppError::localized(
+                    "cli.db_flag_unsupported",
+                    format!("--db 只能用于 {}", INSPECT_COMMANDS.join(", ")),
+                    format!("--db only applies to {}", INSPECT_COMMANDS.join(", ")),
+                ),
+            );
+            return cli_error::USAGE;
+        }
+        if let Some(e) = unsupported_registry_app(name, &args) {
+            report_error(name, &e);
+            return cli_error::USAGE;
+        }
+        let inspecting = inspect_db.is_some();
+        *INSPECT_DB.lock().unwrap_or_else(|e| e.into_inner()) = inspect_db;
+        let read_only = crate::settings::is_read_only();
+        if read_only && is_mutating(name, &args) {
+            report_error(name, &AppError::ReadOnly);
+            return cli_error::READ_ONLY;
+        }
+        if ONBOARDING_COMMANDS.contains(&name) && !read_only && !inspecting {
+            if format == OutputFormat::Human {
+                first_run_onboarding();
+            }
+            adopt_unmanaged_live(format == OutputFormat::Human);
+        }
+        match handler(&args, &output) {
+            Ok(result) => {
+                output.emit(&result);
+                result.code
+            }
+            Err(CliError::Usage(usage)) => {
+                eprintln!("{usage}");
+                cli_error::USAGE
+            }
+            Err(CliError::Argument(e)) => {
+                report_error(name, &e);
+                cli_error::USAGE
+            }
+            Err(CliError::Failed(e)) => {
+                report_error(name, &e);
+                cli_error::exit_code(&e)
+            }
+        }
+    }))
+}
+
+fn capabilities(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    if !args.is_empty() {
+        return Err(CliError::Usage(
+            "用法: cc-switch capabilities [--json]".to_string(),
+        ));
+    }
+    let report = crate::capabilities::capabilities();
+
+    let mut lines = vec![format!(
+        "cc-switch {} (schema v{})",
+        report.version, report.schema_version
+    )];
+    for capability in &report.capabilities {
+        let mark = if capability.available { "yes" } else { "no" };
+        lines.push(format!(
+            "  {:<8} {:<3}  {}",
+            capability.name,
+            mark,
+            capability.detail.unwrap_or_default()
+        ));
+    }
+    lines.push(format!("subcommands: {}", report.subcommands.join(", ")));
+    Ok(CommandOutput::new(&report).human(lines.join("\n")))
+}
+
+fn list(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch list [--app <app>] [--columns <cols>] [--style bordered|plain] [--group-by vendor] [--mine] [--tag <tag>]";
+    let args = ParsedArgs::parse(
+        args,
+        &["--app", "--columns", "--style", "--group-by", "--tag"],
+        &["--mine"],
+        USAGE,
+    )?;
+    if !args.positional.is_empty() {
+        return Err(CliError::Usage(USAGE.to_string()));
+    }
+    let columns = match args.value("--columns") {
+        Some(spec) => parse_columns(spec).map_err(CliError::Argument)?,
+        None => DEFAULT_COLUMNS.to_vec(),
+    };
+    let style = match args.value("--style") {
+        Some(style) => TableStyle::from_str(style).map_err(CliError::Argument)?,
+        None => TableStyle::for_stdout(),
+    };
+    let by_vendor = match args.value("--group-by") {
+        None => false,
+        Some("vendor") => true,
+        Some(other) => {
+            return Err(CliError::Argument(AppError::InvalidInput(format!(
+                "不支持的分组方式: {other}（可选: vendor）"
+            ))))
+        }
+    };
+    let app = args.app_definition()?;
+    if app.app_type().is_none() && (by_vendor || args.has("--columns")) {
+        return Err(unsupported_app(&app));
+    }
+    let state = open_state()?;
+
+    let owners = state.db.get_provider_owners(&app.id)?;
+    let mut providers = state.db.get_all_providers(&app.id)?;
+    if args.has("--mine") {
+        let me = crate::config::current_username();
+        providers.retain(|id, _| me.is_some() && owners.get(id) == me.as_ref());
+    }
+    if let Some(tag) = args.value("--tag") {
+        let tagged: Vec<String> = state
+            .db
+            .find_by_tag(&app.id, tag)?
+            .into_iter()
+            .map(|p| p.id)
+            .collect();
+        providers.retain(|id, _| tagged.contains(id));
+    }
+    let Some(app_type) = app.app_type() else {
+        return list_registered(&state, &app, providers, &owners);
+    };
+    let table = if by_vendor {
+        VendorService::render_grouped(&state, app_type.clone(), &providers, &columns, style)?
+    } else {
+        ProviderService::render_table_of(&state, &app_type, &providers, &columns, style)?
+    };
+    let current = ProviderService::current(&state, app_type.clone())?;
+    let vendors = state.db.get_provider_vendors(Some(app_type.as_str()))?;
+    let aliases = state.db.get_provider_aliases(app_type.as_str())?;
+    let today = chrono::Local::now().date_naive();
+    let providers: Vec<_> = providers
+        .into_values()
+        .map(|p| {
+            json!({
+                "id": p.id,
+                "name": p.name,
+                "category": p.category,
+                "isCurrent": p.id == current,
+                "alias": aliases.get(&p.id),
+                "owner": owners.get(&p.id),
+                "vendorId": vendors.get(&(app_type.as_str().to_string(), p.id.clone())),
+                "keyExpiry": p.key_expiry(today),
+            })
+        })
+        .collect();
+    Ok(CommandOutput::new(providers).human(table))
+}
+
+/// 注册表应用的供应商列表（没有端点、健康度等内置应用专有的列）
+fn list_registered(
+    state: &AppState,
+    app: &AppDefinition,
+    providers: IndexMap<String, Provider>,
+    owners: &HashMap<String, String>,
+) -> Result<CommandOutput, CliError> {
+    let current = RegisteredAppService::current(state, &app.id)?;
+    let aliases = state.db.get_provider_aliases(&app.id)?;
+    let rows = providers
+        .values()
+        .map(|p| {
+            vec![
+                if p.id == current { "*" } else { "" }.to_string(),
+                p.id.clone(),
+                p.name.clone(),
+                p.category.clone().unwrap_or_else(|| "-".to_string()),
+            ]
+        })
+        .collect();
+    let providers: Vec<_> = providers
+        .into_values()
+        .map(|p| {
+            json!({
+                "id": p.id,
+                "name": p.name,
+                "category": p.category,
+                "isCurrent": p.id == current,
+                "alias": aliases.get(&p.id),
+                "owner": owners.get(&p.id),
+            })
+        })
+        .collect();
+    Ok(CommandOutput::new(providers).table(vec!["CURRENT", "ID", "NAME", "CATEGORY"], rows))
+}
+
+fn set_model(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str =
+        "用法: cc-switch provider set-model <id> <model> [--app <app>] [--small-fast <model>]";
+    let args = ParsedArgs::parse(args, &["--app", "--small-fast"], &[], USAGE)?;
+    let [id, model] = args.positional.as_slice() else {
+        return Err(CliError::Usage(USAGE.to_string()));
+    };
+    let app_type = args.app_type()?;
+    let state = open_state()?;
+    let id = &resolve_id(&state, &app_type, id)?;
+
+    let provider =
+        ProviderService::set_model(&state, app_type, id, model, args.value("--small-fast"))?;
+    let meta = provider.meta.unwrap_or_default();
+    let human = format!(
+        "{} ({}): {}",
+        provider.name,
+        provider.id,
+        meta.default_model.as_deref().unwrap_or("-")
+    );
+    Ok(CommandOutput::new(json!({
+        "id": provider.id,
+        "name": provider.name,
+        "defaultModel": meta.default_model,
+        "smallFastModel": meta.small_fast_model,
+    }))
+    .human(human))
+}
+
+fn stats(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch stats [--app <app>] [--by-vendor]";
+    let args = ParsedArgs::parse(args, &["--app"], &["--by-vendor"], USAGE)?;
+    if !args.positional.is_empty() || (args.has("--by-vendor") && args.value("--app").is_some()) {
+        return Err(CliError::Usage(USAGE.to_string()));
+    }
+    let state = open_state()?;
+    let format = UsageFormatter::from_settings(&crate::settings::get_settings());
+
+    if args.has("--by-vendor") {
+        let usage = VendorService::usage(&state)?;
+        let rows = usage
+            .iter()
+            .map(|u| {
+                vec![
+                    u.vendor_id.clone().unwrap_or_else(|| "-".to_string()),
+                    u.name.clone(),
+                    u.providers.to_string(),
+                    format.count(u.requests as f64),
+                    format.count(u.tokens as f64),
+                ]
+            })
+            .collect();
+        return Ok(CommandOutput::new(&usage).table(
+            vec!["VENDOR", "NAME", "PROVIDERS", "REQUESTS", "TOKENS"],
+            rows,
+        ));
+    }
+
+    let counters = state.db.get_usage_counters(args.value("--app"))?;
+    let rows = counters
+        .iter()
+        .map(|c| {
+            vec![
+                c.app_type.clone(),
+                c.provider_id.clone(),
+                format.count(c.requests as f64),
+                format.count(c.tokens as f64),
+            ]
+        })
+        .collect();
+    Ok(CommandOutput::new(&counters).table(vec!["APP", "PROVIDER", "REQUESTS", "TOKENS"], rows))
+}
+
+fn show(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch show <id> [--app <app>]";
+    let args = ParsedArgs::parse(args, &["--app"], &[], USAGE)?;
+    let [id] = args.positional.as_slice() else {
+        return Err(CliError::Usage(USAGE.to_string()));
+    };
+    let app = &args.app_definition()?.id;
+    let state = open_state()?;
+    let id = &RegisteredAppService::resolve_id(&state, app, id)?;
+    let provider = RegisteredAppService::get(&state, app, id)?;
+    let counters = state.db.get_provider_usage_counters(app, id)?;
+
+    let meta = provider.meta.clone().unwrap_or_default();
+    let format = UsageFormatter::from_settings(&crate::settings::get_settings());
+    let budget = |limit: Option<&String>| {
+        limit
+            .and_then(|value| value.trim().parse::<f64>().ok())
+            .map(|value| format.money(value, Currency::Usd))
+            .unwrap_or_else(|| "-".to_string())
+    };
+    let or_dash = |value: Option<&String>| value.cloned().unwrap_or_else(|| "-".to_string());
+    let expiry = provider.key_expiry(chrono::Local::now().date_naive());
+    let human = [
+        format!("Name:      {}", provider.name),
+        format!("ID:        {}", provider.id),
+        format!("App:       {app}"),
+        format!("Category:  {}", or_dash(provider.category.as_ref())),
+        format!("Website:   {}", or_dash(provider.website_url.as_ref())),
+        format!("Model:     {}", or_dash(meta.default_model.as_ref())),
+        format!("Daily:     {}", budget(meta.limit_daily_usd.as_ref())),
+        format!("Monthly:   {}", budget(meta.limit_monthly_usd.as_ref())),
+        format!(
+            "Key:       {}",
+            expiry
+                .map(|e| format!("{}（{}）", e.due.format("%Y-%m-%d"), e.describe()))
+                .unwrap_or_else(|| "-".to_string())
+        ),
+        format!("Requests:  {}", format.count(counters.requests as f64)),
+        format!("Tokens:    {}", format.count(counters.tokens as f64)),
+    ]
+    .join("\n");
+
+    Ok(CommandOutput::new(json!({
+        "id": provider.id,
+        "name": provider.name,
+        "app": app,
+        "category": provider.category,
+        "websiteUrl": provider.website_url,
+        "defaultModel": meta.default_model,
+        "limitDailyUsd": meta.limit_daily_usd,
+        "limitMonthlyUsd": meta.limit_monthly_usd,
+        "keyExpiry": expiry,
+        "requests": counters.requests,
+        "tokens": counters.tokens,
+    }))
+    .human(human))
+}
+
+fn current(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch current [--app <app>|all] [--porcelain]";
+    let args = ParsedArgs::parse(args, &["--app"], &["--porcelain"], USAGE)?;
+    if !args.positional.is_empty() {
+        return Err(CliError::Usage(USAGE.to_string()));
+    }
+    let all = args.app() == "all";
+    if !all {
+        let app = args.app_definition()?;
+        if app.app_type().is_none() {
+            return current_registered(&open_state()?, &app, args.has("--porcelain"));
+        }
+    }
+    let apps = if all {
+        vec![AppType::Claude, AppType::Codex, AppType::Gemini]
+    } else {
+        vec![args.app_type()?]
+    };
+    let state = open_state()?;
+    let statuses = apps
+        .into_iter()
+        .map(|app_type| ProviderService::current_status(&state, app_type))
+        .collect::<Result<Vec<CurrentStatus>, _>>()?;
+
+    let human = if args.has("--porcelain") {
+        statuses
+            .iter()
+            .map(CurrentStatus::porcelain)
+            .collect::<Vec<_>>()
+            .join(" ")
+    } else {
+        statuses
+            .iter()
+            .map(|status| {
+                let Some(p) = &status.provider else {
+                    return format!("{}: 未设置当前供应商", status.app);
+                };
+                let live = if p.in_sync {
+                    "已同步".to_string()
+                } else {
+                    format!(
+                        "不一致（{} 处差异，见 cc-switch provider diff {} --live --app {}）",
+                        p.drift, p.id, status.app
+                    )
+                };
+                [
+                    format!("{}: {} ({})", status.app, p.name, p.id),
+                    format!("  Base URL: {}", p.base_url.as_deref().unwrap_or("-")),
+                    format!("  Key:      {}", p.api_key.as_deref().unwrap_or("-")),
+                    format!("  Live:     {live}"),
+                ]
+                .join("\n")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    let rows = statuses
+        .iter()
+        .map(|status| match &status.provider {
+            Some(p) => vec![
+                status.app.clone(),
+                p.name.clone(),
+                p.base_url.clone().unwrap_or_default(),
+                p.api_key.clone().unwrap_or_default(),
+                if p.in_sync { "yes" } else { "no" }.to_string(),
+            ],
+            None => vec![
+                status.app.clone(),
+                "-".to_string(),
+                String::new(),
+                String::new(),
+                String::new(),
+            ],
+        })
+        .collect();
+    let data = if all {
+        json!(statuses)
+    } else {
+        json!(statuses[0])
+    };
+    Ok(CommandOutput::new(data)
+        .human(human)
+        .table(vec!["APP", "NAME", "BASE URL", "KEY", "IN SYNC"], rows))
+}
+
+/// 注册表应用的当前供应商（不比较 live 配置）
+fn current_registered(
+    state: &AppState,
+    app: &AppDefinition,
+    porcelain: bool,
+) -> Result<CommandOutput, CliError> {
+    let current = RegisteredAppService::current(state, &app.id)?;
+    let provider = state.db.get_provider_by_id(&current, &app.id)?;
+    let human = match (&provider, porcelain) {
+        (Some(p), true) => format!("{}:{}", app.id, p.name),
+        (None, true) => format!("{}:-", app.id),
+        (Some(p), false) => format!("{}: {} ({})", app.id, p.name, p.id),
+        (None, false) => format!("{}: 未设置当前供应商", app.id),
+    };
+    Ok(CommandOutput::new(json!({
+        "app": app.id,
+        "provider": provider.map(|p| json!({ "id": p.id, "name": p.name })),
+    }))
+    .human(human))
+}
+
+fn prompt_segment(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str =
+        "用法: cc-switch prompt-segment [--format powerline|starship|plain] [--app <app>] [--model <name>]";
+    let args = ParsedArgs::parse(args, &["--app", "--format", "--model"], &[], USAGE)?;
+    if !args.positional.is_empty() {
+        return Err(CliError::Usage(USAGE.to_string()));
+    }
+    let format = SegmentFormat::from_str(args.value("--format").unwrap_or("plain"))
+        .map_err(CliError::Argument)?;
+    let app_type = args.app_type()?;
+    let mut segment = match ProviderService::cached_prompt_segment(&app_type) {
+        Some(segment) => segment,
+        None => {
+            // 数据库关闭后再写缓存，缓存记录的修改时间才与之后看到的一致
+            let segment = ProviderService::prompt_segment(&open_state()?, app_type)?;
+            ProviderService::cache_prompt_segment(&segment);
+            segment
+        }
+    };
+    // Claude Code 的 statusLine 会传入实际使用的模型
+    if let Some(model) = args.value("--model").filter(|m| !m.trim().is_empty()) {
+        segment.model = Some(model.to_string());
+    }
+    let rendered = segment.render(format);
+    Ok(CommandOutput::new(json!({ "segment": segment, "rendered": rendered })).human(rendered))
+}
+
+fn integrate(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch integrate claude-statusline
+       cc-switch integrate vscode|nvim|raycast [--dir <dir>]（同 integrations generate）";
+    let args = ParsedArgs::parse(args, &["--dir"], &[], USAGE)?;
+    let [target] = args.positional.as_slice() else {
+        return Err(CliError::Usage(USAGE.to_string()));
+    };
+    let binary = IntegrationService::current_binary();
+
+    if target == "claude-statusline" {
+        if args.has("--dir") {
+            return Err(CliError::Usage(USAGE.to_string()));
+        }
+        let install = IntegrationService::install_claude_statusline(&binary)?;
+        let mut human = vec![
+            format!("已写入 statusLine 脚本: {}", install.script.display()),
+            format!("已更新 {}", install.settings.display()),
+        ];
+        if let Some(previous) = &install.replaced {
+            human.push(format!("原 statusLine 命令已被替换: {previous}"));
+        }
+        return Ok(CommandOutput::new(&install).human(human.join("\n")));
+    }
+
+    generate_integration(target, &args)
+}
+
+fn integrations(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch integrations generate vscode|nvim|raycast [--dir <dir>]";
+    let args = ParsedArgs::parse(args, &["--dir"], &[], USAGE)?;
+    let [action, target] = args.positional.as_slice() else {
+        return Err(CliError::Usage(USAGE.to_string()));
+    };
+    if action != "generate" {
+        return Err(CliError::Usage(USAGE.to_string()));
+    }
+    generate_integration(target, &args)
+}
+
+/// 在 `--dir`（默认当前目录）中生成编辑器 / 启动器的集成文件
+fn generate_integration(target: &str, args: &ParsedArgs) -> Result<CommandOutput, CliError> {
+    let target = IntegrationTarget::from_str(target).map_err(CliError::Argument)?;
+    let binary = IntegrationService::current_binary();
+    let dir = match args.value("--dir") {
+        Some(dir) => PathBuf::from(dir),
+        None => std::env::current_dir()
+            .map_err(|e| AppError::Message(format!("无法获取当前目录: {e}")))?,
+    };
+    let files = IntegrationService::generate(target, &binary);
+    let written = IntegrationService::write_files(&dir, &files)?;
+    let human = written
+        .iter()
+        .map(|path| format!("已写入 {}", path.display()))
+        .collect::<Vec<_>>()
+        .join("\n");
+    Ok(CommandOutput::new(json!({ "files": written })).human(human))
+}
+
+fn shell_env(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch env <id> [--app <app>] [--shell bash|fish|powershell]";
+    let args = ParsedArgs::parse(args, &["--app", "--shell"], &[], USAGE)?;
+    let [id] = args.positional.as_slice() else {
+        return Err(CliError::Usage(USAGE.to_string()));
+    };
+    let shell = match args.value("--shell") {
+        Some(shell) => ShellKind::from_str(shell).map_err(CliError::Argument)?,
+        None => default_shell(),
+    };
+    let app_type = args.app_type()?;
+    let state = open_state()?;
+    let id = &resolve_id(&state, &app_type, id)?;
+    let script = ProviderService::shell_env(&state, app_type, id, shell)?;
+    Ok(CommandOutput::new(json!({ "script": script })).human(script.trim_end()))
+}
+
+/// `switch --temporary`：切换并登记恢复记录；有 `--for` 时启动后台计时进程，
+/// 有包装命令时运行它并在退出后切回
+fn switch_temporary(
+    state: AppState,
+    app_type: AppType,
+    provider: &Provider,
+    duration: Option<std::time::Duration>,
+    command: &[String],
+) -> Result<CommandOutput, CliError> {
+    let pid = (!command.is_empty()).then(std::process::id);
+    let record =
+        TemporarySwitchService::start(&state, app_type.clone(), &provider.id, duration, pid)?;
+    if duration.is_some() {
+        TemporarySwitchService::spawn_revert_timer(&app_type, record.token)?;
+    }
+    let until = record.revert_at.and_then(|at| {
+        chrono::DateTime::from_timestamp_millis(at).map(|at| {
+            at.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+    });
+    let data = json!({
+        "app": app_type.as_str(),
+        "current": provider.id,
+        "name": provider.name,
+        "revertTo": record.from_id,
+        "revertAt": record.revert_at,
+    });
+    if command.is_empty() {
+        let human = format!(
+            "{}: {} ({})\n  {} 后切回 {}",
+            app_type.as_str(),
+            provider.name,
+            provider.id,
+            until.unwrap_or_default(),
+            record.from_id
+        );
+        return Ok(CommandOutput::new(data).human(human));
+    }
+
+    // 命令可能运行很久，期间不占用数据库
+    drop(state);
+    eprintln!(
+        "{}: 临时切换到 {} ({})，命令退出后切回 {}",
+        app_type.as_str(),
+        provider.name,
+        provider.id,
+        record.from_id
+    );
+    let result = ProviderService::run_session(command, &[]);
+    let reverted = TemporarySwitchService::revert(&open_state()?, &app_type, record.token)?;
+    let code = result?;
+    let human = match &reverted {
+        Some(from_id) => format!("{}: 已切回 {from_id}", app_type.as_str()),
+        None => format!(
+            "{}: 限时切换已结束或已被手动切换，未切回",
+            app_type.as_str()
+        ),
+    };
+    Ok(CommandOutput::new(data).human(human).code(code))
+}
+
+/// `switch --revert`：切回所有已到期的限时切换；`--wait <token>` 为后台计时进程，
+/// 等到该记录到期后再处理
+fn switch_revert(args: &ParsedArgs, usage: &str) -> Result<CommandOutput, CliError> {
+    if !args.positional.is_empty()
+        || args.has("--temporary")
+        || args.has("--fastest")
+        || args.has("--for")
+    {
+        return Err(CliError::Usage(usage.to_string()));
+    }
+    if let Some(token) = args.value("--wait") {
+        let token: i64 = token
+            .parse()
+            .map_err(|_| CliError::Usage(usage.to_string()))?;
+        let app_type = args.app_type()?;
+        let revert_at = {
+            let state = open_state()?;
+            TemporarySwitchService::get(&state, &app_type)?
+                .filter(|record| record.token == token)
+                .and_then(|record| record.revert_at)
+        };
+        let Some(revert_at) = revert_at else {
+            return Ok(CommandOutput::new(Value::Null));
+        };
+        let wait = revert_at - chrono::Utc::now().timestamp_millis();
+        if wait > 0 {
+            std::thread::sleep(std::time::Duration::from_millis(wait as u64));
+        }
+        let reverted = TemporarySwitchService::revert(&open_state()?, &app_type, token)?;
+        return Ok(CommandOutput::new(
+            json!({ "app": app_type.as_str(), "reverted": reverted }),
+        ));
+    }
+
+    let state = open_state()?;
+    let only = match args.value("--app") {
+        Some(_) => Some(args.app_type()?),
+        None => None,
+    };
+    let mut reverted = Vec::new();
+    for record in TemporarySwitchService::due(&state)? {
+        let Ok(app_type) = AppType::from_str(&record.app_type) else {
+            continue;
+        };
+        if only.as_ref().is_some_and(|only| *only != app_type) {
+            continue;
+        }
+        if let Some(from_id) = TemporarySwitchService::revert(&state, &app_type, record.token)? {
+            reverted.push((app_type, from_id));
+        }
+    }
+    let pending = TemporarySwitchService::list(&state)?;
+    let mut human: Vec<String> = reverted
+        .iter()
+        .map(|(app_type, from_id)| format!("{}: 已切回 {from_id}", app_type.as_str()))
+        .collect();
+    if human.is_empty() {
+        human.push("没有到期的限时切换".to_string());
+    }
+    let rows = reverted
+        .iter()
+        .map(|(app_type, from_id)| vec![app_type.as_str().to_string(), from_id.clone()])
+        .collect();
+    Ok(CommandOutput::new(json!({
+        "reverted": reverted
+            .iter()
+            .map(|(app_type, from_id)| json!({ "app": app_type.as_str(), "current": from_id }))
+            .collect::<Vec<_>>(),
+        "pending": pending,
+    }))
+    .human(human.join("\n"))
+    .table(vec!["APP", "CURRENT"], rows))
+}
+
+fn run_command(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str =
+        "用法: cc-switch run [--app <app>] [--provider <id>] -- <command> [args...]";
+    let (options, command) = match args.iter().position(|arg| arg == "--") {
+        Some(index) => (&args[..index], &args[index + 1..]),
+        None => (args, &[][..]),
+    };
+    let parsed = ParsedArgs::parse(options, &["--app", "--provider"], &[], USAGE)?;
+    // 省略 `--` 时位置参数即为命令（此时命令本身不能带以 `--` 开头的参数）
+    let command = if command.is_empty() {
+        parsed.positional.as_slice()
+    } else if parsed.positional.is_empty() {
+        command
+    } else {
+        return Err(CliError::Usage(USAGE.to_string()));
+    };
+    if command.is_empty() {
+        return Err(CliError::Usage(USAGE.to_string()));
+    }
+    let app_type = parsed.app_type()?;
+    // 子进程可能运行很久，取完环境变量就关闭数据库
+    let env = {
+        let state = open_state()?;
+        let provider = parsed
+            .value("--provider")
+            .map(|id| resolve_id(&state, &app_type, id))
+            .transpose()?;
+        ProviderService::session_env(&state, app_type, provider.as_deref())?
+    };
+    let code = ProviderService::run_session(command, &env)?;
+    Ok(CommandOutput::new(Value::Null).code(code))
+}
+
+/// 未指定 `--shell` 时的语法：`$SHELL` 为 fish 时用 fish，Windows 上用 PowerShell，其余为 POSIX
+fn default_shell() -> ShellKind {
+    let fish = std::env::var("SHELL").is_ok_and(|shell| shell.ends_with("fish"));
+    if fish {
+        ShellKind::Fish
+    } else if cfg!(windows) {
+        ShellKind::Powershell
+    } else {
+        ShellKind::Bash
+    }
+}
+
+fn usage(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch usage <id> [--app <app>]
+       cc-switch usage --all [--app <app>]";
+    let args = ParsedArgs::parse(args, &["--app"], &["--all"], USAGE)?;
+    if args.has("--all") {
+        if !args.positional.is_empty() {
+            return Err(CliError::Usage(USAGE.to_string()));
+        }
+        return usage_all(&args);
+    }
+    let [id] = args.positional.as_slice() else {
+        return Err(CliError::Usage(USAGE.to_string()));
+    };
+    let app_type = args.app_type()?;
+    let state = open_state()?;
+    let id = &resolve_id(&state, &app_type, id)?;
+    let result = runtime()?.block_on(ProviderService::query_usage(&state, app_type, id))?;
+    if !result.success {
+        return Err(CliError::Failed(AppError::Message(
+            result.error.unwrap_or_else(|| "查询失败".to_string()),
+        )));
+    }
+
+    let plans = result.data.unwrap_or_default();
+    let format = UsageFormatter::from_settings(&crate::settings::get_settings());
+    let mut lines = Vec::new();
+    let mut rows = Vec::new();
+    for plan in &plans {
+        let [name, used, remaining, total] = usage_cells(&format, plan);
+        lines.push(format!(
+            "{name}  used {used}  remaining {remaining}  total {total}"
+        ));
+        if plan.is_valid == Some(false) {
+            if let Some(message) = &plan.invalid_message {
+                lines.push(format!("  {message}"));
+            }
+        }
+        rows.push(vec![name, used, remaining, total]);
+    }
+    Ok(CommandOutput::new(&plans)
+        .human(lines.join("\n"))
+        .table(vec!["PLAN", "USED", "REMAINING", "TOTAL"], rows))
+}
+
+/// `usage --all`：并发查询所有配置了用量查询的供应商，每个套餐一行
+fn usage_all(args: &ParsedArgs) -> Result<CommandOutput, CliError> {
+    let app_type = args.app_type()?;
+    let state = open_state()?;
+    let summaries = runtime()?.block_on(ProviderService::query_all_usage(&state, app_type))?;
+
+    let format = UsageFormatter::from_settings(&crate::settings::get_settings());
+    let mut rows = Vec::new();
+    for summary in &summaries {
+        let result = &summary.result;
+        let plans = result.data.as_deref().unwrap_or_default();
+        if !result.success || plans.is_empty() {
+            let error = result.error.as_deref().unwrap_or("查询失败");
+            let status = if result.success { "-" } else { error };
+            rows.push(vec![
+                summary.provider_name.clone(),
+                "-".to_string(),
+                "-".to_string(),
+                "-".to_string(),
+                "-".to_string(),
+                status.to_string(),
+            ]);
+            continue;
+        }
+        for plan in plans {
+            let [name, used, remaining, total] = usage_cells(&format, plan);
+            let status = match plan.is_valid {
+                Some(false) => plan.invalid_message.as_deref().unwrap_or("无效"),
+                _ => "ok",
+            };
+            rows.push(vec![
+                summary.provider_name.clone(),
+                name,
+                used,
+                remaining,
+                total,
+                status.to_string(),
+            ]);
+        }
+    }
+    let mut output = CommandOutput::new(json!(summaries)).table(
+        vec!["PROVIDER", "PLAN", "USED", "REMAINING", "TOTAL", "STATUS"],
+        rows,
+    );
+    if summaries.is_empty() {
+        output = output.human("没有配置用量查询的供应商".to_string());
+    }
+    Ok(output)
+}
+
+/// 一个套餐的名称、已用、剩余与总额（按设置格式化金额）
+fn usage_cells(format: &UsageFormatter, plan: &UsageData) -> [String; 4] {
+    let unit = plan.unit.as_deref();
+    let amount = |value: Option<f64>| {
+        value
+            .map(|v| format.amount(v, unit))
+            .unwrap_or_else(|| "-".to_string())
+    };
+    [
+        plan.plan_name.as_deref().unwrap_or("default").to_string(),
+        amount(plan.used),
+        amount(plan.remaining),
+        amount(plan.total),
+    ]
+}
+
+fn env(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str =
+        "用法: cc-switch provider env list|set|unset <id> [KEY=VALUE | KEY ...] [--app <app>]";
+    let args = ParsedArgs::parse(args, &["--app"], &[], USAGE)?;
+    let [action, id, vars @ ..] = args.positional.as_slice() else {
+        return Err(CliError::Usage(USAGE.to_string()));
+    };
+
+    let mut set = BTreeMap::new();
+    let mut unset = Vec::new();
+    match action.as_str() {
+        "list" if vars.is_empty() => {}
+        "set" if !vars.is_empty() => {
+            for var in vars {
+                let (key, value) = parse_env_assignment(var).map_err(CliError::Argument)?;
+                set.insert(key, value);
+            }
+        }
+        "unset" if !vars.is_empty() => unset = vars.to_vec(),
+        _ => return Err(CliError::Usage(USAGE.to_string())),
+    }
+
+    let app_type = args.app_type()?;
+    let state = open_state()?;
+    let id = &resolve_id(&state, &app_type, id)?;
+    let env = if action == "list" {
+        ProviderService::env(&state, app_type, id)?
+    } else {
+        ProviderService::set_env(&state, app_type, id, &set, &unset)?
+    };
+
+    let env: BTreeMap<String, String> = env
+        .into_iter()
+        .map(|(key, value)| {
+            let value = if is_secret_env_name(&key) && !value.is_empty() {
+                mask_secret(&value)
+            } else {
+                value
+            };
+            (key, value)
+        })
+        .collect();
+    let human = env
+        .iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let rows = env
+        .iter()
+        .map(|(key, value)| vec![key.clone(), value.clone()])
+        .collect();
+    Ok(CommandOutput::new(&env)
+        .human(human)
+        .table(vec!["KEY", "VALUE"], rows))
+}
+
+fn edit(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch provider edit <id> [--http-proxy <url>] [--https-proxy <url>] [--no-proxy <hosts>]
+                              [--key-expires <YYYY-MM-DD>] [--rotate-every <days>] [--app <app>]";
+    let args = ParsedArgs::parse(
+        args,
+        &[
+            "--app",
+            "--http-proxy",
+            "--https-proxy",
+            "--no-proxy",
+            "--key-expires",
+            "--rotate-every",
+        ],
+        &[],
+        USAGE,
+    )?;
+    let [id] = args.positional.as_slice() else {
+        return Err(CliError::Usage(USAGE.to_string()));
+    };
+    let proxy = ProviderProxy {
+        http: args.value("--http-proxy").map(str::to_string),
+        https: args.value("--https-proxy").map(str::to_string),
+        no_proxy: args.value("--no-proxy").map(str::to_string),
+    };
+    let key_policy = KeyPolicy {
+        expires_at: args.value("--key-expires").map(str::to_string),
+        rotation_days: args.value("--rotate-every").map(str::to_string),
+    };
+    let app_type = args.app_type()?;
+    let state = open_state()?;
+    let id = &resolve_id(&state, &app_type, id)?;
+
+    let provider = if key_policy.is_empty() {
+        ProviderService::set_proxy(&state, app_type, id, &proxy)?
+    } else {
+        ProviderService::set_proxy(&state, app_type.clone(), id, &proxy)?;
+        ProviderService::set_key_policy(&state, app_type, id, &key_policy)?
+    };
+    let expiry = provider.key_expiry(chrono::Local::now().date_naive());
+    let meta = provider.meta.unwrap_or_default();
+    let or_dash = |value: Option<&String>| value.cloned().unwrap_or_else(|| "-".to_string());
+    let mut human = vec![
+        format!("HTTP_PROXY={}", or_dash(meta.http_proxy.as_ref())),
+        format!("HTTPS_PROXY={}", or_dash(meta.https_proxy.as_ref())),
+        format!("NO_PROXY={}", or_dash(meta.no_proxy.as_ref())),
+    ];
+    if let Some(expiry) = &expiry {
+        human.push(format!(
+            "密钥到期: {}（{}）",
+            expiry.due.format("%Y-%m-%d"),
+            expiry.describe()
+        ));
+    }
+    Ok(CommandOutput::new(json!({
+        "httpProxy": meta.http_proxy,
+        "httpsProxy": meta.https_proxy,
+        "noProxy": meta.no_proxy,
+        "keyExpiresAt": meta.key_expires_at,
+        "keyRotationDays": meta.key_rotation_days,
+        "keyExpiry": expiry,
+    }))
+    .human(human.join("\n")))
+}
+
+fn rotate_key(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch provider rotate-key <id> --api-key <key> [--expires <YYYY-MM-DD>] [--app <app>]";
+    let args = ParsedArgs::parse(args, &["--app", "--api-key", "--expires"], &[], USAGE)?;
+    let ([id], Some(api_key)) = (args.positional.as_slice(), args.value("--api-key")) else {
+        return Err(CliError::Usage(USAGE.to_string()));
+    };
+    let app_type = args.app_type()?;
+    let state = open_state()?;
+    let id = &resolve_id(&state, &app_type, id)?;
+    let is_current = ProviderService::current(&state, app_type.clone())? == *id;
+
+    let provider =
+        ProviderService::rotate_key(&state, app_type, id, api_key, args.value("--expires"))?;
+    let meta = provider.meta.unwrap_or_default();
+    let retired = meta.retired_keys.last().map(|k| k.fingerprint.clone());
+    let mut human = format!("已更新 {} ({}) 的 API Key", provider.name, provider.id);
+    if let Some(fingerprint) = &retired {
+        human.push_str(&format!("，旧密钥指纹 {fingerprint} 已记入历史"));
+    }
+    if is_current {
+        human.push_str("；已写入 live 配置");
+    }
+    Ok(CommandOutput::new(json!({
+        "id": provider.id,
+        "retiredFingerprint": retired,
+        "keyExpiresAt": meta.key_expires_at,
+        "liveUpdated": is_current,
+    }))
+    .human(human))
+}
+
+fn proxy(args: &[String], out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str =
+        "用法: cc-switch proxy start [--port <port>] [--address <addr>] | cc-switch proxy status";
+    let args = ParsedArgs::parse(args, &["--port", "--address"], &[], USAGE)?;
+    let action = match args.positional.as_slice() {
+        [action] if action == "start" => "start",
+        [action] if action == "status" && args.options.is_empty() => "status",
+        _ => return Err(CliError::Usage(USAGE.to_string())),
+    };
+    let port = match args.value("--port") {
+        Some(value) => Some(
+            value
+                .parse::<u16>()
+                .map_err(|_| CliError::Usage(USAGE.to_string()))?,
+        ),
+        None => None,
+    };
+
+    let state = open_state()?;
+    let runtime = runtime()?;
+
+    if action == "status" {
+        let config = runtime.block_on(state.db.get_proxy_config())?;
+        let addr = format!("{}:{}", config.listen_address, config.listen_port);
+        let listening = addr
+            .parse()
+            .ok()
+            .and_then(|addr| {
+                std::net::TcpStream::connect_timeout(&addr, std::time::Duration::from_secs(1)).ok()
+            })
+            .is_some();
+        let status = if listening { "running" } else { "stopped" };
+        return Ok(CommandOutput::new(json!({
+            "address": addr,
+            "running": listening,
+        }))
+        .human(format!("{addr}: {status}"))
+        .code(if listening {
+            cli_error::SUCCESS
+        } else {
+            cli_error::FAILURE
+        }));
+    }
+
+    runtime.block_on(async {
+        let address = args.value("--address").map(str::to_string);
+        let info = state
+            .proxy_service
+            .start_detached(address, port)
+            .await
+            .map_err(|e| CliError::Failed(AppError::Message(e)))?;
+        let base = format!("http://{}:{}", info.address, info.port);
+        out.emit(
+            &CommandOutput::new(&info).human(format!(
+                "代理已启动: {base}（Ctrl+C 停止）\n  Claude: ANTHROPIC_BASE_URL={base}\n  Codex:  base_url = \"{base}/v1\"\n  Gemini: GOOGLE_GEMINI_BASE_URL={base}"
+            )),
+        );
+        // 在前台常驻，直到进程被终止
+        std::future::pending::<Result<CommandOutput, CliError>>().await
+    })
+}
+
+/// `cc-switch init` 中一个应用的处理结果
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InitResult {
+    #[serde(flatten)]
+    config: DetectedConfig,
+    imported: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+fn init(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch init [--app <app>] [--yes]";
+    let args = ParsedArgs::parse(args, &["--app"], &["--yes"], USAGE)?;
+    if !args.positional.is_empty() {
+        return Err(CliError::Usage(USAGE.to_string()));
+    }
+    let only = match args.value("--app") {
+        Some(_) => Some(args.app_type()?),
+        None => None,
+    };
+    let state = open_state()?;
+    let results = onboard(&state, only.as_ref(), args.has("--yes"))?;
+
+    let mut human = Vec::new();
+    let mut rows = Vec::new();
+    for result in &results {
+        let status = match (&result.error, result.imported) {
+            (Some(error), _) => format!("导入失败: {error}"),
+            (None, true) => "已导入为 default 并设为当前".to_string(),
+            (None, false) if !result.config.found => "未找到配置".to_string(),
+            (None, false) if result.config.has_providers => "已有供应商，跳过".to_string(),
+            (None, false) => "未导入".to_string(),
+        };
+        let path = result.config.path.display().to_string();
+        human.push(format!("{:<8}{path}  {status}", result.config.app));
+        rows.push(vec![result.config.app.clone(), path, status]);
+    }
+    let skipped = results
+        .iter()
+        .any(|result| result.config.importable() && !result.imported && result.error.is_none());
+    if skipped && !std::io::IsTerminal::is_terminal(&std::io::stdin()) {
+        human.push("非交互环境下加上 --yes 导入检测到的配置".to_string());
+    }
+    let failed = results.iter().any(|result| result.error.is_some());
+    Ok(CommandOutput::new(&results)
+        .human(human.join("\n"))
+        .table(vec!["APP", "PATH", "STATUS"], rows)
+        .code(if failed {
+            cli_error::FAILURE
+        } else {
+            cli_error::SUCCESS
+        }))
+}
+
+/// 检测现有配置并逐个导入：`assume_yes` 时直接导入，否则在终端中逐个确认
+/// （非交互环境下不导入）
+fn onboard(
+    state: &AppState,
+    only: Option<&AppType>,
+    assume_yes: bool,
+) -> Result<Vec<InitResult>, CliError> {
+    use std::io::IsTerminal;
+
+    let interactive = std::io::stdin().is_terminal();
+    let mut results = Vec::new();
+    for config in OnboardingService::detect(state)? {
+        let app_type = AppType::from_str(&config.app)?;
+        if only.is_some_and(|only| *only != app_type) {
+            continue;
+        }
+        let mut result = InitResult {
+            config,
+            imported: false,
+            error: None,
+        };
+        if result.config.importable()
+            && (assume_yes
+                || (interactive
+                    && confirm(&format!(
+                        "导入 {} 的现有配置 {}？",
+                        result.config.app,
+                        result.config.path.display()
+                    ))?))
+        {
+            match OnboardingService::import(state, app_type) {
+                Ok(imported) => result.imported = imported,
+                Err(e) => result.error = Some(e.to_string()),
+            }
+        }
+        results.push(result);
+    }
+    Ok(results)
+}
+
+/// 在 stderr 提问并读取回答，直接回车视为同意
+fn confirm(question: &str) -> Result<bool, CliError> {
+    use std::io::{BufRead, Write};
+
+    let mut stderr = std::io::stderr();
+    let _ = write!(stderr, "{question} [Y/n] ");
+    let _ = stderr.flush();
+    let mut line = String::new();
+    std::io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .map_err(|e| AppError::Message(format!("读取输入失败: {e}")))?;
+    Ok(matches!(
+        line.trim().to_ascii_lowercase().as_str(),
+        "" | "y" | "yes"
+    ))
+}
+
+/// 数据库里还没有任何供应商且在交互式终端中运行时，先引导导入现有配置
+///
+/// 引导失败不影响随后的子命令，只打印警告。
+fn first_run_onboarding() {
+    use std::io::IsTerminal;
+
+    if !std::io::stdin().is_terminal() || !std::io::stderr().is_terminal() {
+        return;
+    }
+    let result = open_state().and_then(|state| {
+        if !OnboardingService::is_first_run(&state)? {
+            return Ok(());
+        }
+        if !OnboardingService::detect(&state)?
+            .iter()
+            .any(DetectedConfig::importable)
+        {
+            return Ok(());
+        }
+        eprintln!("还没有任何供应商，检测到以下应用的现有配置（之后也可运行 `cc-switch init`）：");
+        for result in onboard(&state, None, false)? {
+            if result.imported {
+                eprintln!("  {}: 已导入为 default", result.config.app);
+            } else if let Some(error) = result.error {
+                eprintln!("  {}: 导入失败: {error}", result.config.app);
+            }
+        }
+        Ok(())
+    });
+    if let Err(CliError::Failed(e) | CliError::Argument(e)) = result {
+        log::warn!("首次运行引导失败: {e}");
+    }
+}
+
+/// live 配置使用了未保存的凭据时：`--auto-adopt` 直接导入为新的当前供应商，
+/// 否则在交互式终端中询问（`ask` 为 false 时只在 `--auto-adopt` 下处理）
+fn adopt_unmanaged_live(ask: bool) {
+    use std::io::IsTerminal;
+
+    let auto = auto_adopt();
+    if !auto && (!ask || !std::io::stdin().is_terminal() || !std::io::stderr().is_terminal()) {
+        return;
+    }
+    let result = open_state().and_then(|state| {
+        for live in ProviderService::unmanaged_live(&state)? {
+            let base_url = if live.base_url.is_empty() {
+                "官方地址"
+            } else {
+                live.base_url.as_str()
+            };
+            if !auto
+                && !confirm(&format!(
+                    "{} 的 live 配置（{base_url}）不属于任何供应商，是否保存为新的供应商？",
+                    live.app
+                ))?
+            {
+                continue;
+            }
+            if let Some(provider) =
+                ProviderService::adopt_live(&state, AppType::from_str(&live.app)?)?
+            {
+                eprintln!(
+                    "{}: 已保存为供应商 {} ({})",
+                    live.app, provider.name, provider.id
+                );
+            }
+        }
+        Ok(())
+    });
+    if let Err(CliError::Failed(e) | CliError::Argument(e)) = result {
+        log::warn!("检查 live 配置失败: {e}");
+    }
+}
+
+fn history(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch history [--app <app>] [--cwd [path]] [--limit <n>]";
+    let args = ParsedArgs::parse(args, &["--app", "--limit"], &["--cwd"], USAGE)?;
+    // `--cwd` 后的路径是唯一允许的位置参数
+    let path = match args.positional.as_slice() {
+        [] => None,
+        [path] if args.has("--cwd") => Some(path.clone()),
+        _ => return Err(CliError::Usage(USAGE.to_string())),
+    };
+    let limit = match args.value("--limit") {
+        Some(value) => value
+            .parse::<usize>()
+            .map_err(|_| CliError::Usage(format!("--limit 需要一个正整数\n{USAGE}")))?,
+        None => 50,
+    };
+    // 路径可省略，默认为当前目录
+    let cwd = match path {
+        Some(path) => Some(path),
+        None if args.has("--cwd") => Some(
+            std::env::current_dir()
+                .map_err(|e| AppError::Message(format!("无法获取当前目录: {e}")))?
+                .to_string_lossy()
+                .into_owned(),
+        ),
+        None => None,
+    };
+
+    let state = open_state()?;
+    let records =
+        ProviderService::switch_history(&state, args.value("--app"), cwd.as_deref(), limit)?;
+
+    let rows = records
+        .iter()
+        .map(|record| {
+            vec![
+                local_time(record.switched_at),
+                record
+                    .active_until
+                    .map(local_time)
+                    .unwrap_or_else(|| "now".to_string()),
+                record.app.clone(),
+                format!("{} ({})", record.provider_name, record.provider_id),
+                record.cwd.clone().unwrap_or_else(|| "-".to_string()),
+            ]
+        })
+        .collect();
+    Ok(CommandOutput::new(&records)
+        .table(vec!["SWITCHED", "UNTIL", "APP", "PROVIDER", "CWD"], rows))
+}
+
+fn provider_history(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch provider history <id> [--app <app>]";
+    let args = ParsedArgs::parse(args, &["--app"], &[], USAGE)?;
+    let [id] = args.positional.as_slice() else {
+        return Err(CliError::Usage(USAGE.to_string()));
+    };
+    let app_type = args.app_type()?;
+    let state = open_state()?;
+    let id = &resolve_id(&state, &app_type, id)?;
+    let entries = ProviderService::change_history(&state, app_type, id)?;
+
+    let mut human = Vec::new();
+    let mut rows = Vec::new();
+    for entry in &entries {
+        human.push(format!(
+            "r{}  {}  {}",
+            entry.revision,
+            local_time(entry.changed_at),
+            entry.source
+        ));
+        for change in &entry.changes {
+            human.push(format!("  {}", change_line(change, false)));
+        }
+        rows.push(vec![
+            entry.revision.to_string(),
+            local_time(entry.changed_at),
+            entry.source.clone(),
+            entry
+                .changes
+                .iter()
+                .map(|change| change.path.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+        ]);
+    }
+    if human.is_empty() {
+        human.push(format!("{id} 没有变更记录"));
+    }
+    Ok(CommandOutput::new(&entries)
+        .human(human.join("\n"))
+        .table(vec!["REV", "CHANGED", "SOURCE", "FIELDS"], rows))
+}
+
+/// 一个字段变化的单行描述（`+` 新增、`-` 删除、`~` 修改），`color` 时按类型着色
+fn change_line(change: &JsonChange, color: bool) -> String {
+    let show = |value: &Option<Value>| match value {
+        None => "-".to_string(),
+        Some(Value::String(text)) => text.replace('\n', "\\n"),
+        Some(value) => value.to_string(),
+    };
+    let path = if change.path.is_empty() {
+        "/"
+    } else {
+        change.path.as_str()
+    };
+    let (line, ansi) = match (&change.before, &change.after) {
+        (None, _) => (format!("+ {path} = {}", show(&change.after)), "32"),
+        (_, None) => (format!("- {path} (was {})", show(&change.before)), "31"),
+        _ => (
+            format!(
+                "~ {path}: {} -> {}",
+                show(&change.before),
+                show(&change.after)
+            ),
+            "33",
+        ),
+    };
+    if color {
+        format!("\x1b[{ansi}m{line}\x1b[0m")
+    } else {
+        line
+    }
+}
+
+/// stdout 是终端且未设置 NO_COLOR 时使用颜色
+fn color_stdout() -> bool {
+    use std::io::IsTerminal;
+    std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+}
+
+fn provider_pin(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    set_pinned(
+        args,
+        true,
+        "用法: cc-switch provider pin <id> [--app <app>]",
+    )
+}
+
+fn provider_unpin(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    set_pinned(
+        args,
+        false,
+        "用法: cc-switch provider unpin <id> [--app <app>]",
+    )
+}
+
+fn set_pinned(args: &[String], pinned: bool, usage: &str) -> Result<CommandOutput, CliError> {
+    let args = ParsedArgs::parse(args, &["--app"], &[], usage)?;
+    let [id] = args.positional.as_slice() else {
+        return Err(CliError::Usage(usage.to_string()));
+    };
+    let app_type = args.app_type()?;
+    let state = open_state()?;
+    let id = &resolve_id(&state, &app_type, id)?;
+
+    ProviderService::set_pinned(&state, app_type.clone(), id, pinned)?;
+    let slots = ProviderService::pinned(&state, app_type)?;
+    let human = match slots.iter().position(|p| &p.id == id) {
+        Some(slot) if slot < 9 => format!("已置顶 {id}（快捷切换: cc-switch {}）", slot + 1),
+        Some(_) => format!("已置顶 {id}（超出 9 个快捷切换槽位）"),
+        None => format!("已取消置顶 {id}"),
+    };
+    let ids: Vec<_> = slots.into_iter().map(|p| p.id).collect();
+    Ok(CommandOutput::new(json!({ "id": id, "pinned": pinned, "slots": ids })).human(human))
+}
+
+fn provider_alias(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch provider alias <id> <alias> [--app <app>]
+       cc-switch provider alias <id> --clear [--app <app>]";
+    let args = ParsedArgs::parse(args, &["--app"], &["--clear"], USAGE)?;
+    let (id, alias) = match (args.positional.as_slice(), args.has("--clear")) {
+        ([id, alias], false) => (id, Some(alias.as_str())),
+        ([id], true) => (id, None),
+        _ => return Err(CliError::Usage(USAGE.to_string())),
+    };
+    let app_type = args.app_type()?;
+    let state = open_state()?;
+    let id = &resolve_id(&state, &app_type, id)?;
+
+    ProviderService::set_alias(&state, app_type, id, alias).map_err(CliError::Argument)?;
+    let human = match alias {
+        Some(alias) => format!("{id} 的别名已设为 {}", alias.trim()),
+        None => format!("已清除 {id} 的别名"),
+    };
+    Ok(CommandOutput::new(json!({ "id": id, "alias": alias.map(str::trim) })).human(human))
+}
+
+fn provider_enrich(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch provider enrich <id> [--app <app>] [--dry-run]";
+    let args = ParsedArgs::parse(args, &["--app"], &["--dry-run"], USAGE)?;
+    let [id] = args.positional.as_slice() else {
+        return Err(CliError::Usage(USAGE.to_string()));
+    };
+    let app_type = args.app_type()?;
+    let state = open_state()?;
+    let id = &resolve_id(&state, &app_type, id)?;
+
+    let result = ProviderService::enrich(&state, app_type, id, !args.has("--dry-run"))?;
+    let human = if result.filled_fields.is_empty() {
+        format!("{id} 匹配 {}，没有需要补全的字段", result.vendor)
+    } else {
+        let verb = if result.applied {
+            "已补全"
+        } else {
+            "将补全"
+        };
+        format!(
+            "{id} 匹配 {}，{verb}: {}",
+            result.vendor,
+            result.filled_fields.join(", ")
+        )
+    };
+    Ok(CommandOutput::new(&result).human(human))
+}
+
+fn provider_search(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch provider search <query> [--app <app>]";
+    let args = ParsedArgs::parse(args, &["--app"], &[], USAGE)?;
+    if args.positional.is_empty() {
+        return Err(CliError::Usage(USAGE.to_string()));
+    }
+    let query = args.positional.join(" ");
+    let app_type = args.app_type()?;
+    let state = open_state()?;
+
+    let providers: IndexMap<String, Provider> =
+        ProviderService::search(&state, app_type.clone(), &query)?
+            .into_iter()
+            .map(|p| (p.id.clone(), p))
+            .collect();
+    let human = if providers.is_empty() {
+        format!("没有匹配 {query} 的供应商")
+    } else {
+        ProviderService::render_table_of(
+            &state,
+            &app_type,
+            &providers,
+            DEFAULT_COLUMNS,
+            TableStyle::for_stdout(),
+        )?
+    };
+    let providers: Vec<_> = providers
+        .into_values()
+        .map(|p| json!({ "id": p.id, "name": p.name, "category": p.category }))
+        .collect();
+    Ok(CommandOutput::new(providers).human(human))
+}
+
+fn provider_clone(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str =
+        "用法: cc-switch provider clone <id> [--name <name>] [--api-key <key>] [--app <app>]";
+    let args = ParsedArgs::parse(args, &["--app", "--name", "--api-key"], &[], USAGE)?;
+    let [id] = args.positional.as_slice() else {
+        return Err(CliError::Usage(USAGE.to_string()));
+    };
+    let app_type = args.app_type()?;
+    let state = open_state()?;
+    let id = &resolve_id(&state, &app_type, id)?;
+
+    let provider = ProviderService::clone_provider(
+        &state,
+        app_type,
+        id,
+        args.value("--name"),
+        args.value("--api-key"),
+    )?;
+    Ok(CommandOutput::new(json!({
+        "source": id,
+        "id": provider.id,
+        "name": provider.name,
+    }))
+    .human(format!(
+        "已复制 {id} 为 {} ({})",
+        provider.name, provider.id
+    )))
+}
+
+/// 命令行给出的供应商 ID（解析别名）；没有给出且在终端中运行时交互式多选
+fn selected_ids(
+    state: &AppState,
+    app_type: &AppType,
+    ids: &[String],
+    action: &str,
+    usage: &str,
+) -> Result<Vec<String>, CliError> {
+    if !ids.is_empty() {
+        return ids
+            .iter()
+            .map(|id| resolve_id(state, app_type, id))
+            .collect();
+    }
+    if !std::io::IsTerminal::is_terminal(&std::io::stdin()) {
+        return Err(CliError::Usage(usage.to_string()));
+    }
+    pick_providers(state, app_type, action)
+}
+
+fn provider_delete(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch provider delete [<id>...] [--app <app>] [--yes]";
+    let args = ParsedArgs::parse(args, &["--app"], &["--yes"], USAGE)?;
+    let app = args.app_definition()?;
+    let Some(app_type) = app.app_type() else {
+        return provider_delete_registered(&app, &args.positional, USAGE);
+    };
+    let state = open_state()?;
+    let interactive = args.positional.is_empty();
+    let ids = selected_ids(&state, &app_type, &args.positional, "删除", USAGE)?;
+    if interactive
+        && !args.has("--yes")
+        && !confirm(&format!(
+            "删除 {} 个供应商（{}）？",
+            ids.len(),
+            ids.join(", ")
+        ))?
+    {
+        return Ok(CommandOutput::new(json!({ "deleted": 0 })).human("已取消".to_string()));
+    }
+
+    let deleted = ProviderService::delete_many(&state, app_type, &ids)?;
+    Ok(
+        CommandOutput::new(json!({ "deleted": deleted, "ids": ids }))
+            .human(format!("已删除 {deleted} 个供应商: {}", ids.join(", "))),
+    )
+}
+
+/// 删除注册表应用的供应商（需在命令行中给出 ID，不支持交互选择）
+fn provider_delete_registered(
+    app: &AppDefinition,
+    ids: &[String],
+    usage: &str,
+) -> Result<CommandOutput, CliError> {
+    if ids.is_empty() {
+        return Err(CliError::Usage(usage.to_string()));
+    }
+    let state = open_state()?;
+    let ids = ids
+        .iter()
+        .map(|id| RegisteredAppService::resolve_id(&state, &app.id, id))
+        .collect::<Result<Vec<_>, _>>()?;
+    for id in &ids {
+        RegisteredAppService::delete(&state, &app.id, id)?;
+    }
+    Ok(
+        CommandOutput::new(json!({ "deleted": ids.len(), "ids": ids })).human(format!(
+            "已删除 {} 个供应商: {}",
+            ids.len(),
+            ids.join(", ")
+        )),
+    )
+}
+
+fn provider_tag(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch provider tag <tag> [<id>...] [--app <app>]";
+    let args = ParsedArgs::parse(args, &["--app"], &[], USAGE)?;
+    let Some((tag, ids)) = args.positional.split_first() else {
+        return Err(CliError::Usage(USAGE.to_string()));
+    };
+    let app_type = args.app_type()?;
+    let state = open_state()?;
+    let ids = selected_ids(&state, &app_type, ids, &format!("添加标签 {tag}"), USAGE)?;
+
+    let tagged = ProviderService::add_tag_to_many(&state, app_type, &ids, tag)?;
+    Ok(
+        CommandOutput::new(json!({ "tag": tag, "tagged": tagged, "ids": ids }))
+            .human(format!("已为 {tagged} 个供应商添加标签 {tag}")),
+    )
+}
+
+fn provider_move(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str =
+        "用法: cc-switch provider move <id> --to <position>|--up|--down [--app <app>]";
+    let args = ParsedArgs::parse(args, &["--app", "--to"], &["--up", "--down"], USAGE)?;
+    let [id] = args.positional.as_slice() else {
+        return Err(CliError::Usage(USAGE.to_string()));
+    };
+    let movement = match (args.value("--to"), args.has("--up"), args.has("--down")) {
+        (Some(position), false, false) => match position.parse::<usize>() {
+            Ok(position) if position >= 1 => ProviderMove::To(position - 1),
+            _ => {
+                return Err(CliError::Argument(AppError::InvalidInput(format!(
+                    "无效的位置: {position}（从 1 开始）"
+                ))))
+            }
+        },
+        (None, true, false) => ProviderMove::Up,
+        (None, false, true) => ProviderMove::Down,
+        _ => return Err(CliError::Usage(USAGE.to_string())),
+    };
+    let app_type = args.app_type()?;
+    let state = open_state()?;
+    let id = &resolve_id(&state, &app_type, id)?;
+
+    let position = ProviderService::move_provider(&state, app_type, id, movement)? + 1;
+    Ok(
+        CommandOutput::new(json!({ "id": id, "position": position }))
+            .human(format!("{id} 已移到第 {position} 位")),
+    )
+}
+
+fn provider_reorder(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    use std::io::{BufRead, Write};
+
+    const USAGE: &str = "用法: cc-switch provider reorder [--app <app>]";
+    let args = ParsedArgs::parse(args, &["--app"], &[], USAGE)?;
+    if !args.positional.is_empty() || !std::io::IsTerminal::is_terminal(&std::io::stdin()) {
+        return Err(CliError::Usage(USAGE.to_string()));
+    }
+    let app_type = args.app_type()?;
+    let state = open_state()?;
+    let providers: Vec<Provider> = state
+        .db
+        .get_all_providers(app_type.as_str())?
+        .into_values()
+        .collect();
+    if providers.is_empty() {
+        return Err(CliError::Failed(AppError::Message(format!(
+            "{} 还没有供应商",
+            app_type.as_str()
+        ))));
+    }
+
+    let mut stderr = std::io::stderr();
+    for (index, provider) in providers.iter().enumerate() {
+        let _ = writeln!(
+            stderr,
+            "  {}) {} ({})",
+            index + 1,
+            provider.name,
+            provider.id
+        );
+    }
+    let _ = write!(
+        stderr,
+        "按新顺序输入序号（如 3,1,2，未列出的保持原顺序排在后面）: "
+    );
+    let _ = stderr.flush();
+
+    let mut line = String::new();
+    std::io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .map_err(|e| AppError::Message(format!("读取输入失败: {e}")))?;
+    let mut order: Vec<String> = Vec::new();
+    for part in line
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+    {
+        let provider = part
+            .parse::<usize>()
+            .ok()
+            .and_then(|n| n.checked_sub(1))
+            .and_then(|index| providers.get(index))
+            .ok_or_else(|| CliError::Usage(format!("无效的序号: {part}")))?;
+        if order.contains(&provider.id) {
+            return Err(CliError::Usage(format!("序号重复: {part}")));
+        }
+        order.push(provider.id.clone());
+    }
+    if order.is_empty() {
+        return Ok(CommandOutput::new(json!({ "order": [] })).human("未修改顺序".to_string()));
+    }
+
+    ProviderService::reorder(&state, app_type.clone(), &order)?;
+    let order: Vec<String> = state
+        .db
+        .get_all_providers(app_type.as_str())?
+        .into_keys()
+        .collect();
+    let human = order
+        .iter()
+        .enumerate()
+        .map(|(index, id)| format!("  {}) {id}", index + 1))
+        .collect::<Vec<_>>()
+        .join("\n");
+    Ok(CommandOutput::new(json!({ "order": order })).human(human))
+}
+
+fn provider_snippet(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch provider snippet <id> --lang python|node [--app <app>]";
+    let args = ParsedArgs::parse(args, &["--app", "--lang"], &[], USAGE)?;
+    let [id] = args.positional.as_slice() else {
+        return Err(CliError::Usage(USAGE.to_string()));
+    };
+    let lang = args
+        .value("--lang")
+        .ok_or_else(|| CliError::Usage(USAGE.to_string()))?;
+    let lang = SnippetLang::from_str(lang).map_err(CliError::Argument)?;
+    let app_type = args.app_type()?;
+    let state = open_state()?;
+    let id = &resolve_id(&state, &app_type, id)?;
+
+    let snippet = ProviderService::snippet(&state, app_type, id, lang)?;
+    let human = snippet.trim_end().to_string();
+    Ok(CommandOutput::new(json!({ "content": snippet })).human(human))
+}
+
+fn provider_validate(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch provider validate [--app <app> | --all]";
+    let args = ParsedArgs::parse(args, &["--app"], &["--all"], USAGE)?;
+    if !args.positional.is_empty() || (args.has("--all") && args.has("--app")) {
+        return Err(CliError::Usage(USAGE.to_string()));
+    }
+    let app_type = if args.has("--all") {
+        None
+    } else {
+        Some(args.app_type()?)
+    };
+    let state = open_state()?;
+    let reports = ProviderService::validate_all(&state, app_type)?;
+
+    let mut failed = false;
+    let mut rows = Vec::new();
+    for report in &reports {
+        for issue in &report.issues {
+            failed |= issue.is_error();
+            let severity = match issue.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+            };
+            rows.push(vec![
+                report.app.clone(),
+                format!("{} ({})", report.name, report.id),
+                severity.to_string(),
+                issue.field.clone(),
+                issue.message.clone(),
+            ]);
+        }
+    }
+    let mut output = CommandOutput::new(&reports).table(
+        vec!["APP", "PROVIDER", "SEVERITY", "FIELD", "MESSAGE"],
+        rows,
+    );
+    if reports.is_empty() {
+        output = output.human("所有供应商均通过校验".to_string());
+    }
+    Ok(output.code(if failed {
+        cli_error::FAILURE
+    } else {
+        cli_error::SUCCESS
+    }))
+}
+
+fn tui(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch tui";
+    let args = ParsedArgs::parse(args, &[], &[], USAGE)?;
+    if !args.positional.is_empty() {
+        return Err(CliError::Usage(USAGE.to_string()));
+    }
+    {
+        use std::io::IsTerminal;
+        if !std::io::stdout().is_terminal() || !std::io::stdin().is_terminal() {
+            return Err(CliError::Failed(AppError::Message(
+                "终端界面需要在交互式终端中运行".to_string(),
+            )));
+        }
+    }
+    let state = open_state()?;
+    tui::run(state, runtime()?)?;
+    Ok(CommandOutput::new(Value::Null))
+}
+
+fn provider_diff(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch provider diff <id1> <id2> [--app <app>] [--show-secrets]
+       cc-switch provider diff <id> --live [--app <app>] [--show-secrets]";
+    let args = ParsedArgs::parse(args, &["--app"], &["--live", "--show-secrets"], USAGE)?;
+    let (id, target) = match (args.positional.as_slice(), args.has("--live")) {
+        ([id], true) => (id, DiffTarget::Live),
+        ([id, other], false) => (id, DiffTarget::Provider(other.clone())),
+        _ => return Err(CliError::Usage(USAGE.to_string())),
+    };
+    let app_type = args.app_type()?;
+    let state = open_state()?;
+    let id = &resolve_id(&state, &app_type, id)?;
+    let target = match target {
+        DiffTarget::Provider(other) => DiffTarget::Provider(resolve_id(&state, &app_type, &other)?),
+        target => target,
+    };
+    let diff = ProviderService::diff(&state, app_type, id, &target, args.has("--show-secrets"))?;
+
+    let color = color_stdout();
+    let mut human = vec![format!("--- {}\n+++ {}", diff.left, diff.right)];
+    if diff.changes.is_empty() {
+        human.push("没有差异".to_string());
+    }
+    human.extend(diff.changes.iter().map(|change| change_line(change, color)));
+    let show = |value: &Option<Value>| value.as_ref().map(Value::to_string).unwrap_or_default();
+    let rows = diff
+        .changes
+        .iter()
+        .map(|change| {
+            vec![
+                change.path.clone(),
+                show(&change.before),
+                show(&change.after),
+            ]
+        })
+        .collect();
+    Ok(CommandOutput::new(&diff)
+        .human(human.join("\n"))
+        .table(vec!["PATH", "LEFT", "RIGHT"], rows))
+}
+
+fn provider_dedupe(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch provider dedupe [--app <app>] [--dry-run]";
+    let args = ParsedArgs::parse(args, &["--app"], &["--dry-run"], USAGE)?;
+    if !args.positional.is_empty() {
+        return Err(CliError::Usage(USAGE.to_string()));
+    }
+    let app_type = args.app_type()?;
+    let dry_run = args.has("--dry-run");
+    let state = open_state()?;
+    let groups = ProviderService::find_duplicates(&state, app_type.clone())?;
+    if !dry_run {
+        ProviderService::merge_duplicates(&state, app_type, &groups)?;
+    }
+
+    let label = |p: &ProviderLabel| format!("{} ({})", p.name, p.id);
+    let mut human: Vec<String> = groups
+        .iter()
+        .map(|group| {
+            let mut lines = vec![format!(
+                "[{}] 保留 {}",
+                group.fingerprint,
+                label(&group.keep)
+            )];
+            lines.extend(group.duplicates.iter().map(|p| format!("  - {}", label(p))));
+            lines.join("\n")
+        })
+        .collect();
+    let merged: usize = groups.iter().map(|g| g.duplicates.len()).sum();
+    human.push(match (merged, dry_run) {
+        (0, _) => "没有重复的供应商".to_string(),
+        (n, true) => format!("将合并 {n} 个重复的供应商（--dry-run，未修改）"),
+        (n, false) => format!("已合并 {n} 个重复的供应商"),
+    });
+    let rows = groups
+        .iter()
+        .map(|group| {
+            vec![
+                group.fingerprint.clone(),
+                group.keep.id.clone(),
+                group
+                    .duplicates
+                    .iter()
+                    .map(|p| p.id.as_str())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ]
+        })
+        .collect();
+    Ok(
+        CommandOutput::new(json!({ "dryRun": dry_run, "groups": groups }))
+            .human(human.join("\n"))
+            .table(vec!["FINGERPRINT", "KEEP", "DUPLICATES"], rows),
+    )
+}
+
+fn provider_restore(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch provider restore <id> --to <revision|time> [--app <app>]";
+    let args = ParsedArgs::parse(args, &["--app", "--to"], &[], USAGE)?;
+    let ([id], Some(to)) = (args.positional.as_slice(), args.value("--to")) else {
+        return Err(CliError::Usage(USAGE.to_string()));
+    };
+    let target = to.parse::<RestoreTarget>().map_err(CliError::Argument)?;
+    let app_type = args.app_type()?;
+    let state = open_state()?;
+    let id = &resolve_id(&state, &app_type, id)?;
+
+    let result = ProviderService::restore_revision(&state, app_type, id, target)?;
+    let human = match result.revision {
+        Some(revision) => format!(
+            "已将 {id} 的配置恢复到 r{}（新修订 r{revision}）",
+            result.restored_revision
+        ),
+        None => format!("{id} 的配置与 r{} 相同，无需恢复", result.restored_revision),
+    };
+    Ok(CommandOutput::new(&result).human(human))
+}
+
+fn bench(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch bench [--app <app>] [--all]";
+    let args = ParsedArgs::parse(args, &["--app"], &["--all"], USAGE)?;
+    if !args.positional.is_empty() || (args.has("--all") && args.has("--app")) {
+        return Err(CliError::Usage(USAGE.to_string()));
+    }
+    let apps = if args.has("--all") {
+        vec![AppType::Claude, AppType::Codex, AppType::Gemini]
+    } else {
+        vec![args.app_type()?]
+    };
+    let state = open_state()?;
+    let runtime = runtime()?;
+
+    let mut results = Vec::new();
+    let mut rows = Vec::new();
+    for app_type in apps {
+        let providers = state.db.get_all_providers(app_type.as_str())?;
+        let ranked = runtime.block_on(BenchService::run(&state, app_type))?;
+        for (index, result) in ranked.iter().enumerate() {
+            let name = providers
+                .get(&result.provider_id)
+                .map(|p| p.name.as_str())
+                .unwrap_or_default();
+            let outcome = match &result.error {
+                None => "ok".to_string(),
+                Some(error) => error.chars().take(60).collect(),
+            };
+            rows.push(vec![
+                (index + 1).to_string(),
+                result.app_type.clone(),
+                format!("{name} ({})", result.provider_id),
+                result
+                    .ttfb_ms
+                    .map(|ms| format!("{ms}ms"))
+                    .unwrap_or_else(|| "-".to_string()),
+                result
+                    .tokens_per_sec
+                    .map(|rate| format!("{rate:.1}"))
+                    .unwrap_or_else(|| "-".to_string()),
+                outcome,
+            ]);
+        }
+        results.extend(ranked);
+    }
+    Ok(CommandOutput::new(&results).table(
+        vec!["#", "APP", "PROVIDER", "TTFB", "TOK/S", "RESULT"],
+        rows,
+    ))
+}
+
+fn switch(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch switch [<id>] [--app <app>] [--best-endpoint] | cc-switch switch --fastest|--tag <tag>|--category <cat> [--app <app>] [--best-endpoint]
+       cc-switch switch <id> --for <duration> [--app <app>]
+       cc-switch switch <id> --temporary [--for <duration>] [--app <app>] [-- <command> [args...]]
+       cc-switch switch --revert [--app <app>]";
+    let (args, command) = match args.iter().position(|arg| arg == "--") {
+        Some(index) => (&args[..index], &args[index + 1..]),
+        None => (args, &[][..]),
+    };
+    let args = ParsedArgs::parse(
+        args,
+        &["--app", "--for", "--wait", "--tag", "--category"],
+        &["--fastest", "--best-endpoint", "--temporary", "--revert"],
+        USAGE,
+    )?;
+    if args.has("--revert") {
+        return switch_revert(&args, USAGE);
+    }
+    let tag = args.value("--tag");
+    let category = args.value("--category");
+    let selectors = [args.has("--fastest"), tag.is_some(), category.is_some()]
+        .into_iter()
+        .filter(|selected| *selected)
+        .count();
+    let (id, pick) = match (args.positional.as_slice(), selectors) {
+        ([id], 0) => (Some(id.clone()), false),
+        ([], 1) => (None, false),
+        ([], 0) if std::io::IsTerminal::is_terminal(&std::io::stdin()) => (None, true),
+        _ => return Err(CliError::Usage(USAGE.to_string())),
+    };
+    let duration = args
+        .value("--for")
+        .map(TemporarySwitchService::parse_duration)
+        .transpose()
+        .map_err(CliError::Argument)?;
+    // `--for` 本身即表示限时切换
+    let temporary = args.has("--temporary") || duration.is_some();
+    if args.has("--wait")
+        || (temporary && duration.is_none() && command.is_empty())
+        || (!temporary && !command.is_empty())
+    {
+        return Err(CliError::Usage(USAGE.to_string()));
+    }
+    let app = args.app_definition()?;
+    let Some(app_type) = app.app_type() else {
+        // 注册表应用只支持按 ID 直接切换
+        let Some(id) = id.filter(|_| !temporary && !args.has("--best-endpoint")) else {
+            return Err(unsupported_app(&app));
+        };
+        return switch_registered(&open_state()?, &app, &id);
+    };
+    let state = open_state()?;
+
+    let provider = match id {
+        Some(id) => state
+            .db
+            .get_provider_by_id(&resolve_id(&state, &app_type, &id)?, app_type.as_str())?
+            .ok_or_else(|| AppError::provider_not_found(id.as_str(), app_type.as_str()))?,
+        None if pick => pick_provider(&state, &app_type)?,
+        None => match (tag, category) {
+            (Some(tag), _) => ProviderService::first_by_tag(&state, &app_type, tag)?,
+            (_, Some(category)) => {
+                ProviderService::category_default_provider(&state, &app_type, category)?
+            }
+            _ => ProviderService::fastest(&state, app_type.clone())?.ok_or_else(|| {
+                AppError::Message(
+                    "最近 24 小时内没有成功的基准测试结果，请先运行 `cc-switch bench`".to_string(),
+                )
+            })?,
+        },
+    };
+    let endpoint = if args.has("--best-endpoint") {
+        let timings = runtime()?.block_on(ProviderService::test_endpoints(
+            &state,
+            app_type.clone(),
+            &provider.id,
+        ))?;
+        let best = timings
+            .into_iter()
+            .find(|timing| timing.reachable())
+            .ok_or_else(|| AppError::Message(format!("供应商 {} 没有可达的端点", provider.id)))?;
+        ProviderService::set_active_endpoint(
+            &state,
+            app_type.clone(),
+            &provider.id,
+            Some(best.url.clone()),
+        )?;
+        Some(best)
+    } else {
+        None
+    };
+    if temporary {
+        return switch_temporary(state, app_type, &provider, duration, command);
+    }
+    let login_warning = ProviderService::codex_login_warning(&app_type, &provider);
+    let cwd = std::env::current_dir()
+        .ok()
+        .map(|dir| dir.to_string_lossy().into_owned());
+    let files =
+        ProviderService::switch_from(&state, app_type.clone(), &provider.id, cwd.as_deref())?;
+
+    let warning = login_warning
+        .or_else(|| app_version_warning(&app_type, &provider))
+        .or_else(|| ProviderService::limit_warning(&state, &app_type, &provider));
+    let mut human = format!("{}: {} ({})", app_type.as_str(), provider.name, provider.id);
+    if let Some(endpoint) = &endpoint {
+        human.push_str(&format!(
+            "\n  端点: {} ({}ms)",
+            endpoint.url,
+            endpoint.head_ms.unwrap_or_default()
+        ));
+    }
+    for file in &files {
+        human.push_str(&format!(
+            "\n  文件: {} ({})",
+            file.path.display(),
+            file_status_label(file.status)
+        ));
+    }
+    if let Some(warning) = &warning {
+        human.push_str(&format!("\n  警告: {warning}"));
+    }
+    Ok(CommandOutput::new(json!({
+        "app": app_type.as_str(),
+        "current": provider.id,
+        "name": provider.name,
+        "endpoint": endpoint.map(|timing| timing.url),
+        "files": files,
+        "warning": warning,
+    }))
+    .human(human))
+}
+
+/// 切换注册表应用的供应商：写入 live 配置（以及 `json_patch` 指定的文件）
+fn switch_registered(
+    state: &AppState,
+    app: &AppDefinition,
+    id: &str,
+) -> Result<CommandOutput, CliError> {
+    let id = RegisteredAppService::resolve_id(state, &app.id, id)?;
+    RegisteredAppService::switch(state, &app.id, &id)?;
+    let provider = RegisteredAppService::get(state, &app.id, &id)?;
+
+    let file = app.config_file();
+    Ok(CommandOutput::new(json!({
+        "app": app.id,
+        "current": provider.id,
+        "name": provider.name,
+        "file": file,
+    }))
+    .human(format!(
+        "{}: {} ({})\n  文件: {}",
+        app.id,
+        provider.name,
+        provider.id,
+        file.display()
+    )))
+}
+
+fn file_status_label(status: FileWriteStatus) -> &'static str {
+    match status {
+        FileWriteStatus::Written => "已写入",
+        FileWriteStatus::Unchanged => "未变化",
+        FileWriteStatus::RolledBack => "已回滚",
+        FileWriteStatus::Failed => "失败",
+        FileWriteStatus::Skipped => "未写入",
+    }
+}
+
+fn export(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch provider export [--format ccr|opencode|env] [id... | --ids <id,id...> | --select] [--app <app>] [--out <file>]";
+    let args = ParsedArgs::parse(
+        args,
+        &["--app", "--format", "--out", "--ids"],
+        &["--select"],
+        USAGE,
+    )?;
+    let format = args
+        .value("--format")
+        .map(ExportFormat::from_str)
+        .transpose()
+        .map_err(CliError::Argument)?;
+    let app_type = args.app_type()?;
+    let state = open_state()?;
+    let mut ids = args.positional.clone();
+    if let Some(list) = args.value("--ids") {
+        ids.extend(
+            list.split(',')
+                .map(str::trim)
+                .filter(|id| !id.is_empty())
+                .map(str::to_string),
+        );
+    }
+    let ids = if args.has("--select") {
+        if !ids.is_empty() {
+            return Err(CliError::Usage(USAGE.to_string()));
+        }
+        pick_providers(&state, &app_type, "导出")?
+    } else {
+        ids.iter()
+            .map(|id| resolve_id(&state, &app_type, id))
+            .collect::<Result<Vec<_>, _>>()?
+    };
+    let text = match format {
+        Some(format) => ProviderService::export_as(&state, app_type, &ids, format)?,
+        None => {
+            let bundle = ProviderService::export_bundle(&state, app_type, &ids)?;
+            let value = serde_json::to_value(&bundle)
+                .map_err(|source| AppError::JsonSerialize { source })?;
+            serde_json::to_string_pretty(&crate::database::sort_json_keys(&value))
+                .map_err(|source| AppError::JsonSerialize { source })?
+        }
+    };
+    let text = format!("{}\n", text.trim_end());
+
+    match args.value("--out") {
+        Some(path) => {
+            crate::config::atomic_write(std::path::Path::new(path), text.as_bytes())?;
+            Ok(CommandOutput::new(json!({ "path": path })).human(format!("已导出到 {path}")))
+        }
+        None => {
+            let human = text.trim_end().to_string();
+            Ok(CommandOutput::new(json!({ "content": text })).human(human))
+        }
+    }
+}
+
+fn import(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch provider import <file> [--app <app>]";
+    let args = ParsedArgs::parse(args, &["--app"], &[], USAGE)?;
+    let [path] = args.positional.as_slice() else {
+        return Err(CliError::Usage(USAGE.to_string()));
+    };
+    // 只有旧版的供应商映射需要 --app，其他格式以文件中的 app 为准
+    let app_type = match args.value("--app") {
+        Some(_) => Some(args.app_type()?),
+        None => None,
+    };
+    let value: Value = crate::config::read_json_file(std::path::Path::new(path))?;
+    let state = open_state()?;
+    let result = ProviderService::import_bundle(&state, &value, app_type)?;
+
+    let mut human = vec![format!(
+        "已导入 {} 个供应商到 {}",
+        result.imported.len(),
+        result.app
+    )];
+    if !result.skipped.is_empty() {
+        human.push(format!("已存在，跳过: {}", result.skipped.join(", ")));
+    }
+    if !result.disabled_scripts.is_empty() {
+        human.push(format!(
+            "已停用外部用量脚本（需要时请检查后手动启用）: {}",
+            result.disabled_scripts.join(", ")
+        ));
+    }
+    if !result.dropped_hooks.is_empty() {
+        human.push(format!(
+            "已移除导入的切换钩子（需要时请检查后用 `cc-switch hook add` 重新添加）: {}",
+            result.dropped_hooks.join(", ")
+        ));
+    }
+    Ok(CommandOutput::new(json!(result)).human(human.join("\n")))
+}
+
+fn schema(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch schema provider|export-bundle";
+    let args = ParsedArgs::parse(args, &[], &[], USAGE)?;
+    let [kind] = args.positional.as_slice() else {
+        return Err(CliError::Usage(USAGE.to_string()));
+    };
+    let schema = SchemaKind::from_str(kind)
+        .map_err(CliError::Argument)?
+        .schema();
+    let human = serde_json::to_string_pretty(&schema)
+        .map_err(|source| AppError::JsonSerialize { source })?;
+    Ok(CommandOutput::new(schema).human(human))
+}
+
+fn bundle(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str =
+        "用法: cc-switch bundle export [--include <sections>] [--exclude <sections>] [--out <file>]
+       cc-switch bundle manifest <file>
+       cc-switch bundle import <file> [--include <sections>] [--exclude <sections>]";
+    let args = ParsedArgs::parse(args, &["--include", "--exclude", "--out"], &[], USAGE)?;
+    let sections = || {
+        BundleSection::select(args.value("--include"), args.value("--exclude"))
+            .map_err(CliError::Argument)
+    };
+    let read_bundle = |path: &str| -> Result<ConfigBundle, CliError> {
+        let value: Value = crate::config::read_json_file(std::path::Path::new(path))?;
+        Ok(BundleService::parse(&value)?)
+    };
+    match args.positional.as_slice() {
+        [command] if command == "export" => {
+            let sections = sections()?;
+            let state = open_state()?;
+            let bundle = BundleService::export(&state, &sections)?;
+            let value = serde_json::to_value(&bundle)
+                .map_err(|source| AppError::JsonSerialize { source })?;
+            let text = serde_json::to_string_pretty(&crate::database::sort_json_keys(&value))
+                .map_err(|source| AppError::JsonSerialize { source })?;
+            let text = format!("{text}\n");
+            match args.value("--out") {
+                Some(path) => {
+                    crate::config::atomic_write(std::path::Path::new(path), text.as_bytes())?;
+                    let names: Vec<&str> = sections.iter().map(BundleSection::as_str).collect();
+                    let human = format!("已导出 {} 到 {path}", names.join(", "));
+                    Ok(
+                        CommandOutput::new(json!({ "path": path, "sections": sections }))
+                            .human(human),
+                    )
+                }
+                None => {
+                    let human = text.trim_end().to_string();
+                    Ok(CommandOutput::new(json!({ "content": text })).human(human))
+                }
+            }
+        }
+        [command, path] if command == "manifest" => {
+            let manifest = read_bundle(path)?.manifest();
+            let mut human = vec![format!(
+                "配置包 v{}（导出于 {}，cc-switch {}）",
+                manifest.version,
+                manifest.exported_at.as_deref().unwrap_or("未知时间"),
+                manifest.tool_version.as_deref().unwrap_or("未知版本")
+            )];
+            for section in &manifest.sections {
+                human.push(format!("{} ({}):", section.section.as_str(), section.count));
+                human.extend(section.items.iter().map(|item| format!("  {item}")));
+            }
+            Ok(CommandOutput::new(&manifest).human(human.join("\n")))
+        }
+        [command, path] if command == "import" => {
+            let sections = sections()?;
+            let bundle = read_bundle(path)?;
+            let _lock = OperationLock::acquire()?;
+            let state = open_state()?;
+            let result = BundleService::import(&state, bundle, &sections)?;
+
+            let mut human: Vec<String> = result
+                .providers
+                .iter()
+                .map(|import| {
+                    let mut line =
+                        format!("已导入 {} 个供应商到 {}", import.imported.len(), import.app);
+                    if !import.skipped.is_empty() {
+                        line.push_str(&format!("（已存在，跳过: {}）", import.skipped.join(", ")));
+                    }
+                    if !import.disabled_scripts.is_empty() {
+                        line.push_str(&format!(
+                            "（已停用外部用量脚本: {}）",
+                            import.disabled_scripts.join(", ")
+                        ));
+                    }
+                    if !import.dropped_hooks.is_empty() {
+                        line.push_str(&format!(
+                            "（已移除切换钩子: {}）",
+                            import.dropped_hooks.join(", ")
+                        ));
+                    }
+                    line
+                })
+                .collect();
+            if sections.contains(&BundleSection::Settings) {
+                human.push(format!("已写入 {} 项设置", result.settings));
+            }
+            if sections.contains(&BundleSection::Tags) {
+                human.push(format!("已添加 {} 个标签", result.tags));
+            }
+            if sections.contains(&BundleSection::Mcp) {
+                let mut line = format!("已导入 {} 个 MCP 服务器", result.mcp_imported.len());
+                if !result.mcp_skipped.is_empty() {
+                    line.push_str(&format!(
+                        "（已存在，跳过: {}）",
+                        result.mcp_skipped.join(", ")
+                    ));
+                }
+                human.push(line);
+            }
+            Ok(CommandOutput::new(&result).human(human.join("\n")))
+        }
+        _ => Err(CliError::Usage(USAGE.to_string())),
+    }
+}
+
+fn endpoint(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch endpoint test|list <provider-id> [--app <app>]
+       cc-switch endpoint add|remove <provider-id> <url> [--app <app>]
+       cc-switch endpoint use <provider-id> [<url> | --reset] [--app <app>]";
+    let args = ParsedArgs::parse(args, &["--app"], &["--reset"], USAGE)?;
+    let (command, id, url) = match args.positional.as_slice() {
+        [command, id] => (command.as_str(), id.clone(), None),
+        [command, id, url] => (command.as_str(), id.clone(), Some(url.clone())),
+        _ => return Err(CliError::Usage(USAGE.to_string())),
+    };
+    let app_type = args.app_type()?;
+    let state = open_state()?;
+    let id = resolve_id(&state, &app_type, &id)?;
+    let provider = state
+        .db
+        .get_provider_by_id(&id, app_type.as_str())?
+        .ok_or_else(|| AppError::provider_not_found(id.as_str(), app_type.as_str()))?;
+
+    match (command, url) {
+        ("test", None) => endpoint_test(&state, app_type, &id),
+        ("list", None) => {
+            let active = provider.meta.and_then(|meta| meta.active_endpoint);
+            let endpoints = ProviderService::get_custom_endpoints(&state, app_type, &id)?;
+            let rows = endpoints
+                .iter()
+                .map(|endpoint| {
+                    vec![
+                        if active.as_deref() == Some(endpoint.url.as_str()) {
+                            "*".to_string()
+                        } else {
+                            String::new()
+                        },
+                        endpoint.url.clone(),
+                        local_time(endpoint.added_at),
+                        endpoint
+                            .last_used
+                            .map(local_time)
+                            .unwrap_or_else(|| "-".to_string()),
+                        endpoint
+                            .latency_ms
+                            .map(|ms| format!("{ms}ms"))
+                            .unwrap_or_else(|| "-".to_string()),
+                    ]
+                })
+                .collect();
+            Ok(CommandOutput::new(json!({
+                "provider": id,
+                "activeEndpoint": active,
+                "endpoints": endpoints,
+            }))
+            .table(vec!["", "URL", "ADDED", "LAST USED", "LATENCY"], rows))
+        }
+        ("add", Some(url)) => {
+            ProviderService::add_custom_endpoint(&state, app_type, &id, url.clone())
+                .map_err(CliError::Argument)?;
+            Ok(CommandOutput::new(json!({ "provider": id, "added": url }))
+                .human(format!("已添加端点: {url}")))
+        }
+        ("remove", Some(url)) => {
+            let normalized = url.trim().trim_end_matches('/');
+            let endpoints = ProviderService::get_custom_endpoints(&state, app_type.clone(), &id)?;
+            if !endpoints.iter().any(|endpoint| endpoint.url == normalized) {
+                return Err(CliError::Argument(AppError::Message(format!(
+                    "供应商 {id} 没有端点 {normalized}"
+                ))));
+            }
+            ProviderService::remove_custom_endpoint(&state, app_type, &id, url.clone())?;
+            Ok(
+                CommandOutput::new(json!({ "provider": id, "removed": normalized }))
+                    .human(format!("已移除端点: {normalized}")),
+            )
+        }
+        ("use", url) => {
+            let url = match url {
+                Some(_) if args.has("--reset") => {
+                    return Err(CliError::Usage(USAGE.to_string()));
+                }
+                Some(url) => Some(url),
+                None if args.has("--reset") => None,
+                None => pick_endpoint(&state, &app_type, &provider)?,
+            };
+            let updated = ProviderService::set_active_endpoint(&state, app_type, &id, url)?;
+            let active = updated.meta.and_then(|meta| meta.active_endpoint);
+            let human = match &active {
+                Some(url) => format!("{}: 使用端点 {url}", updated.name),
+                None => format!("{}: 使用基础地址", updated.name),
+            };
+            Ok(
+                CommandOutput::new(json!({ "provider": id, "activeEndpoint": active }))
+                    .human(human),
+            )
+        }
+        _ => Err(CliError::Usage(USAGE.to_string())),
+    }
+}
+
+fn key(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch key list <provider-id> [--app <app>]
+       cc-switch key add <provider-id> <key> [--label <text>] [--weight <n>] [--app <app>]
+       cc-switch key remove <provider-id> <key|#id> [--app <app>]
+       cc-switch key strategy <provider-id> [round-robin|lru|weighted] [--rotate switch|proxy] [--app <app>]";
+    let args = ParsedArgs::parse(
+        args,
+        &["--app", "--label", "--weight", "--rotate"],
+        &[],
+        USAGE,
+    )?;
+    let (command, id, value) = match args.positional.as_slice() {
+        [command, id] => (command.as_str(), id.clone(), None),
+        [command, id, value] => (command.as_str(), id.clone(), Some(value.clone())),
+        _ => return Err(CliError::Usage(USAGE.to_string())),
+    };
+    let app_type = args.app_type()?;
+    let state = open_state()?;
+    let id = resolve_id(&state, &app_type, &id)?;
+
+    match (command, value) {
+        ("list", None) => {
+            let keys = ProviderService::list_keys(&state, app_type, &id)?;
+            let rows = keys
+                .iter()
+                .map(|key| {
+                    vec![
+                        format!("#{}", key.id),
+                        mask_secret(&key.api_key),
+                        key.label.clone().unwrap_or_default(),
+                        key.weight.to_string(),
+                        key.use_count.to_string(),
+                        key.last_used
+                            .map(local_time)
+                            .unwrap_or_else(|| "-".to_string()),
+                    ]
+                })
+                .collect();
+            let masked: Vec<_> = keys
+                .into_iter()
+                .map(|key| ProviderKey {
+                    api_key: mask_secret(&key.api_key),
+                    ..key
+                })
+                .collect();
+            Ok(
+                CommandOutput::new(json!({ "provider": id, "keys": masked })).table(
+                    vec!["ID", "KEY", "LABEL", "WEIGHT", "USES", "LAST USED"],
+                    rows,
+                ),
+            )
+        }
+        ("add", Some(api_key)) => {
+            let weight = match args.value("--weight") {
+                Some(weight) => weight
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|w| *w > 0)
+                    .ok_or_else(|| CliError::Usage(USAGE.to_string()))?,
+                None => 1,
+            };
+            let added = ProviderService::add_key(
+                &state,
+                app_type,
+                &id,
+                &api_key,
+                args.value("--label"),
+                weight,
+            )
+            .map_err(CliError::Argument)?;
+            let masked = mask_secret(&api_key);
+            let human = if added {
+                format!("已添加 Key: {masked}")
+            } else {
+                format!("Key 已在池中，已更新标签与权重: {masked}")
+            };
+            Ok(CommandOutput::new(json!({ "provider": id, "added": added })).human(human))
+        }
+        ("remove", Some(key)) => {
+            let target = key.strip_prefix('#').unwrap_or(&key);
+            if !ProviderService::remove_key(&state, app_type, &id, target)? {
+                return Err(CliError::Argument(AppError::Message(format!(
+                    "供应商 {id} 的 Key 池中没有 {}",
+                    mask_secret(&key)
+                ))));
+            }
+            Ok(
+                CommandOutput::new(json!({ "provider": id, "removed": true }))
+                    .human("已移除 Key".to_string()),
+            )
+        }
+        ("strategy", strategy) => {
+            let strategy = strategy
+                .map(|s| KeyStrategy::from_str(&s))
+                .transpose()
+                .map_err(CliError::Argument)?;
+            let rotation = args
+                .value("--rotate")
+                .map(KeyRotation::from_str)
+                .transpose()
+                .map_err(CliError::Argument)?;
+            let provider =
+                ProviderService::set_key_rotation(&state, app_type, &id, strategy, rotation)?;
+            let meta = provider.meta.unwrap_or_default();
+            let strategy = meta.key_strategy.unwrap_or_default();
+            let rotation = meta.key_rotation.unwrap_or_default();
+            let human = format!(
+                "轮换策略: {}，轮换时机: {}",
+                serde_json::to_value(strategy)
+                    .ok()
+                    .and_then(|v| v.as_str().map(str::to_string))
+                    .unwrap_or_default(),
+                match rotation {
+                    KeyRotation::Switch => "切换时",
+                    KeyRotation::Proxy => "代理按请求",
+                }
+            );
+            Ok(CommandOutput::new(json!({
+                "provider": id,
+                "strategy": strategy,
+                "rotation": rotation,
+            }))
+            .human(human))
+        }
+        _ => Err(CliError::Usage(USAGE.to_string())),
+    }
+}
+
+fn keys(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch keys revoke <key|provider-id> [--archive]";
+    let args = ParsedArgs::parse(args, &[], &["--archive"], USAGE)?;
+    let [action, target] = args.positional.as_slice() else {
+        return Err(CliError::Usage(USAGE.to_string()));
+    };
+    if action != "revoke" {
+        return Err(CliError::Usage(USAGE.to_string()));
+    }
+    let state = open_state()?;
+    let revoked = ProviderService::revoke_key(&state, target, args.has("--archive"))?;
+
+    let mut output = CommandOutput::new(&revoked).table(
+        vec!["APP", "PROVIDER", "CURRENT", "FIELDS"],
+        reference_rows(&revoked),
+    );
+    if revoked.is_empty() {
+        output = output.human("没有供应商使用该密钥".to_string());
+    }
+    Ok(output)
+}
+
+fn vendor(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch vendor list
+       cc-switch vendor add <id> [--name <name>] [--url <url>] [--notes <text>]
+       cc-switch vendor edit <id> [--name <name>] [--url <url>] [--notes <text>]
+       cc-switch vendor remove <id> [--archive|--cascade]
+       cc-switch vendor assign <provider-id> <vendor-id|--none> [--app <app>]";
+    let args = ParsedArgs::parse(
+        args,
+        &["--app", "--name", "--url", "--notes"],
+        &["--archive", "--cascade", "--none"],
+        USAGE,
+    )?;
+    let usage = || CliError::Usage(USAGE.to_string());
+    let (command, rest) = args.positional.split_first().ok_or_else(usage)?;
+    let state = open_state()?;
+
+    match (command.as_str(), rest) {
+        ("list", []) => {
+            let vendors = VendorService::list(&state)?;
+            let rows = vendors
+                .iter()
+                .map(|v| {
+                    let providers: Vec<_> = v
+                        .providers
+                        .iter()
+                        .map(|p| format!("{}:{}", p.app, p.id))
+                        .collect();
+                    vec![
+                        v.vendor.id.clone(),
+                        v.vendor.name.clone(),
+                        v.vendor.website_url.clone().unwrap_or_default(),
+                        providers.join(", "),
+                    ]
+                })
+                .collect();
+            Ok(CommandOutput::new(&vendors).table(vec!["ID", "NAME", "URL", "PROVIDERS"], rows))
+        }
+        ("add", [id]) => {
+            let vendor = VendorService::add(
+                &state,
+                id,
+                args.value("--name"),
+                args.value("--url"),
+                args.value("--notes"),
+            )
+            .map_err(CliError::Argument)?;
+            let human = format!("已添加 vendor: {} ({})", vendor.name, vendor.id);
+            Ok(CommandOutput::new(&vendor).human(human))
+        }
+        ("edit", [id]) => {
+            let vendor = VendorService::update(
+                &state,
+                id,
+                args.value("--name"),
+                args.value("--url"),
+                args.value("--notes"),
+            )
+            .map_err(CliError::Argument)?;
+            let human = format!("已更新 vendor: {} ({})", vendor.name, vendor.id);
+            Ok(CommandOutput::new(&vendor).human(human))
+        }
+        ("remove", [id]) => {
+            let removal = match (args.has("--archive"), args.has("--cascade")) {
+                (false, false) => VendorRemoval::Detach,
+                (true, false) => VendorRemoval::Archive,
+                (false, true) => VendorRemoval::Cascade,
+                (true, true) => return Err(usage()),
+            };
+            let affected = VendorService::remove(&state, id, removal)?;
+            let human = match removal {
+                VendorRemoval::Detach => {
+                    format!("已删除 vendor {id}，{affected} 个供应商已解除关联")
+                }
+                VendorRemoval::Archive => format!("已删除 vendor {id}，已归档 {affected} 个供应商"),
+                VendorRemoval::Cascade => format!("已删除 vendor {id} 及其 {affected} 个供应商"),
+            };
+            Ok(CommandOutput::new(json!({ "id": id, "providers": affected })).human(human))
+        }
+        ("assign", [provider_id, vendor_id]) if !args.has("--none") => {
+            let app_type = args.app_type()?;
+            let provider_id = &resolve_id(&state, &app_type, provider_id)?;
+            VendorService::assign(&state, app_type, provider_id, Some(vendor_id))?;
+            Ok(
+                CommandOutput::new(json!({ "provider": provider_id, "vendorId": vendor_id }))
+                    .human(format!("{provider_id} 已归到 vendor {vendor_id}")),
+            )
+        }
+        ("assign", [provider_id]) if args.has("--none") => {
+            let app_type = args.app_type()?;
+            let provider_id = &resolve_id(&state, &app_type, provider_id)?;
+            VendorService::assign(&state, app_type, provider_id, None)?;
+            Ok(
+                CommandOutput::new(json!({ "provider": provider_id, "vendorId": null }))
+                    .human(format!("{provider_id} 已解除 vendor 关联")),
+            )
+        }
+        _ => Err(usage()),
+    }
+}
+
+fn hook(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch hook list [--provider <id>] [--app <app>]
+       cc-switch hook add pre|post --command <cmd> | --webhook <url> [--provider <id>] [--app <app>]
+       cc-switch hook remove pre|post <index> [--provider <id>] [--app <app>]";
+    let args = ParsedArgs::parse(
+        args,
+        &["--app", "--provider", "--command", "--webhook"],
+        &[],
+        USAGE,
+    )?;
+    let stage = |name: &str| match name {
+        "pre" | "pre-switch" => Ok(HookStage::PreSwitch),
+        "post" | "post-switch" => Ok(HookStage::PostSwitch),
+        _ => Err(CliError::Usage(USAGE.to_string())),
+    };
+    let app_type = args.app_type()?;
+    let state = open_state()?;
+    let provider = args
+        .value("--provider")
+        .map(|id| resolve_id(&state, &app_type, id))
+        .transpose()?;
+    let provider = provider.as_deref();
+    let mut hooks = ProviderService::get_hooks(&state, app_type.clone(), provider)?;
+    let scope = provider.unwrap_or("全局");
+
+    let describe = |hook: &SwitchHook| match hook {
+        SwitchHook::Command { command } => format!("command  {command}"),
+        SwitchHook::Webhook { url } => format!("webhook  {url}"),
+    };
+    fn stage_hooks(hooks: &mut SwitchHooks, stage: HookStage) -> &mut Vec<SwitchHook> {
+        match stage {
+            HookStage::PreSwitch => &mut hooks.pre_switch,
+            HookStage::PostSwitch => &mut hooks.post_switch,
+        }
+    }
+
+    match args.positional.as_slice() {
+        [command] if command == "list" => {
+            let rows = [
+                (HookStage::PreSwitch, &hooks.pre_switch),
+                (HookStage::PostSwitch, &hooks.post_switch),
+            ]
+            .into_iter()
+            .flat_map(|(stage, list)| {
+                list.iter().enumerate().map(move |(index, hook)| {
+                    vec![
+                        stage.as_str().to_string(),
+                        (index + 1).to_string(),
+                        describe(hook),
+                    ]
+                })
+            })
+            .collect();
+            Ok(CommandOutput::new(&hooks).table(vec!["STAGE", "#", "HOOK"], rows))
+        }
+        [command, name] if command == "add" => {
+            let hook = match (args.value("--command"), args.value("--webhook")) {
+                (Some(command), None) if !command.trim().is_empty() => SwitchHook::Command {
+                    command: command.trim().to_string(),
+                },
+                (None, Some(url)) if url.starts_with("http://") || url.starts_with("https://") => {
+                    SwitchHook::Webhook {
+                        url: url.trim().to_string(),
+                    }
+                }
+                _ => return Err(CliError::Usage(USAGE.to_string())),
+            };
+            let stage = stage(name)?;
+            let human = format!(
+                "已添加 {scope} {} 钩子: {}",
+                stage.as_str(),
+                describe(&hook)
+            );
+            stage_hooks(&mut hooks, stage).push(hook);
+            ProviderService::set_hooks(&state, app_type, provider, hooks.clone())?;
+            Ok(CommandOutput::new(&hooks).human(human))
+        }
+        [command, name, index] if command == "remove" => {
+            let stage = stage(name)?;
+            let list = stage_hooks(&mut hooks, stage);
+            let index = index
+                .parse::<usize>()
+                .ok()
+                .filter(|i| (1..=list.len()).contains(i))
+                .ok_or_else(|| {
+                    CliError::Argument(AppError::InvalidInput(format!(
+                        "{scope} 没有第 {index} 个 {} 钩子",
+                        stage.as_str()
+                    )))
+                })?;
+            let removed = list.remove(index - 1);
+            ProviderService::set_hooks(&state, app_type, provider, hooks.clone())?;
+            Ok(CommandOutput::new(&hooks).human(format!(
+                "已移除 {scope} {} 钩子: {}",
+                stage.as_str(),
+                describe(&removed)
+            )))
+        }
+        _ => Err(CliError::Usage(USAGE.to_string())),
+    }
+}
+
+fn endpoint_test(state: &AppState, app_type: AppType, id: &str) -> Result<CommandOutput, CliError> {
+    let timings = runtime()?.block_on(ProviderService::test_endpoints(state, app_type, id))?;
+
+    let ms = |value: Option<u64>| {
+        value
+            .map(|ms| format!("{ms}ms"))
+            .unwrap_or_else(|| "-".to_string())
+    };
+    let rows = timings
+        .iter()
+        .map(|timing| {
+            vec![
+                timing.url.clone(),
+                ms(timing.connect_ms),
+                ms(timing.head_ms),
+                match (&timing.error, timing.status) {
+                    (Some(error), _) => error.chars().take(60).collect(),
+                    (None, Some(status)) => status.to_string(),
+                    (None, None) => "-".to_string(),
+                },
+            ]
+        })
+        .collect();
+    Ok(CommandOutput::new(&timings).table(vec!["ENDPOINT", "TCP", "HEAD", "STATUS"], rows))
+}
+
+/// 在终端中列出供应商（置顶的在最前，序号与 `cc-switch 1`..`9` 的槽位一致），读取用户选择的序号
+fn pick_provider(state: &AppState, app_type: &AppType) -> Result<Provider, CliError> {
+    use std::io::{BufRead, Write};
+
+    let (mut providers, pinned) = ProviderService::list_pinned_first(state, app_type.clone())?;
+    if providers.is_empty() {
+        return Err(CliError::Failed(AppError::Message(format!(
+            "{} 还没有供应商",
+            app_type.as_str()
+        ))));
+    }
+    let current = ProviderService::current(state, app_type.clone())?;
+    let mark = |provider: &Provider| if provider.id == current { "*" } else { " " };
+
+    let mut stderr = std::io::stderr();
+    for (index, provider) in providers.iter().enumerate() {
+        if index == 0 && pinned > 0 {
+            let _ = writeln!(stderr, "置顶:");
+        }
+        if index == pinned && pinned > 0 {
+            let _ = writeln!(stderr, "其他:");
+        }
+        let _ = writeln!(
+            stderr,
+            "{} {}) {} ({})",
+            mark(provider),
+            index + 1,
+            provider.name,
+            provider.id
+        );
+    }
+    let _ = write!(stderr, "选择供应商 [1-{}]: ", providers.len());
+    let _ = stderr.flush();
+
+    let mut line = String::new();
+    std::io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .map_err(|e| AppError::Message(format!("读取输入失败: {e}")))?;
+    match line.trim().parse::<usize>() {
+        Ok(n) if n >= 1 && n <= providers.len() => Ok(providers.swap_remove(n - 1)),
+        _ => Err(CliError::Usage(format!("无效的选择: {}", line.trim()))),
+    }
+}
+
+/// 在终端中列出供应商并多选（如 `1,3,5-7`，`all` 为全部），返回选中供应商的 ID（按列表顺序）
+fn pick_providers(
+    state: &AppState,
+    app_type: &AppType,
+    action: &str,
+) -> Result<Vec<String>, CliError> {
+    use std::io::{BufRead, Write};
+
+    let (providers, _) = ProviderService::list_pinned_first(state, app_type.clone())?;
+    if providers.is_empty() {
+        return Err(CliError::Failed(AppError::Message(format!(
+            "{} 还没有供应商",
+            app_type.as_str()
+        ))));
+    }
+
+    let mut stderr = std::io::stderr();
+    for (index, provider) in providers.iter().enumerate() {
+        let _ = writeln!(
+            stderr,
+            "  {}) {} ({})",
+            index + 1,
+            provider.name,
+            provider.id
+        );
+    }
+    let _ = write!(stderr, "选择要{action}的供应商 [如 1,3,5-7 或 all]: ");
+    let _ = stderr.flush();
+
+    let mut line = String::new();
+    std::io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .map_err(|e| AppError::Message(format!("读取输入失败: {e}")))?;
+    let invalid = || CliError::Usage(format!("无效的选择: {}", line.trim()));
+    let mut selected = vec![false; providers.len()];
+    if line.trim() == "all" {
+        selected.fill(true);
+    }
+    for part in line
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+    {
+        if part == "all" {
+            continue;
+        }
+        let (start, end) = part.split_once('-').unwrap_or((part, part));
+        let (Ok(start), Ok(end)) = (start.trim().parse::<usize>(), end.trim().parse::<usize>())
+        else {
+            return Err(invalid());
+        };
+        if start == 0 || start > end || end > providers.len() {
+            return Err(invalid());
+        }
+        selected[start - 1..end].fill(true);
+    }
+    let ids: Vec<String> = providers
+        .into_iter()
+        .zip(selected)
+        .filter(|(_, selected)| *selected)
+        .map(|(provider, _)| provider.id)
+        .collect();
+    if ids.is_empty() {
+        return Err(invalid());
+    }
+    Ok(ids)
+}
+
+/// `cc-switch <1-9> [--app <app>]`：切换到第 n 个置顶供应商
+fn quick_switch(args: &[String], out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch <1-9> [--app <app>]";
+    let (slot, rest) = args
+        .split_first()
+        .ok_or_else(|| CliError::Usage(USAGE.to_string()))?;
+    let args = ParsedArgs::parse(rest, &["--app"], &[], USAGE)?;
+    let slot: usize = slot
+        .parse()
+        .map_err(|_| CliError::Usage(USAGE.to_string()))?;
+    if !args.positional.is_empty() {
+        return Err(CliError::Usage(USAGE.to_string()));
+    }
+    let app_type = args.app_type()?;
+    let pinned = ProviderService::pinned(&open_state()?, app_type.clone())?;
+    let provider = pinned.get(slot - 1).ok_or_else(|| {
+        CliError::Failed(AppError::InvalidInput(format!(
+            "{} 只有 {} 个置顶供应商，槽位 {slot} 为空（用 `cc-switch provider pin <id>` 置顶）",
+            app_type.as_str(),
+            pinned.len()
+        )))
+    })?;
+    switch(
+        &[
+            provider.id.clone(),
+            "--app".to_string(),
+            app_type.as_str().to_string(),
+        ],
+        out,
+    )
+}
+
+/// 在终端中列出基础地址与自定义端点，读取用户选择的序号
+///
+/// 返回 None 表示选择了基础地址。
+fn pick_endpoint(
+    state: &AppState,
+    app_type: &AppType,
+    provider: &Provider,
+) -> Result<Option<String>, CliError> {
+    use std::io::{BufRead, IsTerminal, Write};
+
+    if !std::io::stdin().is_terminal() {
+        return Err(CliError::Usage(
+            "非交互环境下需要指定 url（或 --reset 恢复基础地址）".to_string(),
+        ));
+    }
+    let endpoints = ProviderService::get_custom_endpoints(state, app_type.clone(), &provider.id)?;
+    if endpoints.is_empty() {
+        return Err(CliError::Failed(AppError::Message(format!(
+            "供应商 {} 没有自定义端点，请先运行 `cc-switch endpoint add`",
+            provider.id
+        ))));
+    }
+    let active = provider
+        .meta
+        .as_ref()
+        .and_then(|meta| meta.active_endpoint.as_deref());
+    let mark = |selected: bool| if selected { "*" } else { " " };
+
+    let mut stderr = std::io::stderr();
+    let _ = writeln!(stderr, "{} 0) 基础地址", mark(active.is_none()));
+    for (index, endpoint) in endpoints.iter().enumerate() {
+        let latency = endpoint
+            .latency_ms
+            .map(|ms| format!(" ({ms}ms)"))
+            .unwrap_or_default();
+        let _ = writeln!(
+            stderr,
+            "{} {}) {}{latency}",
+            mark(active == Some(endpoint.url.as_str())),
+            index + 1,
+            endpoint.url
+        );
+    }
+    let _ = write!(stderr, "选择端点 [0-{}]: ", endpoints.len());
+    let _ = stderr.flush();
+
+    let mut line = String::new();
+    std::io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .map_err(|e| AppError::Message(format!("读取输入失败: {e}")))?;
+    match line.trim().parse::<usize>() {
+        Ok(0) => Ok(None),
+        Ok(n) if n <= endpoints.len() => Ok(Some(endpoints[n - 1].url.clone())),
+        _ => Err(CliError::Usage(format!("无效的选择: {}", line.trim()))),
+    }
+}
+
+fn sync(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch sync push [--force] | pull | status
+       cc-switch sync setup --backend webdav --url <dir-url> [--username <user>] [--password <pass>] [--passphrase <pass>]
+       cc-switch sync setup --backend s3 --url <endpoint> --bucket <bucket> --access-key-id <id> --secret-access-key <key> [--region <region>] [--prefix <prefix>] [--passphrase <pass>]
+       cc-switch sync [setup] --backend git --remote <repo> [--branch <branch>] [--passphrase <pass>]";
+    const SETUP_FLAGS: &[&str] = &[
+        "--backend",
+        "--url",
+        "--username",
+        "--password",
+        "--bucket",
+        "--region",
+        "--access-key-id",
+        "--secret-access-key",
+        "--prefix",
+        "--remote",
+        "--branch",
+        "--passphrase",
+    ];
+    let args = ParsedArgs::parse(args, SETUP_FLAGS, &["--force"], USAGE)?;
+    let command = match args.positional.as_slice() {
+        [command] => command.as_str(),
+        // `sync --backend git --remote <repo>` is shorthand for `sync setup`
+        [] if args.has("--backend") => "setup",
+        _ => return Err(CliError::Usage(USAGE.to_string())),
+    };
+    if command != "setup" && SETUP_FLAGS.iter().any(|flag| args.has(flag)) {
+        return Err(CliError::Usage(USAGE.to_string()));
+    }
+    if command != "push" && args.has("--force") {
+        return Err(CliError::Usage(USAGE.to_string()));
+    }
+
+    let describe = |outcome: &SyncOutcome| match outcome.action {
+        "up-to-date" => "已是最新".to_string(),
+        "pulled" => "已用远端快照替换本地数据".to_string(),
+        "pushed" => "已上传快照".to_string(),
+        _ => format!("已合并远端的 {} 个供应商", outcome.merged),
+    };
+    match command {
+        "setup" => {
+            let value = |flag: &str| args.value(flag).map(str::to_string);
+            let backend = value("--backend").ok_or_else(|| CliError::Usage(USAGE.to_string()))?;
+            let config = SyncSettings {
+                backend,
+                url: value("--url"),
+                username: value("--username"),
+                password: value("--password"),
+                bucket: value("--bucket"),
+                region: value("--region"),
+                access_key_id: value("--access-key-id"),
+                secret_access_key: value("--secret-access-key"),
+                prefix: value("--prefix"),
+                remote: value("--remote"),
+                branch: value("--branch"),
+                passphrase: value("--passphrase"),
+            };
+            let mut settings = crate::settings::get_settings();
+            settings.sync = Some(config);
+            crate::settings::update_settings(settings)?;
+            Ok(CommandOutput::new(json!({ "configured": true })).human("已保存云同步配置"))
+        }
+        "push" => {
+            let state = open_state()?;
+            let outcome = runtime()?.block_on(SyncService::push(&state, args.has("--force")))?;
+            let mut human = describe(&outcome);
+            if outcome.action == "merged" {
+                human.push_str("，并已上传合并结果");
+            }
+            Ok(CommandOutput::new(&outcome).human(human))
+        }
+        "pull" => {
+            let state = open_state()?;
+            let outcome = runtime()?.block_on(SyncService::pull(&state))?;
+            let human = describe(&outcome);
+            Ok(CommandOutput::new(&outcome).human(human))
+        }
+        "status" => {
+            let state = open_state()?;
+            let status = runtime()?.block_on(SyncService::status(&state))?;
+            let time =
+                |millis: Option<i64>| millis.map(local_time).unwrap_or_else(|| "-".to_string());
+            let human = [
+                format!("后端: {} ({})", status.backend, status.target),
+                format!(
+                    "远端版本: {}{}",
+                    status.remote_revision.as_deref().unwrap_or("-"),
+                    if status.remote_changed {
+                        "（有其他设备的修改）"
+                    } else {
+                        ""
+                    }
+                ),
+                format!(
+                    "本地修改: {}",
+                    if status.local_changed {
+                        "有未上传的修改"
+                    } else {
+                        "无"
+                    }
+                ),
+                format!("上次上传: {}", time(status.last_push_at)),
+                format!("上次下载: {}", time(status.last_pull_at)),
+            ]
+            .join("\n");
+            Ok(CommandOutput::new(&status).human(human))
+        }
+        _ => Err(CliError::Usage(USAGE.to_string())),
+    }
+}
+
+fn backup(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch backup list | prune | restore <backup-id|file.db>";
+    let args = ParsedArgs::parse(args, &[], &[], USAGE)?;
+    let size = |bytes: u64| format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0));
+    match args.positional.as_slice() {
+        [command] if command == "list" => {
+            let backups = Database::list_backups()?;
+            let rows = backups
+                .iter()
+                .map(|backup| {
+                    vec![
+                        backup.id.clone(),
+                        local_time(backup.created_at),
+                        size(backup.size),
+                    ]
+                })
+                .collect();
+            Ok(CommandOutput::new(&backups).table(vec!["ID", "CREATED", "SIZE"], rows))
+        }
+        [command] if command == "prune" => {
+            let removed = Database::prune_backups()?;
+            let freed: u64 = removed.iter().map(|backup| backup.size).sum();
+            let human = format!("已删除 {} 个旧备份，释放 {}", removed.len(), size(freed));
+            Ok(CommandOutput::new(&removed).human(human))
+        }
+        [command, source] if command == "restore" => {
+            // 恢复数据库与写回 live 配置之间不让其他 cc-switch 进程插入
+            let _lock = OperationLock::acquire()?;
+            let db = Database::init()?;
+            let safety = db.restore_backup(source)?;
+            let state = AppState::new(Arc::new(db));
+            if let Err(e) = ProviderService::sync_current_to_live(&state) {
+                log::warn!("恢复后写入 live 配置失败: {e}");
+            }
+            let mut human = format!("已从 {source} 恢复数据库");
+            if !safety.is_empty() {
+                human.push_str(&format!("（恢复前的数据已备份为 {safety}）"));
+            }
+            Ok(CommandOutput::new(json!({ "restored": source, "backupId": safety })).human(human))
+        }
+        _ => Err(CliError::Usage(USAGE.to_string())),
+    }
+}
+
+fn app_snapshot(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch app snapshot [--app <app>] [--label <text>]
+       cc-switch app snapshot list [--app <app>]
+       cc-switch app snapshot restore <id>";
+    let args = ParsedArgs::parse(args, &["--app", "--label"], &[], USAGE)?;
+    let size = |bytes: u64| format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0));
+    match args.positional.as_slice() {
+        [command] if command == "snapshot" => {
+            let app_type = args.app_type()?;
+            let snapshot = SnapshotService::create(&app_type, args.value("--label"))?;
+            let human = format!(
+                "已为 {} 生成快照 {}（{}）",
+                SnapshotService::app_dir(&app_type).display(),
+                snapshot.id,
+                size(snapshot.size)
+            );
+            Ok(CommandOutput::new(&snapshot).human(human))
+        }
+        [command, sub] if command == "snapshot" && sub == "list" => {
+            let app_type = args.value("--app").map(|_| args.app_type()).transpose()?;
+            let snapshots = SnapshotService::list(app_type.as_ref())?;
+            let rows = snapshots
+                .iter()
+                .map(|snapshot| {
+                    vec![
+                        snapshot.id.clone(),
+                        snapshot.app.clone(),
+                        local_time(snapshot.created_at),
+                        size(snapshot.size),
+                    ]
+                })
+                .collect();
+            Ok(CommandOutput::new(&snapshots).table(vec!["ID", "APP", "CREATED", "SIZE"], rows))
+        }
+        [command, sub, id] if command == "snapshot" && sub == "restore" => {
+            let (snapshot, safety) = SnapshotService::restore(id)?;
+            let mut human = format!("已从快照 {} 恢复 {} 配置目录", snapshot.id, snapshot.app);
+            if let Some(safety) = &safety {
+                human.push_str(&format!("（恢复前的目录已保存为 {safety}）"));
+            }
+            let data =
+                json!({ "restored": snapshot.id, "app": snapshot.app, "snapshotId": safety });
+            Ok(CommandOutput::new(data).human(human))
+        }
+        _ => Err(CliError::Usage(USAGE.to_string())),
+    }
+}
+
+fn db(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch db doctor [--check-only] | db migrate [--dry-run]";
+    let args = ParsedArgs::parse(args, &[], &["--check-only", "--dry-run"], USAGE)?;
+    match args.positional.as_slice() {
+        [action] if action == "doctor" => db_doctor(&args),
+        [action] if action == "migrate" => db_migrate(&args),
+        _ => Err(CliError::Usage(USAGE.to_string())),
+    }
+}
+
+fn db_migrate(args: &ParsedArgs) -> Result<CommandOutput, CliError> {
+    let dry_run = args.has("--dry-run");
+    let db = Database::open_unmigrated()?;
+    let pending = db.pending_migrations()?;
+    let mismatches = db.migration_mismatches()?;
+    if !dry_run && !mismatches.is_empty() {
+        return Err(MigrationMismatch::error(&mismatches).into());
+    }
+    if !dry_run && !pending.is_empty() {
+        Database::init()?;
+    }
+
+    let mut human = vec![if pending.is_empty() {
+        "数据库已是最新版本，没有需要执行的迁移".to_string()
+    } else if dry_run {
+        format!("将执行 {} 个迁移:", pending.len())
+    } else {
+        format!("已执行 {} 个迁移:", pending.len())
+    }];
+    human.extend(pending.iter().map(|migration| {
+        format!(
+            "  v{:<3} {:<24} {}",
+            migration.version, migration.name, migration.description
+        )
+    }));
+    if !mismatches.is_empty() {
+        human.push(format!(
+            "{} 个已执行的迁移与当前定义不一致，迁移将失败:",
+            mismatches.len()
+        ));
+        human.extend(mismatches.iter().map(|m| format!("  {}", m.describe())));
+    }
+    let code = if mismatches.is_empty() {
+        cli_error::SUCCESS
+    } else {
+        cli_error::FAILURE
+    };
+    Ok(CommandOutput::new(json!({
+        "dryRun": dry_run,
+        "migrations": pending,
+        "mismatches": mismatches,
+    }))
+    .human(human.join("\n"))
+    .code(code))
+}
+
+fn db_doctor(args: &ParsedArgs) -> Result<CommandOutput, CliError> {
+    let db = Database::init()?;
+    let problems = db.integrity_check()?;
+    let mut reclaimed = None;
+    if problems.is_empty() && !args.has("--check-only") {
+        db.analyze()?;
+        reclaimed = Some(db.vacuum()?);
+    }
+    let stats = db.stats()?;
+
+    let size = |bytes: u64| format!("{:.1} KB", bytes as f64 / 1024.0);
+    let mut human = vec![
+        format!(
+            "完整性检查: {}",
+            if problems.is_empty() { "ok" } else { "失败" }
+        ),
+        format!("Schema 版本: {}", stats.schema_version),
+        format!("日志模式: {}", stats.journal_mode),
+        format!(
+            "大小: {}（空闲 {}）",
+            size(stats.size),
+            size(stats.free_bytes)
+        ),
+    ];
+    human.extend(problems.iter().map(|problem| format!("  {problem}")));
+    if let Some(reclaimed) = reclaimed {
+        human.push(format!(
+            "已执行 ANALYZE 与 VACUUM，回收 {}",
+            size(reclaimed)
+        ));
+    }
+    human.extend(
+        stats
+            .tables
+            .iter()
+            .map(|table| format!("  {:<24} {}", table.name, table.rows)),
+    );
+    let rows = stats
+        .tables
+        .iter()
+        .map(|table| vec![table.name.clone(), table.rows.to_string()])
+        .collect();
+    Ok(CommandOutput::new(json!({
+        "ok": problems.is_empty(),
+        "problems": problems,
+        "reclaimedBytes": reclaimed,
+        "stats": stats,
+    }))
+    .human(human.join("\n"))
+    .table(vec!["TABLE", "ROWS"], rows)
+    .code(if problems.is_empty() {
+        cli_error::SUCCESS
+    } else {
+        cli_error::FAILURE
+    }))
+}
+
+fn limits(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch limits status [--app <app>]";
+    let args = ParsedArgs::parse(args, &["--app"], &[], USAGE)?;
+    if args.positional.as_slice() != ["status"] {
+        return Err(CliError::Usage(USAGE.to_string()));
+    }
+    let app_type = args
+        .value("--app")
+        .map(AppType::from_str)
+        .transpose()
+        .map_err(CliError::Argument)?;
+    let state = open_state()?;
+    let reports = ProviderService::limits_status(&state, app_type)?;
+
+    let format = UsageFormatter::from_settings(&crate::settings::get_settings());
+    let spend = |usage: &str, limit: &Option<String>| {
+        let money = |value: &str| {
+            value
+                .parse::<f64>()
+                .map(|v| format.money(v, Currency::Usd))
+                .unwrap_or_else(|_| "-".to_string())
+        };
+        match limit {
+            Some(limit) => format!("{} / {}", money(usage), money(limit)),
+            None => "-".to_string(),
+        }
+    };
+    let rows = reports
+        .iter()
+        .map(|report| {
+            let mark = if report.is_current { "*" } else { "" };
+            vec![
+                report.app.clone(),
+                format!(
+                    "{}{mark} ({})",
+                    report.provider_name, report.status.provider_id
+                ),
+                spend(&report.status.daily_usage, &report.status.daily_limit),
+                spend(&report.status.monthly_usage, &report.status.monthly_limit),
+                if report.exceeded() { "exceeded" } else { "ok" }.to_string(),
+            ]
+        })
+        .collect();
+    let exceeded = reports.iter().any(|report| report.exceeded());
+    Ok(CommandOutput::new(&reports)
+        .table(vec!["APP", "PROVIDER", "DAILY", "MONTHLY", "STATUS"], rows)
+        .code(if exceeded {
+            cli_error::FAILURE
+        } else {
+            cli_error::SUCCESS
+        }))
+}
+
+fn lint(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch lint [--app <app>|all]";
+    let args = ParsedArgs::parse(args, &["--app"], &[], USAGE)?;
+    if !args.positional.is_empty() {
+        return Err(CliError::Usage(USAGE.to_string()));
+    }
+    let apps = if args.app() == "all" {
+        vec![AppType::Claude, AppType::Codex, AppType::Gemini]
+    } else {
+        vec![args.app_type()?]
+    };
+
+    let mut issues = Vec::new();
+    for app_type in &apps {
+        for issue in ProviderService::lint_live(app_type)? {
+            issues.push((app_type.as_str(), issue));
+        }
+    }
+    let rows = issues
+        .iter()
+        .map(|(app, lint)| {
+            let severity = if lint.issue.is_error() {
+                "error"
+            } else {
+                "warning"
+            };
+            vec![
+                app.to_string(),
+                severity.to_string(),
+                lint.file.clone(),
+                lint.issue.field.clone(),
+                lint.issue.message.clone(),
+            ]
+        })
+        .collect();
+    let data: Vec<Value> = issues
+        .iter()
+        .map(|(app, lint)| {
+            let mut value = json!(lint);
+            value["app"] = json!(app);
+            value
+        })
+        .collect();
+    let failed = issues.iter().any(|(_, lint)| lint.issue.is_error());
+    let mut output = CommandOutput::new(&data);
+    if issues.is_empty() {
+        output = output.human("未发现问题");
+    } else {
+        output = output.table(vec!["APP", "LEVEL", "FILE", "FIELD", "MESSAGE"], rows);
+    }
+    Ok(output.code(if failed {
+        cli_error::FAILURE
+    } else {
+        cli_error::SUCCESS
+    }))
+}
+
+fn debug(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch debug replay <id> --request <file> [--app <app>]
+       cc-switch debug headers <id> [--app <app>]";
+    let args = ParsedArgs::parse(args, &["--app", "--request"], &[], USAGE)?;
+    let [action, id] = args.positional.as_slice() else {
+        return Err(CliError::Usage(USAGE.to_string()));
+    };
+    let request = match (action.as_str(), args.value("--request")) {
+        ("replay", Some(path)) => Some(path),
+        ("headers", None) => None,
+        _ => return Err(CliError::Usage(USAGE.to_string())),
+    };
+    let app_type = args.app_type()?;
+    let state = open_state()?;
+    let id = &resolve_id(&state, &app_type, id)?;
+    let provider = state
+        .db
+        .get_provider_by_id(id, app_type.as_str())?
+        .ok_or_else(|| AppError::provider_not_found(id, app_type.as_str()))?;
+
+    let header_lines = |headers: &[(String, String)]| -> Vec<String> {
+        headers
+            .iter()
+            .map(|(name, value)| format!("{name}: {value}"))
+            .collect()
+    };
+
+    let Some(path) = request else {
+        let report = runtime()?.block_on(DebugService::inspect_headers(&app_type, &provider))?;
+        let mut lines = vec![format!(
+            "{} {} {}  {} ms",
+            report.method, report.status, report.url, report.latency_ms
+        )];
+        for (title, headers) in [
+            ("网关", &report.gateway),
+            ("限流", &report.rate_limits),
+            ("全部响应头", &report.headers),
+        ] {
+            lines.push(format!("\n{title}:"));
+            if headers.is_empty() {
+                lines.push("  -".to_string());
+            }
+            lines.extend(
+                header_lines(headers)
+                    .into_iter()
+                    .map(|line| format!("  {line}")),
+            );
+        }
+        return Ok(CommandOutput::new(&report).human(lines.join("\n")));
+    };
+
+    let payload: Value =
+        crate::config::read_json_file(&PathBuf::from(path)).map_err(CliError::Argument)?;
+    let response = runtime()?.block_on(DebugService::replay(
+        &app_type,
+        &provider,
+        ReplayRequest::from_value(payload),
+    ))?;
+
+    let mut lines = vec![format!(
+        "{} {}  {} ms",
+        response.status, response.url, response.latency_ms
+    )];
+    lines.extend(header_lines(&response.headers));
+    lines.push(String::new());
+    lines.push(response.body.clone());
+    Ok(CommandOutput::new(&response).human(lines.join("\n")))
+}
+
+fn failover(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch failover run [--app <app>]";
+    let args = ParsedArgs::parse(args, &["--app"], &[], USAGE)?;
+    if args.positional.as_slice() != ["run"] {
+        return Err(CliError::Usage(USAGE.to_string()));
+    }
+    let app_type = args.app_type()?;
+    let state = open_state()?;
+    let result = runtime()?.block_on(FailoverService::run(&state, app_type))?;
+
+    let mut rows = Vec::new();
+    for check in &result.checks {
+        rows.push(vec![
+            check.provider_name.clone(),
+            if check.result.success { "ok" } else { "failed" }.to_string(),
+            check
+                .result
+                .response_time_ms
+                .map(|ms| format!("{ms}ms"))
+                .unwrap_or_else(|| "-".to_string()),
+            check.result.message.clone(),
+        ]);
+    }
+    let mut lines = vec![if result.switched {
+        format!(
+            "已从 {} 切换到 {}（组 {}）",
+            result.previous_provider_id, result.current_provider_id, result.group
+        )
+    } else {
+        format!(
+            "当前供应商 {} 可用，未切换（组 {}）",
+            result.current_provider_id, result.group
+        )
+    }];
+    lines.extend(rows.iter().map(|row| format!("  {}", row.join("  "))));
+    Ok(CommandOutput::new(&result)
+        .human(lines.join("\n"))
+        .table(vec!["PROVIDER", "STATUS", "LATENCY", "MESSAGE"], rows))
+}
+
+fn find(args: &[String], _out: &Output) -> Result<CommandOutput, CliError> {
+    const USAGE: &str = "用法: cc-switch find --key <key> | --url <url>";
+    let args = ParsedArgs::parse(args, &["--key", "--url"], &[], USAGE)?;
+    if !args.positional.is_empty() {
+        return Err(CliError::Usage(USAGE.to_string()));
+    }
+    let target = LookupTarget::from_args(
+        args.value("--key").map(str::to_string),
+        args.value("--url").map(str::to_string),
+    )
+    .map_err(CliError::Argument)?;
+    let state = open_state()?;
+    let references = ProviderService::find_references(&state, &target)?;
+
+    let mut output = CommandOutput::new(&references).table(
+        vec!["APP", "PROVIDER", "CURRENT", "FIELDS"],
+        reference_rows(&references),
+    );
+    if references.is_empty() {
+        output = output.human("没有供应商使用该值".to_string());
+    }
+    Ok(output)
+}
+
+/// `find` / `keys revoke` 的表格行：应用、供应商、是否为当前供应商、所在字段
+fn reference_rows(references: &[ProviderReference]) -> Vec<Vec<String>> {
+    references
+        .iter()
+        .map(|reference| {
+            vec![
+                reference.app.clone(),
+                format!("{} ({})", reference.name, reference.id),
+                if reference.is_current { "*" } else { "" }.to_string(),
+                reference.fields.join(", "),
+            ]
+        })
+        .collect()
+}
+
+/// Unix 毫秒格式化为本地时间
+fn local_time(millis: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(millis)
+        .map(|t| {
+            t.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
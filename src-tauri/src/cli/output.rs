@@ -0,0 +1,249 @@
+//! 命令行输出渲染
+//!
+//! 子命令只产出 [`CommandOutput`]（结构化数据 + 可选的人类可读文本 / 表格），由
+//! `--output` 选定的 [`Renderer`] 统一决定如何打印。新增输出格式只需实现一个渲染器，
+//! 不必修改各个子命令。
+
+use std::str::FromStr;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::AppError;
+use crate::services::provider::{render_grid, TableStyle};
+
+/// 输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum OutputFormat {
+    /// 人类可读文本（默认）
+    #[default]
+    Human,
+    /// 完整 JSON 文档
+    Json,
+    /// 每行一个 JSON 值（列表逐项输出）
+    Ndjson,
+    /// 表格（终端带边框，管道中为纯文本对齐）
+    Table,
+    /// 不输出，只看退出码
+    Quiet,
+}
+
+impl FromStr for OutputFormat {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "human" | "text" => Ok(Self::Human),
+            "json" => Ok(Self::Json),
+            "ndjson" | "jsonl" => Ok(Self::Ndjson),
+            "table" => Ok(Self::Table),
+            "quiet" | "none" => Ok(Self::Quiet),
+            other => Err(AppError::InvalidInput(format!(
+                "未知的输出格式: {other}（可选: human, json, ndjson, table, quiet）"
+            ))),
+        }
+    }
+}
+
+impl OutputFormat {
+    /// 从参数中取出全局输出选项（`--output <fmt>`、`-o <fmt>`、`--json`、`--quiet` / `-q`），
+    /// 返回格式与剩余参数；`--` 之后的参数原样保留（例如交给 `run` 启动的命令）
+    pub(crate) fn extract(args: &[String]) -> Result<(Self, Vec<String>), AppError> {
+        let mut format = Self::default();
+        let mut rest = Vec::with_capacity(args.len());
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--" => {
+                    rest.push(arg.clone());
+                    rest.extend(iter.cloned());
+                    break;
+                }
+                "--json" => format = Self::Json,
+                "--quiet" | "-q" => format = Self::Quiet,
+                "--output" | "-o" => {
+                    let value = iter
+                        .next()
+                        .ok_or_else(|| AppError::InvalidInput(format!("{arg} 需要一个参数")))?;
+                    format = value.parse()?;
+                }
+                other => match other.strip_prefix("--output=") {
+                    Some(value) => format = value.parse()?,
+                    None => rest.push(arg.clone()),
+                },
+            }
+        }
+        Ok((format, rest))
+    }
+
+    pub(crate) fn renderer(self, style: TableStyle) -> Box<dyn Renderer> {
+        match self {
+            Self::Human => Box::new(HumanRenderer),
+            Self::Json => Box::new(JsonRenderer),
+            Self::Ndjson => Box::new(NdjsonRenderer),
+            Self::Table => Box::new(TableRenderer(style)),
+            Self::Quiet => Box::new(QuietRenderer),
+        }
+    }
+}
+
+/// 表格形式的输出
+#[derive(Debug, Clone)]
+pub(crate) struct Table {
+    pub headers: Vec<&'static str>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// 一个子命令的执行结果
+#[derive(Debug, Clone)]
+pub(crate) struct CommandOutput {
+    /// 结构化数据（json / ndjson）
+    pub data: Value,
+    /// 人类可读文本；未提供时由表格或 JSON 代替
+    pub human: Option<String>,
+    pub table: Option<Table>,
+    /// 进程退出码
+    pub code: i32,
+}
+
+impl CommandOutput {
+    pub(crate) fn new(data: impl Serialize) -> Self {
+        Self {
+            data: serde_json::to_value(data).unwrap_or(Value::Null),
+            human: None,
+            table: None,
+            code: 0,
+        }
+    }
+
+    pub(crate) fn human(mut self, text: impl Into<String>) -> Self {
+        self.human = Some(text.into());
+        self
+    }
+
+    pub(crate) fn table(mut self, headers: Vec<&'static str>, rows: Vec<Vec<String>>) -> Self {
+        self.table = Some(Table { headers, rows });
+        self
+    }
+
+    pub(crate) fn code(mut self, code: i32) -> Self {
+        self.code = code;
+        self
+    }
+}
+
+/// 输出渲染器
+pub(crate) trait Renderer {
+    /// 要打印到 stdout 的文本；None 表示不输出
+    fn render(&self, output: &CommandOutput) -> Option<String>;
+}
+
+struct HumanRenderer;
+
+impl Renderer for HumanRenderer {
+    fn render(&self, output: &CommandOutput) -> Option<String> {
+        if let Some(text) = &output.human {
+            return Some(text.clone());
+        }
+        if let Some(table) = &output.table {
+            return Some(render_grid(
+                &table.headers,
+                &table.rows,
+                TableStyle::for_stdout(),
+            ));
+        }
+        JsonRenderer.render(output)
+    }
+}
+
+struct JsonRenderer;
+
+impl Renderer for JsonRenderer {
+    fn render(&self, output: &CommandOutput) -> Option<String> {
+        if output.data.is_null() {
+            return None;
+        }
+        serde_json::to_string_pretty(&output.data).ok()
+    }
+}
+
+struct NdjsonRenderer;
+
+impl Renderer for NdjsonRenderer {
+    fn render(&self, output: &CommandOutput) -> Option<String> {
+        let lines: Vec<String> = match &output.data {
+            Value::Null => return None,
+            Value::Array(items) => items.iter().map(Value::to_string).collect(),
+            value => vec![value.to_string()],
+        };
+        if lines.is_empty() {
+            return None;
+        }
+        Some(lines.join("\n"))
+    }
+}
+
+struct TableRenderer(TableStyle);
+
+impl Renderer for TableRenderer {
+    fn render(&self, output: &CommandOutput) -> Option<String> {
+        match &output.table {
+            Some(table) => Some(render_grid(&table.headers, &table.rows, self.0)),
+            None => HumanRenderer.render(output),
+        }
+    }
+}
+
+struct QuietRenderer;
+
+impl Renderer for QuietRenderer {
+    fn render(&self, _output: &CommandOutput) -> Option<String> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn renderers_share_one_command_output() {
+        let output = CommandOutput::new(json!([{ "id": "a" }, { "id": "b" }])).table(
+            vec!["ID"],
+            vec![vec!["a".to_string()], vec!["b".to_string()]],
+        );
+
+        let render = |format: OutputFormat| format.renderer(TableStyle::Plain).render(&output);
+        assert_eq!(
+            render(OutputFormat::Ndjson).unwrap(),
+            "{\"id\":\"a\"}\n{\"id\":\"b\"}"
+        );
+        assert_eq!(render(OutputFormat::Table).unwrap(), "ID\na\nb");
+        assert!(render(OutputFormat::Json).unwrap().starts_with('['));
+        assert!(render(OutputFormat::Quiet).is_none());
+    }
+
+    #[test]
+    fn extracts_global_output_flags() {
+        let args: Vec<String> = ["--app", "codex", "-o", "ndjson", "x"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let (format, rest) = OutputFormat::extract(&args).unwrap();
+        assert_eq!(format, OutputFormat::Ndjson);
+        assert_eq!(rest, vec!["--app", "codex", "x"]);
+
+        let (format, _) = OutputFormat::extract(&["--json".to_string()]).unwrap();
+        assert_eq!(format, OutputFormat::Json);
+        assert!(OutputFormat::extract(&["--output=xml".to_string()]).is_err());
+
+        let args: Vec<String> = ["-q", "--", "codex", "--json"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let (format, rest) = OutputFormat::extract(&args).unwrap();
+        assert_eq!(format, OutputFormat::Quiet);
+        assert_eq!(rest, vec!["--", "codex", "--json"]);
+    }
+}
@@ -0,0 +1,437 @@
+//! 终端界面（`cc-switch tui`）
+//!
+//! 顶部为应用标签页，左侧是供应商列表（置顶的供应商排在最前并以 `★` 标记，`●` 标记当前
+//! 供应商，已归档的显示为灰色），
+//! 右侧是选中供应商的详情、累计用量与最近 24 小时的测速结果。所有操作都经由
+//! [`ProviderService`] / [`BenchService`]，与 GUI 和其他子命令行为一致。
+//!
+//! 按键：`Tab` / `←` `→` 切换应用，`↑` `↓`（`j` `k`）选择，`Enter` 切换到选中的供应商，
+//! `n` 编辑备注，`p` 置顶或取消置顶，`a` 归档或取消归档，`t` 测速，`r` 刷新，`q` / `Esc` 退出。
+
+use std::collections::HashMap;
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Tabs, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::app_config::AppType;
+use crate::database::{BenchmarkResult, ProviderCounters};
+use crate::error::AppError;
+use crate::provider::Provider;
+use crate::proxy::providers::get_adapter;
+use crate::services::bench::RECENT_BENCHMARK_MS;
+use crate::services::provider::ARCHIVED_TAG;
+use crate::services::{BenchService, ProviderService};
+use crate::store::AppState;
+use crate::usage_format::UsageFormatter;
+
+const APPS: [AppType; 3] = [AppType::Claude, AppType::Codex, AppType::Gemini];
+
+const HELP: &str =
+    "Tab 切换应用  ↑↓ 选择  Enter 切换  n 备注  p 置顶  a 归档  t 测速  r 刷新  q 退出";
+
+/// 输入模式
+enum Mode {
+    Normal,
+    /// 正在编辑选中供应商的备注
+    Notes(String),
+}
+
+struct Dashboard {
+    state: AppState,
+    runtime: tokio::runtime::Runtime,
+    format: UsageFormatter,
+    tab: usize,
+    /// 置顶的供应商在前
+    providers: Vec<Provider>,
+    /// 置顶供应商的数量
+    pinned: usize,
+    current: String,
+    list: ListState,
+    counters: HashMap<String, ProviderCounters>,
+    latency: HashMap<String, BenchmarkResult>,
+    mode: Mode,
+    status: String,
+    /// 下一帧绘制后执行测速（先让“正在测试”显示出来）
+    testing: bool,
+    quit: bool,
+}
+
+/// 运行终端界面，直到用户退出
+pub(super) fn run(state: AppState, runtime: tokio::runtime::Runtime) -> Result<(), AppError> {
+    let mut dashboard = Dashboard {
+        state,
+        runtime,
+        format: UsageFormatter::from_settings(&crate::settings::get_settings()),
+        tab: 0,
+        providers: Vec::new(),
+        pinned: 0,
+        current: String::new(),
+        list: ListState::default(),
+        counters: HashMap::new(),
+        latency: HashMap::new(),
+        mode: Mode::Normal,
+        status: String::new(),
+        testing: false,
+        quit: false,
+    };
+    dashboard.reload()?;
+
+    let mut terminal = ratatui::init();
+    let result = dashboard.event_loop(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+impl Dashboard {
+    fn app_type(&self) -> AppType {
+        APPS[self.tab].clone()
+    }
+
+    fn selected(&self) -> Option<&Provider> {
+        self.list.selected().and_then(|i| self.providers.get(i))
+    }
+
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> Result<(), AppError> {
+        while !self.quit {
+            terminal
+                .draw(|frame| self.draw(frame))
+                .map_err(|e| AppError::Message(format!("绘制终端界面失败: {e}")))?;
+            if self.testing {
+                self.testing = false;
+                self.test_selected();
+                continue;
+            }
+            let event =
+                event::read().map_err(|e| AppError::Message(format!("读取终端输入失败: {e}")))?;
+            if let Event::Key(key) = event {
+                if key.kind == KeyEventKind::Press {
+                    self.on_key(key);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 重新读取当前应用的供应商、用量与测速结果，尽量保持选中位置
+    fn reload(&mut self) -> Result<(), AppError> {
+        let app_type = self.app_type();
+        let app = app_type.as_str();
+        (self.providers, self.pinned) =
+            ProviderService::list_pinned_first(&self.state, app_type.clone())?;
+        self.current = ProviderService::current(&self.state, app_type.clone())?;
+        self.counters = self
+            .state
+            .db
+            .get_usage_counters(Some(app))?
+            .into_iter()
+            .map(|counters| (counters.provider_id.clone(), counters))
+            .collect();
+        let since = chrono::Utc::now().timestamp_millis() - RECENT_BENCHMARK_MS;
+        self.latency = self.state.db.get_recent_benchmarks(app, since)?;
+
+        let selected = match self.list.selected() {
+            Some(index) => index.min(self.providers.len().saturating_sub(1)),
+            None => self
+                .providers
+                .iter()
+                .position(|p| p.id == self.current)
+                .unwrap_or(0),
+        };
+        self.list
+            .select((!self.providers.is_empty()).then_some(selected));
+        Ok(())
+    }
+
+    /// 操作完成后刷新数据，并在状态栏显示结果或错误
+    fn report(&mut self, result: Result<(), AppError>, done: String) {
+        self.status = match result.and_then(|_| self.reload()) {
+            Ok(()) => done,
+            Err(e) => format!("失败: {e}"),
+        };
+    }
+
+    fn on_key(&mut self, key: KeyEvent) {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.quit = true;
+            return;
+        }
+
+        if let Mode::Notes(buffer) = &mut self.mode {
+            match key.code {
+                KeyCode::Esc => self.mode = Mode::Normal,
+                KeyCode::Enter => {
+                    let text = std::mem::take(buffer);
+                    self.mode = Mode::Normal;
+                    self.save_notes(&text);
+                }
+                KeyCode::Backspace => {
+                    buffer.pop();
+                }
+                KeyCode::Char(c) => buffer.push(c),
+                _ => {}
+            }
+            return;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Tab | KeyCode::Right | KeyCode::Char('l') => {
+                self.select_tab((self.tab + 1) % APPS.len())
+            }
+            KeyCode::BackTab | KeyCode::Left | KeyCode::Char('h') => {
+                self.select_tab((self.tab + APPS.len() - 1) % APPS.len())
+            }
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::Enter | KeyCode::Char('s') => self.switch_selected(),
+            KeyCode::Char('n') => {
+                if let Some(notes) = self.selected().map(|p| p.notes.clone().unwrap_or_default()) {
+                    self.mode = Mode::Notes(notes);
+                }
+            }
+            KeyCode::Char('p') => self.toggle_pinned(),
+            KeyCode::Char('a') => self.toggle_archived(),
+            KeyCode::Char('t') => {
+                if let Some(name) = self.selected().map(|p| p.name.clone()) {
+                    self.status = format!("正在测试 {name} …");
+                    self.testing = true;
+                }
+            }
+            KeyCode::Char('r') => self.report(Ok(()), "已刷新".to_string()),
+            _ => {}
+        }
+    }
+
+    fn select_tab(&mut self, tab: usize) {
+        self.tab = tab;
+        self.list.select(None);
+        self.report(Ok(()), String::new());
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        if self.providers.is_empty() {
+            return;
+        }
+        let index = self.list.selected().unwrap_or(0) as isize + delta;
+        let index = index.clamp(0, self.providers.len() as isize - 1) as usize;
+        self.list.select(Some(index));
+    }
+
+    fn switch_selected(&mut self) {
+        let Some(provider) = self.selected().cloned() else {
+            return;
+        };
+        let result = ProviderService::switch_from(&self.state, self.app_type(), &provider.id, None)
+            .map(|_| ());
+        self.report(result, format!("已切换到 {}", provider.name));
+    }
+
+    fn toggle_pinned(&mut self) {
+        let Some(index) = self.list.selected() else {
+            return;
+        };
+        let Some(provider) = self.providers.get(index).cloned() else {
+            return;
+        };
+        let pinned = index < self.pinned;
+        let result =
+            ProviderService::set_pinned(&self.state, self.app_type(), &provider.id, !pinned);
+        let done = if pinned {
+            format!("已取消置顶 {}", provider.name)
+        } else {
+            format!("已置顶 {}", provider.name)
+        };
+        // 置顶改变了顺序，选中位置跟随该供应商
+        self.report(result, done);
+        if let Some(position) = self.providers.iter().position(|p| p.id == provider.id) {
+            self.list.select(Some(position));
+        }
+    }
+
+    fn toggle_archived(&mut self) {
+        let Some(provider) = self.selected().cloned() else {
+            return;
+        };
+        let app_type = self.app_type();
+        let archived = provider.tags.iter().any(|tag| tag == ARCHIVED_TAG);
+        let (result, done) = if archived {
+            (
+                self.state
+                    .db
+                    .remove_tag(app_type.as_str(), &provider.id, ARCHIVED_TAG),
+                format!("已取消归档 {}", provider.name),
+            )
+        } else {
+            (
+                self.state
+                    .db
+                    .add_tag(app_type.as_str(), &provider.id, ARCHIVED_TAG),
+                format!("已归档 {}", provider.name),
+            )
+        };
+        self.report(result, done);
+    }
+
+    fn save_notes(&mut self, text: &str) {
+        let Some(mut provider) = self.selected().cloned() else {
+            return;
+        };
+        let text = text.trim();
+        provider.notes = (!text.is_empty()).then(|| text.to_string());
+        let done = format!("已保存 {} 的备注", provider.name);
+        let result = ProviderService::update(&self.state, self.app_type(), provider).map(|_| ());
+        self.report(result, done);
+    }
+
+    fn test_selected(&mut self) {
+        let Some(provider) = self.selected().cloned() else {
+            return;
+        };
+        let app_type = self.app_type();
+        let result =
+            self.runtime
+                .block_on(BenchService::run_one(&self.state, &app_type, &provider));
+        let done = match &result {
+            Ok(result) => format!("{}: {}", provider.name, latency_text(result)),
+            Err(_) => String::new(),
+        };
+        self.report(result.map(|_| ()), done);
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [tabs_area, body, status_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [list_area, detail_area] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+                .areas(body);
+
+        let titles: Vec<&str> = APPS.iter().map(AppType::as_str).collect();
+        let tabs = Tabs::new(titles)
+            .select(self.tab)
+            .block(Block::bordered().title(" cc-switch "))
+            .highlight_style(Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD));
+        frame.render_widget(tabs, tabs_area);
+
+        let items: Vec<ListItem> = self
+            .providers
+            .iter()
+            .enumerate()
+            .map(|(index, provider)| {
+                let current = provider.id == self.current;
+                let style = if current {
+                    Style::new().fg(Color::Green)
+                } else if provider.tags.iter().any(|tag| tag == ARCHIVED_TAG) {
+                    Style::new().fg(Color::DarkGray)
+                } else {
+                    Style::new()
+                };
+                let marker = if current { "● " } else { "  " };
+                let pin = if index < self.pinned { "★ " } else { "" };
+                ListItem::new(format!("{marker}{pin}{}", provider.name)).style(style)
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::bordered().title(" 供应商 "))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, list_area, &mut self.list);
+
+        let detail = Paragraph::new(self.detail_lines())
+            .block(Block::bordered().title(" 详情 "))
+            .wrap(Wrap { trim: false });
+        frame.render_widget(detail, detail_area);
+
+        let status = match &self.mode {
+            Mode::Notes(buffer) => format!("备注: {buffer}▏  Enter 保存  Esc 取消"),
+            Mode::Normal if self.status.is_empty() => HELP.to_string(),
+            Mode::Normal => self.status.clone(),
+        };
+        frame.render_widget(
+            Paragraph::new(status).style(Style::new().fg(Color::Gray)),
+            status_area,
+        );
+    }
+
+    fn detail_lines(&self) -> Vec<Line<'static>> {
+        let Some(provider) = self.selected() else {
+            return vec![Line::from("没有供应商")];
+        };
+        let app_type = self.app_type();
+        let or_dash = |value: Option<&str>| {
+            value
+                .filter(|value| !value.trim().is_empty())
+                .unwrap_or("-")
+                .to_string()
+        };
+        let field = |label: &str, value: String| {
+            Line::from(vec![
+                Span::styled(format!("{label:<10}"), Style::new().fg(Color::Cyan)),
+                Span::raw(value),
+            ])
+        };
+        let meta = provider.meta.clone().unwrap_or_default();
+        let counters = self.counters.get(&provider.id);
+        let base_url = get_adapter(&app_type).extract_base_url(provider).ok();
+
+        vec![
+            field("Name", provider.name.clone()),
+            field("ID", provider.id.clone()),
+            field(
+                "Status",
+                if provider.id == self.current {
+                    "current".to_string()
+                } else {
+                    "-".to_string()
+                },
+            ),
+            field("Base URL", or_dash(base_url.as_deref())),
+            field("Model", or_dash(meta.default_model.as_deref())),
+            field("Website", or_dash(provider.website_url.as_deref())),
+            field(
+                "Tags",
+                if provider.tags.is_empty() {
+                    "-".to_string()
+                } else {
+                    provider.tags.join(", ")
+                },
+            ),
+            field(
+                "Requests",
+                self.format
+                    .count(counters.map(|c| c.requests).unwrap_or_default() as f64),
+            ),
+            field(
+                "Tokens",
+                self.format
+                    .count(counters.map(|c| c.tokens).unwrap_or_default() as f64),
+            ),
+            field(
+                "Latency",
+                self.latency
+                    .get(&provider.id)
+                    .map(latency_text)
+                    .unwrap_or_else(|| "-".to_string()),
+            ),
+            Line::default(),
+            field("Notes", or_dash(provider.notes.as_deref())),
+        ]
+    }
+}
+
+fn latency_text(result: &BenchmarkResult) -> String {
+    if !result.success {
+        return format!("失败 {}", result.error.clone().unwrap_or_default());
+    }
+    format!(
+        "TTFB {}ms, {:.1} tok/s",
+        result.ttfb_ms.unwrap_or_default(),
+        result.tokens_per_sec.unwrap_or_default()
+    )
+}
@@ -0,0 +1,122 @@
+//! 命令行退出码约定
+//!
+//! 脚本可以按退出码分支，而不必解析 stderr。已发布的退出码不会改变含义，
+//! 新的失败类型只会追加新的退出码；完整列表也出现在 `cc-switch capabilities` 中。
+
+use serde::Serialize;
+
+use crate::error::AppError;
+
+/// 成功
+pub const SUCCESS: i32 = 0;
+/// 未归类的失败；检查类子命令（`limits status`、`proxy status`、`db doctor`）结果为否时也返回此值
+pub const FAILURE: i32 = 1;
+/// 参数无效或用法错误
+pub const USAGE: i32 = 2;
+/// 供应商不存在
+pub const NOT_FOUND: i32 = 3;
+/// 数据库被其他进程占用，或另一个 cc-switch 操作正在进行中
+pub const LOCKED: i32 = 4;
+/// 写入应用的 live 配置失败（数据库已更新）
+pub const LIVE_WRITE: i32 = 5;
+/// 数据库由更新版本的 CC Switch 写入
+pub const SCHEMA_TOO_NEW: i32 = 6;
+/// 供应商已存在
+pub const CONFLICT: i32 = 7;
+/// 只读模式（或数据库版本更新时的兼容模式）下拒绝修改
+pub const READ_ONLY: i32 = 8;
+
+/// 退出码说明（能力报告）
+#[derive(Debug, Clone, Serialize)]
+pub struct ExitCode {
+    pub code: i32,
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+/// 全部退出码
+pub const EXIT_CODES: &[ExitCode] = &[
+    ExitCode {
+        code: SUCCESS,
+        name: "success",
+        description: "成功",
+    },
+    ExitCode {
+        code: FAILURE,
+        name: "failure",
+        description: "未归类的失败，或检查结果为否",
+    },
+    ExitCode {
+        code: USAGE,
+        name: "usage",
+        description: "参数无效或用法错误",
+    },
+    ExitCode {
+        code: NOT_FOUND,
+        name: "not_found",
+        description: "供应商不存在",
+    },
+    ExitCode {
+        code: LOCKED,
+        name: "locked",
+        description: "数据库被其他进程占用，或另一个 cc-switch 操作正在进行中",
+    },
+    ExitCode {
+        code: LIVE_WRITE,
+        name: "live_write",
+        description: "写入 live 配置失败",
+    },
+    ExitCode {
+        code: SCHEMA_TOO_NEW,
+        name: "schema_too_new",
+        description: "数据库版本高于当前构建",
+    },
+    ExitCode {
+        code: CONFLICT,
+        name: "conflict",
+        description: "供应商已存在",
+    },
+    ExitCode {
+        code: READ_ONLY,
+        name: "read_only",
+        description: "只读模式下拒绝修改",
+    },
+];
+
+/// 执行失败时的退出码
+pub fn exit_code(err: &AppError) -> i32 {
+    match err {
+        AppError::InvalidInput(_) => USAGE,
+        AppError::ProviderNotFound { .. } => NOT_FOUND,
+        AppError::Locked(_) | AppError::Busy { .. } => LOCKED,
+        AppError::LiveWrite { .. } | AppError::ApplyFailed { .. } => LIVE_WRITE,
+        AppError::SchemaTooNew { .. } => SCHEMA_TOO_NEW,
+        AppError::DuplicateProvider { .. } => CONFLICT,
+        AppError::ReadOnly | AppError::SchemaCompat { .. } => READ_ONLY,
+        _ => FAILURE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exit_codes_are_unique_and_mapped() {
+        let mut codes: Vec<i32> = EXIT_CODES.iter().map(|c| c.code).collect();
+        codes.sort();
+        codes.dedup();
+        assert_eq!(codes.len(), EXIT_CODES.len());
+
+        assert_eq!(exit_code(&AppError::provider_not_found("p", "claude")), 3);
+        assert_eq!(exit_code(&AppError::Locked("busy".to_string())), 4);
+        assert_eq!(
+            exit_code(&AppError::Busy {
+                path: "cc-switch.lock".to_string()
+            }),
+            4
+        );
+        assert_eq!(exit_code(&AppError::ReadOnly), 8);
+        assert_eq!(exit_code(&AppError::Message("x".to_string())), 1);
+    }
+}
@@ -8,6 +8,24 @@ use crate::error::AppError;
 use serde_json::Value;
 use std::fs;
 use std::path::Path;
+use toml_edit::{DocumentMut, Item};
+
+/// 供应商专属的顶层键
+///
+/// 切换供应商时，新供应商未设置的这些键会从现有 config.toml 中移除，避免沿用上一个供应商的值。
+const PROVIDER_OWNED_KEYS: &[&str] = &[
+    "model_provider",
+    "model",
+    "model_reasoning_effort",
+    "model_verbosity",
+    "base_url",
+    "wire_api",
+    "env_key",
+    "requires_openai_auth",
+    "preferred_auth_method",
+    "disable_response_storage",
+    "experimental_bearer_token",
+];
 
 /// 获取 Codex 配置目录路径
 pub fn get_codex_config_dir() -> PathBuf {
@@ -15,7 +33,9 @@ pub fn get_codex_config_dir() -> PathBuf {
         return custom;
     }
 
-    dirs::home_dir().expect("无法获取用户主目录").join(".codex")
+    crate::config::home_dir()
+        .expect("无法获取用户主目录")
+        .join(".codex")
 }
 
 /// 获取 Codex auth.json 路径
@@ -28,6 +48,42 @@ pub fn get_codex_config_path() -> PathBuf {
     get_codex_config_dir().join("config.toml")
 }
 
+/// 获取 Codex .env 路径（Codex CLI 启动时加载）
+pub fn get_codex_env_path() -> PathBuf {
+    get_codex_config_dir().join(".env")
+}
+
+/// cc-switch 写入的 .env 的首行标记
+const MANAGED_ENV_MARKER: &str = "# Managed by CC Switch";
+
+/// 写入供应商的额外环境变量到 Codex .env
+///
+/// 变量为空时删除由 cc-switch 写入的 .env；用户自行维护的 .env（没有标记）不会被覆盖或删除。
+pub fn write_codex_env(env: &std::collections::BTreeMap<String, String>) -> Result<(), AppError> {
+    let path = get_codex_env_path();
+    let managed = match fs::read_to_string(&path) {
+        Ok(content) => content.starts_with(MANAGED_ENV_MARKER),
+        Err(_) => !path.exists(),
+    };
+    if !managed {
+        if !env.is_empty() {
+            log::warn!("{} 由用户维护，跳过写入供应商环境变量", path.display());
+        }
+        return Ok(());
+    }
+    if env.is_empty() {
+        return delete_file(&path);
+    }
+
+    let mut content = String::from(MANAGED_ENV_MARKER);
+    for (key, value) in env {
+        content.push('\n');
+        content.push_str(&format!("{key}={value}"));
+    }
+    content.push('\n');
+    write_text_file(&path, &content)
+}
+
 /// 获取 Codex 供应商配置文件路径
 #[allow(dead_code)]
 pub fn get_codex_provider_paths(
@@ -134,3 +190,102 @@ pub fn read_and_validate_codex_config_text() -> Result<String, AppError> {
     validate_config_toml(&s)?;
     Ok(s)
 }
+
+/// 将供应商的 config.toml 片段合并进现有的 config.toml
+///
+/// 只改动供应商相关的部分，保留用户的其他设置（MCP 服务器、profiles、注释与格式）：
+/// - 供应商片段中的顶层键覆盖现有值；[`PROVIDER_OWNED_KEYS`] 中片段未设置的键被移除
+/// - 两边都是表的（如 `model_providers`）按条目合并，同名条目整体替换
+pub fn merge_provider_config(existing: &str, provider_config: &str) -> Result<String, AppError> {
+    let parse = |text: &str, label: &str| -> Result<DocumentMut, AppError> {
+        if text.trim().is_empty() {
+            return Ok(DocumentMut::default());
+        }
+        text.parse::<DocumentMut>().map_err(|e| {
+            AppError::localized(
+                "codex.config.invalid_toml",
+                format!("{label} 不是有效的 TOML: {e}"),
+                format!("{label} is not valid TOML: {e}"),
+            )
+        })
+    };
+    let mut doc = parse(existing, "config.toml")?;
+    let incoming = parse(provider_config, "供应商 config")?;
+
+    for key in PROVIDER_OWNED_KEYS {
+        if !incoming.contains_key(key) {
+            doc.remove(key);
+        }
+    }
+
+    for (key, item) in incoming.iter() {
+        match (doc.get_mut(key), item) {
+            (Some(Item::Table(target)), Item::Table(source)) => {
+                for (entry_key, entry) in source.iter() {
+                    target.insert(entry_key, entry.clone());
+                }
+            }
+            _ => {
+                doc.insert(key, item.clone());
+            }
+        }
+    }
+
+    Ok(doc.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_replaces_provider_keys_and_keeps_user_settings() {
+        let existing = r#"model_provider = "old"
+model_reasoning_effort = "high"
+approval_policy = "never"
+
+[model_providers.old]
+name = "old"
+base_url = "https://old.example.com/v1"
+
+# docs server
+[mcp_servers.docs]
+command = "docs-mcp"
+"#;
+        let provider = r#"model_provider = "relay"
+model = "gpt-5-codex"
+
+[model_providers.relay]
+name = "relay"
+base_url = "https://relay.example.com/v1"
+env_key = "RELAY_API_KEY"
+"#;
+
+        let merged = merge_provider_config(existing, provider).unwrap();
+        let table: toml::Table = toml::from_str(&merged).unwrap();
+
+        assert_eq!(table["model_provider"].as_str(), Some("relay"));
+        assert_eq!(table["model"].as_str(), Some("gpt-5-codex"));
+        assert!(table.get("model_reasoning_effort").is_none());
+        assert_eq!(table["approval_policy"].as_str(), Some("never"));
+        assert_eq!(
+            table["model_providers"]["relay"]["env_key"].as_str(),
+            Some("RELAY_API_KEY")
+        );
+        assert!(table["model_providers"].get("old").is_some());
+        assert_eq!(
+            table["mcp_servers"]["docs"]["command"].as_str(),
+            Some("docs-mcp")
+        );
+        assert!(merged.contains("# docs server\n[mcp_servers.docs]"));
+    }
+
+    #[test]
+    fn merge_rejects_invalid_toml() {
+        assert!(merge_provider_config("model = ", "").is_err());
+        assert_eq!(
+            merge_provider_config("", "model = \"m\"\n").unwrap(),
+            "model = \"m\"\n"
+        );
+    }
+}
@@ -0,0 +1,70 @@
+//! 注册表应用命令
+
+use tauri::State;
+
+use crate::app_registry::AppDefinition;
+use crate::provider::Provider;
+use crate::services::RegisteredAppService;
+use crate::store::AppState;
+
+/// 获取已注册的应用（内置 + `~/.cc-switch/apps/*.toml`）
+#[tauri::command]
+pub fn get_app_definitions() -> Vec<AppDefinition> {
+    RegisteredAppService::definitions()
+}
+
+/// 获取任意已注册应用的供应商列表（按排序顺序）
+#[tauri::command]
+pub fn get_registered_app_providers(
+    state: State<'_, AppState>,
+    app: String,
+) -> Result<Vec<Provider>, String> {
+    RegisteredAppService::list(state.inner(), &app)
+        .map(|providers| providers.into_values().collect())
+        .map_err(|e| e.to_string())
+}
+
+/// 获取任意已注册应用的当前供应商 ID
+#[tauri::command]
+pub fn get_registered_app_current_provider(
+    state: State<'_, AppState>,
+    app: String,
+) -> Result<String, String> {
+    RegisteredAppService::current(state.inner(), &app).map_err(|e| e.to_string())
+}
+
+/// 新增或更新任意已注册应用的供应商
+#[tauri::command]
+pub fn save_registered_app_provider(
+    state: State<'_, AppState>,
+    app: String,
+    provider: Provider,
+) -> Result<bool, String> {
+    RegisteredAppService::save(state.inner(), &app, provider)
+        .map(|_| true)
+        .map_err(|e| e.to_string())
+}
+
+/// 切换任意已注册应用的供应商
+#[tauri::command]
+pub fn switch_registered_app_provider(
+    state: State<'_, AppState>,
+    app: String,
+    id: String,
+) -> Result<bool, String> {
+    RegisteredAppService::switch(state.inner(), &app, &id)
+        .map(|_| true)
+        .map_err(|e| e.to_string())
+}
+
+/// 删除任意已注册应用的供应商
+#[tauri::command]
+pub fn delete_registered_app_provider(
+    state: State<'_, AppState>,
+    app: String,
+    id: String,
+) -> Result<bool, String> {
+    RegisteredAppService::delete(state.inner(), &app, &id)
+        .map(|_| true)
+        .map_err(|e| e.to_string())
+}
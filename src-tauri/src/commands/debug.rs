@@ -0,0 +1,44 @@
+//! 供应商调试命令
+
+use std::path::PathBuf;
+
+use serde_json::Value;
+use tauri::State;
+
+use crate::app_config::AppType;
+use crate::config::read_json_file;
+use crate::error::AppError;
+use crate::services::debug::{DebugService, HeaderReport, RawResponse, ReplayRequest};
+use crate::store::AppState;
+
+/// 使用指定供应商的凭据重放保存的请求文件
+#[tauri::command]
+pub async fn debug_replay_request(
+    state: State<'_, AppState>,
+    app_type: AppType,
+    provider_id: String,
+    request_path: String,
+) -> Result<RawResponse, AppError> {
+    let provider = state
+        .db
+        .get_provider_by_id(&provider_id, app_type.as_str())?
+        .ok_or_else(|| AppError::provider_not_found(&provider_id, app_type.as_str()))?;
+
+    let payload: Value = read_json_file(&PathBuf::from(request_path))?;
+    DebugService::replay(&app_type, &provider, ReplayRequest::from_value(payload)).await
+}
+
+/// 发送最小请求并返回供应商响应头（网关、限流、延迟）
+#[tauri::command]
+pub async fn debug_provider_headers(
+    state: State<'_, AppState>,
+    app_type: AppType,
+    provider_id: String,
+) -> Result<HeaderReport, AppError> {
+    let provider = state
+        .db
+        .get_provider_by_id(&provider_id, app_type.as_str())?
+        .ok_or_else(|| AppError::provider_not_found(&provider_id, app_type.as_str()))?;
+
+    DebugService::inspect_headers(&app_type, &provider).await
+}
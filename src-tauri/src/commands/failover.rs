@@ -1,9 +1,14 @@
 //! 故障转移队列命令
 //!
-//! 管理代理模式下的故障转移队列（基于 providers 表的 in_failover_queue 字段）
+//! 管理代理模式下的故障转移队列（基于 providers 表的 in_failover_queue 字段），
+//! 以及基于故障转移组的自动切换
 
-use crate::database::FailoverQueueItem;
+use std::str::FromStr;
+
+use crate::app_config::AppType;
+use crate::database::{FailoverGroupMember, FailoverQueueItem};
 use crate::provider::Provider;
+use crate::services::failover::{FailoverRunResult, FailoverService};
 use crate::store::AppState;
 
 /// 获取故障转移队列
@@ -86,5 +91,49 @@ pub async fn set_auto_failover_enabled(
         "[Failover] Setting auto_failover_enabled: key='{key}', value='{value}', app_type='{app_type}'"
     );
 
-    state.db.set_setting(&key, value).map_err(|e| e.to_string())
+    state
+        .async_db()
+        .set_setting(key, value.to_string())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 设置供应商所属的故障转移组及组内优先级（group 为空时移出分组）
+#[tauri::command]
+pub async fn set_provider_failover_group(
+    state: tauri::State<'_, AppState>,
+    app_type: String,
+    provider_id: String,
+    group: Option<String>,
+    priority: Option<i64>,
+) -> Result<(), String> {
+    state
+        .db
+        .set_provider_failover_group(&app_type, &provider_id, group.as_deref(), priority)
+        .map_err(|e| e.to_string())
+}
+
+/// 获取故障转移组成员（按优先级排序）
+#[tauri::command]
+pub async fn get_failover_group_members(
+    state: tauri::State<'_, AppState>,
+    app_type: String,
+    group: String,
+) -> Result<Vec<FailoverGroupMember>, String> {
+    state
+        .db
+        .get_failover_group_members(&app_type, &group)
+        .map_err(|e| e.to_string())
+}
+
+/// 检查当前供应商，不可用时自动切换到故障转移组中下一个健康的供应商
+#[tauri::command]
+pub async fn run_failover(
+    state: tauri::State<'_, AppState>,
+    app_type: String,
+) -> Result<FailoverRunResult, String> {
+    let app = AppType::from_str(&app_type).map_err(|e| e.to_string())?;
+    FailoverService::run(state.inner(), app)
+        .await
+        .map_err(|e| e.to_string())
 }
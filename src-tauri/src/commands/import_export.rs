@@ -5,20 +5,27 @@ use std::path::PathBuf;
 use tauri::State;
 use tauri_plugin_dialog::DialogExt;
 
+use crate::database::SqlExportOptions;
 use crate::error::AppError;
 use crate::services::provider::ProviderService;
 use crate::store::AppState;
 
 /// 导出数据库为 SQL 备份
+///
+/// `normalizeTimestamps` 为 true 时省略生成时间，便于提交到版本库后比较差异。
 #[tauri::command]
 pub async fn export_config_to_file(
     #[allow(non_snake_case)] filePath: String,
+    #[allow(non_snake_case)] normalizeTimestamps: Option<bool>,
     state: State<'_, AppState>,
 ) -> Result<Value, String> {
     let db = state.db.clone();
     tauri::async_runtime::spawn_blocking(move || {
         let target_path = PathBuf::from(&filePath);
-        db.export_sql(&target_path)?;
+        let options = SqlExportOptions {
+            normalize_timestamps: normalizeTimestamps.unwrap_or(false),
+        };
+        db.export_sql_with_options(&target_path, options)?;
         Ok::<_, AppError>(json!({
             "success": true,
             "message": "SQL exported successfully",
@@ -0,0 +1,24 @@
+//! 编辑器 / 启动器集成命令
+
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::error::AppError;
+use crate::services::integrations::{GeneratedFile, IntegrationService, IntegrationTarget};
+
+/// 生成集成文件；指定 `outputDir` 时同时写入该目录
+#[allow(non_snake_case)]
+#[tauri::command]
+pub fn generate_integration(
+    target: String,
+    #[allow(non_snake_case)] outputDir: Option<String>,
+) -> Result<Vec<GeneratedFile>, AppError> {
+    let target = IntegrationTarget::from_str(&target)?;
+    let files = IntegrationService::generate(target, &IntegrationService::current_binary());
+
+    if let Some(dir) = outputDir.filter(|d| !d.trim().is_empty()) {
+        IntegrationService::write_files(&PathBuf::from(dir), &files)?;
+    }
+
+    Ok(files)
+}
@@ -1,15 +1,10 @@
 #![allow(non_snake_case)]
 
 use crate::init_status::InitErrorPayload;
+use crate::services::tool_version;
 use tauri::AppHandle;
 use tauri_plugin_opener::OpenerExt;
 
-#[cfg(target_os = "windows")]
-use std::os::windows::process::CommandExt;
-
-#[cfg(target_os = "windows")]
-const CREATE_NO_WINDOW: u32 = 0x08000000;
-
 /// 打开外部链接
 #[tauri::command]
 pub async fn open_external(app: AppHandle, url: String) -> Result<bool, String> {
@@ -40,6 +35,12 @@ pub async fn check_for_updates(handle: AppHandle) -> Result<bool, String> {
     Ok(true)
 }
 
+/// 获取当前构建的能力报告（可用子系统、子命令与 RPC 方法）
+#[tauri::command]
+pub fn get_capabilities() -> crate::capabilities::CapabilityReport {
+    crate::capabilities::capabilities()
+}
+
 /// 判断是否为便携版（绿色版）运行
 #[tauri::command]
 pub async fn is_portable_mode() -> Result<bool, String> {
@@ -86,17 +87,7 @@ pub async fn get_tool_versions() -> Result<Vec<ToolVersion>, String> {
 
     for tool in tools {
         // 1. 获取本地版本 - 先尝试直接执行，失败则扫描常见路径
-        let (local_version, local_error) = {
-            // 先尝试直接执行
-            let direct_result = try_get_version(tool);
-
-            if direct_result.0.is_some() {
-                direct_result
-            } else {
-                // 扫描常见的 npm 全局安装路径
-                scan_cli_version(tool)
-            }
-        };
+        let (local_version, local_error) = tool_version::detect_local_version(tool);
 
         // 2. 获取远程最新版本
         let latest_version = match tool {
@@ -134,151 +125,3 @@ async fn fetch_npm_latest_version(client: &reqwest::Client, package: &str) -> Op
         Err(_) => None,
     }
 }
-
-/// 从版本输出中提取纯版本号
-fn extract_version(raw: &str) -> String {
-    // 匹配 semver 格式: x.y.z 或 x.y.z-xxx
-    let re = regex::Regex::new(r"\d+\.\d+\.\d+(-[\w.]+)?").unwrap();
-    re.find(raw)
-        .map(|m| m.as_str().to_string())
-        .unwrap_or_else(|| raw.to_string())
-}
-
-/// 尝试直接执行命令获取版本
-fn try_get_version(tool: &str) -> (Option<String>, Option<String>) {
-    use std::process::Command;
-
-    #[cfg(target_os = "windows")]
-    let output = {
-        Command::new("cmd")
-            .args(["/C", &format!("{tool} --version")])
-            .creation_flags(CREATE_NO_WINDOW)
-            .output()
-    };
-
-    #[cfg(not(target_os = "windows"))]
-    let output = {
-        Command::new("sh")
-            .arg("-c")
-            .arg(format!("{tool} --version"))
-            .output()
-    };
-
-    match output {
-        Ok(out) => {
-            let stdout = String::from_utf8_lossy(&out.stdout).trim().to_string();
-            let stderr = String::from_utf8_lossy(&out.stderr).trim().to_string();
-            if out.status.success() {
-                let raw = if stdout.is_empty() { &stderr } else { &stdout };
-                if raw.is_empty() {
-                    (None, Some("未安装或无法执行".to_string()))
-                } else {
-                    (Some(extract_version(raw)), None)
-                }
-            } else {
-                let err = if stderr.is_empty() { stdout } else { stderr };
-                (
-                    None,
-                    Some(if err.is_empty() {
-                        "未安装或无法执行".to_string()
-                    } else {
-                        err
-                    }),
-                )
-            }
-        }
-        Err(e) => (None, Some(e.to_string())),
-    }
-}
-
-/// 扫描常见路径查找 CLI
-fn scan_cli_version(tool: &str) -> (Option<String>, Option<String>) {
-    use std::process::Command;
-
-    let home = dirs::home_dir().unwrap_or_default();
-
-    // 常见的 npm 全局安装路径
-    let mut search_paths: Vec<std::path::PathBuf> = vec![
-        home.join(".npm-global/bin"),
-        home.join(".local/bin"),
-        home.join("n/bin"), // n version manager
-    ];
-
-    #[cfg(target_os = "macos")]
-    {
-        search_paths.push(std::path::PathBuf::from("/opt/homebrew/bin"));
-        search_paths.push(std::path::PathBuf::from("/usr/local/bin"));
-    }
-
-    #[cfg(target_os = "linux")]
-    {
-        search_paths.push(std::path::PathBuf::from("/usr/local/bin"));
-        search_paths.push(std::path::PathBuf::from("/usr/bin"));
-    }
-
-    #[cfg(target_os = "windows")]
-    {
-        if let Some(appdata) = dirs::data_dir() {
-            search_paths.push(appdata.join("npm"));
-        }
-        search_paths.push(std::path::PathBuf::from("C:\\Program Files\\nodejs"));
-    }
-
-    // 扫描 nvm 目录下的所有 node 版本
-    let nvm_base = home.join(".nvm/versions/node");
-    if nvm_base.exists() {
-        if let Ok(entries) = std::fs::read_dir(&nvm_base) {
-            for entry in entries.flatten() {
-                let bin_path = entry.path().join("bin");
-                if bin_path.exists() {
-                    search_paths.push(bin_path);
-                }
-            }
-        }
-    }
-
-    // 在每个路径中查找工具
-    for path in &search_paths {
-        let tool_path = if cfg!(target_os = "windows") {
-            path.join(format!("{tool}.cmd"))
-        } else {
-            path.join(tool)
-        };
-
-        if tool_path.exists() {
-            // 构建 PATH 环境变量，确保 node 可被找到
-            let current_path = std::env::var("PATH").unwrap_or_default();
-            let new_path = format!("{}:{}", path.display(), current_path);
-
-            #[cfg(target_os = "windows")]
-            let output = {
-                Command::new(&tool_path)
-                    .arg("--version")
-                    .env("PATH", &new_path)
-                    .creation_flags(CREATE_NO_WINDOW)
-                    .output()
-            };
-
-            #[cfg(not(target_os = "windows"))]
-            let output = {
-                Command::new(&tool_path)
-                    .arg("--version")
-                    .env("PATH", &new_path)
-                    .output()
-            };
-
-            if let Ok(out) = output {
-                let stdout = String::from_utf8_lossy(&out.stdout).trim().to_string();
-                let stderr = String::from_utf8_lossy(&out.stderr).trim().to_string();
-                if out.status.success() {
-                    let raw = if stdout.is_empty() { &stderr } else { &stdout };
-                    if !raw.is_empty() {
-                        return (Some(extract_version(raw)), None);
-                    }
-                }
-            }
-        }
-    }
-
-    (None, Some("未安装或无法执行".to_string()))
-}
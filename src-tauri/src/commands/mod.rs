@@ -1,10 +1,13 @@
 #![allow(non_snake_case)]
 
+mod apps;
 mod config;
+mod debug;
 mod deeplink;
 mod env;
 mod failover;
 mod import_export;
+mod integrations;
 mod mcp;
 mod misc;
 mod plugin;
@@ -14,13 +17,17 @@ mod proxy;
 mod settings;
 pub mod skill;
 mod stream_check;
+mod sync;
 mod usage;
 
+pub use apps::*;
 pub use config::*;
+pub use debug::*;
 pub use deeplink::*;
 pub use env::*;
 pub use failover::*;
 pub use import_export::*;
+pub use integrations::*;
 pub use mcp::*;
 pub use misc::*;
 pub use plugin::*;
@@ -30,4 +37,5 @@ pub use proxy::*;
 pub use settings::*;
 pub use skill::*;
 pub use stream_check::*;
+pub use sync::*;
 pub use usage::*;
@@ -1,10 +1,17 @@
 use indexmap::IndexMap;
-use tauri::State;
+use tauri::{Manager, State};
 
 use crate::app_config::AppType;
+use crate::database::{ProviderPage, QueryOptions};
 use crate::error::AppError;
 use crate::provider::Provider;
-use crate::services::{EndpointLatency, ProviderService, ProviderSortUpdate, SpeedtestService};
+use crate::services::provider::{
+    BundleImport, ExportBundle, ExportFormat, LookupTarget, ProviderReference, SnippetLang,
+};
+use crate::services::{
+    EndpointLatency, EndpointTiming, ProviderMove, ProviderService, ProviderSortUpdate,
+    SpeedtestService, TemporarySwitch, TemporarySwitchService,
+};
 use crate::store::AppState;
 use std::str::FromStr;
 
@@ -60,6 +67,99 @@ pub fn delete_provider(
         .map_err(|e| e.to_string())
 }
 
+/// 获取团队供应商策略（必填字段与备注模板）
+#[tauri::command]
+pub fn get_provider_policy() -> Result<crate::services::provider::ProviderPolicy, String> {
+    crate::services::provider::ProviderPolicy::load().map_err(|e| e.to_string())
+}
+
+/// 批量删除供应商
+#[tauri::command]
+pub fn delete_providers(
+    state: State<'_, AppState>,
+    app: String,
+    ids: Vec<String>,
+) -> Result<usize, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::delete_many(state.inner(), app_type, &ids).map_err(|e| e.to_string())
+}
+
+/// 批量为供应商添加标签，返回新增的标签数量
+#[tauri::command]
+pub fn add_tag_to_providers(
+    state: State<'_, AppState>,
+    app: String,
+    ids: Vec<String>,
+    tag: String,
+) -> Result<usize, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::add_tag_to_many(state.inner(), app_type, &ids, &tag).map_err(|e| e.to_string())
+}
+
+/// 将选中的供应商导出为 JSON 文件（键排序，便于比较差异）
+///
+/// 指定 `format`（ccr / opencode / env）时改为导出为对应工具的配置格式。
+#[allow(non_snake_case)]
+#[tauri::command]
+pub fn export_providers_to_file(
+    state: State<'_, AppState>,
+    app: String,
+    ids: Vec<String>,
+    #[allow(non_snake_case)] filePath: String,
+    format: Option<String>,
+) -> Result<usize, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    let format = format
+        .map(|f| ExportFormat::from_str(&f))
+        .transpose()
+        .map_err(|e| e.to_string())?;
+    let providers = ProviderService::export_selected(state.inner(), app_type.clone(), &ids)
+        .map_err(|e| e.to_string())?;
+    if let Some(format) = format {
+        let text = ProviderService::render_export(&app_type, &providers, format);
+        crate::config::atomic_write(
+            std::path::Path::new(&filePath),
+            format!("{}\n", text.trim_end()).as_bytes(),
+        )
+        .map_err(|e| e.to_string())?;
+        return Ok(providers.len());
+    }
+
+    let count = providers.len();
+    let bundle = ExportBundle::new(&app_type, providers);
+    let payload = serde_json::to_value(&bundle).map_err(|e| e.to_string())?;
+    let text = serde_json::to_string_pretty(&crate::database::sort_json_keys(&payload))
+        .map_err(|e| e.to_string())?;
+    crate::config::atomic_write(
+        std::path::Path::new(&filePath),
+        format!("{text}\n").as_bytes(),
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(count)
+}
+
+/// 从导出文件导入供应商（旧格式先升级，再按 JSON Schema 校验），已存在的 ID 跳过
+///
+/// `app` 仅用于没有记录所属应用的旧版供应商映射
+#[allow(non_snake_case)]
+#[tauri::command]
+pub fn import_providers_from_file(
+    state: State<'_, AppState>,
+    #[allow(non_snake_case)] filePath: String,
+    app: Option<String>,
+) -> Result<BundleImport, String> {
+    let app_type = app
+        .as_deref()
+        .map(AppType::from_str)
+        .transpose()
+        .map_err(|e| e.to_string())?;
+    let path = std::path::Path::new(&filePath);
+    let value: serde_json::Value =
+        crate::config::read_json_file(path).map_err(|e| e.to_string())?;
+    ProviderService::import_bundle(state.inner(), &value, app_type).map_err(|e| e.to_string())
+}
+
 /// 切换供应商
 fn switch_provider_internal(state: &AppState, app_type: AppType, id: &str) -> Result<(), AppError> {
     ProviderService::switch(state, app_type, id)
@@ -105,6 +205,24 @@ pub fn import_default_config(state: State<'_, AppState>, app: String) -> Result<
     import_default_config_internal(&state, app_type).map_err(Into::into)
 }
 
+/// 凭据不属于任何已保存供应商的 live 配置
+#[tauri::command]
+pub fn get_unmanaged_live_configs(
+    state: State<'_, AppState>,
+) -> Result<Vec<crate::services::provider::UnmanagedLive>, String> {
+    ProviderService::unmanaged_live(&state).map_err(|e| e.to_string())
+}
+
+/// 把应用的 live 配置导入为新的当前供应商（已属于某个供应商时返回 None）
+#[tauri::command]
+pub fn adopt_live_provider(
+    state: State<'_, AppState>,
+    app: String,
+) -> Result<Option<Provider>, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::adopt_live(&state, app_type).map_err(|e| e.to_string())
+}
+
 /// 查询供应商用量
 #[allow(non_snake_case)]
 #[tauri::command]
@@ -119,6 +237,463 @@ pub async fn queryProviderUsage(
         .map_err(|e| e.to_string())
 }
 
+/// 根据 Base URL 匹配已知供应商，补全缺失的元数据（apply 为 false 时仅预览）
+#[tauri::command]
+pub fn enrich_provider(
+    state: State<'_, AppState>,
+    app: String,
+    id: String,
+    apply: bool,
+) -> Result<crate::services::provider::EnrichResult, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::enrich(state.inner(), app_type, &id, apply).map_err(|e| e.to_string())
+}
+
+/// 校验已保存的供应商配置（app 为空时校验全部应用），仅返回存在问题的供应商
+#[tauri::command]
+pub fn validate_providers(
+    state: State<'_, AppState>,
+    app: Option<String>,
+) -> Result<Vec<crate::services::provider::ProviderValidationReport>, String> {
+    let app_type = app
+        .as_deref()
+        .map(AppType::from_str)
+        .transpose()
+        .map_err(|e| e.to_string())?;
+    ProviderService::validate_all(state.inner(), app_type).map_err(|e| e.to_string())
+}
+
+/// 跨应用查找包含指定 API Key 或端点的供应商（key 与 url 二选一）
+#[tauri::command]
+pub fn find_provider_references(
+    state: State<'_, AppState>,
+    key: Option<String>,
+    url: Option<String>,
+) -> Result<Vec<ProviderReference>, String> {
+    let target = LookupTarget::from_args(key, url).map_err(|e| e.to_string())?;
+    ProviderService::find_references(state.inner(), &target).map_err(|e| e.to_string())
+}
+
+/// 吊销 API Key：target 为密钥本身或供应商 ID，所有应用中使用该密钥的供应商都会被清除密钥
+///
+/// archive 为 true 时同时为受影响的供应商打上 `archived` 标签。
+#[tauri::command]
+pub fn revoke_provider_key(
+    state: State<'_, AppState>,
+    target: String,
+    archive: Option<bool>,
+) -> Result<Vec<ProviderReference>, String> {
+    ProviderService::revoke_key(state.inner(), &target, archive.unwrap_or(false))
+        .map_err(|e| e.to_string())
+}
+
+/// 获取最近的审计记录
+#[tauri::command]
+pub fn get_audit_log(
+    state: State<'_, AppState>,
+    limit: Option<usize>,
+) -> Result<Vec<crate::database::AuditEntry>, String> {
+    state
+        .db
+        .get_audit_log(limit.unwrap_or(100))
+        .map_err(|e| e.to_string())
+}
+
+/// 获取供应商累计用量（经代理转发的请求数与 token 数）
+#[tauri::command]
+pub fn get_provider_usage_counters(
+    state: State<'_, AppState>,
+    app: Option<String>,
+) -> Result<Vec<crate::database::ProviderCounters>, String> {
+    state
+        .db
+        .get_usage_counters(app.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// 获取切换历史（按时间倒序），cwd 过滤该目录及其子目录下发起的切换
+#[tauri::command]
+pub fn get_switch_history(
+    state: State<'_, AppState>,
+    app: Option<String>,
+    cwd: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<crate::services::provider::SwitchRecord>, String> {
+    ProviderService::switch_history(
+        state.inner(),
+        app.as_deref(),
+        cwd.as_deref(),
+        limit.unwrap_or(100),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// 生成使用该供应商的官方 SDK 客户端代码片段（lang: python | node）
+#[tauri::command]
+pub fn get_provider_snippet(
+    state: State<'_, AppState>,
+    app: String,
+    id: String,
+    lang: String,
+) -> Result<String, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    let lang = SnippetLang::from_str(&lang).map_err(|e| e.to_string())?;
+    ProviderService::snippet(state.inner(), app_type, &id, lang).map_err(|e| e.to_string())
+}
+
+/// 检查本地安装的应用版本是否在供应商声明的兼容范围内，返回警告信息（若有）
+#[tauri::command]
+pub async fn check_provider_app_version(
+    state: State<'_, AppState>,
+    app: String,
+    id: String,
+) -> Result<Option<String>, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    let provider = state
+        .db
+        .get_provider_by_id(&id, app_type.as_str())
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("供应商 {id} 不存在"))?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        crate::services::provider::app_version_warning(&app_type, &provider)
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// 复制供应商（可指定新名称与新的 API Key）
+#[allow(non_snake_case)]
+#[tauri::command]
+pub fn clone_provider(
+    state: State<'_, AppState>,
+    app: String,
+    id: String,
+    name: Option<String>,
+    #[allow(non_snake_case)] apiKey: Option<String>,
+) -> Result<Provider, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::clone_provider(
+        state.inner(),
+        app_type,
+        &id,
+        name.as_deref(),
+        apiKey.as_deref(),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// 设置供应商的默认模型（空字符串表示清除）；smallFastModel 仅适用于 Claude
+#[allow(non_snake_case)]
+#[tauri::command]
+pub fn set_provider_model(
+    state: State<'_, AppState>,
+    app: String,
+    id: String,
+    model: String,
+    #[allow(non_snake_case)] smallFastModel: Option<String>,
+) -> Result<Provider, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::set_model(
+        state.inner(),
+        app_type,
+        &id,
+        &model,
+        smallFastModel.as_deref(),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// 获取供应商的环境变量
+#[tauri::command]
+pub fn get_provider_env(
+    state: State<'_, AppState>,
+    app: String,
+    id: String,
+) -> Result<std::collections::BTreeMap<String, String>, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::env(state.inner(), app_type, &id).map_err(|e| e.to_string())
+}
+
+/// 设置/移除供应商的额外环境变量，返回修改后的全部变量
+#[tauri::command]
+pub fn set_provider_env(
+    state: State<'_, AppState>,
+    app: String,
+    id: String,
+    set: Option<std::collections::BTreeMap<String, String>>,
+    unset: Option<Vec<String>>,
+) -> Result<std::collections::BTreeMap<String, String>, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::set_env(
+        state.inner(),
+        app_type,
+        &id,
+        &set.unwrap_or_default(),
+        &unset.unwrap_or_default(),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// 设置供应商的 HTTP/SOCKS 代理（传空字符串清除，不传保持不变）
+#[allow(non_snake_case)]
+#[tauri::command]
+pub fn set_provider_proxy(
+    state: State<'_, AppState>,
+    app: String,
+    id: String,
+    #[allow(non_snake_case)] httpProxy: Option<String>,
+    #[allow(non_snake_case)] httpsProxy: Option<String>,
+    #[allow(non_snake_case)] noProxy: Option<String>,
+) -> Result<Provider, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    let proxy = crate::services::provider::ProviderProxy {
+        http: httpProxy,
+        https: httpsProxy,
+        no_proxy: noProxy,
+    };
+    ProviderService::set_proxy(state.inner(), app_type, &id, &proxy).map_err(|e| e.to_string())
+}
+
+/// 为供应商添加标签
+#[tauri::command]
+pub fn add_provider_tag(
+    state: State<'_, AppState>,
+    app: String,
+    id: String,
+    tag: String,
+) -> Result<bool, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::add_tag(state.inner(), app_type, &id, &tag)
+        .map(|_| true)
+        .map_err(|e| e.to_string())
+}
+
+/// 移除供应商标签
+#[tauri::command]
+pub fn remove_provider_tag(
+    state: State<'_, AppState>,
+    app: String,
+    id: String,
+    tag: String,
+) -> Result<bool, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::remove_tag(state.inner(), app_type, &id, &tag)
+        .map(|_| true)
+        .map_err(|e| e.to_string())
+}
+
+/// 按标签列出供应商
+#[tauri::command]
+pub fn get_providers_by_tag(
+    state: State<'_, AppState>,
+    app: String,
+    tag: String,
+) -> Result<Vec<Provider>, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::list_by_tag(state.inner(), app_type, &tag).map_err(|e| e.to_string())
+}
+
+/// 全文搜索供应商（名称、备注、分类、端点）
+#[tauri::command]
+pub fn search_providers(
+    state: State<'_, AppState>,
+    app: String,
+    query: String,
+) -> Result<Vec<Provider>, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::search(state.inner(), app_type, &query).map_err(|e| e.to_string())
+}
+
+/// 分页、排序并过滤供应商
+#[tauri::command]
+pub fn query_providers(
+    state: State<'_, AppState>,
+    app: String,
+    options: QueryOptions,
+) -> Result<ProviderPage, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::query(state.inner(), app_type, &options).map_err(|e| e.to_string())
+}
+
+/// 切换到第一个带指定标签的供应商，返回切换后的供应商 ID
+#[tauri::command]
+pub fn switch_provider_by_tag(
+    state: State<'_, AppState>,
+    app: String,
+    tag: String,
+) -> Result<String, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::switch_by_tag(state.inner(), app_type, &tag).map_err(|e| e.to_string())
+}
+
+/// 将供应商设为其分类的默认供应商，返回分类名
+#[tauri::command]
+pub fn set_category_default(
+    state: State<'_, AppState>,
+    app: String,
+    id: String,
+) -> Result<String, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::set_category_default(state.inner(), app_type, &id).map_err(|e| e.to_string())
+}
+
+/// 清除分类的默认供应商
+#[tauri::command]
+pub fn clear_category_default(
+    state: State<'_, AppState>,
+    app: String,
+    category: String,
+) -> Result<bool, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::clear_category_default(state.inner(), app_type, &category)
+        .map(|_| true)
+        .map_err(|e| e.to_string())
+}
+
+/// 获取各分类的默认供应商（分类 -> 供应商 ID）
+#[tauri::command]
+pub fn get_category_defaults(
+    state: State<'_, AppState>,
+    app: String,
+) -> Result<std::collections::BTreeMap<String, String>, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::category_defaults(state.inner(), app_type).map_err(|e| e.to_string())
+}
+
+/// 切换到分类的默认供应商，返回切换后的供应商 ID
+#[tauri::command]
+pub fn switch_provider_by_category(
+    state: State<'_, AppState>,
+    app: String,
+    category: String,
+) -> Result<String, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::switch_by_category(state.inner(), app_type, &category)
+        .map_err(|e| e.to_string())
+}
+
+/// 限时切换：立即切换到指定供应商，duration（如 "2h"、"30m"）到期后自动切回之前的供应商
+#[tauri::command]
+pub fn switch_provider_temporarily(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    app: String,
+    id: String,
+    duration: String,
+) -> Result<TemporarySwitch, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    let duration = TemporarySwitchService::parse_duration(&duration).map_err(|e| e.to_string())?;
+    let record =
+        TemporarySwitchService::start(state.inner(), app_type.clone(), &id, Some(duration), None)
+            .map_err(|e| e.to_string())?;
+    schedule_temporary_revert(app_handle, app_type, record.token, duration);
+    Ok(record)
+}
+
+/// 在 `delay` 后处理限时切换的到期
+fn schedule_temporary_revert(
+    app_handle: tauri::AppHandle,
+    app_type: AppType,
+    token: i64,
+    delay: std::time::Duration,
+) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(delay).await;
+        let _ = tauri::async_runtime::spawn_blocking(move || {
+            revert_temporary_switch(&app_handle, app_type, token)
+        })
+        .await;
+    });
+}
+
+/// 启动时接管数据库中未恢复的限时切换（包括 CLI 发起的）：已到期的立即恢复，其余重新计时
+pub fn resume_temporary_switches(app_handle: &tauri::AppHandle) {
+    let Some(state) = app_handle.try_state::<AppState>() else {
+        return;
+    };
+    let records = match TemporarySwitchService::list(state.inner()) {
+        Ok(records) => records,
+        Err(e) => {
+            log::error!("读取限时切换记录失败: {e}");
+            return;
+        }
+    };
+    let due: Vec<i64> = TemporarySwitchService::due(state.inner())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|record| record.token)
+        .collect();
+    let now = chrono::Utc::now().timestamp_millis();
+    for record in records {
+        let Ok(app_type) = AppType::from_str(&record.app_type) else {
+            continue;
+        };
+        let delay = if due.contains(&record.token) {
+            0
+        } else if let Some(at) = record.revert_at {
+            (at - now).max(0) as u64
+        } else {
+            // 只等待包装命令退出的记录由 CLI 进程负责
+            continue;
+        };
+        schedule_temporary_revert(
+            app_handle.clone(),
+            app_type,
+            record.token,
+            std::time::Duration::from_millis(delay),
+        );
+    }
+}
+
+/// 限时切换到期：仍在使用临时供应商时切回之前的供应商（同时刷新托盘并通知前端）
+fn revert_temporary_switch(app_handle: &tauri::AppHandle, app_type: AppType, token: i64) {
+    let Some(state) = app_handle.try_state::<AppState>() else {
+        return;
+    };
+    match TemporarySwitchService::take_due(state.inner(), &app_type, token) {
+        Ok(Some(from_id)) => {
+            log::info!("限时切换到期，{} 切回供应商 {from_id}", app_type.as_str());
+            if let Err(e) = crate::tray::switch_provider_internal(app_handle, app_type, from_id) {
+                log::error!("限时切换自动恢复失败: {e}");
+            }
+        }
+        Ok(None) => {}
+        Err(e) => log::error!("限时切换自动恢复失败: {e}"),
+    }
+}
+
+/// 获取应用当前未到期的限时切换
+#[tauri::command]
+pub fn get_temporary_switch(
+    state: State<'_, AppState>,
+    app: String,
+) -> Result<Option<TemporarySwitch>, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    TemporarySwitchService::get(state.inner(), &app_type).map_err(|e| e.to_string())
+}
+
+/// 取消限时切换（保留当前供应商，不再自动切回）
+#[tauri::command]
+pub fn cancel_temporary_switch(state: State<'_, AppState>, app: String) -> Result<bool, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    TemporarySwitchService::cancel(state.inner(), &app_type)
+        .map(|record| record.is_some())
+        .map_err(|e| e.to_string())
+}
+
+/// 查询所有已配置用量查询的供应商用量
+#[tauri::command]
+pub async fn query_all_provider_usage(
+    state: State<'_, AppState>,
+    app: String,
+) -> Result<Vec<crate::services::provider::ProviderUsageSummary>, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::query_all_usage(state.inner(), app_type)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 /// 测试用量脚本（使用当前编辑器中的脚本，不保存）
 #[allow(non_snake_case)]
 #[allow(clippy::too_many_arguments)]
@@ -133,12 +708,14 @@ pub async fn testUsageScript(
     #[allow(non_snake_case)] baseUrl: Option<String>,
     #[allow(non_snake_case)] accessToken: Option<String>,
     #[allow(non_snake_case)] userId: Option<String>,
+    language: Option<String>,
 ) -> Result<crate::provider::UsageResult, String> {
     let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
     ProviderService::test_usage_script(
         state.inner(),
         app_type,
         &providerId,
+        language.as_deref().unwrap_or("javascript"),
         &scriptCode,
         timeout.unwrap_or(10),
         apiKey.as_deref(),
@@ -219,6 +796,32 @@ pub fn update_endpoint_last_used(
         .map_err(|e| e.to_string())
 }
 
+/// 测试供应商的基础地址与全部自定义端点，按速度排序返回
+#[tauri::command]
+pub async fn test_provider_endpoints(
+    state: State<'_, AppState>,
+    app: String,
+    #[allow(non_snake_case)] providerId: String,
+) -> Result<Vec<EndpointTiming>, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::test_endpoints(state.inner(), app_type, &providerId)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 选择写入 live 配置的端点，`url` 为空时恢复为基础地址
+#[tauri::command]
+pub fn set_active_endpoint(
+    state: State<'_, AppState>,
+    app: String,
+    #[allow(non_snake_case)] providerId: String,
+    url: Option<String>,
+) -> Result<Provider, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::set_active_endpoint(state.inner(), app_type, &providerId, url)
+        .map_err(|e| e.to_string())
+}
+
 /// 更新多个供应商的排序
 #[tauri::command]
 pub fn update_providers_sort_order(
@@ -229,3 +832,29 @@ pub fn update_providers_sort_order(
     let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
     ProviderService::update_sort_order(state.inner(), app_type, updates).map_err(|e| e.to_string())
 }
+
+/// 按给定顺序重排供应商（未列出的供应商保持相对顺序排在其后）
+#[tauri::command]
+pub fn reorder_providers(
+    state: State<'_, AppState>,
+    app: String,
+    ids: Vec<String>,
+) -> Result<bool, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::reorder(state.inner(), app_type, &ids)
+        .map(|_| true)
+        .map_err(|e| e.to_string())
+}
+
+/// 移动单个供应商（"up" / "down" / { "to": 位置 }），返回新位置（从 0 开始）
+#[tauri::command]
+pub fn move_provider(
+    state: State<'_, AppState>,
+    app: String,
+    id: String,
+    movement: ProviderMove,
+) -> Result<usize, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::move_provider(state.inner(), app_type, &id, movement)
+        .map_err(|e| e.to_string())
+}
@@ -1,10 +1,12 @@
 //! 流式健康检查命令
 
 use crate::app_config::AppType;
+use crate::database::BenchmarkResult;
 use crate::error::AppError;
 use crate::services::stream_check::{
     HealthStatus, StreamCheckConfig, StreamCheckResult, StreamCheckService,
 };
+use crate::services::BenchService;
 use crate::store::AppState;
 use std::collections::HashSet;
 use tauri::State;
@@ -16,9 +18,10 @@ pub async fn stream_check_provider(
     app_type: AppType,
     provider_id: String,
 ) -> Result<StreamCheckResult, AppError> {
-    let config = state.db.get_stream_check_config()?;
+    let db = state.async_db();
+    let config = db.get_stream_check_config().await?;
 
-    let providers = state.db.get_all_providers(app_type.as_str())?;
+    let providers = db.get_all_providers(app_type.as_str().to_string()).await?;
     let provider = providers
         .get(&provider_id)
         .ok_or_else(|| AppError::Message(format!("供应商 {provider_id} 不存在")))?;
@@ -26,14 +29,27 @@ pub async fn stream_check_provider(
     let result = StreamCheckService::check_with_retry(&app_type, provider, &config).await?;
 
     // 记录日志
-    let _ =
-        state
-            .db
-            .save_stream_check_log(&provider_id, &provider.name, app_type.as_str(), &result);
+    let _ = db
+        .save_stream_check_log(
+            provider_id,
+            provider.name.clone(),
+            app_type.as_str().to_string(),
+            result.clone(),
+        )
+        .await;
 
     Ok(result)
 }
 
+/// 延迟基准测试：依次测试应用的所有供应商，按首字节时间排序返回
+#[tauri::command]
+pub async fn benchmark_providers(
+    state: State<'_, AppState>,
+    app_type: AppType,
+) -> Result<Vec<BenchmarkResult>, AppError> {
+    BenchService::run(&state, app_type).await
+}
+
 /// 批量流式健康检查
 #[tauri::command]
 pub async fn stream_check_all_providers(
@@ -41,16 +57,17 @@ pub async fn stream_check_all_providers(
     app_type: AppType,
     proxy_targets_only: bool,
 ) -> Result<Vec<(String, StreamCheckResult)>, AppError> {
-    let config = state.db.get_stream_check_config()?;
-    let providers = state.db.get_all_providers(app_type.as_str())?;
+    let db = state.async_db();
+    let config = db.get_stream_check_config().await?;
+    let providers = db.get_all_providers(app_type.as_str().to_string()).await?;
 
     let mut results = Vec::new();
     let allowed_ids: Option<HashSet<String>> = if proxy_targets_only {
         let mut ids = HashSet::new();
-        if let Ok(Some(current_id)) = state.db.get_current_provider(app_type.as_str()) {
+        if let Ok(Some(current_id)) = db.get_current_provider(app_type.as_str().to_string()).await {
             ids.insert(current_id);
         }
-        if let Ok(queue) = state.db.get_failover_queue(app_type.as_str()) {
+        if let Ok(queue) = db.get_failover_queue(app_type.as_str().to_string()).await {
             for item in queue {
                 ids.insert(item.provider_id);
             }
@@ -80,9 +97,14 @@ pub async fn stream_check_all_providers(
                 retry_count: 0,
             });
 
-        let _ = state
-            .db
-            .save_stream_check_log(&id, &provider.name, app_type.as_str(), &result);
+        let _ = db
+            .save_stream_check_log(
+                id.clone(),
+                provider.name.clone(),
+                app_type.as_str().to_string(),
+                result.clone(),
+            )
+            .await;
 
         results.push((id, result));
     }
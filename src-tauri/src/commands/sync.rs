@@ -0,0 +1,29 @@
+//! 云同步命令
+
+use tauri::State;
+
+use crate::error::AppError;
+use crate::services::sync::{SyncOutcome, SyncStatus};
+use crate::services::SyncService;
+use crate::store::AppState;
+
+/// 上传数据库快照；远端有其他设备的修改时先合并，`force` 为 true 时直接覆盖
+#[tauri::command]
+pub async fn sync_push(
+    state: State<'_, AppState>,
+    force: Option<bool>,
+) -> Result<SyncOutcome, AppError> {
+    SyncService::push(state.inner(), force.unwrap_or(false)).await
+}
+
+/// 下载并应用远端快照
+#[tauri::command]
+pub async fn sync_pull(state: State<'_, AppState>) -> Result<SyncOutcome, AppError> {
+    SyncService::pull(state.inner()).await
+}
+
+/// 比较本地与远端的同步状态
+#[tauri::command]
+pub async fn sync_status(state: State<'_, AppState>) -> Result<SyncStatus, AppError> {
+    SyncService::status(state.inner()).await
+}
@@ -1,6 +1,10 @@
 //! 使用统计相关命令
 
+use std::str::FromStr;
+
+use crate::app_config::AppType;
 use crate::error::AppError;
+use crate::services::provider::{LimitReport, ProviderService};
 use crate::services::usage_stats::*;
 use crate::store::AppState;
 use tauri::State;
@@ -149,6 +153,16 @@ pub fn check_provider_limits(
     state.db.check_provider_limits(&provider_id, &app_type)
 }
 
+/// 所有设置了限额的供应商及其用量（app 为空时包含全部应用）
+#[tauri::command]
+pub fn get_spending_limits(
+    state: State<'_, AppState>,
+    app: Option<String>,
+) -> Result<Vec<LimitReport>, AppError> {
+    let app_type = app.as_deref().map(AppType::from_str).transpose()?;
+    ProviderService::limits_status(&state, app_type)
+}
+
 /// 删除模型定价
 #[tauri::command]
 pub fn delete_model_pricing(state: State<'_, AppState>, model_id: String) -> Result<(), AppError> {
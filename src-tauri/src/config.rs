@@ -2,25 +2,90 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
 
 use crate::error::AppError;
 
+/// 指定应用配置目录的环境变量，用于便携安装、多套配置与测试隔离
+pub const HOME_ENV: &str = "CC_SWITCH_HOME";
+
+/// 数据库文件名
+pub const DATABASE_FILE_NAME: &str = "cc-switch.db";
+
+/// 命令行 `--config-dir` / `--db-path` 指定的路径，进程内全局生效
+#[derive(Debug, Clone, Default)]
+struct PathOverrides {
+    config_dir: Option<PathBuf>,
+    db_path: Option<PathBuf>,
+}
+
+static PATH_OVERRIDES: OnceLock<RwLock<PathOverrides>> = OnceLock::new();
+
+fn path_overrides() -> &'static RwLock<PathOverrides> {
+    PATH_OVERRIDES.get_or_init(|| RwLock::new(PathOverrides::default()))
+}
+
+/// 设置进程内的应用配置目录（优先于 `CC_SWITCH_HOME`）
+pub fn set_config_dir_override(dir: Option<PathBuf>) {
+    if let Ok(mut guard) = path_overrides().write() {
+        guard.config_dir = dir;
+    }
+}
+
+/// 设置进程内的数据库文件路径（默认位于应用配置目录下）
+pub fn set_db_path_override(path: Option<PathBuf>) {
+    if let Ok(mut guard) = path_overrides().write() {
+        guard.db_path = path;
+    }
+}
+
+/// 命令行 `--db-path` 指定的数据库文件路径
+pub fn get_db_path_override() -> Option<PathBuf> {
+    path_overrides()
+        .read()
+        .ok()
+        .and_then(|guard| guard.db_path.clone())
+}
+
+/// 显式指定的应用配置目录：命令行 `--config-dir` 优先，其次为 `CC_SWITCH_HOME`
+pub fn get_home_override() -> Option<PathBuf> {
+    if let Some(dir) = path_overrides()
+        .read()
+        .ok()
+        .and_then(|guard| guard.config_dir.clone())
+    {
+        return Some(dir);
+    }
+    std::env::var_os(HOME_ENV)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+/// 用户主目录
+///
+/// Windows 上优先取 `USERPROFILE`：Claude Code 与 Gemini CLI 通过 Node 的 `os.homedir()` 定位
+/// 配置，它同样先读 `USERPROFILE`，而 `dirs` 只查询系统的用户配置文件目录；其他平台交给
+/// `dirs`（`$HOME`，未设置时查 passwd）。所有应用配置路径都应经由这里解析。
+pub fn home_dir() -> Option<PathBuf> {
+    #[cfg(windows)]
+    if let Some(profile) = std::env::var_os("USERPROFILE").filter(|value| !value.is_empty()) {
+        return Some(PathBuf::from(profile));
+    }
+    dirs::home_dir()
+}
+
 /// 获取 Claude Code 配置目录路径
 pub fn get_claude_config_dir() -> PathBuf {
     if let Some(custom) = crate::settings::get_claude_override_dir() {
         return custom;
     }
 
-    dirs::home_dir()
-        .expect("无法获取用户主目录")
-        .join(".claude")
+    home_dir().expect("无法获取用户主目录").join(".claude")
 }
 
 /// 默认 Claude MCP 配置文件路径 (~/.claude.json)
 pub fn get_default_claude_mcp_path() -> PathBuf {
-    dirs::home_dir()
-        .expect("无法获取用户主目录")
-        .join(".claude.json")
+    home_dir().expect("无法获取用户主目录").join(".claude.json")
 }
 
 fn derive_mcp_path_from_override(dir: &Path) -> Option<PathBuf> {
@@ -63,14 +128,56 @@ pub fn get_claude_settings_path() -> PathBuf {
 }
 
 /// 获取应用配置目录路径 (~/.cc-switch)
+///
+/// 依次取 `--config-dir`、`CC_SWITCH_HOME`、GUI 中设置的目录覆盖，最后回落到默认目录
 pub fn get_app_config_dir() -> PathBuf {
+    if let Some(custom) = get_home_override() {
+        return custom;
+    }
+
     if let Some(custom) = crate::app_store::get_app_config_dir_override() {
         return custom;
     }
 
-    dirs::home_dir()
-        .expect("无法获取用户主目录")
-        .join(".cc-switch")
+    home_dir().expect("无法获取用户主目录").join(".cc-switch")
+}
+
+/// 数据库与数据库备份所在目录
+///
+/// 默认即应用配置目录。设置 perUserDatabase 后改为当前用户的数据目录（Linux 上为
+/// `$XDG_DATA_HOME/cc-switch`，默认 `~/.local/share/cc-switch`）：共享服务器上多个用户
+/// 共用一个应用配置目录时，各自的供应商仍分开保存，live 配置照常写入各自的主目录。
+pub fn get_data_dir() -> PathBuf {
+    if crate::settings::get_settings().per_user_database {
+        if let Some(dir) = dirs::data_dir() {
+            return dir.join("cc-switch");
+        }
+    }
+    get_app_config_dir()
+}
+
+/// 获取数据库文件路径（`--db-path` 优先，默认位于 [`get_data_dir`] 下）
+pub fn get_database_path() -> PathBuf {
+    if let Some(path) = path_overrides()
+        .read()
+        .ok()
+        .and_then(|guard| guard.db_path.clone())
+    {
+        return path;
+    }
+    get_data_dir().join(DATABASE_FILE_NAME)
+}
+
+/// 当前系统用户名（`USER`，Windows 为 `USERNAME`），记录为新建供应商的所有者
+pub fn current_username() -> Option<String> {
+    ["USER", "USERNAME", "LOGNAME"]
+        .into_iter()
+        .find_map(|name| {
+            std::env::var(name)
+                .ok()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        })
 }
 
 /// 获取应用配置文件路径
@@ -132,16 +239,45 @@ pub fn write_text_file(path: &Path, data: &str) -> Result<(), AppError> {
     atomic_write(path, data.as_bytes())
 }
 
+/// [`atomic_write_with`] 的选项
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WriteOptions {
+    /// 替换前把原文件复制为 `<文件名>.bak`
+    pub keep_backup: bool,
+}
+
 /// 原子写入：写入临时文件后 rename 替换，避免半写状态
 pub fn atomic_write(path: &Path, data: &[u8]) -> Result<(), AppError> {
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
+    atomic_write_with(path, data, WriteOptions::default())
+}
+
+/// 原子且持久地写入文件
+///
+/// 临时文件写完后先 fsync（Windows 上为 FlushFileBuffers），再 rename 替换目标文件，Unix 上
+/// 最后 fsync 所在目录，使 rename 本身落盘；崩溃时目标文件要么是旧内容，要么是完整的新内容。
+/// 任何一步失败都会删除临时文件，目标文件保持不变。
+pub fn atomic_write_with(path: &Path, data: &[u8], options: WriteOptions) -> Result<(), AppError> {
+    let tmp = temp_path(path)?;
+    let result = stage_file(path, &tmp, data).and_then(|_| {
+        if options.keep_backup && path.exists() {
+            copy_to_backup(path)?;
+        }
+        replace(&tmp, path)
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
     }
+    result?;
+    sync_parent(path);
+    Ok(())
+}
 
+/// 目标文件旁的临时文件路径（同一目录，保证 rename 不跨文件系统）；同时创建所在目录
+fn temp_path(path: &Path) -> Result<PathBuf, AppError> {
     let parent = path
         .parent()
         .ok_or_else(|| AppError::Config("无效的路径".to_string()))?;
-    let mut tmp = parent.to_path_buf();
+    fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
     let file_name = path
         .file_name()
         .ok_or_else(|| AppError::Config("无效的文件名".to_string()))?
@@ -151,12 +287,20 @@ pub fn atomic_write(path: &Path, data: &[u8]) -> Result<(), AppError> {
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap_or_default()
         .as_nanos();
-    tmp.push(format!("{file_name}.tmp.{ts}"));
+    Ok(parent.join(format!("{file_name}.tmp.{ts}")))
+}
 
+/// 把内容写入临时文件并 fsync，沿用目标文件的权限
+fn stage_file(path: &Path, tmp: &Path, data: &[u8]) -> Result<(), AppError> {
     {
-        let mut f = fs::File::create(&tmp).map_err(|e| AppError::io(&tmp, e))?;
-        f.write_all(data).map_err(|e| AppError::io(&tmp, e))?;
-        f.flush().map_err(|e| AppError::io(&tmp, e))?;
+        let mut f = fs::File::create(tmp).map_err(|e| AppError::io(tmp, e))?;
+        fail_point(WriteStep::Write)
+            .and_then(|_| f.write_all(data))
+            .and_then(|_| f.flush())
+            .map_err(|e| AppError::io(tmp, e))?;
+        fail_point(WriteStep::Sync)
+            .and_then(|_| f.sync_all())
+            .map_err(|e| AppError::io(tmp, e))?;
     }
 
     #[cfg(unix)]
@@ -164,32 +308,237 @@ pub fn atomic_write(path: &Path, data: &[u8]) -> Result<(), AppError> {
         use std::os::unix::fs::PermissionsExt;
         if let Ok(meta) = fs::metadata(path) {
             let perm = meta.permissions().mode();
-            let _ = fs::set_permissions(&tmp, fs::Permissions::from_mode(perm));
+            let _ = fs::set_permissions(tmp, fs::Permissions::from_mode(perm));
         }
     }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
 
-    #[cfg(windows)]
-    {
-        // Windows 上 rename 目标存在会失败，先移除再重命名（尽量接近原子性）
-        if path.exists() {
-            let _ = fs::remove_file(path);
-        }
-        fs::rename(&tmp, path).map_err(|e| AppError::IoContext {
+fn copy_to_backup(path: &Path) -> Result<(), AppError> {
+    let backup = backup_path(path);
+    fail_point(WriteStep::Backup)
+        .and_then(|_| fs::copy(path, &backup))
+        .and_then(|_| fs::File::open(&backup)?.sync_all())
+        .map_err(|e| AppError::io(&backup, e))
+}
+
+/// Windows 上 rename 使用 MoveFileEx(MOVEFILE_REPLACE_EXISTING)，可以直接替换已有文件
+fn replace(tmp: &Path, path: &Path) -> Result<(), AppError> {
+    fail_point(WriteStep::Rename)
+        .and_then(|_| fs::rename(tmp, path))
+        .map_err(|e| AppError::IoContext {
             context: format!("原子替换失败: {} -> {}", tmp.display(), path.display()),
             source: e,
-        })?;
+        })
+}
+
+/// [`WriteOptions::keep_backup`] 保留的上一版文件路径
+pub fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    path.with_file_name(name)
+}
+
+/// fsync 文件所在目录，使 rename 落盘（仅 Unix；失败只记录日志，文件内容已写入）
+fn sync_parent(path: &Path) {
+    #[cfg(unix)]
+    if let Some(parent) = path.parent() {
+        if let Err(e) = fs::File::open(parent).and_then(|dir| dir.sync_all()) {
+            log::warn!("同步目录失败（文件已写入）: {}: {e}", parent.display());
+        }
     }
+    #[cfg(not(unix))]
+    let _ = path;
+}
 
-    #[cfg(not(windows))]
-    {
-        fs::rename(&tmp, path).map_err(|e| AppError::IoContext {
-            context: format!("原子替换失败: {} -> {}", tmp.display(), path.display()),
-            source: e,
-        })?;
+/// [`FileTransaction`] 中单个文件的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FileWriteStatus {
+    Written,
+    /// 内容与现有文件相同，未写入
+    Unchanged,
+    /// 已写入，但因后续文件失败而恢复为原内容
+    RolledBack,
+    Failed,
+    /// 因前面的文件失败而未写入
+    Skipped,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileWriteResult {
+    pub path: PathBuf,
+    pub status: FileWriteStatus,
+}
+
+/// 多个文件的两阶段写入
+///
+/// 一组文件要么全部替换，要么全部保持原样（如 Codex 的 auth.json 与 config.toml）：
+/// 1. 暂存：每个文件写入同目录的临时文件并 fsync，再读回校验；任何失败都只删除临时文件
+/// 2. 提交：依次 rename 替换目标文件；某个文件失败时，已替换的文件恢复为原内容
+///
+/// 结果按文件给出（[`FileWriteResult`]），失败时包含在 [`AppError::ApplyFailed`] 中。
+#[derive(Debug, Default)]
+pub struct FileTransaction {
+    files: Vec<(PathBuf, Vec<u8>)>,
+}
+
+impl FileTransaction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn write(&mut self, path: impl Into<PathBuf>, data: impl Into<Vec<u8>>) -> &mut Self {
+        self.files.push((path.into(), data.into()));
+        self
+    }
+
+    pub fn write_json<T: Serialize>(
+        &mut self,
+        path: impl Into<PathBuf>,
+        data: &T,
+    ) -> Result<&mut Self, AppError> {
+        let json = serde_json::to_string_pretty(data)
+            .map_err(|e| AppError::JsonSerialize { source: e })?;
+        Ok(self.write(path, json))
+    }
+
+    /// 写入全部文件；`options.keep_backup` 时替换前把原文件保留为 `.bak`
+    pub fn commit(self, options: WriteOptions) -> Result<Vec<FileWriteResult>, AppError> {
+        let mut results: Vec<FileWriteResult> = self
+            .files
+            .iter()
+            .map(|(path, _)| FileWriteResult {
+                path: path.clone(),
+                status: FileWriteStatus::Skipped,
+            })
+            .collect();
+
+        // 暂存
+        let mut staged: Vec<(usize, PathBuf, Option<Vec<u8>>)> = Vec::new();
+        for (index, (path, data)) in self.files.iter().enumerate() {
+            let previous = match fs::read(path) {
+                Ok(bytes) => Some(bytes),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+                Err(e) => return Self::abort(&staged, results, index, AppError::io(path, e)),
+            };
+            if previous.as_deref() == Some(data.as_slice()) {
+                results[index].status = FileWriteStatus::Unchanged;
+                continue;
+            }
+            let tmp = match temp_path(path) {
+                Ok(tmp) => tmp,
+                Err(e) => return Self::abort(&staged, results, index, e),
+            };
+            if let Err(e) = stage_file(path, &tmp, data).and_then(|_| verify(&tmp, data)) {
+                let _ = fs::remove_file(&tmp);
+                return Self::abort(&staged, results, index, e);
+            }
+            staged.push((index, tmp, previous));
+        }
+
+        // 提交
+        for (position, (index, tmp, previous)) in staged.iter().enumerate() {
+            let path = &self.files[*index].0;
+            let result = if options.keep_backup && previous.is_some() {
+                copy_to_backup(path).and_then(|_| replace(tmp, path))
+            } else {
+                replace(tmp, path)
+            };
+            if let Err(e) = result {
+                for (_, pending, _) in &staged[position..] {
+                    let _ = fs::remove_file(pending);
+                }
+                results[*index].status = FileWriteStatus::Failed;
+                for (done, _, previous) in &staged[..position] {
+                    let done_path = &self.files[*done].0;
+                    let restored = match previous {
+                        Some(bytes) => atomic_write(done_path, bytes),
+                        None => delete_file(done_path),
+                    };
+                    match restored {
+                        Ok(()) => results[*done].status = FileWriteStatus::RolledBack,
+                        Err(err) => log::error!("回滚 {} 失败: {err}", done_path.display()),
+                    }
+                }
+                return Err(AppError::ApplyFailed {
+                    results,
+                    source: Box::new(e),
+                });
+            }
+            results[*index].status = FileWriteStatus::Written;
+            sync_parent(path);
+        }
+        Ok(results)
+    }
+
+    fn abort(
+        staged: &[(usize, PathBuf, Option<Vec<u8>>)],
+        mut results: Vec<FileWriteResult>,
+        failed: usize,
+        error: AppError,
+    ) -> Result<Vec<FileWriteResult>, AppError> {
+        for (_, tmp, _) in staged {
+            let _ = fs::remove_file(tmp);
+        }
+        results[failed].status = FileWriteStatus::Failed;
+        Err(AppError::ApplyFailed {
+            results,
+            source: Box::new(error),
+        })
+    }
+}
+
+/// 读回暂存的临时文件，确认内容完整
+fn verify(tmp: &Path, data: &[u8]) -> Result<(), AppError> {
+    let written = fail_point(WriteStep::Verify)
+        .and_then(|_| fs::read(tmp))
+        .map_err(|e| AppError::io(tmp, e))?;
+    if written != data {
+        return Err(AppError::Message(format!(
+            "暂存文件内容校验失败: {}",
+            tmp.display()
+        )));
     }
     Ok(())
 }
 
+/// 写入过程中可注入失败的步骤（仅测试）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WriteStep {
+    Write,
+    Sync,
+    Verify,
+    Backup,
+    Rename,
+}
+
+#[cfg(test)]
+thread_local! {
+    /// 在第 n+1 次到达该步骤时失败
+    static FAIL_AT: std::cell::Cell<Option<(WriteStep, usize)>> = const { std::cell::Cell::new(None) };
+}
+
+fn fail_point(step: WriteStep) -> std::io::Result<()> {
+    #[cfg(test)]
+    if let Some((target, skip)) = FAIL_AT.with(|fail| fail.get()) {
+        if target == step {
+            if skip > 0 {
+                FAIL_AT.with(|fail| fail.set(Some((target, skip - 1))));
+            } else {
+                return Err(std::io::Error::other(format!(
+                    "injected failure at {step:?}"
+                )));
+            }
+        }
+    }
+    let _ = step;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -223,6 +572,162 @@ mod tests {
         let override_dir = PathBuf::from("/");
         assert!(derive_mcp_path_from_override(&override_dir).is_none());
     }
+
+    fn write_failing_at(step: Option<WriteStep>, path: &Path, data: &str) -> Result<(), AppError> {
+        FAIL_AT.with(|fail| fail.set(step.map(|step| (step, 0))));
+        let result = atomic_write_with(path, data.as_bytes(), WriteOptions { keep_backup: true });
+        FAIL_AT.with(|fail| fail.set(None));
+        result
+    }
+
+    fn dir_entries(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn atomic_write_failures_leave_target_untouched_and_no_temp_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.json");
+        fs::write(&path, "old").unwrap();
+
+        for step in [
+            WriteStep::Write,
+            WriteStep::Sync,
+            WriteStep::Backup,
+            WriteStep::Rename,
+        ] {
+            assert!(
+                write_failing_at(Some(step), &path, "new").is_err(),
+                "{step:?}"
+            );
+            assert_eq!(fs::read_to_string(&path).unwrap(), "old", "{step:?}");
+            let entries = dir_entries(dir.path());
+            assert!(
+                entries.iter().all(|name| !name.contains(".tmp.")),
+                "{step:?}: {entries:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn atomic_write_keeps_previous_contents_as_bak() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("auth.json");
+
+        write_failing_at(None, &path, "first").unwrap();
+        assert_eq!(dir_entries(dir.path()), vec!["auth.json"]);
+
+        write_failing_at(None, &path, "second").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), "first");
+        assert_eq!(dir_entries(dir.path()), vec!["auth.json", "auth.json.bak"]);
+
+        atomic_write(&path, b"third").unwrap();
+        assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), "first");
+    }
+
+    fn transaction(dir: &Path, auth: &str, config: &str) -> FileTransaction {
+        let mut tx = FileTransaction::new();
+        tx.write(dir.join("auth.json"), auth)
+            .write(dir.join("config.toml"), config);
+        tx
+    }
+
+    #[test]
+    fn transaction_writes_all_files_and_reports_unchanged_ones() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("config.toml"), "model = \"o3\"\n").unwrap();
+
+        let results = transaction(dir.path(), "{}", "model = \"o3\"\n")
+            .commit(WriteOptions::default())
+            .unwrap();
+        let statuses: Vec<_> = results.iter().map(|r| r.status).collect();
+        assert_eq!(
+            statuses,
+            vec![FileWriteStatus::Written, FileWriteStatus::Unchanged]
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("auth.json")).unwrap(),
+            "{}"
+        );
+    }
+
+    #[test]
+    fn transaction_rolls_back_renamed_files_when_a_later_rename_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("auth.json"), "old-auth").unwrap();
+
+        // 第一个文件替换成功，第二个失败：auth.json 恢复原内容，新建的文件不存在
+        FAIL_AT.with(|fail| fail.set(Some((WriteStep::Rename, 1))));
+        let err = transaction(dir.path(), "new-auth", "new-config")
+            .commit(WriteOptions::default())
+            .unwrap_err();
+        FAIL_AT.with(|fail| fail.set(None));
+
+        let AppError::ApplyFailed { results, .. } = err else {
+            panic!("unexpected error: {err}");
+        };
+        let statuses: Vec<_> = results.iter().map(|r| r.status).collect();
+        assert_eq!(
+            statuses,
+            vec![FileWriteStatus::RolledBack, FileWriteStatus::Failed]
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("auth.json")).unwrap(),
+            "old-auth"
+        );
+        assert_eq!(dir_entries(dir.path()), vec!["auth.json"]);
+    }
+
+    #[test]
+    fn transaction_staging_failure_touches_no_target() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("auth.json"), "old-auth").unwrap();
+
+        for step in [WriteStep::Write, WriteStep::Sync, WriteStep::Verify] {
+            FAIL_AT.with(|fail| fail.set(Some((step, 1))));
+            let err = transaction(dir.path(), "new-auth", "new-config")
+                .commit(WriteOptions::default())
+                .unwrap_err();
+            FAIL_AT.with(|fail| fail.set(None));
+
+            let AppError::ApplyFailed { results, .. } = err else {
+                panic!("unexpected error: {err}");
+            };
+            assert_eq!(results[0].status, FileWriteStatus::Skipped, "{step:?}");
+            assert_eq!(results[1].status, FileWriteStatus::Failed, "{step:?}");
+            assert_eq!(dir_entries(dir.path()), vec!["auth.json"], "{step:?}");
+            assert_eq!(
+                fs::read_to_string(dir.path().join("auth.json")).unwrap(),
+                "old-auth"
+            );
+        }
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn home_dir_follows_userprofile_on_windows() {
+        let profile = std::env::var_os("USERPROFILE").expect("USERPROFILE is set on Windows");
+        assert_eq!(home_dir(), Some(PathBuf::from(&profile)));
+        assert_eq!(
+            get_default_claude_mcp_path(),
+            PathBuf::from(&profile).join(".claude.json")
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn derive_mcp_path_from_windows_override() {
+        let override_dir = PathBuf::from(r"D:\profiles\work\.claude");
+        let derived = derive_mcp_path_from_override(&override_dir)
+            .expect("should derive path for drive-letter dir");
+        assert_eq!(derived, PathBuf::from(r"D:\profiles\work\.claude.json"));
+    }
 }
 
 /// 复制文件
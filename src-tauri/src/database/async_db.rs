@@ -0,0 +1,338 @@
+//! 异步数据库接口
+//!
+//! [`Database`] 的 DAO 方法是同步的（rusqlite + Mutex），在 Tauri 等异步运行时中直接调用会
+//! 阻塞工作线程。[`AsyncDatabase`] 把调用交给一个专用的数据库线程执行，通过 channel 取回结果，
+//! 调用方只需 `.await`。
+//!
+//! 每个 DAO 方法都有同名的异步版本，参数改为自有类型（`&str` → `String`、`&T` → `T`）；
+//! 其他操作可用 [`AsyncDatabase::call`] 传入闭包执行。
+
+use std::collections::{BTreeMap, HashMap};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::mpsc;
+use std::sync::Arc;
+
+use indexmap::IndexMap;
+use serde_json::Value;
+use tokio::sync::oneshot;
+
+use super::{
+    AuditEntry, BenchmarkResult, ChangeEvent, ChangeSource, Database, FailoverGroupMember,
+    FailoverQueueItem, PendingRevert, ProviderCounters, ProviderHistoryEntry, ProviderKey,
+    ProviderPage, QueryOptions, SwitchHistoryEntry, Vendor,
+};
+use crate::app_config::McpServer;
+use crate::error::AppError;
+use crate::prompt::Prompt;
+use crate::provider::Provider;
+use crate::proxy::circuit_breaker::CircuitBreakerConfig;
+use crate::proxy::types::{LiveBackup, ProviderHealth, ProxyConfig};
+use crate::services::skill::{SkillRepo, SkillState};
+use crate::services::stream_check::{StreamCheckConfig, StreamCheckResult};
+
+type Job = Box<dyn FnOnce(&Database) + Send>;
+
+/// 在专用线程上执行 DAO 调用的数据库句柄
+///
+/// 克隆开销很小，所有克隆共用同一个数据库线程；最后一个句柄释放后线程退出。
+#[derive(Clone)]
+pub struct AsyncDatabase {
+    db: Arc<Database>,
+    jobs: mpsc::Sender<Job>,
+}
+
+impl AsyncDatabase {
+    /// 为已打开的数据库启动数据库线程
+    pub fn new(db: Arc<Database>) -> Self {
+        let (jobs, queue) = mpsc::channel::<Job>();
+        let worker = Arc::clone(&db);
+        std::thread::Builder::new()
+            .name("cc-switch-db".to_string())
+            .spawn(move || {
+                for job in queue {
+                    // 单个调用 panic 时只影响该调用（其结果通道被丢弃），线程继续服务
+                    if catch_unwind(AssertUnwindSafe(|| job(&worker))).is_err() {
+                        log::error!("数据库调用发生 panic");
+                    }
+                }
+            })
+            .expect("创建数据库线程失败");
+        Self { db, jobs }
+    }
+
+    /// 底层的同步数据库（在已经位于阻塞上下文时使用）
+    pub fn database(&self) -> &Arc<Database> {
+        &self.db
+    }
+
+    /// 在数据库线程上执行 `f`
+    pub async fn call<R, F>(&self, f: F) -> Result<R, AppError>
+    where
+        F: FnOnce(&Database) -> Result<R, AppError> + Send + 'static,
+        R: Send + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+        self.jobs
+            .send(Box::new(move |db| {
+                let _ = tx.send(f(db));
+            }))
+            .map_err(|_| AppError::Database("数据库线程已退出".to_string()))?;
+        rx.await
+            .map_err(|_| AppError::Database("数据库调用未返回结果".to_string()))?
+    }
+}
+
+/// 为 DAO 方法生成异步版本：`名称(参数) -> 返回值 = |db| 调用;`
+macro_rules! async_dao {
+    ($($name:ident($($arg:ident: $ty:ty),*) -> $ret:ty = |$db:ident| $call:expr;)*) => {
+        impl AsyncDatabase {
+            $(
+                #[doc = concat!("[`Database::", stringify!($name), "`] 的异步版本")]
+                pub async fn $name(&self $(, $arg: $ty)*) -> Result<$ret, AppError> {
+                    self.call(move |$db| $call).await
+                }
+            )*
+        }
+    };
+}
+
+async_dao! {
+    // ===== 供应商 =====
+    get_all_providers(app_type: String) -> IndexMap<String, Provider> =
+        |db| db.get_all_providers(&app_type);
+    query_providers(app_type: String, options: QueryOptions) -> ProviderPage =
+        |db| db.query_providers(&app_type, &options);
+    get_current_provider(app_type: String) -> Option<String> =
+        |db| db.get_current_provider(&app_type);
+    get_provider_by_id(id: String, app_type: String) -> Option<Provider> =
+        |db| db.get_provider_by_id(&id, &app_type);
+    save_provider(app_type: String, provider: Provider) -> () =
+        |db| db.save_provider(&app_type, &provider);
+    save_provider_as(app_type: String, provider: Provider, source: ChangeSource) -> () =
+        |db| db.save_provider_as(&app_type, &provider, source);
+    get_provider_timestamps(app_type: String) -> HashMap<String, i64> =
+        |db| db.get_provider_timestamps(&app_type);
+    set_provider_updated_at(app_type: String, id: String, updated_at: i64) -> () =
+        |db| db.set_provider_updated_at(&app_type, &id, updated_at);
+    latest_provider_change() -> Option<i64> = |db| db.latest_provider_change();
+    clone_provider(app_type: String, source_id: String, name: Option<String>) -> Provider =
+        |db| db.clone_provider(&app_type, &source_id, name.as_deref());
+    add_tag(app_type: String, provider_id: String, tag: String) -> () =
+        |db| db.add_tag(&app_type, &provider_id, &tag);
+    remove_tag(app_type: String, provider_id: String, tag: String) -> () =
+        |db| db.remove_tag(&app_type, &provider_id, &tag);
+    set_provider_pinned(app_type: String, provider_id: String, pinned: bool) -> bool =
+        |db| db.set_provider_pinned(&app_type, &provider_id, pinned);
+    set_provider_alias(app_type: String, provider_id: String, alias: Option<String>) -> bool =
+        |db| db.set_provider_alias(&app_type, &provider_id, alias.as_deref());
+    resolve_provider_id(app_type: String, id_or_alias: String) -> Option<String> =
+        |db| db.resolve_provider_id(&app_type, &id_or_alias);
+    get_provider_aliases(app_type: String) -> HashMap<String, String> =
+        |db| db.get_provider_aliases(&app_type);
+    get_provider_owners(app_type: String) -> HashMap<String, String> =
+        |db| db.get_provider_owners(&app_type);
+    get_pinned_provider_ids(app_type: String) -> Vec<String> =
+        |db| db.get_pinned_provider_ids(&app_type);
+    find_by_tag(app_type: String, tag: String) -> Vec<Provider> =
+        |db| db.find_by_tag(&app_type, &tag);
+    find_provider_matching_config(app_type: String, settings_config: Value) -> Option<Provider> =
+        |db| db.find_provider_matching_config(&app_type, &settings_config);
+    list_tags(app_type: String) -> Vec<String> = |db| db.list_tags(&app_type);
+    search_providers(app_type: String, query: String) -> Vec<Provider> =
+        |db| db.search_providers(&app_type, &query);
+    set_category_default(app_type: String, provider_id: String) -> String =
+        |db| db.set_category_default(&app_type, &provider_id);
+    clear_category_default(app_type: String, category: String) -> () =
+        |db| db.clear_category_default(&app_type, &category);
+    get_category_default(app_type: String, category: String) -> Option<String> =
+        |db| db.get_category_default(&app_type, &category);
+    get_category_defaults(app_type: String) -> BTreeMap<String, String> =
+        |db| db.get_category_defaults(&app_type);
+    delete_provider(app_type: String, id: String) -> () =
+        |db| db.delete_provider(&app_type, &id);
+    reorder_providers(app_type: String, ordered_ids: Vec<String>) -> () =
+        |db| db.reorder_providers(&app_type, &ordered_ids);
+    delete_providers(app_type: String, ids: Vec<String>) -> usize =
+        |db| db.delete_providers(&app_type, &ids);
+    merge_providers(app_type: String, keep_id: String, merged_ids: Vec<String>, notes: Option<String>) -> () =
+        |db| db.merge_providers(&app_type, &keep_id, &merged_ids, notes.as_deref());
+    add_tag_to_providers(app_type: String, ids: Vec<String>, tag: String) -> usize =
+        |db| db.add_tag_to_providers(&app_type, &ids, &tag);
+    set_current_provider(app_type: String, id: String) -> () =
+        |db| db.set_current_provider(&app_type, &id);
+    update_provider_settings_config(app_type: String, provider_id: String, settings_config: Value) -> () =
+        |db| db.update_provider_settings_config(&app_type, &provider_id, &settings_config);
+    add_custom_endpoint(app_type: String, provider_id: String, url: String) -> () =
+        |db| db.add_custom_endpoint(&app_type, &provider_id, &url);
+    remove_custom_endpoint(app_type: String, provider_id: String, url: String) -> () =
+        |db| db.remove_custom_endpoint(&app_type, &provider_id, &url);
+    record_endpoint_test(app_type: String, provider_id: String, url: String, latency_ms: Option<u64>) -> () =
+        |db| db.record_endpoint_test(&app_type, &provider_id, &url, latency_ms);
+    mark_endpoint_used(app_type: String, provider_id: String, url: String) -> () =
+        |db| db.mark_endpoint_used(&app_type, &provider_id, &url);
+
+    // ===== 供应商历史、密钥与服务商 =====
+    get_provider_history(app_type: String, provider_id: String) -> Vec<ProviderHistoryEntry> =
+        |db| db.get_provider_history(&app_type, &provider_id);
+    get_provider_keys(app_type: String, provider_id: String) -> Vec<ProviderKey> =
+        |db| db.get_provider_keys(&app_type, &provider_id);
+    add_provider_key(app_type: String, provider_id: String, api_key: String, label: Option<String>, weight: u32) -> bool =
+        |db| db.add_provider_key(&app_type, &provider_id, &api_key, label.as_deref(), weight);
+    remove_provider_key(app_type: String, provider_id: String, key_or_id: String) -> bool =
+        |db| db.remove_provider_key(&app_type, &provider_id, &key_or_id);
+    mark_provider_key_used(id: i64) -> () = |db| db.mark_provider_key_used(id);
+    get_vendors() -> Vec<Vendor> = |db| db.get_vendors();
+    get_vendor(id: String) -> Option<Vendor> = |db| db.get_vendor(&id);
+    save_vendor(vendor: Vendor) -> () = |db| db.save_vendor(&vendor);
+    delete_vendor(id: String) -> bool = |db| db.delete_vendor(&id);
+    set_provider_vendor(app_type: String, provider_id: String, vendor_id: Option<String>) -> bool =
+        |db| db.set_provider_vendor(&app_type, &provider_id, vendor_id.as_deref());
+    get_provider_vendors(app_type: Option<String>) -> HashMap<(String, String), String> =
+        |db| db.get_provider_vendors(app_type.as_deref());
+
+    // ===== 故障转移 =====
+    get_failover_queue(app_type: String) -> Vec<FailoverQueueItem> =
+        |db| db.get_failover_queue(&app_type);
+    get_failover_providers(app_type: String) -> Vec<Provider> =
+        |db| db.get_failover_providers(&app_type);
+    add_to_failover_queue(app_type: String, provider_id: String) -> () =
+        |db| db.add_to_failover_queue(&app_type, &provider_id);
+    remove_from_failover_queue(app_type: String, provider_id: String) -> () =
+        |db| db.remove_from_failover_queue(&app_type, &provider_id);
+    clear_failover_queue(app_type: String) -> () = |db| db.clear_failover_queue(&app_type);
+    is_in_failover_queue(app_type: String, provider_id: String) -> bool =
+        |db| db.is_in_failover_queue(&app_type, &provider_id);
+    set_provider_failover_group(app_type: String, provider_id: String, group: Option<String>, priority: Option<i64>) -> () =
+        |db| db.set_provider_failover_group(&app_type, &provider_id, group.as_deref(), priority);
+    get_provider_failover_group(app_type: String, provider_id: String) -> Option<String> =
+        |db| db.get_provider_failover_group(&app_type, &provider_id);
+    get_failover_group_members(app_type: String, group: String) -> Vec<FailoverGroupMember> =
+        |db| db.get_failover_group_members(&app_type, &group);
+    get_available_providers_for_failover(app_type: String) -> Vec<Provider> =
+        |db| db.get_available_providers_for_failover(&app_type);
+
+    // ===== 用量、基准测试、历史与审计 =====
+    increment_usage_counters(app_type: String, provider_id: String, requests: u64, tokens: u64) -> () =
+        |db| db.increment_usage_counters(&app_type, &provider_id, requests, tokens);
+    flush_usage_counters() -> () = |db| db.flush_usage_counters();
+    get_usage_counters(app_type: Option<String>) -> Vec<ProviderCounters> =
+        |db| db.get_usage_counters(app_type.as_deref());
+    get_provider_usage_counters(app_type: String, provider_id: String) -> ProviderCounters =
+        |db| db.get_provider_usage_counters(&app_type, &provider_id);
+    record_benchmark(result: BenchmarkResult) -> i64 = |db| db.record_benchmark(&result);
+    get_recent_benchmarks(app_type: String, since: i64) -> HashMap<String, BenchmarkResult> =
+        |db| db.get_recent_benchmarks(&app_type, since);
+    record_switch(app_type: String, provider_id: String, provider_name: String, cwd: Option<String>) -> i64 =
+        |db| db.record_switch(&app_type, &provider_id, &provider_name, cwd.as_deref());
+    get_switch_history(app_type: Option<String>) -> Vec<SwitchHistoryEntry> =
+        |db| db.get_switch_history(app_type.as_deref());
+    record_audit(action: String, app_type: Option<String>, provider_id: Option<String>, detail: Option<String>) -> i64 =
+        |db| db.record_audit(&action, app_type.as_deref(), provider_id.as_deref(), detail.as_deref());
+    get_audit_log(limit: usize) -> Vec<AuditEntry> = |db| db.get_audit_log(limit);
+    latest_change_id() -> i64 = |db| db.latest_change_id();
+    changes_since(after: i64) -> Vec<ChangeEvent> = |db| db.changes_since(after);
+
+    // ===== 限时切换 =====
+    save_pending_revert(revert: PendingRevert) -> () = |db| db.save_pending_revert(&revert);
+    get_pending_revert(app_type: String) -> Option<PendingRevert> =
+        |db| db.get_pending_revert(&app_type);
+    list_pending_reverts() -> Vec<PendingRevert> = |db| db.list_pending_reverts();
+    take_pending_revert(app_type: String, token: Option<i64>) -> Option<PendingRevert> =
+        |db| db.take_pending_revert(&app_type, token);
+
+    // ===== MCP、提示词与 Skills =====
+    get_all_mcp_servers() -> IndexMap<String, McpServer> = |db| db.get_all_mcp_servers();
+    save_mcp_server(server: McpServer) -> () = |db| db.save_mcp_server(&server);
+    delete_mcp_server(id: String) -> () = |db| db.delete_mcp_server(&id);
+    get_prompts(app_type: String) -> IndexMap<String, Prompt> = |db| db.get_prompts(&app_type);
+    save_prompt(app_type: String, prompt: Prompt) -> () = |db| db.save_prompt(&app_type, &prompt);
+    delete_prompt(app_type: String, id: String) -> () = |db| db.delete_prompt(&app_type, &id);
+    get_skills() -> IndexMap<String, SkillState> = |db| db.get_skills();
+    update_skill_state(key: String, state: SkillState) -> () =
+        |db| db.update_skill_state(&key, &state);
+    get_skill_repos() -> Vec<SkillRepo> = |db| db.get_skill_repos();
+    save_skill_repo(repo: SkillRepo) -> () = |db| db.save_skill_repo(&repo);
+    delete_skill_repo(owner: String, name: String) -> () =
+        |db| db.delete_skill_repo(&owner, &name);
+    init_default_skill_repos() -> usize = |db| db.init_default_skill_repos();
+
+    // ===== 设置 =====
+    get_setting(key: String) -> Option<String> = |db| db.get_setting(&key);
+    set_setting(key: String, value: String) -> () = |db| db.set_setting(&key, &value);
+    get_config_snippet(app_type: String) -> Option<String> =
+        |db| db.get_config_snippet(&app_type);
+    set_config_snippet(app_type: String, snippet: Option<String>) -> () =
+        |db| db.set_config_snippet(&app_type, snippet);
+    get_proxy_takeover_enabled(app_type: String) -> bool =
+        |db| db.get_proxy_takeover_enabled(&app_type);
+    set_proxy_takeover_enabled(app_type: String, enabled: bool) -> () =
+        |db| db.set_proxy_takeover_enabled(&app_type, enabled);
+    has_any_proxy_takeover() -> bool = |db| db.has_any_proxy_takeover();
+    clear_all_proxy_takeover() -> () = |db| db.clear_all_proxy_takeover();
+
+    // ===== 流式健康检查 =====
+    save_stream_check_log(provider_id: String, provider_name: String, app_type: String, result: StreamCheckResult) -> i64 =
+        |db| db.save_stream_check_log(&provider_id, &provider_name, &app_type, &result);
+    get_stream_check_config() -> StreamCheckConfig = |db| db.get_stream_check_config();
+    save_stream_check_config(config: StreamCheckConfig) -> () =
+        |db| db.save_stream_check_config(&config);
+
+    // ===== 代理（同步实现的 async 方法同样移到数据库线程） =====
+    get_proxy_config() -> ProxyConfig = |db| futures::executor::block_on(db.get_proxy_config());
+    update_proxy_config(config: ProxyConfig) -> () =
+        |db| futures::executor::block_on(db.update_proxy_config(config));
+    set_live_takeover_active(active: bool) -> () =
+        |db| futures::executor::block_on(db.set_live_takeover_active(active));
+    is_live_takeover_active() -> bool =
+        |db| futures::executor::block_on(db.is_live_takeover_active());
+    get_provider_health(provider_id: String, app_type: String) -> ProviderHealth =
+        |db| futures::executor::block_on(db.get_provider_health(&provider_id, &app_type));
+    get_provider_health_map(app_type: String) -> HashMap<String, ProviderHealth> =
+        |db| db.get_provider_health_map(&app_type);
+    reset_provider_health(provider_id: String, app_type: String) -> () =
+        |db| futures::executor::block_on(db.reset_provider_health(&provider_id, &app_type));
+    clear_provider_health_for_app(app_type: String) -> () =
+        |db| futures::executor::block_on(db.clear_provider_health_for_app(&app_type));
+    clear_all_provider_health() -> () =
+        |db| futures::executor::block_on(db.clear_all_provider_health());
+    get_circuit_breaker_config() -> CircuitBreakerConfig =
+        |db| futures::executor::block_on(db.get_circuit_breaker_config());
+    update_circuit_breaker_config(config: CircuitBreakerConfig) -> () =
+        |db| futures::executor::block_on(db.update_circuit_breaker_config(&config));
+    save_live_backup(app_type: String, config_json: String) -> () =
+        |db| futures::executor::block_on(db.save_live_backup(&app_type, &config_json));
+    has_any_live_backup() -> bool = |db| futures::executor::block_on(db.has_any_live_backup());
+    get_live_backup(app_type: String) -> Option<LiveBackup> =
+        |db| futures::executor::block_on(db.get_live_backup(&app_type));
+    delete_live_backup(app_type: String) -> () =
+        |db| futures::executor::block_on(db.delete_live_backup(&app_type));
+    delete_all_live_backups() -> () =
+        |db| futures::executor::block_on(db.delete_all_live_backups());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn dao_calls_run_on_the_database_thread() {
+        let db = AsyncDatabase::new(Arc::new(Database::memory().expect("create memory db")));
+        let provider = Provider::with_id("a".to_string(), "A".to_string(), json!({}), None);
+        db.save_provider("claude".to_string(), provider)
+            .await
+            .expect("save provider");
+
+        let providers = db
+            .get_all_providers("claude".to_string())
+            .await
+            .expect("read providers");
+        assert_eq!(providers["a"].name, "A");
+
+        let thread = db
+            .call(|_| Ok(std::thread::current().name().map(str::to_string)))
+            .await
+            .expect("call");
+        assert_eq!(thread.as_deref(), Some("cc-switch-db"));
+    }
+}
@@ -1,25 +1,68 @@
 //! 数据库备份和恢复
 //!
-//! 提供 SQL 导出/导入和二进制快照备份功能。
+//! 提供 SQL 导出/导入和二进制快照备份功能。备份保存在 `~/.cc-switch/backups`：
+//! 导入与恢复前自动生成 `db_backup_*`，启动时按设置的间隔生成 `db_auto_*`，
+//! 两者共用同一保留策略（数量与总大小，见 [`crate::settings::BackupSettings`]）。
+//!
+//! SQL 导出是确定性的：对象与数据行按固定顺序输出，JSON 字段按键排序，
+//! 便于将导出文件提交到 dotfiles 仓库并在多台机器间获得最小的 diff。
 
-use super::{lock_conn, Database, DB_BACKUP_RETAIN};
-use crate::config::get_app_config_dir;
+use super::{lock_conn, Database, DB_BACKUP_INTERVAL_HOURS, DB_BACKUP_RETAIN, SCHEMA_VERSION};
+use crate::config::get_data_dir;
 use crate::error::AppError;
+use crate::operation_lock::OperationLock;
 use chrono::Utc;
 use rusqlite::backup::Backup;
 use rusqlite::types::ValueRef;
-use rusqlite::Connection;
+use rusqlite::{Connection, OpenFlags};
+use serde::Serialize;
+use serde_json::Value;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 use tempfile::NamedTempFile;
 
 const CC_SWITCH_SQL_EXPORT_HEADER: &str = "-- CC Switch SQLite 导出";
 
+/// 导入、恢复前生成的备份
+const MANUAL_BACKUP_PREFIX: &str = "db_backup";
+/// 启动时按间隔生成的备份
+const AUTO_BACKUP_PREFIX: &str = "db_auto";
+
+/// `~/.cc-switch/backups` 中的一个数据库备份
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupInfo {
+    /// 文件名（不含 `.db`）
+    pub id: String,
+    pub path: PathBuf,
+    /// 字节数
+    pub size: u64,
+    /// 文件修改时间（Unix 毫秒）
+    pub created_at: i64,
+}
+
+/// SQL 导出选项
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SqlExportOptions {
+    /// 省略导出头中的生成时间，使相同数据的导出结果逐字节一致
+    pub normalize_timestamps: bool,
+}
+
 impl Database {
     /// 导出为 SQLite 兼容的 SQL 文本
     pub fn export_sql(&self, target_path: &Path) -> Result<(), AppError> {
+        self.export_sql_with_options(target_path, SqlExportOptions::default())
+    }
+
+    /// 按指定选项导出为 SQL 文本
+    pub fn export_sql_with_options(
+        &self,
+        target_path: &Path,
+        options: SqlExportOptions,
+    ) -> Result<(), AppError> {
         let snapshot = self.snapshot_to_memory()?;
-        let dump = Self::dump_sql(&snapshot)?;
+        let dump = Self::dump_sql(&snapshot, options)?;
 
         if let Some(parent) = target_path.parent() {
             fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
@@ -38,11 +81,17 @@ impl Database {
         }
 
         let sql_raw = fs::read_to_string(source_path).map_err(|e| AppError::io(source_path, e))?;
+        self.import_sql_str(&sql_raw)
+    }
+
+    /// 从 SQL 文本导入（整体替换当前数据），返回生成的备份 ID
+    pub(crate) fn import_sql_str(&self, sql_raw: &str) -> Result<String, AppError> {
+        let _lock = OperationLock::acquire()?;
         let sql_content = sql_raw.trim_start_matches('\u{feff}');
         Self::validate_cc_switch_sql_export(sql_content)?;
 
         // 导入前备份现有数据库
-        let backup_path = self.backup_database_file()?;
+        let backup_path = self.backup_database_file(MANUAL_BACKUP_PREFIX)?;
 
         // 在临时数据库执行导入，确保失败不会污染主库
         let temp_file = NamedTempFile::new().map_err(|e| AppError::IoContext {
@@ -50,8 +99,7 @@ impl Database {
             source: e,
         })?;
         let temp_path = temp_file.path().to_path_buf();
-        let temp_conn =
-            Connection::open(&temp_path).map_err(|e| AppError::Database(e.to_string()))?;
+        let temp_conn = Connection::open(&temp_path).map_err(AppError::from)?;
 
         temp_conn
             .execute_batch(sql_content)
@@ -60,16 +108,14 @@ impl Database {
         // 补齐缺失表/索引并进行基础校验
         Self::create_tables_on_conn(&temp_conn)?;
         Self::apply_schema_migrations_on_conn(&temp_conn)?;
+        Self::rebuild_provider_fts(&temp_conn)?;
         Self::validate_basic_state(&temp_conn)?;
 
         // 使用 Backup 将临时库原子写回主库
         {
             let mut main_conn = lock_conn!(self.conn);
-            let backup = Backup::new(&temp_conn, &mut main_conn)
-                .map_err(|e| AppError::Database(e.to_string()))?;
-            backup
-                .step(-1)
-                .map_err(|e| AppError::Database(e.to_string()))?;
+            let backup = Backup::new(&temp_conn, &mut main_conn).map_err(AppError::from)?;
+            backup.step(-1).map_err(AppError::from)?;
         }
 
         let backup_id = backup_path
@@ -79,18 +125,102 @@ impl Database {
         Ok(backup_id)
     }
 
+    /// 以只读方式打开另一个数据库文件（备份或他人导出的 .db）
+    ///
+    /// 源文件以只读标志打开并整体复制到内存中，再在内存副本上补齐表结构与迁移，
+    /// 因此可以查看旧版本的数据库，且不会修改源文件，也不影响当前使用的数据库。
+    pub fn open_read_only(path: &Path) -> Result<Self, AppError> {
+        if !path.is_file() {
+            return Err(AppError::localized(
+                "database.file_not_found",
+                format!("数据库文件不存在: {}", path.display()),
+                format!("Database file not found: {}", path.display()),
+            ));
+        }
+
+        let source = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+        .map_err(AppError::from)?;
+        let mut conn = Connection::open_in_memory().map_err(AppError::from)?;
+        {
+            let backup = Backup::new(&source, &mut conn).map_err(AppError::from)?;
+            backup.step(-1).map_err(AppError::from)?;
+        }
+        drop(source);
+
+        let is_cc_switch_db: bool = conn
+            .query_row(
+                "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'providers')",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(AppError::from)?;
+        if !is_cc_switch_db {
+            return Err(AppError::localized(
+                "database.not_cc_switch",
+                format!("不是 CC Switch 数据库: {}", path.display()),
+                format!("Not a CC Switch database: {}", path.display()),
+            ));
+        }
+        conn.execute("PRAGMA foreign_keys = ON;", [])
+            .map_err(AppError::from)?;
+
+        let db = Self {
+            conn: Mutex::new(conn),
+            readers: Default::default(),
+            counters: Default::default(),
+            change_source: Default::default(),
+            key_pool_generation: Default::default(),
+        };
+        db.create_tables()?;
+        db.apply_schema_migrations()?;
+        Ok(db)
+    }
+
+    /// 当前数据的确定性 SQL 快照（不含生成时间）
+    pub(crate) fn snapshot_sql(&self) -> Result<String, AppError> {
+        let snapshot = self.snapshot_to_memory()?;
+        Self::dump_sql(
+            &snapshot,
+            SqlExportOptions {
+                normalize_timestamps: true,
+            },
+        )
+    }
+
+    /// 把 SQL 快照载入一个独立的内存数据库，用于查看或合并，不影响当前数据库
+    pub(crate) fn from_sql_snapshot(sql: &str) -> Result<Self, AppError> {
+        let sql = sql.trim_start_matches('\u{feff}');
+        Self::validate_cc_switch_sql_export(sql)?;
+
+        let conn = Connection::open_in_memory().map_err(AppError::from)?;
+        conn.execute_batch(sql)
+            .map_err(|e| AppError::Database(format!("载入 SQL 快照失败: {e}")))?;
+        Self::create_tables_on_conn(&conn)?;
+        Self::apply_schema_migrations_on_conn(&conn)?;
+        Self::rebuild_provider_fts(&conn)?;
+        conn.execute("PRAGMA foreign_keys = ON;", [])
+            .map_err(AppError::from)?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+            readers: Default::default(),
+            counters: Default::default(),
+            change_source: Default::default(),
+            key_pool_generation: Default::default(),
+        })
+    }
+
     /// 创建内存快照以避免长时间持有数据库锁
     pub(crate) fn snapshot_to_memory(&self) -> Result<Connection, AppError> {
         let conn = lock_conn!(self.conn);
-        let mut snapshot =
-            Connection::open_in_memory().map_err(|e| AppError::Database(e.to_string()))?;
+        let mut snapshot = Connection::open_in_memory().map_err(AppError::from)?;
 
         {
-            let backup =
-                Backup::new(&conn, &mut snapshot).map_err(|e| AppError::Database(e.to_string()))?;
-            backup
-                .step(-1)
-                .map_err(|e| AppError::Database(e.to_string()))?;
+            let backup = Backup::new(&conn, &mut snapshot).map_err(AppError::from)?;
+            backup.step(-1).map_err(AppError::from)?;
         }
 
         Ok(snapshot)
@@ -109,21 +239,187 @@ impl Database {
         ))
     }
 
+    /// 备份目录 `~/.cc-switch/backups`（数据库按用户存放时随之移到用户的数据目录）
+    pub fn backup_dir() -> PathBuf {
+        get_data_dir().join("backups")
+    }
+
+    /// 列出全部数据库备份（最新的在前）
+    pub fn list_backups() -> Result<Vec<BackupInfo>, AppError> {
+        let dir = Self::backup_dir();
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(AppError::io(&dir, e)),
+        };
+        let mut backups: Vec<BackupInfo> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let path = entry.path();
+                if path.extension().is_none_or(|ext| ext != "db") {
+                    return None;
+                }
+                let metadata = entry.metadata().ok()?;
+                let created_at = metadata
+                    .modified()
+                    .ok()
+                    .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+                    .map(|age| age.as_millis() as i64)
+                    .unwrap_or_default();
+                Some(BackupInfo {
+                    id: path.file_stem()?.to_string_lossy().into_owned(),
+                    size: metadata.len(),
+                    created_at,
+                    path,
+                })
+            })
+            .collect();
+        backups.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(b.id.cmp(&a.id)));
+        Ok(backups)
+    }
+
+    /// 距离最近一次备份超过设置的间隔时生成一份自动备份（启动时调用）
+    ///
+    /// 间隔设为 0 时关闭；未到间隔时返回 None。
+    pub fn auto_backup(&self) -> Result<Option<BackupInfo>, AppError> {
+        let interval_hours = crate::settings::get_settings()
+            .backup
+            .and_then(|backup| backup.interval_hours)
+            .unwrap_or(DB_BACKUP_INTERVAL_HOURS);
+        if interval_hours == 0 {
+            return Ok(None);
+        }
+        let now = Utc::now().timestamp_millis();
+        let interval_ms = interval_hours.saturating_mul(60 * 60 * 1000) as i64;
+        let latest = Self::list_backups()?.first().map(|b| b.created_at);
+        if latest.is_some_and(|latest| now - latest < interval_ms) {
+            return Ok(None);
+        }
+
+        let Some(path) = self.backup_database_file(AUTO_BACKUP_PREFIX)? else {
+            return Ok(None);
+        };
+        log::info!("已生成自动备份: {}", path.display());
+        Ok(Self::list_backups()?
+            .into_iter()
+            .find(|backup| backup.path == path))
+    }
+
+    /// 按设置的保留策略删除旧备份，返回被删除的备份
+    pub fn prune_backups() -> Result<Vec<BackupInfo>, AppError> {
+        let _lock = OperationLock::acquire()?;
+        let policy = crate::settings::get_settings().backup.unwrap_or_default();
+        let retain = policy.retain_count.unwrap_or(DB_BACKUP_RETAIN).max(1);
+        let max_bytes = policy.max_total_mb.map(|mb| mb.saturating_mul(1024 * 1024));
+
+        let expired = expired_backups(Self::list_backups()?, retain, max_bytes);
+        for backup in &expired {
+            fs::remove_file(&backup.path).map_err(|e| AppError::io(&backup.path, e))?;
+        }
+        Ok(expired)
+    }
+
+    /// 用备份替换当前数据库，返回替换前自动生成的备份 ID
+    ///
+    /// `source` 为备份 ID 或任意 `.db` 文件路径。文件先经过完整性检查与版本检查，
+    /// 并在内存副本上补齐表结构，之后才用 Backup API 一次性写回主库，
+    /// 因此任何一步失败都不会改动当前数据库。
+    pub fn restore_backup(&self, source: &str) -> Result<String, AppError> {
+        let _lock = OperationLock::acquire()?;
+        let path = Self::resolve_backup(source)?;
+        Self::validate_db_file(&path)?;
+        let restored = Self::open_read_only(&path)?;
+        {
+            let conn = lock_conn!(restored.conn);
+            Self::validate_basic_state(&conn)?;
+        }
+
+        let safety = self.backup_database_file(MANUAL_BACKUP_PREFIX)?;
+        {
+            let source_conn = lock_conn!(restored.conn);
+            let mut main_conn = lock_conn!(self.conn);
+            let restore = Backup::new(&source_conn, &mut main_conn).map_err(AppError::from)?;
+            restore.step(-1).map_err(AppError::from)?;
+        }
+
+        Ok(safety
+            .and_then(|p| p.file_stem().map(|s| s.to_string_lossy().to_string()))
+            .unwrap_or_default())
+    }
+
+    /// 备份 ID 或文件路径 → 文件路径
+    fn resolve_backup(source: &str) -> Result<PathBuf, AppError> {
+        let path = Path::new(source);
+        if path.is_file() {
+            return Ok(path.to_path_buf());
+        }
+        let id = source.trim_end_matches(".db");
+        Self::list_backups()?
+            .into_iter()
+            .find(|backup| backup.id == id)
+            .map(|backup| backup.path)
+            .ok_or_else(|| {
+                AppError::localized(
+                    "backup.not_found",
+                    format!("备份不存在: {source}"),
+                    format!("Backup not found: {source}"),
+                )
+            })
+    }
+
+    /// 校验待恢复的 SQLite 文件：完整性检查通过，且不是更新版本的应用写入的
+    pub(crate) fn validate_db_file(path: &Path) -> Result<(), AppError> {
+        let conn = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+        .map_err(AppError::from)?;
+
+        let integrity: Result<Vec<String>, _> =
+            conn.prepare("PRAGMA integrity_check").and_then(|mut stmt| {
+                stmt.query_map([], |row| row.get::<_, String>(0))?
+                    .collect::<Result<Vec<_>, _>>()
+            });
+        match integrity {
+            Ok(rows) if rows == ["ok"] => {}
+            Ok(rows) => {
+                return Err(AppError::localized(
+                    "backup.integrity_failed",
+                    format!("数据库文件已损坏: {}", rows.join("; ")),
+                    format!("The database file is corrupted: {}", rows.join("; ")),
+                ))
+            }
+            Err(e) => {
+                return Err(AppError::localized(
+                    "backup.not_sqlite",
+                    format!("不是有效的 SQLite 数据库: {} ({e})", path.display()),
+                    format!("Not a valid SQLite database: {} ({e})", path.display()),
+                ))
+            }
+        }
+
+        let version = Self::get_user_version(&conn)?;
+        if version > SCHEMA_VERSION {
+            return Err(AppError::SchemaTooNew {
+                found: version,
+                supported: SCHEMA_VERSION,
+            });
+        }
+        Ok(())
+    }
+
     /// 生成一致性快照备份，返回备份文件路径（不存在主库时返回 None）
-    fn backup_database_file(&self) -> Result<Option<PathBuf>, AppError> {
-        let db_path = get_app_config_dir().join("cc-switch.db");
+    fn backup_database_file(&self, prefix: &str) -> Result<Option<PathBuf>, AppError> {
+        let _lock = OperationLock::acquire()?;
+        let db_path = crate::config::get_database_path();
         if !db_path.exists() {
             return Ok(None);
         }
 
-        let backup_dir = db_path
-            .parent()
-            .ok_or_else(|| AppError::Config("无效的数据库路径".to_string()))?
-            .join("backups");
-
+        let backup_dir = Self::backup_dir();
         fs::create_dir_all(&backup_dir).map_err(|e| AppError::io(&backup_dir, e))?;
 
-        let base_id = format!("db_backup_{}", Utc::now().format("%Y%m%d_%H%M%S"));
+        let base_id = format!("{prefix}_{}", Utc::now().format("%Y%m%d_%H%M%S"));
         let mut backup_id = base_id.clone();
         let mut backup_path = backup_dir.join(format!("{backup_id}.db"));
         let mut counter = 1;
@@ -135,59 +431,25 @@ impl Database {
 
         {
             let conn = lock_conn!(self.conn);
-            let mut dest_conn =
-                Connection::open(&backup_path).map_err(|e| AppError::Database(e.to_string()))?;
-            let backup = Backup::new(&conn, &mut dest_conn)
-                .map_err(|e| AppError::Database(e.to_string()))?;
-            backup
-                .step(-1)
-                .map_err(|e| AppError::Database(e.to_string()))?;
+            let mut dest_conn = Connection::open(&backup_path).map_err(AppError::from)?;
+            let backup = Backup::new(&conn, &mut dest_conn).map_err(AppError::from)?;
+            backup.step(-1).map_err(AppError::from)?;
         }
 
-        Self::cleanup_db_backups(&backup_dir)?;
-        Ok(Some(backup_path))
-    }
-
-    /// 清理旧的数据库备份，保留最新的 N 个
-    fn cleanup_db_backups(dir: &Path) -> Result<(), AppError> {
-        let entries = match fs::read_dir(dir) {
-            Ok(iter) => iter
-                .filter_map(|entry| entry.ok())
-                .filter(|entry| {
-                    entry
-                        .path()
-                        .extension()
-                        .map(|ext| ext == "db")
-                        .unwrap_or(false)
-                })
-                .collect::<Vec<_>>(),
-            Err(_) => return Ok(()),
-        };
-
-        if entries.len() <= DB_BACKUP_RETAIN {
-            return Ok(());
+        if let Err(err) = Self::prune_backups() {
+            log::warn!("清理旧数据库备份失败: {err}");
         }
-
-        let remove_count = entries.len().saturating_sub(DB_BACKUP_RETAIN);
-        let mut sorted = entries;
-        sorted.sort_by_key(|entry| entry.metadata().and_then(|m| m.modified()).ok());
-
-        for entry in sorted.into_iter().take(remove_count) {
-            if let Err(err) = fs::remove_file(entry.path()) {
-                log::warn!("删除旧数据库备份失败 {}: {}", entry.path().display(), err);
-            }
-        }
-        Ok(())
+        Ok(Some(backup_path))
     }
 
     /// 基础状态校验
     fn validate_basic_state(conn: &Connection) -> Result<(), AppError> {
         let provider_count: i64 = conn
             .query_row("SELECT COUNT(*) FROM providers", [], |row| row.get(0))
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            .map_err(AppError::from)?;
         let mcp_count: i64 = conn
             .query_row("SELECT COUNT(*) FROM mcp_servers", [], |row| row.get(0))
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            .map_err(AppError::from)?;
 
         if provider_count == 0 && mcp_count == 0 {
             return Err(AppError::Config(
@@ -198,16 +460,19 @@ impl Database {
     }
 
     /// 导出数据库为 SQL 文本
-    fn dump_sql(conn: &Connection) -> Result<String, AppError> {
+    fn dump_sql(conn: &Connection, options: SqlExportOptions) -> Result<String, AppError> {
         let mut output = String::new();
-        let timestamp = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
         let user_version: i64 = conn
             .query_row("PRAGMA user_version;", [], |row| row.get(0))
             .unwrap_or(0);
 
-        output.push_str(&format!(
-            "-- CC Switch SQLite 导出\n-- 生成时间: {timestamp}\n-- user_version: {user_version}\n"
-        ));
+        output.push_str(CC_SWITCH_SQL_EXPORT_HEADER);
+        output.push('\n');
+        if !options.normalize_timestamps {
+            let timestamp = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+            output.push_str(&format!("-- 生成时间: {timestamp}\n"));
+        }
+        output.push_str(&format!("-- user_version: {user_version}\n"));
         output.push_str("PRAGMA foreign_keys=OFF;\n");
         output.push_str(&format!("PRAGMA user_version={user_version};\n"));
         output.push_str("BEGIN TRANSACTION;\n");
@@ -220,27 +485,39 @@ impl Database {
                  WHERE sql NOT NULL AND type IN ('table','index','trigger','view')
                  ORDER BY type='table' DESC, name",
             )
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            .map_err(AppError::from)?;
 
         let mut tables = Vec::new();
-        let mut rows = stmt
-            .query([])
-            .map_err(|e| AppError::Database(e.to_string()))?;
-        while let Some(row) = rows.next().map_err(|e| AppError::Database(e.to_string()))? {
-            let obj_type: String = row.get(0).map_err(|e| AppError::Database(e.to_string()))?;
-            let name: String = row.get(1).map_err(|e| AppError::Database(e.to_string()))?;
-            let sql: String = row.get(3).map_err(|e| AppError::Database(e.to_string()))?;
+        let mut virtual_tables: Vec<String> = Vec::new();
+        let mut rows = stmt.query([]).map_err(AppError::from)?;
+        while let Some(row) = rows.next().map_err(AppError::from)? {
+            let obj_type: String = row.get(0).map_err(AppError::from)?;
+            let name: String = row.get(1).map_err(AppError::from)?;
+            let sql: String = row.get(3).map_err(AppError::from)?;
 
             // 跳过 SQLite 内部对象（如 sqlite_sequence）
             if name.starts_with("sqlite_") {
                 continue;
             }
 
+            // 虚拟表（FTS）的影子表由虚拟表自行创建，数据由触发器重建
+            if obj_type == "table"
+                && virtual_tables
+                    .iter()
+                    .any(|vt| name.starts_with(&format!("{vt}_")))
+            {
+                continue;
+            }
+
             output.push_str(&sql);
             output.push_str(";\n");
 
-            if obj_type == "table" && !name.starts_with("sqlite_") {
-                tables.push(name);
+            if obj_type == "table" {
+                if sql.starts_with("CREATE VIRTUAL TABLE") {
+                    virtual_tables.push(name);
+                } else {
+                    tables.push(name);
+                }
             }
         }
 
@@ -251,19 +528,16 @@ impl Database {
                 continue;
             }
 
+            let order_by = Self::stable_order_by(conn, &table, &columns)?;
             let mut stmt = conn
-                .prepare(&format!("SELECT * FROM \"{table}\""))
-                .map_err(|e| AppError::Database(e.to_string()))?;
-            let mut rows = stmt
-                .query([])
-                .map_err(|e| AppError::Database(e.to_string()))?;
+                .prepare(&format!("SELECT * FROM \"{table}\" ORDER BY {order_by}"))
+                .map_err(AppError::from)?;
+            let mut rows = stmt.query([]).map_err(AppError::from)?;
 
-            while let Some(row) = rows.next().map_err(|e| AppError::Database(e.to_string()))? {
+            while let Some(row) = rows.next().map_err(AppError::from)? {
                 let mut values = Vec::with_capacity(columns.len());
                 for idx in 0..columns.len() {
-                    let value = row
-                        .get_ref(idx)
-                        .map_err(|e| AppError::Database(e.to_string()))?;
+                    let value = row.get_ref(idx).map_err(AppError::from)?;
                     values.push(Self::format_sql_value(value)?);
                 }
 
@@ -283,18 +557,59 @@ impl Database {
         Ok(output)
     }
 
+    /// 生成稳定的行排序子句
+    ///
+    /// 供应商按 sort_index、id 排序（与界面一致）；其他表按主键排序，
+    /// 无主键时按全部列排序。
+    fn stable_order_by(
+        conn: &Connection,
+        table: &str,
+        columns: &[String],
+    ) -> Result<String, AppError> {
+        if table == "providers" {
+            return Ok("app_type, sort_index IS NULL, sort_index, id".to_string());
+        }
+
+        let mut stmt = conn
+            .prepare(&format!("PRAGMA table_info(\"{table}\")"))
+            .map_err(AppError::from)?;
+        let mut pk_columns = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, i64>(5)?, row.get::<_, String>(1)?))
+            })
+            .map_err(AppError::from)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(AppError::from)?
+            .into_iter()
+            .filter(|(pk, _)| *pk > 0)
+            .collect::<Vec<_>>();
+        pk_columns.sort();
+
+        let order_columns: Vec<&String> = if pk_columns.is_empty() {
+            columns.iter().collect()
+        } else {
+            pk_columns.iter().map(|(_, name)| name).collect()
+        };
+
+        Ok(order_columns
+            .into_iter()
+            .map(|c| format!("\"{c}\""))
+            .collect::<Vec<_>>()
+            .join(", "))
+    }
+
     /// 获取表的列名列表
     fn get_table_columns(conn: &Connection, table: &str) -> Result<Vec<String>, AppError> {
         let mut stmt = conn
             .prepare(&format!("PRAGMA table_info(\"{table}\")"))
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            .map_err(AppError::from)?;
         let iter = stmt
             .query_map([], |row| row.get::<_, String>(1))
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            .map_err(AppError::from)?;
 
         let mut columns = Vec::new();
         for col in iter {
-            columns.push(col.map_err(|e| AppError::Database(e.to_string()))?);
+            columns.push(col.map_err(AppError::from)?);
         }
         Ok(columns)
     }
@@ -308,6 +623,7 @@ impl Database {
             ValueRef::Text(t) => {
                 let text = std::str::from_utf8(t)
                     .map_err(|e| AppError::Database(format!("文本字段不是有效的 UTF-8: {e}")))?;
+                let text = canonical_json_text(text);
                 let escaped = text.replace('\'', "''");
                 Ok(format!("'{escaped}'"))
             }
@@ -323,3 +639,61 @@ impl Database {
         }
     }
 }
+
+/// 若文本是由 serde 写入的紧凑 JSON 对象/数组，则按键排序后重新序列化
+///
+/// 只处理能无损往返的文本，其余内容原样返回。
+/// 超出保留策略的备份：最新的 `retain` 个之外的全部，以及使总大小超过
+/// `max_bytes` 的较旧备份（最新的一个始终保留）；`backups` 需按新到旧排序
+pub(crate) fn expired_backups(
+    backups: Vec<BackupInfo>,
+    retain: usize,
+    max_bytes: Option<u64>,
+) -> Vec<BackupInfo> {
+    let mut total = 0u64;
+    backups
+        .into_iter()
+        .enumerate()
+        .filter(|(index, backup)| {
+            total = total.saturating_add(backup.size);
+            *index >= retain || (*index > 0 && max_bytes.is_some_and(|max| total > max))
+        })
+        .map(|(_, backup)| backup)
+        .collect()
+}
+
+fn canonical_json_text(text: &str) -> std::borrow::Cow<'_, str> {
+    use std::borrow::Cow;
+
+    if !(text.starts_with('{') || text.starts_with('[')) {
+        return Cow::Borrowed(text);
+    }
+    let Ok(value) = serde_json::from_str::<Value>(text) else {
+        return Cow::Borrowed(text);
+    };
+    if serde_json::to_string(&value).ok().as_deref() != Some(text) {
+        return Cow::Borrowed(text);
+    }
+    match serde_json::to_string(&sort_json_keys(&value)) {
+        Ok(sorted) => Cow::Owned(sorted),
+        Err(_) => Cow::Borrowed(text),
+    }
+}
+
+/// 递归排序 JSON 对象的键
+pub(crate) fn sort_json_keys(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k.clone(), sort_json_keys(v)))
+                    .collect(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.iter().map(sort_json_keys).collect()),
+        other => other.clone(),
+    }
+}
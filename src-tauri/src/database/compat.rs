@@ -0,0 +1,121 @@
+//! 数据库版本高于当前构建时的兼容模式
+//!
+//! 执行迁移时，`schema_migrations` 的每条记录带上 `compatible_from`：仍能安全使用该版本
+//! 数据库的最低 Schema 版本（见 [`super::SCHEMA_COMPAT_VERSION`]）。另一台机器上较旧的 cc-switch
+//! 打开更新的数据库时，若最新记录的 `compatible_from` 不高于自身的 [`SCHEMA_VERSION`]，
+//! 就以兼容模式打开：不建表、不迁移，连接设为 `query_only` 并记录警告；切换供应商只更新
+//! 已知的列，执行期间临时允许写入。
+//!
+//! 其他情况仍返回 [`AppError::SchemaTooNew`]；全局选项 `--force-schema`（或
+//! `CC_SWITCH_FORCE_SCHEMA=1`）强制以兼容模式打开。
+
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::{lock_conn, Database, SCHEMA_VERSION};
+use crate::error::AppError;
+
+pub const FORCE_SCHEMA_ENV: &str = "CC_SWITCH_FORCE_SCHEMA";
+
+/// `--force-schema`
+static FORCE_SCHEMA: AtomicBool = AtomicBool::new(false);
+
+/// 以兼容模式打开时数据库的版本；0 表示未处于兼容模式
+static COMPAT_VERSION: AtomicI32 = AtomicI32::new(0);
+
+pub fn set_force_schema(enabled: bool) {
+    FORCE_SCHEMA.store(enabled, Ordering::Relaxed);
+}
+
+fn force_schema() -> bool {
+    FORCE_SCHEMA.load(Ordering::Relaxed) || crate::settings::env_flag(FORCE_SCHEMA_ENV)
+}
+
+/// 处于兼容模式时返回数据库的 Schema 版本
+pub fn schema_compat() -> Option<i32> {
+    match COMPAT_VERSION.load(Ordering::Relaxed) {
+        0 => None,
+        version => Some(version),
+    }
+}
+
+/// 兼容模式下临时允许写入，drop 时恢复只读
+#[must_use = "drop 时恢复只读"]
+pub(crate) struct CompatWrites<'a> {
+    db: Option<&'a Database>,
+}
+
+impl Drop for CompatWrites<'_> {
+    fn drop(&mut self) {
+        if let Some(db) = self.db {
+            if let Err(e) = db.set_query_only(true) {
+                log::warn!("恢复兼容模式的只读状态失败: {e}");
+            }
+        }
+    }
+}
+
+impl Database {
+    /// 写入 `version` 版本数据库的构建所声明的最低兼容版本（没有记录时为 None）
+    pub(crate) fn compatible_from_on_conn(
+        conn: &Connection,
+        version: i32,
+    ) -> Result<Option<i32>, AppError> {
+        if !Self::table_exists(conn, "schema_migrations")?
+            || !Self::has_column(conn, "schema_migrations", "compatible_from")?
+        {
+            return Ok(None);
+        }
+        let compatible_from = conn
+            .query_row(
+                "SELECT compatible_from FROM schema_migrations WHERE version = ?1",
+                params![version],
+                |row| row.get::<_, Option<i32>>(0),
+            )
+            .optional()
+            .map_err(AppError::from)?;
+        Ok(compatible_from.flatten())
+    }
+
+    /// 当前构建能否以兼容模式使用版本更新的数据库
+    pub(crate) fn can_open_newer_on_conn(conn: &Connection) -> Result<bool, AppError> {
+        let version = Self::get_user_version(conn)?;
+        Ok(Self::compatible_from_on_conn(conn, version)?
+            .is_some_and(|compatible_from| compatible_from <= SCHEMA_VERSION))
+    }
+
+    /// 数据库版本高于当前构建：可兼容（或强制）时以只读的兼容模式打开
+    pub(crate) fn open_newer(&self, found: i32) -> Result<(), AppError> {
+        let compatible = {
+            let conn = lock_conn!(self.conn);
+            Self::can_open_newer_on_conn(&conn)?
+        };
+        if !compatible && !force_schema() {
+            return Err(AppError::SchemaTooNew {
+                found,
+                supported: SCHEMA_VERSION,
+            });
+        }
+        log::warn!(
+            "数据库版本（v{found}）高于当前构建（v{SCHEMA_VERSION}），以兼容模式只读打开{}",
+            if compatible {
+                ""
+            } else {
+                "（--force-schema）"
+            }
+        );
+        self.set_query_only(true)?;
+        COMPAT_VERSION.store(found, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// 兼容模式下允许本次操作写入数据库（仅用于只更新已知列的切换）；非兼容模式下不做任何事
+    pub(crate) fn compat_writes(&self) -> Result<CompatWrites<'_>, AppError> {
+        if schema_compat().is_none() || crate::settings::is_read_only() {
+            return Ok(CompatWrites { db: None });
+        }
+        self.set_query_only(false)?;
+        Ok(CompatWrites { db: Some(self) })
+    }
+}
@@ -0,0 +1,75 @@
+//! 审计日志 DAO
+//!
+//! 记录吊销密钥等敏感操作，便于事后追溯。日志中不保存密钥原文。
+
+use rusqlite::params;
+use serde::Serialize;
+
+use crate::database::{lock_conn, Database};
+use crate::error::AppError;
+
+/// 一条审计记录
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    pub id: i64,
+    /// 操作类型（如 `key.revoke`）
+    pub action: String,
+    pub app_type: Option<String>,
+    pub provider_id: Option<String>,
+    pub detail: Option<String>,
+    /// Unix 毫秒
+    pub created_at: i64,
+}
+
+impl Database {
+    /// 写入一条审计记录
+    pub fn record_audit(
+        &self,
+        action: &str,
+        app_type: Option<&str>,
+        provider_id: Option<&str>,
+        detail: Option<&str>,
+    ) -> Result<i64, AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.execute(
+            "INSERT INTO audit_log (action, app_type, provider_id, detail, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                action,
+                app_type,
+                provider_id,
+                detail,
+                chrono::Utc::now().timestamp_millis()
+            ],
+        )
+        .map_err(AppError::from)?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// 获取最近的审计记录（按时间倒序）
+    pub fn get_audit_log(&self, limit: usize) -> Result<Vec<AuditEntry>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT id, action, app_type, provider_id, detail, created_at
+                 FROM audit_log ORDER BY id DESC LIMIT ?1",
+            )
+            .map_err(AppError::from)?;
+        let entries = stmt
+            .query_map(params![limit as i64], |row| {
+                Ok(AuditEntry {
+                    id: row.get(0)?,
+                    action: row.get(1)?,
+                    app_type: row.get(2)?,
+                    provider_id: row.get(3)?,
+                    detail: row.get(4)?,
+                    created_at: row.get(5)?,
+                })
+            })
+            .map_err(AppError::from)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(AppError::from)?;
+        Ok(entries)
+    }
+}
@@ -0,0 +1,97 @@
+//! 基准测试结果 DAO
+//!
+//! 每次 `cc-switch bench` 对每个供应商记录一条结果（失败也记录），
+//! 供列表显示近期延迟、`switch --fastest` 选择最快的供应商。
+
+use std::collections::HashMap;
+
+use rusqlite::params;
+use serde::Serialize;
+
+use crate::database::{lock_conn, Database};
+use crate::error::AppError;
+
+/// 一次基准测试的结果
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkResult {
+    pub app_type: String,
+    pub provider_id: String,
+    pub success: bool,
+    /// 首字节时间（毫秒）
+    pub ttfb_ms: Option<u64>,
+    /// 整个流式响应的耗时（毫秒）
+    pub total_ms: Option<u64>,
+    pub output_tokens: Option<u64>,
+    /// 首字节之后的输出速度
+    pub tokens_per_sec: Option<f64>,
+    pub error: Option<String>,
+    /// Unix 毫秒
+    pub created_at: i64,
+}
+
+impl Database {
+    /// 写入一条基准测试结果
+    pub fn record_benchmark(&self, result: &BenchmarkResult) -> Result<i64, AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.execute(
+            "INSERT INTO benchmarks (app_type, provider_id, success, ttfb_ms, total_ms,
+                 output_tokens, tokens_per_sec, error, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                result.app_type,
+                result.provider_id,
+                result.success,
+                result.ttfb_ms.map(|v| v as i64),
+                result.total_ms.map(|v| v as i64),
+                result.output_tokens.map(|v| v as i64),
+                result.tokens_per_sec,
+                result.error,
+                result.created_at
+            ],
+        )
+        .map_err(AppError::from)?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// 每个供应商在 `since`（Unix 毫秒）之后最近一次成功的结果
+    pub fn get_recent_benchmarks(
+        &self,
+        app_type: &str,
+        since: i64,
+    ) -> Result<HashMap<String, BenchmarkResult>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT app_type, provider_id, success, ttfb_ms, total_ms, output_tokens,
+                        tokens_per_sec, error, created_at
+                 FROM benchmarks
+                 WHERE app_type = ?1 AND success = 1 AND created_at >= ?2
+                 ORDER BY created_at ASC, id ASC",
+            )
+            .map_err(AppError::from)?;
+        let rows = stmt
+            .query_map(params![app_type, since], |row| {
+                Ok(BenchmarkResult {
+                    app_type: row.get(0)?,
+                    provider_id: row.get(1)?,
+                    success: row.get(2)?,
+                    ttfb_ms: row.get::<_, Option<i64>>(3)?.map(|v| v as u64),
+                    total_ms: row.get::<_, Option<i64>>(4)?.map(|v| v as u64),
+                    output_tokens: row.get::<_, Option<i64>>(5)?.map(|v| v as u64),
+                    tokens_per_sec: row.get(6)?,
+                    error: row.get(7)?,
+                    created_at: row.get(8)?,
+                })
+            })
+            .map_err(AppError::from)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(AppError::from)?;
+
+        // 按时间正序写入，后面的结果覆盖前面的
+        Ok(rows
+            .into_iter()
+            .map(|result| (result.provider_id.clone(), result))
+            .collect())
+    }
+}
@@ -0,0 +1,139 @@
+//! 数据变更通知
+//!
+//! GUI 与命令行各自打开同一个数据库文件。providers / provider_history 上的触发器把
+//! 供应商的新增、修改、删除与切换写入 `change_log` 表（只保留最近
+//! [`CHANGE_LOG_RETAIN`] 条），因此无论哪个进程写入都会留下记录；订阅者按自增 ID
+//! 轮询新记录即可，查询只走主键索引，开销很小。
+
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::time::Duration;
+
+use rusqlite::params;
+use serde::Serialize;
+
+use crate::database::Database;
+use crate::error::AppError;
+
+/// change_log 表保留的记录数
+pub(crate) const CHANGE_LOG_RETAIN: i64 = 1000;
+
+/// 订阅线程的轮询间隔
+const CHANGE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 变更类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ChangeKind {
+    ProviderAdded,
+    ProviderUpdated,
+    ProviderDeleted,
+    ProviderSwitched,
+}
+
+impl ChangeKind {
+    fn from_str(kind: &str) -> Option<Self> {
+        match kind {
+            "provider_added" => Some(ChangeKind::ProviderAdded),
+            "provider_updated" => Some(ChangeKind::ProviderUpdated),
+            "provider_deleted" => Some(ChangeKind::ProviderDeleted),
+            "provider_switched" => Some(ChangeKind::ProviderSwitched),
+            _ => None,
+        }
+    }
+}
+
+/// 一条变更通知
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeEvent {
+    /// 自增 ID，可作为下次查询的起点
+    pub id: i64,
+    pub kind: ChangeKind,
+    pub app_type: String,
+    pub provider_id: String,
+    /// Unix 毫秒
+    pub changed_at: i64,
+}
+
+impl Database {
+    /// 最新一条变更的 ID（没有记录时为 0）
+    pub fn latest_change_id(&self) -> Result<i64, AppError> {
+        self.with_read(|conn| {
+            conn.query_row("SELECT COALESCE(MAX(id), 0) FROM change_log", [], |row| {
+                row.get(0)
+            })
+            .map_err(AppError::from)
+        })
+    }
+
+    /// ID 大于 `after` 的变更（按 ID 正序）
+    pub fn changes_since(&self, after: i64) -> Result<Vec<ChangeEvent>, AppError> {
+        self.with_read(|conn| {
+            let mut stmt = conn
+                .prepare_cached(
+                    "SELECT id, kind, app_type, provider_id, changed_at
+                     FROM change_log WHERE id > ?1 ORDER BY id ASC",
+                )
+                .map_err(AppError::from)?;
+            let rows = stmt
+                .query_map(params![after], |row| {
+                    Ok((
+                        row.get::<_, i64>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                        row.get::<_, String>(3)?,
+                        row.get::<_, i64>(4)?,
+                    ))
+                })
+                .map_err(AppError::from)?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(AppError::from)?;
+            Ok(rows
+                .into_iter()
+                .filter_map(|(id, kind, app_type, provider_id, changed_at)| {
+                    Some(ChangeEvent {
+                        id,
+                        kind: ChangeKind::from_str(&kind)?,
+                        app_type,
+                        provider_id,
+                        changed_at,
+                    })
+                })
+                .collect())
+        })
+    }
+
+    /// 订阅此后的变更（包括其他进程写入的）
+    ///
+    /// 后台线程定期轮询 change_log，接收端被丢弃或数据库被释放后线程退出。
+    pub fn subscribe_changes(self: &Arc<Self>) -> Result<Receiver<ChangeEvent>, AppError> {
+        let mut last = self.latest_change_id()?;
+        let db = Arc::downgrade(self);
+        let (tx, rx) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("db-changes".to_string())
+            .spawn(move || loop {
+                std::thread::sleep(CHANGE_POLL_INTERVAL);
+                let Some(db) = db.upgrade() else {
+                    return;
+                };
+                let events = match db.changes_since(last) {
+                    Ok(events) => events,
+                    Err(e) => {
+                        log::debug!("轮询数据变更失败: {e}");
+                        continue;
+                    }
+                };
+                drop(db);
+                for event in events {
+                    last = event.id;
+                    if tx.send(event).is_err() {
+                        return;
+                    }
+                }
+            })
+            .map_err(|e| AppError::Message(format!("启动变更订阅线程失败: {e}")))?;
+        Ok(rx)
+    }
+}
@@ -0,0 +1,218 @@
+//! 供应商用量计数器 DAO
+//!
+//! 代理每转发一个请求就累加一次请求数与 token 数。为避免高频写放大以及长时间
+//! 占用数据库锁（阻塞 GUI），增量先在内存中合并，累计到一定次数或间隔后再以
+//! 单个事务批量写入；读取前会先落盘未写入的增量。
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use rusqlite::params;
+use serde::Serialize;
+
+use crate::database::{begin_write, lock_conn, Database};
+use crate::error::AppError;
+
+/// 累计多少次增量后落盘
+const FLUSH_EVERY: u64 = 500;
+/// 距上次落盘超过该间隔时，下一次增量触发落盘
+const FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 一个供应商的累计用量
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderCounters {
+    pub app_type: String,
+    pub provider_id: String,
+    /// 经代理转发的请求数
+    pub requests: u64,
+    /// 记录到的 token 数（输入 + 输出）
+    pub tokens: u64,
+    /// Unix 毫秒
+    pub updated_at: i64,
+}
+
+/// 尚未落盘的增量
+#[derive(Debug)]
+pub(crate) struct CounterBuffer {
+    inner: Mutex<PendingCounters>,
+}
+
+#[derive(Debug)]
+struct PendingCounters {
+    /// (app_type, provider_id) -> (requests, tokens)
+    deltas: HashMap<(String, String), (u64, u64)>,
+    increments: u64,
+    last_flush: Instant,
+}
+
+impl Default for CounterBuffer {
+    fn default() -> Self {
+        Self {
+            inner: Mutex::new(PendingCounters {
+                deltas: HashMap::new(),
+                increments: 0,
+                last_flush: Instant::now(),
+            }),
+        }
+    }
+}
+
+impl CounterBuffer {
+    /// 合并一次增量；达到落盘条件时取出全部增量
+    fn add(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+        requests: u64,
+        tokens: u64,
+    ) -> Option<HashMap<(String, String), (u64, u64)>> {
+        let mut pending = self.inner.lock().ok()?;
+        let entry = pending
+            .deltas
+            .entry((app_type.to_string(), provider_id.to_string()))
+            .or_default();
+        entry.0 += requests;
+        entry.1 += tokens;
+        pending.increments += 1;
+        if pending.increments >= FLUSH_EVERY || pending.last_flush.elapsed() >= FLUSH_INTERVAL {
+            Some(Self::drain(&mut pending))
+        } else {
+            None
+        }
+    }
+
+    fn take(&self) -> HashMap<(String, String), (u64, u64)> {
+        self.inner
+            .lock()
+            .map(|mut pending| Self::drain(&mut pending))
+            .unwrap_or_default()
+    }
+
+    fn drain(pending: &mut PendingCounters) -> HashMap<(String, String), (u64, u64)> {
+        pending.increments = 0;
+        pending.last_flush = Instant::now();
+        std::mem::take(&mut pending.deltas)
+    }
+
+    /// 落盘失败时放回，等待下一次落盘
+    fn restore(&self, deltas: HashMap<(String, String), (u64, u64)>) {
+        if let Ok(mut pending) = self.inner.lock() {
+            for (key, (requests, tokens)) in deltas {
+                let entry = pending.deltas.entry(key).or_default();
+                entry.0 += requests;
+                entry.1 += tokens;
+            }
+        }
+    }
+}
+
+impl Database {
+    /// 累加供应商用量（只写内存，按批落盘）
+    pub fn increment_usage_counters(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+        requests: u64,
+        tokens: u64,
+    ) -> Result<(), AppError> {
+        match self.counters.add(app_type, provider_id, requests, tokens) {
+            Some(deltas) => self.write_counter_deltas(deltas),
+            None => Ok(()),
+        }
+    }
+
+    /// 立即写入所有未落盘的增量
+    pub fn flush_usage_counters(&self) -> Result<(), AppError> {
+        let deltas = self.counters.take();
+        if deltas.is_empty() {
+            return Ok(());
+        }
+        self.write_counter_deltas(deltas)
+    }
+
+    /// 获取供应商用量（按请求数倒序），可按应用过滤
+    pub fn get_usage_counters(
+        &self,
+        app_type: Option<&str>,
+    ) -> Result<Vec<ProviderCounters>, AppError> {
+        self.flush_usage_counters()?;
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT app_type, provider_id, requests, tokens, updated_at
+                 FROM provider_usage_counters
+                 WHERE ?1 IS NULL OR app_type = ?1
+                 ORDER BY requests DESC, provider_id ASC",
+            )
+            .map_err(AppError::from)?;
+        let counters = stmt
+            .query_map(params![app_type], |row| {
+                Ok(ProviderCounters {
+                    app_type: row.get(0)?,
+                    provider_id: row.get(1)?,
+                    requests: row.get::<_, i64>(2)? as u64,
+                    tokens: row.get::<_, i64>(3)? as u64,
+                    updated_at: row.get(4)?,
+                })
+            })
+            .map_err(AppError::from)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(AppError::from)?;
+        Ok(counters)
+    }
+
+    /// 获取单个供应商的用量（没有记录时为零）
+    pub fn get_provider_usage_counters(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+    ) -> Result<ProviderCounters, AppError> {
+        let counters = self.get_usage_counters(Some(app_type))?;
+        Ok(counters
+            .into_iter()
+            .find(|c| c.provider_id == provider_id)
+            .unwrap_or_else(|| ProviderCounters {
+                app_type: app_type.to_string(),
+                provider_id: provider_id.to_string(),
+                ..Default::default()
+            }))
+    }
+
+    fn write_counter_deltas(
+        &self,
+        deltas: HashMap<(String, String), (u64, u64)>,
+    ) -> Result<(), AppError> {
+        let result = (|| {
+            let conn = lock_conn!(self.conn);
+            let tx = begin_write(&conn)?;
+            let now = chrono::Utc::now().timestamp_millis();
+            for ((app_type, provider_id), (requests, tokens)) in &deltas {
+                tx.execute(
+                    "INSERT INTO provider_usage_counters (app_type, provider_id, requests, tokens, updated_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5)
+                     ON CONFLICT(app_type, provider_id) DO UPDATE SET
+                        requests = requests + excluded.requests,
+                        tokens = tokens + excluded.tokens,
+                        updated_at = excluded.updated_at",
+                    params![app_type, provider_id, *requests as i64, *tokens as i64, now],
+                )
+                .map_err(AppError::from)?;
+            }
+            tx.commit().map_err(AppError::from)
+        })();
+        if result.is_err() {
+            self.counters.restore(deltas);
+        }
+        result
+    }
+}
+
+impl Drop for Database {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush_usage_counters() {
+            log::warn!("写入用量计数器失败: {e}");
+        }
+    }
+}
@@ -5,6 +5,7 @@
 use crate::database::{lock_conn, Database};
 use crate::error::AppError;
 use crate::provider::Provider;
+use rusqlite::OptionalExtension;
 use serde::{Deserialize, Serialize};
 
 /// 故障转移队列条目（简化版，用于前端展示）
@@ -16,19 +17,29 @@ pub struct FailoverQueueItem {
     pub sort_index: Option<usize>,
 }
 
+/// 故障转移组成员（按 failover_priority 排序的备用供应商链）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailoverGroupMember {
+    pub provider_id: String,
+    pub provider_name: String,
+    pub failover_group: String,
+    pub failover_priority: Option<i64>,
+}
+
 impl Database {
     /// 获取故障转移队列（按 sort_index 排序）
     pub fn get_failover_queue(&self, app_type: &str) -> Result<Vec<FailoverQueueItem>, AppError> {
         let conn = lock_conn!(self.conn);
 
         let mut stmt = conn
-            .prepare(
+            .prepare_cached(
                 "SELECT id, name, sort_index
                  FROM providers
                  WHERE app_type = ?1 AND in_failover_queue = 1
                  ORDER BY COALESCE(sort_index, 999999), id ASC",
             )
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            .map_err(AppError::from)?;
 
         let items = stmt
             .query_map([app_type], |row| {
@@ -38,9 +49,9 @@ impl Database {
                     sort_index: row.get(2)?,
                 })
             })
-            .map_err(|e| AppError::Database(e.to_string()))?
+            .map_err(AppError::from)?
             .collect::<Result<Vec<_>, _>>()
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            .map_err(AppError::from)?;
 
         Ok(items)
     }
@@ -65,7 +76,7 @@ impl Database {
             "UPDATE providers SET in_failover_queue = 1 WHERE id = ?1 AND app_type = ?2",
             rusqlite::params![provider_id, app_type],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         Ok(())
     }
@@ -83,14 +94,14 @@ impl Database {
             "UPDATE providers SET in_failover_queue = 0 WHERE id = ?1 AND app_type = ?2",
             rusqlite::params![provider_id, app_type],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         // 2. 清除该供应商的健康状态（退出队列后不再需要健康监控）
         conn.execute(
             "DELETE FROM provider_health WHERE provider_id = ?1 AND app_type = ?2",
             rusqlite::params![provider_id, app_type],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         log::info!("已从故障转移队列移除供应商 {provider_id} ({app_type}), 并清除其健康状态");
 
@@ -105,7 +116,7 @@ impl Database {
             "UPDATE providers SET in_failover_queue = 0 WHERE app_type = ?1",
             [app_type],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         Ok(())
     }
@@ -129,6 +140,87 @@ impl Database {
         Ok(in_queue)
     }
 
+    /// 设置供应商所属的故障转移组及优先级（group 为 None 时移出分组）
+    pub fn set_provider_failover_group(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+        group: Option<&str>,
+        priority: Option<i64>,
+    ) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+
+        let group = group.map(str::trim).filter(|g| !g.is_empty());
+        let priority = group.and(priority);
+
+        let affected = conn
+            .execute(
+                "UPDATE providers SET failover_group = ?1, failover_priority = ?2
+                 WHERE id = ?3 AND app_type = ?4",
+                rusqlite::params![group, priority, provider_id, app_type],
+            )
+            .map_err(AppError::from)?;
+
+        if affected == 0 {
+            return Err(AppError::provider_not_found(provider_id, app_type));
+        }
+
+        Ok(())
+    }
+
+    /// 获取供应商所属的故障转移组
+    pub fn get_provider_failover_group(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+    ) -> Result<Option<String>, AppError> {
+        let conn = lock_conn!(self.conn);
+
+        let group: Option<Option<String>> = conn
+            .query_row(
+                "SELECT failover_group FROM providers WHERE id = ?1 AND app_type = ?2",
+                rusqlite::params![provider_id, app_type],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(AppError::from)?;
+
+        Ok(group.flatten())
+    }
+
+    /// 获取故障转移组的成员（按优先级升序，未设置优先级的排在最后）
+    pub fn get_failover_group_members(
+        &self,
+        app_type: &str,
+        group: &str,
+    ) -> Result<Vec<FailoverGroupMember>, AppError> {
+        let conn = lock_conn!(self.conn);
+
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT id, name, failover_group, failover_priority
+                 FROM providers
+                 WHERE app_type = ?1 AND failover_group = ?2
+                 ORDER BY COALESCE(failover_priority, 999999), COALESCE(sort_index, 999999), id ASC",
+            )
+            .map_err(AppError::from)?;
+
+        let members = stmt
+            .query_map(rusqlite::params![app_type, group], |row| {
+                Ok(FailoverGroupMember {
+                    provider_id: row.get(0)?,
+                    provider_name: row.get(1)?,
+                    failover_group: row.get(2)?,
+                    failover_priority: row.get(3)?,
+                })
+            })
+            .map_err(AppError::from)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(AppError::from)?;
+
+        Ok(members)
+    }
+
     /// 获取可添加到故障转移队列的供应商（不在队列中的）
     pub fn get_available_providers_for_failover(
         &self,
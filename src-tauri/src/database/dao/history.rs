@@ -0,0 +1,80 @@
+//! 切换历史 DAO
+//!
+//! 每次成功切换记录一条，附带发起切换时的工作目录（如有），用于按项目归集用量。
+
+use rusqlite::params;
+use serde::Serialize;
+
+use crate::database::{lock_conn, Database};
+use crate::error::AppError;
+
+/// 一条切换记录
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SwitchHistoryEntry {
+    pub id: i64,
+    pub app_type: String,
+    pub provider_id: String,
+    pub provider_name: String,
+    /// 发起切换的工作目录（GUI 切换时为空）
+    pub cwd: Option<String>,
+    /// Unix 毫秒
+    pub switched_at: i64,
+}
+
+impl Database {
+    /// 写入一条切换记录
+    pub fn record_switch(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+        provider_name: &str,
+        cwd: Option<&str>,
+    ) -> Result<i64, AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.execute(
+            "INSERT INTO switch_history (app_type, provider_id, provider_name, cwd, switched_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                app_type,
+                provider_id,
+                provider_name,
+                cwd,
+                chrono::Utc::now().timestamp_millis()
+            ],
+        )
+        .map_err(AppError::from)?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// 获取切换历史（按时间正序），可按应用过滤
+    pub fn get_switch_history(
+        &self,
+        app_type: Option<&str>,
+    ) -> Result<Vec<SwitchHistoryEntry>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT id, app_type, provider_id, provider_name, cwd, switched_at
+                 FROM switch_history
+                 WHERE ?1 IS NULL OR app_type = ?1
+                 ORDER BY switched_at ASC, id ASC",
+            )
+            .map_err(AppError::from)?;
+        let entries = stmt
+            .query_map(params![app_type], |row| {
+                Ok(SwitchHistoryEntry {
+                    id: row.get(0)?,
+                    app_type: row.get(1)?,
+                    provider_id: row.get(2)?,
+                    provider_name: row.get(3)?,
+                    cwd: row.get(4)?,
+                    switched_at: row.get(5)?,
+                })
+            })
+            .map_err(AppError::from)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(AppError::from)?;
+        Ok(entries)
+    }
+}
@@ -12,11 +12,11 @@ impl Database {
     /// 获取所有 MCP 服务器
     pub fn get_all_mcp_servers(&self) -> Result<IndexMap<String, McpServer>, AppError> {
         let conn = lock_conn!(self.conn);
-        let mut stmt = conn.prepare(
+        let mut stmt = conn.prepare_cached(
             "SELECT id, name, server_config, description, homepage, docs, tags, enabled_claude, enabled_codex, enabled_gemini
              FROM mcp_servers
              ORDER BY name ASC, id ASC"
-        ).map_err(|e| AppError::Database(e.to_string()))?;
+        ).map_err(AppError::from)?;
 
         let server_iter = stmt
             .query_map([], |row| {
@@ -52,11 +52,11 @@ impl Database {
                     },
                 ))
             })
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            .map_err(AppError::from)?;
 
         let mut servers = IndexMap::new();
         for server_res in server_iter {
-            let (id, server) = server_res.map_err(|e| AppError::Database(e.to_string()))?;
+            let (id, server) = server_res.map_err(AppError::from)?;
             servers.insert(id, server);
         }
         Ok(servers)
@@ -83,7 +83,7 @@ impl Database {
                 server.apps.gemini,
             ],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
         Ok(())
     }
 
@@ -91,7 +91,7 @@ impl Database {
     pub fn delete_mcp_server(&self, id: &str) -> Result<(), AppError> {
         let conn = lock_conn!(self.conn);
         conn.execute("DELETE FROM mcp_servers WHERE id = ?1", params![id])
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            .map_err(AppError::from)?;
         Ok(())
     }
 }
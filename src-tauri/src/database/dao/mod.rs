@@ -2,15 +2,35 @@
 //!
 //! Database access operations for each domain
 
+pub mod audit;
+pub mod benchmarks;
+pub mod change_log;
+pub mod counters;
 pub mod failover;
+pub mod history;
 pub mod mcp;
+pub mod pending_reverts;
 pub mod prompts;
+pub mod provider_history;
+pub mod provider_keys;
 pub mod providers;
 pub mod proxy;
 pub mod settings;
 pub mod skills;
 pub mod stream_check;
+pub mod vendors;
 
 // 所有 DAO 方法都通过 Database impl 提供，无需单独导出
 // 导出 FailoverQueueItem 供外部使用
-pub use failover::FailoverQueueItem;
+pub use audit::AuditEntry;
+pub use benchmarks::BenchmarkResult;
+pub use change_log::{ChangeEvent, ChangeKind};
+pub(crate) use counters::CounterBuffer;
+pub use counters::ProviderCounters;
+pub use failover::{FailoverGroupMember, FailoverQueueItem};
+pub use history::SwitchHistoryEntry;
+pub use pending_reverts::PendingRevert;
+pub use provider_history::{ChangeSource, JsonChange, ProviderHistoryEntry};
+pub use provider_keys::ProviderKey;
+pub use providers::{ProviderPage, ProviderSort, QueryOptions};
+pub use vendors::Vendor;
@@ -0,0 +1,119 @@
+//! 限时切换待恢复记录 DAO
+//!
+//! 限时切换（`switch --temporary`）把切换前的供应商记在这里，到期或包装的命令退出后切回。
+//! 记录保存在数据库中，GUI、CLI 启动的计时进程都能看到并处理同一条记录。
+
+use rusqlite::{params, OptionalExtension, Row};
+use serde::Serialize;
+
+use crate::database::{lock_conn, Database};
+use crate::error::AppError;
+
+/// 一条待恢复记录（每个应用最多一条）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingRevert {
+    pub app_type: String,
+    /// 恢复到的供应商
+    pub from_id: String,
+    /// 临时使用的供应商
+    pub to_id: String,
+    /// 到期时间（Unix 毫秒）；为空时只在包装的命令退出后恢复
+    pub revert_at: Option<i64>,
+    /// 计时凭据：同一应用再次限时切换后，旧的计时器失效
+    pub token: i64,
+    /// 包装命令的 cc-switch 进程；进程已不存在时记录视为到期
+    pub pid: Option<u32>,
+    /// Unix 毫秒
+    pub created_at: i64,
+}
+
+const COLUMNS: &str = "app_type, from_id, to_id, revert_at, token, pid, created_at";
+
+fn from_row(row: &Row<'_>) -> rusqlite::Result<PendingRevert> {
+    Ok(PendingRevert {
+        app_type: row.get(0)?,
+        from_id: row.get(1)?,
+        to_id: row.get(2)?,
+        revert_at: row.get(3)?,
+        token: row.get(4)?,
+        pid: row.get(5)?,
+        created_at: row.get(6)?,
+    })
+}
+
+impl Database {
+    /// 保存待恢复记录，替换该应用已有的记录
+    pub fn save_pending_revert(&self, revert: &PendingRevert) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.execute(
+            &format!("INSERT OR REPLACE INTO pending_reverts ({COLUMNS}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)"),
+            params![
+                revert.app_type,
+                revert.from_id,
+                revert.to_id,
+                revert.revert_at,
+                revert.token,
+                revert.pid,
+                revert.created_at
+            ],
+        )
+        .map_err(AppError::from)?;
+        Ok(())
+    }
+
+    /// 应用的待恢复记录
+    pub fn get_pending_revert(&self, app_type: &str) -> Result<Option<PendingRevert>, AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.query_row(
+            &format!("SELECT {COLUMNS} FROM pending_reverts WHERE app_type = ?1"),
+            params![app_type],
+            from_row,
+        )
+        .optional()
+        .map_err(AppError::from)
+    }
+
+    /// 所有待恢复记录（按到期时间，无到期时间的排在最后）
+    pub fn list_pending_reverts(&self) -> Result<Vec<PendingRevert>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare_cached(&format!(
+                "SELECT {COLUMNS} FROM pending_reverts
+                 ORDER BY revert_at IS NULL, revert_at ASC, app_type ASC"
+            ))
+            .map_err(AppError::from)?;
+        let reverts = stmt
+            .query_map([], from_row)
+            .map_err(AppError::from)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(AppError::from)?;
+        Ok(reverts)
+    }
+
+    /// 删除并返回应用的待恢复记录；`token` 不为空时只在凭据一致时删除
+    pub fn take_pending_revert(
+        &self,
+        app_type: &str,
+        token: Option<i64>,
+    ) -> Result<Option<PendingRevert>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let revert = conn
+            .query_row(
+                &format!("SELECT {COLUMNS} FROM pending_reverts WHERE app_type = ?1"),
+                params![app_type],
+                from_row,
+            )
+            .optional()
+            .map_err(AppError::from)?;
+        let Some(revert) = revert.filter(|r| token.is_none_or(|token| r.token == token)) else {
+            return Ok(None);
+        };
+        conn.execute(
+            "DELETE FROM pending_reverts WHERE app_type = ?1 AND token = ?2",
+            params![app_type, revert.token],
+        )
+        .map_err(AppError::from)?;
+        Ok(Some(revert))
+    }
+}
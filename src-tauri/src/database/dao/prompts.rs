@@ -13,12 +13,12 @@ impl Database {
     pub fn get_prompts(&self, app_type: &str) -> Result<IndexMap<String, Prompt>, AppError> {
         let conn = lock_conn!(self.conn);
         let mut stmt = conn
-            .prepare(
+            .prepare_cached(
                 "SELECT id, name, content, description, enabled, created_at, updated_at
              FROM prompts WHERE app_type = ?1
              ORDER BY created_at ASC, id ASC",
             )
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            .map_err(AppError::from)?;
 
         let prompt_iter = stmt
             .query_map(params![app_type], |row| {
@@ -43,11 +43,11 @@ impl Database {
                     },
                 ))
             })
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            .map_err(AppError::from)?;
 
         let mut prompts = IndexMap::new();
         for prompt_res in prompt_iter {
-            let (id, prompt) = prompt_res.map_err(|e| AppError::Database(e.to_string()))?;
+            let (id, prompt) = prompt_res.map_err(AppError::from)?;
             prompts.insert(id, prompt);
         }
         Ok(prompts)
@@ -71,7 +71,7 @@ impl Database {
                 prompt.updated_at,
             ],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
         Ok(())
     }
 
@@ -82,7 +82,7 @@ impl Database {
             "DELETE FROM prompts WHERE id = ?1 AND app_type = ?2",
             params![id, app_type],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
         Ok(())
     }
 }
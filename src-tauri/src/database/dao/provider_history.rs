@@ -0,0 +1,392 @@
+//! 供应商变更日志 DAO
+//!
+//! 每次保存供应商且内容有变化时，修订号加一，并记录一条变更：时间、来源
+//! （cli / gui / import / sync）以及与上一修订的 JSON 差异。新建供应商时记录的是整个状态
+//! （路径为空的一条变化），日志开始前已存在的供应商在第一次修改时先补记一条 baseline，
+//! 因此从空对象按顺序回放差异即可还原任一修订时的供应商。
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::database::{lock_conn, Database};
+use crate::error::AppError;
+
+/// 变更来源
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeSource {
+    #[default]
+    Gui,
+    Cli,
+    Import,
+    Sync,
+    /// 变更日志开始之前已有的状态
+    Baseline,
+}
+
+impl ChangeSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeSource::Gui => "gui",
+            ChangeSource::Cli => "cli",
+            ChangeSource::Import => "import",
+            ChangeSource::Sync => "sync",
+            ChangeSource::Baseline => "baseline",
+        }
+    }
+}
+
+/// 一个字段的变化；`path` 为 JSON Pointer（空字符串表示整个供应商），
+/// 字段新增时 `before` 为空，删除时 `after` 为空
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonChange {
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after: Option<Value>,
+}
+
+/// 一条供应商变更记录
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderHistoryEntry {
+    pub id: i64,
+    pub app_type: String,
+    pub provider_id: String,
+    pub revision: i64,
+    /// Unix 毫秒
+    pub changed_at: i64,
+    pub source: String,
+    pub changes: Vec<JsonChange>,
+}
+
+impl Database {
+    /// 设置此后保存供应商时记录的默认来源（命令行进程为 cli）
+    pub fn set_change_source(&self, source: ChangeSource) {
+        if let Ok(mut current) = self.change_source.write() {
+            *current = source;
+        }
+    }
+
+    pub(crate) fn change_source(&self) -> ChangeSource {
+        self.change_source
+            .read()
+            .map(|source| *source)
+            .unwrap_or_default()
+    }
+
+    /// 获取供应商的变更记录（按修订号正序）
+    pub fn get_provider_history(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+    ) -> Result<Vec<ProviderHistoryEntry>, AppError> {
+        self.with_read(|conn| {
+            let mut stmt = conn
+                .prepare_cached(
+                    "SELECT id, app_type, provider_id, revision, changed_at, source, diff
+                     FROM provider_history
+                     WHERE app_type = ?1 AND provider_id = ?2
+                     ORDER BY revision ASC, id ASC",
+                )
+                .map_err(AppError::from)?;
+            let entries = stmt
+                .query_map(params![app_type, provider_id], |row| {
+                    let diff: String = row.get(6)?;
+                    Ok(ProviderHistoryEntry {
+                        id: row.get(0)?,
+                        app_type: row.get(1)?,
+                        provider_id: row.get(2)?,
+                        revision: row.get(3)?,
+                        changed_at: row.get(4)?,
+                        source: row.get(5)?,
+                        changes: serde_json::from_str(&diff).unwrap_or_default(),
+                    })
+                })
+                .map_err(AppError::from)?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(AppError::from)?;
+            Ok(entries)
+        })
+    }
+
+    /// 从所有变更记录中抹去一个密钥（吊销密钥后调用），返回改写的记录数
+    ///
+    /// 与吊销本身一致：包含该密钥的字符串中删去密钥，恰为该密钥的数组元素被移除，
+    /// 因此按日志恢复旧修订时不会把已吊销的密钥写回。
+    pub fn scrub_provider_history(&self, secret: &str) -> Result<usize, AppError> {
+        if secret.is_empty() {
+            return Ok(0);
+        }
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare("SELECT id, diff FROM provider_history WHERE instr(diff, ?1) > 0")
+            .map_err(AppError::from)?;
+        let rows = stmt
+            .query_map(params![secret], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(AppError::from)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(AppError::from)?;
+
+        let mut scrubbed = 0;
+        for (id, diff) in rows {
+            let Ok(mut changes) = serde_json::from_str::<Value>(&diff) else {
+                continue;
+            };
+            if !scrub_value(&mut changes, secret) {
+                continue;
+            }
+            conn.execute(
+                "UPDATE provider_history SET diff = ?2 WHERE id = ?1",
+                params![
+                    id,
+                    serde_json::to_string(&changes)
+                        .map_err(|e| AppError::Database(e.to_string()))?
+                ],
+            )
+            .map_err(AppError::from)?;
+            scrubbed += 1;
+        }
+        Ok(scrubbed)
+    }
+}
+
+fn scrub_value(value: &mut Value, secret: &str) -> bool {
+    match value {
+        Value::String(text) if text.contains(secret) => {
+            *text = text.replace(secret, "");
+            true
+        }
+        Value::Object(map) => map.values_mut().fold(false, |changed, child| {
+            scrub_value(child, secret) || changed
+        }),
+        Value::Array(items) => {
+            let before = items.len();
+            items.retain(|item| item.as_str() != Some(secret));
+            let removed = items.len() != before;
+            items.iter_mut().fold(removed, |changed, child| {
+                scrub_value(child, secret) || changed
+            })
+        }
+        _ => false,
+    }
+}
+
+/// 供应商在变更日志中的状态：providers 表中用户可编辑的列
+///
+/// 自定义端点与标签由单独的接口管理，不计入日志。
+pub(crate) fn provider_state(
+    conn: &Connection,
+    app_type: &str,
+    provider_id: &str,
+) -> Result<Option<Value>, AppError> {
+    conn.query_row(
+        "SELECT name, settings_config, website_url, category, sort_index, notes, icon, icon_color, meta
+         FROM providers WHERE id = ?1 AND app_type = ?2",
+        params![provider_id, app_type],
+        |row| {
+            let json_column = |index: usize| -> rusqlite::Result<Value> {
+                let text: Option<String> = row.get(index)?;
+                Ok(text
+                    .and_then(|text| serde_json::from_str(&text).ok())
+                    .unwrap_or(Value::Null))
+            };
+            let mut state = Map::new();
+            state.insert("name".into(), Value::from(row.get::<_, String>(0)?));
+            state.insert("settingsConfig".into(), json_column(1)?);
+            state.insert("websiteUrl".into(), Value::from(row.get::<_, Option<String>>(2)?));
+            state.insert("category".into(), Value::from(row.get::<_, Option<String>>(3)?));
+            state.insert("sortIndex".into(), Value::from(row.get::<_, Option<i64>>(4)?));
+            state.insert("notes".into(), Value::from(row.get::<_, Option<String>>(5)?));
+            state.insert("icon".into(), Value::from(row.get::<_, Option<String>>(6)?));
+            state.insert("iconColor".into(), Value::from(row.get::<_, Option<String>>(7)?));
+            state.insert("meta".into(), json_column(8)?);
+            // 空值视为字段不存在，避免差异中出现 null
+            state.retain(|_, value| !value.is_null());
+            Ok(Value::Object(state))
+        },
+    )
+    .optional()
+    .map_err(AppError::from)
+}
+
+/// 与保存前的状态比较，有变化时递增修订号、更新修改时间并写入一条变更记录
+///
+/// 需在保存供应商的同一事务内调用；返回新的修订号，没有变化时返回 None。
+pub(crate) fn record_provider_change(
+    conn: &Connection,
+    app_type: &str,
+    provider_id: &str,
+    before: Option<&Value>,
+    source: ChangeSource,
+) -> Result<Option<i64>, AppError> {
+    let Some(after) = provider_state(conn, app_type, provider_id)? else {
+        return Ok(None);
+    };
+    let changes = match before {
+        Some(before) => diff_json(before, &after),
+        None => vec![JsonChange {
+            path: String::new(),
+            before: None,
+            after: Some(after),
+        }],
+    };
+    if changes.is_empty() {
+        return Ok(None);
+    }
+
+    let (has_history, current_revision, last_changed): (bool, i64, i64) = conn
+        .query_row(
+            "SELECT
+                EXISTS(SELECT 1 FROM provider_history WHERE provider_id = ?1 AND app_type = ?2),
+                revision,
+                COALESCE(updated_at, created_at, 0)
+             FROM providers WHERE id = ?1 AND app_type = ?2",
+            params![provider_id, app_type],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .map_err(AppError::from)?;
+    if let (false, Some(before)) = (has_history, before) {
+        let baseline = [JsonChange {
+            path: String::new(),
+            before: None,
+            after: Some(before.clone()),
+        }];
+        insert_entry(
+            conn,
+            app_type,
+            provider_id,
+            current_revision,
+            last_changed,
+            ChangeSource::Baseline,
+            &baseline,
+        )?;
+    }
+
+    // 供应商被删除后以相同 ID 重新创建时，修订号接着已有的日志继续
+    let revision: i64 = conn
+        .query_row(
+            "SELECT MAX(
+                (SELECT revision FROM providers WHERE id = ?1 AND app_type = ?2),
+                COALESCE((SELECT MAX(revision) FROM provider_history
+                          WHERE provider_id = ?1 AND app_type = ?2), 0)
+             ) + 1",
+            params![provider_id, app_type],
+            |row| row.get(0),
+        )
+        .map_err(AppError::from)?;
+    let now = chrono::Utc::now().timestamp_millis();
+
+    conn.execute(
+        "UPDATE providers SET revision = ?3, updated_at = ?4 WHERE id = ?1 AND app_type = ?2",
+        params![provider_id, app_type, revision, now],
+    )
+    .map_err(AppError::from)?;
+    insert_entry(conn, app_type, provider_id, revision, now, source, &changes)?;
+    Ok(Some(revision))
+}
+
+fn insert_entry(
+    conn: &Connection,
+    app_type: &str,
+    provider_id: &str,
+    revision: i64,
+    changed_at: i64,
+    source: ChangeSource,
+    changes: &[JsonChange],
+) -> Result<(), AppError> {
+    conn.execute(
+        "INSERT INTO provider_history (app_type, provider_id, revision, changed_at, source, diff)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            app_type,
+            provider_id,
+            revision,
+            changed_at,
+            source.as_str(),
+            serde_json::to_string(changes).map_err(|e| AppError::Database(e.to_string()))?
+        ],
+    )
+    .map_err(AppError::from)?;
+    Ok(())
+}
+
+/// 计算两个 JSON 值之间的差异：对象逐键递归，其他值不相等时整体替换
+pub(crate) fn diff_json(before: &Value, after: &Value) -> Vec<JsonChange> {
+    let mut changes = Vec::new();
+    diff_into(&mut changes, String::new(), before, after);
+    changes
+}
+
+fn diff_into(changes: &mut Vec<JsonChange>, path: String, before: &Value, after: &Value) {
+    match (before, after) {
+        (Value::Object(old), Value::Object(new)) => {
+            let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let child = format!("{path}/{}", key.replace('~', "~0").replace('/', "~1"));
+                match (old.get(key), new.get(key)) {
+                    (Some(old), Some(new)) => diff_into(changes, child, old, new),
+                    (old, new) => changes.push(JsonChange {
+                        path: child,
+                        before: old.cloned(),
+                        after: new.cloned(),
+                    }),
+                }
+            }
+        }
+        _ if before != after => changes.push(JsonChange {
+            path,
+            before: Some(before.clone()),
+            after: Some(after.clone()),
+        }),
+        _ => {}
+    }
+}
+
+/// 把差异应用到 `value` 上（[`diff_json`] 的逆操作），缺少的中间对象会被创建
+pub(crate) fn apply_changes(value: &mut Value, changes: &[JsonChange]) {
+    for change in changes {
+        let segments: Vec<String> = change
+            .path
+            .split('/')
+            .skip(1)
+            .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+            .collect();
+        let Some((last, parents)) = segments.split_last() else {
+            if let Some(after) = &change.after {
+                *value = after.clone();
+            }
+            continue;
+        };
+
+        let mut target = &mut *value;
+        for segment in parents {
+            if !target.is_object() {
+                *target = Value::Object(Map::new());
+            }
+            target = target
+                .as_object_mut()
+                .expect("just made an object")
+                .entry(segment.clone())
+                .or_insert_with(|| Value::Object(Map::new()));
+        }
+        if !target.is_object() {
+            *target = Value::Object(Map::new());
+        }
+        let object = target.as_object_mut().expect("just made an object");
+        match &change.after {
+            Some(after) => {
+                object.insert(last.clone(), after.clone());
+            }
+            None => {
+                object.remove(last);
+            }
+        }
+    }
+}
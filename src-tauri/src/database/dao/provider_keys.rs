@@ -0,0 +1,137 @@
+//! 供应商 Key 池 DAO
+//!
+//! 同一供应商可以保存多个 API Key（各自有独立的限流额度），切换时或由代理按请求从中挑选。
+//! 每次使用都会记录使用次数与时间，轮换策略据此决定下一个 Key。
+
+use std::sync::atomic::Ordering;
+
+use rusqlite::params;
+use serde::Serialize;
+
+use crate::database::{lock_conn, Database};
+use crate::error::AppError;
+
+/// Key 池中的一个 Key
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderKey {
+    pub id: i64,
+    pub provider_id: String,
+    pub app_type: String,
+    pub api_key: String,
+    pub label: Option<String>,
+    /// 加权轮换时的权重（至少为 1）
+    pub weight: u32,
+    pub use_count: i64,
+    /// Unix 毫秒
+    pub last_used: Option<i64>,
+    /// Unix 毫秒
+    pub added_at: i64,
+}
+
+impl Database {
+    /// 供应商的 Key 池（按添加顺序）
+    pub fn get_provider_keys(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+    ) -> Result<Vec<ProviderKey>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT id, provider_id, app_type, api_key, label, weight, use_count, last_used, added_at
+                 FROM provider_keys WHERE app_type = ?1 AND provider_id = ?2 ORDER BY id ASC",
+            )
+            .map_err(AppError::from)?;
+        let keys = stmt
+            .query_map(params![app_type, provider_id], |row| {
+                Ok(ProviderKey {
+                    id: row.get(0)?,
+                    provider_id: row.get(1)?,
+                    app_type: row.get(2)?,
+                    api_key: row.get(3)?,
+                    label: row.get(4)?,
+                    weight: row.get::<_, i64>(5)?.clamp(1, u32::MAX as i64) as u32,
+                    use_count: row.get(6)?,
+                    last_used: row.get(7)?,
+                    added_at: row.get(8)?,
+                })
+            })
+            .map_err(AppError::from)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(AppError::from)?;
+        Ok(keys)
+    }
+
+    /// 添加 Key；已存在时只更新标签与权重，返回是否新增
+    pub fn add_provider_key(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+        api_key: &str,
+        label: Option<&str>,
+        weight: u32,
+    ) -> Result<bool, AppError> {
+        let conn = lock_conn!(self.conn);
+        let inserted = conn
+            .execute(
+                "INSERT OR IGNORE INTO provider_keys (provider_id, app_type, api_key, label, weight, added_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    provider_id,
+                    app_type,
+                    api_key,
+                    label,
+                    weight.max(1),
+                    chrono::Utc::now().timestamp_millis()
+                ],
+            )
+            .map_err(AppError::from)?
+            > 0;
+        if !inserted {
+            conn.execute(
+                "UPDATE provider_keys SET label = COALESCE(?4, label), weight = ?5
+                 WHERE provider_id = ?1 AND app_type = ?2 AND api_key = ?3",
+                params![provider_id, app_type, api_key, label, weight.max(1)],
+            )
+            .map_err(AppError::from)?;
+        }
+        self.key_pool_generation.fetch_add(1, Ordering::Relaxed);
+        Ok(inserted)
+    }
+
+    /// 按 Key 原文或池内 ID 删除，返回是否删除了记录
+    pub fn remove_provider_key(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+        key_or_id: &str,
+    ) -> Result<bool, AppError> {
+        let conn = lock_conn!(self.conn);
+        let removed = conn
+            .execute(
+                "DELETE FROM provider_keys
+                 WHERE provider_id = ?1 AND app_type = ?2 AND (api_key = ?3 OR CAST(id AS TEXT) = ?3)",
+                params![provider_id, app_type, key_or_id],
+            )
+            .map_err(AppError::from)?;
+        self.key_pool_generation.fetch_add(1, Ordering::Relaxed);
+        Ok(removed > 0)
+    }
+
+    /// 本进程修改 Key 池的次数（添加、删除 Key 后递增）
+    pub fn key_pool_generation(&self) -> u64 {
+        self.key_pool_generation.load(Ordering::Relaxed)
+    }
+
+    /// 记录一次使用
+    pub fn mark_provider_key_used(&self, id: i64) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.execute(
+            "UPDATE provider_keys SET use_count = use_count + 1, last_used = ?2 WHERE id = ?1",
+            params![id, chrono::Utc::now().timestamp_millis()],
+        )
+        .map_err(AppError::from)?;
+        Ok(())
+    }
+}
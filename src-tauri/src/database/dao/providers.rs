@@ -2,125 +2,296 @@
 //!
 //! 提供供应商（Provider）的 CRUD 操作。
 
-use crate::database::{lock_conn, Database};
+use super::provider_history::{provider_state, record_provider_change, ChangeSource};
+use crate::app_config::AppType;
+use crate::database::{begin_write, lock_conn, Database};
 use crate::error::AppError;
 use crate::provider::{Provider, ProviderMeta};
 use indexmap::IndexMap;
-use rusqlite::params;
-use std::collections::HashMap;
+use rusqlite::{params, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+
+/// 供应商列表的排序字段
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProviderSort {
+    /// 用户拖拽的顺序（与 get_all_providers 相同）
+    #[default]
+    SortIndex,
+    Name,
+    CreatedAt,
+    UpdatedAt,
+}
+
+/// 分页查询供应商的选项
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryOptions {
+    #[serde(default)]
+    pub offset: usize,
+    /// 为空时返回 offset 之后的全部供应商
+    #[serde(default)]
+    pub limit: Option<usize>,
+    #[serde(default)]
+    pub sort_by: ProviderSort,
+    #[serde(default)]
+    pub descending: bool,
+    /// 全文过滤（同 [`Database::search_providers`] 的查询语法）
+    #[serde(default)]
+    pub filter: Option<String>,
+}
+
+/// 一页供应商
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderPage {
+    /// 满足过滤条件的供应商总数
+    pub total: usize,
+    pub providers: Vec<Provider>,
+}
+
+/// 与 [`provider_from_row`] 对应的列
+const PROVIDER_COLUMNS: &str = "p.id, p.name, p.settings_config, p.website_url, p.category, p.created_at, p.sort_index, p.notes, p.icon, p.icon_color, p.meta, p.in_failover_queue";
+
+/// 从 [`PROVIDER_COLUMNS`] 顺序的列读取供应商（endpoints 与 tags 为空）
+fn provider_from_row(row: &Row) -> rusqlite::Result<Provider> {
+    let settings_config_str: String = row.get(2)?;
+    let meta_str: String = row.get(10)?;
+    let meta: ProviderMeta = serde_json::from_str(&meta_str).unwrap_or_default();
+    Ok(Provider {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        settings_config: serde_json::from_str(&settings_config_str)
+            .unwrap_or(serde_json::Value::Null),
+        website_url: row.get(3)?,
+        category: row.get(4)?,
+        created_at: row.get(5)?,
+        sort_index: row.get(6)?,
+        notes: row.get(7)?,
+        meta: Some(meta),
+        icon: row.get(8)?,
+        icon_color: row.get(9)?,
+        in_failover_queue: row.get(11)?,
+        tags: Vec::new(),
+    })
+}
+
+/// 从 `url, added_at, last_used, latency_ms, tested_at` 五列（自 `start` 起）读取端点
+fn endpoint_from_row(
+    row: &Row,
+    start: usize,
+) -> rusqlite::Result<Option<crate::settings::CustomEndpoint>> {
+    let Some(url) = row.get::<_, Option<String>>(start)? else {
+        return Ok(None);
+    };
+    Ok(Some(crate::settings::CustomEndpoint {
+        url,
+        added_at: row.get::<_, Option<i64>>(start + 1)?.unwrap_or(0),
+        last_used: row.get(start + 2)?,
+        latency_ms: row.get::<_, Option<i64>>(start + 3)?.map(|v| v as u64),
+        tested_at: row.get(start + 4)?,
+    }))
+}
 
 impl Database {
     /// 获取指定应用类型的所有供应商
+    ///
+    /// 供应商、自定义端点与标签各用一条查询取出，再在内存中按供应商分组。
     pub fn get_all_providers(
         &self,
         app_type: &str,
     ) -> Result<IndexMap<String, Provider>, AppError> {
-        let conn = lock_conn!(self.conn);
-        let mut stmt = conn.prepare(
-            "SELECT id, name, settings_config, website_url, category, created_at, sort_index, notes, icon, icon_color, meta, in_failover_queue
-             FROM providers WHERE app_type = ?1
-             ORDER BY COALESCE(sort_index, 999999), created_at ASC, id ASC"
-        ).map_err(|e| AppError::Database(e.to_string()))?;
-
-        let provider_iter = stmt
-            .query_map(params![app_type], |row| {
-                let id: String = row.get(0)?;
-                let name: String = row.get(1)?;
-                let settings_config_str: String = row.get(2)?;
-                let website_url: Option<String> = row.get(3)?;
-                let category: Option<String> = row.get(4)?;
-                let created_at: Option<i64> = row.get(5)?;
-                let sort_index: Option<usize> = row.get(6)?;
-                let notes: Option<String> = row.get(7)?;
-                let icon: Option<String> = row.get(8)?;
-                let icon_color: Option<String> = row.get(9)?;
-                let meta_str: String = row.get(10)?;
-                let in_failover_queue: bool = row.get(11)?;
-
-                let settings_config =
-                    serde_json::from_str(&settings_config_str).unwrap_or(serde_json::Value::Null);
-                let meta: ProviderMeta = serde_json::from_str(&meta_str).unwrap_or_default();
-
-                Ok((
-                    id,
-                    Provider {
-                        id: "".to_string(), // Placeholder, set below
-                        name,
-                        settings_config,
-                        website_url,
-                        category,
-                        created_at,
-                        sort_index,
-                        notes,
-                        meta: Some(meta),
-                        icon,
-                        icon_color,
-                        in_failover_queue,
-                    },
+        self.with_read(|conn| {
+            let mut stmt = conn
+                .prepare_cached(&format!(
+                    "SELECT {PROVIDER_COLUMNS} FROM providers p WHERE p.app_type = ?1
+                 ORDER BY COALESCE(p.sort_index, 999999), p.created_at ASC, p.id ASC"
                 ))
-            })
-            .map_err(|e| AppError::Database(e.to_string()))?;
-
-        let mut providers = IndexMap::new();
-        for provider_res in provider_iter {
-            let (id, mut provider) = provider_res.map_err(|e| AppError::Database(e.to_string()))?;
-            provider.id = id.clone();
-
-            // 加载 endpoints
-            let mut stmt_endpoints = conn.prepare(
-                "SELECT url, added_at FROM provider_endpoints WHERE provider_id = ?1 AND app_type = ?2 ORDER BY added_at ASC, url ASC"
-            ).map_err(|e| AppError::Database(e.to_string()))?;
-
-            let endpoints_iter = stmt_endpoints
-                .query_map(params![id, app_type], |row| {
-                    let url: String = row.get(0)?;
-                    let added_at: Option<i64> = row.get(1)?;
-                    Ok((
-                        url,
-                        crate::settings::CustomEndpoint {
-                            url: "".to_string(),
-                            added_at: added_at.unwrap_or(0),
-                            last_used: None,
-                        },
-                    ))
-                })
-                .map_err(|e| AppError::Database(e.to_string()))?;
+                .map_err(AppError::from)?;
+            let mut providers: IndexMap<String, Provider> = stmt
+                .query_map(params![app_type], provider_from_row)
+                .map_err(AppError::from)?
+                .map(|provider| provider.map(|provider| (provider.id.clone(), provider)))
+                .collect::<Result<_, _>>()
+                .map_err(AppError::from)?;
 
-            let mut custom_endpoints = HashMap::new();
-            for ep_res in endpoints_iter {
-                let (url, mut ep) = ep_res.map_err(|e| AppError::Database(e.to_string()))?;
-                ep.url = url.clone();
-                custom_endpoints.insert(url, ep);
+            let mut stmt = conn
+                .prepare_cached(
+                    "SELECT provider_id, url, added_at, last_used, latency_ms, tested_at
+                 FROM provider_endpoints WHERE app_type = ?1
+                 ORDER BY added_at ASC, url ASC",
+                )
+                .map_err(AppError::from)?;
+            let endpoints = stmt
+                .query_map(params![app_type], |row| {
+                    Ok((row.get::<_, String>(0)?, endpoint_from_row(row, 1)?))
+                })
+                .map_err(AppError::from)?;
+            for endpoint in endpoints {
+                let (provider_id, endpoint) = endpoint.map_err(AppError::from)?;
+                if let (Some(endpoint), Some(meta)) = (
+                    endpoint,
+                    providers
+                        .get_mut(&provider_id)
+                        .and_then(|provider| provider.meta.as_mut()),
+                ) {
+                    meta.custom_endpoints.insert(endpoint.url.clone(), endpoint);
+                }
             }
 
-            if let Some(meta) = &mut provider.meta {
-                meta.custom_endpoints = custom_endpoints;
+            let mut stmt = conn
+                .prepare_cached(
+                    "SELECT provider_id, tag FROM provider_tags WHERE app_type = ?1
+                 ORDER BY provider_id ASC, tag ASC",
+                )
+                .map_err(AppError::from)?;
+            let tags = stmt
+                .query_map(params![app_type], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                })
+                .map_err(AppError::from)?;
+            for tag in tags {
+                let (provider_id, tag) = tag.map_err(AppError::from)?;
+                if let Some(provider) = providers.get_mut(&provider_id) {
+                    provider.tags.push(tag);
+                }
             }
 
-            providers.insert(id, provider);
+            Ok(providers)
+        })
+    }
+
+    /// 分页、排序并过滤供应商
+    ///
+    /// 供应商、标签与自定义端点在一条查询中取出（端点通过 LEFT JOIN，按供应商分组），
+    /// 供应商较多时 GUI 可以只加载当前页。
+    pub fn query_providers(
+        &self,
+        app_type: &str,
+        options: &QueryOptions,
+    ) -> Result<ProviderPage, AppError> {
+        let fts_query = options.filter.as_deref().and_then(build_fts_query);
+        if options.filter.is_some() && fts_query.is_none() {
+            return Ok(ProviderPage {
+                total: 0,
+                providers: Vec::new(),
+            });
         }
+        let filter = if fts_query.is_some() {
+            "AND p.id IN (SELECT provider_id FROM providers_fts
+                          WHERE providers_fts MATCH ?4 AND app_type = ?1)"
+        } else {
+            "AND ?4 IS NULL"
+        };
+        let dir = if options.descending { "DESC" } else { "ASC" };
+        let order = match options.sort_by {
+            ProviderSort::SortIndex => {
+                format!("COALESCE(p.sort_index, 999999) {dir}, p.created_at {dir}, p.id {dir}")
+            }
+            ProviderSort::Name => format!("p.name COLLATE NOCASE {dir}, p.id {dir}"),
+            ProviderSort::CreatedAt => format!("p.created_at {dir}, p.id {dir}"),
+            ProviderSort::UpdatedAt => {
+                format!("COALESCE(p.updated_at, p.created_at) {dir}, p.id {dir}")
+            }
+        };
+        let limit = options.limit.map(|limit| limit as i64).unwrap_or(-1);
 
-        Ok(providers)
+        self.with_read(|conn| {
+            let total: i64 = conn
+                .query_row(
+                    &format!(
+                        "SELECT COUNT(*) FROM providers p WHERE p.app_type = ?1 {}",
+                        filter.replace("?4", "?2")
+                    ),
+                    params![app_type, fts_query],
+                    |row| row.get(0),
+                )
+                .map_err(AppError::from)?;
+
+            let sql = format!(
+                "WITH page AS (
+                SELECT {PROVIDER_COLUMNS},
+                       (SELECT group_concat(tag, char(31)) FROM
+                           (SELECT tag FROM provider_tags t
+                            WHERE t.provider_id = p.id AND t.app_type = p.app_type
+                            ORDER BY tag ASC)) AS tags,
+                       ROW_NUMBER() OVER (ORDER BY {order}) AS position
+                FROM providers p
+                WHERE p.app_type = ?1 {filter}
+                ORDER BY {order}
+                LIMIT ?2 OFFSET ?3
+             )
+             SELECT page.*, e.url, e.added_at, e.last_used, e.latency_ms, e.tested_at
+             FROM page
+             LEFT JOIN provider_endpoints e ON e.provider_id = page.id AND e.app_type = ?1
+             ORDER BY page.position, e.added_at ASC, e.url ASC"
+            );
+            let mut stmt = conn.prepare_cached(&sql).map_err(AppError::from)?;
+            let mut rows = stmt
+                .query(params![app_type, limit, options.offset as i64, fts_query])
+                .map_err(AppError::from)?;
+
+            let mut providers: Vec<Provider> = Vec::new();
+            while let Some(row) = rows.next().map_err(AppError::from)? {
+                let id: String = row.get(0).map_err(AppError::from)?;
+                if providers.last().map(|p| &p.id) != Some(&id) {
+                    let mut provider = provider_from_row(row).map_err(AppError::from)?;
+                    let tags: Option<String> = row.get(12).map_err(AppError::from)?;
+                    provider.tags = tags
+                        .map(|tags| tags.split('\u{1f}').map(str::to_string).collect())
+                        .unwrap_or_default();
+                    providers.push(provider);
+                }
+                let endpoint = endpoint_from_row(row, 14).map_err(AppError::from)?;
+                if let (Some(endpoint), Some(meta)) =
+                    (endpoint, providers.last_mut().and_then(|p| p.meta.as_mut()))
+                {
+                    meta.custom_endpoints.insert(endpoint.url.clone(), endpoint);
+                }
+            }
+
+            Ok(ProviderPage {
+                total: total as usize,
+                providers,
+            })
+        })
+    }
+
+    /// 存有供应商的所有应用（内置应用与注册表应用的 app_type，按名称排序）
+    pub fn provider_app_types(&self) -> Result<Vec<String>, AppError> {
+        self.with_read(|conn| {
+            let mut stmt = conn
+                .prepare_cached("SELECT DISTINCT app_type FROM providers ORDER BY app_type")
+                .map_err(AppError::from)?;
+            let apps = stmt
+                .query_map([], |row| row.get(0))
+                .map_err(AppError::from)?
+                .collect::<Result<Vec<String>, _>>()
+                .map_err(AppError::from)?;
+            Ok(apps)
+        })
     }
 
     /// 获取当前激活的供应商 ID
     pub fn get_current_provider(&self, app_type: &str) -> Result<Option<String>, AppError> {
-        let conn = lock_conn!(self.conn);
-        let mut stmt = conn
-            .prepare("SELECT id FROM providers WHERE app_type = ?1 AND is_current = 1 LIMIT 1")
-            .map_err(|e| AppError::Database(e.to_string()))?;
+        self.with_read(|conn| {
+            let mut stmt = conn
+                .prepare_cached(
+                    "SELECT id FROM providers WHERE app_type = ?1 AND is_current = 1 LIMIT 1",
+                )
+                .map_err(AppError::from)?;
 
-        let mut rows = stmt
-            .query(params![app_type])
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            let mut rows = stmt.query(params![app_type]).map_err(AppError::from)?;
 
-        if let Some(row) = rows.next().map_err(|e| AppError::Database(e.to_string()))? {
-            Ok(Some(
-                row.get(0).map_err(|e| AppError::Database(e.to_string()))?,
-            ))
-        } else {
-            Ok(None)
-        }
+            if let Some(row) = rows.next().map_err(AppError::from)? {
+                Ok(Some(row.get(0).map_err(AppError::from)?))
+            } else {
+                Ok(None)
+            }
+        })
     }
 
     /// 根据 ID 获取单个供应商
@@ -129,49 +300,58 @@ impl Database {
         id: &str,
         app_type: &str,
     ) -> Result<Option<Provider>, AppError> {
-        let conn = lock_conn!(self.conn);
-        let result = conn.query_row(
-            "SELECT name, settings_config, website_url, category, created_at, sort_index, notes, icon, icon_color, meta, in_failover_queue
-             FROM providers WHERE id = ?1 AND app_type = ?2",
-            params![id, app_type],
-            |row| {
-                let name: String = row.get(0)?;
-                let settings_config_str: String = row.get(1)?;
-                let website_url: Option<String> = row.get(2)?;
-                let category: Option<String> = row.get(3)?;
-                let created_at: Option<i64> = row.get(4)?;
-                let sort_index: Option<usize> = row.get(5)?;
-                let notes: Option<String> = row.get(6)?;
-                let icon: Option<String> = row.get(7)?;
-                let icon_color: Option<String> = row.get(8)?;
-                let meta_str: String = row.get(9)?;
-                let in_failover_queue: bool = row.get(10)?;
-
-                let settings_config = serde_json::from_str(&settings_config_str).unwrap_or(serde_json::Value::Null);
-                let meta: ProviderMeta = serde_json::from_str(&meta_str).unwrap_or_default();
-
-                Ok(Provider {
-                    id: id.to_string(),
-                    name,
-                    settings_config,
-                    website_url,
-                    category,
-                    created_at,
-                    sort_index,
-                    notes,
-                    meta: Some(meta),
-                    icon,
-                    icon_color,
-                    in_failover_queue,
-                })
-            },
-        );
+        self.with_read(|conn| {
+            let mut stmt = conn
+                .prepare_cached(
+                    "SELECT name, settings_config, website_url, category, created_at, sort_index, notes, icon, icon_color, meta, in_failover_queue
+                     FROM providers WHERE id = ?1 AND app_type = ?2",
+                )
+                .map_err(AppError::from)?;
+            let result = stmt.query_row(
+                params![id, app_type],
+                |row| {
+                    let name: String = row.get(0)?;
+                    let settings_config_str: String = row.get(1)?;
+                    let website_url: Option<String> = row.get(2)?;
+                    let category: Option<String> = row.get(3)?;
+                    let created_at: Option<i64> = row.get(4)?;
+                    let sort_index: Option<usize> = row.get(5)?;
+                    let notes: Option<String> = row.get(6)?;
+                    let icon: Option<String> = row.get(7)?;
+                    let icon_color: Option<String> = row.get(8)?;
+                    let meta_str: String = row.get(9)?;
+                    let in_failover_queue: bool = row.get(10)?;
 
-        match result {
-            Ok(provider) => Ok(Some(provider)),
-            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(AppError::Database(e.to_string())),
-        }
+                    let settings_config = serde_json::from_str(&settings_config_str).unwrap_or(serde_json::Value::Null);
+                    let meta: ProviderMeta = serde_json::from_str(&meta_str).unwrap_or_default();
+
+                    Ok(Provider {
+                        id: id.to_string(),
+                        name,
+                        settings_config,
+                        website_url,
+                        category,
+                        created_at,
+                        sort_index,
+                        notes,
+                        meta: Some(meta),
+                        icon,
+                        icon_color,
+                        in_failover_queue,
+                        tags: Vec::new(),
+                    })
+                },
+            );
+
+            match result {
+                Ok(mut provider) => {
+                    provider.tags = Self::load_provider_tags(conn, id, app_type)?;
+                    Ok(Some(provider))
+                }
+                Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+                Err(e) => Err(AppError::from(e)),
+            }
+        })
     }
 
     /// 保存供应商（新增或更新）
@@ -179,10 +359,18 @@ impl Database {
     /// 注意：更新模式下不同步 endpoints，因为编辑模式下端点通过单独的 API 管理
     /// （add_custom_endpoint / remove_custom_endpoint），避免覆盖用户的修改。
     pub fn save_provider(&self, app_type: &str, provider: &Provider) -> Result<(), AppError> {
-        let mut conn = lock_conn!(self.conn);
-        let tx = conn
-            .transaction()
-            .map_err(|e| AppError::Database(e.to_string()))?;
+        self.save_provider_as(app_type, provider, self.change_source())
+    }
+
+    /// 保存供应商，并以指定来源记录变更日志
+    pub fn save_provider_as(
+        &self,
+        app_type: &str,
+        provider: &Provider,
+        source: ChangeSource,
+    ) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        let tx = begin_write(&conn)?;
 
         // 处理 meta：取出 endpoints 以便单独处理
         let mut meta_clone = provider.meta.clone().unwrap_or_default();
@@ -198,6 +386,7 @@ impl Database {
             .ok();
 
         let is_update = existing.is_some();
+        let before = provider_state(&tx, app_type, &provider.id)?;
         let (is_current, in_failover_queue) =
             existing.unwrap_or((false, provider.in_failover_queue));
 
@@ -235,14 +424,15 @@ impl Database {
                     app_type,
                 ],
             )
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            .map_err(AppError::from)?;
         } else {
             // 新增模式：使用 INSERT
             tx.execute(
                 "INSERT INTO providers (
                     id, app_type, name, settings_config, website_url, category,
-                    created_at, sort_index, notes, icon, icon_color, meta, is_current, in_failover_queue
-                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+                    created_at, sort_index, notes, icon, icon_color, meta, is_current, in_failover_queue,
+                    owner
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
                 params![
                     provider.id,
                     app_type,
@@ -258,9 +448,10 @@ impl Database {
                     serde_json::to_string(&meta_clone).unwrap(),
                     is_current,
                     in_failover_queue,
+                    crate::config::current_username(),
                 ],
             )
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            .map_err(AppError::from)?;
 
             // 只有新增时才同步 endpoints
             for (url, endpoint) in endpoints {
@@ -269,14 +460,466 @@ impl Database {
                      VALUES (?1, ?2, ?3, ?4)",
                     params![provider.id, app_type, url, endpoint.added_at],
                 )
-                .map_err(|e| AppError::Database(e.to_string()))?;
+                .map_err(AppError::from)?;
+            }
+
+            // 标签同理：编辑模式下通过 add_tag / remove_tag 单独管理
+            for tag in provider.tags.iter().filter_map(|t| normalize_tag(t)) {
+                tx.execute(
+                    "INSERT OR IGNORE INTO provider_tags (provider_id, app_type, tag)
+                     VALUES (?1, ?2, ?3)",
+                    params![provider.id, app_type, tag],
+                )
+                .map_err(AppError::from)?;
+            }
+        }
+
+        record_provider_change(&tx, app_type, &provider.id, before.as_ref(), source)?;
+
+        tx.commit().map_err(AppError::from)?;
+        Ok(())
+    }
+
+    /// 各供应商最近一次保存的时间（Unix 毫秒），从未记录时为 0
+    pub fn get_provider_timestamps(
+        &self,
+        app_type: &str,
+    ) -> Result<HashMap<String, i64>, AppError> {
+        self.with_read(|conn| {
+            let mut stmt = conn
+                .prepare_cached(
+                    "SELECT id, COALESCE(updated_at, created_at, 0) FROM providers WHERE app_type = ?1",
+                )
+                .map_err(AppError::from)?;
+            let rows = stmt
+                .query_map(params![app_type], |row| Ok((row.get(0)?, row.get(1)?)))
+                .map_err(AppError::from)?
+                .collect::<Result<HashMap<String, i64>, _>>()
+                .map_err(AppError::from)?;
+            Ok(rows)
+        })
+    }
+
+    /// 覆盖供应商的修改时间（同步合并时保留远端的时间）
+    pub fn set_provider_updated_at(
+        &self,
+        app_type: &str,
+        id: &str,
+        updated_at: i64,
+    ) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.execute(
+            "UPDATE providers SET updated_at = ?3 WHERE id = ?1 AND app_type = ?2",
+            params![id, app_type, updated_at],
+        )
+        .map_err(AppError::from)?;
+        Ok(())
+    }
+
+    /// 所有应用中最近一次修改供应商的时间
+    pub fn latest_provider_change(&self) -> Result<Option<i64>, AppError> {
+        self.with_read(|conn| {
+            conn.query_row("SELECT MAX(updated_at) FROM providers", [], |row| {
+                row.get(0)
+            })
+            .map_err(AppError::from)
+        })
+    }
+
+    /// 复制供应商
+    ///
+    /// 深拷贝 settings_config、meta（含自定义端点）与标签，生成新的 UUID 与创建时间，
+    /// 并排在原供应商之后。未指定名称时使用 "<原名称> Copy"。返回新创建的供应商。
+    pub fn clone_provider(
+        &self,
+        app_type: &str,
+        source_id: &str,
+        name: Option<&str>,
+    ) -> Result<Provider, AppError> {
+        let providers = self.get_all_providers(app_type)?;
+        let (position, _, source) = providers
+            .get_full(source_id)
+            .ok_or_else(|| AppError::provider_not_found(source_id, app_type))?;
+
+        let mut copy = source.clone();
+        copy.id = uuid::Uuid::new_v4().to_string();
+        copy.name = match name.map(str::trim).filter(|n| !n.is_empty()) {
+            Some(name) => name.to_string(),
+            None => format!("{} Copy", source.name),
+        };
+        copy.created_at = Some(chrono::Utc::now().timestamp_millis());
+        copy.in_failover_queue = false;
+        copy.sort_index = Some(position + 1);
+
+        self.save_provider(app_type, &copy)?;
+        // 副本紧跟在原供应商之后，其后的供应商依次后移
+        let mut order: Vec<String> = providers.keys().cloned().collect();
+        order.insert(position + 1, copy.id.clone());
+        self.reorder_providers(app_type, &order)?;
+        Ok(copy)
+    }
+
+    /// 为供应商添加标签（已存在时忽略）
+    pub fn add_tag(&self, app_type: &str, provider_id: &str, tag: &str) -> Result<(), AppError> {
+        let tag =
+            normalize_tag(tag).ok_or_else(|| AppError::InvalidInput("标签不能为空".to_string()))?;
+
+        let conn = lock_conn!(self.conn);
+        let exists: bool = conn
+            .query_row(
+                "SELECT EXISTS(SELECT 1 FROM providers WHERE id = ?1 AND app_type = ?2)",
+                params![provider_id, app_type],
+                |row| row.get(0),
+            )
+            .map_err(AppError::from)?;
+        if !exists {
+            return Err(AppError::provider_not_found(provider_id, app_type));
+        }
+
+        conn.execute(
+            "INSERT OR IGNORE INTO provider_tags (provider_id, app_type, tag) VALUES (?1, ?2, ?3)",
+            params![provider_id, app_type, tag],
+        )
+        .map_err(AppError::from)?;
+        Ok(())
+    }
+
+    /// 移除供应商的标签
+    pub fn remove_tag(&self, app_type: &str, provider_id: &str, tag: &str) -> Result<(), AppError> {
+        let Some(tag) = normalize_tag(tag) else {
+            return Ok(());
+        };
+
+        let conn = lock_conn!(self.conn);
+        conn.execute(
+            "DELETE FROM provider_tags WHERE provider_id = ?1 AND app_type = ?2 AND tag = ?3",
+            params![provider_id, app_type, tag],
+        )
+        .map_err(AppError::from)?;
+        Ok(())
+    }
+
+    /// 置顶或取消置顶供应商，返回供应商是否存在
+    pub fn set_provider_pinned(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+        pinned: bool,
+    ) -> Result<bool, AppError> {
+        let conn = lock_conn!(self.conn);
+        let updated = conn
+            .execute(
+                "UPDATE providers SET is_pinned = ?3 WHERE id = ?1 AND app_type = ?2",
+                params![provider_id, app_type, pinned],
+            )
+            .map_err(AppError::from)?;
+        Ok(updated > 0)
+    }
+
+    /// 设置（或清除）供应商别名，返回供应商是否存在
+    ///
+    /// 同一应用内别名唯一，且不能与其他供应商的 ID 相同（否则无法判断指的是哪一个）。
+    pub fn set_provider_alias(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+        alias: Option<&str>,
+    ) -> Result<bool, AppError> {
+        let conn = lock_conn!(self.conn);
+        if let Some(alias) = alias {
+            let owner: Option<String> = conn
+                .query_row(
+                    "SELECT id FROM providers
+                     WHERE app_type = ?1 AND id != ?2 AND (id = ?3 OR alias = ?3)",
+                    params![app_type, provider_id, alias],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(AppError::from)?;
+            if let Some(owner) = owner {
+                return Err(AppError::localized(
+                    "provider.alias.taken",
+                    format!("别名 {alias} 已被供应商 {owner} 使用"),
+                    format!("Alias {alias} is already used by provider {owner}"),
+                ));
             }
         }
+        let updated = conn
+            .execute(
+                "UPDATE providers SET alias = ?3 WHERE id = ?1 AND app_type = ?2",
+                params![provider_id, app_type, alias],
+            )
+            .map_err(AppError::from)?;
+        Ok(updated > 0)
+    }
+
+    /// 按 ID 或别名查找供应商 ID（ID 优先）
+    pub fn resolve_provider_id(
+        &self,
+        app_type: &str,
+        id_or_alias: &str,
+    ) -> Result<Option<String>, AppError> {
+        self.with_read(|conn| {
+            conn.query_row(
+                "SELECT id FROM providers WHERE app_type = ?1 AND (id = ?2 OR alias = ?2)
+                 ORDER BY id = ?2 DESC LIMIT 1",
+                params![app_type, id_or_alias],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(AppError::from)
+        })
+    }
 
-        tx.commit().map_err(|e| AppError::Database(e.to_string()))?;
+    /// 设置了别名的供应商：ID -> 别名
+    pub fn get_provider_aliases(
+        &self,
+        app_type: &str,
+    ) -> Result<HashMap<String, String>, AppError> {
+        self.with_read(|conn| {
+            let mut stmt = conn
+                .prepare_cached(
+                    "SELECT id, alias FROM providers WHERE app_type = ?1 AND alias IS NOT NULL",
+                )
+                .map_err(AppError::from)?;
+            let aliases = stmt
+                .query_map(params![app_type], |row| Ok((row.get(0)?, row.get(1)?)))
+                .map_err(AppError::from)?
+                .collect::<Result<HashMap<_, _>, _>>()
+                .map_err(AppError::from)?;
+            Ok(aliases)
+        })
+    }
+
+    /// 记录了所有者的供应商：ID -> 所有者（新增供应商时的系统用户名）
+    pub fn get_provider_owners(&self, app_type: &str) -> Result<HashMap<String, String>, AppError> {
+        self.with_read(|conn| {
+            let mut stmt = conn
+                .prepare_cached(
+                    "SELECT id, owner FROM providers WHERE app_type = ?1 AND owner IS NOT NULL",
+                )
+                .map_err(AppError::from)?;
+            let owners = stmt
+                .query_map(params![app_type], |row| Ok((row.get(0)?, row.get(1)?)))
+                .map_err(AppError::from)?
+                .collect::<Result<HashMap<_, _>, _>>()
+                .map_err(AppError::from)?;
+            Ok(owners)
+        })
+    }
+
+    /// 置顶供应商的 ID（与列表排序一致，即快捷切换的槽位顺序）
+    pub fn get_pinned_provider_ids(&self, app_type: &str) -> Result<Vec<String>, AppError> {
+        self.with_read(|conn| {
+            let mut stmt = conn
+                .prepare_cached(
+                    "SELECT id FROM providers WHERE app_type = ?1 AND is_pinned = 1
+                 ORDER BY COALESCE(sort_index, 999999), created_at ASC, id ASC",
+                )
+                .map_err(AppError::from)?;
+            let ids = stmt
+                .query_map(params![app_type], |row| row.get(0))
+                .map_err(AppError::from)?
+                .collect::<Result<Vec<String>, _>>()
+                .map_err(AppError::from)?;
+            Ok(ids)
+        })
+    }
+
+    /// 按标签查找供应商（保持与列表一致的排序）
+    pub fn find_by_tag(&self, app_type: &str, tag: &str) -> Result<Vec<Provider>, AppError> {
+        let Some(tag) = normalize_tag(tag) else {
+            return Ok(Vec::new());
+        };
+
+        Ok(self
+            .get_all_providers(app_type)?
+            .into_values()
+            .filter(|p| p.tags.iter().any(|t| t == &tag))
+            .collect())
+    }
+
+    /// 查找与一份配置（如 live 配置）连接身份相同的供应商
+    ///
+    /// 按 Base URL 与 API Key 指纹匹配（见 [`Provider::connection_identity`]）；
+    /// 配置中没有 API Key 或应用不是内置应用时不匹配任何供应商。当前供应商优先，其余按列表顺序。
+    pub fn find_provider_matching_config(
+        &self,
+        app_type: &str,
+        settings_config: &Value,
+    ) -> Result<Option<Provider>, AppError> {
+        // 注册表中的其他应用没有连接身份
+        let Ok(app) = AppType::from_str(app_type) else {
+            return Ok(None);
+        };
+        let probe = Provider::with_id(String::new(), String::new(), settings_config.clone(), None);
+        let Some(identity) = probe.connection_identity(&app) else {
+            return Ok(None);
+        };
+
+        let current = self.get_current_provider(app_type)?;
+        let mut matches: Vec<Provider> = self
+            .get_all_providers(app_type)?
+            .into_values()
+            .filter(|p| p.connection_identity(&app).as_ref() == Some(&identity))
+            .collect();
+        matches.sort_by_key(|p| Some(&p.id) != current.as_ref());
+        Ok(matches.into_iter().next())
+    }
+
+    /// 获取指定应用下使用过的全部标签（按字母排序）
+    pub fn list_tags(&self, app_type: &str) -> Result<Vec<String>, AppError> {
+        self.with_read(|conn| {
+            let mut stmt = conn
+                .prepare_cached(
+                    "SELECT DISTINCT tag FROM provider_tags WHERE app_type = ?1 ORDER BY tag ASC",
+                )
+                .map_err(AppError::from)?;
+
+            let tags = stmt
+                .query_map(params![app_type], |row| row.get(0))
+                .map_err(AppError::from)?
+                .collect::<Result<Vec<String>, _>>()
+                .map_err(AppError::from)?;
+            Ok(tags)
+        })
+    }
+
+    /// 全文搜索供应商（名称、备注、分类、端点），按相关度排序
+    ///
+    /// 查询按空白拆分为多个词，每个词做前缀匹配，所有词都需命中。
+    pub fn search_providers(&self, app_type: &str, query: &str) -> Result<Vec<Provider>, AppError> {
+        let Some(fts_query) = build_fts_query(query) else {
+            return Ok(Vec::new());
+        };
+
+        let ids: Vec<String> = self.with_read(|conn| {
+            let mut stmt = conn
+                .prepare_cached(
+                    "SELECT provider_id FROM providers_fts
+                     WHERE providers_fts MATCH ?1 AND app_type = ?2
+                     ORDER BY rank",
+                )
+                .map_err(AppError::from)?;
+            let ids = stmt
+                .query_map(params![fts_query, app_type], |row| row.get(0))
+                .map_err(AppError::from)?
+                .collect::<Result<Vec<String>, _>>()
+                .map_err(AppError::from)?;
+            Ok(ids)
+        })?;
+
+        let mut providers = self.get_all_providers(app_type)?;
+        Ok(ids
+            .iter()
+            .filter_map(|id| providers.swap_remove(id))
+            .collect())
+    }
+
+    fn load_provider_tags(
+        conn: &rusqlite::Connection,
+        provider_id: &str,
+        app_type: &str,
+    ) -> Result<Vec<String>, AppError> {
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT tag FROM provider_tags WHERE provider_id = ?1 AND app_type = ?2 ORDER BY tag ASC",
+            )
+            .map_err(AppError::from)?;
+
+        let tags = stmt
+            .query_map(params![provider_id, app_type], |row| row.get(0))
+            .map_err(AppError::from)?
+            .collect::<Result<Vec<String>, _>>()
+            .map_err(AppError::from)?;
+        Ok(tags)
+    }
+
+    /// 将供应商设为其所属分类的默认供应商（替换该分类原有的默认值）
+    ///
+    /// 返回分类名；供应商未设置分类时报错。
+    pub fn set_category_default(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+    ) -> Result<String, AppError> {
+        let conn = lock_conn!(self.conn);
+        let category: Option<String> = conn
+            .query_row(
+                "SELECT category FROM providers WHERE id = ?1 AND app_type = ?2",
+                params![provider_id, app_type],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(AppError::from)?
+            .ok_or_else(|| AppError::provider_not_found(provider_id, app_type))?;
+        let category = category
+            .map(|c| c.trim().to_string())
+            .filter(|c| !c.is_empty())
+            .ok_or_else(|| {
+                AppError::localized(
+                    "provider.category.missing",
+                    format!("供应商 {provider_id} 未设置分类"),
+                    format!("Provider {provider_id} has no category"),
+                )
+            })?;
+
+        conn.execute(
+            "INSERT OR REPLACE INTO category_defaults (app_type, category, provider_id) VALUES (?1, ?2, ?3)",
+            params![app_type, category, provider_id],
+        )
+        .map_err(AppError::from)?;
+        Ok(category)
+    }
+
+    /// 清除分类的默认供应商
+    pub fn clear_category_default(&self, app_type: &str, category: &str) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.execute(
+            "DELETE FROM category_defaults WHERE app_type = ?1 AND category = ?2",
+            params![app_type, category.trim()],
+        )
+        .map_err(AppError::from)?;
         Ok(())
     }
 
+    /// 获取分类的默认供应商 ID
+    pub fn get_category_default(
+        &self,
+        app_type: &str,
+        category: &str,
+    ) -> Result<Option<String>, AppError> {
+        self.with_read(|conn| {
+            conn.query_row(
+                "SELECT provider_id FROM category_defaults WHERE app_type = ?1 AND category = ?2",
+                params![app_type, category.trim()],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(AppError::from)
+        })
+    }
+
+    /// 获取应用的全部分类默认供应商（分类 -> 供应商 ID）
+    pub fn get_category_defaults(
+        &self,
+        app_type: &str,
+    ) -> Result<BTreeMap<String, String>, AppError> {
+        self.with_read(|conn| {
+            let mut stmt = conn
+                .prepare_cached(
+                    "SELECT category, provider_id FROM category_defaults WHERE app_type = ?1",
+                )
+                .map_err(AppError::from)?;
+            let defaults = stmt
+                .query_map(params![app_type], |row| Ok((row.get(0)?, row.get(1)?)))
+                .map_err(AppError::from)?
+                .collect::<Result<BTreeMap<String, String>, _>>()
+                .map_err(AppError::from)?;
+            Ok(defaults)
+        })
+    }
+
     /// 删除供应商
     pub fn delete_provider(&self, app_type: &str, id: &str) -> Result<(), AppError> {
         let conn = lock_conn!(self.conn);
@@ -284,32 +927,169 @@ impl Database {
             "DELETE FROM providers WHERE id = ?1 AND app_type = ?2",
             params![id, app_type],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
+        Ok(())
+    }
+
+    /// 按给定顺序重写 sort_index（单个事务）
+    ///
+    /// 未出现在 `ordered_ids` 中的供应商保持原有相对顺序，排在其后。
+    pub fn reorder_providers(
+        &self,
+        app_type: &str,
+        ordered_ids: &[String],
+    ) -> Result<(), AppError> {
+        let existing: Vec<String> = self.get_all_providers(app_type)?.into_keys().collect();
+
+        let mut seen = std::collections::HashSet::new();
+        for id in ordered_ids {
+            if !existing.contains(id) {
+                return Err(AppError::provider_not_found(id, app_type));
+            }
+            if !seen.insert(id.as_str()) {
+                return Err(AppError::InvalidInput(format!("供应商重复: {id}")));
+            }
+        }
+
+        let order = ordered_ids
+            .iter()
+            .chain(existing.iter().filter(|id| !seen.contains(id.as_str())));
+
+        let conn = lock_conn!(self.conn);
+        let tx = begin_write(&conn)?;
+        for (index, id) in order.enumerate() {
+            tx.execute(
+                "UPDATE providers SET sort_index = ?1 WHERE id = ?2 AND app_type = ?3",
+                params![index as i64, id, app_type],
+            )
+            .map_err(AppError::from)?;
+        }
+        tx.commit().map_err(AppError::from)?;
+        Ok(())
+    }
+
+    /// 批量删除供应商（单个事务），返回实际删除的数量
+    pub fn delete_providers(&self, app_type: &str, ids: &[String]) -> Result<usize, AppError> {
+        let conn = lock_conn!(self.conn);
+        let tx = begin_write(&conn)?;
+
+        let mut deleted = 0;
+        for id in ids {
+            deleted += tx
+                .execute(
+                    "DELETE FROM providers WHERE id = ?1 AND app_type = ?2",
+                    params![id, app_type],
+                )
+                .map_err(AppError::from)?;
+        }
+
+        tx.commit().map_err(AppError::from)?;
+        Ok(deleted)
+    }
+
+    /// 把重复的供应商合并到 `keep_id`（单个事务）
+    ///
+    /// 被合并者的标签与自定义端点（含测速记录）并入保留者，保留者的备注改为 `notes`，
+    /// 随后删除被合并者。
+    pub fn merge_providers(
+        &self,
+        app_type: &str,
+        keep_id: &str,
+        merged_ids: &[String],
+        notes: Option<&str>,
+    ) -> Result<(), AppError> {
+        let source = self.change_source();
+        let conn = lock_conn!(self.conn);
+        let tx = begin_write(&conn)?;
+        let before = provider_state(&tx, app_type, keep_id)?;
+
+        tx.execute(
+            "UPDATE providers SET notes = ?1 WHERE id = ?2 AND app_type = ?3",
+            params![notes, keep_id, app_type],
+        )
+        .map_err(AppError::from)?;
+        for id in merged_ids.iter().filter(|id| id.as_str() != keep_id) {
+            tx.execute(
+                "INSERT OR IGNORE INTO provider_tags (provider_id, app_type, tag)
+                 SELECT ?1, app_type, tag FROM provider_tags WHERE provider_id = ?2 AND app_type = ?3",
+                params![keep_id, id, app_type],
+            )
+            .map_err(AppError::from)?;
+            tx.execute(
+                "INSERT INTO provider_endpoints
+                    (provider_id, app_type, url, added_at, last_used, latency_ms, tested_at)
+                 SELECT ?1, app_type, url, added_at, last_used, latency_ms, tested_at
+                 FROM provider_endpoints e
+                 WHERE e.provider_id = ?2 AND e.app_type = ?3
+                   AND NOT EXISTS (
+                       SELECT 1 FROM provider_endpoints k
+                       WHERE k.provider_id = ?1 AND k.app_type = ?3 AND k.url = e.url
+                   )",
+                params![keep_id, id, app_type],
+            )
+            .map_err(AppError::from)?;
+            tx.execute(
+                "DELETE FROM providers WHERE id = ?1 AND app_type = ?2",
+                params![id, app_type],
+            )
+            .map_err(AppError::from)?;
+        }
+
+        record_provider_change(&tx, app_type, keep_id, before.as_ref(), source)?;
+        tx.commit().map_err(AppError::from)?;
         Ok(())
     }
 
+    /// 为多个供应商添加同一标签（单个事务），返回新增标签的数量
+    ///
+    /// 不存在的供应商会被跳过。
+    pub fn add_tag_to_providers(
+        &self,
+        app_type: &str,
+        ids: &[String],
+        tag: &str,
+    ) -> Result<usize, AppError> {
+        let tag =
+            normalize_tag(tag).ok_or_else(|| AppError::InvalidInput("标签不能为空".to_string()))?;
+
+        let conn = lock_conn!(self.conn);
+        let tx = begin_write(&conn)?;
+
+        let mut added = 0;
+        for id in ids {
+            added += tx
+                .execute(
+                    "INSERT OR IGNORE INTO provider_tags (provider_id, app_type, tag)
+                     SELECT id, app_type, ?3 FROM providers WHERE id = ?1 AND app_type = ?2",
+                    params![id, app_type, tag],
+                )
+                .map_err(AppError::from)?;
+        }
+
+        tx.commit().map_err(AppError::from)?;
+        Ok(added)
+    }
+
     /// 设置当前供应商
     pub fn set_current_provider(&self, app_type: &str, id: &str) -> Result<(), AppError> {
-        let mut conn = lock_conn!(self.conn);
-        let tx = conn
-            .transaction()
-            .map_err(|e| AppError::Database(e.to_string()))?;
+        let conn = lock_conn!(self.conn);
+        let tx = begin_write(&conn)?;
 
         // 重置所有为 0
         tx.execute(
             "UPDATE providers SET is_current = 0 WHERE app_type = ?1",
             params![app_type],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         // 设置新的当前供应商
         tx.execute(
             "UPDATE providers SET is_current = 1 WHERE id = ?1 AND app_type = ?2",
             params![id, app_type],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
-        tx.commit().map_err(|e| AppError::Database(e.to_string()))?;
+        tx.commit().map_err(AppError::from)?;
         Ok(())
     }
 
@@ -321,7 +1101,9 @@ impl Database {
         settings_config: &serde_json::Value,
     ) -> Result<(), AppError> {
         let conn = lock_conn!(self.conn);
-        conn.execute(
+        let tx = begin_write(&conn)?;
+        let before = provider_state(&tx, app_type, provider_id)?;
+        tx.execute(
             "UPDATE providers SET settings_config = ?1 WHERE id = ?2 AND app_type = ?3",
             params![
                 serde_json::to_string(settings_config).unwrap(),
@@ -329,7 +1111,17 @@ impl Database {
                 app_type
             ],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
+        if before.is_some() {
+            record_provider_change(
+                &tx,
+                app_type,
+                provider_id,
+                before.as_ref(),
+                self.change_source(),
+            )?;
+        }
+        tx.commit().map_err(AppError::from)?;
         Ok(())
     }
 
@@ -345,7 +1137,7 @@ impl Database {
         conn.execute(
             "INSERT INTO provider_endpoints (provider_id, app_type, url, added_at) VALUES (?1, ?2, ?3, ?4)",
             params![provider_id, app_type, url, added_at],
-        ).map_err(|e| AppError::Database(e.to_string()))?;
+        ).map_err(AppError::from)?;
         Ok(())
     }
 
@@ -361,7 +1153,65 @@ impl Database {
             "DELETE FROM provider_endpoints WHERE provider_id = ?1 AND app_type = ?2 AND url = ?3",
             params![provider_id, app_type, url],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
+        Ok(())
+    }
+
+    /// 记录端点测速结果，`latency_ms` 为空表示本次测速失败
+    pub fn record_endpoint_test(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+        url: &str,
+        latency_ms: Option<u64>,
+    ) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        let tested_at = chrono::Utc::now().timestamp_millis();
+        conn.execute(
+            "UPDATE provider_endpoints SET latency_ms = ?4, tested_at = ?5
+             WHERE provider_id = ?1 AND app_type = ?2 AND url = ?3",
+            params![
+                provider_id,
+                app_type,
+                url,
+                latency_ms.map(|v| v as i64),
+                tested_at
+            ],
+        )
+        .map_err(AppError::from)?;
         Ok(())
     }
+
+    /// 更新端点的最近使用时间
+    pub fn mark_endpoint_used(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+        url: &str,
+    ) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        let last_used = chrono::Utc::now().timestamp_millis();
+        conn.execute(
+            "UPDATE provider_endpoints SET last_used = ?4
+             WHERE provider_id = ?1 AND app_type = ?2 AND url = ?3",
+            params![provider_id, app_type, url, last_used],
+        )
+        .map_err(AppError::from)?;
+        Ok(())
+    }
+}
+
+/// 规范化标签：去除首尾空白并转为小写，空标签返回 None
+fn normalize_tag(tag: &str) -> Option<String> {
+    let tag = tag.trim().to_lowercase();
+    (!tag.is_empty()).then_some(tag)
+}
+
+/// 将用户输入转换为 FTS5 查询：每个词加引号（转义内部引号）并做前缀匹配
+fn build_fts_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"*", term.replace('"', "\"\"")))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
 }
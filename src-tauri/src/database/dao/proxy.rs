@@ -41,7 +41,7 @@ impl Database {
                 self.update_proxy_config(default_config.clone()).await?;
                 Ok(default_config)
             }
-            Err(e) => Err(AppError::Database(e.to_string())),
+            Err(e) => Err(AppError::from(e)),
         }
     }
 
@@ -66,7 +66,7 @@ impl Database {
                 "claude", // 兼容旧字段，写入默认值
             ],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         Ok(())
     }
@@ -82,7 +82,7 @@ impl Database {
             "UPDATE proxy_config SET live_takeover_active = ?1, updated_at = datetime('now') WHERE id = 1",
             rusqlite::params![if active { 1 } else { 0 }],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         Ok(())
     }
@@ -139,10 +139,50 @@ impl Database {
                 last_error: None,
                 updated_at: chrono::Utc::now().to_rfc3339(),
             }),
-            Err(e) => Err(AppError::Database(e.to_string())),
+            Err(e) => Err(AppError::from(e)),
         }
     }
 
+    /// 获取某个应用下所有已记录的 Provider 健康状态（按 provider_id 索引）
+    ///
+    /// 与 `get_provider_health` 不同，没有记录的 Provider 不会出现在结果中。
+    pub fn get_provider_health_map(
+        &self,
+        app_type: &str,
+    ) -> Result<std::collections::HashMap<String, ProviderHealth>, AppError> {
+        self.with_read(|conn| {
+            let mut stmt = conn
+                .prepare_cached(
+                    "SELECT provider_id, app_type, is_healthy, consecutive_failures,
+                        last_success_at, last_failure_at, last_error, updated_at
+                 FROM provider_health
+                 WHERE app_type = ?1",
+                )
+                .map_err(AppError::from)?;
+            let rows = stmt
+                .query_map(rusqlite::params![app_type], |row| {
+                    Ok(ProviderHealth {
+                        provider_id: row.get(0)?,
+                        app_type: row.get(1)?,
+                        is_healthy: row.get::<_, i64>(2)? != 0,
+                        consecutive_failures: row.get::<_, i64>(3)? as u32,
+                        last_success_at: row.get(4)?,
+                        last_failure_at: row.get(5)?,
+                        last_error: row.get(6)?,
+                        updated_at: row.get(7)?,
+                    })
+                })
+                .map_err(AppError::from)?;
+
+            let mut map = std::collections::HashMap::new();
+            for health in rows {
+                let health = health.map_err(AppError::from)?;
+                map.insert(health.provider_id.clone(), health);
+            }
+            Ok(map)
+        })
+    }
+
     /// 更新Provider健康状态
     ///
     /// 使用默认阈值（5）判断是否健康，建议使用 `update_provider_health_with_threshold` 传入配置的阈值
@@ -221,7 +261,7 @@ impl Database {
                 &now,
             ],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         Ok(())
     }
@@ -238,7 +278,7 @@ impl Database {
             "DELETE FROM provider_health WHERE provider_id = ?1 AND app_type = ?2",
             rusqlite::params![provider_id, app_type],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         log::debug!("Reset health status for provider {provider_id} (app: {app_type})");
 
@@ -253,7 +293,7 @@ impl Database {
             "DELETE FROM provider_health WHERE app_type = ?1",
             [app_type],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         log::debug!("Cleared provider health records for app {app_type}");
         Ok(())
@@ -264,7 +304,7 @@ impl Database {
         let conn = lock_conn!(self.conn);
 
         conn.execute("DELETE FROM provider_health", [])
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            .map_err(AppError::from)?;
 
         log::debug!("Cleared all provider health records");
         Ok(())
@@ -294,7 +334,7 @@ impl Database {
                     })
                 },
             )
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            .map_err(AppError::from)?;
 
         Ok(config)
     }
@@ -323,7 +363,7 @@ impl Database {
                 config.min_requests as i32,
             ],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         Ok(())
     }
@@ -344,7 +384,7 @@ impl Database {
              VALUES (?1, ?2, ?3)",
             rusqlite::params![app_type, config_json, now],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         log::info!("已备份 {app_type} Live 配置");
         Ok(())
@@ -357,7 +397,7 @@ impl Database {
             .query_row("SELECT COUNT(*) FROM proxy_live_backup", [], |row| {
                 row.get(0)
             })
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            .map_err(AppError::from)?;
         Ok(count > 0)
     }
 
@@ -380,7 +420,7 @@ impl Database {
         match result {
             Ok(backup) => Ok(Some(backup)),
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(AppError::Database(e.to_string())),
+            Err(e) => Err(AppError::from(e)),
         }
     }
 
@@ -392,7 +432,7 @@ impl Database {
             "DELETE FROM proxy_live_backup WHERE app_type = ?1",
             rusqlite::params![app_type],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         log::info!("已删除 {app_type} Live 配置备份");
         Ok(())
@@ -403,7 +443,7 @@ impl Database {
         let conn = lock_conn!(self.conn);
 
         conn.execute("DELETE FROM proxy_live_backup", [])
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            .map_err(AppError::from)?;
 
         log::info!("已删除所有 Live 配置备份");
         Ok(())
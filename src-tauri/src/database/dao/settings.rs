@@ -5,23 +5,20 @@
 use crate::database::{lock_conn, Database};
 use crate::error::AppError;
 use rusqlite::params;
+use std::collections::BTreeMap;
 
 impl Database {
     /// 获取设置值
     pub fn get_setting(&self, key: &str) -> Result<Option<String>, AppError> {
         let conn = lock_conn!(self.conn);
         let mut stmt = conn
-            .prepare("SELECT value FROM settings WHERE key = ?1")
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            .prepare_cached("SELECT value FROM settings WHERE key = ?1")
+            .map_err(AppError::from)?;
 
-        let mut rows = stmt
-            .query(params![key])
-            .map_err(|e| AppError::Database(e.to_string()))?;
+        let mut rows = stmt.query(params![key]).map_err(AppError::from)?;
 
-        if let Some(row) = rows.next().map_err(|e| AppError::Database(e.to_string()))? {
-            Ok(Some(
-                row.get(0).map_err(|e| AppError::Database(e.to_string()))?,
-            ))
+        if let Some(row) = rows.next().map_err(AppError::from)? {
+            Ok(Some(row.get(0).map_err(AppError::from)?))
         } else {
             Ok(None)
         }
@@ -34,10 +31,22 @@ impl Database {
             "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
             params![key, value],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
         Ok(())
     }
 
+    /// 获取全部设置（按 key 排序）
+    pub fn get_all_settings(&self) -> Result<BTreeMap<String, String>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare_cached("SELECT key, value FROM settings")
+            .map_err(AppError::from)?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(AppError::from)?;
+        rows.collect::<Result<_, _>>().map_err(AppError::from)
+    }
+
     // --- Config Snippets 辅助方法 ---
 
     /// 获取通用配置片段
@@ -58,7 +67,7 @@ impl Database {
             // 如果为 None 则删除
             let conn = lock_conn!(self.conn);
             conn.execute("DELETE FROM settings WHERE key = ?1", params![key])
-                .map_err(|e| AppError::Database(e.to_string()))?;
+                .map_err(AppError::from)?;
             Ok(())
         }
     }
@@ -99,7 +108,7 @@ impl Database {
                 [],
                 |row| row.get(0),
             )
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            .map_err(AppError::from)?;
         Ok(count > 0)
     }
 
@@ -110,7 +119,7 @@ impl Database {
             "UPDATE settings SET value = 'false' WHERE key LIKE 'proxy_takeover_%'",
             [],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
         log::info!("已清除所有代理接管状态");
         Ok(())
     }
@@ -0,0 +1,144 @@
+//! 供应商账号（vendor）DAO
+//!
+//! 同一服务商的多个供应商（不同端点或 Key）可以归到一个 vendor 下，列表按 vendor 分组、
+//! 用量按 vendor 汇总。vendor 不区分应用，供应商通过 `providers.vendor_id` 关联。
+
+use std::collections::HashMap;
+
+use rusqlite::{params, OptionalExtension, Row};
+use serde::Serialize;
+
+use crate::database::{begin_write, lock_conn, Database};
+use crate::error::AppError;
+
+/// 服务商账号
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Vendor {
+    pub id: String,
+    pub name: String,
+    pub website_url: Option<String>,
+    pub notes: Option<String>,
+    /// Unix 毫秒
+    pub created_at: i64,
+}
+
+const COLUMNS: &str = "id, name, website_url, notes, created_at";
+
+fn from_row(row: &Row<'_>) -> rusqlite::Result<Vendor> {
+    Ok(Vendor {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        website_url: row.get(2)?,
+        notes: row.get(3)?,
+        created_at: row.get(4)?,
+    })
+}
+
+impl Database {
+    /// 所有 vendor（按名称）
+    pub fn get_vendors(&self) -> Result<Vec<Vendor>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare_cached(&format!(
+                "SELECT {COLUMNS} FROM vendors ORDER BY name COLLATE NOCASE ASC, id ASC"
+            ))
+            .map_err(AppError::from)?;
+        let vendors = stmt
+            .query_map([], from_row)
+            .map_err(AppError::from)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(AppError::from)?;
+        Ok(vendors)
+    }
+
+    pub fn get_vendor(&self, id: &str) -> Result<Option<Vendor>, AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.query_row(
+            &format!("SELECT {COLUMNS} FROM vendors WHERE id = ?1"),
+            params![id],
+            from_row,
+        )
+        .optional()
+        .map_err(AppError::from)
+    }
+
+    /// 新增或更新 vendor（创建时间保持不变）
+    pub fn save_vendor(&self, vendor: &Vendor) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.execute(
+            &format!(
+                "INSERT INTO vendors ({COLUMNS}) VALUES (?1, ?2, ?3, ?4, ?5)
+                 ON CONFLICT(id) DO UPDATE SET
+                    name = excluded.name,
+                    website_url = excluded.website_url,
+                    notes = excluded.notes"
+            ),
+            params![
+                vendor.id,
+                vendor.name,
+                vendor.website_url,
+                vendor.notes,
+                vendor.created_at
+            ],
+        )
+        .map_err(AppError::from)?;
+        Ok(())
+    }
+
+    /// 删除 vendor 并解除其供应商的关联，返回是否存在
+    pub fn delete_vendor(&self, id: &str) -> Result<bool, AppError> {
+        let conn = lock_conn!(self.conn);
+        let tx = begin_write(&conn)?;
+        tx.execute(
+            "UPDATE providers SET vendor_id = NULL WHERE vendor_id = ?1",
+            params![id],
+        )
+        .map_err(AppError::from)?;
+        let deleted = tx
+            .execute("DELETE FROM vendors WHERE id = ?1", params![id])
+            .map_err(AppError::from)?
+            > 0;
+        tx.commit().map_err(AppError::from)?;
+        Ok(deleted)
+    }
+
+    /// 设置（或清除）供应商所属的 vendor，返回供应商是否存在
+    pub fn set_provider_vendor(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+        vendor_id: Option<&str>,
+    ) -> Result<bool, AppError> {
+        let conn = lock_conn!(self.conn);
+        let updated = conn
+            .execute(
+                "UPDATE providers SET vendor_id = ?3 WHERE id = ?1 AND app_type = ?2",
+                params![provider_id, app_type, vendor_id],
+            )
+            .map_err(AppError::from)?;
+        Ok(updated > 0)
+    }
+
+    /// 已关联 vendor 的供应商：(app_type, provider_id) -> vendor_id；`app_type` 为空时包含所有应用
+    pub fn get_provider_vendors(
+        &self,
+        app_type: Option<&str>,
+    ) -> Result<HashMap<(String, String), String>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT app_type, id, vendor_id FROM providers
+                 WHERE vendor_id IS NOT NULL AND (?1 IS NULL OR app_type = ?1)",
+            )
+            .map_err(AppError::from)?;
+        let rows = stmt
+            .query_map(params![app_type], |row| {
+                Ok(((row.get(0)?, row.get(1)?), row.get(2)?))
+            })
+            .map_err(AppError::from)?
+            .collect::<Result<HashMap<_, _>, _>>()
+            .map_err(AppError::from)?;
+        Ok(rows)
+    }
+}
@@ -0,0 +1,108 @@
+//! 数据库维护：完整性检查、VACUUM、ANALYZE 与统计信息
+//!
+//! 大量导入导出或数据库表现异常时使用（命令行 `cc-switch db doctor`）。
+
+use super::{lock_conn, Database};
+use crate::error::AppError;
+use rusqlite::Connection;
+use serde::Serialize;
+
+/// 单个表的行数
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableStats {
+    pub name: String,
+    pub rows: i64,
+}
+
+/// 数据库概况
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DatabaseStats {
+    /// 数据库大小（字节，page_count × page_size）
+    pub size: u64,
+    /// 空闲页占用的字节数，VACUUM 可回收
+    pub free_bytes: u64,
+    pub schema_version: i32,
+    pub journal_mode: String,
+    /// 按表名排序
+    pub tables: Vec<TableStats>,
+}
+
+impl Database {
+    /// 运行 `PRAGMA integrity_check`，返回发现的问题（为空表示数据库完好）
+    pub fn integrity_check(&self) -> Result<Vec<String>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let rows = query_strings(&conn, "PRAGMA integrity_check")?;
+        Ok(if rows == ["ok"] { Vec::new() } else { rows })
+    }
+
+    /// 重建数据库文件以回收空闲页，返回回收的字节数
+    pub fn vacuum(&self) -> Result<u64, AppError> {
+        let conn = lock_conn!(self.conn);
+        let before = page_bytes(&conn, "page_count")?;
+        conn.execute_batch("VACUUM;").map_err(AppError::from)?;
+        let after = page_bytes(&conn, "page_count")?;
+        Ok(before.saturating_sub(after))
+    }
+
+    /// 更新查询优化器使用的统计信息
+    pub fn analyze(&self) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.execute_batch("ANALYZE;").map_err(AppError::from)
+    }
+
+    /// 各表行数、数据库大小、Schema 版本与日志模式
+    pub fn stats(&self) -> Result<DatabaseStats, AppError> {
+        let conn = lock_conn!(self.conn);
+        let names = query_strings(
+            &conn,
+            "SELECT name FROM sqlite_master
+             WHERE type = 'table' AND name NOT LIKE 'sqlite_%'
+             ORDER BY name",
+        )?;
+        let mut tables = Vec::with_capacity(names.len());
+        for name in names {
+            let rows: i64 = conn
+                .query_row(
+                    &format!("SELECT COUNT(*) FROM \"{}\"", name.replace('"', "\"\"")),
+                    [],
+                    |row| row.get(0),
+                )
+                .map_err(AppError::from)?;
+            tables.push(TableStats { name, rows });
+        }
+
+        let journal_mode: String = conn
+            .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+            .map_err(AppError::from)?;
+        Ok(DatabaseStats {
+            size: page_bytes(&conn, "page_count")?,
+            free_bytes: page_bytes(&conn, "freelist_count")?,
+            schema_version: Self::get_user_version(&conn)?,
+            journal_mode,
+            tables,
+        })
+    }
+}
+
+fn query_strings(conn: &Connection, sql: &str) -> Result<Vec<String>, AppError> {
+    let mut stmt = conn.prepare(sql).map_err(AppError::from)?;
+    let rows = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(AppError::from)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(AppError::from)?;
+    Ok(rows)
+}
+
+/// `PRAGMA <pages>` 换算为字节
+fn page_bytes(conn: &Connection, pages: &str) -> Result<u64, AppError> {
+    let count: i64 = conn
+        .query_row(&format!("PRAGMA {pages}"), [], |row| row.get(0))
+        .map_err(AppError::from)?;
+    let page_size: i64 = conn
+        .query_row("PRAGMA page_size", [], |row| row.get(0))
+        .map_err(AppError::from)?;
+    Ok((count.max(0) * page_size.max(0)) as u64)
+}
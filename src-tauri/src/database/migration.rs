@@ -11,9 +11,7 @@ impl Database {
     /// 从 MultiAppConfig 迁移数据到数据库
     pub fn migrate_from_json(&self, config: &MultiAppConfig) -> Result<(), AppError> {
         let mut conn = lock_conn!(self.conn);
-        let tx = conn
-            .transaction()
-            .map_err(|e| AppError::Database(e.to_string()))?;
+        let tx = conn.transaction().map_err(AppError::from)?;
 
         Self::migrate_from_json_tx(&tx, config)?;
 
@@ -26,14 +24,11 @@ impl Database {
     ///
     /// 用于部署前验证迁移逻辑是否正确。
     pub fn migrate_from_json_dry_run(config: &MultiAppConfig) -> Result<(), AppError> {
-        let mut conn =
-            Connection::open_in_memory().map_err(|e| AppError::Database(e.to_string()))?;
+        let mut conn = Connection::open_in_memory().map_err(AppError::from)?;
         Self::create_tables_on_conn(&conn)?;
         Self::apply_schema_migrations_on_conn(&conn)?;
 
-        let tx = conn
-            .transaction()
-            .map_err(|e| AppError::Database(e.to_string()))?;
+        let tx = conn.transaction().map_err(AppError::from)?;
         Self::migrate_from_json_tx(&tx, config)?;
 
         // 显式 drop transaction 而不提交（内存数据库会被丢弃）
@@ -0,0 +1,476 @@
+//! Schema 迁移注册表
+//!
+//! 每个迁移有递增的版本号、名称和执行内容（SQL 或 Rust 函数），按版本顺序执行。执行后
+//! `PRAGMA user_version` 更新为该版本，并在 `schema_migrations` 表中记录名称、校验和与
+//! 执行时间。新增迁移时在 [`MIGRATIONS`] 末尾追加一项，并同步递增
+//! [`SCHEMA_VERSION`](super::SCHEMA_VERSION)。
+//!
+//! SQL 迁移的校验和覆盖 SQL 文本，Rust 迁移只覆盖版本号与名称。已执行迁移的校验和与
+//! 注册表不一致说明迁移在发布后被改动过：还有待执行的迁移时拒绝迁移（在其上继续迁移的结果
+//! 无法预期），没有时只记录警告；`cc-switch db migrate --dry-run` 列出不一致的迁移。
+
+use rusqlite::{params, Connection, OpenFlags};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use super::{lock_conn, Database, SCHEMA_COMPAT_VERSION};
+use crate::config::get_database_path;
+use crate::error::AppError;
+
+pub(crate) type MigrationFn = fn(&Connection) -> Result<(), AppError>;
+
+/// 迁移的执行内容
+pub(crate) enum MigrationStep {
+    /// 用 `execute_batch` 执行的 SQL
+    Sql(&'static str),
+    /// 需要读取现有数据或做条件判断的迁移
+    Rust(MigrationFn),
+}
+
+pub(crate) struct Migration {
+    /// 执行后的 user_version
+    pub version: i32,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub step: MigrationStep,
+}
+
+impl Migration {
+    /// 十六进制 SHA-256
+    pub(crate) fn checksum(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(format!("{}\n{}\n", self.version, self.name));
+        if let MigrationStep::Sql(sql) = self.step {
+            hasher.update(sql);
+        }
+        format!("{:x}", hasher.finalize())
+    }
+
+    fn run(&self, conn: &Connection) -> Result<(), AppError> {
+        match self.step {
+            MigrationStep::Sql(sql) => conn.execute_batch(sql).map_err(|e| {
+                AppError::Database(format!(
+                    "执行迁移 v{} {} 失败: {e}",
+                    self.version, self.name
+                ))
+            }),
+            MigrationStep::Rust(run) => run(conn),
+        }
+    }
+}
+
+/// 尚未执行的迁移（`cc-switch db migrate --dry-run` 的输出）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingMigration {
+    pub version: i32,
+    pub name: String,
+    pub description: String,
+    pub checksum: String,
+}
+
+impl From<&Migration> for PendingMigration {
+    fn from(migration: &Migration) -> Self {
+        Self {
+            version: migration.version,
+            name: migration.name.to_string(),
+            description: migration.description.to_string(),
+            checksum: migration.checksum(),
+        }
+    }
+}
+
+/// 已执行迁移的记录与当前注册表不一致（迁移在发布后被改动过）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationMismatch {
+    pub version: i32,
+    pub name: String,
+    /// schema_migrations 中记录的名称
+    pub recorded_name: String,
+    pub checksum: String,
+    /// schema_migrations 中记录的校验和
+    pub recorded_checksum: String,
+}
+
+impl MigrationMismatch {
+    pub fn describe(&self) -> String {
+        if self.name != self.recorded_name {
+            format!(
+                "迁移 v{} 的记录（{}）与当前定义（{}）不一致",
+                self.version, self.recorded_name, self.name
+            )
+        } else {
+            format!(
+                "迁移 v{}（{}）的校验和与执行时的记录不一致",
+                self.version, self.name
+            )
+        }
+    }
+
+    /// 存在不一致时拒绝迁移的错误
+    pub fn error(mismatches: &[Self]) -> AppError {
+        let details: Vec<String> = mismatches.iter().map(Self::describe).collect();
+        AppError::Database(format!(
+            "{}，已执行的迁移在发布后被改动过，拒绝继续迁移",
+            details.join("；")
+        ))
+    }
+}
+
+/// 全部迁移，按版本排序且版本连续
+pub(crate) const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "fill_missing_columns",
+        description: "补齐缺失列并设置版本",
+        step: MigrationStep::Rust(Database::migrate_v0_to_v1),
+    },
+    Migration {
+        version: 2,
+        name: "usage_stats_and_skills",
+        description: "添加使用统计表和完整字段，重构 skills 表",
+        step: MigrationStep::Rust(Database::migrate_v1_to_v2),
+    },
+    Migration {
+        version: 3,
+        name: "failover_groups",
+        description: "添加故障转移组字段",
+        step: MigrationStep::Rust(Database::migrate_v2_to_v3),
+    },
+    Migration {
+        version: 4,
+        name: "provider_tags",
+        description: "添加供应商标签表",
+        step: MigrationStep::Sql(
+            "CREATE TABLE IF NOT EXISTS provider_tags (
+                provider_id TEXT NOT NULL,
+                app_type TEXT NOT NULL,
+                tag TEXT NOT NULL,
+                PRIMARY KEY (provider_id, app_type, tag),
+                FOREIGN KEY (provider_id, app_type) REFERENCES providers(id, app_type) ON DELETE CASCADE
+            );
+            CREATE INDEX IF NOT EXISTS idx_provider_tags_tag ON provider_tags(app_type, tag);",
+        ),
+    },
+    Migration {
+        version: 5,
+        name: "provider_fts",
+        description: "添加供应商全文索引",
+        step: MigrationStep::Rust(Database::migrate_v4_to_v5),
+    },
+    Migration {
+        version: 6,
+        name: "category_defaults",
+        description: "添加分类默认供应商表",
+        step: MigrationStep::Sql(
+            "CREATE TABLE IF NOT EXISTS category_defaults (
+                app_type TEXT NOT NULL,
+                category TEXT NOT NULL,
+                provider_id TEXT NOT NULL,
+                PRIMARY KEY (app_type, category),
+                FOREIGN KEY (provider_id, app_type) REFERENCES providers(id, app_type) ON DELETE CASCADE
+            );",
+        ),
+    },
+    Migration {
+        version: 7,
+        name: "audit_log",
+        description: "添加审计日志表",
+        step: MigrationStep::Sql(
+            "CREATE TABLE IF NOT EXISTS audit_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                action TEXT NOT NULL,
+                app_type TEXT,
+                provider_id TEXT,
+                detail TEXT,
+                created_at INTEGER NOT NULL
+            );",
+        ),
+    },
+    Migration {
+        version: 8,
+        name: "switch_history",
+        description: "添加切换历史表",
+        step: MigrationStep::Sql(
+            "CREATE TABLE IF NOT EXISTS switch_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                app_type TEXT NOT NULL,
+                provider_id TEXT NOT NULL,
+                provider_name TEXT NOT NULL,
+                cwd TEXT,
+                switched_at INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_switch_history_app
+                ON switch_history(app_type, switched_at);",
+        ),
+    },
+    Migration {
+        version: 9,
+        name: "provider_usage_counters",
+        description: "添加供应商用量计数器表",
+        step: MigrationStep::Sql(
+            "CREATE TABLE IF NOT EXISTS provider_usage_counters (
+                app_type TEXT NOT NULL,
+                provider_id TEXT NOT NULL,
+                requests INTEGER NOT NULL DEFAULT 0,
+                tokens INTEGER NOT NULL DEFAULT 0,
+                updated_at INTEGER NOT NULL,
+                PRIMARY KEY (app_type, provider_id)
+            );",
+        ),
+    },
+    Migration {
+        version: 10,
+        name: "benchmarks",
+        description: "添加基准测试结果表",
+        step: MigrationStep::Sql(
+            "CREATE TABLE IF NOT EXISTS benchmarks (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                app_type TEXT NOT NULL,
+                provider_id TEXT NOT NULL,
+                success INTEGER NOT NULL,
+                ttfb_ms INTEGER,
+                total_ms INTEGER,
+                output_tokens INTEGER,
+                tokens_per_sec REAL,
+                error TEXT,
+                created_at INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_benchmarks_provider
+                ON benchmarks(app_type, provider_id, created_at);",
+        ),
+    },
+    Migration {
+        version: 11,
+        name: "endpoint_latency",
+        description: "记录端点测速结果",
+        step: MigrationStep::Rust(Database::migrate_v10_to_v11),
+    },
+    Migration {
+        version: 12,
+        name: "provider_updated_at",
+        description: "记录供应商修改时间",
+        step: MigrationStep::Rust(Database::migrate_v11_to_v12),
+    },
+    Migration {
+        version: 13,
+        name: "provider_history",
+        description: "添加供应商变更日志",
+        step: MigrationStep::Rust(Database::migrate_v12_to_v13),
+    },
+    Migration {
+        version: 14,
+        name: "change_log",
+        description: "添加变更通知表",
+        step: MigrationStep::Rust(Database::migrate_v13_to_v14),
+    },
+    Migration {
+        version: 15,
+        name: "provider_keys",
+        description: "添加供应商 Key 池表",
+        step: MigrationStep::Rust(Database::migrate_v14_to_v15),
+    },
+    Migration {
+        version: 16,
+        name: "pending_reverts",
+        description: "添加限时切换待恢复表",
+        step: MigrationStep::Rust(Database::migrate_v15_to_v16),
+    },
+    Migration {
+        version: 17,
+        name: "vendors",
+        description: "添加服务商账号表",
+        step: MigrationStep::Rust(Database::migrate_v16_to_v17),
+    },
+    Migration {
+        version: 18,
+        name: "provider_pinned",
+        description: "添加供应商置顶标记",
+        step: MigrationStep::Rust(Database::migrate_v17_to_v18),
+    },
+    Migration {
+        version: 19,
+        name: "provider_alias",
+        description: "添加供应商别名",
+        step: MigrationStep::Rust(Database::migrate_v18_to_v19),
+    },
+    Migration {
+        version: 20,
+        name: "provider_owner",
+        description: "添加供应商所有者",
+        step: MigrationStep::Rust(Database::migrate_v19_to_v20),
+    },
+    Migration {
+        version: 21,
+        name: "gemini_key_pool",
+        description: "Gemini Key 池并入 provider_keys 表",
+        step: MigrationStep::Rust(Database::migrate_v20_to_v21),
+    },
+];
+
+impl Database {
+    /// 当前数据库尚未执行的迁移
+    pub fn pending_migrations(&self) -> Result<Vec<PendingMigration>, AppError> {
+        let conn = lock_conn!(self.conn);
+        Self::pending_migrations_on_conn(&conn)
+    }
+
+    pub(crate) fn pending_migrations_on_conn(
+        conn: &Connection,
+    ) -> Result<Vec<PendingMigration>, AppError> {
+        let version = Self::get_user_version(conn)?;
+        Ok(MIGRATIONS
+            .iter()
+            .filter(|migration| migration.version > version)
+            .map(PendingMigration::from)
+            .collect())
+    }
+
+    /// 打开数据库文件但不建表、不迁移，供预览待执行的迁移
+    ///
+    /// 文件不存在时返回一个空的内存数据库，即首次启动时会执行全部迁移。
+    pub fn open_unmigrated() -> Result<Self, AppError> {
+        let db_path = get_database_path();
+        let conn = if db_path.is_file() {
+            Connection::open_with_flags(
+                &db_path,
+                OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+            )
+            .map_err(AppError::from)?
+        } else {
+            Connection::open_in_memory().map_err(AppError::from)?
+        };
+        Ok(Self {
+            conn: std::sync::Mutex::new(conn),
+            readers: Default::default(),
+            counters: Default::default(),
+            change_source: Default::default(),
+            key_pool_generation: Default::default(),
+        })
+    }
+
+    /// 依次执行版本高于 `version` 的迁移（调用方负责 savepoint）
+    pub(crate) fn run_migrations_on_conn(conn: &Connection, version: i32) -> Result<(), AppError> {
+        Self::create_schema_migrations_on_conn(conn)?;
+        Self::record_baseline(conn, version)?;
+        Self::verify_migration_checksums(conn, version)?;
+
+        for migration in MIGRATIONS.iter().filter(|m| m.version > version) {
+            log::info!(
+                "迁移数据库从 v{} 到 v{}（{}）",
+                migration.version - 1,
+                migration.version,
+                migration.description
+            );
+            migration.run(conn)?;
+            Self::set_user_version(conn, migration.version)?;
+            Self::record_migration(conn, migration, Some(SCHEMA_COMPAT_VERSION))?;
+        }
+        Ok(())
+    }
+
+    fn create_schema_migrations_on_conn(conn: &Connection) -> Result<(), AppError> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_migrations (
+                version INTEGER PRIMARY KEY,
+                name TEXT NOT NULL,
+                checksum TEXT NOT NULL,
+                applied_at INTEGER NOT NULL,
+                compatible_from INTEGER
+            )",
+            [],
+        )
+        .map_err(|e| AppError::Database(format!("创建 schema_migrations 表失败: {e}")))?;
+        Ok(())
+    }
+
+    /// 引入迁移记录之前已执行的迁移补记一条记录
+    fn record_baseline(conn: &Connection, version: i32) -> Result<(), AppError> {
+        for migration in MIGRATIONS.iter().filter(|m| m.version <= version) {
+            Self::record_migration(conn, migration, None)?;
+        }
+        Ok(())
+    }
+
+    /// `compatible_from` 为执行迁移的构建的 [`SCHEMA_COMPAT_VERSION`]，补记的记录为空
+    fn record_migration(
+        conn: &Connection,
+        migration: &Migration,
+        compatible_from: Option<i32>,
+    ) -> Result<(), AppError> {
+        conn.execute(
+            "INSERT OR IGNORE INTO schema_migrations
+                (version, name, checksum, applied_at, compatible_from)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                migration.version,
+                migration.name,
+                migration.checksum(),
+                chrono::Utc::now().timestamp_millis(),
+                compatible_from
+            ],
+        )
+        .map_err(|e| AppError::Database(format!("记录迁移 v{} 失败: {e}", migration.version)))?;
+        Ok(())
+    }
+
+    /// 已执行迁移中与当前注册表不一致的记录
+    pub fn migration_mismatches(&self) -> Result<Vec<MigrationMismatch>, AppError> {
+        let conn = lock_conn!(self.conn);
+        Self::migration_mismatches_on_conn(&conn)
+    }
+
+    pub(crate) fn migration_mismatches_on_conn(
+        conn: &Connection,
+    ) -> Result<Vec<MigrationMismatch>, AppError> {
+        if !Self::table_exists(conn, "schema_migrations")? {
+            return Ok(Vec::new());
+        }
+        let mut stmt = conn
+            .prepare("SELECT version, name, checksum FROM schema_migrations ORDER BY version")
+            .map_err(AppError::from)?;
+        let recorded = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i32>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })
+            .map_err(AppError::from)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(AppError::from)?;
+        Ok(recorded
+            .into_iter()
+            .filter_map(|(version, recorded_name, recorded_checksum)| {
+                let migration = MIGRATIONS.iter().find(|m| m.version == version)?;
+                let checksum = migration.checksum();
+                (migration.name != recorded_name || checksum != recorded_checksum).then(|| {
+                    MigrationMismatch {
+                        version,
+                        name: migration.name.to_string(),
+                        recorded_name,
+                        checksum,
+                        recorded_checksum,
+                    }
+                })
+            })
+            .collect())
+    }
+
+    /// 有待执行的迁移时拒绝在不一致的记录上继续迁移，否则只记录警告
+    fn verify_migration_checksums(conn: &Connection, version: i32) -> Result<(), AppError> {
+        let mismatches = Self::migration_mismatches_on_conn(conn)?;
+        if mismatches.is_empty() {
+            return Ok(());
+        }
+        if MIGRATIONS.iter().any(|m| m.version > version) {
+            return Err(MigrationMismatch::error(&mismatches));
+        }
+        for mismatch in &mismatches {
+            log::warn!("{}，已执行的迁移不会重新执行", mismatch.describe());
+        }
+        Ok(())
+    }
+}
@@ -12,9 +12,14 @@
 //! ```text
 //! database/
 //! ├── mod.rs        - Database 结构体 + 初始化
+//! ├── async_db.rs   - 异步接口（专用数据库线程）
+//! ├── pool.rs       - 只读连接池（列表、用量统计等查询）
 //! ├── schema.rs     - 表结构定义 + Schema 迁移
 //! ├── backup.rs     - SQL 导入导出 + 快照备份
+//! ├── compat.rs     - 数据库版本高于当前构建时的兼容模式
+//! ├── maintenance.rs - 完整性检查、VACUUM 与统计信息
 //! ├── migration.rs  - JSON → SQLite 数据迁移
+//! ├── migrations.rs - Schema 迁移注册表（校验和、待执行迁移预览）
 //! └── dao/          - 数据访问对象
 //!     ├── providers.rs
 //!     ├── mcp.rs
@@ -23,31 +28,69 @@
 //!     └── settings.rs
 //! ```
 
+mod async_db;
 mod backup;
+mod compat;
 mod dao;
+mod maintenance;
 mod migration;
+mod migrations;
+mod pool;
 mod schema;
 
 #[cfg(test)]
 mod tests;
 
 // DAO 类型导出供外部使用
-pub use dao::FailoverQueueItem;
+pub use dao::{
+    AuditEntry, BenchmarkResult, ChangeEvent, ChangeKind, ChangeSource, FailoverGroupMember,
+    FailoverQueueItem, JsonChange, PendingRevert, ProviderCounters, ProviderHistoryEntry,
+    ProviderKey, ProviderPage, ProviderSort, QueryOptions, SwitchHistoryEntry, Vendor,
+};
 
-use crate::config::get_app_config_dir;
+pub use async_db::AsyncDatabase;
+pub(crate) use backup::sort_json_keys;
+pub use backup::{BackupInfo, SqlExportOptions};
+pub use compat::{schema_compat, set_force_schema, FORCE_SCHEMA_ENV};
+pub(crate) use dao::provider_history::{apply_changes, diff_json};
+pub use maintenance::{DatabaseStats, TableStats};
+pub use migrations::{MigrationMismatch, PendingMigration};
+
+use crate::config::get_database_path;
 use crate::error::AppError;
-use rusqlite::Connection;
+use rusqlite::{Connection, ErrorCode, Transaction, TransactionBehavior};
 use serde::Serialize;
-use std::sync::Mutex;
+use std::sync::atomic::AtomicU64;
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
 
 // DAO 方法通过 impl Database 提供，无需额外导出
 
-/// 数据库备份保留数量
+/// 数据库备份默认保留数量
 const DB_BACKUP_RETAIN: usize = 10;
 
+/// 默认自动备份间隔（小时）
+const DB_BACKUP_INTERVAL_HOURS: u64 = 24;
+
+/// 其他进程（GUI 与命令行）持有写锁时的等待时间
+const DB_BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 每个连接缓存的预编译语句数（DAO 通过 `prepare_cached` 复用，默认的 16 条不够用）
+const DB_STATEMENT_CACHE_CAPACITY: usize = 64;
+
+/// 开始写事务时遇到 SQLITE_BUSY 的重试次数与首次退避时间（之后每次翻倍）
+const DB_BUSY_RETRIES: u32 = 5;
+const DB_BUSY_BACKOFF: Duration = Duration::from_millis(50);
+
 /// 当前 Schema 版本号
-/// 每次修改表结构时递增，并在 schema.rs 中添加相应的迁移逻辑
-pub(crate) const SCHEMA_VERSION: i32 = 2;
+/// 每次修改表结构时递增，并在 migrations.rs 的注册表末尾添加相应的迁移
+pub(crate) const SCHEMA_VERSION: i32 = 21;
+
+/// 仍能安全使用当前数据库的最低 Schema 版本（旧版本据此以兼容模式打开，见 compat.rs）
+///
+/// 只新增表、可空列或带默认值的列时保持不变；删除列、改变已有列含义或约束时提高到新的
+/// SCHEMA_VERSION。兼容模式自 v20 起才有，因此不低于 20。
+pub(crate) const SCHEMA_COMPAT_VERSION: i32 = 20;
 
 /// 安全地序列化 JSON，避免 unwrap panic
 pub(crate) fn to_json_string<T: Serialize>(value: &T) -> Result<String, AppError> {
@@ -67,52 +110,122 @@ macro_rules! lock_conn {
 // 导出宏供子模块使用
 pub(crate) use lock_conn;
 
+/// 开始写事务（BEGIN IMMEDIATE），遇到 SQLITE_BUSY 时退避重试
+///
+/// WAL 模式下，延迟事务从读升级为写时若另一进程正在写入会直接返回 SQLITE_BUSY，
+/// 不会等待 busy_timeout，因此写事务一开始就获取写锁。
+pub(crate) fn begin_write(conn: &Connection) -> Result<Transaction<'_>, AppError> {
+    let mut backoff = DB_BUSY_BACKOFF;
+    let mut attempt = 0;
+    loop {
+        match Transaction::new_unchecked(conn, TransactionBehavior::Immediate) {
+            Ok(tx) => return Ok(tx),
+            Err(e) if is_busy(&e) && attempt < DB_BUSY_RETRIES => {
+                log::debug!("数据库被占用，{}ms 后重试: {e}", backoff.as_millis());
+                std::thread::sleep(backoff);
+                backoff *= 2;
+                attempt += 1;
+            }
+            Err(e) => return Err(AppError::from(e)),
+        }
+    }
+}
+
+fn is_busy(err: &rusqlite::Error) -> bool {
+    matches!(
+        err.sqlite_error_code(),
+        Some(ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked)
+    )
+}
+
 /// 数据库连接封装
 ///
 /// 使用 Mutex 包装 Connection 以支持在多线程环境（如 Tauri State）中共享。
-/// rusqlite::Connection 本身不是 Sync 的，因此需要这层包装。
+/// rusqlite::Connection 本身不是 Sync 的，因此需要这层包装。写入只经过这一个连接，
+/// 只读查询通过 [`Database::with_read`] 使用 `readers` 中的连接，互不阻塞。
 pub struct Database {
     pub(crate) conn: Mutex<Connection>,
+    /// 只读连接池
+    pub(crate) readers: pool::ReadPool,
+    /// 尚未落盘的用量计数增量
+    pub(crate) counters: dao::CounterBuffer,
+    /// 保存供应商时记录到变更日志的默认来源
+    pub(crate) change_source: RwLock<ChangeSource>,
+    /// 本进程修改 Key 池（provider_keys 表）的次数，代理据此判断缓存的 Key 池是否过期
+    pub(crate) key_pool_generation: AtomicU64,
 }
 
 impl Database {
     /// 初始化数据库连接并创建表
     ///
-    /// 数据库文件位于 `~/.cc-switch/cc-switch.db`
+    /// 数据库文件默认位于 `~/.cc-switch/cc-switch.db`，可由 `CC_SWITCH_HOME` 或
+    /// 命令行 `--config-dir` / `--db-path` 改变
     pub fn init() -> Result<Self, AppError> {
-        let db_path = get_app_config_dir().join("cc-switch.db");
+        let db_path = get_database_path();
 
         // 确保父目录存在
         if let Some(parent) = db_path.parent() {
             std::fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
         }
 
-        let conn = Connection::open(&db_path).map_err(|e| AppError::Database(e.to_string()))?;
+        let conn = Connection::open(&db_path).map_err(AppError::from)?;
+        conn.set_prepared_statement_cache_capacity(DB_STATEMENT_CACHE_CAPACITY);
 
         // 启用外键约束
         conn.execute("PRAGMA foreign_keys = ON;", [])
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            .map_err(AppError::from)?;
+
+        // GUI 与命令行可能同时打开数据库：WAL 模式下读写互不阻塞，写入冲突时等待而不是立即报错
+        conn.busy_timeout(DB_BUSY_TIMEOUT).map_err(AppError::from)?;
+        let journal_mode: String = conn
+            .query_row("PRAGMA journal_mode = WAL;", [], |row| row.get(0))
+            .map_err(AppError::from)?;
+        if !journal_mode.eq_ignore_ascii_case("wal") {
+            log::warn!("无法启用 WAL 模式，当前日志模式: {journal_mode}");
+        }
 
+        let version = Self::get_user_version(&conn)?;
         let db = Self {
             conn: Mutex::new(conn),
+            readers: pool::ReadPool::new(db_path),
+            counters: Default::default(),
+            change_source: Default::default(),
+            key_pool_generation: Default::default(),
         };
+
+        // 更新的版本写入的数据库：不建表、不迁移，能兼容时只读打开
+        if version > SCHEMA_VERSION {
+            db.open_newer(version)?;
+            return Ok(db);
+        }
+
         db.create_tables()?;
         db.apply_schema_migrations()?;
         db.ensure_model_pricing_seeded()?;
 
+        // 只读模式：表结构就绪后拒绝一切写入
+        if crate::settings::is_read_only() {
+            db.set_query_only(true)?;
+        }
+
         Ok(db)
     }
 
     /// 创建内存数据库（用于测试）
     pub fn memory() -> Result<Self, AppError> {
-        let conn = Connection::open_in_memory().map_err(|e| AppError::Database(e.to_string()))?;
+        let conn = Connection::open_in_memory().map_err(AppError::from)?;
+        conn.set_prepared_statement_cache_capacity(DB_STATEMENT_CACHE_CAPACITY);
 
         // 启用外键约束
         conn.execute("PRAGMA foreign_keys = ON;", [])
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            .map_err(AppError::from)?;
 
         let db = Self {
             conn: Mutex::new(conn),
+            readers: Default::default(),
+            counters: Default::default(),
+            change_source: Default::default(),
+            key_pool_generation: Default::default(),
         };
         db.create_tables()?;
         db.ensure_model_pricing_seeded()?;
@@ -120,12 +233,20 @@ impl Database {
         Ok(db)
     }
 
+    /// 开启后 SQLite 拒绝该连接上的所有写入（`PRAGMA query_only`），写入返回
+    /// [`AppError::ReadOnly`]
+    pub(crate) fn set_query_only(&self, enabled: bool) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.pragma_update(None, "query_only", enabled)
+            .map_err(AppError::from)
+    }
+
     /// 检查 MCP 服务器表是否为空
     pub fn is_mcp_table_empty(&self) -> Result<bool, AppError> {
         let conn = lock_conn!(self.conn);
         let count: i64 = conn
             .query_row("SELECT COUNT(*) FROM mcp_servers", [], |row| row.get(0))
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            .map_err(AppError::from)?;
         Ok(count == 0)
     }
 
@@ -134,7 +255,7 @@ impl Database {
         let conn = lock_conn!(self.conn);
         let count: i64 = conn
             .query_row("SELECT COUNT(*) FROM prompts", [], |row| row.get(0))
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            .map_err(AppError::from)?;
         Ok(count == 0)
     }
 }
@@ -0,0 +1,75 @@
+//! 只读连接池
+//!
+//! 写入仍然经过 [`Database::conn`] 这一个连接；列表、用量统计等只读查询从池中取一个只读连接，
+//! WAL 模式下它们彼此并行，也不会被正在进行的写入阻塞。内存数据库（测试、导入预检等）
+//! 没有文件可供其他连接打开，读取回落到写连接。
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use rusqlite::{Connection, OpenFlags};
+
+use super::{lock_conn, Database, DB_BUSY_TIMEOUT, DB_STATEMENT_CACHE_CAPACITY};
+use crate::error::AppError;
+
+/// 池中保留的空闲只读连接数（并发更高时临时打开的连接用完即关闭）
+const READ_POOL_SIZE: usize = 4;
+
+#[derive(Default)]
+pub(crate) struct ReadPool {
+    /// 数据库文件；为 None 时读取使用写连接
+    path: Option<PathBuf>,
+    idle: Mutex<Vec<Connection>>,
+}
+
+impl ReadPool {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self {
+            path: Some(path),
+            idle: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn open(path: &Path) -> Result<Connection, AppError> {
+        let conn = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+        .map_err(AppError::from)?;
+        conn.busy_timeout(DB_BUSY_TIMEOUT).map_err(AppError::from)?;
+        conn.set_prepared_statement_cache_capacity(DB_STATEMENT_CACHE_CAPACITY);
+        Ok(conn)
+    }
+}
+
+impl Database {
+    /// 用只读连接执行查询
+    pub(crate) fn with_read<T>(
+        &self,
+        f: impl FnOnce(&Connection) -> Result<T, AppError>,
+    ) -> Result<T, AppError> {
+        let Some(path) = &self.readers.path else {
+            return self.with_write(f);
+        };
+        let idle = self.readers.idle.lock()?.pop();
+        let conn = match idle {
+            Some(conn) => conn,
+            None => ReadPool::open(path)?,
+        };
+        let result = f(&conn);
+        let mut idle = self.readers.idle.lock()?;
+        if idle.len() < READ_POOL_SIZE {
+            idle.push(conn);
+        }
+        result
+    }
+
+    /// 用写连接执行（同一时刻只有一个写入者）
+    pub(crate) fn with_write<T>(
+        &self,
+        f: impl FnOnce(&Connection) -> Result<T, AppError>,
+    ) -> Result<T, AppError> {
+        let conn = lock_conn!(self.conn);
+        f(&conn)
+    }
+}
@@ -1,11 +1,19 @@
 //! Schema 定义和迁移
 //!
-//! 负责数据库表结构的创建和版本迁移。
+//! 负责数据库表结构的创建和版本迁移；迁移的注册与执行见 [`super::migrations`]。
 
 use super::{lock_conn, Database, SCHEMA_VERSION};
 use crate::error::AppError;
 use rusqlite::Connection;
 
+/// 从 settings_config 提取全文索引端点字段的 SQL 表达式（`{t}` 为行别名）
+const PROVIDER_FTS_ENDPOINT: &str = "CASE WHEN json_valid({t}.settings_config) THEN COALESCE(
+    json_extract({t}.settings_config, '$.env.ANTHROPIC_BASE_URL'),
+    json_extract({t}.settings_config, '$.env.GOOGLE_GEMINI_BASE_URL'),
+    CASE WHEN json_type({t}.settings_config, '$.config') = 'text'
+        THEN json_extract({t}.settings_config, '$.config') END
+) END";
+
 impl Database {
     /// 创建所有数据库表
     pub(crate) fn create_tables(&self) -> Result<(), AppError> {
@@ -32,11 +40,19 @@ impl Database {
                 meta TEXT NOT NULL DEFAULT '{}',
                 is_current BOOLEAN NOT NULL DEFAULT 0,
                 in_failover_queue BOOLEAN NOT NULL DEFAULT 0,
+                failover_group TEXT,
+                failover_priority INTEGER,
+                updated_at INTEGER,
+                revision INTEGER NOT NULL DEFAULT 0,
+                vendor_id TEXT,
+                is_pinned BOOLEAN NOT NULL DEFAULT 0,
+                alias TEXT,
+                owner TEXT,
                 PRIMARY KEY (id, app_type)
             )",
             [],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         // 2. Provider Endpoints 表
         conn.execute(
@@ -46,11 +62,130 @@ impl Database {
                 app_type TEXT NOT NULL,
                 url TEXT NOT NULL,
                 added_at INTEGER,
+                last_used INTEGER,
+                latency_ms INTEGER,
+                tested_at INTEGER,
+                FOREIGN KEY (provider_id, app_type) REFERENCES providers(id, app_type) ON DELETE CASCADE
+            )",
+            [],
+        )
+        .map_err(AppError::from)?;
+
+        // 2.1 Provider Tags 表
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS provider_tags (
+                provider_id TEXT NOT NULL,
+                app_type TEXT NOT NULL,
+                tag TEXT NOT NULL,
+                PRIMARY KEY (provider_id, app_type, tag),
+                FOREIGN KEY (provider_id, app_type) REFERENCES providers(id, app_type) ON DELETE CASCADE
+            )",
+            [],
+        )
+        .map_err(AppError::from)?;
+
+        // 2.2 Provider 全文索引（FTS5，由触发器维护）
+        Self::create_provider_fts_on_conn(conn)?;
+
+        // 2.3 分类默认供应商表（每个应用的每个分类至多一个）
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS category_defaults (
+                app_type TEXT NOT NULL,
+                category TEXT NOT NULL,
+                provider_id TEXT NOT NULL,
+                PRIMARY KEY (app_type, category),
                 FOREIGN KEY (provider_id, app_type) REFERENCES providers(id, app_type) ON DELETE CASCADE
             )",
             [],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
+
+        // 2.4 审计日志（吊销密钥等敏感操作）
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS audit_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                action TEXT NOT NULL,
+                app_type TEXT,
+                provider_id TEXT,
+                detail TEXT,
+                created_at INTEGER NOT NULL
+            )",
+            [],
+        )
+        .map_err(AppError::from)?;
+
+        // 2.5 切换历史（cwd 为发起切换的工作目录，GUI 切换时为空）
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS switch_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                app_type TEXT NOT NULL,
+                provider_id TEXT NOT NULL,
+                provider_name TEXT NOT NULL,
+                cwd TEXT,
+                switched_at INTEGER NOT NULL
+            )",
+            [],
+        )
+        .map_err(AppError::from)?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_switch_history_app
+             ON switch_history(app_type, switched_at)",
+            [],
+        )
+        .map_err(AppError::from)?;
+
+        // 2.5.1 供应商变更日志
+        Self::create_provider_history_on_conn(conn)?;
+
+        // 2.5.2 跨进程变更通知
+        Self::create_change_log_on_conn(conn)?;
+
+        // 2.5.3 供应商 Key 池
+        Self::create_provider_keys_on_conn(conn)?;
+
+        // 2.5.4 限时切换的待恢复记录
+        Self::create_pending_reverts_on_conn(conn)?;
+
+        // 2.5.5 服务商账号（供应商分组）
+        Self::create_vendors_on_conn(conn)?;
+
+        // 2.6 供应商用量计数器
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS provider_usage_counters (
+                app_type TEXT NOT NULL,
+                provider_id TEXT NOT NULL,
+                requests INTEGER NOT NULL DEFAULT 0,
+                tokens INTEGER NOT NULL DEFAULT 0,
+                updated_at INTEGER NOT NULL,
+                PRIMARY KEY (app_type, provider_id)
+            )",
+            [],
+        )
+        .map_err(AppError::from)?;
+
+        // 2.7 供应商基准测试结果
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS benchmarks (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                app_type TEXT NOT NULL,
+                provider_id TEXT NOT NULL,
+                success INTEGER NOT NULL,
+                ttfb_ms INTEGER,
+                total_ms INTEGER,
+                output_tokens INTEGER,
+                tokens_per_sec REAL,
+                error TEXT,
+                created_at INTEGER NOT NULL
+            )",
+            [],
+        )
+        .map_err(AppError::from)?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_benchmarks_provider
+             ON benchmarks(app_type, provider_id, created_at)",
+            [],
+        )
+        .map_err(AppError::from)?;
 
         // 3. MCP Servers 表
         conn.execute(
@@ -68,7 +203,7 @@ impl Database {
             )",
             [],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         // 4. Prompts 表
         conn.execute(
@@ -85,7 +220,7 @@ impl Database {
             )",
             [],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         // 5. Skills 表
         conn.execute(
@@ -98,7 +233,7 @@ impl Database {
             )",
             [],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         // 6. Skill Repos 表
         conn.execute(
@@ -111,7 +246,7 @@ impl Database {
             )",
             [],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         // 7. Settings 表 (通用配置)
         conn.execute(
@@ -121,7 +256,7 @@ impl Database {
             )",
             [],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         // 8. Proxy Config 表 (代理服务器配置)
         // 代理配置表（单例）
@@ -140,7 +275,7 @@ impl Database {
             )",
             [],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         // 尝试添加 target_app 列（如果表已存在但缺少该列）
         // 忽略 "duplicate column name" 错误
@@ -165,7 +300,7 @@ impl Database {
             )",
             [],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         // 10. Proxy Request Logs 表 (详细请求日志)
         conn.execute(
@@ -196,42 +331,42 @@ impl Database {
             )",
             [],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_request_logs_provider
              ON proxy_request_logs(provider_id, app_type)",
             [],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_request_logs_created_at
              ON proxy_request_logs(created_at)",
             [],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_request_logs_model
              ON proxy_request_logs(model)",
             [],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_request_logs_session
              ON proxy_request_logs(session_id)",
             [],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_request_logs_status
              ON proxy_request_logs(status_code)",
             [],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         // 11. Model Pricing 表 (模型定价)
         conn.execute(
@@ -245,7 +380,7 @@ impl Database {
             )",
             [],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         // 12. Stream Check Logs 表 (流式健康检查日志)
         conn.execute(
@@ -265,14 +400,14 @@ impl Database {
             )",
             [],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_stream_check_logs_provider
              ON stream_check_logs(app_type, provider_id, tested_at DESC)",
             [],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         // 13. Circuit Breaker Config 表 (熔断器配置)
         conn.execute(
@@ -287,14 +422,14 @@ impl Database {
             )",
             [],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         // 插入默认熔断器配置
         conn.execute(
             "INSERT OR IGNORE INTO circuit_breaker_config (id) VALUES (1)",
             [],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         // 16. Proxy Live Backup 表 (Live 配置备份)
         conn.execute(
@@ -305,7 +440,7 @@ impl Database {
             )",
             [],
         )
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         // 尝试添加 live_takeover_active 列到 proxy_config 表
         let _ = conn.execute(
@@ -346,41 +481,18 @@ impl Database {
         conn.execute("SAVEPOINT schema_migration;", [])
             .map_err(|e| AppError::Database(format!("开启迁移 savepoint 失败: {e}")))?;
 
-        let mut version = Self::get_user_version(conn)?;
+        let version = Self::get_user_version(conn)?;
 
         if version > SCHEMA_VERSION {
             conn.execute("ROLLBACK TO schema_migration;", []).ok();
             conn.execute("RELEASE schema_migration;", []).ok();
-            return Err(AppError::Database(format!(
-                "数据库版本过新（{version}），当前应用仅支持 {SCHEMA_VERSION}，请升级应用后再尝试。"
-            )));
+            return Err(AppError::SchemaTooNew {
+                found: version,
+                supported: SCHEMA_VERSION,
+            });
         }
 
-        let result = (|| {
-            while version < SCHEMA_VERSION {
-                match version {
-                    0 => {
-                        log::info!("检测到 user_version=0，迁移到 1（补齐缺失列并设置版本）");
-                        Self::migrate_v0_to_v1(conn)?;
-                        Self::set_user_version(conn, 1)?;
-                    }
-                    1 => {
-                        log::info!(
-                            "迁移数据库从 v1 到 v2（添加使用统计表和完整字段，重构 skills 表）"
-                        );
-                        Self::migrate_v1_to_v2(conn)?;
-                        Self::set_user_version(conn, 2)?;
-                    }
-                    _ => {
-                        return Err(AppError::Database(format!(
-                            "未知的数据库版本 {version}，无法迁移到 {SCHEMA_VERSION}"
-                        )));
-                    }
-                }
-                version = Self::get_user_version(conn)?;
-            }
-            Ok(())
-        })();
+        let result = Self::run_migrations_on_conn(conn, version);
 
         match result {
             Ok(_) => {
@@ -397,7 +509,7 @@ impl Database {
     }
 
     /// v0 -> v1 迁移：补齐所有缺失列
-    fn migrate_v0_to_v1(conn: &Connection) -> Result<(), AppError> {
+    pub(super) fn migrate_v0_to_v1(conn: &Connection) -> Result<(), AppError> {
         // providers 表
         Self::add_column_if_missing(conn, "providers", "category", "TEXT")?;
         Self::add_column_if_missing(conn, "providers", "created_at", "INTEGER")?;
@@ -457,7 +569,7 @@ impl Database {
     }
 
     /// v1 -> v2 迁移：添加使用统计表和完整字段，重构 skills 表
-    fn migrate_v1_to_v2(conn: &Connection) -> Result<(), AppError> {
+    pub(super) fn migrate_v1_to_v2(conn: &Connection) -> Result<(), AppError> {
         // providers 表字段
         Self::add_column_if_missing(
             conn,
@@ -560,6 +672,368 @@ impl Database {
         Ok(())
     }
 
+    /// v2 -> v3 迁移：添加故障转移组（有序的备用供应商链）
+    pub(super) fn migrate_v2_to_v3(conn: &Connection) -> Result<(), AppError> {
+        Self::add_column_if_missing(conn, "providers", "failover_group", "TEXT")?;
+        Self::add_column_if_missing(conn, "providers", "failover_priority", "INTEGER")?;
+
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_providers_failover_group
+             ON providers(app_type, failover_group, failover_priority)",
+            [],
+        )
+        .map_err(|e| AppError::Database(format!("创建故障转移组索引失败: {e}")))?;
+
+        Ok(())
+    }
+
+    /// v4 -> v5 迁移：添加供应商全文索引并为已有数据建立索引
+    pub(super) fn migrate_v4_to_v5(conn: &Connection) -> Result<(), AppError> {
+        Self::create_provider_fts_on_conn(conn)?;
+        Self::rebuild_provider_fts(conn)
+    }
+
+    /// v10 -> v11 迁移：自定义端点记录最近使用时间与测速结果
+    pub(super) fn migrate_v10_to_v11(conn: &Connection) -> Result<(), AppError> {
+        Self::add_column_if_missing(conn, "provider_endpoints", "last_used", "INTEGER")?;
+        Self::add_column_if_missing(conn, "provider_endpoints", "latency_ms", "INTEGER")?;
+        Self::add_column_if_missing(conn, "provider_endpoints", "tested_at", "INTEGER")?;
+        Ok(())
+    }
+
+    /// v11 -> v12 迁移：供应商记录修改时间（同步时按供应商合并），已有数据以创建时间为准
+    pub(super) fn migrate_v11_to_v12(conn: &Connection) -> Result<(), AppError> {
+        Self::add_column_if_missing(conn, "providers", "updated_at", "INTEGER")?;
+        conn.execute(
+            "UPDATE providers SET updated_at = created_at WHERE updated_at IS NULL",
+            [],
+        )
+        .map_err(|e| AppError::Database(format!("回填 providers.updated_at 失败: {e}")))?;
+        Ok(())
+    }
+
+    /// v12 -> v13 迁移：供应商修订号与变更日志（已有供应商从修订 0 开始）
+    pub(super) fn migrate_v12_to_v13(conn: &Connection) -> Result<(), AppError> {
+        Self::add_column_if_missing(conn, "providers", "revision", "INTEGER NOT NULL DEFAULT 0")?;
+        Self::create_provider_history_on_conn(conn)
+    }
+
+    /// 创建供应商变更日志表（diff 为 JSON 数组，见 [`super::JsonChange`]）
+    fn create_provider_history_on_conn(conn: &Connection) -> Result<(), AppError> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS provider_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                app_type TEXT NOT NULL,
+                provider_id TEXT NOT NULL,
+                revision INTEGER NOT NULL,
+                changed_at INTEGER NOT NULL,
+                source TEXT NOT NULL,
+                diff TEXT NOT NULL
+            )",
+            [],
+        )
+        .map_err(|e| AppError::Database(format!("创建 provider_history 表失败: {e}")))?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_provider_history_provider
+             ON provider_history(app_type, provider_id, revision)",
+            [],
+        )
+        .map_err(|e| AppError::Database(format!("创建 provider_history 索引失败: {e}")))?;
+        Ok(())
+    }
+
+    /// v13 -> v14 迁移：跨进程变更通知
+    pub(super) fn migrate_v13_to_v14(conn: &Connection) -> Result<(), AppError> {
+        Self::create_change_log_on_conn(conn)
+    }
+
+    /// v14 -> v15 迁移：供应商 Key 池
+    pub(super) fn migrate_v14_to_v15(conn: &Connection) -> Result<(), AppError> {
+        Self::create_provider_keys_on_conn(conn)
+    }
+
+    /// v15 -> v16 迁移：限时切换的待恢复记录
+    pub(super) fn migrate_v15_to_v16(conn: &Connection) -> Result<(), AppError> {
+        Self::create_pending_reverts_on_conn(conn)
+    }
+
+    /// v16 -> v17 迁移：服务商账号及供应商的 vendor_id
+    pub(super) fn migrate_v16_to_v17(conn: &Connection) -> Result<(), AppError> {
+        Self::add_column_if_missing(conn, "providers", "vendor_id", "TEXT")?;
+        Self::create_vendors_on_conn(conn)
+    }
+
+    /// v17 -> v18 迁移：供应商置顶标记
+    pub(super) fn migrate_v17_to_v18(conn: &Connection) -> Result<(), AppError> {
+        Self::add_column_if_missing(conn, "providers", "is_pinned", "BOOLEAN NOT NULL DEFAULT 0")
+    }
+
+    /// v18 -> v19 迁移：供应商别名（同一应用内唯一）
+    pub(super) fn migrate_v18_to_v19(conn: &Connection) -> Result<(), AppError> {
+        Self::add_column_if_missing(conn, "providers", "alias", "TEXT")?;
+        conn.execute(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_providers_alias
+             ON providers(app_type, alias) WHERE alias IS NOT NULL",
+            [],
+        )
+        .map_err(|e| AppError::Database(format!("创建供应商别名索引失败: {e}")))?;
+        Ok(())
+    }
+
+    /// v19 -> v20 迁移：供应商所有者（创建者的系统用户名）
+    pub(super) fn migrate_v19_to_v20(conn: &Connection) -> Result<(), AppError> {
+        Self::add_column_if_missing(conn, "providers", "owner", "TEXT")
+    }
+
+    /// v20 -> v21 迁移：Gemini 供应商 `meta.geminiKeyPool` 中的备用 Key 移入 provider_keys 表
+    ///
+    /// 主 Key 排在最前面，与原来的轮换顺序一致；池中至少有两个 Key 且未设置轮换时机时改为
+    /// 由代理按请求轮换，保持原来遇到 429 换 Key 的行为。
+    pub(super) fn migrate_v20_to_v21(conn: &Connection) -> Result<(), AppError> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, settings_config, meta FROM providers
+                 WHERE app_type = 'gemini' AND meta LIKE '%geminiKeyPool%'",
+            )
+            .map_err(AppError::from)?;
+        let rows: Vec<(String, String, String)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .map_err(AppError::from)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(AppError::from)?;
+        drop(stmt);
+
+        let now = chrono::Utc::now().timestamp_millis();
+        for (id, settings_config, meta) in rows {
+            let Ok(mut meta) = serde_json::from_str::<serde_json::Value>(&meta) else {
+                continue;
+            };
+            let Some(pool) = meta.as_object_mut().and_then(|m| m.remove("geminiKeyPool")) else {
+                continue;
+            };
+            let primary = serde_json::from_str::<serde_json::Value>(&settings_config)
+                .ok()
+                .and_then(|settings| {
+                    settings
+                        .pointer("/env/GEMINI_API_KEY")
+                        .and_then(|key| key.as_str())
+                        .map(str::to_string)
+                });
+
+            let pool = pool.as_array().into_iter().flatten();
+            let mut keys: Vec<String> = Vec::new();
+            for key in primary
+                .as_deref()
+                .into_iter()
+                .chain(pool.filter_map(|k| k.as_str()))
+            {
+                let key = key.trim();
+                if !key.is_empty() && !keys.iter().any(|k| k == key) {
+                    keys.push(key.to_string());
+                }
+            }
+            for key in &keys {
+                conn.execute(
+                    "INSERT OR IGNORE INTO provider_keys (provider_id, app_type, api_key, weight, added_at)
+                     VALUES (?1, 'gemini', ?2, 1, ?3)",
+                    rusqlite::params![id, key, now],
+                )
+                .map_err(|e| AppError::Database(format!("迁移 Gemini Key 池失败: {e}")))?;
+            }
+
+            if let Some(meta) = meta.as_object_mut() {
+                if keys.len() > 1 && !meta.contains_key("keyRotation") {
+                    meta.insert("keyRotation".to_string(), serde_json::json!("proxy"));
+                }
+            }
+            conn.execute(
+                "UPDATE providers SET meta = ?1 WHERE id = ?2 AND app_type = 'gemini'",
+                rusqlite::params![meta.to_string(), id],
+            )
+            .map_err(|e| AppError::Database(format!("迁移 Gemini Key 池失败: {e}")))?;
+        }
+        Ok(())
+    }
+
+    /// 创建服务商账号表
+    fn create_vendors_on_conn(conn: &Connection) -> Result<(), AppError> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS vendors (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                website_url TEXT,
+                notes TEXT,
+                created_at INTEGER NOT NULL
+            )",
+            [],
+        )
+        .map_err(|e| AppError::Database(format!("创建 vendors 表失败: {e}")))?;
+        Ok(())
+    }
+
+    /// 创建限时切换待恢复表（每个应用最多一条）
+    fn create_pending_reverts_on_conn(conn: &Connection) -> Result<(), AppError> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS pending_reverts (
+                app_type TEXT PRIMARY KEY,
+                from_id TEXT NOT NULL,
+                to_id TEXT NOT NULL,
+                revert_at INTEGER,
+                token INTEGER NOT NULL,
+                pid INTEGER,
+                created_at INTEGER NOT NULL
+            )",
+            [],
+        )
+        .map_err(|e| AppError::Database(format!("创建 pending_reverts 表失败: {e}")))?;
+        Ok(())
+    }
+
+    /// 创建供应商 Key 池表（同一供应商内 Key 不重复）
+    fn create_provider_keys_on_conn(conn: &Connection) -> Result<(), AppError> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS provider_keys (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                provider_id TEXT NOT NULL,
+                app_type TEXT NOT NULL,
+                api_key TEXT NOT NULL,
+                label TEXT,
+                weight INTEGER NOT NULL DEFAULT 1,
+                use_count INTEGER NOT NULL DEFAULT 0,
+                last_used INTEGER,
+                added_at INTEGER NOT NULL,
+                UNIQUE (provider_id, app_type, api_key),
+                FOREIGN KEY (provider_id, app_type) REFERENCES providers(id, app_type) ON DELETE CASCADE
+            )",
+            [],
+        )
+        .map_err(|e| AppError::Database(format!("创建 provider_keys 表失败: {e}")))?;
+        Ok(())
+    }
+
+    /// 创建变更通知表及写入它的触发器（见 [`super::ChangeEvent`]）
+    ///
+    /// 新增与修改取自变更日志（只有内容真正变化时才会写入），切换取自 is_current 的变化。
+    fn create_change_log_on_conn(conn: &Connection) -> Result<(), AppError> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS change_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                kind TEXT NOT NULL,
+                app_type TEXT NOT NULL,
+                provider_id TEXT NOT NULL,
+                changed_at INTEGER NOT NULL
+            )",
+            [],
+        )
+        .map_err(|e| AppError::Database(format!("创建 change_log 表失败: {e}")))?;
+
+        let now = "CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER)";
+        let triggers = [
+            format!(
+                "CREATE TRIGGER IF NOT EXISTS change_log_provider_history
+                 AFTER INSERT ON provider_history WHEN new.source <> 'baseline' BEGIN
+                    INSERT INTO change_log (kind, app_type, provider_id, changed_at)
+                    VALUES (
+                        CASE WHEN json_extract(new.diff, '$[0].path') = ''
+                             THEN 'provider_added' ELSE 'provider_updated' END,
+                        new.app_type, new.provider_id, {now}
+                    );
+                END"
+            ),
+            format!(
+                "CREATE TRIGGER IF NOT EXISTS change_log_provider_delete
+                 AFTER DELETE ON providers BEGIN
+                    INSERT INTO change_log (kind, app_type, provider_id, changed_at)
+                    VALUES ('provider_deleted', old.app_type, old.id, {now});
+                END"
+            ),
+            format!(
+                "CREATE TRIGGER IF NOT EXISTS change_log_provider_switch
+                 AFTER UPDATE OF is_current ON providers
+                 WHEN new.is_current = 1 AND old.is_current = 0 BEGIN
+                    INSERT INTO change_log (kind, app_type, provider_id, changed_at)
+                    VALUES ('provider_switched', new.app_type, new.id, {now});
+                END"
+            ),
+            format!(
+                "CREATE TRIGGER IF NOT EXISTS change_log_retain
+                 AFTER INSERT ON change_log BEGIN
+                    DELETE FROM change_log WHERE id <= new.id - {};
+                END",
+                super::dao::change_log::CHANGE_LOG_RETAIN
+            ),
+        ];
+        for sql in &triggers {
+            conn.execute(sql, [])
+                .map_err(|e| AppError::Database(format!("创建 change_log 触发器失败: {e}")))?;
+        }
+        Ok(())
+    }
+
+    /// 创建供应商全文索引表及维护触发器
+    ///
+    /// 索引 name / notes / category 以及从 settings_config 中提取的端点：
+    /// Claude 与 Gemini 取 env 中的 Base URL，Codex 取整段 config.toml 文本。
+    fn create_provider_fts_on_conn(conn: &Connection) -> Result<(), AppError> {
+        conn.execute(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS providers_fts USING fts5(
+                provider_id UNINDEXED,
+                app_type UNINDEXED,
+                name,
+                notes,
+                category,
+                endpoint,
+                tokenize = 'unicode61 remove_diacritics 2'
+            )",
+            [],
+        )
+        .map_err(|e| AppError::Database(format!("创建 providers_fts 表失败: {e}")))?;
+
+        let triggers = [
+            format!(
+                "CREATE TRIGGER IF NOT EXISTS providers_fts_insert AFTER INSERT ON providers BEGIN
+                    INSERT INTO providers_fts (provider_id, app_type, name, notes, category, endpoint)
+                    VALUES (new.id, new.app_type, new.name, new.notes, new.category, {});
+                END",
+                PROVIDER_FTS_ENDPOINT.replace("{t}", "new")
+            ),
+            "CREATE TRIGGER IF NOT EXISTS providers_fts_delete AFTER DELETE ON providers BEGIN
+                DELETE FROM providers_fts WHERE provider_id = old.id AND app_type = old.app_type;
+            END"
+                .to_string(),
+            format!(
+                "CREATE TRIGGER IF NOT EXISTS providers_fts_update AFTER UPDATE ON providers BEGIN
+                    DELETE FROM providers_fts WHERE provider_id = old.id AND app_type = old.app_type;
+                    INSERT INTO providers_fts (provider_id, app_type, name, notes, category, endpoint)
+                    VALUES (new.id, new.app_type, new.name, new.notes, new.category, {});
+                END",
+                PROVIDER_FTS_ENDPOINT.replace("{t}", "new")
+            ),
+        ];
+        for sql in &triggers {
+            conn.execute(sql, [])
+                .map_err(|e| AppError::Database(format!("创建 providers_fts 触发器失败: {e}")))?;
+        }
+
+        Ok(())
+    }
+
+    /// 根据 providers 表重建全文索引
+    pub(crate) fn rebuild_provider_fts(conn: &Connection) -> Result<(), AppError> {
+        conn.execute("DELETE FROM providers_fts", [])
+            .map_err(|e| AppError::Database(format!("清空 providers_fts 失败: {e}")))?;
+        conn.execute(
+            &format!(
+                "INSERT INTO providers_fts (provider_id, app_type, name, notes, category, endpoint)
+                 SELECT id, app_type, name, notes, category, {} FROM providers",
+                PROVIDER_FTS_ENDPOINT.replace("{t}", "providers")
+            ),
+            [],
+        )
+        .map_err(|e| AppError::Database(format!("重建 providers_fts 失败: {e}")))?;
+        Ok(())
+    }
+
     /// 迁移 skills 表：从单 key 主键改为 (directory, app_type) 复合主键
     fn migrate_skills_table(conn: &Connection) -> Result<(), AppError> {
         // 检查是否已经是新表结构
@@ -816,7 +1290,7 @@ impl Database {
         let mut rows = stmt
             .query([])
             .map_err(|e| AppError::Database(format!("查询表名失败: {e}")))?;
-        while let Some(row) = rows.next().map_err(|e| AppError::Database(e.to_string()))? {
+        while let Some(row) = rows.next().map_err(AppError::from)? {
             let name: String = row
                 .get(0)
                 .map_err(|e| AppError::Database(format!("解析表名失败: {e}")))?;
@@ -842,7 +1316,7 @@ impl Database {
         let mut rows = stmt
             .query([])
             .map_err(|e| AppError::Database(format!("查询表结构失败: {e}")))?;
-        while let Some(row) = rows.next().map_err(|e| AppError::Database(e.to_string()))? {
+        while let Some(row) = rows.next().map_err(AppError::from)? {
             let name: String = row
                 .get(1)
                 .map_err(|e| AppError::Database(format!("读取列名失败: {e}")))?;
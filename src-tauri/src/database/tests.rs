@@ -6,7 +6,7 @@ use super::*;
 use crate::app_config::MultiAppConfig;
 use crate::provider::{Provider, ProviderManager};
 use indexmap::IndexMap;
-use rusqlite::Connection;
+use rusqlite::{Connection, StatementStatus};
 use serde_json::json;
 use std::collections::HashMap;
 
@@ -102,6 +102,120 @@ fn migration_sets_user_version_when_missing() {
     );
 }
 
+#[test]
+fn migration_registry_is_contiguous_and_ends_at_schema_version() {
+    for (index, migration) in migrations::MIGRATIONS.iter().enumerate() {
+        assert_eq!(migration.version, index as i32 + 1, "{}", migration.name);
+    }
+    assert_eq!(
+        migrations::MIGRATIONS.last().map(|m| m.version),
+        Some(SCHEMA_VERSION)
+    );
+}
+
+#[test]
+fn migrations_record_checksums_and_clear_pending() {
+    let conn = Connection::open_in_memory().expect("open memory db");
+    Database::set_user_version(&conn, 0).expect("set version");
+    conn.execute_batch(LEGACY_SCHEMA_SQL)
+        .expect("seed old schema");
+
+    let pending = Database::pending_migrations_on_conn(&conn).expect("pending before");
+    assert_eq!(pending.len(), SCHEMA_VERSION as usize);
+    assert_eq!(pending[0].name, "fill_missing_columns");
+
+    Database::apply_schema_migrations_on_conn(&conn).expect("apply migrations");
+    assert!(Database::pending_migrations_on_conn(&conn)
+        .expect("pending after")
+        .is_empty());
+
+    let (count, checksum): (i64, String) = conn
+        .query_row(
+            "SELECT COUNT(*), (SELECT checksum FROM schema_migrations WHERE version = 4)
+             FROM schema_migrations",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .expect("read schema_migrations");
+    assert_eq!(count, SCHEMA_VERSION as i64);
+    assert_eq!(checksum, migrations::MIGRATIONS[3].checksum());
+}
+
+#[test]
+fn existing_databases_get_baseline_migration_records() {
+    let conn = Connection::open_in_memory().expect("open memory db");
+    Database::create_tables_on_conn(&conn).expect("create tables");
+    Database::set_user_version(&conn, SCHEMA_VERSION).expect("set version");
+
+    Database::apply_schema_migrations_on_conn(&conn).expect("apply migrations");
+    let count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM schema_migrations", [], |row| {
+            row.get(0)
+        })
+        .expect("count records");
+    assert_eq!(count, SCHEMA_VERSION as i64);
+}
+
+#[test]
+fn checksum_mismatch_is_reported_and_blocks_pending_migrations() {
+    let conn = Connection::open_in_memory().expect("open memory db");
+    Database::create_tables_on_conn(&conn).expect("create tables");
+    Database::set_user_version(&conn, SCHEMA_VERSION).expect("set version");
+    Database::apply_schema_migrations_on_conn(&conn).expect("apply migrations");
+    conn.execute(
+        "UPDATE schema_migrations SET checksum = 'edited' WHERE version = 4",
+        [],
+    )
+    .expect("tamper checksum");
+
+    let mismatches = Database::migration_mismatches_on_conn(&conn).expect("read mismatches");
+    assert_eq!(
+        mismatches.iter().map(|m| m.version).collect::<Vec<_>>(),
+        [4]
+    );
+    assert_eq!(mismatches[0].recorded_checksum, "edited");
+
+    // 没有待执行的迁移时照常打开
+    Database::apply_schema_migrations_on_conn(&conn).expect("nothing to migrate");
+
+    Database::set_user_version(&conn, SCHEMA_VERSION - 1).expect("set version");
+    let err = Database::apply_schema_migrations_on_conn(&conn)
+        .expect_err("pending migration on tampered history");
+    assert!(err.to_string().contains("v4"), "unexpected error: {err}");
+    assert_eq!(
+        Database::get_user_version(&conn).expect("read version"),
+        SCHEMA_VERSION - 1
+    );
+}
+
+#[test]
+fn newer_schema_opens_only_when_declared_compatible() {
+    let conn = Connection::open_in_memory().expect("open memory db");
+    Database::create_tables_on_conn(&conn).expect("create tables");
+    Database::apply_schema_migrations_on_conn(&conn).expect("apply migrations");
+    assert_eq!(
+        Database::compatible_from_on_conn(&conn, SCHEMA_VERSION).expect("read compat"),
+        Some(SCHEMA_COMPAT_VERSION)
+    );
+
+    // 模拟更新的构建追加的迁移
+    let newer = SCHEMA_VERSION + 1;
+    Database::set_user_version(&conn, newer).expect("set newer version");
+    for (compatible_from, expected) in [(SCHEMA_COMPAT_VERSION, true), (newer, false)] {
+        conn.execute(
+            "INSERT OR REPLACE INTO schema_migrations
+                (version, name, checksum, applied_at, compatible_from)
+             VALUES (?1, 'future', '', 0, ?2)",
+            rusqlite::params![newer, compatible_from],
+        )
+        .expect("record newer migration");
+        assert_eq!(
+            Database::can_open_newer_on_conn(&conn).expect("check compat"),
+            expected
+        );
+    }
+}
+
 #[test]
 fn migration_rejects_future_version() {
     let conn = Connection::open_in_memory().expect("open memory db");
@@ -130,7 +244,12 @@ fn migration_adds_missing_columns_for_providers() {
     for (table, column) in [
         ("providers", "meta"),
         ("providers", "is_current"),
+        ("providers", "failover_group"),
+        ("providers", "failover_priority"),
+        ("providers", "updated_at"),
+        ("providers", "revision"),
         ("provider_endpoints", "added_at"),
+        ("provider_endpoints", "latency_ms"),
         ("mcp_servers", "enabled_gemini"),
         ("prompts", "updated_at"),
         ("skills", "installed_at"),
@@ -246,6 +365,7 @@ fn dry_run_validates_schema_compatibility() {
             icon: None,
             icon_color: None,
             in_failover_queue: false,
+            tags: Vec::new(),
         },
     );
 
@@ -332,3 +452,1205 @@ fn model_pricing_is_seeded_on_init() {
         gemini_count
     );
 }
+
+#[test]
+fn failover_group_members_are_ordered_by_priority() {
+    let db = Database::memory().expect("create memory db");
+
+    for id in ["a", "b", "c", "d"] {
+        let provider = Provider::with_id(id.to_string(), id.to_uppercase(), json!({}), None);
+        db.save_provider("claude", &provider)
+            .expect("save provider");
+    }
+
+    db.set_provider_failover_group("claude", "a", Some("primary"), Some(2))
+        .expect("set group a");
+    db.set_provider_failover_group("claude", "b", Some("primary"), Some(1))
+        .expect("set group b");
+    db.set_provider_failover_group("claude", "c", Some("primary"), None)
+        .expect("set group c");
+    db.set_provider_failover_group("claude", "d", Some("backup"), Some(0))
+        .expect("set group d");
+
+    let ids: Vec<String> = db
+        .get_failover_group_members("claude", "primary")
+        .expect("load members")
+        .into_iter()
+        .map(|m| m.provider_id)
+        .collect();
+    assert_eq!(ids, vec!["b", "a", "c"]);
+
+    db.set_provider_failover_group("claude", "a", None, Some(5))
+        .expect("clear group a");
+    assert_eq!(
+        db.get_provider_failover_group("claude", "a")
+            .expect("read group"),
+        None
+    );
+    assert!(db
+        .set_provider_failover_group("claude", "missing", Some("primary"), None)
+        .is_err());
+}
+
+#[test]
+fn provider_tags_round_trip_and_filter() {
+    let db = Database::memory().expect("create memory db");
+
+    let mut tagged = Provider::with_id("a".to_string(), "A".to_string(), json!({}), None);
+    tagged.tags = vec!["Cheap".to_string(), " ".to_string()];
+    db.save_provider("claude", &tagged).expect("save a");
+    let plain = Provider::with_id("b".to_string(), "B".to_string(), json!({}), None);
+    db.save_provider("claude", &plain).expect("save b");
+
+    db.add_tag("claude", "b", "backup").expect("tag b");
+    db.add_tag("claude", "b", "cheap").expect("tag b again");
+    assert!(db.add_tag("claude", "b", "  ").is_err());
+    assert!(db.add_tag("claude", "missing", "cheap").is_err());
+
+    let a = db
+        .get_provider_by_id("a", "claude")
+        .expect("load a")
+        .expect("a exists");
+    assert_eq!(a.tags, vec!["cheap"]);
+
+    let cheap: Vec<String> = db
+        .find_by_tag("claude", "CHEAP")
+        .expect("find cheap")
+        .into_iter()
+        .map(|p| p.id)
+        .collect();
+    assert_eq!(cheap, vec!["a", "b"]);
+    assert_eq!(
+        db.list_tags("claude").expect("list tags"),
+        vec!["backup", "cheap"]
+    );
+
+    // 编辑已有供应商不会覆盖标签
+    db.save_provider("claude", &plain).expect("update b");
+    db.remove_tag("claude", "b", "cheap").expect("untag b");
+    let b = db.get_all_providers("claude").expect("load all")["b"].clone();
+    assert_eq!(b.tags, vec!["backup"]);
+    assert!(db
+        .find_by_tag("codex", "cheap")
+        .expect("find in codex")
+        .is_empty());
+}
+
+#[test]
+fn open_read_only_migrates_in_memory_and_leaves_file_untouched() {
+    let dir = tempfile::tempdir().expect("create temp dir");
+    let path = dir.path().join("backup.db");
+    {
+        let conn = Connection::open(&path).expect("open file db");
+        conn.execute_batch(LEGACY_SCHEMA_SQL)
+            .expect("seed old schema");
+        conn.execute(
+            "INSERT INTO providers (id, app_type, name, settings_config) VALUES ('old', 'claude', 'Old', '{}')",
+            [],
+        )
+        .expect("insert provider");
+    }
+    let before = std::fs::read(&path).expect("read db file");
+
+    let db = Database::open_read_only(&path).expect("open read-only");
+    let providers = db.get_all_providers("claude").expect("list providers");
+    assert_eq!(providers["old"].name, "Old");
+
+    assert_eq!(std::fs::read(&path).expect("read db file"), before);
+    assert!(Database::open_read_only(&dir.path().join("missing.db")).is_err());
+}
+
+#[test]
+fn validate_db_file_rejects_garbage_and_newer_schema() {
+    let dir = tempfile::tempdir().expect("create temp dir");
+
+    let garbage = dir.path().join("garbage.db");
+    std::fs::write(&garbage, b"definitely not sqlite").expect("write garbage");
+    assert!(Database::validate_db_file(&garbage).is_err());
+
+    let newer = dir.path().join("newer.db");
+    {
+        let conn = Connection::open(&newer).expect("open file db");
+        conn.execute_batch(LEGACY_SCHEMA_SQL).expect("seed schema");
+        Database::set_user_version(&conn, SCHEMA_VERSION + 1).expect("set future version");
+    }
+    assert!(matches!(
+        Database::validate_db_file(&newer),
+        Err(AppError::SchemaTooNew { .. })
+    ));
+
+    let current = dir.path().join("current.db");
+    {
+        let conn = Connection::open(&current).expect("open file db");
+        conn.execute_batch(LEGACY_SCHEMA_SQL).expect("seed schema");
+    }
+    assert!(Database::validate_db_file(&current).is_ok());
+}
+
+#[test]
+fn category_default_is_unique_per_category() {
+    let db = Database::memory().expect("create memory db");
+
+    for id in ["a", "b"] {
+        let mut provider = Provider::with_id(id.to_string(), id.to_string(), json!({}), None);
+        provider.category = Some("aggregator".to_string());
+        db.save_provider("claude", &provider)
+            .expect("save provider");
+    }
+    let plain = Provider::with_id("c".to_string(), "C".to_string(), json!({}), None);
+    db.save_provider("claude", &plain).expect("save c");
+
+    assert_eq!(
+        db.set_category_default("claude", "a").expect("mark a"),
+        "aggregator"
+    );
+    db.set_category_default("claude", "b").expect("mark b");
+    assert!(db.set_category_default("claude", "c").is_err());
+    assert_eq!(
+        db.get_category_default("claude", "aggregator")
+            .expect("get default")
+            .as_deref(),
+        Some("b")
+    );
+
+    // 删除供应商时一并移除默认标记
+    db.delete_provider("claude", "b").expect("delete b");
+    assert!(db
+        .get_category_defaults("claude")
+        .expect("list defaults")
+        .is_empty());
+}
+
+#[test]
+fn search_providers_matches_name_notes_and_endpoint() {
+    let db = Database::memory().expect("create memory db");
+
+    let mut deepseek = Provider::with_id(
+        "ds".to_string(),
+        "DeepSeek".to_string(),
+        json!({ "env": { "ANTHROPIC_BASE_URL": "https://api.deepseek.com/anthropic" } }),
+        None,
+    );
+    deepseek.notes = Some("cheap backup".to_string());
+    db.save_provider("claude", &deepseek)
+        .expect("save deepseek");
+
+    let codex = Provider::with_id(
+        "cx".to_string(),
+        "Relay".to_string(),
+        json!({ "config": "[model_providers.relay]\nbase_url = \"https://relay.example.com/v1\"\n" }),
+        None,
+    );
+    db.save_provider("codex", &codex).expect("save codex");
+
+    let ids = |app: &str, query: &str| -> Vec<String> {
+        db.search_providers(app, query)
+            .expect("search")
+            .into_iter()
+            .map(|p| p.id)
+            .collect()
+    };
+
+    assert_eq!(ids("claude", "deep"), vec!["ds"]);
+    assert_eq!(ids("claude", "cheap backup"), vec!["ds"]);
+    assert!(ids("claude", "cheap missing").is_empty());
+    assert_eq!(ids("codex", "relay.example"), vec!["cx"]);
+    assert!(ids("codex", "deepseek").is_empty());
+    assert!(ids("claude", "  ").is_empty());
+    assert!(ids("claude", "\"unbalanced").is_empty());
+
+    // 更新与删除会同步索引
+    deepseek.name = "Renamed".to_string();
+    db.save_provider("claude", &deepseek)
+        .expect("update deepseek");
+    assert_eq!(ids("claude", "renamed"), vec!["ds"]);
+    db.delete_provider("claude", "ds").expect("delete deepseek");
+    assert!(ids("claude", "renamed").is_empty());
+}
+
+#[test]
+fn clone_provider_copies_config_endpoints_and_tags() {
+    let db = Database::memory().expect("create memory db");
+
+    let mut source = Provider::with_id(
+        "src".to_string(),
+        "Vendor".to_string(),
+        json!({ "env": { "ANTHROPIC_AUTH_TOKEN": "k1" } }),
+        None,
+    );
+    source.tags = vec!["work".to_string()];
+    db.save_provider("claude", &source).expect("save source");
+    db.add_custom_endpoint("claude", "src", "https://mirror.example.com")
+        .expect("add endpoint");
+    db.set_current_provider("claude", "src")
+        .expect("set current");
+
+    let copy = db
+        .clone_provider("claude", "src", None)
+        .expect("clone provider");
+    assert_ne!(copy.id, "src");
+    assert_eq!(copy.name, "Vendor Copy");
+    assert!(copy.created_at.is_some());
+
+    let providers = db.get_all_providers("claude").expect("load providers");
+    let stored = &providers[&copy.id];
+    assert_eq!(stored.settings_config, source.settings_config);
+    assert_eq!(stored.tags, vec!["work"]);
+    assert!(stored
+        .meta
+        .as_ref()
+        .expect("meta")
+        .custom_endpoints
+        .contains_key("https://mirror.example.com"));
+    assert_eq!(
+        db.get_current_provider("claude")
+            .expect("current")
+            .as_deref(),
+        Some("src")
+    );
+
+    let named = db
+        .clone_provider("claude", "src", Some("Second key"))
+        .expect("clone with name");
+    assert_eq!(named.name, "Second key");
+    assert!(db.clone_provider("claude", "missing", None).is_err());
+}
+
+#[test]
+fn clone_is_placed_right_after_its_source() {
+    let db = Database::memory().expect("create memory db");
+    for (index, id) in ["a", "b", "c"].into_iter().enumerate() {
+        let mut provider = Provider::with_id(id.to_string(), id.to_uppercase(), json!({}), None);
+        provider.sort_index = Some(index);
+        db.save_provider("claude", &provider)
+            .expect("save provider");
+    }
+
+    let copy = db
+        .clone_provider("claude", "a", None)
+        .expect("clone provider");
+    assert_eq!(copy.sort_index, Some(1));
+
+    let providers = db.get_all_providers("claude").expect("load providers");
+    let order: Vec<&str> = providers.keys().map(String::as_str).collect();
+    assert_eq!(order, ["a", copy.id.as_str(), "b", "c"]);
+    let indexes: Vec<Option<usize>> = providers.values().map(|p| p.sort_index).collect();
+    assert_eq!(indexes, [Some(0), Some(1), Some(2), Some(3)]);
+}
+
+#[test]
+fn bulk_delete_and_tag_providers() {
+    let db = Database::memory().expect("create memory db");
+    for id in ["a", "b", "c"] {
+        let provider = Provider::with_id(id.to_string(), id.to_uppercase(), json!({}), None);
+        db.save_provider("claude", &provider)
+            .expect("save provider");
+    }
+
+    let ids = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+    assert_eq!(
+        db.add_tag_to_providers("claude", &ids(&["a", "b", "missing"]), "team")
+            .expect("bulk tag"),
+        2
+    );
+    assert_eq!(
+        db.find_by_tag("claude", "team").expect("find tagged").len(),
+        2
+    );
+
+    assert_eq!(
+        db.delete_providers("claude", &ids(&["a", "c", "missing"]))
+            .expect("bulk delete"),
+        2
+    );
+    let remaining: Vec<String> = db
+        .get_all_providers("claude")
+        .expect("load providers")
+        .into_keys()
+        .collect();
+    assert_eq!(remaining, vec!["b"]);
+}
+
+#[test]
+fn reorder_providers_rewrites_sort_index() {
+    let db = Database::memory().expect("create memory db");
+    for id in ["a", "b", "c", "d"] {
+        let provider = Provider::with_id(id.to_string(), id.to_uppercase(), json!({}), None);
+        db.save_provider("claude", &provider)
+            .expect("save provider");
+    }
+
+    let ids = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    let order = |db: &Database| -> Vec<String> {
+        db.get_all_providers("claude")
+            .expect("load providers")
+            .into_keys()
+            .collect()
+    };
+
+    db.reorder_providers("claude", &ids(&["c", "a"]))
+        .expect("reorder");
+    assert_eq!(order(&db), vec!["c", "a", "b", "d"]);
+    assert_eq!(
+        db.get_provider_by_id("d", "claude")
+            .expect("load d")
+            .expect("d exists")
+            .sort_index,
+        Some(3)
+    );
+
+    assert!(db
+        .reorder_providers("claude", &ids(&["a", "missing"]))
+        .is_err());
+    assert!(db.reorder_providers("claude", &ids(&["a", "a"])).is_err());
+    assert_eq!(order(&db), vec!["c", "a", "b", "d"]);
+}
+
+#[test]
+fn switch_history_is_recorded_in_order_and_filtered_by_app() {
+    let db = Database::memory().expect("create memory db");
+
+    db.record_switch("claude", "a", "A", Some("/work/api"))
+        .expect("record a");
+    db.record_switch("codex", "x", "X", None).expect("record x");
+    db.record_switch("claude", "b", "B", None)
+        .expect("record b");
+
+    let all = db.get_switch_history(None).expect("load history");
+    assert_eq!(all.len(), 3);
+    assert_eq!(all[0].cwd.as_deref(), Some("/work/api"));
+
+    let claude = db
+        .get_switch_history(Some("claude"))
+        .expect("load claude history");
+    let ids: Vec<_> = claude.iter().map(|e| e.provider_id.as_str()).collect();
+    assert_eq!(ids, vec!["a", "b"]);
+}
+
+#[test]
+fn usage_counters_are_buffered_and_flushed_on_read() {
+    let db = Database::memory().expect("create memory db");
+
+    for _ in 0..3 {
+        db.increment_usage_counters("claude", "p1", 1, 100)
+            .expect("increment p1");
+    }
+    db.increment_usage_counters("claude", "p2", 1, 10)
+        .expect("increment p2");
+    db.increment_usage_counters("codex", "p1", 1, 5)
+        .expect("increment codex");
+
+    let claude = db
+        .get_usage_counters(Some("claude"))
+        .expect("load counters");
+    assert_eq!(claude.len(), 2);
+    assert_eq!(claude[0].provider_id, "p1");
+    assert_eq!((claude[0].requests, claude[0].tokens), (3, 300));
+
+    db.increment_usage_counters("claude", "p1", 1, 50)
+        .expect("increment again");
+    let p1 = db
+        .get_provider_usage_counters("claude", "p1")
+        .expect("load p1");
+    assert_eq!((p1.requests, p1.tokens), (4, 350));
+    let missing = db
+        .get_provider_usage_counters("gemini", "none")
+        .expect("load missing");
+    assert_eq!(missing.requests, 0);
+}
+
+#[test]
+fn recent_benchmarks_keep_latest_success_per_provider() {
+    let db = Database::memory().expect("create memory db");
+    let result = |provider: &str, ttfb: u64, success: bool, created_at: i64| BenchmarkResult {
+        app_type: "claude".to_string(),
+        provider_id: provider.to_string(),
+        success,
+        ttfb_ms: Some(ttfb),
+        created_at,
+        ..Default::default()
+    };
+
+    db.record_benchmark(&result("a", 900, true, 1_000))
+        .expect("record stale a");
+    db.record_benchmark(&result("a", 300, true, 5_000))
+        .expect("record a");
+    db.record_benchmark(&result("a", 100, false, 6_000))
+        .expect("record failed a");
+    db.record_benchmark(&result("b", 500, true, 500))
+        .expect("record old b");
+
+    let recent = db
+        .get_recent_benchmarks("claude", 2_000)
+        .expect("load benchmarks");
+    assert_eq!(recent.len(), 1);
+    assert_eq!(recent["a"].ttfb_ms, Some(300));
+}
+
+#[test]
+fn endpoint_tests_and_usage_are_persisted() {
+    let db = Database::memory().expect("create memory db");
+    let provider = Provider::with_id(
+        "relay".to_string(),
+        "Relay".to_string(),
+        json!({ "env": { "ANTHROPIC_BASE_URL": "https://api.example.com" } }),
+        None,
+    );
+    db.save_provider("claude", &provider)
+        .expect("save provider");
+    db.add_custom_endpoint("claude", "relay", "https://mirror.example.com")
+        .expect("add endpoint");
+
+    db.record_endpoint_test("claude", "relay", "https://mirror.example.com", Some(85))
+        .expect("record test");
+    db.mark_endpoint_used("claude", "relay", "https://mirror.example.com")
+        .expect("mark used");
+
+    let providers = db.get_all_providers("claude").expect("load providers");
+    let endpoint = &providers["relay"]
+        .meta
+        .as_ref()
+        .expect("meta")
+        .custom_endpoints["https://mirror.example.com"];
+    assert_eq!(endpoint.latency_ms, Some(85));
+    assert!(endpoint.tested_at.is_some());
+    assert!(endpoint.last_used.is_some());
+}
+
+#[test]
+fn provider_history_records_diffs_that_replay_to_each_revision() {
+    let db = Database::memory().expect("create memory db");
+    let mut provider = Provider::with_id(
+        "p1".to_string(),
+        "Relay".to_string(),
+        json!({ "env": { "ANTHROPIC_BASE_URL": "https://a.example.com" } }),
+        None,
+    );
+    db.save_provider("claude", &provider).expect("create");
+    // 内容未变化的保存不产生新修订
+    db.save_provider("claude", &provider)
+        .expect("save unchanged");
+
+    db.set_change_source(ChangeSource::Cli);
+    provider.settings_config["env"]["ANTHROPIC_BASE_URL"] = json!("https://b.example.com");
+    provider.notes = Some("moved".to_string());
+    db.save_provider("claude", &provider).expect("update");
+
+    let history = db.get_provider_history("claude", "p1").expect("history");
+    let revisions: Vec<_> = history.iter().map(|e| e.revision).collect();
+    assert_eq!(revisions, vec![1, 2]);
+    assert_eq!(history[0].source, "gui");
+    assert_eq!(history[0].changes.len(), 1);
+    assert_eq!(history[0].changes[0].path, "");
+    assert_eq!(history[1].source, "cli");
+    assert_eq!(
+        history[1].changes,
+        vec![
+            JsonChange {
+                path: "/notes".to_string(),
+                before: None,
+                after: Some(json!("moved")),
+            },
+            JsonChange {
+                path: "/settingsConfig/env/ANTHROPIC_BASE_URL".to_string(),
+                before: Some(json!("https://a.example.com")),
+                after: Some(json!("https://b.example.com")),
+            },
+        ]
+    );
+
+    let mut state = json!({});
+    apply_changes(&mut state, &history[0].changes);
+    assert_eq!(
+        state["settingsConfig"]["env"]["ANTHROPIC_BASE_URL"],
+        "https://a.example.com"
+    );
+    apply_changes(&mut state, &history[1].changes);
+    assert_eq!(
+        state["settingsConfig"]["env"]["ANTHROPIC_BASE_URL"],
+        "https://b.example.com"
+    );
+    assert_eq!(state["notes"], "moved");
+}
+
+#[test]
+fn first_change_of_a_provider_from_before_the_journal_records_a_baseline() {
+    let db = Database::memory().expect("create memory db");
+    let mut provider = Provider::with_id(
+        "legacy".to_string(),
+        "Legacy".to_string(),
+        json!({ "env": { "ANTHROPIC_BASE_URL": "https://old.example.com" } }),
+        None,
+    );
+    db.save_provider("claude", &provider).expect("create");
+    {
+        // 模拟升级前创建、没有变更记录的供应商
+        let conn = db.conn.lock().expect("lock");
+        conn.execute("DELETE FROM provider_history", [])
+            .expect("clear history");
+        conn.execute("UPDATE providers SET revision = 0", [])
+            .expect("reset revision");
+    }
+
+    provider.settings_config["env"]["ANTHROPIC_BASE_URL"] = json!("https://new.example.com");
+    db.save_provider("claude", &provider).expect("update");
+
+    let history = db
+        .get_provider_history("claude", "legacy")
+        .expect("history");
+    let sources: Vec<_> = history
+        .iter()
+        .map(|e| (e.revision, e.source.as_str()))
+        .collect();
+    assert_eq!(sources, vec![(0, "baseline"), (1, "gui")]);
+
+    let mut state = json!({});
+    apply_changes(&mut state, &history[0].changes);
+    assert_eq!(
+        state["settingsConfig"]["env"]["ANTHROPIC_BASE_URL"],
+        "https://old.example.com"
+    );
+}
+
+#[test]
+fn expired_backups_respect_count_and_total_size() {
+    let info = |id: &str, size: u64| BackupInfo {
+        id: id.to_string(),
+        path: std::path::PathBuf::from(format!("{id}.db")),
+        size,
+        created_at: 0,
+    };
+    let backups = || {
+        vec![
+            info("newest", 40),
+            info("middle", 40),
+            info("older", 40),
+            info("oldest", 40),
+        ]
+    };
+    let ids = |expired: Vec<BackupInfo>| expired.into_iter().map(|b| b.id).collect::<Vec<_>>();
+
+    assert_eq!(
+        ids(super::backup::expired_backups(backups(), 2, None)),
+        vec!["older", "oldest"]
+    );
+    assert_eq!(
+        ids(super::backup::expired_backups(backups(), 10, Some(100))),
+        vec!["older", "oldest"]
+    );
+    // 最新的备份即使超出大小上限也保留
+    assert!(super::backup::expired_backups(vec![info("big", 500)], 10, Some(100)).is_empty());
+}
+
+#[test]
+fn maintenance_reports_stats_and_passes_integrity_check() {
+    let db = Database::memory().expect("create memory db");
+    let provider = Provider::with_id("a".to_string(), "A".to_string(), json!({}), None);
+    db.save_provider("claude", &provider)
+        .expect("save provider");
+
+    assert!(db.integrity_check().expect("integrity check").is_empty());
+    db.analyze().expect("analyze");
+    db.vacuum().expect("vacuum");
+
+    let stats = db.stats().expect("stats");
+    assert!(stats.size > 0);
+    let providers = stats
+        .tables
+        .iter()
+        .find(|table| table.name == "providers")
+        .expect("providers table");
+    assert_eq!(providers.rows, 1);
+}
+
+#[test]
+fn begin_write_waits_for_another_writer() {
+    let dir = tempfile::tempdir().expect("create temp dir");
+    let path = dir.path().join("shared.db");
+    let holder = Connection::open(&path).expect("open first connection");
+    holder
+        .execute_batch("PRAGMA journal_mode = WAL; CREATE TABLE t (v INTEGER);")
+        .expect("create table");
+    holder
+        .execute_batch("BEGIN IMMEDIATE;")
+        .expect("take write lock");
+
+    let release = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        holder.execute_batch("COMMIT;").expect("release write lock");
+    });
+
+    let conn = Connection::open(&path).expect("open second connection");
+    let tx = begin_write(&conn).expect("write transaction after retry");
+    tx.execute("INSERT INTO t (v) VALUES (1)", [])
+        .expect("insert");
+    tx.commit().expect("commit");
+    release.join().expect("join");
+}
+
+#[test]
+fn reads_use_pool_while_writer_is_held() {
+    let dir = tempfile::tempdir().expect("create temp dir");
+    let path = dir.path().join("pooled.db");
+    let conn = Connection::open(&path).expect("open file db");
+    conn.execute_batch("PRAGMA journal_mode = WAL;")
+        .expect("enable wal");
+    let db = Database {
+        conn: Mutex::new(conn),
+        readers: pool::ReadPool::new(path),
+        counters: Default::default(),
+        change_source: Default::default(),
+        key_pool_generation: Default::default(),
+    };
+    db.create_tables().expect("create tables");
+    let provider = Provider::with_id("p1".to_string(), "One".to_string(), json!({}), None);
+    db.save_provider("claude", &provider)
+        .expect("save provider");
+
+    // 写连接被占用时，只读查询仍可进行并看到已提交的数据
+    let _writer = db.conn.lock().expect("lock writer");
+    let providers = db.get_all_providers("claude").expect("read via pool");
+    assert!(providers.contains_key("p1"));
+    assert_eq!(
+        db.get_current_provider("claude").expect("read via pool"),
+        None
+    );
+}
+
+#[test]
+fn change_log_records_provider_events() {
+    let db = Database::memory().expect("create memory db");
+    let start = db.latest_change_id().expect("latest change id");
+
+    let mut provider = Provider::with_id("p1".to_string(), "One".to_string(), json!({}), None);
+    db.save_provider("claude", &provider)
+        .expect("save provider");
+    db.save_provider("claude", &provider)
+        .expect("save unchanged provider");
+    provider.name = "Renamed".to_string();
+    db.save_provider("claude", &provider)
+        .expect("update provider");
+    db.set_current_provider("claude", "p1").expect("switch");
+    db.delete_provider("claude", "p1").expect("delete provider");
+
+    let kinds: Vec<ChangeKind> = db
+        .changes_since(start)
+        .expect("changes since")
+        .into_iter()
+        .map(|event| event.kind)
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            ChangeKind::ProviderAdded,
+            ChangeKind::ProviderUpdated,
+            ChangeKind::ProviderSwitched,
+            ChangeKind::ProviderDeleted,
+        ]
+    );
+}
+
+#[test]
+fn find_provider_matching_config_uses_base_url_and_key() {
+    let db = Database::memory().expect("create memory db");
+    let settings = |url: &str, key: &str| json!({ "env": { "ANTHROPIC_BASE_URL": url, "ANTHROPIC_AUTH_TOKEN": key } });
+    let provider = Provider::with_id(
+        "relay".to_string(),
+        "Relay".to_string(),
+        settings("https://relay.example/api", "sk-relay"),
+        None,
+    );
+    db.save_provider("claude", &provider)
+        .expect("save provider");
+
+    let found = db
+        .find_provider_matching_config(
+            "claude",
+            &settings("https://RELAY.example/api/", "sk-relay"),
+        )
+        .expect("match by identity");
+    assert_eq!(found.map(|p| p.id).as_deref(), Some("relay"));
+
+    for other in [
+        settings("https://relay.example/api", "sk-other"),
+        settings("https://other.example", "sk-relay"),
+        json!({ "env": { "ANTHROPIC_BASE_URL": "https://relay.example/api" } }),
+    ] {
+        assert!(db
+            .find_provider_matching_config("claude", &other)
+            .expect("no match")
+            .is_none());
+    }
+    assert!(db
+        .find_provider_matching_config(
+            "opencode",
+            &settings("https://relay.example/api", "sk-relay")
+        )
+        .expect("registered apps have no identity")
+        .is_none());
+}
+
+#[test]
+fn merge_providers_unites_tags_and_endpoints() {
+    let db = Database::memory().expect("create memory db");
+    for id in ["keep", "dup"] {
+        let provider = Provider::with_id(id.to_string(), id.to_string(), json!({}), None);
+        db.save_provider("claude", &provider)
+            .expect("save provider");
+    }
+    db.add_tag("claude", "keep", "work").expect("tag keep");
+    db.add_tag("claude", "dup", "work").expect("tag dup");
+    db.add_tag("claude", "dup", "relay").expect("tag dup");
+    db.add_custom_endpoint("claude", "keep", "https://a.example")
+        .expect("endpoint keep");
+    db.add_custom_endpoint("claude", "dup", "https://a.example")
+        .expect("endpoint dup");
+    db.add_custom_endpoint("claude", "dup", "https://b.example")
+        .expect("endpoint dup");
+
+    db.merge_providers("claude", "keep", &["dup".to_string()], Some("a\nb"))
+        .expect("merge");
+
+    let providers = db.get_all_providers("claude").expect("list providers");
+    assert_eq!(providers.len(), 1);
+    let keep = &providers["keep"];
+    assert_eq!(keep.notes.as_deref(), Some("a\nb"));
+    let mut tags = keep.tags.clone();
+    tags.sort();
+    assert_eq!(tags, vec!["relay", "work"]);
+    let mut urls: Vec<_> = keep
+        .meta
+        .as_ref()
+        .map(|m| m.custom_endpoints.keys().cloned().collect())
+        .unwrap_or_default();
+    urls.sort();
+    assert_eq!(urls, vec!["https://a.example", "https://b.example"]);
+}
+
+#[test]
+fn pending_revert_is_replaced_and_taken_by_token() {
+    let db = Database::memory().expect("create memory db");
+    let revert = |token: i64, revert_at: Option<i64>| PendingRevert {
+        app_type: "claude".to_string(),
+        from_id: "old".to_string(),
+        to_id: "trial".to_string(),
+        revert_at,
+        token,
+        pid: None,
+        created_at: 1,
+    };
+    db.save_pending_revert(&revert(1, Some(100)))
+        .expect("save first");
+    db.save_pending_revert(&revert(2, None))
+        .expect("replace with second");
+    assert_eq!(
+        db.list_pending_reverts().expect("list"),
+        vec![revert(2, None)]
+    );
+
+    // 旧计时器的凭据已失效
+    assert_eq!(
+        db.take_pending_revert("claude", Some(1)).expect("take"),
+        None
+    );
+    assert_eq!(
+        db.take_pending_revert("claude", Some(2)).expect("take"),
+        Some(revert(2, None))
+    );
+    assert_eq!(db.get_pending_revert("claude").expect("get"), None);
+}
+
+#[test]
+fn pinned_providers_follow_sort_order_and_survive_updates() {
+    let db = Database::memory().expect("create memory db");
+    for (index, id) in ["a", "b", "c"].iter().enumerate() {
+        let mut provider =
+            Provider::with_id(id.to_string(), id.to_string(), json!({ "env": {} }), None);
+        provider.sort_index = Some(index);
+        db.save_provider("claude", &provider)
+            .expect("save provider");
+    }
+    assert!(db.set_provider_pinned("claude", "c", true).expect("pin"));
+    assert!(db.set_provider_pinned("claude", "a", true).expect("pin"));
+    assert!(!db
+        .set_provider_pinned("claude", "missing", true)
+        .expect("pin"));
+
+    let mut provider = db
+        .get_provider_by_id("c", "claude")
+        .expect("get")
+        .expect("exists");
+    provider.notes = Some("edited".to_string());
+    db.save_provider("claude", &provider)
+        .expect("update provider");
+    assert_eq!(
+        db.get_pinned_provider_ids("claude").expect("pinned"),
+        vec!["a".to_string(), "c".to_string()]
+    );
+
+    db.set_provider_pinned("claude", "a", false).expect("unpin");
+    assert_eq!(
+        db.get_pinned_provider_ids("claude").expect("pinned"),
+        vec!["c".to_string()]
+    );
+}
+
+#[test]
+fn provider_aliases_are_unique_per_app_and_resolve_to_ids() {
+    let db = Database::memory().expect("create memory db");
+    for app in ["claude", "codex"] {
+        for id in ["p1", "p2"] {
+            let provider =
+                Provider::with_id(id.to_string(), id.to_string(), json!({ "env": {} }), None);
+            db.save_provider(app, &provider).expect("save provider");
+        }
+    }
+    assert!(db
+        .set_provider_alias("claude", "p1", Some("work"))
+        .expect("set alias"));
+    // 同一应用内别名唯一，也不能与其他供应商的 ID 相同；其他应用不受影响
+    assert!(db.set_provider_alias("claude", "p2", Some("work")).is_err());
+    assert!(db.set_provider_alias("claude", "p1", Some("p2")).is_err());
+    assert!(db
+        .set_provider_alias("codex", "p2", Some("work"))
+        .expect("set alias in other app"));
+
+    assert_eq!(
+        db.resolve_provider_id("claude", "work").expect("resolve"),
+        Some("p1".to_string())
+    );
+    assert_eq!(
+        db.resolve_provider_id("claude", "p2").expect("resolve"),
+        Some("p2".to_string())
+    );
+    assert_eq!(
+        db.resolve_provider_id("claude", "nope").expect("resolve"),
+        None
+    );
+
+    db.set_provider_alias("claude", "p1", None)
+        .expect("clear alias");
+    assert!(db
+        .get_provider_aliases("claude")
+        .expect("aliases")
+        .is_empty());
+    assert!(!db
+        .set_provider_alias("claude", "missing", Some("x"))
+        .expect("missing provider"));
+}
+
+#[test]
+fn vendor_assignment_survives_save_and_is_cleared_on_delete() {
+    let db = Database::memory().expect("create memory db");
+    let vendor = Vendor {
+        id: "openrouter".to_string(),
+        name: "OpenRouter".to_string(),
+        website_url: None,
+        notes: None,
+        created_at: 1,
+    };
+    db.save_vendor(&vendor).expect("save vendor");
+    let mut provider = Provider::with_id(
+        "or-main".to_string(),
+        "OpenRouter".to_string(),
+        json!({ "env": {} }),
+        None,
+    );
+    db.save_provider("claude", &provider)
+        .expect("save provider");
+    assert!(db
+        .set_provider_vendor("claude", "or-main", Some("openrouter"))
+        .expect("assign"));
+    assert!(!db
+        .set_provider_vendor("claude", "missing", Some("openrouter"))
+        .expect("assign missing"));
+
+    // 编辑供应商不会丢失关联
+    provider.notes = Some("edited".to_string());
+    db.save_provider("claude", &provider)
+        .expect("update provider");
+    let key = ("claude".to_string(), "or-main".to_string());
+    assert_eq!(
+        db.get_provider_vendors(Some("claude"))
+            .expect("vendors")
+            .get(&key),
+        Some(&"openrouter".to_string())
+    );
+    assert!(db
+        .get_provider_vendors(Some("codex"))
+        .expect("vendors")
+        .is_empty());
+
+    assert!(db.delete_vendor("openrouter").expect("delete"));
+    assert!(db.get_vendors().expect("list").is_empty());
+    assert!(db.get_provider_vendors(None).expect("vendors").is_empty());
+}
+
+#[test]
+fn query_providers_pages_sorts_and_joins_endpoints() {
+    let db = Database::memory().expect("create memory db");
+    for (index, name) in ["Charlie", "alpha", "Bravo"].iter().enumerate() {
+        let mut provider = Provider::with_id(
+            format!("p{index}"),
+            name.to_string(),
+            json!({ "env": { "ANTHROPIC_BASE_URL": format!("https://{name}.example.com") } }),
+            None,
+        );
+        provider.sort_index = Some(index);
+        db.save_provider("claude", &provider)
+            .expect("save provider");
+    }
+    db.add_custom_endpoint("claude", "p1", "https://a.example.com")
+        .expect("add endpoint");
+    db.add_custom_endpoint("claude", "p1", "https://b.example.com")
+        .expect("add endpoint");
+    db.add_tag("claude", "p1", "cheap").expect("add tag");
+
+    let page = db
+        .query_providers(
+            "claude",
+            &QueryOptions {
+                offset: 0,
+                limit: Some(2),
+                sort_by: ProviderSort::Name,
+                ..Default::default()
+            },
+        )
+        .expect("query providers");
+    assert_eq!(page.total, 3);
+    let names: Vec<&str> = page.providers.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["alpha", "Bravo"]);
+    assert_eq!(
+        page.providers[0]
+            .meta
+            .as_ref()
+            .expect("meta")
+            .custom_endpoints
+            .len(),
+        2
+    );
+    assert_eq!(page.providers[0].tags, ["cheap"]);
+
+    let filtered = db
+        .query_providers(
+            "claude",
+            &QueryOptions {
+                filter: Some("charlie".to_string()),
+                ..Default::default()
+            },
+        )
+        .expect("query providers");
+    assert_eq!(filtered.total, 1);
+    assert_eq!(filtered.providers[0].id, "p0");
+}
+
+#[test]
+fn get_all_providers_attaches_endpoints_to_their_providers() {
+    let db = Database::memory().expect("create memory db");
+    for id in ["alpha", "beta", "gamma"] {
+        let provider = Provider::with_id(id.to_string(), id.to_string(), json!({}), None);
+        db.save_provider("claude", &provider)
+            .expect("save provider");
+    }
+    for (id, count) in [("alpha", 2), ("beta", 1)] {
+        for endpoint in 0..count {
+            db.add_custom_endpoint(
+                "claude",
+                id,
+                &format!("https://{id}-{endpoint}.example.com"),
+            )
+            .expect("add endpoint");
+        }
+    }
+    // 其他应用下同 ID 供应商的端点不应混入
+    let other = Provider::with_id("alpha".to_string(), "alpha".to_string(), json!({}), None);
+    db.save_provider("codex", &other).expect("save provider");
+    db.add_custom_endpoint("codex", "alpha", "https://codex.example.com")
+        .expect("add endpoint");
+
+    let providers = db.get_all_providers("claude").expect("list providers");
+    let urls = |id: &str| {
+        let mut urls: Vec<String> = providers[id]
+            .meta
+            .as_ref()
+            .map(|meta| meta.custom_endpoints.keys().cloned().collect())
+            .unwrap_or_default();
+        urls.sort();
+        urls
+    };
+    assert_eq!(
+        urls("alpha"),
+        ["https://alpha-0.example.com", "https://alpha-1.example.com"]
+    );
+    assert_eq!(urls("beta"), ["https://beta-0.example.com"]);
+    assert!(urls("gamma").is_empty());
+}
+
+/// 基准测试：`cargo test --lib get_all_providers_batches -- --ignored --nocapture`
+///
+/// 只输出耗时，不对快慢做断言（机器负载不同，结果会波动）
+#[test]
+#[ignore]
+fn get_all_providers_batches_endpoint_loading() {
+    use std::time::Instant;
+
+    let db = Database::memory().expect("create memory db");
+    for index in 0..500 {
+        let id = format!("p{index:03}");
+        let provider = Provider::with_id(id.clone(), id.clone(), json!({}), None);
+        db.save_provider("claude", &provider)
+            .expect("save provider");
+        for endpoint in 0..3 {
+            db.add_custom_endpoint(
+                "claude",
+                &id,
+                &format!("https://{id}-{endpoint}.example.com"),
+            )
+            .expect("add endpoint");
+        }
+    }
+
+    let start = Instant::now();
+    let providers = db.get_all_providers("claude").expect("list providers");
+    let batched = start.elapsed();
+
+    // 旧实现的查询方式：先取供应商，再逐个查询端点
+    let start = Instant::now();
+    {
+        let conn = db.conn.lock().expect("lock conn");
+        for id in providers.keys() {
+            let mut stmt = conn
+                .prepare(
+                    "SELECT url, added_at, last_used, latency_ms, tested_at FROM provider_endpoints
+                     WHERE provider_id = ?1 AND app_type = ?2 ORDER BY added_at ASC, url ASC",
+                )
+                .expect("prepare");
+            let count = stmt
+                .query_map(rusqlite::params![id, "claude"], |_| Ok(()))
+                .expect("query endpoints")
+                .count();
+            assert_eq!(count, 3);
+        }
+    }
+    let per_provider = start.elapsed() + batched;
+
+    println!("500 providers: batched {batched:?}, per-provider endpoints {per_provider:?}");
+    assert_eq!(providers.len(), 500);
+    assert!(providers
+        .values()
+        .all(|p| p.meta.as_ref().expect("meta").custom_endpoints.len() == 3));
+}
+
+#[test]
+fn repeated_reads_reuse_prepared_statements() {
+    const ROUNDS: i32 = 5;
+    const CURRENT_SQL: &str =
+        "SELECT id FROM providers WHERE app_type = ?1 AND is_current = 1 LIMIT 1";
+    let db = Database::memory().expect("create memory db");
+    let run_count = |db: &Database| {
+        let conn = db.conn.lock().expect("lock conn");
+        let stmt = conn.prepare_cached(CURRENT_SQL).expect("prepare cached");
+        stmt.get_status(StatementStatus::RunCount)
+    };
+
+    // 反复读取复用缓存中的同一条语句，执行次数累计在该语句上
+    let before = run_count(&db);
+    for _ in 0..ROUNDS {
+        db.get_current_provider("claude").expect("current provider");
+    }
+    assert_eq!(run_count(&db), before + ROUNDS);
+
+    // 连接上存活的预编译语句数；DAO 用完的语句都回到缓存，即缓存的占用
+    let cached = |db: &Database| {
+        let conn = db.conn.lock().expect("lock conn");
+        let mut count = 0;
+        // SAFETY: 只遍历连接的语句链表计数，持有连接锁期间没有其他调用方使用该连接
+        unsafe {
+            let handle = conn.handle();
+            let mut stmt = rusqlite::ffi::sqlite3_next_stmt(handle, std::ptr::null_mut());
+            while !stmt.is_null() {
+                count += 1;
+                stmt = rusqlite::ffi::sqlite3_next_stmt(handle, stmt);
+            }
+        }
+        count
+    };
+
+    // 同样的 DAO 调用第二次不再新增缓存条目
+    db.get_all_providers("claude").expect("list providers");
+    let occupied = cached(&db);
+    db.get_all_providers("claude").expect("list providers");
+    assert!(occupied > 0);
+    assert_eq!(cached(&db), occupied);
+}
+
+#[test]
+fn query_only_rejects_writes_but_keeps_reads() {
+    let db = Database::memory().expect("create memory db");
+    let provider = Provider::with_id("a".to_string(), "A".to_string(), json!({}), None);
+    db.save_provider("claude", &provider)
+        .expect("save provider");
+
+    db.set_query_only(true).expect("enable query_only");
+    let renamed = Provider::with_id("a".to_string(), "Renamed".to_string(), json!({}), None);
+    assert!(db.save_provider("claude", &renamed).is_err());
+    assert_eq!(
+        db.get_all_providers("claude").expect("read providers")["a"].name,
+        "A"
+    );
+
+    db.set_query_only(false).expect("disable query_only");
+    db.save_provider("claude", &renamed)
+        .expect("save after disabling");
+}
+
+#[test]
+fn new_providers_record_the_system_user_as_owner() {
+    let db = Database::memory().expect("create memory db");
+    let provider = Provider::with_id("a".to_string(), "A".to_string(), json!({}), None);
+    db.save_provider("claude", &provider)
+        .expect("save provider");
+
+    let owners = db.get_provider_owners("claude").expect("read owners");
+    assert_eq!(owners.get("a"), crate::config::current_username().as_ref());
+}
+
+#[test]
+fn gemini_key_pool_moves_into_provider_keys() {
+    let db = Database::memory().expect("create memory db");
+    let provider = Provider::with_id(
+        "g".to_string(),
+        "Gemini".to_string(),
+        json!({ "env": { "GEMINI_API_KEY": "key-a" } }),
+        None,
+    );
+    db.save_provider("gemini", &provider)
+        .expect("save provider");
+    {
+        let conn = db.conn.lock().expect("lock conn");
+        conn.execute(
+            "UPDATE providers SET meta = ?1 WHERE id = 'g' AND app_type = 'gemini'",
+            [json!({ "geminiKeyPool": ["key-b", "key-a", " "] }).to_string()],
+        )
+        .expect("set legacy pool");
+        Database::migrate_v20_to_v21(&conn).expect("migrate");
+    }
+
+    let keys: Vec<String> = db
+        .get_provider_keys("gemini", "g")
+        .expect("read keys")
+        .into_iter()
+        .map(|key| key.api_key)
+        .collect();
+    assert_eq!(keys, vec!["key-a", "key-b"]);
+
+    let meta = db
+        .get_provider_by_id("g", "gemini")
+        .expect("read provider")
+        .and_then(|provider| provider.meta)
+        .expect("meta");
+    assert_eq!(meta.key_rotation, Some(crate::provider::KeyRotation::Proxy));
+    let raw = serde_json::to_value(&meta).expect("serialize meta");
+    assert!(raw.get("geminiKeyPool").is_none());
+}
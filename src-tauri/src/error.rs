@@ -52,6 +52,52 @@ pub enum AppError {
     },
     #[error("数据库错误: {0}")]
     Database(String),
+    #[error("数据库正被其他进程占用: {0}")]
+    Locked(String),
+    #[error("另一个 cc-switch 操作正在进行中（{path}）")]
+    Busy { path: String },
+    #[error("当前为只读模式，不能修改配置")]
+    ReadOnly,
+    #[error("供应商不存在: {id} ({app})")]
+    ProviderNotFound { id: String, app: String },
+    #[error("供应商已存在: {id} ({app})")]
+    DuplicateProvider { id: String, app: String },
+    #[error("数据库版本过新（{found}），当前应用仅支持 {supported}")]
+    SchemaTooNew { found: i32, supported: i32 },
+    #[error("数据库由更新的版本写入（{found}），当前以兼容模式只读打开，只能查看与切换供应商")]
+    SchemaCompat { found: i32 },
+    #[error("写入 {app} 的 live 配置失败: {source}")]
+    LiveWrite {
+        app: String,
+        #[source]
+        source: Box<AppError>,
+    },
+    #[error("{source}（{}）", describe_file_results(.results))]
+    ApplyFailed {
+        results: Vec<crate::config::FileWriteResult>,
+        #[source]
+        source: Box<AppError>,
+    },
+}
+
+/// 多文件写入失败时各文件的状态，例如 `auth.json: rolledBack, config.toml: failed`
+fn describe_file_results(results: &[crate::config::FileWriteResult]) -> String {
+    results
+        .iter()
+        .map(|result| {
+            let name = result
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| result.path.display().to_string());
+            let status = serde_json::to_value(result.status)
+                .ok()
+                .and_then(|value| value.as_str().map(str::to_string))
+                .unwrap_or_default();
+            format!("{name}: {status}")
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
 impl AppError {
@@ -83,6 +129,87 @@ impl AppError {
             en: en.into(),
         }
     }
+
+    pub fn provider_not_found(id: impl Into<String>, app: impl Into<String>) -> Self {
+        Self::ProviderNotFound {
+            id: id.into(),
+            app: app.into(),
+        }
+    }
+}
+
+impl AppError {
+    /// 针对常见失败给出可操作的建议，由命令行显示在错误信息下方
+    pub fn hint(&self) -> Option<String> {
+        match self {
+            Self::Locked(_) => Some(
+                "数据库正被其他进程占用：关闭 CC Switch 界面后重试，或在界面运行时改用 `cc-switch rpc`"
+                    .to_string(),
+            ),
+            Self::Database(message) | Self::Message(message) | Self::Lock(message)
+                if is_database_locked(message) =>
+            {
+                Some(
+                    "数据库正被其他进程占用：关闭 CC Switch 界面后重试，或在界面运行时改用 `cc-switch rpc`"
+                        .to_string(),
+                )
+            }
+            Self::Localized { key, .. } => match *key {
+                "unsupported_app" => {
+                    let apps: Vec<String> = crate::app_registry::AppRegistry::load()
+                        .all()
+                        .iter()
+                        .map(|app| app.id.clone())
+                        .collect();
+                    Some(format!("可用的应用: {}", apps.join(", ")))
+                }
+                "home.not_found" => {
+                    Some(
+                    "设置 HOME 环境变量（Windows 为 USERPROFILE），或用 CC_SWITCH_HOME 指定配置目录后重试"
+                        .to_string(),
+                )
+                }
+                "provider.limit.exceeded" => Some(
+                    "运行 `cc-switch limits status` 查看用量；调高限额，或将 spendingLimitAction 设为 warn"
+                        .to_string(),
+                ),
+                _ => None,
+            },
+            Self::Busy { .. } => Some(
+                "等待其他 cc-switch 命令（或界面中的切换、导入）完成后重试".to_string(),
+            ),
+            Self::ReadOnly => Some(
+                "只读模式由设置 readOnly 或 CC_SWITCH_READ_ONLY 开启；需要修改时由管理员设置 CC_SWITCH_ADMIN=1 后重试"
+                    .to_string(),
+            ),
+            Self::ProviderNotFound { app, .. } => {
+                Some(format!("运行 `cc-switch list --app {app}` 查看可用的供应商 ID"))
+            }
+            Self::DuplicateProvider { id, .. } => {
+                Some(format!("换一个 ID，或用 `cc-switch show {id}` 查看已有的供应商"))
+            }
+            Self::SchemaTooNew { .. } => Some(
+                "数据库由更新版本的 CC Switch 写入：升级后重试，或用 `cc-switch backup restore` 恢复旧备份"
+                    .to_string(),
+            ),
+            Self::SchemaCompat { .. } => Some(
+                "升级 cc-switch 到与写入数据库的版本一致后再修改配置".to_string(),
+            ),
+            Self::LiveWrite { source, .. } | Self::ApplyFailed { source, .. } => source.hint(),
+            Self::Io { path, source } if source.kind() == std::io::ErrorKind::PermissionDenied => {
+                Some(format!("检查 {path} 及其所在目录的读写权限"))
+            }
+            Self::Json { path, .. } | Self::Toml { path, .. } => {
+                Some(format!("检查 {path} 的格式，或将其备份后移走再重试"))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn is_database_locked(message: &str) -> bool {
+    let message = message.to_ascii_lowercase();
+    message.contains("database is locked") || message.contains("database table is locked")
 }
 
 impl<T> From<PoisonError<T>> for AppError {
@@ -93,7 +220,22 @@ impl<T> From<PoisonError<T>> for AppError {
 
 impl From<rusqlite::Error> for AppError {
     fn from(err: rusqlite::Error) -> Self {
-        Self::Database(err.to_string())
+        match err.sqlite_error_code() {
+            Some(rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked) => {
+                Self::Locked(err.to_string())
+            }
+            // 只读模式下连接设置了 query_only
+            Some(rusqlite::ErrorCode::ReadOnly) if crate::settings::is_read_only() => {
+                Self::ReadOnly
+            }
+            // 数据库版本更新时以兼容模式打开，连接同样设置了 query_only
+            Some(rusqlite::ErrorCode::ReadOnly) if crate::database::schema_compat().is_some() => {
+                Self::SchemaCompat {
+                    found: crate::database::schema_compat().unwrap_or_default(),
+                }
+            }
+            _ => Self::Database(err.to_string()),
+        }
     }
 }
 
@@ -136,3 +278,25 @@ pub fn format_skill_error(
         format!("ERROR:{code}")
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hints_cover_common_failures() {
+        let locked = AppError::Database("database is locked".to_string());
+        assert!(locked.hint().unwrap().contains("cc-switch rpc"));
+
+        let missing = AppError::provider_not_found("p1", "codex");
+        assert!(missing.hint().unwrap().contains("--app codex"));
+
+        let denied = AppError::io(
+            "/etc/cc-switch.db",
+            std::io::Error::from(std::io::ErrorKind::PermissionDenied),
+        );
+        assert!(denied.hint().unwrap().contains("/etc/cc-switch.db"));
+
+        assert!(AppError::InvalidInput("x".to_string()).hint().is_none());
+    }
+}
@@ -11,7 +11,7 @@ pub fn get_gemini_dir() -> PathBuf {
         return custom;
     }
 
-    dirs::home_dir()
+    crate::config::home_dir()
         .expect("无法获取用户主目录")
         .join(".gemini")
 }
@@ -72,7 +72,6 @@ pub fn parse_env_file(content: &str) -> HashMap<String, String> {
 /// - 配置文件错误诊断
 ///
 /// 已有完整的测试覆盖，可直接使用。
-#[allow(dead_code)]
 pub fn parse_env_file_strict(content: &str) -> Result<HashMap<String, String>, AppError> {
     let mut map = HashMap::new();
 
@@ -277,6 +276,38 @@ pub fn validate_gemini_settings_strict(settings: &Value) -> Result<(), AppError>
     Ok(())
 }
 
+/// 获取 Gemini OAuth 凭据文件路径
+///
+/// 返回路径：`~/.gemini/oauth_creds.json`（Gemini CLI 登录 Google 账号后生成）
+pub fn get_gemini_oauth_creds_path() -> PathBuf {
+    get_gemini_dir().join("oauth_creds.json")
+}
+
+/// 验证 Vertex AI 模式的 Gemini 配置
+///
+/// 要求 env 中包含 `GOOGLE_CLOUD_PROJECT` 与 `GOOGLE_CLOUD_LOCATION`；
+/// 使用 Vertex AI 快速模式时也可仅提供 `GOOGLE_API_KEY`。
+pub fn validate_gemini_vertex_settings(settings: &Value) -> Result<(), AppError> {
+    validate_gemini_settings(settings)?;
+
+    let env_map = json_to_env(settings)?;
+    let has = |key: &str| env_map.get(key).is_some_and(|v| !v.trim().is_empty());
+    if has("GOOGLE_API_KEY") {
+        return Ok(());
+    }
+    for key in ["GOOGLE_CLOUD_PROJECT", "GOOGLE_CLOUD_LOCATION"] {
+        if !has(key) {
+            return Err(AppError::localized(
+                "gemini.validation.missing_vertex_field",
+                format!("Vertex AI 配置缺少必需字段: {key}"),
+                format!("Vertex AI config missing required field: {key}"),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
 /// 获取 Gemini settings.json 文件路径
 ///
 /// 返回路径：`~/.gemini/settings.json`（与 `.env` 文件同级）
@@ -372,6 +403,13 @@ pub fn write_google_oauth_settings() -> Result<(), AppError> {
     update_selected_type("oauth-personal")
 }
 
+/// 为 Vertex AI 模式写入 settings.json（`security.auth.selectedType = "vertex-ai"`）
+///
+/// 保留文件中的其他所有字段。
+pub fn write_vertex_ai_settings() -> Result<(), AppError> {
+    update_selected_type("vertex-ai")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,8 +1,12 @@
 mod app_config;
+mod app_registry;
 mod app_store;
 mod auto_launch;
+mod capabilities;
 mod claude_mcp;
 mod claude_plugin;
+mod cli;
+mod cli_error;
 mod codex_config;
 mod commands;
 mod config;
@@ -13,22 +17,31 @@ mod gemini_config;
 mod gemini_mcp;
 mod init_status;
 mod mcp;
+mod notifications;
+mod operation_lock;
 mod prompt;
 mod prompt_files;
 mod provider;
 mod provider_defaults;
 mod proxy;
+mod rpc;
 mod services;
 mod settings;
 mod store;
+#[cfg(feature = "test-util")]
+pub mod test_util;
 mod tray;
+mod usage_format;
 mod usage_script;
 
 pub use app_config::{AppType, McpApps, McpServer, MultiAppConfig};
+pub use app_registry::{AppDefinition, AppRegistry, LiveFormat};
+pub use capabilities::{capabilities, Capability, CapabilityReport};
+pub use cli::run_cli;
 pub use codex_config::{get_codex_auth_path, get_codex_config_path, write_codex_live_atomic};
 pub use commands::*;
 pub use config::{get_claude_mcp_path, get_claude_settings_path, read_json_file};
-pub use database::Database;
+pub use database::{Database, SqlExportOptions};
 pub use deeplink::{import_provider_from_deeplink, parse_deeplink_url, DeepLinkImportRequest};
 pub use error::AppError;
 pub use mcp::{
@@ -37,10 +50,14 @@ pub use mcp::{
     sync_enabled_to_codex, sync_enabled_to_gemini, sync_single_server_to_claude,
     sync_single_server_to_codex, sync_single_server_to_gemini,
 };
-pub use provider::{Provider, ProviderMeta};
+pub use provider::{
+    key_fingerprint, GeminiAuthMode, KeyExpiry, KeyRotation, LiveWriteMode, Provider, ProviderMeta,
+    RetiredKey,
+};
+pub use rpc::run_rpc;
 pub use services::{
-    ConfigService, EndpointLatency, McpService, PromptService, ProviderService, ProxyService,
-    SkillService, SpeedtestService,
+    ConfigService, EndpointLatency, McpService, OnboardingService, PromptService, ProviderService,
+    ProxyService, SkillService, SpeedtestService,
 };
 pub use settings::{update_settings, AppSettings};
 pub use store::AppState;
@@ -237,7 +254,7 @@ pub fn run() {
 
             // 初始化数据库
             let app_config_dir = crate::config::get_app_config_dir();
-            let db_path = app_config_dir.join("cc-switch.db");
+            let db_path = crate::config::get_database_path();
             let json_path = app_config_dir.join("config.json");
 
             // 检查是否需要从 config.json 迁移到 SQLite
@@ -282,6 +299,11 @@ pub fn run() {
                 }
             };
 
+            // 距离上次备份超过设定间隔时自动备份
+            if let Err(e) = db.auto_backup() {
+                log::warn!("自动备份数据库失败: {e}");
+            }
+
             // 如果有预加载的配置，执行迁移
             if let Some(config) = migration_config {
                 log::info!("开始执行数据迁移...");
@@ -348,6 +370,31 @@ pub fn run() {
                 }
             }
 
+            // 2.1 live 配置被外部改成了未保存的凭据：--auto-adopt 时导入为新的当前供应商，
+            //     否则交给前端提示（get_unmanaged_live_configs）
+            if crate::services::provider::auto_adopt() {
+                for app in [
+                    crate::app_config::AppType::Claude,
+                    crate::app_config::AppType::Codex,
+                    crate::app_config::AppType::Gemini,
+                ] {
+                    match crate::services::provider::ProviderService::adopt_live(
+                        &app_state,
+                        app.clone(),
+                    ) {
+                        Ok(Some(provider)) => log::info!(
+                            "✓ Adopted live config of {} as provider {}",
+                            app.as_str(),
+                            provider.id
+                        ),
+                        Ok(None) => {}
+                        Err(e) => {
+                            log::warn!("✗ Failed to adopt live config of {}: {e}", app.as_str())
+                        }
+                    }
+                }
+            }
+
             // 3. 导入 MCP 服务器配置（表空时触发）
             if app_state.db.is_mcp_table_empty().unwrap_or(false) {
                 log::info!("MCP table empty, importing from live configurations...");
@@ -503,6 +550,30 @@ pub fn run() {
             }
 
             let _tray = tray_builder.build(app)?;
+
+            // 命令行等其他进程修改数据后通知前端刷新并重建托盘菜单
+            match app_state.db.subscribe_changes() {
+                Ok(changes) => {
+                    let app_handle = app.handle().clone();
+                    std::thread::spawn(move || {
+                        for event in changes {
+                            if let Err(e) = app_handle.emit("data-changed", &event) {
+                                log::error!("发射数据变更事件失败: {e}");
+                            }
+                            let state = app_handle.state::<AppState>();
+                            if let Ok(menu) = tray::create_tray_menu(&app_handle, state.inner()) {
+                                if let Some(tray) = app_handle.tray_by_id("main") {
+                                    if let Err(e) = tray.set_menu(Some(menu)) {
+                                        log::error!("更新托盘菜单失败: {e}");
+                                    }
+                                }
+                            }
+                        }
+                    });
+                }
+                Err(e) => log::warn!("订阅数据变更失败: {e}"),
+            }
+
             // 将同一个实例注入到全局状态，避免重复创建导致的不一致
             app.manage(app_state);
 
@@ -516,6 +587,9 @@ pub fn run() {
                 }
             }
 
+            // 接管未恢复的限时切换（包括 CLI 发起的）
+            commands::resume_temporary_switches(app.handle());
+
             // 异常退出恢复 + 代理状态自动恢复
             let app_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
@@ -555,6 +629,8 @@ pub fn run() {
             commands::delete_provider,
             commands::switch_provider,
             commands::import_default_config,
+            commands::get_unmanaged_live_configs,
+            commands::adopt_live_provider,
             commands::get_claude_config_status,
             commands::get_config_status,
             commands::get_claude_code_config_path,
@@ -576,6 +652,7 @@ pub fn run() {
             commands::restart_app,
             commands::check_for_updates,
             commands::is_portable_mode,
+            commands::get_capabilities,
             commands::get_claude_plugin_status,
             commands::read_claude_plugin_config,
             commands::apply_claude_plugin_config,
@@ -590,6 +667,45 @@ pub fn run() {
             commands::validate_mcp_command,
             // usage query
             commands::queryProviderUsage,
+            commands::query_all_provider_usage,
+            commands::enrich_provider,
+            commands::get_provider_policy,
+            commands::clone_provider,
+            commands::set_provider_model,
+            commands::get_provider_env,
+            commands::set_provider_env,
+            commands::set_provider_proxy,
+            commands::get_provider_snippet,
+            commands::validate_providers,
+            commands::find_provider_references,
+            commands::revoke_provider_key,
+            commands::get_audit_log,
+            commands::get_switch_history,
+            commands::get_provider_usage_counters,
+            commands::get_app_definitions,
+            commands::get_registered_app_providers,
+            commands::get_registered_app_current_provider,
+            commands::save_registered_app_provider,
+            commands::switch_registered_app_provider,
+            commands::delete_registered_app_provider,
+            commands::delete_providers,
+            commands::add_tag_to_providers,
+            commands::export_providers_to_file,
+            commands::import_providers_from_file,
+            commands::check_provider_app_version,
+            commands::add_provider_tag,
+            commands::remove_provider_tag,
+            commands::get_providers_by_tag,
+            commands::switch_provider_by_tag,
+            commands::set_category_default,
+            commands::clear_category_default,
+            commands::get_category_defaults,
+            commands::switch_provider_by_category,
+            commands::switch_provider_temporarily,
+            commands::get_temporary_switch,
+            commands::cancel_temporary_switch,
+            commands::search_providers,
+            commands::query_providers,
             commands::testUsageScript,
             // New MCP via config.json (SSOT)
             commands::get_mcp_config,
@@ -614,11 +730,15 @@ pub fn run() {
             commands::add_custom_endpoint,
             commands::remove_custom_endpoint,
             commands::update_endpoint_last_used,
+            commands::test_provider_endpoints,
+            commands::set_active_endpoint,
             // app_config_dir override via Store
             commands::get_app_config_dir_override,
             commands::set_app_config_dir_override,
             // provider sort order management
             commands::update_providers_sort_order,
+            commands::reorder_providers,
+            commands::move_provider,
             // theirs: config import/export and dialogs
             commands::export_config_to_file,
             commands::import_config_from_file,
@@ -672,6 +792,9 @@ pub fn run() {
             commands::remove_from_failover_queue,
             commands::get_auto_failover_enabled,
             commands::set_auto_failover_enabled,
+            commands::set_provider_failover_group,
+            commands::get_failover_group_members,
+            commands::run_failover,
             // Usage statistics
             commands::get_usage_summary,
             commands::get_usage_trends,
@@ -683,12 +806,22 @@ pub fn run() {
             commands::update_model_pricing,
             commands::delete_model_pricing,
             commands::check_provider_limits,
+            commands::get_spending_limits,
             // Stream health check
             commands::stream_check_provider,
             commands::stream_check_all_providers,
             commands::get_stream_check_config,
+            commands::benchmark_providers,
+            commands::sync_push,
+            commands::sync_pull,
+            commands::sync_status,
             commands::save_stream_check_config,
             commands::get_tool_versions,
+            // Provider debugging
+            commands::debug_replay_request,
+            commands::debug_provider_headers,
+            // Editor integrations
+            commands::generate_integration,
         ]);
 
     let app = builder
@@ -2,6 +2,11 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    // 无界面子命令（`cc-switch rpc`、`cc-switch list` 等），执行后直接退出
+    if let Some(code) = cc_switch_lib::run_cli(std::env::args().skip(1).collect()) {
+        std::process::exit(code);
+    }
+
     // 在 Linux 上设置 WebKit 环境变量以解决 DMA-BUF 渲染问题
     // 某些 Linux 系统（如 Debian 13.2、Nvidia GPU）上 WebKitGTK 的 DMA-BUF 渲染器可能导致白屏/黑屏
     // 参考: https://github.com/tauri-apps/tauri/issues/9394
@@ -0,0 +1,301 @@
+//! 事件通知
+//!
+//! 代理故障转移到其他供应商、供应商超出消费限额时，按设置中的 `notifications.channels`
+//! 发送通知。每个渠道是一个 [`NotificationSink`]：标准输出、系统桌面通知、webhook 或
+//! shell 命令。通知在后台线程中发送，不会阻塞代理请求；单个渠道失败只写日志。
+//!
+//! 切换钩子（`services::provider::hooks`）复用这里的命令与 webhook 实现。
+
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::error::AppError;
+use crate::settings::{NotificationChannel, NotificationSettings};
+
+/// 单个命令或 webhook 的最长执行时间
+pub(crate) const SINK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 通知类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NotificationKind {
+    /// 代理把请求转移到了另一个供应商
+    Failover,
+    /// 供应商超出每日或每月消费限额
+    LimitExceeded,
+}
+
+impl NotificationKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NotificationKind::Failover => "failover",
+            NotificationKind::LimitExceeded => "limit-exceeded",
+        }
+    }
+}
+
+/// 一条通知
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Notification {
+    pub kind: NotificationKind,
+    pub app: String,
+    pub provider_id: String,
+    pub provider_name: String,
+    pub title: String,
+    pub message: String,
+    /// Unix 毫秒
+    pub timestamp: i64,
+}
+
+impl Notification {
+    pub fn new(
+        kind: NotificationKind,
+        app: &str,
+        provider_id: &str,
+        provider_name: &str,
+        message: impl Into<String>,
+    ) -> Self {
+        let title = match kind {
+            NotificationKind::Failover => format!("CC Switch: {app} 已故障转移"),
+            NotificationKind::LimitExceeded => format!("CC Switch: {app} 超出消费限额"),
+        };
+        Self {
+            kind,
+            app: app.to_string(),
+            provider_id: provider_id.to_string(),
+            provider_name: provider_name.to_string(),
+            title,
+            message: message.into(),
+            timestamp: chrono::Utc::now().timestamp_millis(),
+        }
+    }
+}
+
+/// 通知渠道
+pub trait NotificationSink: Send {
+    fn name(&self) -> &'static str;
+    fn send(&self, notification: &Notification) -> Result<(), AppError>;
+}
+
+struct StdoutSink;
+
+impl NotificationSink for StdoutSink {
+    fn name(&self) -> &'static str {
+        "stdout"
+    }
+
+    fn send(&self, notification: &Notification) -> Result<(), AppError> {
+        println!("[{}] {}", notification.title, notification.message);
+        Ok(())
+    }
+}
+
+struct DesktopSink;
+
+impl NotificationSink for DesktopSink {
+    fn name(&self) -> &'static str {
+        "desktop"
+    }
+
+    fn send(&self, notification: &Notification) -> Result<(), AppError> {
+        notify_rust::Notification::new()
+            .appname("CC Switch")
+            .summary(&notification.title)
+            .body(&notification.message)
+            .show()
+            .map(|_| ())
+            .map_err(|e| AppError::Message(format!("桌面通知失败: {e}")))
+    }
+}
+
+struct WebhookSink {
+    url: String,
+}
+
+impl NotificationSink for WebhookSink {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn send(&self, notification: &Notification) -> Result<(), AppError> {
+        post_json(&self.url, notification)
+    }
+}
+
+struct CommandSink {
+    command: String,
+}
+
+impl NotificationSink for CommandSink {
+    fn name(&self) -> &'static str {
+        "command"
+    }
+
+    fn send(&self, notification: &Notification) -> Result<(), AppError> {
+        let payload = serde_json::to_string(notification).unwrap_or_default();
+        run_command(
+            &self.command,
+            &[
+                ("CC_SWITCH_EVENT", notification.kind.as_str().to_string()),
+                ("CC_SWITCH_APP", notification.app.clone()),
+                ("CC_SWITCH_PROVIDER", notification.provider_id.clone()),
+                (
+                    "CC_SWITCH_PROVIDER_NAME",
+                    notification.provider_name.clone(),
+                ),
+                ("CC_SWITCH_MESSAGE", notification.message.clone()),
+                ("CC_SWITCH_PAYLOAD", payload),
+            ],
+        )
+    }
+}
+
+/// 按配置创建渠道
+pub fn sinks(settings: &NotificationSettings) -> Vec<Box<dyn NotificationSink>> {
+    settings
+        .channels
+        .iter()
+        .map(|channel| -> Box<dyn NotificationSink> {
+            match channel {
+                NotificationChannel::Stdout => Box::new(StdoutSink),
+                NotificationChannel::Desktop => Box::new(DesktopSink),
+                NotificationChannel::Webhook { url } => Box::new(WebhookSink { url: url.clone() }),
+                NotificationChannel::Command { command } => Box::new(CommandSink {
+                    command: command.clone(),
+                }),
+            }
+        })
+        .collect()
+}
+
+/// 在后台线程中把通知发送到设置中的所有渠道
+pub fn notify(notification: Notification) {
+    let settings = crate::settings::get_settings()
+        .notifications
+        .unwrap_or_default();
+    let sinks = sinks(&settings);
+    if sinks.is_empty() {
+        return;
+    }
+    std::thread::spawn(move || {
+        for sink in sinks {
+            if let Err(e) = sink.send(&notification) {
+                log::warn!("[Notify] {} 渠道发送失败: {e}", sink.name());
+            }
+        }
+    });
+}
+
+/// 通过 `sh -c`（Windows 为 `cmd /C`）执行命令，超过 [`SINK_TIMEOUT`] 时终止
+///
+/// 非零退出时返回的错误包含 stderr。
+pub(crate) fn run_command(command: &str, envs: &[(&str, String)]) -> Result<(), AppError> {
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    };
+    let mut child = cmd
+        .envs(envs.iter().map(|(key, value)| (*key, value.as_str())))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| AppError::Message(format!("无法执行 `{command}`: {e}")))?;
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child
+            .try_wait()
+            .map_err(|e| AppError::Message(e.to_string()))?
+        {
+            break status;
+        }
+        if started.elapsed() > SINK_TIMEOUT {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(AppError::Message(format!(
+                "`{command}` 超过 {} 秒未结束，已终止",
+                SINK_TIMEOUT.as_secs()
+            )));
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    };
+    if status.success() {
+        return Ok(());
+    }
+    let mut stderr = String::new();
+    if let Some(mut pipe) = child.stderr.take() {
+        let _ = pipe.read_to_string(&mut stderr);
+    }
+    let stderr = stderr.trim();
+    Err(AppError::Message(if stderr.is_empty() {
+        format!("`{command}` 退出状态 {status}")
+    } else {
+        format!("`{command}` 退出状态 {status}: {stderr}")
+    }))
+}
+
+/// POST JSON 并等待结果
+///
+/// 在单独的线程中使用自己的运行时，因此在 GUI（已处于 tokio 中）与命令行中行为一致。
+pub(crate) fn post_json<T: Serialize>(url: &str, payload: &T) -> Result<(), AppError> {
+    let url = url.to_string();
+    let body = serde_json::to_value(payload).map_err(|e| AppError::Message(e.to_string()))?;
+    std::thread::spawn(move || -> Result<(), AppError> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| AppError::Message(e.to_string()))?;
+        runtime.block_on(async {
+            let response = reqwest::Client::new()
+                .post(&url)
+                .timeout(SINK_TIMEOUT)
+                .json(&body)
+                .send()
+                .await
+                .map_err(|e| AppError::Message(format!("{url}: {e}")))?;
+            if !response.status().is_success() {
+                return Err(AppError::Message(format!(
+                    "{url}: HTTP {}",
+                    response.status()
+                )));
+            }
+            Ok(())
+        })
+    })
+    .join()
+    .unwrap_or_else(|_| Err(AppError::Message("webhook 线程异常退出".to_string())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channels_map_to_sinks_and_default_to_desktop() {
+        let names = |settings: &NotificationSettings| -> Vec<&'static str> {
+            sinks(settings).iter().map(|sink| sink.name()).collect()
+        };
+        assert_eq!(names(&NotificationSettings::default()), vec!["desktop"]);
+
+        let settings: NotificationSettings = serde_json::from_value(serde_json::json!({
+            "channels": [
+                { "type": "stdout" },
+                { "type": "webhook", "url": "https://hooks.example/x" },
+                { "type": "command", "command": "true" }
+            ]
+        }))
+        .unwrap();
+        assert_eq!(names(&settings), vec!["stdout", "webhook", "command"]);
+        assert!(names(&NotificationSettings { channels: vec![] }).is_empty());
+    }
+}
@@ -0,0 +1,119 @@
+//! 跨进程操作锁
+//!
+//! 脚本中并发执行的多个 `cc-switch switch`（或界面与命令行同时操作）可能交错地更新数据库
+//! 与 live 配置文件。切换、导入、备份与恢复等操作先获取 `~/.cc-switch/cc-switch.lock`
+//! 上的排他锁（advisory lock，进程退出时由系统释放，不会残留），等待超时则返回
+//! [`AppError::Busy`]。
+//!
+//! 同一线程内可以重入：外层操作持有锁时，内部调用的其他加锁操作直接通过。
+
+use std::cell::Cell;
+use std::fs::{self, File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use fs2::FileExt;
+
+use crate::config::get_app_config_dir;
+use crate::error::AppError;
+
+const LOCK_FILE: &str = "cc-switch.lock";
+
+/// 等待其他进程释放锁的最长时间
+const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+
+const RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+thread_local! {
+    static HELD: Cell<bool> = const { Cell::new(false) };
+}
+
+/// 持有期间其他 cc-switch 进程无法开始加锁的操作，drop 时释放
+#[must_use = "锁在 drop 时释放"]
+pub struct OperationLock {
+    /// 重入时为 None，由外层负责释放
+    file: Option<File>,
+}
+
+impl OperationLock {
+    pub fn lock_path() -> PathBuf {
+        get_app_config_dir().join(LOCK_FILE)
+    }
+
+    /// 获取操作锁，最多等待 10 秒
+    pub fn acquire() -> Result<Self, AppError> {
+        Self::acquire_at(&Self::lock_path(), LOCK_TIMEOUT)
+    }
+
+    fn acquire_at(path: &Path, timeout: Duration) -> Result<Self, AppError> {
+        if HELD.with(Cell::get) {
+            return Ok(Self { file: None });
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
+        }
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .map_err(|e| AppError::io(path, e))?;
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            match file.try_lock_exclusive() {
+                Ok(()) => break,
+                Err(e) if e.kind() == fs2::lock_contended_error().kind() => {
+                    if Instant::now() >= deadline {
+                        return Err(AppError::Busy {
+                            path: path.display().to_string(),
+                        });
+                    }
+                    std::thread::sleep(RETRY_INTERVAL);
+                }
+                Err(e) => return Err(AppError::io(path, e)),
+            }
+        }
+        HELD.with(|held| held.set(true));
+        Ok(Self { file: Some(file) })
+    }
+}
+
+impl Drop for OperationLock {
+    fn drop(&mut self) {
+        if let Some(file) = self.file.take() {
+            let _ = FileExt::unlock(&file);
+            HELD.with(|held| held.set(false));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lock_is_reentrant_but_exclusive_across_holders() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(LOCK_FILE);
+
+        let outer = OperationLock::acquire_at(&path, Duration::ZERO).unwrap();
+        let inner = OperationLock::acquire_at(&path, Duration::ZERO).unwrap();
+        drop(inner);
+
+        let other = |timeout| {
+            let path = path.clone();
+            std::thread::spawn(move || OperationLock::acquire_at(&path, timeout).map(|_| ()))
+                .join()
+                .unwrap()
+        };
+        assert!(matches!(
+            other(Duration::from_millis(200)),
+            Err(AppError::Busy { .. })
+        ));
+
+        drop(outer);
+        other(Duration::ZERO).unwrap();
+    }
+}
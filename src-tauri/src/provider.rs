@@ -1,12 +1,18 @@
 use indexmap::IndexMap;
+use regex::Regex;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 
+use crate::app_config::AppType;
+use crate::proxy::providers::get_adapter;
+
 // SSOT 模式：不再写供应商副本文件
 
 /// 供应商结构体
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Provider {
     pub id: String,
     pub name: String,
@@ -40,6 +46,9 @@ pub struct Provider {
     #[serde(default)]
     #[serde(rename = "inFailoverQueue")]
     pub in_failover_queue: bool,
+    /// 标签（多值，存于 provider_tags 表）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
 
 impl Provider {
@@ -63,10 +72,267 @@ impl Provider {
             icon: None,
             icon_color: None,
             in_failover_queue: false,
+            tags: Vec::new(),
+        }
+    }
+
+    /// settings_config 的规范化指纹，用于发现重复的供应商
+    ///
+    /// 对象键排序、字符串去掉首尾空白，Codex 的 config.toml 文本按解析后的结构计算，
+    /// 因此仅格式、注释或键顺序不同的两份配置指纹相同。
+    pub fn fingerprint(&self) -> String {
+        let mut settings = self.settings_config.clone();
+        let parsed = settings
+            .get("config")
+            .and_then(Value::as_str)
+            .and_then(|text| text.parse::<toml::Table>().ok())
+            .and_then(|table| serde_json::to_value(table).ok());
+        if let (Some(parsed), Some(object)) = (parsed, settings.as_object_mut()) {
+            object.insert("config".to_string(), parsed);
+        }
+        let canonical = canonical_json(&settings).to_string();
+        sha256_prefix(&canonical)
+    }
+
+    /// 连接身份（Base URL 与 API Key 指纹）；配置中没有 API Key 时返回 None
+    pub fn connection_identity(&self, app_type: &AppType) -> Option<ConnectionIdentity> {
+        let adapter = get_adapter(app_type);
+        let key = adapter.extract_auth(self)?.api_key;
+        if key.trim().is_empty() {
+            return None;
+        }
+        let base_url = adapter
+            .extract_base_url(self)
+            .map(|url| url.trim().trim_end_matches('/').to_ascii_lowercase())
+            .unwrap_or_default();
+        Some(ConnectionIdentity {
+            base_url,
+            key_fingerprint: key_fingerprint(&key),
+        })
+    }
+
+    /// 返回遮蔽了密钥字段的副本，用于展示
+    ///
+    /// 遮蔽范围按应用区分：Claude/Gemini 的 `env` 中以 `_KEY`/`_TOKEN`/`_SECRET` 结尾的变量，
+    /// Codex 的 `auth` 全部字符串值及 config.toml 中的 `api_key`/`experimental_bearer_token`，
+    /// 以及元数据中的用量查询凭据。
+    pub fn redacted(&self, app_type: &AppType) -> Provider {
+        self.map_secrets(app_type, &mut |_, secret| mask_secret(secret))
+    }
+
+    /// 返回对每个密钥字段应用 `f` 后的副本
+    ///
+    /// 字段范围与 [`Provider::redacted`] 相同；`f` 的第一个参数是字段在供应商中的稳定路径
+    /// （如 `env.ANTHROPIC_AUTH_TOKEN`、`config.api_key#0`），可用于加密后还原或按字段合并。
+    pub fn map_secrets(
+        &self,
+        app_type: &AppType,
+        f: &mut dyn FnMut(&str, &str) -> String,
+    ) -> Provider {
+        let mut provider = self.clone();
+        let settings = &mut provider.settings_config;
+
+        match app_type {
+            AppType::Claude | AppType::Gemini => {
+                if let Some(env) = settings.get_mut("env").and_then(Value::as_object_mut) {
+                    for (name, value) in env.iter_mut() {
+                        if is_secret_env_name(name) {
+                            map_string(value, &format!("env.{name}"), f);
+                        }
+                    }
+                }
+                for field in ["apiKey", "api_key"] {
+                    if let Some(value) = settings.get_mut(field) {
+                        map_string(value, field, f);
+                    }
+                }
+            }
+            AppType::Codex => {
+                if let Some(auth) = settings.get_mut("auth") {
+                    map_all_strings(auth, "auth", f);
+                }
+                if let Some(config) = settings.get_mut("config") {
+                    if let Some(text) = config.as_str() {
+                        *config = Value::String(map_toml_secrets(text, f));
+                    }
+                }
+            }
+        }
+
+        if let Some(meta) = provider.meta.as_mut() {
+            if let Some(script) = meta.usage_script.as_mut() {
+                for (path, secret) in [
+                    ("meta.usageScript.apiKey", &mut script.api_key),
+                    ("meta.usageScript.accessToken", &mut script.access_token),
+                ] {
+                    if let Some(value) = secret.as_mut() {
+                        *value = f(path, value);
+                    }
+                }
+            }
+        }
+
+        provider
+    }
+
+    /// 密钥的到期状态；没有设置到期日与轮换周期时为 None
+    pub fn key_expiry(&self, today: chrono::NaiveDate) -> Option<KeyExpiry> {
+        let meta = self.meta.as_ref()?;
+        let expires = meta
+            .key_expires_at
+            .as_deref()
+            .and_then(|date| chrono::NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").ok());
+        let rotation = meta.key_rotation_days.and_then(|days| {
+            let since = meta.key_rotated_at.or(self.created_at)?;
+            let since = chrono::DateTime::from_timestamp_millis(since)?
+                .with_timezone(&chrono::Local)
+                .date_naive();
+            since.checked_add_days(chrono::Days::new(days.into()))
+        });
+        let due = match (expires, rotation) {
+            (Some(a), Some(b)) => a.min(b),
+            (due, None) | (None, due) => due?,
+        };
+        Some(KeyExpiry {
+            due,
+            days_left: (due - today).num_days(),
+        })
+    }
+
+    /// 按字段名遮蔽密钥（用于注册表中的非内置应用，配置结构未知）
+    ///
+    /// 任意层级中名称以 `_KEY` / `_TOKEN` / `_SECRET` 结尾，或为 `apiKey` / `api_key` / `token`
+    /// 的字符串值都会被遮蔽。
+    pub fn redacted_by_field_name(&self) -> Provider {
+        fn walk(value: &mut Value) {
+            match value {
+                Value::Object(map) => {
+                    for (name, child) in map.iter_mut() {
+                        let secret = is_secret_env_name(name)
+                            || ["apikey", "api_key", "token"]
+                                .contains(&name.to_ascii_lowercase().as_str());
+                        if secret && child.is_string() {
+                            mask_value(child);
+                        } else {
+                            walk(child);
+                        }
+                    }
+                }
+                Value::Array(items) => items.iter_mut().for_each(walk),
+                _ => {}
+            }
+        }
+
+        let mut provider = self.clone();
+        walk(&mut provider.settings_config);
+        provider
+    }
+}
+
+/// 供应商的连接身份，用于判断一份配置属于哪个已保存的供应商
+///
+/// Base URL 去掉结尾的 `/` 并转为小写；密钥只保留指纹，不保存原文。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ConnectionIdentity {
+    pub base_url: String,
+    pub key_fingerprint: String,
+}
+
+/// 密钥指纹：SHA-256 摘要的前 16 位十六进制
+pub fn key_fingerprint(key: &str) -> String {
+    sha256_prefix(key.trim())
+}
+
+fn sha256_prefix(text: &str) -> String {
+    Sha256::digest(text.as_bytes())
+        .iter()
+        .take(8)
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// 键排序、字符串去掉首尾空白后的 JSON
+fn canonical_json(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            Value::Object(
+                keys.into_iter()
+                    .map(|key| (key.clone(), canonical_json(&map[key])))
+                    .collect(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.iter().map(canonical_json).collect()),
+        Value::String(text) => Value::String(text.trim().to_string()),
+        other => other.clone(),
+    }
+}
+
+/// 遮蔽密钥，仅保留末 4 位（过短的密钥完全遮蔽）
+pub fn mask_secret(secret: &str) -> String {
+    let chars: Vec<char> = secret.trim().chars().collect();
+    if chars.len() <= 8 {
+        return "****".to_string();
+    }
+    let suffix: String = chars[chars.len() - 4..].iter().collect();
+    format!("****{suffix}")
+}
+
+pub(crate) fn is_secret_env_name(name: &str) -> bool {
+    let upper = name.to_ascii_uppercase();
+    ["_KEY", "_TOKEN", "_SECRET"]
+        .iter()
+        .any(|suffix| upper.ends_with(suffix))
+}
+
+fn mask_value(value: &mut Value) {
+    if let Some(secret) = value.as_str().filter(|s| !s.is_empty()) {
+        *value = Value::String(mask_secret(secret));
+    }
+}
+
+fn map_string(value: &mut Value, path: &str, f: &mut dyn FnMut(&str, &str) -> String) {
+    if let Some(secret) = value.as_str().filter(|s| !s.is_empty()) {
+        *value = Value::String(f(path, secret));
+    }
+}
+
+fn map_all_strings(value: &mut Value, path: &str, f: &mut dyn FnMut(&str, &str) -> String) {
+    match value {
+        Value::String(_) => map_string(value, path, f),
+        Value::Array(items) => {
+            for (index, item) in items.iter_mut().enumerate() {
+                map_all_strings(item, &format!("{path}[{index}]"), f);
+            }
+        }
+        Value::Object(map) => {
+            for (name, item) in map.iter_mut() {
+                map_all_strings(item, &format!("{path}.{name}"), f);
+            }
         }
+        _ => {}
     }
 }
 
+/// 遮蔽 Codex config.toml 文本中的 `api_key` / `experimental_bearer_token`
+pub(crate) fn mask_toml_secrets(text: &str) -> String {
+    map_toml_secrets(text, &mut |_, secret| mask_secret(secret))
+}
+
+fn map_toml_secrets(text: &str, f: &mut dyn FnMut(&str, &str) -> String) -> String {
+    let re = Regex::new(r#"(?m)^(\s*(api_key|experimental_bearer_token)\s*=\s*)"([^"]*)""#)
+        .expect("valid secret regex");
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    re.replace_all(text, |caps: &regex::Captures| {
+        let count = seen.entry(caps[2].to_string()).or_default();
+        let path = format!("config.{}#{count}", &caps[2]);
+        *count += 1;
+        format!("{}\"{}\"", &caps[1], f(&path, &caps[3]))
+    })
+    .into_owned()
+}
+
 /// 供应商管理器
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ProviderManager {
@@ -75,7 +341,7 @@ pub struct ProviderManager {
 }
 
 /// 用量查询脚本配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct UsageScript {
     pub enabled: bool,
     pub language: String,
@@ -139,7 +405,7 @@ pub struct UsageResult {
 }
 
 /// 供应商元数据
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct ProviderMeta {
     /// 自定义端点列表（按 URL 去重存储）
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
@@ -165,6 +431,176 @@ pub struct ProviderMeta {
     /// 每月消费限额（USD）
     #[serde(rename = "limitMonthlyUsd", skip_serializing_if = "Option::is_none")]
     pub limit_monthly_usd: Option<String>,
+    /// 内置用量查询（如 "newapi"、"openrouter"、"anthropic"），未启用用量脚本时使用
+    #[serde(rename = "usageProvider", skip_serializing_if = "Option::is_none")]
+    pub usage_provider: Option<String>,
+    /// 兼容的应用版本范围（如 ">=0.30"），切换时若本地版本不在范围内会给出警告
+    #[serde(rename = "appVersionRange", skip_serializing_if = "Option::is_none")]
+    pub app_version_range: Option<String>,
+    /// 写入 live 配置的方式（目前用于 Claude settings.json），未设置时为合并
+    #[serde(rename = "liveWriteMode", skip_serializing_if = "Option::is_none")]
+    pub live_write_mode: Option<LiveWriteMode>,
+    /// Gemini 认证方式，未设置时按供应商名称/合作伙伴标记自动识别
+    #[serde(rename = "geminiAuthMode", skip_serializing_if = "Option::is_none")]
+    pub gemini_auth_mode: Option<GeminiAuthMode>,
+    /// 默认模型，写入 live 配置时注入（Claude ANTHROPIC_MODEL / Codex model / Gemini GEMINI_MODEL）
+    #[serde(rename = "defaultModel", skip_serializing_if = "Option::is_none")]
+    pub default_model: Option<String>,
+    /// 小型快速模型（仅 Claude，写入 ANTHROPIC_DEFAULT_HAIKU_MODEL）
+    #[serde(rename = "smallFastModel", skip_serializing_if = "Option::is_none")]
+    pub small_fast_model: Option<String>,
+    /// HTTP 代理（如 `http://127.0.0.1:7890`），写入 live 配置时导出为 HTTP_PROXY
+    #[serde(rename = "httpProxy", skip_serializing_if = "Option::is_none")]
+    pub http_proxy: Option<String>,
+    /// HTTPS 代理，支持 `socks5://`，导出为 HTTPS_PROXY
+    #[serde(rename = "httpsProxy", skip_serializing_if = "Option::is_none")]
+    pub https_proxy: Option<String>,
+    /// 不走代理的主机列表（逗号分隔），导出为 NO_PROXY
+    #[serde(rename = "noProxy", skip_serializing_if = "Option::is_none")]
+    pub no_proxy: Option<String>,
+    /// 选中的自定义端点，写入 live 配置时替换 Base URL
+    #[serde(rename = "activeEndpoint", skip_serializing_if = "Option::is_none")]
+    pub active_endpoint: Option<String>,
+    /// API Key 到期日（`YYYY-MM-DD`）
+    #[serde(rename = "keyExpiresAt", skip_serializing_if = "Option::is_none")]
+    pub key_expires_at: Option<String>,
+    /// 建议的密钥轮换周期（天），从上次轮换（没有时为创建时间）起算
+    #[serde(rename = "keyRotationDays", skip_serializing_if = "Option::is_none")]
+    pub key_rotation_days: Option<u32>,
+    /// 上次轮换密钥的时间（Unix 毫秒）
+    #[serde(rename = "keyRotatedAt", skip_serializing_if = "Option::is_none")]
+    pub key_rotated_at: Option<i64>,
+    /// 已轮换掉的密钥（只保存指纹），按时间先后排列
+    #[serde(rename = "retiredKeys", default, skip_serializing_if = "Vec::is_empty")]
+    pub retired_keys: Vec<RetiredKey>,
+    /// Key 池（provider_keys 表）的轮换策略，未设置时为轮询
+    #[serde(rename = "keyStrategy", skip_serializing_if = "Option::is_none")]
+    pub key_strategy: Option<KeyStrategy>,
+    /// Key 池的轮换时机，未设置时在切换时轮换
+    #[serde(rename = "keyRotation", skip_serializing_if = "Option::is_none")]
+    pub key_rotation: Option<KeyRotation>,
+    /// 切换到该供应商时执行的钩子（在全局钩子之后）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hooks: Option<crate::settings::SwitchHooks>,
+}
+
+/// Key 池的轮换策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum KeyStrategy {
+    /// 按添加顺序依次使用
+    #[default]
+    RoundRobin,
+    /// 使用最久未用的 Key
+    LeastRecentlyUsed,
+    /// 按权重分配使用次数
+    Weighted,
+}
+
+impl std::str::FromStr for KeyStrategy {
+    type Err = crate::error::AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "round-robin" | "rr" => Ok(KeyStrategy::RoundRobin),
+            "least-recently-used" | "lru" => Ok(KeyStrategy::LeastRecentlyUsed),
+            "weighted" => Ok(KeyStrategy::Weighted),
+            other => Err(crate::error::AppError::InvalidInput(format!(
+                "未知的轮换策略: {other}（可选: round-robin, lru, weighted）"
+            ))),
+        }
+    }
+}
+
+/// Key 池的轮换时机
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyRotation {
+    /// 每次切换到该供应商时选出一个 Key 写入 live 配置
+    #[default]
+    Switch,
+    /// 由本地代理为每个请求选择 Key（需开启代理接管）
+    Proxy,
+}
+
+impl std::str::FromStr for KeyRotation {
+    type Err = crate::error::AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "switch" => Ok(KeyRotation::Switch),
+            "proxy" => Ok(KeyRotation::Proxy),
+            other => Err(crate::error::AppError::InvalidInput(format!(
+                "未知的轮换时机: {other}（可选: switch, proxy）"
+            ))),
+        }
+    }
+}
+
+/// 被轮换掉的密钥
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RetiredKey {
+    /// [`key_fingerprint`]
+    pub fingerprint: String,
+    /// Unix 毫秒
+    pub retired_at: i64,
+}
+
+/// 距到期不足此天数时提醒轮换
+pub const KEY_EXPIRY_WARNING_DAYS: i64 = 14;
+
+/// 密钥的到期状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyExpiry {
+    /// 到期日与轮换到期日中较早的一个
+    pub due: chrono::NaiveDate,
+    /// 剩余天数，已过期时为负数
+    pub days_left: i64,
+}
+
+impl KeyExpiry {
+    pub fn is_expired(&self) -> bool {
+        self.days_left < 0
+    }
+
+    /// 已过期或将在 [`KEY_EXPIRY_WARNING_DAYS`] 天内到期
+    pub fn needs_attention(&self) -> bool {
+        self.days_left <= KEY_EXPIRY_WARNING_DAYS
+    }
+
+    /// 简短说明，如 `3 天后到期`、`已过期 2 天`
+    pub fn describe(&self) -> String {
+        match self.days_left {
+            n if n < 0 => format!("已过期 {} 天", -n),
+            0 => "今天到期".to_string(),
+            n => format!("{n} 天后到期"),
+        }
+    }
+}
+
+/// Gemini CLI 认证方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum GeminiAuthMode {
+    /// `GEMINI_API_KEY`
+    ApiKey,
+    /// Google 账号登录（凭据保存在 `~/.gemini/oauth_creds.json`）
+    Oauth,
+    /// Vertex AI（`GOOGLE_CLOUD_PROJECT` + `GOOGLE_CLOUD_LOCATION`）
+    Vertex,
+}
+
+/// 写入 live 配置的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LiveWriteMode {
+    /// 只替换 cc-switch 管理的 env 变量及供应商配置中出现的键，保留 live 文件中的其他设置
+    #[default]
+    Merge,
+    /// 用供应商配置整体覆盖 live 文件
+    Overwrite,
 }
 
 impl ProviderManager {
@@ -173,3 +609,98 @@ impl ProviderManager {
         &self.providers
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn redacted_masks_secrets_per_app() {
+        let claude = Provider::with_id(
+            "c".to_string(),
+            "Claude".to_string(),
+            json!({ "env": {
+                "ANTHROPIC_AUTH_TOKEN": "sk-ant-secret-abcd",
+                "ANTHROPIC_BASE_URL": "https://api.example.com"
+            } }),
+            None,
+        )
+        .redacted(&AppType::Claude);
+        assert_eq!(
+            claude.settings_config["env"]["ANTHROPIC_AUTH_TOKEN"],
+            "****abcd"
+        );
+        assert_eq!(
+            claude.settings_config["env"]["ANTHROPIC_BASE_URL"],
+            "https://api.example.com"
+        );
+
+        let codex = Provider::with_id(
+            "x".to_string(),
+            "Codex".to_string(),
+            json!({
+                "auth": { "OPENAI_API_KEY": "sk-openai-secret-wxyz" },
+                "config": "model = \"gpt-5\"\nexperimental_bearer_token = \"tok-secret-1234\"\n"
+            }),
+            None,
+        )
+        .redacted(&AppType::Codex);
+        assert_eq!(codex.settings_config["auth"]["OPENAI_API_KEY"], "****wxyz");
+        let config = codex.settings_config["config"].as_str().unwrap();
+        assert!(config.contains("experimental_bearer_token = \"****1234\""));
+        assert!(config.contains("model = \"gpt-5\""));
+    }
+
+    #[test]
+    fn fingerprint_ignores_key_order_whitespace_and_toml_layout() {
+        let provider =
+            |settings: Value| Provider::with_id("p".to_string(), "P".to_string(), settings, None);
+        let a = provider(json!({
+            "auth": { "OPENAI_API_KEY": "sk-1" },
+            "config": "model = \"gpt-5\"\nbase_url = \"https://relay.example\"\n",
+        }));
+        let b = provider(json!({
+            "config": "# relay\nbase_url = 'https://relay.example'\nmodel   = \"gpt-5\"",
+            "auth": { "OPENAI_API_KEY": " sk-1 " },
+        }));
+        let c = provider(json!({
+            "auth": { "OPENAI_API_KEY": "sk-2" },
+            "config": "model = \"gpt-5\"\nbase_url = \"https://relay.example\"\n",
+        }));
+        assert_eq!(a.fingerprint(), b.fingerprint());
+        assert_ne!(a.fingerprint(), c.fingerprint());
+    }
+
+    #[test]
+    fn key_expiry_uses_earliest_of_date_and_rotation() {
+        let today = chrono::NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+        let mut provider = Provider::with_id("p".into(), "P".into(), json!({}), None);
+        assert!(provider.key_expiry(today).is_none());
+
+        provider.meta = Some(ProviderMeta {
+            key_expires_at: Some("2026-03-20".into()),
+            ..Default::default()
+        });
+        let expiry = provider.key_expiry(today).unwrap();
+        assert_eq!(expiry.days_left, 10);
+        assert!(expiry.needs_attention() && !expiry.is_expired());
+
+        let rotated = chrono::NaiveDate::from_ymd_opt(2026, 2, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_local_timezone(chrono::Local)
+            .unwrap()
+            .timestamp_millis();
+        let meta = provider.meta.as_mut().unwrap();
+        meta.key_rotation_days = Some(30);
+        meta.key_rotated_at = Some(rotated);
+        let expiry = provider.key_expiry(today).unwrap();
+        assert_eq!(
+            expiry.due,
+            chrono::NaiveDate::from_ymd_opt(2026, 3, 3).unwrap()
+        );
+        assert!(expiry.is_expired());
+    }
+}
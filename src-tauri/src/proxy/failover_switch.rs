@@ -5,10 +5,12 @@
 //! - 数据库更新
 //! - 托盘菜单更新
 //! - 前端事件发射
+//! - 桌面/Webhook 通知（见 `crate::notifications`）
 //! - Live 备份更新
 
 use crate::database::Database;
 use crate::error::AppError;
+use crate::notifications::{self, Notification, NotificationKind};
 use std::collections::HashSet;
 use std::str::FromStr;
 use std::sync::Arc;
@@ -128,6 +130,13 @@ impl FailoverSwitchManager {
         }
 
         log::info!("[Failover] 供应商切换完成: {app_type} -> {provider_name} ({provider_id})");
+        notifications::notify(Notification::new(
+            NotificationKind::Failover,
+            app_type,
+            provider_id,
+            provider_name,
+            format!("请求失败后已切换到 {provider_name}"),
+        ));
 
         Ok(true)
     }
@@ -5,6 +5,7 @@
 use super::{
     error::*,
     failover_switch::FailoverSwitchManager,
+    key_pool::ProviderKeyPool,
     provider_router::ProviderRouter,
     providers::{get_adapter, ProviderAdapter},
     types::ProxyStatus,
@@ -37,6 +38,8 @@ pub struct RequestForwarder {
     current_providers: Arc<RwLock<std::collections::HashMap<String, (String, String)>>>,
     /// 故障转移切换管理器
     failover_manager: Arc<FailoverSwitchManager>,
+    /// 供应商 Key 池（按请求轮换）
+    provider_key_pool: Arc<ProviderKeyPool>,
     /// AppHandle，用于发射事件和更新托盘
     app_handle: Option<tauri::AppHandle>,
     /// 请求开始时的“当前供应商 ID”（用于判断是否需要同步 UI/托盘）
@@ -52,6 +55,7 @@ impl RequestForwarder {
         status: Arc<RwLock<ProxyStatus>>,
         current_providers: Arc<RwLock<std::collections::HashMap<String, (String, String)>>>,
         failover_manager: Arc<FailoverSwitchManager>,
+        provider_key_pool: Arc<ProviderKeyPool>,
         app_handle: Option<tauri::AppHandle>,
         current_provider_id_at_start: String,
     ) -> Self {
@@ -71,6 +75,7 @@ impl RequestForwarder {
             status,
             current_providers,
             failover_manager,
+            provider_key_pool,
             app_handle,
             current_provider_id_at_start,
         }
@@ -78,37 +83,51 @@ impl RequestForwarder {
 
     /// 对单个 Provider 执行请求（带重试）
     ///
-    /// 在同一个 Provider 上最多重试 max_retries 次，使用指数退避
+    /// 在同一个 Provider 上最多重试 max_retries 次，使用指数退避。
+    /// 供应商的 Key 池按请求轮换时，遇到 429 会先换用池中的下一个 Key 立即重试
+    /// （不计入重试次数），直到所有 Key 都被尝试过一遍。
     async fn forward_with_provider_retry(
         &self,
+        app_type: &AppType,
         provider: &Provider,
         endpoint: &str,
         body: &Value,
         headers: &axum::http::HeaderMap,
         adapter: &dyn ProviderAdapter,
     ) -> Result<Response, ProxyError> {
+        let key_count = self.provider_key_pool.len(app_type, provider);
+
         let mut last_error = None;
+        let mut attempt: u8 = 0;
+        let mut key_rotations = 0usize;
 
-        for attempt in 0..=self.max_retries {
-            if attempt > 0 {
-                // 指数退避：100ms, 200ms, 400ms, ...
-                let delay_ms = 100 * 2u64.pow(attempt as u32 - 1);
-                log::info!(
-                    "[{}] 重试第 {}/{} 次（等待 {}ms）",
-                    adapter.name(),
-                    attempt,
-                    self.max_retries,
-                    delay_ms
-                );
-                tokio::time::sleep(Duration::from_millis(delay_ms)).await;
-            }
+        loop {
+            let effective_provider = if key_count > 0 {
+                self.provider_key_pool.apply(app_type, provider)
+            } else {
+                provider.clone()
+            };
 
             match self
-                .forward(provider, endpoint, body, headers, adapter)
+                .forward(&effective_provider, endpoint, body, headers, adapter)
                 .await
             {
                 Ok(response) => return Ok(response),
                 Err(e) => {
+                    // Key 池：429 限流时换用下一个 Key
+                    let rate_limited = matches!(e, ProxyError::UpstreamError { status: 429, .. });
+                    if rate_limited && key_rotations + 1 < key_count {
+                        // 下一次 apply 会按轮换策略选出另一个 Key
+                        key_rotations += 1;
+                        log::warn!(
+                            "[{}] Provider {} 触发限流，换用 Key 池中的下一个 Key",
+                            adapter.name(),
+                            provider.name
+                        );
+                        last_error = Some(e);
+                        continue;
+                    }
+
                     // 只有“同一 Provider 内可重试”的错误才继续重试
                     if !self.should_retry_same_provider(&e) {
                         return Err(e);
@@ -124,6 +143,22 @@ impl RequestForwarder {
                     last_error = Some(e);
                 }
             }
+
+            if attempt >= self.max_retries {
+                break;
+            }
+            attempt += 1;
+
+            // 指数退避：100ms, 200ms, 400ms, ...
+            let delay_ms = 100 * 2u64.pow(attempt as u32 - 1);
+            log::info!(
+                "[{}] 重试第 {}/{} 次（等待 {}ms）",
+                adapter.name(),
+                attempt,
+                self.max_retries,
+                delay_ms
+            );
+            tokio::time::sleep(Duration::from_millis(delay_ms)).await;
         }
 
         Err(last_error.unwrap_or(ProxyError::MaxRetriesExceeded))
@@ -208,7 +243,14 @@ impl RequestForwarder {
 
             // 转发请求（带单 Provider 内重试）
             match self
-                .forward_with_provider_retry(provider, endpoint, &body, &headers, adapter.as_ref())
+                .forward_with_provider_retry(
+                    app_type,
+                    provider,
+                    endpoint,
+                    &body,
+                    &headers,
+                    adapter.as_ref(),
+                )
                 .await
             {
                 Ok(response) => {
@@ -81,6 +81,14 @@ impl RequestContext {
             .await
             .map_err(|e| ProxyError::DatabaseError(e.to_string()))?;
 
+        // 超出消费限额的供应商：提醒，或按 block 策略移出故障转移链
+        let providers = crate::services::provider::apply_spending_limits(
+            &state.db,
+            app_type_str,
+            providers,
+            state.app_handle.as_ref(),
+        );
+
         let provider = providers
             .first()
             .cloned()
@@ -140,6 +148,7 @@ impl RequestContext {
             state.status.clone(),
             state.current_providers.clone(),
             state.failover_manager.clone(),
+            state.provider_key_pool.clone(),
             state.app_handle.clone(),
             self.current_provider_id.clone(),
         )
@@ -0,0 +1,213 @@
+//! 多 Key 轮换
+//!
+//! 供应商的 Key 池保存在 provider_keys 表中：轮换时机为 `proxy` 时，代理按供应商的轮换策略
+//! 为每个请求（以及 429 后的重试）选择 Key。
+//!
+//! Key 池按供应商缓存在内存中，请求路径上不查询数据库。本进程添加、删除 Key 后缓存立即失效
+//! （见 [`Database::key_pool_generation`]）；命令行等其他进程的修改在 [`POOL_CACHE_TTL`] 内生效。
+//! 使用记录先更新缓存，再在后台写入数据库。
+
+use crate::app_config::AppType;
+use crate::database::{Database, ProviderKey};
+use crate::provider::{KeyRotation, Provider};
+use crate::services::provider::{pick_pool_key, with_pool_key};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 缓存的 Key 池重新从数据库读取的间隔
+const POOL_CACHE_TTL: Duration = Duration::from_secs(30);
+
+struct CachedPool {
+    keys: Vec<ProviderKey>,
+    generation: u64,
+    loaded_at: Instant,
+}
+
+/// provider_keys 表中的 Key 池（按请求轮换）
+pub struct ProviderKeyPool {
+    db: Arc<Database>,
+    /// key 格式: (app_type, provider_id)
+    cache: Mutex<HashMap<(String, String), CachedPool>>,
+}
+
+impl ProviderKeyPool {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self {
+            db,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 供应商按请求轮换的 Key 数量（轮换时机不是 `proxy` 时为 0）
+    pub fn len(&self, app_type: &AppType, provider: &Provider) -> usize {
+        if !rotates_per_request(provider) {
+            return 0;
+        }
+        self.with_keys(app_type, &provider.id, |keys| keys.len())
+    }
+
+    /// 返回应用了下一个 Key 的供应商副本
+    ///
+    /// 未配置按请求轮换的 Key 池时原样返回。
+    pub fn apply(&self, app_type: &AppType, provider: &Provider) -> Provider {
+        if !rotates_per_request(provider) {
+            return provider.clone();
+        }
+        let strategy = provider
+            .meta
+            .as_ref()
+            .and_then(|m| m.key_strategy)
+            .unwrap_or_default();
+        let picked = self.with_keys(app_type, &provider.id, |keys| {
+            let id = pick_pool_key(strategy, keys)?.id;
+            let key = keys.iter_mut().find(|key| key.id == id)?;
+            key.use_count += 1;
+            key.last_used = Some(chrono::Utc::now().timestamp_millis());
+            Some((key.id, key.api_key.clone()))
+        });
+        let Some((id, api_key)) = picked else {
+            return provider.clone();
+        };
+
+        self.mark_used(id);
+        with_pool_key(app_type, provider, &api_key)
+    }
+
+    /// 在缓存的 Key 池上执行 `f`，缓存缺失或过期时先从数据库读取
+    fn with_keys<R>(
+        &self,
+        app_type: &AppType,
+        provider_id: &str,
+        f: impl FnOnce(&mut Vec<ProviderKey>) -> R,
+    ) -> R {
+        let generation = self.db.key_pool_generation();
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = (app_type.as_str().to_string(), provider_id.to_string());
+        let stale = match cache.get(&entry) {
+            Some(pool) => {
+                pool.generation != generation || pool.loaded_at.elapsed() >= POOL_CACHE_TTL
+            }
+            None => true,
+        };
+        if stale {
+            let keys = self
+                .db
+                .get_provider_keys(app_type.as_str(), provider_id)
+                .unwrap_or_else(|e| {
+                    log::warn!("读取供应商 {provider_id} 的 Key 池失败: {e}");
+                    Vec::new()
+                });
+            cache.insert(
+                entry.clone(),
+                CachedPool {
+                    keys,
+                    generation,
+                    loaded_at: Instant::now(),
+                },
+            );
+        }
+        let pool = cache.get_mut(&entry).expect("pool cached above");
+        f(&mut pool.keys)
+    }
+
+    /// 记录一次使用：在 Tokio 运行时中放到阻塞线程池执行，不占用请求路径
+    fn mark_used(&self, id: i64) {
+        let db = self.db.clone();
+        let mark = move || {
+            if let Err(e) = db.mark_provider_key_used(id) {
+                log::warn!("记录 Key 使用失败: {e}");
+            }
+        };
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn_blocking(mark);
+            }
+            Err(_) => mark(),
+        }
+    }
+}
+
+fn rotates_per_request(provider: &Provider) -> bool {
+    provider.meta.as_ref().and_then(|m| m.key_rotation) == Some(KeyRotation::Proxy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::ProviderMeta;
+    use serde_json::json;
+
+    fn setup() -> (Arc<Database>, Provider) {
+        let db = Arc::new(Database::memory().expect("memory db"));
+        let mut provider = Provider::with_id(
+            "gemini-free".to_string(),
+            "Gemini Free".to_string(),
+            json!({ "env": { "GEMINI_API_KEY": "key-a" } }),
+            None,
+        );
+        provider.meta = Some(ProviderMeta {
+            key_rotation: Some(KeyRotation::Proxy),
+            ..Default::default()
+        });
+        db.save_provider("gemini", &provider)
+            .expect("save provider");
+        for key in ["key-a", "key-b"] {
+            db.add_provider_key("gemini", &provider.id, key, None, 1)
+                .expect("add key");
+        }
+        (db, provider)
+    }
+
+    fn key_of(provider: &Provider) -> serde_json::Value {
+        provider.settings_config["env"]["GEMINI_API_KEY"].clone()
+    }
+
+    #[test]
+    fn apply_rotates_through_cached_pool() {
+        let (db, provider) = setup();
+        let pool = ProviderKeyPool::new(db);
+
+        assert_eq!(pool.len(&AppType::Gemini, &provider), 2);
+        assert_eq!(
+            key_of(&pool.apply(&AppType::Gemini, &provider)),
+            json!("key-a")
+        );
+        assert_eq!(
+            key_of(&pool.apply(&AppType::Gemini, &provider)),
+            json!("key-b")
+        );
+        assert_eq!(
+            key_of(&pool.apply(&AppType::Gemini, &provider)),
+            json!("key-a")
+        );
+    }
+
+    #[test]
+    fn key_edits_invalidate_cache() {
+        let (db, provider) = setup();
+        let pool = ProviderKeyPool::new(db.clone());
+        assert_eq!(pool.len(&AppType::Gemini, &provider), 2);
+
+        db.add_provider_key("gemini", &provider.id, "key-c", None, 1)
+            .expect("add key");
+        assert_eq!(pool.len(&AppType::Gemini, &provider), 3);
+
+        db.remove_provider_key("gemini", &provider.id, "key-a")
+            .expect("remove key");
+        assert_eq!(pool.len(&AppType::Gemini, &provider), 2);
+    }
+
+    #[test]
+    fn switch_rotation_is_ignored_by_proxy() {
+        let (db, mut provider) = setup();
+        provider.meta = Some(ProviderMeta::default());
+        let pool = ProviderKeyPool::new(db);
+
+        assert_eq!(pool.len(&AppType::Gemini, &provider), 0);
+        assert_eq!(
+            key_of(&pool.apply(&AppType::Gemini, &provider)),
+            json!("key-a")
+        );
+    }
+}
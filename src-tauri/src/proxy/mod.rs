@@ -11,6 +11,7 @@ pub mod handler_config;
 pub mod handler_context;
 mod handlers;
 mod health;
+pub mod key_pool;
 pub mod provider_router;
 pub mod providers;
 pub mod response_handler;
@@ -175,6 +175,7 @@ mod tests {
             icon: None,
             icon_color: None,
             in_failover_queue: false,
+            tags: Vec::new(),
         }
     }
 
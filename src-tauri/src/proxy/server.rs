@@ -3,8 +3,8 @@
 //! 基于Axum的HTTP服务器，处理代理请求
 
 use super::{
-    failover_switch::FailoverSwitchManager, handlers, provider_router::ProviderRouter, types::*,
-    ProxyError,
+    failover_switch::FailoverSwitchManager, handlers, key_pool::ProviderKeyPool,
+    provider_router::ProviderRouter, types::*, ProxyError,
 };
 use crate::database::Database;
 use axum::{
@@ -32,6 +32,8 @@ pub struct ProxyState {
     pub app_handle: Option<tauri::AppHandle>,
     /// 故障转移切换管理器
     pub failover_manager: Arc<FailoverSwitchManager>,
+    /// 供应商 Key 池（provider_keys 表）
+    pub provider_key_pool: Arc<ProviderKeyPool>,
 }
 
 /// 代理HTTP服务器
@@ -53,6 +55,7 @@ impl ProxyServer {
         let provider_router = Arc::new(ProviderRouter::new(db.clone()));
         // 创建故障转移切换管理器
         let failover_manager = Arc::new(FailoverSwitchManager::new(db.clone()));
+        let provider_key_pool = Arc::new(ProviderKeyPool::new(db.clone()));
 
         let state = ProxyState {
             db,
@@ -63,6 +66,7 @@ impl ProxyServer {
             provider_router,
             app_handle,
             failover_manager,
+            provider_key_pool,
         };
 
         Self {
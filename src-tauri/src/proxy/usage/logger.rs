@@ -102,8 +102,11 @@ impl<'a> UsageLogger<'a> {
             ],
         )
         .map_err(|e| AppError::Database(format!("记录请求日志失败: {e}")))?;
+        drop(conn);
 
-        Ok(())
+        let tokens = log.usage.input_tokens as u64 + log.usage.output_tokens as u64;
+        self.db
+            .increment_usage_counters(&log.app_type, &log.provider_id, 1, tokens)
     }
 
     /// 记录失败的请求
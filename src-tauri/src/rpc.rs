@@ -0,0 +1,790 @@
+//! 无界面 JSON-RPC 模式（`cc-switch rpc`）
+//!
+//! 从 stdin 逐行读取 JSON-RPC 2.0 请求，并将响应逐行写入 stdout，
+//! 供编辑器插件（VS Code、Neovim 等）以子进程方式嵌入。不启动 GUI，也不常驻后台：
+//! stdin 关闭后进程退出。
+//!
+//! 每行一个请求对象（或批量请求数组）；不带 `id` 的通知不会产生响应。
+//!
+//! 返回供应商的方法默认遮蔽 API Key 等密钥字段，需显式传入 `"reveal": true` 才返回原值。
+//!
+//! `cc-switch rpc --db <path>` 以只读方式查看另一个数据库文件（备份或他人导出的 .db），
+//! 此时切换、排序等会修改数据或写入应用配置的方法一律拒绝。
+
+use std::collections::BTreeMap;
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::app_config::AppType;
+use crate::cli_error;
+use crate::database::Database;
+use crate::error::AppError;
+use crate::provider::{is_secret_env_name, mask_secret, Provider};
+use crate::services::provider::{
+    app_version_warning, parse_columns, LookupTarget, ProviderMove, ProviderProxy, ProviderService,
+    SnippetLang, TableStyle,
+};
+use crate::services::RegisteredAppService;
+use crate::store::AppState;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// 业务错误（供应商不存在、写入配置失败等）
+const APP_ERROR: i64 = -32000;
+/// 只读模式（`--db`）下调用了会修改数据的方法
+const READ_ONLY: i64 = -32001;
+
+/// 支持的方法列表
+pub(crate) const METHODS: &[&str] = &[
+    "rpc.methods",
+    "capabilities",
+    "apps.list",
+    "providers.list",
+    "providers.table",
+    "providers.current",
+    "providers.get",
+    "providers.snippet",
+    "providers.validate",
+    "providers.find",
+    "keys.revoke",
+    "audit.list",
+    "history.list",
+    "usage.counters",
+    "limits.status",
+    "providers.switch",
+    "providers.switchCategory",
+    "providers.search",
+    "providers.byTag",
+    "providers.move",
+    "providers.setModel",
+    "providers.env",
+    "providers.setEnv",
+    "providers.setProxy",
+    "database.export",
+];
+
+/// 会修改数据库或应用配置的方法，只读模式下拒绝
+const MUTATING_METHODS: &[&str] = &[
+    "keys.revoke",
+    "providers.switch",
+    "providers.switchCategory",
+    "providers.move",
+    "providers.setModel",
+    "providers.setEnv",
+    "providers.setProxy",
+];
+
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<AppError> for RpcError {
+    fn from(err: AppError) -> Self {
+        Self::new(APP_ERROR, err.to_string())
+    }
+}
+
+#[derive(Deserialize)]
+struct AppParams {
+    app: String,
+    /// 返回未遮蔽的密钥
+    #[serde(default)]
+    reveal: bool,
+}
+
+#[derive(Deserialize)]
+struct TableParams {
+    app: String,
+    /// 逗号分隔的列名，缺省时显示全部列
+    #[serde(default)]
+    columns: Option<String>,
+    /// `bordered`（默认）或 `plain`
+    #[serde(default)]
+    style: TableStyle,
+}
+
+#[derive(Deserialize)]
+struct ProviderParams {
+    app: String,
+    id: String,
+    #[serde(default)]
+    reveal: bool,
+    /// 发起切换的工作目录，记录到切换历史；缺省为 rpc 进程的工作目录
+    #[serde(default)]
+    cwd: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ValidateParams {
+    /// 缺省时校验全部应用
+    #[serde(default)]
+    app: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct FindParams {
+    #[serde(default)]
+    key: Option<String>,
+    #[serde(default)]
+    url: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RevokeParams {
+    /// 密钥或供应商 ID
+    target: String,
+    #[serde(default)]
+    archive: bool,
+}
+
+#[derive(Deserialize)]
+struct AuditParams {
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct CountersParams {
+    #[serde(default)]
+    app: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct HistoryParams {
+    #[serde(default)]
+    app: Option<String>,
+    #[serde(default)]
+    cwd: Option<String>,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct SnippetParams {
+    app: String,
+    id: String,
+    lang: String,
+}
+
+#[derive(Deserialize)]
+struct SearchParams {
+    app: String,
+    query: String,
+    #[serde(default)]
+    reveal: bool,
+}
+
+#[derive(Deserialize)]
+struct MoveParams {
+    app: String,
+    id: String,
+    movement: ProviderMove,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ModelParams {
+    app: String,
+    id: String,
+    model: String,
+    #[serde(default)]
+    small_fast_model: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct EnvParams {
+    app: String,
+    id: String,
+    #[serde(default)]
+    set: BTreeMap<String, String>,
+    #[serde(default)]
+    unset: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProxyParams {
+    app: String,
+    id: String,
+    #[serde(default)]
+    http_proxy: Option<String>,
+    #[serde(default)]
+    https_proxy: Option<String>,
+    #[serde(default)]
+    no_proxy: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CategoryParams {
+    app: String,
+    category: String,
+}
+
+#[derive(Deserialize)]
+struct ExportParams {
+    /// 导出的 SQL 文件路径
+    path: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct TagParams {
+    app: String,
+    tag: String,
+    #[serde(default)]
+    reveal: bool,
+}
+
+/// 运行 stdin/stdout 循环，返回进程退出码
+///
+/// `args` 为 `rpc` 之后的命令行参数，目前仅支持 `--db <path>`。
+pub fn run_rpc(args: Vec<String>) -> i32 {
+    let db_path = match parse_db_arg(&args) {
+        Ok(path) => path,
+        Err(message) => {
+            eprintln!("cc-switch rpc: {message}");
+            return cli_error::USAGE;
+        }
+    };
+    let read_only = db_path.is_some();
+    let db = match db_path {
+        Some(path) => Database::open_read_only(&path),
+        None => Database::init(),
+    };
+    let db = match db {
+        Ok(db) => Arc::new(db),
+        Err(e) => {
+            eprintln!("cc-switch rpc: 初始化数据库失败: {e}");
+            return cli_error::exit_code(&e);
+        }
+    };
+    let state = AppState::new(db);
+
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout().lock();
+    for line in stdin.lock().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                eprintln!("cc-switch rpc: 读取 stdin 失败: {e}");
+                return cli_error::FAILURE;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if let Some(response) = handle_message(&state, &line, read_only) {
+            if writeln!(stdout, "{response}")
+                .and_then(|_| stdout.flush())
+                .is_err()
+            {
+                // stdout 已关闭（调用方退出），无需继续
+                return 0;
+            }
+        }
+    }
+
+    0
+}
+
+/// 解析 `--db <path>` / `--db=<path>`
+fn parse_db_arg(args: &[String]) -> Result<Option<PathBuf>, String> {
+    let mut db_path = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(path) = arg.strip_prefix("--db=") {
+            db_path = Some(PathBuf::from(path));
+        } else if arg == "--db" {
+            let path = iter.next().ok_or("--db 需要指定数据库文件路径")?;
+            db_path = Some(PathBuf::from(path));
+        } else {
+            return Err(format!("未知参数: {arg}"));
+        }
+    }
+    Ok(db_path)
+}
+
+/// 处理一行输入，返回需要写回的响应（通知返回 None）
+///
+/// `read_only` 为 true 时拒绝 [`MUTATING_METHODS`] 中的方法。
+pub(crate) fn handle_message(state: &AppState, line: &str, read_only: bool) -> Option<Value> {
+    let message: Value = match serde_json::from_str(line) {
+        Ok(value) => value,
+        Err(e) => {
+            return Some(error_response(
+                Value::Null,
+                RpcError::new(PARSE_ERROR, format!("Parse error: {e}")),
+            ))
+        }
+    };
+
+    match message {
+        Value::Array(batch) if batch.is_empty() => Some(error_response(
+            Value::Null,
+            RpcError::new(INVALID_REQUEST, "Empty batch"),
+        )),
+        Value::Array(batch) => {
+            let responses: Vec<Value> = batch
+                .iter()
+                .filter_map(|request| handle_request(state, request, read_only))
+                .collect();
+            (!responses.is_empty()).then(|| Value::Array(responses))
+        }
+        request => handle_request(state, &request, read_only),
+    }
+}
+
+fn handle_request(state: &AppState, request: &Value, read_only: bool) -> Option<Value> {
+    let id = request.get("id").cloned();
+    let method = request.get("method").and_then(Value::as_str);
+
+    let (Some(method), true) = (method, request.get("jsonrpc") == Some(&json!("2.0"))) else {
+        return Some(error_response(
+            id.unwrap_or(Value::Null),
+            RpcError::new(INVALID_REQUEST, "Invalid request"),
+        ));
+    };
+
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+    let result = if read_only && MUTATING_METHODS.contains(&method) {
+        Err(RpcError::new(
+            READ_ONLY,
+            format!("Method not allowed in read-only mode (--db): {method}"),
+        ))
+    } else {
+        dispatch(state, method, params)
+    };
+
+    // 通知（无 id）不返回响应
+    let id = id?;
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(err) => error_response(id, err),
+    })
+}
+
+fn dispatch(state: &AppState, method: &str, params: Value) -> Result<Value, RpcError> {
+    let params = resolve_alias(state, params)?;
+    match method {
+        "rpc.methods" => Ok(json!(METHODS)),
+        "capabilities" => Ok(json!(crate::capabilities::capabilities())),
+        "apps.list" => Ok(json!(RegisteredAppService::definitions())),
+        "providers.list" => {
+            let p: AppParams = parse_params(params)?;
+            let Some(app_type) = resolve_app(&p.app)? else {
+                // 注册表中的非内置应用：配置结构未知，按字段名遮蔽密钥
+                let current = RegisteredAppService::current(state, &p.app)?;
+                let providers: Vec<_> = RegisteredAppService::list(state, &p.app)?
+                    .into_values()
+                    .map(|provider| {
+                        if p.reveal {
+                            provider
+                        } else {
+                            provider.redacted_by_field_name()
+                        }
+                    })
+                    .collect();
+                return Ok(json!({ "current": current, "providers": providers }));
+            };
+            let current = ProviderService::current(state, app_type.clone())?;
+            let providers: Vec<_> = ProviderService::list(state, app_type.clone())?
+                .into_values()
+                .collect();
+            let providers = present(&app_type, providers, p.reveal);
+            Ok(json!({ "current": current, "providers": providers }))
+        }
+        "providers.table" => {
+            let p: TableParams = parse_params(params)?;
+            let app_type = parse_app(&p.app)?;
+            let columns = parse_columns(p.columns.as_deref().unwrap_or_default())
+                .map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))?;
+            Ok(json!(ProviderService::render_table(
+                state, app_type, &columns, p.style
+            )?))
+        }
+        "providers.current" => {
+            let p: AppParams = parse_params(params)?;
+            resolve_app(&p.app)?;
+            let current = RegisteredAppService::current(state, &p.app)?;
+            let app_id = p.app.trim().to_lowercase();
+            let provider = state.db.get_provider_by_id(&current, &app_id)?;
+            Ok(json!({
+                "id": current,
+                "name": provider.map(|p| p.name),
+            }))
+        }
+        "providers.get" => {
+            let p: ProviderParams = parse_params(params)?;
+            let app_type = resolve_app(&p.app)?;
+            let app_id = p.app.trim().to_lowercase();
+            let provider = state
+                .db
+                .get_provider_by_id(&p.id, &app_id)?
+                .ok_or_else(|| RpcError::new(APP_ERROR, format!("供应商 {} 不存在", p.id)))?;
+            Ok(match app_type {
+                Some(app_type) => json!(present(&app_type, vec![provider], p.reveal).remove(0)),
+                None if p.reveal => json!(provider),
+                None => json!(provider.redacted_by_field_name()),
+            })
+        }
+        "providers.snippet" => {
+            let p: SnippetParams = parse_params(params)?;
+            let app_type = parse_app(&p.app)?;
+            let lang = SnippetLang::from_str(&p.lang)
+                .map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))?;
+            Ok(json!(ProviderService::snippet(
+                state, app_type, &p.id, lang
+            )?))
+        }
+        "providers.validate" => {
+            let p: ValidateParams = parse_params(params)?;
+            let app_type = p.app.as_deref().map(parse_app).transpose()?;
+            Ok(json!(ProviderService::validate_all(state, app_type)?))
+        }
+        "providers.find" => {
+            let p: FindParams = parse_params(params)?;
+            let target = LookupTarget::from_args(p.key, p.url)
+                .map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))?;
+            Ok(json!(ProviderService::find_references(state, &target)?))
+        }
+        "keys.revoke" => {
+            let p: RevokeParams = parse_params(params)?;
+            Ok(json!(ProviderService::revoke_key(
+                state, &p.target, p.archive
+            )?))
+        }
+        "audit.list" => {
+            let p: AuditParams = parse_params(params)?;
+            Ok(json!(state.db.get_audit_log(p.limit.unwrap_or(100))?))
+        }
+        "history.list" => {
+            let p: HistoryParams = parse_params(params)?;
+            Ok(json!(ProviderService::switch_history(
+                state,
+                p.app.as_deref(),
+                p.cwd.as_deref(),
+                p.limit.unwrap_or(100),
+            )?))
+        }
+        "usage.counters" => {
+            let p: CountersParams = parse_params(params)?;
+            Ok(json!(state.db.get_usage_counters(p.app.as_deref())?))
+        }
+        "limits.status" => {
+            let p: CountersParams = parse_params(params)?;
+            let app_type = p.app.as_deref().map(parse_app).transpose()?;
+            Ok(json!(ProviderService::limits_status(state, app_type)?))
+        }
+        "providers.switch" => {
+            let p: ProviderParams = parse_params(params)?;
+            let Some(app_type) = resolve_app(&p.app)? else {
+                RegisteredAppService::switch(state, &p.app, &p.id)?;
+                return Ok(json!({ "current": p.id, "warning": null }));
+            };
+            let cwd = p.cwd.or_else(|| {
+                std::env::current_dir()
+                    .ok()
+                    .map(|dir| dir.to_string_lossy().into_owned())
+            });
+            ProviderService::switch_from(state, app_type.clone(), &p.id, cwd.as_deref())?;
+            let warning = state
+                .db
+                .get_provider_by_id(&p.id, app_type.as_str())?
+                .and_then(|provider| {
+                    app_version_warning(&app_type, &provider)
+                        .or_else(|| ProviderService::limit_warning(state, &app_type, &provider))
+                });
+            Ok(json!({ "current": p.id, "warning": warning }))
+        }
+        "providers.switchCategory" => {
+            let p: CategoryParams = parse_params(params)?;
+            let app_type = parse_app(&p.app)?;
+            let id = ProviderService::switch_by_category(state, app_type.clone(), &p.category)?;
+            let warning = state
+                .db
+                .get_provider_by_id(&id, app_type.as_str())?
+                .and_then(|provider| {
+                    app_version_warning(&app_type, &provider)
+                        .or_else(|| ProviderService::limit_warning(state, &app_type, &provider))
+                });
+            Ok(json!({ "current": id, "warning": warning }))
+        }
+        "providers.search" => {
+            let p: SearchParams = parse_params(params)?;
+            let app_type = parse_app(&p.app)?;
+            let providers = ProviderService::search(state, app_type.clone(), &p.query)?;
+            Ok(json!(present(&app_type, providers, p.reveal)))
+        }
+        "providers.byTag" => {
+            let p: TagParams = parse_params(params)?;
+            let app_type = parse_app(&p.app)?;
+            let providers = ProviderService::list_by_tag(state, app_type.clone(), &p.tag)?;
+            Ok(json!(present(&app_type, providers, p.reveal)))
+        }
+        "providers.move" => {
+            let p: MoveParams = parse_params(params)?;
+            let app_type = parse_app(&p.app)?;
+            let position = ProviderService::move_provider(state, app_type, &p.id, p.movement)?;
+            Ok(json!({ "position": position }))
+        }
+        "providers.setModel" => {
+            let p: ModelParams = parse_params(params)?;
+            let app_type = parse_app(&p.app)?;
+            let provider = ProviderService::set_model(
+                state,
+                app_type,
+                &p.id,
+                &p.model,
+                p.small_fast_model.as_deref(),
+            )?;
+            let meta = provider.meta.unwrap_or_default();
+            Ok(json!({
+                "model": meta.default_model,
+                "smallFastModel": meta.small_fast_model,
+            }))
+        }
+        "providers.env" => {
+            let p: ProviderParams = parse_params(params)?;
+            let app_type = parse_app(&p.app)?;
+            let mut env = ProviderService::env(state, app_type, &p.id)?;
+            if !p.reveal {
+                for (key, value) in env.iter_mut() {
+                    if is_secret_env_name(key) && !value.is_empty() {
+                        *value = mask_secret(value);
+                    }
+                }
+            }
+            Ok(json!(env))
+        }
+        "providers.setEnv" => {
+            let p: EnvParams = parse_params(params)?;
+            let app_type = parse_app(&p.app)?;
+            let env = ProviderService::set_env(state, app_type, &p.id, &p.set, &p.unset)?;
+            Ok(json!({ "keys": env.keys().collect::<Vec<_>>() }))
+        }
+        "providers.setProxy" => {
+            let p: ProxyParams = parse_params(params)?;
+            let app_type = parse_app(&p.app)?;
+            let proxy = ProviderProxy {
+                http: p.http_proxy,
+                https: p.https_proxy,
+                no_proxy: p.no_proxy,
+            };
+            let meta = ProviderService::set_proxy(state, app_type, &p.id, &proxy)?
+                .meta
+                .unwrap_or_default();
+            Ok(json!({
+                "httpProxy": meta.http_proxy,
+                "httpsProxy": meta.https_proxy,
+                "noProxy": meta.no_proxy,
+            }))
+        }
+        "database.export" => {
+            let p: ExportParams = parse_params(params)?;
+            state.db.export_sql(&p.path)?;
+            Ok(json!({ "path": p.path }))
+        }
+        other => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("Method not found: {other}"),
+        )),
+    }
+}
+
+/// 参数中的供应商 `id` 也可以是别名：按 `app` 解析为供应商 ID
+fn resolve_alias(state: &AppState, mut params: Value) -> Result<Value, RpcError> {
+    let (Some(app), Some(id)) = (
+        params.get("app").and_then(Value::as_str),
+        params.get("id").and_then(Value::as_str),
+    ) else {
+        return Ok(params);
+    };
+    if let Some(resolved) = state
+        .db
+        .resolve_provider_id(&app.trim().to_lowercase(), id)?
+    {
+        params["id"] = json!(resolved);
+    }
+    Ok(params)
+}
+
+/// 默认遮蔽密钥；`reveal` 时原样返回并记录日志
+fn present(app_type: &AppType, providers: Vec<Provider>, reveal: bool) -> Vec<Provider> {
+    if reveal {
+        log::info!(
+            "cc-switch rpc: 按请求返回 {} 个 {} 供应商的明文密钥",
+            providers.len(),
+            app_type.as_str()
+        );
+        return providers;
+    }
+    providers.iter().map(|p| p.redacted(app_type)).collect()
+}
+
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    // 省略 params 等同于空对象，便于参数全部可选的方法
+    let params = if params.is_null() { json!({}) } else { params };
+    serde_json::from_value(params)
+        .map_err(|e| RpcError::new(INVALID_PARAMS, format!("Invalid params: {e}")))
+}
+
+fn parse_app(app: &str) -> Result<AppType, RpcError> {
+    AppType::from_str(app).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+/// 内置应用返回其 [`AppType`]；注册表中的其他应用返回 None
+fn resolve_app(app: &str) -> Result<Option<AppType>, RpcError> {
+    if let Ok(app_type) = AppType::from_str(app) {
+        return Ok(Some(app_type));
+    }
+    RegisteredAppService::definition(app)
+        .map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))?;
+    Ok(None)
+}
+
+fn error_response(id: Value, err: RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": err.code, "message": err.message },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state_with_provider() -> AppState {
+        let db = Arc::new(Database::memory().expect("create memory db"));
+        let provider = Provider::with_id(
+            "p1".to_string(),
+            "Relay".to_string(),
+            json!({ "env": {
+                "ANTHROPIC_BASE_URL": "https://relay.example.com",
+                "ANTHROPIC_AUTH_TOKEN": "sk-relay-12345678"
+            } }),
+            None,
+        );
+        db.save_provider("claude", &provider)
+            .expect("save provider");
+        AppState::new(db)
+    }
+
+    #[test]
+    fn list_and_errors_follow_json_rpc() {
+        let state = state_with_provider();
+
+        let response = handle_message(
+            &state,
+            r#"{"jsonrpc":"2.0","id":1,"method":"providers.list","params":{"app":"claude"}}"#,
+            false,
+        )
+        .expect("response");
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"]["providers"][0]["id"], "p1");
+        assert_eq!(
+            response["result"]["providers"][0]["settingsConfig"]["env"]["ANTHROPIC_AUTH_TOKEN"],
+            "****5678"
+        );
+
+        let response = handle_message(&state, r#"{"jsonrpc":"2.0","id":1,"method":"providers.get","params":{"app":"claude","id":"p1","reveal":true}}"#, false)
+        .expect("response");
+        assert_eq!(
+            response["result"]["settingsConfig"]["env"]["ANTHROPIC_AUTH_TOKEN"],
+            "sk-relay-12345678"
+        );
+
+        let response = handle_message(&state, r#"{"jsonrpc":"2.0","id":2,"method":"nope"}"#, false)
+            .expect("response");
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+
+        let response = handle_message(
+            &state,
+            r#"{"jsonrpc":"2.0","id":3,"method":"providers.get","params":{"app":"claude"}}"#,
+            false,
+        )
+        .expect("response");
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+
+        let response = handle_message(&state, "{not json", false).expect("response");
+        assert_eq!(response["error"]["code"], PARSE_ERROR);
+    }
+
+    #[test]
+    fn provider_ids_accept_aliases() {
+        let state = state_with_provider();
+        state
+            .db
+            .set_provider_alias("claude", "p1", Some("work"))
+            .expect("set alias");
+
+        let response = handle_message(
+            &state,
+            r#"{"jsonrpc":"2.0","id":1,"method":"providers.get","params":{"app":"claude","id":"work"}}"#,
+            false,
+        )
+        .expect("response");
+        assert_eq!(response["result"]["id"], "p1");
+    }
+
+    #[test]
+    fn notifications_and_batches() {
+        let state = state_with_provider();
+
+        assert!(
+            handle_message(&state, r#"{"jsonrpc":"2.0","method":"rpc.methods"}"#, false).is_none()
+        );
+
+        let response = handle_message(&state, r#"[{"jsonrpc":"2.0","id":"a","method":"rpc.methods"},{"jsonrpc":"2.0","method":"rpc.methods"},{"id":"b"}]"#, false)
+        .expect("batch response");
+        let responses = response.as_array().expect("array");
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0]["id"], "a");
+        assert_eq!(responses[1]["error"]["code"], INVALID_REQUEST);
+    }
+
+    #[test]
+    fn read_only_mode_rejects_mutations() {
+        let state = state_with_provider();
+
+        let response = handle_message(
+            &state,
+            r#"{"jsonrpc":"2.0","id":1,"method":"providers.switch","params":{"app":"claude","id":"p1"}}"#,
+            true,
+        )
+        .expect("response");
+        assert_eq!(response["error"]["code"], READ_ONLY);
+
+        let response = handle_message(
+            &state,
+            r#"{"jsonrpc":"2.0","id":2,"method":"providers.current","params":{"app":"claude"}}"#,
+            true,
+        )
+        .expect("response");
+        assert!(response.get("error").is_none());
+
+        assert_eq!(
+            parse_db_arg(&["--db".to_string(), "/tmp/a.db".to_string()]).unwrap(),
+            Some(PathBuf::from("/tmp/a.db"))
+        );
+        assert!(parse_db_arg(&["--db".to_string()]).is_err());
+    }
+}
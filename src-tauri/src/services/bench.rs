@@ -0,0 +1,296 @@
+//! 供应商延迟基准测试
+//!
+//! 向每个供应商发送一个很小的流式补全请求，记录首字节时间（TTFB）与输出速度，
+//! 结果写入 `benchmarks` 表，供列表的 Latency 列与 `switch --fastest` 使用。
+
+use std::cmp::Ordering;
+use std::time::{Duration, Instant};
+
+use futures::StreamExt;
+use reqwest::{Client, RequestBuilder};
+use serde_json::{json, Value};
+
+use crate::app_config::AppType;
+use crate::database::BenchmarkResult;
+use crate::error::AppError;
+use crate::provider::Provider;
+use crate::proxy::providers::{get_adapter, AuthInfo};
+use crate::store::AppState;
+
+/// 基准测试结果的有效期：超过该时间的结果不再用于列表与 `--fastest`
+pub const RECENT_BENCHMARK_MS: i64 = 24 * 60 * 60 * 1000;
+
+const PROMPT: &str = "Count from 1 to 20, separated by spaces.";
+const MAX_TOKENS: u32 = 64;
+const TIMEOUT_SECS: u64 = 30;
+
+/// 基准测试服务
+pub struct BenchService;
+
+impl BenchService {
+    /// 依次测试应用的所有供应商，保存结果并按速度排序返回
+    pub async fn run(
+        state: &AppState,
+        app_type: AppType,
+    ) -> Result<Vec<BenchmarkResult>, AppError> {
+        let providers = state.db.get_all_providers(app_type.as_str())?;
+        let mut results = Vec::with_capacity(providers.len());
+        for provider in providers.values() {
+            results.push(Self::run_one(state, &app_type, provider).await?);
+        }
+        Self::rank(&mut results);
+        Ok(results)
+    }
+
+    /// 测试单个供应商并保存结果；模型取供应商的默认模型，未设置时用流式检查配置中的模型
+    pub async fn run_one(
+        state: &AppState,
+        app_type: &AppType,
+        provider: &Provider,
+    ) -> Result<BenchmarkResult, AppError> {
+        let config = state.db.get_stream_check_config()?;
+        let fallback_model = match app_type {
+            AppType::Claude => config.claude_model,
+            AppType::Codex => config.codex_model,
+            AppType::Gemini => config.gemini_model,
+        };
+        let model = provider
+            .meta
+            .as_ref()
+            .and_then(|meta| meta.default_model.clone())
+            .filter(|m| !m.is_empty())
+            .unwrap_or(fallback_model);
+        let result = Self::bench_provider(app_type, provider, &model).await;
+        if let Err(e) = state.db.record_benchmark(&result) {
+            log::warn!("[Bench] 保存 {} 的结果失败: {e}", provider.id);
+        }
+        Ok(result)
+    }
+
+    /// 测试单个供应商；请求失败时返回 `success = false` 的结果
+    pub async fn bench_provider(
+        app_type: &AppType,
+        provider: &Provider,
+        model: &str,
+    ) -> BenchmarkResult {
+        let mut result = BenchmarkResult {
+            app_type: app_type.as_str().to_string(),
+            provider_id: provider.id.clone(),
+            created_at: chrono::Utc::now().timestamp_millis(),
+            ..Default::default()
+        };
+        match Self::measure(app_type, provider, model).await {
+            Ok((ttfb_ms, total_ms, tokens)) => {
+                result.success = true;
+                result.ttfb_ms = Some(ttfb_ms);
+                result.total_ms = Some(total_ms);
+                result.output_tokens = Some(tokens);
+                result.tokens_per_sec = tokens_per_sec(tokens, ttfb_ms, total_ms);
+            }
+            Err(e) => result.error = Some(e.to_string()),
+        }
+        result
+    }
+
+    /// 成功的结果在前，按 TTFB 升序；TTFB 相同时输出速度快的在前
+    pub fn rank(results: &mut [BenchmarkResult]) {
+        results.sort_by(|a, b| {
+            b.success
+                .cmp(&a.success)
+                .then_with(|| {
+                    a.ttfb_ms
+                        .unwrap_or(u64::MAX)
+                        .cmp(&b.ttfb_ms.unwrap_or(u64::MAX))
+                })
+                .then_with(|| {
+                    b.tokens_per_sec
+                        .unwrap_or_default()
+                        .partial_cmp(&a.tokens_per_sec.unwrap_or_default())
+                        .unwrap_or(Ordering::Equal)
+                })
+        });
+    }
+
+    /// 返回 (TTFB 毫秒, 总耗时毫秒, 输出 token 数)
+    async fn measure(
+        app_type: &AppType,
+        provider: &Provider,
+        model: &str,
+    ) -> Result<(u64, u64, u64), AppError> {
+        let adapter = get_adapter(app_type);
+        let base_url = adapter
+            .extract_base_url(provider)
+            .map_err(|e| AppError::Message(format!("提取 base_url 失败: {e}")))?;
+        let auth = adapter
+            .extract_auth(provider)
+            .ok_or_else(|| AppError::Message("未找到 API Key".to_string()))?;
+        let client = Client::builder()
+            .timeout(Duration::from_secs(TIMEOUT_SECS))
+            .user_agent("cc-switch/1.0")
+            .build()
+            .map_err(|e| AppError::Message(format!("创建客户端失败: {e}")))?;
+
+        let start = Instant::now();
+        let response = request(&client, app_type, &base_url, &auth, model)
+            .send()
+            .await
+            .map_err(|e| AppError::Message(format!("请求失败: {e}")))?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(AppError::Message(format!(
+                "HTTP {}: {text}",
+                status.as_u16()
+            )));
+        }
+
+        let mut ttfb_ms = None;
+        let mut counter = TokenCounter::default();
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|e| AppError::Message(format!("读取流失败: {e}")))?;
+            ttfb_ms.get_or_insert_with(|| start.elapsed().as_millis() as u64);
+            counter.feed(&chunk);
+        }
+        let ttfb_ms = ttfb_ms.ok_or_else(|| AppError::Message("未收到响应数据".to_string()))?;
+        Ok((
+            ttfb_ms,
+            start.elapsed().as_millis() as u64,
+            counter.tokens(),
+        ))
+    }
+}
+
+fn request(
+    client: &Client,
+    app_type: &AppType,
+    base_url: &str,
+    auth: &AuthInfo,
+    model: &str,
+) -> RequestBuilder {
+    let base = base_url.trim_end_matches('/');
+    let versioned = |path: &str| {
+        if base.ends_with("/v1") {
+            format!("{base}{path}")
+        } else {
+            format!("{base}/v1{path}")
+        }
+    };
+    match app_type {
+        AppType::Claude => client
+            .post(versioned("/messages"))
+            .header("x-api-key", &auth.api_key)
+            .header("anthropic-version", "2023-06-01")
+            .json(&json!({
+                "model": model,
+                "max_tokens": MAX_TOKENS,
+                "messages": [{ "role": "user", "content": PROMPT }],
+                "stream": true
+            })),
+        AppType::Codex | AppType::Gemini => client
+            .post(versioned("/chat/completions"))
+            .bearer_auth(&auth.api_key)
+            .json(&json!({
+                "model": model.split(['@', '#']).next().unwrap_or(model),
+                "max_tokens": MAX_TOKENS,
+                "messages": [{ "role": "user", "content": PROMPT }],
+                "stream": true,
+                "stream_options": { "include_usage": true }
+            })),
+    }
+}
+
+/// 首字节之后的输出速度
+fn tokens_per_sec(tokens: u64, ttfb_ms: u64, total_ms: u64) -> Option<f64> {
+    let streaming_ms = total_ms.saturating_sub(ttfb_ms);
+    if tokens == 0 || streaming_ms == 0 {
+        return None;
+    }
+    Some(tokens as f64 * 1000.0 / streaming_ms as f64)
+}
+
+/// 从 SSE 流中统计输出 token 数
+///
+/// 优先使用上游报告的用量（Anthropic `message_delta.usage.output_tokens`、
+/// OpenAI `usage.completion_tokens`），没有时按内容增量事件数估算。
+#[derive(Debug, Default)]
+struct TokenCounter {
+    buffer: String,
+    deltas: u64,
+    reported: Option<u64>,
+}
+
+impl TokenCounter {
+    fn feed(&mut self, chunk: &[u8]) {
+        self.buffer.push_str(&String::from_utf8_lossy(chunk));
+        while let Some(pos) = self.buffer.find('\n') {
+            let line: String = self.buffer.drain(..=pos).collect();
+            if let Some(data) = line.trim().strip_prefix("data:") {
+                if let Ok(event) = serde_json::from_str::<Value>(data.trim()) {
+                    self.event(&event);
+                }
+            }
+        }
+    }
+
+    fn event(&mut self, event: &Value) {
+        if event["type"] == "content_block_delta" {
+            self.deltas += 1;
+        }
+        if let Some(tokens) = event["usage"]["output_tokens"].as_u64() {
+            self.reported = Some(tokens);
+        }
+        if let Some(tokens) = event["usage"]["completion_tokens"].as_u64() {
+            self.reported = Some(tokens);
+        }
+        let content = &event["choices"][0]["delta"]["content"];
+        if content.as_str().is_some_and(|text| !text.is_empty()) {
+            self.deltas += 1;
+        }
+    }
+
+    fn tokens(&self) -> u64 {
+        self.reported.unwrap_or(self.deltas)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_reported_tokens_and_falls_back_to_deltas() {
+        let mut claude = TokenCounter::default();
+        claude.feed(b"event: content_block_delta\ndata: {\"type\":\"content_block_delta\"}\n\n");
+        claude.feed(b"data: {\"type\":\"content_block_delta\"}\n\ndata: {\"type\":\"message_del");
+        assert_eq!(claude.tokens(), 2);
+        claude.feed(b"ta\",\"usage\":{\"output_tokens\":41}}\n\n");
+        assert_eq!(claude.tokens(), 41);
+
+        let mut openai = TokenCounter::default();
+        openai.feed(b"data: {\"choices\":[{\"delta\":{\"content\":\"1 2\"}}]}\n\n");
+        openai.feed(b"data: {\"choices\":[{\"delta\":{\"content\":\"\"}}]}\n\ndata: [DONE]\n\n");
+        assert_eq!(openai.tokens(), 1);
+    }
+
+    #[test]
+    fn ranks_successes_by_ttfb() {
+        let result = |id: &str, success: bool, ttfb: Option<u64>| BenchmarkResult {
+            provider_id: id.to_string(),
+            success,
+            ttfb_ms: ttfb,
+            ..Default::default()
+        };
+        let mut results = vec![
+            result("failed", false, None),
+            result("slow", true, Some(900)),
+            result("fast", true, Some(250)),
+        ];
+        BenchService::rank(&mut results);
+        let ids: Vec<_> = results.iter().map(|r| r.provider_id.as_str()).collect();
+        assert_eq!(ids, vec!["fast", "slow", "failed"]);
+
+        assert_eq!(tokens_per_sec(40, 200, 1200), Some(40.0));
+        assert_eq!(tokens_per_sec(0, 200, 1200), None);
+    }
+}
@@ -0,0 +1,477 @@
+//! 配置包：在设备之间迁移整套配置
+//!
+//! `cc-switch bundle export` 把所选分区写入一个 JSON 文件：各应用的供应商、settings 表、
+//! 供应商标签与 MCP 服务器。`--include/--exclude` 按分区筛选，导入前可用
+//! `cc-switch bundle manifest` 查看文件中包含哪些内容。
+//!
+//! settings 表中只属于本机的状态（代理接管、同步进度）不会导出，也不会被导入覆盖。
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::app_config::{AppType, McpServer};
+use crate::error::AppError;
+use crate::provider::Provider;
+use crate::services::provider::{BundleImport, ExportBundle};
+use crate::services::{McpService, ProviderService};
+use crate::store::AppState;
+
+/// 当前构建写入的配置包格式版本
+pub const CONFIG_BUNDLE_VERSION: u32 = 1;
+
+/// 只属于本机、不随配置包迁移的设置前缀
+const LOCAL_SETTING_PREFIXES: [&str; 2] = ["proxy_takeover_", "sync_"];
+
+const APPS: [AppType; 3] = [AppType::Claude, AppType::Codex, AppType::Gemini];
+
+/// 配置包的分区
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BundleSection {
+    Providers,
+    Settings,
+    Tags,
+    Mcp,
+}
+
+impl BundleSection {
+    pub const ALL: [BundleSection; 4] = [
+        BundleSection::Providers,
+        BundleSection::Settings,
+        BundleSection::Tags,
+        BundleSection::Mcp,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BundleSection::Providers => "providers",
+            BundleSection::Settings => "settings",
+            BundleSection::Tags => "tags",
+            BundleSection::Mcp => "mcp",
+        }
+    }
+
+    /// 解析 `--include/--exclude` 的值（逗号分隔），返回选中的分区；都未指定时为全部分区
+    pub fn select(include: Option<&str>, exclude: Option<&str>) -> Result<Vec<Self>, AppError> {
+        let parse_list = |list: &str| -> Result<Vec<Self>, AppError> {
+            list.split(',')
+                .filter(|item| !item.trim().is_empty())
+                .map(Self::from_str)
+                .collect()
+        };
+        let mut sections = match include {
+            Some(list) => parse_list(list)?,
+            None => Self::ALL.to_vec(),
+        };
+        if let Some(list) = exclude {
+            let excluded = parse_list(list)?;
+            sections.retain(|section| !excluded.contains(section));
+        }
+        sections.sort();
+        sections.dedup();
+        if sections.is_empty() {
+            return Err(AppError::InvalidInput("没有选中任何分区".to_string()));
+        }
+        Ok(sections)
+    }
+}
+
+impl FromStr for BundleSection {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|section| section.as_str() == s.trim())
+            .ok_or_else(|| {
+                AppError::InvalidInput(format!(
+                    "未知的分区: {s}（可选: providers, settings, tags, mcp）"
+                ))
+            })
+    }
+}
+
+/// 配置包文件；未导出的分区为 None
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigBundle {
+    pub version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exported_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_version: Option<String>,
+    /// 应用 -> 供应商
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub providers: Option<BTreeMap<String, Vec<Provider>>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settings: Option<BTreeMap<String, String>>,
+    /// 应用 -> 供应商 ID -> 标签
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<BTreeMap<String, BTreeMap<String, Vec<String>>>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mcp: Option<Vec<McpServer>>,
+}
+
+/// 配置包中一个分区的内容清单
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SectionManifest {
+    pub section: BundleSection,
+    pub count: usize,
+    pub items: Vec<String>,
+}
+
+/// 配置包包含的内容
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleManifest {
+    pub version: u32,
+    pub exported_at: Option<String>,
+    pub tool_version: Option<String>,
+    pub sections: Vec<SectionManifest>,
+}
+
+/// 导入结果
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigBundleImport {
+    pub providers: Vec<BundleImport>,
+    /// 写入的设置数
+    pub settings: usize,
+    /// 新增的标签数
+    pub tags: usize,
+    pub mcp_imported: Vec<String>,
+    /// 已存在而跳过的 MCP 服务器
+    pub mcp_skipped: Vec<String>,
+}
+
+fn is_local_setting(key: &str) -> bool {
+    LOCAL_SETTING_PREFIXES
+        .iter()
+        .any(|prefix| key.starts_with(prefix))
+}
+
+impl ConfigBundle {
+    /// 文件中实际包含的分区
+    pub fn sections(&self) -> Vec<BundleSection> {
+        BundleSection::ALL
+            .into_iter()
+            .filter(|section| match section {
+                BundleSection::Providers => self.providers.is_some(),
+                BundleSection::Settings => self.settings.is_some(),
+                BundleSection::Tags => self.tags.is_some(),
+                BundleSection::Mcp => self.mcp.is_some(),
+            })
+            .collect()
+    }
+
+    pub fn manifest(&self) -> BundleManifest {
+        let sections = self
+            .sections()
+            .into_iter()
+            .map(|section| {
+                let items: Vec<String> = match section {
+                    BundleSection::Providers => self
+                        .providers
+                        .iter()
+                        .flatten()
+                        .flat_map(|(app, providers)| {
+                            providers.iter().map(move |p| format!("{app}/{}", p.id))
+                        })
+                        .collect(),
+                    BundleSection::Settings => self
+                        .settings
+                        .iter()
+                        .flat_map(|s| s.keys().cloned())
+                        .collect(),
+                    BundleSection::Tags => self
+                        .tags
+                        .iter()
+                        .flatten()
+                        .flat_map(|(app, providers)| {
+                            providers
+                                .iter()
+                                .map(move |(id, tags)| format!("{app}/{id}: {}", tags.join(", ")))
+                        })
+                        .collect(),
+                    BundleSection::Mcp => self
+                        .mcp
+                        .iter()
+                        .flatten()
+                        .map(|server| server.id.clone())
+                        .collect(),
+                };
+                SectionManifest {
+                    section,
+                    count: items.len(),
+                    items,
+                }
+            })
+            .collect();
+        BundleManifest {
+            version: self.version,
+            exported_at: self.exported_at.clone(),
+            tool_version: self.tool_version.clone(),
+            sections,
+        }
+    }
+}
+
+/// 配置包导出与导入
+pub struct BundleService;
+
+impl BundleService {
+    /// 导出选中的分区
+    pub fn export(state: &AppState, sections: &[BundleSection]) -> Result<ConfigBundle, AppError> {
+        let mut bundle = ConfigBundle {
+            version: CONFIG_BUNDLE_VERSION,
+            exported_at: Some(chrono::Utc::now().to_rfc3339()),
+            tool_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            providers: None,
+            settings: None,
+            tags: None,
+            mcp: None,
+        };
+
+        if sections.contains(&BundleSection::Providers) || sections.contains(&BundleSection::Tags) {
+            let mut providers = BTreeMap::new();
+            let mut tags = BTreeMap::new();
+            for app_type in APPS {
+                let app_providers: Vec<Provider> = state
+                    .db
+                    .get_all_providers(app_type.as_str())?
+                    .into_values()
+                    .collect();
+                let app_tags: BTreeMap<String, Vec<String>> = app_providers
+                    .iter()
+                    .filter(|p| !p.tags.is_empty())
+                    .map(|p| (p.id.clone(), p.tags.clone()))
+                    .collect();
+                if !app_tags.is_empty() {
+                    tags.insert(app_type.as_str().to_string(), app_tags);
+                }
+                if !app_providers.is_empty() {
+                    providers.insert(app_type.as_str().to_string(), app_providers);
+                }
+            }
+            if sections.contains(&BundleSection::Providers) {
+                bundle.providers = Some(providers);
+            }
+            if sections.contains(&BundleSection::Tags) {
+                bundle.tags = Some(tags);
+            }
+        }
+
+        if sections.contains(&BundleSection::Settings) {
+            let mut settings = state.db.get_all_settings()?;
+            settings.retain(|key, _| !is_local_setting(key));
+            bundle.settings = Some(settings);
+        }
+
+        if sections.contains(&BundleSection::Mcp) {
+            bundle.mcp = Some(McpService::get_all_servers(state)?.into_values().collect());
+        }
+
+        Ok(bundle)
+    }
+
+    /// 解析配置包文件；格式版本高于当前构建时拒绝
+    pub fn parse(value: &Value) -> Result<ConfigBundle, AppError> {
+        let bundle: ConfigBundle = serde_json::from_value(value.clone())
+            .map_err(|e| AppError::InvalidInput(format!("配置包格式无效: {e}")))?;
+        if bundle.version > CONFIG_BUNDLE_VERSION {
+            return Err(AppError::InvalidInput(format!(
+                "配置包格式版本 v{} 高于当前支持的 v{CONFIG_BUNDLE_VERSION}，请升级 cc-switch 后再导入",
+                bundle.version
+            )));
+        }
+        Ok(bundle)
+    }
+
+    /// 导入配置包中选中的分区
+    ///
+    /// 已存在的供应商与 MCP 服务器跳过；设置覆盖同名 key（本机状态除外）；标签只添加到
+    /// 已存在的供应商上。
+    pub fn import(
+        state: &AppState,
+        bundle: ConfigBundle,
+        sections: &[BundleSection],
+    ) -> Result<ConfigBundleImport, AppError> {
+        let mut result = ConfigBundleImport::default();
+
+        if let Some(settings) = bundle
+            .settings
+            .filter(|_| sections.contains(&BundleSection::Settings))
+        {
+            for (key, value) in settings.iter().filter(|(key, _)| !is_local_setting(key)) {
+                state.db.set_setting(key, value)?;
+                result.settings += 1;
+            }
+        }
+
+        if let Some(servers) = bundle
+            .mcp
+            .filter(|_| sections.contains(&BundleSection::Mcp))
+        {
+            let existing = McpService::get_all_servers(state)?;
+            for server in servers {
+                if existing.contains_key(&server.id) {
+                    result.mcp_skipped.push(server.id);
+                    continue;
+                }
+                let id = server.id.clone();
+                McpService::upsert_server(state, server)?;
+                result.mcp_imported.push(id);
+            }
+        }
+
+        if let Some(providers) = bundle
+            .providers
+            .filter(|_| sections.contains(&BundleSection::Providers))
+        {
+            for (app, providers) in providers {
+                let app_type = AppType::from_str(&app)?;
+                // 经由供应商导出文件的 schema 校验，错误信息能指出出错的字段
+                let value = serde_json::to_value(ExportBundle::new(&app_type, providers))
+                    .map_err(|source| AppError::JsonSerialize { source })?;
+                result
+                    .providers
+                    .push(ProviderService::import_bundle(state, &value, None)?);
+            }
+        }
+
+        if let Some(tags) = bundle
+            .tags
+            .filter(|_| sections.contains(&BundleSection::Tags))
+        {
+            for (app, providers) in tags {
+                let app_type = AppType::from_str(&app)?;
+                let mut by_tag: BTreeMap<String, Vec<String>> = BTreeMap::new();
+                for (id, tags) in providers {
+                    for tag in tags {
+                        by_tag.entry(tag).or_default().push(id.clone());
+                    }
+                }
+                for (tag, ids) in by_tag {
+                    result.tags += state
+                        .db
+                        .add_tag_to_providers(app_type.as_str(), &ids, &tag)?;
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::Database;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn state() -> AppState {
+        AppState::new(Arc::new(Database::memory().expect("memory db")))
+    }
+
+    #[test]
+    fn sections_are_selected_by_include_and_exclude() {
+        assert_eq!(
+            BundleSection::select(None, None).unwrap(),
+            BundleSection::ALL.to_vec()
+        );
+        assert_eq!(
+            BundleSection::select(Some("mcp,providers"), None).unwrap(),
+            [BundleSection::Providers, BundleSection::Mcp]
+        );
+        assert_eq!(
+            BundleSection::select(None, Some("settings, tags")).unwrap(),
+            [BundleSection::Providers, BundleSection::Mcp]
+        );
+        assert!(BundleSection::select(Some("profiles"), None).is_err());
+        assert!(BundleSection::select(Some("mcp"), Some("mcp")).is_err());
+    }
+
+    #[test]
+    fn export_skips_local_settings_and_lists_contents_in_the_manifest() {
+        let state = state();
+        let mut provider = Provider::with_id("p1".to_string(), "One".to_string(), json!({}), None);
+        provider.tags = vec!["team".to_string()];
+        state.db.save_provider("claude", &provider).unwrap();
+        state.db.set_setting("stream_check_config", "{}").unwrap();
+        state.db.set_proxy_takeover_enabled("claude", true).unwrap();
+
+        let bundle =
+            BundleService::export(&state, &BundleSection::select(None, Some("mcp")).unwrap())
+                .unwrap();
+        assert!(bundle.mcp.is_none());
+        let settings = bundle.settings.as_ref().unwrap();
+        assert!(settings.contains_key("stream_check_config"));
+        assert!(!settings.contains_key("proxy_takeover_claude"));
+
+        let manifest = bundle.manifest();
+        let sections: Vec<_> = manifest.sections.iter().map(|s| s.section).collect();
+        assert_eq!(
+            sections,
+            [
+                BundleSection::Providers,
+                BundleSection::Settings,
+                BundleSection::Tags
+            ]
+        );
+        assert_eq!(manifest.sections[0].items, ["claude/p1"]);
+        assert_eq!(manifest.sections[2].items, ["claude/p1: team"]);
+    }
+
+    #[test]
+    fn import_applies_only_the_selected_sections() {
+        let source = state();
+        source
+            .db
+            .set_setting("stream_check_config", "{\"x\":1}")
+            .unwrap();
+        let mut provider = Provider::with_id("p1".to_string(), "One".to_string(), json!({}), None);
+        provider.tags = vec!["team".to_string()];
+        source.db.save_provider("claude", &provider).unwrap();
+        let bundle = BundleService::export(&source, &BundleSection::ALL).unwrap();
+        let bundle = BundleService::parse(&serde_json::to_value(&bundle).unwrap()).unwrap();
+
+        let target = state();
+        target
+            .db
+            .save_provider(
+                "claude",
+                &Provider::with_id("p1".to_string(), "One".to_string(), json!({}), None),
+            )
+            .unwrap();
+        let result = BundleService::import(
+            &target,
+            bundle,
+            &[BundleSection::Settings, BundleSection::Tags],
+        )
+        .unwrap();
+
+        assert_eq!(result.settings, 1);
+        assert_eq!(result.tags, 1);
+        assert!(result.providers.is_empty());
+        assert_eq!(
+            target
+                .db
+                .get_setting("stream_check_config")
+                .unwrap()
+                .as_deref(),
+            Some("{\"x\":1}")
+        );
+        assert_eq!(target.db.list_tags("claude").unwrap(), ["team"]);
+    }
+
+    #[test]
+    fn bundles_from_a_newer_format_are_rejected() {
+        let value = json!({ "version": CONFIG_BUNDLE_VERSION + 1 });
+        assert!(BundleService::parse(&value).is_err());
+    }
+}
@@ -0,0 +1,288 @@
+//! 供应商调试服务
+//!
+//! 用于排查中转服务的兼容性问题：使用供应商凭据重放保存的请求，返回原始响应；
+//! 或发送最小请求并检查响应头，识别中转实际经过的 CDN / 网关。
+
+use reqwest::{Client, Method};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::provider::Provider;
+use crate::proxy::providers::get_adapter;
+
+const REPLAY_TIMEOUT_SECS: u64 = 120;
+
+/// 待重放的请求
+///
+/// 请求文件既可以是完整的描述（含 `endpoint`/`method`/`headers`/`body`），
+/// 也可以只是原始请求体（此时使用应用的默认端点）。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayRequest {
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    #[serde(default)]
+    pub method: Option<String>,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    pub body: Value,
+}
+
+impl ReplayRequest {
+    /// 从请求文件内容解析
+    pub fn from_value(value: Value) -> Self {
+        let is_envelope = value
+            .as_object()
+            .is_some_and(|obj| obj.contains_key("body") && !obj.contains_key("model"));
+
+        if is_envelope {
+            if let Ok(request) = serde_json::from_value::<ReplayRequest>(value.clone()) {
+                return request;
+            }
+        }
+
+        Self {
+            endpoint: None,
+            method: None,
+            headers: BTreeMap::new(),
+            body: value,
+        }
+    }
+}
+
+/// 原始响应
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawResponse {
+    pub url: String,
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+    pub latency_ms: u64,
+}
+
+/// 响应头检查结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeaderReport {
+    pub url: String,
+    pub method: String,
+    pub status: u16,
+    pub latency_ms: u64,
+    /// 网关/CDN 相关的关键响应头（server、via、cf-ray 等）
+    pub gateway: Vec<(String, String)>,
+    /// 限流相关响应头（*ratelimit*、retry-after）
+    pub rate_limits: Vec<(String, String)>,
+    /// 全部响应头
+    pub headers: Vec<(String, String)>,
+}
+
+/// 用于识别网关/CDN 的响应头
+const GATEWAY_HEADERS: &[&str] = &[
+    "server",
+    "via",
+    "cf-ray",
+    "cf-cache-status",
+    "x-amz-cf-id",
+    "x-amz-cf-pop",
+    "x-served-by",
+    "x-cache",
+    "x-powered-by",
+    "x-request-id",
+    "request-id",
+    "x-oneapi-request-id",
+    "x-new-api-version",
+];
+
+/// 供应商调试业务
+pub struct DebugService;
+
+impl DebugService {
+    /// 使用供应商的地址和凭据重放请求
+    pub async fn replay(
+        app_type: &AppType,
+        provider: &Provider,
+        request: ReplayRequest,
+    ) -> Result<RawResponse, AppError> {
+        let adapter = get_adapter(app_type);
+
+        let base_url = adapter
+            .extract_base_url(provider)
+            .map_err(|e| AppError::Message(format!("提取 base_url 失败: {e}")))?;
+        let auth = adapter
+            .extract_auth(provider)
+            .ok_or_else(|| AppError::Message("未找到 API Key".to_string()))?;
+
+        let endpoint = match request.endpoint.as_deref() {
+            Some(endpoint) => endpoint.to_string(),
+            None => Self::default_endpoint(app_type)?.to_string(),
+        };
+        let url = adapter.build_url(&base_url, &endpoint);
+
+        let method = match request.method.as_deref() {
+            Some(method) => method
+                .to_ascii_uppercase()
+                .parse::<Method>()
+                .map_err(|_| AppError::InvalidInput(format!("不支持的 HTTP 方法: {method}")))?,
+            None => Method::POST,
+        };
+
+        let client = Self::build_client()?;
+        let mut req = client.request(method.clone(), &url);
+        for (name, value) in &request.headers {
+            // 认证头由供应商凭据决定，忽略请求文件中的值
+            let lower = name.to_ascii_lowercase();
+            if matches!(
+                lower.as_str(),
+                "authorization" | "x-api-key" | "x-goog-api-key" | "host" | "content-length"
+            ) {
+                continue;
+            }
+            req = req.header(name, value);
+        }
+        req = adapter.add_auth_headers(req, &auth);
+        if method != Method::GET && method != Method::HEAD {
+            req = req.json(&request.body);
+        }
+
+        Self::send(req, url).await
+    }
+
+    /// 发送最小请求（HEAD，不支持时回退为 GET）并汇总响应头
+    pub async fn inspect_headers(
+        app_type: &AppType,
+        provider: &Provider,
+    ) -> Result<HeaderReport, AppError> {
+        let adapter = get_adapter(app_type);
+
+        let base_url = adapter
+            .extract_base_url(provider)
+            .map_err(|e| AppError::Message(format!("提取 base_url 失败: {e}")))?;
+        let auth = adapter.extract_auth(provider);
+
+        let endpoint = match app_type {
+            AppType::Gemini => "/v1beta/models",
+            AppType::Claude | AppType::Codex => "/v1/models",
+        };
+        let url = adapter.build_url(&base_url, endpoint);
+
+        let client = Self::build_client()?;
+        let request = |method: Method| {
+            let req = client.request(method, &url);
+            match &auth {
+                Some(auth) => adapter.add_auth_headers(req, auth),
+                None => req,
+            }
+        };
+
+        let mut method = Method::HEAD;
+        let mut raw = Self::send(request(Method::HEAD), url.clone()).await?;
+        // 部分网关不支持 HEAD，回退为 GET
+        if matches!(raw.status, 404 | 405 | 501) {
+            method = Method::GET;
+            raw = Self::send(request(Method::GET), url.clone()).await?;
+        }
+
+        let pick = |filter: &dyn Fn(&str) -> bool| -> Vec<(String, String)> {
+            raw.headers
+                .iter()
+                .filter(|(name, _)| filter(name.as_str()))
+                .cloned()
+                .collect()
+        };
+        let gateway = pick(&|name| GATEWAY_HEADERS.contains(&name));
+        let rate_limits = pick(&|name| name.contains("ratelimit") || name == "retry-after");
+
+        Ok(HeaderReport {
+            url: raw.url.clone(),
+            method: method.to_string(),
+            status: raw.status,
+            latency_ms: raw.latency_ms,
+            gateway,
+            rate_limits,
+            headers: raw.headers.clone(),
+        })
+    }
+
+    /// 应用的默认重放端点
+    fn default_endpoint(app_type: &AppType) -> Result<&'static str, AppError> {
+        match app_type {
+            AppType::Claude => Ok("/v1/messages"),
+            AppType::Codex => Ok("/v1/responses"),
+            AppType::Gemini => Err(AppError::InvalidInput(
+                "Gemini 请求需要在请求文件中指定 endpoint".to_string(),
+            )),
+        }
+    }
+
+    fn build_client() -> Result<Client, AppError> {
+        Client::builder()
+            .timeout(Duration::from_secs(REPLAY_TIMEOUT_SECS))
+            .user_agent("cc-switch/1.0")
+            .build()
+            .map_err(|e| AppError::Message(format!("创建客户端失败: {e}")))
+    }
+
+    async fn send(req: reqwest::RequestBuilder, url: String) -> Result<RawResponse, AppError> {
+        let start = Instant::now();
+        let resp = req
+            .send()
+            .await
+            .map_err(|e| AppError::Message(format!("请求失败: {e}")))?;
+        let latency_ms = start.elapsed().as_millis() as u64;
+
+        let status = resp.status().as_u16();
+        let headers = resp
+            .headers()
+            .iter()
+            .map(|(name, value)| {
+                (
+                    name.to_string(),
+                    String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                )
+            })
+            .collect();
+        let body = resp
+            .text()
+            .await
+            .map_err(|e| AppError::Message(format!("读取响应失败: {e}")))?;
+
+        Ok(RawResponse {
+            url,
+            status,
+            headers,
+            body,
+            latency_ms,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn replay_request_accepts_envelope() {
+        let request = ReplayRequest::from_value(json!({
+            "endpoint": "/v1/messages?beta=true",
+            "headers": { "anthropic-beta": "tools" },
+            "body": { "model": "claude", "messages": [] }
+        }));
+        assert_eq!(request.endpoint.as_deref(), Some("/v1/messages?beta=true"));
+        assert_eq!(request.headers["anthropic-beta"], "tools");
+        assert_eq!(request.body["model"], "claude");
+    }
+
+    #[test]
+    fn replay_request_treats_plain_payload_as_body() {
+        let payload = json!({ "model": "claude", "messages": [], "body": "x" });
+        let request = ReplayRequest::from_value(payload.clone());
+        assert!(request.endpoint.is_none());
+        assert_eq!(request.body, payload);
+    }
+}
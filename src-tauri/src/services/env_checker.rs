@@ -103,7 +103,9 @@ fn check_system_env(keywords: &[&str]) -> Result<Vec<EnvConflict>, String> {
 fn check_shell_configs(keywords: &[&str]) -> Result<Vec<EnvConflict>, String> {
     let mut conflicts = Vec::new();
 
-    let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".to_string());
+    let home = crate::config::home_dir()
+        .map(|home| home.display().to_string())
+        .unwrap_or_else(|| "/tmp".to_string());
     let config_files = vec![
         format!("{}/.bashrc", home),
         format!("{}/.bash_profile", home),
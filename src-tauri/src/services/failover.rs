@@ -0,0 +1,125 @@
+//! 故障转移组服务
+//!
+//! 供应商可以加入一个有序的故障转移组（`failover_group` + `failover_priority`）。
+//! 执行故障转移时，先检查当前供应商的健康状态；若不可用，则按组内顺序
+//! 依次检查后续供应商，切换到第一个健康的供应商（写入 Live 配置）。
+
+use serde::Serialize;
+
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::services::provider::ProviderService;
+use crate::services::stream_check::{StreamCheckResult, StreamCheckService};
+use crate::store::AppState;
+
+/// 单个供应商的检查记录
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailoverCheck {
+    pub provider_id: String,
+    pub provider_name: String,
+    pub result: StreamCheckResult,
+}
+
+/// 故障转移执行结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailoverRunResult {
+    pub group: String,
+    /// 执行前的当前供应商
+    pub previous_provider_id: String,
+    /// 执行后的当前供应商（未切换时与 previous 相同）
+    pub current_provider_id: String,
+    pub switched: bool,
+    pub checks: Vec<FailoverCheck>,
+}
+
+/// 故障转移组业务
+pub struct FailoverService;
+
+impl FailoverService {
+    /// 检查当前供应商，不可用时切换到组内下一个健康的供应商
+    pub async fn run(state: &AppState, app_type: AppType) -> Result<FailoverRunResult, AppError> {
+        let current_id = ProviderService::current(state, app_type.clone())?;
+        if current_id.is_empty() {
+            return Err(AppError::Message("当前没有选中的供应商".to_string()));
+        }
+
+        let group = state
+            .db
+            .get_provider_failover_group(app_type.as_str(), &current_id)?
+            .ok_or_else(|| {
+                AppError::Message(format!("当前供应商 {current_id} 未加入任何故障转移组"))
+            })?;
+
+        let members = state
+            .db
+            .get_failover_group_members(app_type.as_str(), &group)?;
+        let providers = state.db.get_all_providers(app_type.as_str())?;
+        let config = state.db.get_stream_check_config()?;
+
+        // 从当前供应商开始，按组内顺序环形遍历
+        let start = members
+            .iter()
+            .position(|m| m.provider_id == current_id)
+            .unwrap_or(0);
+        let ordered = members[start..].iter().chain(members[..start].iter());
+
+        let mut checks = Vec::new();
+        for member in ordered {
+            let Some(provider) = providers.get(&member.provider_id) else {
+                continue;
+            };
+
+            let result = StreamCheckService::check_with_retry(&app_type, provider, &config).await?;
+            let _ = state.db.save_stream_check_log(
+                &provider.id,
+                &provider.name,
+                app_type.as_str(),
+                &result,
+            );
+
+            let healthy = result.success;
+            checks.push(FailoverCheck {
+                provider_id: provider.id.clone(),
+                provider_name: provider.name.clone(),
+                result,
+            });
+
+            if !healthy {
+                log::warn!(
+                    "[Failover] {} 供应商 {} 检查失败，尝试组 {group} 中的下一个",
+                    app_type.as_str(),
+                    provider.id
+                );
+                continue;
+            }
+
+            let switched = provider.id != current_id;
+            if switched {
+                ProviderService::switch(state, app_type.clone(), &provider.id)?;
+                log::info!(
+                    "[Failover] {} 已从 {current_id} 切换到 {}（组 {group}）",
+                    app_type.as_str(),
+                    provider.id
+                );
+            }
+
+            return Ok(FailoverRunResult {
+                group,
+                previous_provider_id: current_id,
+                current_provider_id: provider.id.clone(),
+                switched,
+                checks,
+            });
+        }
+
+        log::error!(
+            "[Failover] {} 故障转移组 {group} 中没有可用的供应商",
+            app_type.as_str()
+        );
+        Err(AppError::Message(format!(
+            "故障转移组 {group} 中没有可用的供应商"
+        )))
+    }
+}
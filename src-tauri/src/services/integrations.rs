@@ -0,0 +1,472 @@
+//! 编辑器 / 启动器集成文件生成
+//!
+//! 生成调用 `cc-switch rpc` 的现成配置，让编辑器无需自行编写包装脚本即可
+//! 查看和切换供应商：
+//! - VS Code：`.vscode/tasks.json`（命令面板 → Run Task）
+//! - Neovim：Lua 模块，提供 `:CcSwitch` / `:CcSwitchStatus` 命令
+//! - Raycast：Script Commands（列出 / 切换各应用的供应商）
+//!
+//! 另外可为 Claude Code 安装 statusLine 脚本（[`IntegrationService::install_claude_statusline`]），
+//! 在状态栏显示当前的 cc-switch 供应商与模型。
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::error::AppError;
+
+/// 集成目标
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IntegrationTarget {
+    Vscode,
+    Nvim,
+    Raycast,
+}
+
+impl std::str::FromStr for IntegrationTarget {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "vscode" | "code" => Ok(Self::Vscode),
+            "nvim" | "neovim" => Ok(Self::Nvim),
+            "raycast" => Ok(Self::Raycast),
+            other => Err(AppError::localized(
+                "integrations.unknown_target",
+                format!("不支持的集成目标: {other}（可选: vscode, nvim, raycast）"),
+                format!(
+                    "Unsupported integration target: {other} (expected: vscode, nvim, raycast)"
+                ),
+            )),
+        }
+    }
+}
+
+/// 生成的文件（相对路径 + 内容）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeneratedFile {
+    pub path: String,
+    pub content: String,
+}
+
+/// 已安装的 Claude Code statusLine
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatuslineInstall {
+    /// statusLine 脚本
+    pub script: PathBuf,
+    /// 写入了 statusLine 的 Claude settings.json
+    pub settings: PathBuf,
+    /// 被替换的原 statusLine 命令
+    pub replaced: Option<String>,
+}
+
+/// statusLine 脚本文件名（位于 Claude 配置目录）
+const STATUSLINE_SCRIPT: &str = "cc-switch-statusline.sh";
+
+/// 集成文件生成业务
+pub struct IntegrationService;
+
+impl IntegrationService {
+    /// 生成集成文件；`binary` 为 cc-switch 可执行文件路径
+    pub fn generate(target: IntegrationTarget, binary: &Path) -> Vec<GeneratedFile> {
+        let binary = binary.to_string_lossy();
+        match target {
+            IntegrationTarget::Vscode => vec![vscode_tasks(&binary)],
+            IntegrationTarget::Nvim => vec![nvim_module(&binary)],
+            IntegrationTarget::Raycast => RAYCAST_APPS
+                .iter()
+                .flat_map(|(app, title)| {
+                    [
+                        raycast_list_script(&binary, app, title),
+                        raycast_switch_script(&binary, app, title),
+                    ]
+                })
+                .collect(),
+        }
+    }
+
+    /// 将生成的文件写入目录，返回写入的完整路径
+    pub fn write_files(dir: &Path, files: &[GeneratedFile]) -> Result<Vec<PathBuf>, AppError> {
+        files
+            .iter()
+            .map(|file| {
+                let path = dir.join(&file.path);
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
+                }
+                crate::config::atomic_write(&path, file.content.as_bytes())?;
+
+                // 脚本需要可执行权限（Raycast 只加载可执行的 Script Command）
+                #[cfg(unix)]
+                if file.path.ends_with(".sh") {
+                    use std::os::unix::fs::PermissionsExt;
+                    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))
+                        .map_err(|e| AppError::io(&path, e))?;
+                }
+
+                Ok(path)
+            })
+            .collect()
+    }
+
+    /// 安装 Claude Code statusLine：把脚本写入 Claude 配置目录，并在 settings.json 中设置
+    /// `statusLine`。切换供应商时 statusLine 保留在 live 文件中（见
+    /// [`crate::services::provider`] 中的 `CLAUDE_DEVICE_KEYS`）
+    pub fn install_claude_statusline(binary: &Path) -> Result<StatuslineInstall, AppError> {
+        let dir = crate::config::get_claude_config_dir();
+        let script =
+            Self::write_files(&dir, &[claude_statusline(&binary.to_string_lossy())])?.remove(0);
+
+        let settings_path = crate::config::get_claude_settings_path();
+        let mut settings = if settings_path.exists() {
+            crate::config::read_json_file::<serde_json::Value>(&settings_path)?
+        } else {
+            json!({})
+        };
+        let obj = settings.as_object_mut().ok_or_else(|| {
+            AppError::localized(
+                "integrations.claude_settings_invalid",
+                "Claude settings.json 不是 JSON 对象",
+                "Claude settings.json is not a JSON object",
+            )
+        })?;
+        let command = shell_quote(&script.to_string_lossy());
+        let replaced = obj
+            .get("statusLine")
+            .and_then(|line| line.get("command"))
+            .and_then(serde_json::Value::as_str)
+            .filter(|previous| *previous != command)
+            .map(str::to_string);
+        obj.insert(
+            "statusLine".to_string(),
+            json!({ "type": "command", "command": command, "padding": 0 }),
+        );
+        crate::config::write_json_file(&settings_path, &settings)?;
+
+        Ok(StatuslineInstall {
+            script,
+            settings: settings_path,
+            replaced,
+        })
+    }
+
+    /// 当前可执行文件路径（用于生成的脚本）
+    pub fn current_binary() -> PathBuf {
+        std::env::current_exe().unwrap_or_else(|_| PathBuf::from("cc-switch"))
+    }
+}
+
+/// 单引号包裹的 POSIX shell 参数
+pub(crate) fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// 构造一行 JSON-RPC 请求（`${...}` 占位符保持原样，交由编辑器替换）
+fn rpc_line(method: &str, params: serde_json::Value) -> String {
+    json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }).to_string()
+}
+
+fn vscode_tasks(binary: &str) -> GeneratedFile {
+    let rpc = |method: &str, params: serde_json::Value| {
+        format!(
+            "echo {} | {} rpc",
+            shell_quote(&rpc_line(method, params)),
+            shell_quote(binary)
+        )
+    };
+
+    let tasks = json!({
+        "version": "2.0.0",
+        "tasks": [
+            {
+                "label": "cc-switch: list providers",
+                "type": "shell",
+                "command": rpc("providers.list", json!({ "app": "${input:ccSwitchApp}" })),
+                "problemMatcher": [],
+                "presentation": { "reveal": "always", "panel": "shared" }
+            },
+            {
+                "label": "cc-switch: current provider",
+                "type": "shell",
+                "command": rpc("providers.current", json!({ "app": "${input:ccSwitchApp}" })),
+                "problemMatcher": [],
+                "presentation": { "reveal": "always", "panel": "shared" }
+            },
+            {
+                "label": "cc-switch: switch provider",
+                "type": "shell",
+                "command": rpc(
+                    "providers.switch",
+                    json!({ "app": "${input:ccSwitchApp}", "id": "${input:ccSwitchProviderId}" })
+                ),
+                "problemMatcher": [],
+                "presentation": { "reveal": "always", "panel": "shared" }
+            }
+        ],
+        "inputs": [
+            {
+                "id": "ccSwitchApp",
+                "type": "pickString",
+                "description": "cc-switch app",
+                "options": ["claude", "codex", "gemini"],
+                "default": "claude"
+            },
+            {
+                "id": "ccSwitchProviderId",
+                "type": "promptString",
+                "description": "Provider id (see \"cc-switch: list providers\")"
+            }
+        ]
+    });
+
+    GeneratedFile {
+        path: ".vscode/tasks.json".to_string(),
+        content: format!(
+            "{}\n",
+            serde_json::to_string_pretty(&tasks).unwrap_or_default()
+        ),
+    }
+}
+
+fn nvim_module(binary: &str) -> GeneratedFile {
+    // 转义为 Lua 字符串字面量
+    let binary_lua = format!("\"{}\"", binary.replace('\\', "\\\\").replace('"', "\\\""));
+
+    let content = format!(
+        r#"-- cc-switch integration (generated by `cc-switch integrate nvim`)
+--
+-- Usage: require("cc_switch").setup({{ app = "claude" }})
+--   :CcSwitch [app]        pick a provider and switch to it
+--   :CcSwitchStatus [app]  show the current provider
+
+local M = {{}}
+
+M.config = {{
+  binary = {binary_lua},
+  app = "claude",
+}}
+
+local function rpc(method, params)
+  local request = vim.json.encode({{ jsonrpc = "2.0", id = 1, method = method, params = params }})
+  local output = vim.fn.system({{ M.config.binary, "rpc" }}, request .. "\n")
+  if vim.v.shell_error ~= 0 then
+    return nil, output
+  end
+  local ok, response = pcall(vim.json.decode, output)
+  if not ok then
+    return nil, output
+  end
+  if response.error then
+    return nil, response.error.message
+  end
+  return response.result
+end
+
+function M.status(app)
+  app = app or M.config.app
+  local result, err = rpc("providers.current", {{ app = app }})
+  if not result then
+    vim.notify("cc-switch: " .. tostring(err), vim.log.levels.ERROR)
+    return
+  end
+  vim.notify(string.format("cc-switch [%s]: %s", app, result.name ~= vim.NIL and result.name or result.id))
+end
+
+function M.switch(app)
+  app = app or M.config.app
+  local result, err = rpc("providers.list", {{ app = app }})
+  if not result then
+    vim.notify("cc-switch: " .. tostring(err), vim.log.levels.ERROR)
+    return
+  end
+  vim.ui.select(result.providers, {{
+    prompt = "cc-switch (" .. app .. ")",
+    format_item = function(p)
+      return (p.id == result.current and "* " or "  ") .. p.name
+    end,
+  }}, function(choice)
+    if not choice then
+      return
+    end
+    local switched, switch_err = rpc("providers.switch", {{ app = app, id = choice.id }})
+    if not switched then
+      vim.notify("cc-switch: " .. tostring(switch_err), vim.log.levels.ERROR)
+      return
+    end
+    if switched.warning ~= vim.NIL and switched.warning then
+      vim.notify("cc-switch: " .. switched.warning, vim.log.levels.WARN)
+    end
+    vim.notify(string.format("cc-switch [%s]: switched to %s", app, choice.name))
+  end)
+end
+
+function M.setup(opts)
+  M.config = vim.tbl_extend("force", M.config, opts or {{}})
+  vim.api.nvim_create_user_command("CcSwitch", function(cmd)
+    M.switch(cmd.args ~= "" and cmd.args or nil)
+  end, {{ nargs = "?" }})
+  vim.api.nvim_create_user_command("CcSwitchStatus", function(cmd)
+    M.status(cmd.args ~= "" and cmd.args or nil)
+  end, {{ nargs = "?" }})
+end
+
+return M
+"#
+    );
+
+    GeneratedFile {
+        path: "lua/cc_switch.lua".to_string(),
+        content,
+    }
+}
+
+fn claude_statusline(binary: &str) -> GeneratedFile {
+    let binary = shell_quote(binary);
+    let content = format!(
+        r#"#!/bin/sh
+# cc-switch statusline for Claude Code (generated by `cc-switch integrate claude-statusline`)
+#
+# Claude Code passes the session as JSON on stdin; the model it reports is shown
+# next to the current cc-switch provider. The segment is cached by cc-switch, so
+# this does not open the database on every refresh.
+
+input=$(cat)
+model=$(printf '%s' "$input" | sed -n 's/.*"display_name" *: *"\([^"]*\)".*//p' | head -n 1)
+
+exec {binary} prompt-segment --app claude --format starship ${{model:+--model "$model"}}
+"#
+    );
+
+    GeneratedFile {
+        path: STATUSLINE_SCRIPT.to_string(),
+        content,
+    }
+}
+
+/// Raycast 脚本覆盖的应用（id, 显示名）
+const RAYCAST_APPS: &[(&str, &str)] = &[
+    ("claude", "Claude"),
+    ("codex", "Codex"),
+    ("gemini", "Gemini"),
+];
+
+fn raycast_list_script(binary: &str, app: &str, title: &str) -> GeneratedFile {
+    let request = shell_quote(&rpc_line("providers.list", json!({ "app": app })));
+    let binary = shell_quote(binary);
+    let content = format!(
+        r#"#!/bin/bash
+
+# Required parameters:
+# @raycast.schemaVersion 1
+# @raycast.title List {title} Providers
+# @raycast.mode fullOutput
+
+# Optional parameters:
+# @raycast.icon 🔀
+# @raycast.packageName cc-switch
+
+# Generated by `cc-switch integrate raycast`
+
+out=$(echo {request} | {binary} rpc) || exit 1
+
+if command -v jq >/dev/null 2>&1; then
+  echo "$out" | jq -r '
+    if .error then "Error: " + .error.message
+    else .result as $r | $r.providers[]
+      | (if .id == $r.current then "● " else "  " end) + .name + "  (" + .id + ")"
+    end'
+else
+  echo "$out"
+fi
+"#
+    );
+
+    GeneratedFile {
+        path: format!("raycast/cc-switch-list-{app}.sh"),
+        content,
+    }
+}
+
+fn raycast_switch_script(binary: &str, app: &str, title: &str) -> GeneratedFile {
+    let binary = shell_quote(binary);
+    let content = format!(
+        r#"#!/bin/bash
+
+# Required parameters:
+# @raycast.schemaVersion 1
+# @raycast.title Switch {title} Provider
+# @raycast.mode compact
+
+# Optional parameters:
+# @raycast.icon 🔀
+# @raycast.argument1 {{ "type": "text", "placeholder": "provider id" }}
+# @raycast.packageName cc-switch
+
+# Generated by `cc-switch integrate raycast`
+
+id=$(printf '%s' "$1" | sed 's/\/\\/g; s/"/\"/g')
+request="{{"jsonrpc":"2.0","id":1,"method":"providers.switch","params":{{"app":"{app}","id":"$id"}}}}"
+out=$(echo "$request" | {binary} rpc) || exit 1
+
+case "$out" in
+  *'"error"'*) echo "Switch failed: $out"; exit 1 ;;
+  *) echo "{title} → $1" ;;
+esac
+"#
+    );
+
+    GeneratedFile {
+        path: format!("raycast/cc-switch-switch-{app}.sh"),
+        content,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vscode_tasks_invoke_rpc_with_quoted_binary() {
+        let files =
+            IntegrationService::generate(IntegrationTarget::Vscode, Path::new("/opt/cc switch"));
+        let tasks: serde_json::Value =
+            serde_json::from_str(&files[0].content).expect("valid tasks.json");
+        let command = tasks["tasks"][2]["command"].as_str().unwrap();
+        assert!(command.ends_with("| '/opt/cc switch' rpc"), "{command}");
+        assert!(command.contains("${input:ccSwitchProviderId}"));
+    }
+
+    #[test]
+    fn raycast_scripts_cover_each_app() {
+        let files = IntegrationService::generate(IntegrationTarget::Raycast, Path::new("/bin/ccs"));
+        assert_eq!(files.len(), RAYCAST_APPS.len() * 2);
+
+        let switch = files
+            .iter()
+            .find(|f| f.path == "raycast/cc-switch-switch-codex.sh")
+            .expect("codex switch script");
+        assert!(switch.content.starts_with("#!/bin/bash"));
+        assert!(switch.content.contains("@raycast.argument1"));
+        assert!(switch.content.contains(r#"\"app\":\"codex\""#));
+        assert!(switch.content.contains("| '/bin/ccs' rpc"));
+    }
+
+    #[test]
+    fn claude_statusline_passes_model_to_prompt_segment() {
+        let script = claude_statusline("/opt/cc switch");
+        assert_eq!(script.path, STATUSLINE_SCRIPT);
+        assert!(script.content.starts_with("#!/bin/sh"));
+        assert!(script.content.contains(
+            r#"exec '/opt/cc switch' prompt-segment --app claude --format starship ${model:+--model "$model"}"#
+        ));
+    }
+
+    #[test]
+    fn shell_quote_escapes_single_quotes() {
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+    }
+}
@@ -1,24 +1,44 @@
+pub mod bench;
+pub mod bundle;
 pub mod config;
+pub mod debug;
 pub mod env_checker;
 pub mod env_manager;
+pub mod failover;
+pub mod integrations;
 pub mod mcp;
+pub mod onboarding;
 pub mod prompt;
 pub mod provider;
 pub mod proxy;
+pub mod registered_app;
 pub mod skill;
+pub mod snapshot;
 pub mod speedtest;
 pub mod stream_check;
+pub mod sync;
+pub mod temporary_switch;
+pub mod tool_version;
 pub mod usage_stats;
+pub mod vendor;
 
+pub use bench::BenchService;
+pub use bundle::{BundleSection, BundleService};
 pub use config::ConfigService;
 pub use mcp::McpService;
+pub use onboarding::{DetectedConfig, OnboardingService};
 pub use prompt::PromptService;
-pub use provider::{ProviderService, ProviderSortUpdate};
+pub use provider::{ProviderMove, ProviderService, ProviderSortUpdate};
 pub use proxy::ProxyService;
+pub use registered_app::RegisteredAppService;
 pub use skill::{Skill, SkillRepo, SkillService};
-pub use speedtest::{EndpointLatency, SpeedtestService};
+pub use snapshot::{SnapshotInfo, SnapshotService};
+pub use speedtest::{EndpointLatency, EndpointTiming, SpeedtestService};
+pub use sync::SyncService;
+pub use temporary_switch::{TemporarySwitch, TemporarySwitchService};
 #[allow(unused_imports)]
 pub use usage_stats::{
     DailyStats, LogFilters, ModelStats, PaginatedLogs, ProviderLimitStatus, ProviderStats,
     RequestLogDetail, UsageSummary,
 };
+pub use vendor::{VendorRemoval, VendorService};
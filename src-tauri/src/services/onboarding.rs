@@ -0,0 +1,77 @@
+//! 首次运行引导
+//!
+//! 数据库里还没有任何供应商时，检测 Claude Code / Codex / Gemini CLI 现有的 live 配置
+//! （`~/.claude/settings.json`、`~/.codex/auth.json`、`~/.gemini/.env`），
+//! 由用户确认后导入为各应用名为 "default" 的供应商并设为当前供应商。
+//! GUI 启动时会静默完成同样的导入；命令行通过 `cc-switch init` 交互完成。
+
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::app_config::AppType;
+use crate::codex_config::get_codex_auth_path;
+use crate::config::get_claude_settings_path;
+use crate::error::AppError;
+use crate::gemini_config::get_gemini_env_path;
+use crate::services::provider::ProviderService;
+use crate::store::AppState;
+
+/// 一个应用的现有配置
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DetectedConfig {
+    pub app: String,
+    /// 判断配置是否存在所依据的文件
+    pub path: PathBuf,
+    pub found: bool,
+    /// 该应用已有供应商（不会再导入）
+    pub has_providers: bool,
+}
+
+impl DetectedConfig {
+    /// 可以导入：配置存在且应用还没有供应商
+    pub fn importable(&self) -> bool {
+        self.found && !self.has_providers
+    }
+}
+
+/// 首次运行引导
+pub struct OnboardingService;
+
+impl OnboardingService {
+    /// 所有内置应用都还没有供应商
+    pub fn is_first_run(state: &AppState) -> Result<bool, AppError> {
+        for app_type in [AppType::Claude, AppType::Codex, AppType::Gemini] {
+            if !state.db.get_all_providers(app_type.as_str())?.is_empty() {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// 检测各应用的现有配置
+    pub fn detect(state: &AppState) -> Result<Vec<DetectedConfig>, AppError> {
+        [AppType::Claude, AppType::Codex, AppType::Gemini]
+            .into_iter()
+            .map(|app_type| {
+                let path = match app_type {
+                    AppType::Claude => get_claude_settings_path(),
+                    AppType::Codex => get_codex_auth_path(),
+                    AppType::Gemini => get_gemini_env_path(),
+                };
+                Ok(DetectedConfig {
+                    app: app_type.as_str().to_string(),
+                    found: path.exists(),
+                    path,
+                    has_providers: !state.db.get_all_providers(app_type.as_str())?.is_empty(),
+                })
+            })
+            .collect()
+    }
+
+    /// 把应用的现有配置导入为 "default" 供应商并设为当前；已有供应商时返回 false
+    pub fn import(state: &AppState, app_type: AppType) -> Result<bool, AppError> {
+        ProviderService::import_default_config(state, app_type)
+    }
+}
@@ -0,0 +1,103 @@
+//! Adopting live configs changed outside cc-switch
+//!
+//! When another tool (or the user by hand) rewrites the live config with
+//! credentials that match no stored provider, the app silently uses something
+//! cc-switch does not know about while the old provider is still shown as
+//! current. Such configs are detected by their
+//! [connection identity](crate::provider::Provider::connection_identity) and can
+//! be adopted as a new provider, which then becomes current; the live files are
+//! left untouched.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::live::read_live_settings;
+use super::ProviderService;
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::provider::Provider;
+use crate::store::AppState;
+
+/// Adopt unmanaged live configs without asking (`--auto-adopt`)
+static AUTO_ADOPT: AtomicBool = AtomicBool::new(false);
+
+pub fn set_auto_adopt(enabled: bool) {
+    AUTO_ADOPT.store(enabled, Ordering::Relaxed);
+}
+
+pub fn auto_adopt() -> bool {
+    AUTO_ADOPT.load(Ordering::Relaxed)
+}
+
+/// A live config whose credentials match no stored provider
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnmanagedLive {
+    pub app: String,
+    /// Empty when the config has no base URL (the official endpoint)
+    pub base_url: String,
+    pub key_fingerprint: String,
+    #[serde(skip)]
+    settings: Value,
+}
+
+/// The live config of `app_type` if it has an API key that no stored provider uses
+///
+/// Apps without any provider are left to the first-run import, and a missing or
+/// unreadable live config is not an error here.
+pub(crate) fn unmanaged_live(
+    state: &AppState,
+    app_type: &AppType,
+) -> Result<Option<UnmanagedLive>, AppError> {
+    if state.db.get_all_providers(app_type.as_str())?.is_empty() {
+        return Ok(None);
+    }
+    let Ok(settings) = read_live_settings(app_type.clone()) else {
+        return Ok(None);
+    };
+    let probe = Provider::with_id(String::new(), String::new(), settings.clone(), None);
+    let Some(identity) = probe.connection_identity(app_type) else {
+        return Ok(None);
+    };
+    if state
+        .db
+        .find_provider_matching_config(app_type.as_str(), &settings)?
+        .is_some()
+    {
+        return Ok(None);
+    }
+    Ok(Some(UnmanagedLive {
+        app: app_type.as_str().to_string(),
+        base_url: identity.base_url,
+        key_fingerprint: identity.key_fingerprint,
+        settings,
+    }))
+}
+
+/// Save an unmanaged live config as a new provider and make it current
+pub(crate) fn adopt(
+    state: &AppState,
+    app_type: AppType,
+    live: UnmanagedLive,
+) -> Result<Provider, AppError> {
+    let host = url::Url::parse(&live.base_url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string));
+    let mut provider = Provider::with_id(
+        uuid::Uuid::new_v4().to_string(),
+        host.unwrap_or_else(|| format!("{} (live)", app_type.as_str())),
+        live.settings,
+        None,
+    );
+    provider.created_at = Some(chrono::Utc::now().timestamp_millis());
+    provider.notes = Some("从外部修改的 live 配置导入".to_string());
+
+    ProviderService::add(state, app_type.clone(), provider.clone())?;
+    crate::settings::set_current_provider(&app_type, Some(&provider.id))?;
+    state
+        .db
+        .set_current_provider(app_type.as_str(), &provider.id)?;
+    Ok(provider)
+}
@@ -0,0 +1,342 @@
+//! Provider export bundles
+//!
+//! The JSON file written by "export providers" in the GUI and by
+//! `cc-switch provider export` without `--format`: one app and its providers.
+//! The JSON Schema of the bundle (and of a single provider) is generated from
+//! the Rust types with schemars, printed by `cc-switch schema`, and imports are
+//! validated against it so that tools generating bundles get errors that point
+//! at the offending field (`/providers/2/settingsConfig: ...`).
+//!
+//! Bundles carry a format `version` next to the export time and the cc-switch
+//! version that wrote them. Older files are upgraded before validation:
+//! version 1 is the `{app, providers}` object written before the envelope
+//! existed, and version 0 a bare `{id: provider}` map, which names no app and
+//! so needs one from the caller. Bundles from a newer format are rejected
+//! instead of being half-imported.
+
+use std::sync::OnceLock;
+
+use jsonschema::JSONSchema;
+use schemars::{schema_for, JsonSchema};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{disable_untrusted_script, drop_untrusted_hooks, ProviderService};
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::provider::Provider;
+use crate::store::AppState;
+
+/// At most this many schema violations are reported for one import
+const MAX_REPORTED_ERRORS: usize = 20;
+
+/// Format version written by this build
+pub const BUNDLE_VERSION: u32 = 2;
+
+/// Providers of one app, as exported to (and imported from) a JSON file
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+#[schemars(title = "cc-switch provider export bundle")]
+pub struct ExportBundle {
+    /// Bundle format version
+    #[schemars(range(min = 2))]
+    pub version: u32,
+    /// App the providers belong to (`claude`, `codex`, `gemini`, ...)
+    pub app: String,
+    /// When the bundle was written (RFC 3339)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exported_at: Option<String>,
+    /// cc-switch version that wrote the bundle
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_version: Option<String>,
+    pub providers: Vec<Provider>,
+}
+
+impl ExportBundle {
+    /// A bundle in the current format, stamped with the export time and this build's version
+    pub fn new(app_type: &AppType, providers: Vec<Provider>) -> Self {
+        Self {
+            version: BUNDLE_VERSION,
+            app: app_type.as_str().to_string(),
+            exported_at: Some(chrono::Utc::now().to_rfc3339()),
+            tool_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            providers,
+        }
+    }
+}
+
+/// Result of importing a bundle
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleImport {
+    pub app: String,
+    pub imported: Vec<String>,
+    /// Ids that already exist and were left untouched
+    pub skipped: Vec<String>,
+    /// Imported ids whose shell/node/python usage script was disabled
+    pub disabled_scripts: Vec<String>,
+    /// Imported ids whose switch hooks were dropped
+    pub dropped_hooks: Vec<String>,
+}
+
+/// Documents printed by `cc-switch schema <kind>`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaKind {
+    Provider,
+    ExportBundle,
+}
+
+impl SchemaKind {
+    pub const ALL: [SchemaKind; 2] = [SchemaKind::Provider, SchemaKind::ExportBundle];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SchemaKind::Provider => "provider",
+            SchemaKind::ExportBundle => "export-bundle",
+        }
+    }
+
+    pub fn schema(&self) -> Value {
+        let schema = match self {
+            SchemaKind::Provider => schema_for!(Provider),
+            SchemaKind::ExportBundle => schema_for!(ExportBundle),
+        };
+        serde_json::to_value(schema).unwrap_or(Value::Null)
+    }
+}
+
+impl std::str::FromStr for SchemaKind {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == s.trim())
+            .ok_or_else(|| {
+                AppError::InvalidInput(format!(
+                    "未知的 schema: {s}（可选: provider, export-bundle）"
+                ))
+            })
+    }
+}
+
+fn bundle_validator() -> Result<&'static JSONSchema, AppError> {
+    static VALIDATOR: OnceLock<Result<JSONSchema, String>> = OnceLock::new();
+    VALIDATOR
+        .get_or_init(|| {
+            JSONSchema::compile(&SchemaKind::ExportBundle.schema()).map_err(|e| e.to_string())
+        })
+        .as_ref()
+        .map_err(|e| AppError::Message(format!("编译导出文件 schema 失败: {e}")))
+}
+
+/// Bring a bundle written in an older format up to [`BUNDLE_VERSION`]
+///
+/// `app` is used for bare provider maps, which don't record one.
+fn upgrade(value: &Value, app: Option<&AppType>) -> Result<Value, AppError> {
+    let Some(object) = value.as_object() else {
+        return Ok(value.clone());
+    };
+    if let Some(version) = object.get("version") {
+        if let Some(version) = version.as_u64().filter(|v| *v > u64::from(BUNDLE_VERSION)) {
+            return Err(AppError::InvalidInput(format!(
+                "导出文件格式版本 v{version} 高于当前支持的 v{BUNDLE_VERSION}，请升级 cc-switch 后再导入"
+            )));
+        }
+        return Ok(value.clone());
+    }
+
+    let mut upgraded = serde_json::Map::new();
+    upgraded.insert("version".to_string(), Value::from(BUNDLE_VERSION));
+    if object.contains_key("providers") {
+        // v1: {app, providers}
+        for (key, field) in object {
+            upgraded.insert(key.clone(), field.clone());
+        }
+    } else {
+        // v0: {id: provider}
+        let app = app.ok_or_else(|| {
+            AppError::InvalidInput(
+                "导出文件是旧版的供应商映射，没有记录所属应用，请指定应用（--app）".to_string(),
+            )
+        })?;
+        let providers = object
+            .iter()
+            .map(|(id, provider)| {
+                let mut provider = provider.clone();
+                if let Some(fields) = provider.as_object_mut() {
+                    fields
+                        .entry("id")
+                        .or_insert_with(|| Value::String(id.clone()));
+                }
+                provider
+            })
+            .collect();
+        upgraded.insert("app".to_string(), Value::from(app.as_str()));
+        upgraded.insert("providers".to_string(), Value::Array(providers));
+    }
+    Ok(Value::Object(upgraded))
+}
+
+/// Upgrade `value` to the current format, validate it against the bundle
+/// schema, then deserialize it
+pub fn parse(value: &Value, app: Option<&AppType>) -> Result<ExportBundle, AppError> {
+    let value = &upgrade(value, app)?;
+    if let Err(errors) = bundle_validator()?.validate(value) {
+        let mut messages: Vec<String> = errors
+            .map(|error| {
+                let path = error.instance_path.to_string();
+                let path = if path.is_empty() {
+                    "/".to_string()
+                } else {
+                    path
+                };
+                format!("{path}: {error}")
+            })
+            .collect();
+        let total = messages.len();
+        messages.truncate(MAX_REPORTED_ERRORS);
+        if total > MAX_REPORTED_ERRORS {
+            messages.push(format!("... 另有 {} 处错误", total - MAX_REPORTED_ERRORS));
+        }
+        return Err(AppError::InvalidInput(format!(
+            "导出文件不符合 schema（`cc-switch schema export-bundle`）:\n  {}",
+            messages.join("\n  ")
+        )));
+    }
+    serde_json::from_value(value.clone()).map_err(|e| AppError::JsonSerialize { source: e })
+}
+
+pub(crate) fn export(
+    state: &AppState,
+    app_type: AppType,
+    ids: &[String],
+) -> Result<ExportBundle, AppError> {
+    let providers: Vec<Provider> = if ids.is_empty() {
+        state
+            .db
+            .get_all_providers(app_type.as_str())?
+            .into_values()
+            .collect()
+    } else {
+        ProviderService::export_selected(state, app_type.clone(), ids)?
+    };
+    Ok(ExportBundle::new(&app_type, providers))
+}
+
+/// Add the bundle's providers; providers whose id already exists are skipped
+pub(crate) fn import(state: &AppState, bundle: ExportBundle) -> Result<BundleImport, AppError> {
+    let app_type = bundle.app.parse::<AppType>()?;
+    let existing = state.db.get_all_providers(app_type.as_str())?;
+    let mut result = BundleImport {
+        app: app_type.as_str().to_string(),
+        ..Default::default()
+    };
+    for mut provider in bundle.providers {
+        if existing.contains_key(&provider.id) {
+            result.skipped.push(provider.id);
+            continue;
+        }
+        let id = provider.id.clone();
+        if disable_untrusted_script(&mut provider, None) {
+            result.disabled_scripts.push(id.clone());
+        }
+        if drop_untrusted_hooks(&mut provider, None) {
+            result.dropped_hooks.push(id.clone());
+        }
+        ProviderService::add(state, app_type.clone(), provider)?;
+        result.imported.push(id);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parse_reports_schema_violations_with_paths() {
+        let value = json!({
+            "version": BUNDLE_VERSION,
+            "app": "claude",
+            "providers": [
+                { "id": "ok", "name": "Ok", "settingsConfig": {} },
+                { "id": "bad", "settingsConfig": {}, "sortIndex": "first" }
+            ]
+        });
+        let err = parse(&value, None).expect_err("invalid bundle").to_string();
+        assert!(err.contains("/providers/1: "), "{err}");
+        assert!(err.contains("/providers/1/sortIndex: "), "{err}");
+        assert!(!err.contains("/providers/0"), "{err}");
+    }
+
+    #[test]
+    fn exported_providers_round_trip_through_the_schema() {
+        let mut provider = Provider::with_id(
+            "p1".to_string(),
+            "One".to_string(),
+            json!({ "env": { "ANTHROPIC_BASE_URL": "https://example.com" } }),
+            None,
+        );
+        provider.tags = vec!["team".to_string()];
+        let bundle = ExportBundle::new(&AppType::Claude, vec![provider]);
+
+        let value = serde_json::to_value(&bundle).unwrap();
+        assert_eq!(value["version"], BUNDLE_VERSION);
+        assert!(value["exportedAt"].is_string());
+        assert_eq!(value["toolVersion"], env!("CARGO_PKG_VERSION"));
+
+        let parsed = parse(&value, None).expect("valid bundle");
+        assert_eq!(parsed.version, BUNDLE_VERSION);
+        assert_eq!(parsed.tool_version, bundle.tool_version);
+        assert_eq!(parsed.providers[0].id, "p1");
+        assert_eq!(parsed.providers[0].tags, ["team"]);
+    }
+
+    #[test]
+    fn v1_bundles_without_envelope_are_upgraded() {
+        let value = json!({
+            "app": "codex",
+            "providers": [{ "id": "p1", "name": "One", "settingsConfig": {} }]
+        });
+
+        let parsed = parse(&value, Some(&AppType::Claude)).expect("v1 bundle");
+        assert_eq!(parsed.version, BUNDLE_VERSION);
+        assert_eq!(parsed.app, "codex");
+        assert_eq!(parsed.exported_at, None);
+        assert_eq!(parsed.providers[0].id, "p1");
+    }
+
+    #[test]
+    fn v0_provider_maps_need_an_app() {
+        let value = json!({
+            "p1": { "name": "One", "settingsConfig": {} },
+            "p2": { "id": "p2", "name": "Two", "settingsConfig": {} }
+        });
+
+        let err = parse(&value, None).expect_err("no app").to_string();
+        assert!(err.contains("--app"), "{err}");
+
+        let parsed = parse(&value, Some(&AppType::Gemini)).expect("v0 bundle");
+        assert_eq!(parsed.app, "gemini");
+        let ids: Vec<&str> = parsed.providers.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, ["p1", "p2"]);
+
+        // Re-exporting the upgraded providers reads back in the current format
+        let again = ExportBundle::new(&AppType::Gemini, parsed.providers);
+        let reparsed = parse(&serde_json::to_value(&again).unwrap(), None).expect("round trip");
+        assert_eq!(reparsed.providers.len(), 2);
+    }
+
+    #[test]
+    fn bundles_from_a_newer_format_are_rejected() {
+        let value = json!({
+            "version": BUNDLE_VERSION + 1,
+            "app": "claude",
+            "providers": []
+        });
+        let err = parse(&value, None).expect_err("too new").to_string();
+        assert!(err.contains(&format!("v{}", BUNDLE_VERSION + 1)), "{err}");
+    }
+}
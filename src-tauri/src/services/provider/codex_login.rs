@@ -0,0 +1,152 @@
+//! Codex ChatGPT login awareness
+//!
+//! Codex accepts either an `OPENAI_API_KEY` or ChatGPT OAuth `tokens` in
+//! `auth.json`. Switching to an API-key provider replaces the tokens, so before
+//! that happens the existing `auth.json` is saved as a snapshot in the cc-switch
+//! config dir and an "official login" pseudo-provider is created. Switching to
+//! that provider writes the snapshot back instead of its own (empty) `auth`.
+
+use std::path::PathBuf;
+
+use serde_json::{json, Value};
+
+use crate::app_config::AppType;
+use crate::codex_config::get_codex_auth_path;
+use crate::config::{get_app_config_dir, read_json_file, write_json_file};
+use crate::error::AppError;
+use crate::provider::Provider;
+use crate::store::AppState;
+
+/// ID of the pseudo-provider that restores the saved ChatGPT login
+pub const OFFICIAL_LOGIN_ID: &str = "codex-official-login";
+
+const SNAPSHOT_FILE: &str = "codex-oauth-auth.json";
+
+/// How a Codex `auth.json` authenticates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CodexAuthMethod {
+    ApiKey,
+    ChatGpt,
+    Missing,
+}
+
+/// ChatGPT tokens take precedence: Codex prefers them when both are present
+pub(crate) fn detect_auth_method(auth: &Value) -> CodexAuthMethod {
+    let has_tokens = auth
+        .get("tokens")
+        .and_then(Value::as_object)
+        .is_some_and(|tokens| !tokens.is_empty());
+    if has_tokens {
+        return CodexAuthMethod::ChatGpt;
+    }
+    match auth.get("OPENAI_API_KEY").and_then(Value::as_str) {
+        Some(key) if !key.trim().is_empty() => CodexAuthMethod::ApiKey,
+        _ => CodexAuthMethod::Missing,
+    }
+}
+
+fn snapshot_path() -> PathBuf {
+    get_app_config_dir().join(SNAPSHOT_FILE)
+}
+
+fn read_live_auth() -> Value {
+    let path = get_codex_auth_path();
+    if !path.exists() {
+        return Value::Null;
+    }
+    read_json_file(&path).unwrap_or(Value::Null)
+}
+
+/// Whether writing `provider` would replace a ChatGPT login in the live `auth.json`
+pub(crate) fn overwrites_login(provider: &Provider) -> bool {
+    provider.id != OFFICIAL_LOGIN_ID
+        && detect_auth_method(&read_live_auth()) == CodexAuthMethod::ChatGpt
+        && detect_auth_method(&provider.settings_config["auth"]) != CodexAuthMethod::ChatGpt
+}
+
+/// Warning shown before switching to a provider that replaces the ChatGPT login
+pub(crate) fn warning(provider: &Provider) -> Option<String> {
+    overwrites_login(provider).then(|| {
+        format!(
+            "auth.json 中的 ChatGPT 登录凭据将被替换；已保存快照，可切换到 {OFFICIAL_LOGIN_ID} 恢复"
+        )
+    })
+}
+
+/// Save the live ChatGPT login before `provider` overwrites it and make sure the
+/// official-login pseudo-provider exists; returns whether a snapshot was taken
+pub(crate) fn preserve_login(state: &AppState, provider: &Provider) -> Result<bool, AppError> {
+    if !overwrites_login(provider) {
+        return Ok(false);
+    }
+    write_json_file(&snapshot_path(), &read_live_auth())?;
+    log::warn!(
+        "Codex auth.json 中的 ChatGPT 登录将被供应商 {} 覆盖，已保存到 {}",
+        provider.id,
+        snapshot_path().display()
+    );
+
+    let app = AppType::Codex;
+    if state
+        .db
+        .get_provider_by_id(OFFICIAL_LOGIN_ID, app.as_str())?
+        .is_none()
+    {
+        let mut login = Provider::with_id(
+            OFFICIAL_LOGIN_ID.to_string(),
+            "OpenAI 官方登录".to_string(),
+            json!({ "auth": {}, "config": "" }),
+            Some("https://chatgpt.com".to_string()),
+        );
+        login.category = Some("official".to_string());
+        login.created_at = Some(chrono::Utc::now().timestamp_millis());
+        state.db.save_provider(app.as_str(), &login)?;
+    }
+    Ok(true)
+}
+
+/// The saved `auth.json` to write for the official-login provider; None for
+/// every other provider
+pub(crate) fn login_auth(provider: &Provider) -> Result<Option<Value>, AppError> {
+    if provider.id != OFFICIAL_LOGIN_ID {
+        return Ok(None);
+    }
+    let path = snapshot_path();
+    if !path.exists() {
+        return Err(AppError::localized(
+            "codex.login.snapshot_missing",
+            "没有保存的 ChatGPT 登录快照，请先运行 `codex login`",
+            "No saved ChatGPT login snapshot; run `codex login` first",
+        ));
+    }
+    read_json_file(&path).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_auth_method() {
+        assert_eq!(
+            detect_auth_method(&json!({ "OPENAI_API_KEY": "sk-1" })),
+            CodexAuthMethod::ApiKey
+        );
+        assert_eq!(
+            detect_auth_method(&json!({
+                "OPENAI_API_KEY": null,
+                "tokens": { "access_token": "a", "refresh_token": "r" }
+            })),
+            CodexAuthMethod::ChatGpt
+        );
+        assert_eq!(
+            detect_auth_method(&json!({ "OPENAI_API_KEY": "sk-1", "tokens": { "id_token": "i" } })),
+            CodexAuthMethod::ChatGpt
+        );
+        assert_eq!(
+            detect_auth_method(&json!({ "OPENAI_API_KEY": "", "tokens": {} })),
+            CodexAuthMethod::Missing
+        );
+        assert_eq!(detect_auth_method(&Value::Null), CodexAuthMethod::Missing);
+    }
+}
@@ -0,0 +1,169 @@
+//! Provider ↔ app version compatibility
+//!
+//! A provider may declare the app versions it works with in
+//! `meta.appVersionRange` (e.g. `">=0.30"` or `">=1.0.0, <2"`). Relays
+//! sometimes only support newer wire APIs, so switching to a provider while
+//! an out-of-range CLI is installed produces a warning.
+
+use std::cmp::Ordering;
+
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::provider::Provider;
+use crate::services::tool_version::detect_local_version;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+/// A comma-separated list of comparators, all of which must hold
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct VersionRange {
+    comparators: Vec<(Op, Vec<u64>)>,
+}
+
+impl VersionRange {
+    pub(crate) fn parse(spec: &str) -> Result<Self, AppError> {
+        let invalid = || {
+            AppError::localized(
+                "provider.app_version_range.invalid",
+                format!("无效的版本范围: {spec}（示例: >=0.30, <1.0）"),
+                format!("Invalid version range: {spec} (example: >=0.30, <1.0)"),
+            )
+        };
+
+        let mut comparators = Vec::new();
+        for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (op, rest) = [
+                (">=", Op::Ge),
+                ("<=", Op::Le),
+                (">", Op::Gt),
+                ("<", Op::Lt),
+                ("=", Op::Eq),
+            ]
+            .iter()
+            .find_map(|(prefix, op)| part.strip_prefix(prefix).map(|rest| (*op, rest)))
+            .unwrap_or((Op::Eq, part));
+
+            let version = parse_version(rest.trim()).ok_or_else(invalid)?;
+            comparators.push((op, version));
+        }
+
+        if comparators.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self { comparators })
+    }
+
+    pub(crate) fn matches(&self, version: &str) -> bool {
+        let Some(version) = parse_version(version) else {
+            return true;
+        };
+        self.comparators.iter().all(|(op, bound)| {
+            let ord = compare(&version, bound);
+            match op {
+                Op::Eq => ord == Ordering::Equal,
+                Op::Gt => ord == Ordering::Greater,
+                Op::Ge => ord != Ordering::Less,
+                Op::Lt => ord == Ordering::Less,
+                Op::Le => ord != Ordering::Greater,
+            }
+        })
+    }
+}
+
+/// Parse `1.2.3`, `v0.30` or `1.2.3-beta.1` into numeric components
+/// (pre-release and build suffixes are ignored)
+fn parse_version(raw: &str) -> Option<Vec<u64>> {
+    let core = raw
+        .trim()
+        .trim_start_matches('v')
+        .split(['-', '+'])
+        .next()?;
+    if core.is_empty() {
+        return None;
+    }
+    core.split('.').map(|p| p.parse().ok()).collect()
+}
+
+/// Compare versions, treating missing components as 0
+fn compare(a: &[u64], b: &[u64]) -> Ordering {
+    let len = a.len().max(b.len());
+    (0..len)
+        .map(|i| {
+            a.get(i)
+                .copied()
+                .unwrap_or(0)
+                .cmp(&b.get(i).copied().unwrap_or(0))
+        })
+        .find(|ord| *ord != Ordering::Equal)
+        .unwrap_or(Ordering::Equal)
+}
+
+/// Validate the provider's declared range (if any)
+pub(crate) fn validate_app_version_range(provider: &Provider) -> Result<(), AppError> {
+    match provider
+        .meta
+        .as_ref()
+        .and_then(|m| m.app_version_range.as_deref())
+    {
+        Some(spec) if !spec.trim().is_empty() => VersionRange::parse(spec).map(|_| ()),
+        _ => Ok(()),
+    }
+}
+
+/// Compare the installed app version against the provider's declared range
+///
+/// Returns a warning when the installed version is outside the range. Nothing
+/// is reported when no range is declared or the version cannot be detected.
+pub fn app_version_warning(app_type: &AppType, provider: &Provider) -> Option<String> {
+    let spec = provider
+        .meta
+        .as_ref()?
+        .app_version_range
+        .as_deref()
+        .filter(|s| !s.trim().is_empty())?;
+    let range = VersionRange::parse(spec).ok()?;
+
+    let (installed, _) = detect_local_version(app_type.as_str());
+    let installed = installed?;
+    if range.matches(&installed) {
+        return None;
+    }
+
+    Some(format!(
+        "供应商 {} 仅兼容 {} {spec}，当前安装版本为 {installed}",
+        provider.name,
+        app_type.as_str()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_matches_bounds() {
+        let range = VersionRange::parse(">=0.30, <1").unwrap();
+        assert!(range.matches("0.30.0"));
+        assert!(range.matches("0.46.2"));
+        assert!(!range.matches("0.29.9"));
+        assert!(!range.matches("1.0.0-beta.1"));
+
+        let exact = VersionRange::parse("2.0.1").unwrap();
+        assert!(exact.matches("v2.0.1"));
+        assert!(!exact.matches("2.0.10"));
+    }
+
+    #[test]
+    fn invalid_ranges_are_rejected() {
+        assert!(VersionRange::parse("").is_err());
+        assert!(VersionRange::parse(">=abc").is_err());
+        assert!(VersionRange::parse("~1.2").is_err());
+    }
+}
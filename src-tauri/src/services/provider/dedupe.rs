@@ -0,0 +1,101 @@
+//! Provider deduplication
+//!
+//! Repeated imports leave providers with different ids but the same settings.
+//! Providers are grouped by [`Provider::fingerprint`]; each group keeps one
+//! provider (the current one if it is in the group, otherwise the first in list
+//! order) and the others are merged into it: notes are concatenated, tags and
+//! custom endpoints are united, then the duplicates are deleted.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use super::ProviderService;
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::provider::Provider;
+use crate::store::AppState;
+
+/// Providers with identical settings
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateGroup {
+    pub fingerprint: String,
+    /// The provider that is kept
+    pub keep: ProviderLabel,
+    /// Providers merged into `keep`
+    pub duplicates: Vec<ProviderLabel>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderLabel {
+    pub id: String,
+    pub name: String,
+}
+
+impl From<&Provider> for ProviderLabel {
+    fn from(provider: &Provider) -> Self {
+        Self {
+            id: provider.id.clone(),
+            name: provider.name.clone(),
+        }
+    }
+}
+
+/// Groups of duplicate providers, in list order of the kept provider
+pub(crate) fn find(state: &AppState, app_type: &AppType) -> Result<Vec<DuplicateGroup>, AppError> {
+    let current = ProviderService::current(state, app_type.clone())?;
+    let mut order: Vec<String> = Vec::new();
+    let mut groups: HashMap<String, Vec<Provider>> = HashMap::new();
+    for provider in state.db.get_all_providers(app_type.as_str())?.into_values() {
+        let fingerprint = provider.fingerprint();
+        let members = groups.entry(fingerprint.clone()).or_default();
+        if members.is_empty() {
+            order.push(fingerprint);
+        }
+        members.push(provider);
+    }
+
+    Ok(order
+        .into_iter()
+        .filter_map(|fingerprint| {
+            let mut members = groups.remove(&fingerprint)?;
+            if members.len() < 2 {
+                return None;
+            }
+            let keep = members.iter().position(|p| p.id == current).unwrap_or(0);
+            let keep = members.remove(keep);
+            Some(DuplicateGroup {
+                fingerprint,
+                keep: ProviderLabel::from(&keep),
+                duplicates: members.iter().map(ProviderLabel::from).collect(),
+            })
+        })
+        .collect())
+}
+
+/// Merge every duplicate of a group into the kept provider
+pub(crate) fn merge(
+    state: &AppState,
+    app_type: &AppType,
+    group: &DuplicateGroup,
+) -> Result<(), AppError> {
+    let app = app_type.as_str();
+    let mut notes: Vec<String> = Vec::new();
+    for id in std::iter::once(&group.keep.id).chain(group.duplicates.iter().map(|d| &d.id)) {
+        let provider = state
+            .db
+            .get_provider_by_id(id, app)?
+            .ok_or_else(|| AppError::provider_not_found(id.as_str(), app))?;
+        if let Some(note) = provider.notes.map(|n| n.trim().to_string()) {
+            if !note.is_empty() && !notes.contains(&note) {
+                notes.push(note);
+            }
+        }
+    }
+    let notes = (!notes.is_empty()).then(|| notes.join("\n"));
+    let ids: Vec<String> = group.duplicates.iter().map(|d| d.id.clone()).collect();
+    state
+        .db
+        .merge_providers(app, &group.keep.id, &ids, notes.as_deref())
+}
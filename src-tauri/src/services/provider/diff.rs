@@ -0,0 +1,186 @@
+//! Provider diff
+//!
+//! Compares the settings of two providers, or what switching to a provider would
+//! change in the live config. Values are compared structurally with
+//! [`diff_json`]; the Codex `config` string is parsed as TOML first, so a changed
+//! `base_url` shows up as one key rather than as two different documents.
+//! Secrets in the result are masked unless asked otherwise.
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::live::{projected_live_settings, read_live_settings};
+use crate::app_config::AppType;
+use crate::database::{diff_json, JsonChange};
+use crate::error::AppError;
+use crate::provider::{is_secret_env_name, mask_secret};
+use crate::store::AppState;
+
+/// What a provider is compared with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffTarget {
+    /// Another provider of the same app
+    Provider(String),
+    /// The current live config
+    Live,
+}
+
+/// Differences from `left` to `right`; paths are JSON Pointers into the settings
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderDiff {
+    pub app_type: String,
+    pub left: String,
+    pub right: String,
+    pub changes: Vec<JsonChange>,
+}
+
+/// Label of the live config in [`ProviderDiff`]
+pub const LIVE_LABEL: &str = "live";
+
+pub(crate) fn diff(
+    state: &AppState,
+    app_type: AppType,
+    id: &str,
+    target: &DiffTarget,
+    show_secrets: bool,
+) -> Result<ProviderDiff, AppError> {
+    let load = |id: &str| {
+        state
+            .db
+            .get_provider_by_id(id, app_type.as_str())?
+            .ok_or_else(|| AppError::provider_not_found(id, app_type.as_str()))
+    };
+    let provider = load(id)?;
+
+    let (left, right, before, after) = match target {
+        DiffTarget::Provider(other) => {
+            let other_provider = load(other)?;
+            (
+                id.to_string(),
+                other.clone(),
+                provider.settings_config,
+                other_provider.settings_config,
+            )
+        }
+        DiffTarget::Live => {
+            let live = read_live_settings(app_type.clone()).unwrap_or(Value::Null);
+            let projected = projected_live_settings(&app_type, &provider, &live)?;
+            (LIVE_LABEL.to_string(), id.to_string(), live, projected)
+        }
+    };
+
+    let mut changes = diff_json(
+        &structured(&app_type, before),
+        &structured(&app_type, after),
+    );
+    if !show_secrets {
+        changes.iter_mut().for_each(redact_change);
+    }
+    Ok(ProviderDiff {
+        app_type: app_type.as_str().to_string(),
+        left,
+        right,
+        changes,
+    })
+}
+
+/// Parse the Codex config.toml text so it is compared key by key
+fn structured(app_type: &AppType, mut settings: Value) -> Value {
+    if *app_type != AppType::Codex {
+        return settings;
+    }
+    let parsed = settings
+        .get("config")
+        .and_then(Value::as_str)
+        .and_then(|text| text.parse::<toml::Table>().ok())
+        .and_then(|table| serde_json::to_value(table).ok());
+    if let (Some(parsed), Some(object)) = (parsed, settings.as_object_mut()) {
+        object.insert("config".to_string(), parsed);
+    }
+    settings
+}
+
+fn redact_change(change: &mut JsonChange) {
+    let path: Vec<String> = change
+        .path
+        .split('/')
+        .skip(1)
+        .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+        .collect();
+    for value in [change.before.as_mut(), change.after.as_mut()]
+        .into_iter()
+        .flatten()
+    {
+        redact_value(&mut path.clone(), value);
+    }
+}
+
+fn redact_value(path: &mut Vec<String>, value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                path.push(key.clone());
+                redact_value(path, child);
+                path.pop();
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                redact_value(path, item);
+            }
+        }
+        Value::String(text) if !text.is_empty() && is_secret_path(path) => {
+            *text = mask_secret(text);
+        }
+        _ => {}
+    }
+}
+
+/// Codex `auth`, env-style `*_KEY` / `*_TOKEN` / `*_SECRET` names and API key fields
+fn is_secret_path(path: &[String]) -> bool {
+    if path.first().map(String::as_str) == Some("auth") {
+        return true;
+    }
+    path.last().is_some_and(|name| {
+        is_secret_env_name(name)
+            || matches!(
+                name.as_str(),
+                "apiKey" | "api_key" | "experimental_bearer_token"
+            )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn codex_config_is_compared_per_key_and_secrets_are_masked() {
+        let before = json!({
+            "auth": { "OPENAI_API_KEY": "sk-old-0000000001" },
+            "config": "model = \"gpt-5\"\n[model_providers.relay]\nbase_url = \"https://a.example/v1\"\n",
+        });
+        let after = json!({
+            "auth": { "OPENAI_API_KEY": "sk-new-0000000002" },
+            "config": "model = \"gpt-5\"\n[model_providers.relay]\nbase_url = \"https://b.example/v1\"\n",
+        });
+        let mut changes = diff_json(
+            &structured(&AppType::Codex, before),
+            &structured(&AppType::Codex, after),
+        );
+        changes.iter_mut().for_each(redact_change);
+
+        let paths: Vec<&str> = changes.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "/auth/OPENAI_API_KEY",
+                "/config/model_providers/relay/base_url"
+            ]
+        );
+        assert_eq!(changes[0].after, Some(json!("****0002")));
+        assert_eq!(changes[1].after, Some(json!("https://b.example/v1")));
+    }
+}
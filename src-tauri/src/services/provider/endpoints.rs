@@ -1,11 +1,16 @@
 //! Custom endpoints management
 //!
-//! Handles CRUD operations for provider custom endpoints.
+//! Handles CRUD operations for provider custom endpoints, racing them against
+//! each other and selecting the one written to the live config.
 
-use std::time::{SystemTime, UNIX_EPOCH};
+use serde_json::Value;
 
 use crate::app_config::AppType;
 use crate::error::AppError;
+use crate::provider::Provider;
+use crate::proxy::providers::get_adapter;
+use crate::services::proxy::ProxyService;
+use crate::services::speedtest::{EndpointTiming, SpeedtestService};
 use crate::settings::CustomEndpoint;
 use crate::store::AppState;
 
@@ -38,12 +43,13 @@ pub fn add_custom_endpoint(
     provider_id: &str,
     url: String,
 ) -> Result<(), AppError> {
-    let normalized = url.trim().trim_end_matches('/').to_string();
-    if normalized.is_empty() {
+    let normalized = normalize_endpoint_url(&url)?;
+    let existing = get_custom_endpoints(state, app_type.clone(), provider_id)?;
+    if existing.iter().any(|endpoint| endpoint.url == normalized) {
         return Err(AppError::localized(
-            "provider.endpoint.url_required",
-            "URL 不能为空",
-            "URL cannot be empty",
+            "provider.endpoint.exists",
+            format!("端点已存在: {normalized}"),
+            format!("Endpoint already exists: {normalized}"),
         ));
     }
 
@@ -53,6 +59,29 @@ pub fn add_custom_endpoint(
     Ok(())
 }
 
+/// Trim an endpoint URL and check that it is an absolute http(s) URL
+fn normalize_endpoint_url(url: &str) -> Result<String, AppError> {
+    let normalized = url.trim().trim_end_matches('/').to_string();
+    if normalized.is_empty() {
+        return Err(AppError::localized(
+            "provider.endpoint.url_required",
+            "URL 不能为空",
+            "URL cannot be empty",
+        ));
+    }
+    let valid = reqwest::Url::parse(&normalized).is_ok_and(|parsed| {
+        matches!(parsed.scheme(), "http" | "https") && parsed.host_str().is_some()
+    });
+    if !valid {
+        return Err(AppError::localized(
+            "provider.endpoint.url_invalid",
+            format!("URL 无效（需要 http:// 或 https:// 开头的完整地址）: {normalized}"),
+            format!("Invalid URL (expected an absolute http:// or https:// address): {normalized}"),
+        ));
+    }
+    Ok(normalized)
+}
+
 /// Remove a custom endpoint from a provider
 pub fn remove_custom_endpoint(
     state: &AppState,
@@ -64,6 +93,24 @@ pub fn remove_custom_endpoint(
     state
         .db
         .remove_custom_endpoint(app_type.as_str(), provider_id, &normalized)?;
+
+    // A removed endpoint must not stay selected
+    if let Some(mut provider) = state
+        .db
+        .get_provider_by_id(provider_id, app_type.as_str())?
+    {
+        let selected = provider
+            .meta
+            .as_ref()
+            .and_then(|meta| meta.active_endpoint.as_deref())
+            == Some(normalized.as_str());
+        if selected {
+            if let Some(meta) = provider.meta.as_mut() {
+                meta.active_endpoint = None;
+            }
+            super::ProviderService::update(state, app_type, provider)?;
+        }
+    }
     Ok(())
 }
 
@@ -75,24 +122,182 @@ pub fn update_endpoint_last_used(
     url: String,
 ) -> Result<(), AppError> {
     let normalized = url.trim().trim_end_matches('/').to_string();
+    // save_provider does not sync endpoints for existing providers, so the
+    // timestamp is written to the endpoints table directly
+    state
+        .db
+        .mark_endpoint_used(app_type.as_str(), provider_id, &normalized)
+}
+
+/// Race the provider's base URL and all of its custom endpoints
+///
+/// Results are ordered fastest first. Latencies of custom endpoints are saved
+/// so the GUI can show them next to each endpoint.
+pub async fn test_endpoints(
+    state: &AppState,
+    app_type: AppType,
+    provider_id: &str,
+) -> Result<Vec<EndpointTiming>, AppError> {
+    let providers = state.db.get_all_providers(app_type.as_str())?;
+    let provider = providers
+        .get(provider_id)
+        .ok_or_else(|| AppError::provider_not_found(provider_id, app_type.as_str()))?;
 
-    // Get provider, update last_used, save back
-    let mut providers = state.db.get_all_providers(app_type.as_str())?;
-    if let Some(provider) = providers.get_mut(provider_id) {
-        if let Some(meta) = provider.meta.as_mut() {
-            if let Some(endpoint) = meta.custom_endpoints.get_mut(&normalized) {
-                endpoint.last_used = Some(now_millis());
-                state.db.save_provider(app_type.as_str(), provider)?;
+    let custom: Vec<String> = provider
+        .meta
+        .as_ref()
+        .map(|meta| meta.custom_endpoints.keys().cloned().collect())
+        .unwrap_or_default();
+    let mut urls = Vec::with_capacity(custom.len() + 1);
+    if let Ok(base_url) = get_adapter(&app_type).extract_base_url(provider) {
+        urls.push(base_url);
+    }
+    for url in &custom {
+        if !urls.contains(url) {
+            urls.push(url.clone());
+        }
+    }
+    if urls.is_empty() {
+        return Err(AppError::localized(
+            "provider.endpoint.none",
+            "该供应商没有可测试的端点",
+            "The provider has no endpoints to test",
+        ));
+    }
+
+    let results = SpeedtestService::race_endpoints(urls, None).await?;
+    for timing in results.iter().filter(|t| custom.contains(&t.url)) {
+        state.db.record_endpoint_test(
+            app_type.as_str(),
+            provider_id,
+            &timing.url,
+            timing.head_ms,
+        )?;
+    }
+    Ok(results)
+}
+
+/// Select the endpoint written to the live config; `None` restores the base URL
+pub fn set_active_endpoint(
+    state: &AppState,
+    app_type: AppType,
+    provider_id: &str,
+    url: Option<String>,
+) -> Result<Provider, AppError> {
+    let normalized = url
+        .map(|url| url.trim().trim_end_matches('/').to_string())
+        .filter(|url| !url.is_empty());
+    let mut provider = state
+        .db
+        .get_provider_by_id(provider_id, app_type.as_str())?
+        .ok_or_else(|| AppError::provider_not_found(provider_id, app_type.as_str()))?;
+    let base_url = get_adapter(&app_type).extract_base_url(&provider).ok();
+    // Selecting the base URL itself needs no rewrite
+    let normalized = normalized.filter(|url| Some(url) != base_url.as_ref());
+
+    provider
+        .meta
+        .get_or_insert_with(Default::default)
+        .active_endpoint = normalized.clone();
+    super::ProviderService::update(state, app_type.clone(), provider.clone())?;
+    if let Some(url) = normalized {
+        state
+            .db
+            .mark_endpoint_used(app_type.as_str(), provider_id, &url)?;
+    }
+    Ok(provider)
+}
+
+/// Rewrite the base URL of the provider's config to the selected endpoint
+pub(crate) fn apply_active_endpoint(app_type: &AppType, provider: &mut Provider) {
+    let Some(url) = provider
+        .meta
+        .as_ref()
+        .and_then(|meta| meta.active_endpoint.clone())
+        .filter(|url| !url.trim().is_empty())
+    else {
+        return;
+    };
+    let env_key = match app_type {
+        AppType::Claude => "ANTHROPIC_BASE_URL",
+        AppType::Gemini => "GOOGLE_GEMINI_BASE_URL",
+        AppType::Codex => {
+            if let Some(config) = provider
+                .settings_config
+                .get("config")
+                .and_then(Value::as_str)
+            {
+                let updated = ProxyService::update_toml_base_url(config, &url);
+                provider.settings_config["config"] = Value::String(updated);
             }
+            return;
         }
+    };
+    if let Some(env) = provider
+        .settings_config
+        .get_mut("env")
+        .and_then(Value::as_object_mut)
+    {
+        env.insert(env_key.to_string(), Value::String(url));
     }
-    Ok(())
 }
 
-/// Get current timestamp in milliseconds
-fn now_millis() -> i64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_millis() as i64
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn with_endpoint(settings: Value, url: &str) -> Provider {
+        let mut provider = Provider::with_id("p1".into(), "Relay".into(), settings, None);
+        provider.meta = Some(crate::provider::ProviderMeta {
+            active_endpoint: Some(url.to_string()),
+            ..Default::default()
+        });
+        provider
+    }
+
+    #[test]
+    fn endpoint_urls_must_be_absolute_http() {
+        assert_eq!(
+            normalize_endpoint_url(" https://mirror.example.com/v1/ ").unwrap(),
+            "https://mirror.example.com/v1"
+        );
+        assert!(normalize_endpoint_url("http://127.0.0.1:8080").is_ok());
+        assert!(normalize_endpoint_url("").is_err());
+        assert!(normalize_endpoint_url("mirror.example.com").is_err());
+        assert!(normalize_endpoint_url("ftp://mirror.example.com").is_err());
+    }
+
+    #[test]
+    fn rewrites_base_url_where_each_app_reads_it() {
+        let mirror = "https://mirror.example.com";
+
+        let mut claude = with_endpoint(
+            json!({ "env": { "ANTHROPIC_BASE_URL": "https://api.example.com" } }),
+            mirror,
+        );
+        apply_active_endpoint(&AppType::Claude, &mut claude);
+        assert_eq!(claude.settings_config["env"]["ANTHROPIC_BASE_URL"], mirror);
+
+        let mut gemini = with_endpoint(json!({ "env": {} }), mirror);
+        apply_active_endpoint(&AppType::Gemini, &mut gemini);
+        assert_eq!(
+            gemini.settings_config["env"]["GOOGLE_GEMINI_BASE_URL"],
+            mirror
+        );
+
+        let mut codex = with_endpoint(
+            json!({
+                "config": "model_provider = \"relay\"\n\n[model_providers.relay]\nbase_url = \"https://api.example.com/v1\"\n"
+            }),
+            mirror,
+        );
+        apply_active_endpoint(&AppType::Codex, &mut codex);
+        let config = codex.settings_config["config"].as_str().unwrap();
+        assert!(config.contains(&format!("base_url = \"{mirror}\"")));
+
+        let mut untouched = with_endpoint(json!({ "env": {} }), "");
+        apply_active_endpoint(&AppType::Claude, &mut untouched);
+        assert_eq!(untouched.settings_config, json!({ "env": {} }));
+    }
 }
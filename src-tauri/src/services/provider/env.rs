@@ -0,0 +1,219 @@
+//! Per-provider extra environment variables
+//!
+//! Extra variables (timeouts, default headers, proxy settings, ...) live in
+//! `settings_config.env` for every app. Claude and Gemini already read their
+//! provider env from there; the Codex writer puts it into `~/.codex/.env`,
+//! which the Codex CLI loads at startup.
+//!
+//! Proxy settings kept in the provider meta are exported the same way, as
+//! `HTTP_PROXY` / `HTTPS_PROXY` / `NO_PROXY`, when the live config is written.
+
+use std::collections::BTreeMap;
+
+use serde_json::{Map, Value};
+
+use crate::error::AppError;
+use crate::provider::{Provider, ProviderMeta};
+
+/// Proxy settings to change; `Some("")` clears a field, `None` leaves it as is
+#[derive(Debug, Clone, Default)]
+pub struct ProviderProxy {
+    pub http: Option<String>,
+    pub https: Option<String>,
+    pub no_proxy: Option<String>,
+}
+
+impl ProviderProxy {
+    /// Validate and write into the meta
+    pub(crate) fn apply(&self, meta: &mut ProviderMeta) -> Result<(), AppError> {
+        let normalize = |value: &str| Some(value.trim().to_string()).filter(|v| !v.is_empty());
+        for url in [&self.http, &self.https].into_iter().flatten() {
+            if let Some(url) = normalize(url) {
+                validate_proxy_url(&url)?;
+            }
+        }
+        if let Some(http) = &self.http {
+            meta.http_proxy = normalize(http);
+        }
+        if let Some(https) = &self.https {
+            meta.https_proxy = normalize(https);
+        }
+        if let Some(no_proxy) = &self.no_proxy {
+            meta.no_proxy = normalize(no_proxy);
+        }
+        Ok(())
+    }
+}
+
+/// Export the meta proxy settings into `settings_config.env`
+pub(crate) fn apply_proxy_env(provider: &mut Provider) -> Result<(), AppError> {
+    let Some(meta) = provider.meta.as_ref() else {
+        return Ok(());
+    };
+    let set: BTreeMap<String, String> = [
+        ("HTTP_PROXY", &meta.http_proxy),
+        ("HTTPS_PROXY", &meta.https_proxy),
+        ("NO_PROXY", &meta.no_proxy),
+    ]
+    .into_iter()
+    .filter_map(|(key, value)| Some((key.to_string(), value.clone()?)))
+    .collect();
+    if !set.is_empty() {
+        apply_env_changes(provider, &set, &[])?;
+    }
+    Ok(())
+}
+
+fn validate_proxy_url(url: &str) -> Result<(), AppError> {
+    const SCHEMES: &[&str] = &["http", "https", "socks4", "socks5", "socks5h"];
+    let valid = url.split_once("://").is_some_and(|(scheme, rest)| {
+        SCHEMES.contains(&scheme.to_ascii_lowercase().as_str()) && !rest.is_empty()
+    });
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::InvalidInput(format!(
+            "无效的代理地址: {url}（支持 http、https、socks4、socks5、socks5h）"
+        )))
+    }
+}
+
+/// The provider's environment variables (non-string values are skipped)
+pub(crate) fn provider_env(provider: &Provider) -> BTreeMap<String, String> {
+    provider
+        .settings_config
+        .get("env")
+        .and_then(Value::as_object)
+        .map(|env| {
+            env.iter()
+                .filter_map(|(key, value)| Some((key.clone(), value.as_str()?.to_string())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Set and remove variables; returns whether anything changed
+pub(crate) fn apply_env_changes(
+    provider: &mut Provider,
+    set: &BTreeMap<String, String>,
+    unset: &[String],
+) -> Result<bool, AppError> {
+    for key in set.keys().chain(unset) {
+        validate_env_key(key)?;
+    }
+    let obj = provider
+        .settings_config
+        .as_object_mut()
+        .ok_or_else(|| AppError::Config("供应商配置必须是 JSON 对象".to_string()))?;
+    let env = obj
+        .entry("env")
+        .or_insert_with(|| Value::Object(Map::new()))
+        .as_object_mut()
+        .ok_or_else(|| AppError::Config("供应商配置中的 env 必须是对象".to_string()))?;
+
+    let mut changed = false;
+    for key in unset {
+        changed |= env.remove(key).is_some();
+    }
+    for (key, value) in set {
+        let value = Value::String(value.clone());
+        if env.get(key) != Some(&value) {
+            env.insert(key.clone(), value);
+            changed = true;
+        }
+    }
+    Ok(changed)
+}
+
+/// Parse `KEY=VALUE`
+pub fn parse_env_assignment(text: &str) -> Result<(String, String), AppError> {
+    let (key, value) = text
+        .split_once('=')
+        .ok_or_else(|| AppError::InvalidInput(format!("环境变量格式应为 KEY=VALUE: {text}")))?;
+    let key = key.trim().to_string();
+    validate_env_key(&key)?;
+    Ok((key, value.to_string()))
+}
+
+fn validate_env_key(key: &str) -> Result<(), AppError> {
+    let mut chars = key.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::InvalidInput(format!("无效的环境变量名: {key}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn sets_and_unsets_env_in_settings_config() {
+        let mut provider = Provider::with_id(
+            "codex".to_string(),
+            "Codex".to_string(),
+            json!({ "auth": {}, "config": "" }),
+            None,
+        );
+
+        let (key, value) = parse_env_assignment("HTTPS_PROXY=http://127.0.0.1:7890").unwrap();
+        assert_eq!(value, "http://127.0.0.1:7890");
+        let set = BTreeMap::from([
+            (key, value),
+            ("API_TIMEOUT_MS".to_string(), "600000".to_string()),
+        ]);
+        assert!(apply_env_changes(&mut provider, &set, &[]).unwrap());
+        assert!(!apply_env_changes(&mut provider, &set, &[]).unwrap());
+        assert_eq!(provider_env(&provider).len(), 2);
+
+        assert!(apply_env_changes(
+            &mut provider,
+            &BTreeMap::new(),
+            &["API_TIMEOUT_MS".to_string()]
+        )
+        .unwrap());
+        assert_eq!(
+            provider_env(&provider).into_keys().collect::<Vec<_>>(),
+            vec!["HTTPS_PROXY"]
+        );
+
+        assert!(parse_env_assignment("NO_EQUALS").is_err());
+        assert!(parse_env_assignment("1BAD=x").is_err());
+        assert!(
+            apply_env_changes(&mut provider, &BTreeMap::new(), &["BAD-KEY".to_string()]).is_err()
+        );
+    }
+
+    #[test]
+    fn proxy_settings_are_validated_and_exported() {
+        let mut provider = Provider::with_id("p".to_string(), "P".to_string(), json!({}), None);
+        let mut meta = ProviderMeta::default();
+
+        let invalid = ProviderProxy {
+            http: Some("127.0.0.1:7890".to_string()),
+            ..Default::default()
+        };
+        assert!(invalid.apply(&mut meta).is_err());
+
+        ProviderProxy {
+            https: Some("socks5://127.0.0.1:1080".to_string()),
+            no_proxy: Some("localhost,127.0.0.1".to_string()),
+            ..Default::default()
+        }
+        .apply(&mut meta)
+        .unwrap();
+        provider.meta = Some(meta);
+        apply_proxy_env(&mut provider).unwrap();
+
+        let env = provider_env(&provider);
+        assert_eq!(env["HTTPS_PROXY"], "socks5://127.0.0.1:1080");
+        assert_eq!(env["NO_PROXY"], "localhost,127.0.0.1");
+        assert!(!env.contains_key("HTTP_PROXY"));
+    }
+}
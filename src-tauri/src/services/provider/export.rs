@@ -0,0 +1,369 @@
+//! Export providers to other tools' config formats
+//!
+//! Converts stored providers into the config shape of another tool so the same
+//! credentials can be used outside cc-switch:
+//!
+//! - `ccr`: claude-code-router `config.json` (`Providers` + `Router`)
+//! - `opencode`: OpenCode `opencode.json` (`provider` entries)
+//! - `env`: a `.env` snippet; only the first provider is active, the others
+//!   are commented out so they can be swapped in by hand
+//!
+//! [`shell_exports`] renders the same variables as shell statements for
+//! `eval "$(cc-switch env <id>)"`, so a single shell session can use a provider
+//! without touching the live config.
+//!
+//! Unlike snippets, exports contain the API keys.
+
+use std::str::FromStr;
+
+use serde_json::{json, Map, Value};
+
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::provider::Provider;
+use crate::proxy::providers::get_adapter;
+
+use super::env::provider_env;
+use super::snippet::model_of;
+
+/// Target format of an export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Ccr,
+    Opencode,
+    Env,
+}
+
+impl FromStr for ExportFormat {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "ccr" | "claude-code-router" => Ok(ExportFormat::Ccr),
+            "opencode" => Ok(ExportFormat::Opencode),
+            "env" | "dotenv" => Ok(ExportFormat::Env),
+            other => Err(AppError::InvalidInput(format!(
+                "不支持的导出格式: {other}（可选: ccr, opencode, env）"
+            ))),
+        }
+    }
+}
+
+/// Shell syntax for [`shell_exports`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShellKind {
+    /// POSIX `export` (bash, zsh, sh)
+    Bash,
+    Fish,
+    Powershell,
+}
+
+impl FromStr for ShellKind {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "bash" | "zsh" | "sh" | "posix" => Ok(ShellKind::Bash),
+            "fish" => Ok(ShellKind::Fish),
+            "powershell" | "pwsh" | "ps" => Ok(ShellKind::Powershell),
+            other => Err(AppError::InvalidInput(format!(
+                "不支持的 shell: {other}（可选: bash, fish, powershell）"
+            ))),
+        }
+    }
+}
+
+/// Statements that set the provider's variables in the current shell
+///
+/// The provider should already be prepared for the live config (default model,
+/// active endpoint, proxy), so the session sees what a switch would write.
+pub(crate) fn shell_exports(app_type: &AppType, provider: &Provider, shell: ShellKind) -> String {
+    let mut out = format!("# {} ({})\n", provider.name, provider.id);
+    for (key, value) in env_vars(app_type, provider) {
+        let line = match shell {
+            ShellKind::Bash => format!("export {key}='{}'", value.replace('\'', r"'\''")),
+            ShellKind::Fish => format!(
+                "set -gx {key} '{}'",
+                value.replace('\\', r"\\").replace('\'', r"\'")
+            ),
+            ShellKind::Powershell => format!("$env:{key} = '{}'", value.replace('\'', "''")),
+        };
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
+
+/// Render the providers in the given format
+pub(crate) fn render(app_type: &AppType, providers: &[Provider], format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Ccr => pretty(&to_ccr(app_type, providers)),
+        ExportFormat::Opencode => pretty(&to_opencode(app_type, providers)),
+        ExportFormat::Env => to_env(app_type, providers),
+    }
+}
+
+fn pretty(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
+
+/// Connection details shared by all formats
+struct Credentials {
+    base_url: String,
+    api_key: String,
+    model: String,
+}
+
+fn credentials(app_type: &AppType, provider: &Provider) -> Credentials {
+    let adapter = get_adapter(app_type);
+    let base_url = adapter
+        .extract_base_url(provider)
+        .ok()
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| {
+            match app_type {
+                AppType::Claude => "https://api.anthropic.com",
+                AppType::Codex => "https://api.openai.com/v1",
+                AppType::Gemini => "https://generativelanguage.googleapis.com",
+            }
+            .to_string()
+        });
+    let api_key = adapter
+        .extract_auth(provider)
+        .map(|auth| auth.api_key)
+        .unwrap_or_default();
+    let model = model_of(app_type, provider)
+        .or_else(|| provider.meta.as_ref()?.default_model.clone())
+        .unwrap_or_else(|| {
+            match app_type {
+                AppType::Claude => "claude-sonnet-4-5",
+                AppType::Codex => "gpt-5-codex",
+                AppType::Gemini => "gemini-2.5-pro",
+            }
+            .to_string()
+        });
+    Credentials {
+        base_url,
+        api_key,
+        model,
+    }
+}
+
+/// Base URL with exactly one trailing `/v1`
+fn versioned(base_url: &str) -> String {
+    let base = base_url.trim_end_matches('/');
+    if base.ends_with("/v1") {
+        base.to_string()
+    } else {
+        format!("{base}/v1")
+    }
+}
+
+/// claude-code-router: one entry per provider, the first one is the default route
+fn to_ccr(app_type: &AppType, providers: &[Provider]) -> Value {
+    let entries: Vec<Value> = providers
+        .iter()
+        .map(|provider| {
+            let creds = credentials(app_type, provider);
+            let (api_base_url, transformer) = match app_type {
+                AppType::Claude => (
+                    format!("{}/messages", versioned(&creds.base_url)),
+                    Some("Anthropic"),
+                ),
+                AppType::Codex => (
+                    format!("{}/chat/completions", versioned(&creds.base_url)),
+                    None,
+                ),
+                AppType::Gemini => (
+                    format!("{}/v1beta/models/", creds.base_url.trim_end_matches('/')),
+                    Some("gemini"),
+                ),
+            };
+            let mut entry = json!({
+                "name": provider.id,
+                "api_base_url": api_base_url,
+                "api_key": creds.api_key,
+                "models": [creds.model],
+            });
+            if let Some(transformer) = transformer {
+                entry["transformer"] = json!({ "use": [transformer] });
+            }
+            entry
+        })
+        .collect();
+
+    let mut config = json!({ "Providers": entries });
+    if let Some(first) = providers.first() {
+        let model = credentials(app_type, first).model;
+        config["Router"] = json!({ "default": format!("{},{model}", first.id) });
+    }
+    config
+}
+
+/// OpenCode: one `provider` entry per provider, using the matching AI SDK package
+fn to_opencode(app_type: &AppType, providers: &[Provider]) -> Value {
+    let mut entries = Map::new();
+    for provider in providers {
+        let creds = credentials(app_type, provider);
+        let (npm, base_url) = match app_type {
+            AppType::Claude => ("@ai-sdk/anthropic", versioned(&creds.base_url)),
+            AppType::Codex => ("@ai-sdk/openai-compatible", versioned(&creds.base_url)),
+            AppType::Gemini => (
+                "@ai-sdk/google",
+                format!("{}/v1beta", creds.base_url.trim_end_matches('/')),
+            ),
+        };
+        entries.insert(
+            provider.id.clone(),
+            json!({
+                "npm": npm,
+                "name": provider.name,
+                "options": { "baseURL": base_url, "apiKey": creds.api_key },
+                "models": { creds.model.clone(): { "name": creds.model } },
+            }),
+        );
+    }
+    json!({
+        "$schema": "https://opencode.ai/config.json",
+        "provider": entries,
+    })
+}
+
+/// `.env` snippet; providers after the first are commented out
+fn to_env(app_type: &AppType, providers: &[Provider]) -> String {
+    let mut out = String::new();
+    for (index, provider) in providers.iter().enumerate() {
+        if index > 0 {
+            out.push('\n');
+        }
+        out.push_str(&format!("# {} ({})\n", provider.name, provider.id));
+        let prefix = if index == 0 { "" } else { "# " };
+        for (key, value) in env_vars(app_type, provider) {
+            out.push_str(&format!("{prefix}{key}={}\n", dotenv_quote(&value)));
+        }
+    }
+    out
+}
+
+/// Variables each tool reads; Claude and Gemini providers already store them in `env`
+pub(super) fn env_vars(app_type: &AppType, provider: &Provider) -> Vec<(String, String)> {
+    match app_type {
+        AppType::Claude | AppType::Gemini => provider_env(provider).into_iter().collect(),
+        AppType::Codex => {
+            let creds = credentials(app_type, provider);
+            vec![
+                ("OPENAI_BASE_URL".to_string(), creds.base_url),
+                ("OPENAI_API_KEY".to_string(), creds.api_key),
+                ("OPENAI_MODEL".to_string(), creds.model),
+            ]
+        }
+    }
+}
+
+/// Double-quote values that a dotenv parser would otherwise split or expand
+fn dotenv_quote(value: &str) -> String {
+    let plain = value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "-_./:@,+=".contains(c));
+    if plain && !value.is_empty() {
+        value.to_string()
+    } else {
+        format!(
+            "\"{}\"",
+            value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('$', "\\$")
+                .replace('\n', "\\n")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claude(id: &str, url: &str) -> Provider {
+        Provider::with_id(
+            id.to_string(),
+            id.to_uppercase(),
+            json!({ "env": {
+                "ANTHROPIC_BASE_URL": url,
+                "ANTHROPIC_AUTH_TOKEN": format!("sk-{id}"),
+                "ANTHROPIC_MODEL": "glm-4.6"
+            } }),
+            None,
+        )
+    }
+
+    #[test]
+    fn ccr_routes_to_the_first_provider() {
+        let providers = [
+            claude("zhipu", "https://open.bigmodel.cn/api/anthropic"),
+            claude("relay", "https://relay.example.com/v1/"),
+        ];
+        let config = to_ccr(&AppType::Claude, &providers);
+        assert_eq!(config["Router"]["default"], "zhipu,glm-4.6");
+        assert_eq!(
+            config["Providers"][0]["api_base_url"],
+            "https://open.bigmodel.cn/api/anthropic/v1/messages"
+        );
+        assert_eq!(
+            config["Providers"][1]["api_base_url"],
+            "https://relay.example.com/v1/messages"
+        );
+        assert_eq!(config["Providers"][1]["api_key"], "sk-relay");
+        assert_eq!(config["Providers"][0]["transformer"]["use"][0], "Anthropic");
+    }
+
+    #[test]
+    fn opencode_uses_openai_compatible_for_codex() {
+        let codex = Provider::with_id(
+            "relay".to_string(),
+            "Relay".to_string(),
+            json!({
+                "auth": { "OPENAI_API_KEY": "sk-codex" },
+                "config": "model = \"gpt-5\"\nmodel_provider = \"relay\"\n\n[model_providers.relay]\nbase_url = \"https://relay.example.com/v1\"\n"
+            }),
+            None,
+        );
+        let config = to_opencode(&AppType::Codex, &[codex]);
+        let entry = &config["provider"]["relay"];
+        assert_eq!(entry["npm"], "@ai-sdk/openai-compatible");
+        assert_eq!(entry["options"]["baseURL"], "https://relay.example.com/v1");
+        assert_eq!(entry["options"]["apiKey"], "sk-codex");
+        assert!(entry["models"].get("gpt-5").is_some());
+    }
+
+    #[test]
+    fn env_comments_out_all_but_the_first_provider() {
+        let providers = [
+            claude("a", "https://a.example.com"),
+            claude("b", "https://b.example.com"),
+        ];
+        let text = to_env(&AppType::Claude, &providers);
+        assert!(text.contains("\nANTHROPIC_BASE_URL=https://a.example.com\n"));
+        assert!(text.contains("\n# ANTHROPIC_BASE_URL=https://b.example.com\n"));
+        assert_eq!(dotenv_quote("a b$c"), "\"a b\\$c\"");
+        assert_eq!("CCR".parse::<ExportFormat>().unwrap(), ExportFormat::Ccr);
+        assert!("yaml".parse::<ExportFormat>().is_err());
+    }
+
+    #[test]
+    fn shell_exports_quote_for_each_shell() {
+        let mut provider = claude("a", "https://a.example.com");
+        provider.settings_config["env"]["ANTHROPIC_AUTH_TOKEN"] = json!("sk-it's");
+
+        let bash = shell_exports(&AppType::Claude, &provider, ShellKind::Bash);
+        assert!(bash.starts_with("# A (a)\n"));
+        assert!(bash.contains("\nexport ANTHROPIC_BASE_URL='https://a.example.com'\n"));
+        assert!(bash.contains(r"export ANTHROPIC_AUTH_TOKEN='sk-it'\''s'"));
+
+        let fish = shell_exports(&AppType::Claude, &provider, ShellKind::Fish);
+        assert!(fish.contains(r"set -gx ANTHROPIC_AUTH_TOKEN 'sk-it\'s'"));
+
+        let pwsh = shell_exports(&AppType::Claude, &provider, ShellKind::Powershell);
+        assert!(pwsh.contains("$env:ANTHROPIC_AUTH_TOKEN = 'sk-it''s'"));
+        assert_eq!("zsh".parse::<ShellKind>().unwrap(), ShellKind::Bash);
+    }
+}
@@ -1,9 +1,10 @@
 //! Gemini authentication type detection
 //!
-//! Detects whether a Gemini provider uses PackyCode API Key, Google OAuth, or generic API Key.
+//! Detects whether a Gemini provider uses PackyCode API Key, Google OAuth, Vertex AI,
+//! or generic API Key. An explicit `meta.geminiAuthMode` always wins over detection.
 
 use crate::error::AppError;
-use crate::provider::Provider;
+use crate::provider::{GeminiAuthMode, Provider};
 
 /// Gemini authentication type enumeration
 ///
@@ -16,6 +17,8 @@ pub(crate) enum GeminiAuthType {
     GoogleOfficial,
     /// Generic Gemini provider (uses API Key)
     Generic,
+    /// Vertex AI (Google Cloud project + location)
+    Vertex,
 }
 
 // Partner Promotion Key constants
@@ -33,8 +36,27 @@ const PACKYCODE_KEYWORDS: [&str; 3] = ["packycode", "packyapi", "packy"];
 ///
 /// - `GeminiAuthType::GoogleOfficial`: Google official, uses OAuth
 /// - `GeminiAuthType::Packycode`: PackyCode provider, uses API Key
+/// - `GeminiAuthType::Vertex`: Vertex AI, uses Google Cloud project/location
 /// - `GeminiAuthType::Generic`: Other generic providers, uses API Key
 pub(crate) fn detect_gemini_auth_type(provider: &Provider) -> GeminiAuthType {
+    // Priority 0: Explicit auth mode selected by the user
+    match provider
+        .meta
+        .as_ref()
+        .and_then(|meta| meta.gemini_auth_mode)
+    {
+        Some(GeminiAuthMode::Oauth) => return GeminiAuthType::GoogleOfficial,
+        Some(GeminiAuthMode::Vertex) => return GeminiAuthType::Vertex,
+        Some(GeminiAuthMode::ApiKey) => {
+            return if looks_like_packycode(provider) {
+                GeminiAuthType::Packycode
+            } else {
+                GeminiAuthType::Generic
+            };
+        }
+        None => {}
+    }
+
     // Priority 1: Check partner_promotion_key (most reliable)
     if let Some(key) = provider
         .meta
@@ -56,29 +78,27 @@ pub(crate) fn detect_gemini_auth_type(provider: &Provider) -> GeminiAuthType {
     }
 
     // Priority 3: Check PackyCode keywords
-    if contains_packycode_keyword(&provider.name) {
+    if looks_like_packycode(provider) {
         return GeminiAuthType::Packycode;
     }
 
-    if let Some(site) = provider.website_url.as_deref() {
-        if contains_packycode_keyword(site) {
-            return GeminiAuthType::Packycode;
-        }
-    }
-
-    if let Some(base_url) = provider
-        .settings_config
-        .pointer("/env/GOOGLE_GEMINI_BASE_URL")
-        .and_then(|v| v.as_str())
-    {
-        if contains_packycode_keyword(base_url) {
-            return GeminiAuthType::Packycode;
-        }
-    }
-
     GeminiAuthType::Generic
 }
 
+/// Check provider name, website and base URL for PackyCode keywords
+fn looks_like_packycode(provider: &Provider) -> bool {
+    contains_packycode_keyword(&provider.name)
+        || provider
+            .website_url
+            .as_deref()
+            .is_some_and(contains_packycode_keyword)
+        || provider
+            .settings_config
+            .pointer("/env/GOOGLE_GEMINI_BASE_URL")
+            .and_then(|v| v.as_str())
+            .is_some_and(contains_packycode_keyword)
+}
+
 /// Check if string contains PackyCode related keywords (case-insensitive)
 ///
 /// Keyword list: ["packycode", "packyapi", "packy"]
@@ -140,3 +160,36 @@ pub(crate) fn ensure_google_oauth_security_flag(provider: &Provider) -> Result<(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::ProviderMeta;
+    use serde_json::json;
+
+    #[test]
+    fn explicit_auth_mode_overrides_detection() {
+        let mut provider = Provider::with_id(
+            "google".to_string(),
+            "Google".to_string(),
+            json!({ "env": {} }),
+            None,
+        );
+        assert_eq!(
+            detect_gemini_auth_type(&provider),
+            GeminiAuthType::GoogleOfficial
+        );
+
+        provider.meta = Some(ProviderMeta {
+            gemini_auth_mode: Some(GeminiAuthMode::Vertex),
+            ..Default::default()
+        });
+        assert_eq!(detect_gemini_auth_type(&provider), GeminiAuthType::Vertex);
+
+        provider.meta = Some(ProviderMeta {
+            gemini_auth_mode: Some(GeminiAuthMode::ApiKey),
+            ..Default::default()
+        });
+        assert_eq!(detect_gemini_auth_type(&provider), GeminiAuthType::Generic);
+    }
+}
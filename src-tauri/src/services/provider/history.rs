@@ -0,0 +1,126 @@
+//! Switch history
+//!
+//! Every successful switch is recorded together with the directory it was
+//! requested from. Reading the history back turns those rows into periods
+//! ("provider X was active from A until B"), so spend can be attributed to the
+//! repository that was being worked on.
+
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::database::SwitchHistoryEntry;
+use crate::provider::Provider;
+use crate::store::AppState;
+
+/// A provider that was active for a period of time
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SwitchRecord {
+    pub app: String,
+    pub provider_id: String,
+    pub provider_name: String,
+    /// Directory the switch was requested from (None for GUI switches)
+    pub cwd: Option<String>,
+    /// Unix milliseconds
+    pub switched_at: i64,
+    /// When the next switch of the same app happened; None while still active
+    pub active_until: Option<i64>,
+}
+
+/// Record a switch; failures are logged and never fail the switch itself
+pub(crate) fn record(state: &AppState, app: &str, provider: &Provider, cwd: Option<&str>) {
+    let cwd = cwd.map(str::trim).filter(|dir| !dir.is_empty());
+    if let Err(e) = state
+        .db
+        .record_switch(app, &provider.id, &provider.name, cwd)
+    {
+        log::warn!("记录切换历史失败: {e}");
+    }
+}
+
+/// Turn chronological rows into newest-first periods
+///
+/// A period ends at the next switch of the same app regardless of where that
+/// switch came from, because the live config is shared by every project.
+/// `cwd` keeps only switches made from that directory or a subdirectory.
+pub(crate) fn build(
+    entries: Vec<SwitchHistoryEntry>,
+    cwd: Option<&str>,
+    limit: usize,
+) -> Vec<SwitchRecord> {
+    let mut records: Vec<SwitchRecord> = Vec::with_capacity(entries.len());
+    for entry in entries {
+        if let Some(previous) = records
+            .iter_mut()
+            .rev()
+            .find(|record| record.app == entry.app_type)
+        {
+            if previous.active_until.is_none() {
+                previous.active_until = Some(entry.switched_at);
+            }
+        }
+        records.push(SwitchRecord {
+            app: entry.app_type,
+            provider_id: entry.provider_id,
+            provider_name: entry.provider_name,
+            cwd: entry.cwd,
+            switched_at: entry.switched_at,
+            active_until: None,
+        });
+    }
+
+    records
+        .into_iter()
+        .rev()
+        .filter(|record| match cwd {
+            Some(filter) => record
+                .cwd
+                .as_deref()
+                .is_some_and(|dir| Path::new(dir).starts_with(filter)),
+            None => true,
+        })
+        .take(limit)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(app: &str, id: &str, cwd: Option<&str>, at: i64) -> SwitchHistoryEntry {
+        SwitchHistoryEntry {
+            id: at,
+            app_type: app.to_string(),
+            provider_id: id.to_string(),
+            provider_name: id.to_uppercase(),
+            cwd: cwd.map(str::to_string),
+            switched_at: at,
+        }
+    }
+
+    #[test]
+    fn periods_end_at_next_switch_and_filter_by_cwd() {
+        let entries = vec![
+            entry("claude", "a", Some("/work/api"), 100),
+            entry("codex", "x", Some("/work/api"), 150),
+            entry("claude", "b", None, 200),
+            entry("claude", "c", Some("/work/api/server"), 300),
+            entry("claude", "d", Some("/work/api-old"), 400),
+        ];
+
+        let all = build(entries.clone(), None, 10);
+        assert_eq!(all.len(), 5);
+        assert_eq!(all[0].provider_id, "d");
+        assert_eq!(all[0].active_until, None);
+
+        let api = build(entries.clone(), Some("/work/api"), 10);
+        let ids: Vec<_> = api.iter().map(|r| r.provider_id.as_str()).collect();
+        assert_eq!(ids, vec!["c", "x", "a"]);
+        assert_eq!(api[0].active_until, Some(400));
+        assert_eq!(api[1].active_until, None);
+        assert_eq!(api[2].active_until, Some(200));
+
+        assert_eq!(build(entries, Some("/work/api"), 1).len(), 1);
+    }
+}
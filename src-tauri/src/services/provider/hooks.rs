@@ -0,0 +1,177 @@
+//! Switch hooks
+//!
+//! Shell commands and webhooks run before and after the active provider of an
+//! app changes, e.g. to refresh a tmux status line or tell a team channel.
+//! Global hooks come from the settings, provider hooks from the meta of the
+//! provider being switched to; global hooks run first.
+//!
+//! A pre-switch command that exits non-zero cancels the switch. Every other
+//! failure (webhooks, post-switch commands, timeouts) is only logged, so a
+//! hook can never leave the live config half switched. Commands and webhooks
+//! share their implementation (and timeout) with [`crate::notifications`].
+
+use serde::Serialize;
+
+use crate::error::AppError;
+use crate::notifications;
+use crate::provider::Provider;
+use crate::settings::{SwitchHook, SwitchHooks};
+
+/// Which side of the switch a hook runs on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HookStage {
+    PreSwitch,
+    PostSwitch,
+}
+
+impl HookStage {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HookStage::PreSwitch => "pre-switch",
+            HookStage::PostSwitch => "post-switch",
+        }
+    }
+}
+
+/// Payload posted to webhooks; commands get the same fields as `CC_SWITCH_*` variables
+#[derive(Debug, Clone, Serialize)]
+pub struct SwitchEvent {
+    pub event: HookStage,
+    pub app: String,
+    /// Provider id active before the switch
+    pub from: Option<String>,
+    pub to: String,
+    #[serde(rename = "toName")]
+    pub to_name: String,
+    /// Unix milliseconds
+    pub timestamp: i64,
+}
+
+impl SwitchEvent {
+    pub(crate) fn new(stage: HookStage, app: &str, from: Option<&str>, to: &Provider) -> Self {
+        Self {
+            event: stage,
+            app: app.to_string(),
+            from: from.map(str::to_string),
+            to: to.id.clone(),
+            to_name: to.name.clone(),
+            timestamp: chrono::Utc::now().timestamp_millis(),
+        }
+    }
+}
+
+/// Run the hooks of one stage for a switch to `event.to`
+///
+/// Returns an error only when a pre-switch command fails.
+pub(crate) fn run(target: &Provider, event: &SwitchEvent) -> Result<(), AppError> {
+    let global = crate::settings::get_settings().hooks.unwrap_or_default();
+    let own = target
+        .meta
+        .as_ref()
+        .and_then(|meta| meta.hooks.clone())
+        .unwrap_or_default();
+    let hooks = |hooks: &SwitchHooks| match event.event {
+        HookStage::PreSwitch => hooks.pre_switch.clone(),
+        HookStage::PostSwitch => hooks.post_switch.clone(),
+    };
+
+    for hook in hooks(&global).into_iter().chain(hooks(&own)) {
+        let result = match &hook {
+            SwitchHook::Command { command } => run_command(command, event),
+            SwitchHook::Webhook { url } => notifications::post_json(url, event),
+        };
+        match result {
+            Ok(()) => {}
+            Err(e)
+                if event.event == HookStage::PreSwitch
+                    && matches!(hook, SwitchHook::Command { .. }) =>
+            {
+                return Err(AppError::localized(
+                    "provider.hook.pre_switch_failed",
+                    format!("pre-switch 钩子失败，已取消切换: {e}"),
+                    format!("Pre-switch hook failed, switch cancelled: {e}"),
+                ));
+            }
+            Err(e) => log::warn!("[Hook] {} 钩子失败: {e}", event.event.as_str()),
+        }
+    }
+    Ok(())
+}
+
+/// Drop the switch hooks of a provider from an import file or a sync remote
+///
+/// Like external usage scripts, hooks run commands on this machine at the next
+/// switch, so they are only kept when identical to those of `trusted` (the
+/// local copy of the provider). Returns whether hooks were dropped.
+pub(crate) fn drop_untrusted_hooks(provider: &mut Provider, trusted: Option<&Provider>) -> bool {
+    let Some(meta) = provider.meta.as_mut() else {
+        return false;
+    };
+    let Some(hooks) = meta.hooks.as_ref().filter(|hooks| !hooks.is_empty()) else {
+        return false;
+    };
+    let known = trusted.and_then(|local| local.meta.as_ref()?.hooks.as_ref()) == Some(hooks);
+    if known {
+        return false;
+    }
+    meta.hooks = None;
+    log::warn!("已移除供应商 {} 导入的切换钩子", provider.id);
+    true
+}
+
+fn run_command(command: &str, event: &SwitchEvent) -> Result<(), AppError> {
+    let payload = serde_json::to_string(event).unwrap_or_default();
+    notifications::run_command(
+        command,
+        &[
+            ("CC_SWITCH_EVENT", event.event.as_str().to_string()),
+            ("CC_SWITCH_APP", event.app.clone()),
+            ("CC_SWITCH_FROM", event.from.clone().unwrap_or_default()),
+            ("CC_SWITCH_TO", event.to.clone()),
+            ("CC_SWITCH_TO_NAME", event.to_name.clone()),
+            ("CC_SWITCH_TIMESTAMP", event.timestamp.to_string()),
+            ("CC_SWITCH_PAYLOAD", payload),
+        ],
+    )
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::provider::ProviderMeta;
+    use serde_json::json;
+
+    fn provider_with(hooks: SwitchHooks) -> Provider {
+        let mut provider = Provider::with_id("p".into(), "P".into(), json!({}), None);
+        provider.meta = Some(ProviderMeta {
+            hooks: Some(hooks),
+            ..Default::default()
+        });
+        provider
+    }
+
+    #[test]
+    fn only_failing_pre_switch_commands_cancel() {
+        let command = |command: &str| SwitchHook::Command {
+            command: command.to_string(),
+        };
+        let provider = provider_with(SwitchHooks {
+            pre_switch: vec![command(
+                r#"test "$CC_SWITCH_TO" = p && test "$CC_SWITCH_FROM" = old"#,
+            )],
+            post_switch: vec![command("exit 3")],
+        });
+        let pre = SwitchEvent::new(HookStage::PreSwitch, "claude", Some("old"), &provider);
+        let post = SwitchEvent::new(HookStage::PostSwitch, "claude", Some("old"), &provider);
+        assert!(run(&provider, &pre).is_ok());
+        assert!(run(&provider, &post).is_ok());
+
+        let failing = provider_with(SwitchHooks {
+            pre_switch: vec![command("echo nope >&2; exit 3")],
+            post_switch: Vec::new(),
+        });
+        let err = run(&failing, &pre).expect_err("pre-switch failure cancels");
+        assert!(err.to_string().contains("nope"));
+    }
+}
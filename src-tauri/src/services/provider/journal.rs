@@ -0,0 +1,365 @@
+//! Provider change journal
+//!
+//! The database records a JSON diff for every change of a provider (see
+//! [`Database::get_provider_history`](crate::database::Database::get_provider_history)).
+//! Diffs keep the real values so that old revisions can be reconstructed; the
+//! copies handed out here have their secrets masked like
+//! [`Provider::redacted`](crate::provider::Provider::redacted).
+//!
+//! Replaying the diffs from an empty object gives the provider at any revision,
+//! which is how a single provider is restored to an earlier point in time.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use super::ProviderService;
+use crate::app_config::AppType;
+use crate::database::{apply_changes, JsonChange, ProviderHistoryEntry};
+use crate::error::AppError;
+use crate::provider::{is_secret_env_name, mask_secret, mask_toml_secrets};
+use crate::store::AppState;
+
+/// Revision numbers below this are revisions, larger numbers are Unix timestamps
+const MIN_TIMESTAMP: i64 = 1_000_000_000;
+
+/// Point in the history of a provider
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestoreTarget {
+    Revision(i64),
+    /// Unix milliseconds; the last revision at or before this time
+    Time(i64),
+}
+
+impl FromStr for RestoreTarget {
+    type Err = AppError;
+
+    /// `12` / `r12`, a Unix timestamp in seconds or milliseconds, RFC 3339, or
+    /// a local `YYYY-MM-DD[ HH:MM[:SS]]`
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        let number = value.strip_prefix('r').unwrap_or(value);
+        if let Ok(number) = number.parse::<i64>() {
+            return Ok(match number {
+                n if n < MIN_TIMESTAMP => RestoreTarget::Revision(n),
+                n if n < MIN_TIMESTAMP * 1000 => RestoreTarget::Time(n * 1000),
+                n => RestoreTarget::Time(n),
+            });
+        }
+        if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+            return Ok(RestoreTarget::Time(time.timestamp_millis()));
+        }
+        let local = [
+            "%Y-%m-%d %H:%M:%S",
+            "%Y-%m-%d %H:%M",
+            "%Y-%m-%dT%H:%M:%S",
+            "%Y-%m-%dT%H:%M",
+        ]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+        .and_then(|time| Local.from_local_datetime(&time).earliest());
+        match local {
+            Some(time) => Ok(RestoreTarget::Time(time.timestamp_millis())),
+            None => Err(AppError::localized(
+                "provider.restore.invalid_target",
+                format!("无法识别的修订号或时间: {value}"),
+                format!("Not a revision or time: {value}"),
+            )),
+        }
+    }
+}
+
+/// Result of [`ProviderService::restore_revision`]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreResult {
+    pub provider_id: String,
+    /// Revision whose settings were restored
+    pub restored_revision: i64,
+    /// New revision created by the restore; None when nothing changed
+    pub revision: Option<i64>,
+}
+
+/// Change history of a provider, oldest first, with secrets masked
+///
+/// Also works for deleted providers as long as their history is still there.
+/// Save the settings of the provider as they were at `target`
+///
+/// Only `settingsConfig` is restored; the restore itself becomes a new revision.
+pub(crate) fn restore(
+    state: &AppState,
+    app_type: AppType,
+    id: &str,
+    target: RestoreTarget,
+) -> Result<RestoreResult, AppError> {
+    let mut provider = state
+        .db
+        .get_provider_by_id(id, app_type.as_str())?
+        .ok_or_else(|| AppError::provider_not_found(id, app_type.as_str()))?;
+    let entries = state.db.get_provider_history(app_type.as_str(), id)?;
+    let (restored_revision, snapshot) = state_at(&entries, target)
+        .filter(|(_, snapshot)| snapshot.get("settingsConfig").is_some())
+        .ok_or_else(|| {
+            AppError::localized(
+                "provider.restore.no_revision",
+                format!("{id} 在指定位置没有可恢复的修订"),
+                format!("{id} has no revision to restore at that point"),
+            )
+        })?;
+
+    let settings = snapshot["settingsConfig"].clone();
+    if provider.settings_config == settings {
+        return Ok(RestoreResult {
+            provider_id: id.to_string(),
+            restored_revision,
+            revision: None,
+        });
+    }
+    provider.settings_config = settings;
+    ProviderService::update(state, app_type.clone(), provider)?;
+
+    let revision = state
+        .db
+        .get_provider_history(app_type.as_str(), id)?
+        .last()
+        .map(|entry| entry.revision)
+        .filter(|revision| *revision > restored_revision);
+    Ok(RestoreResult {
+        provider_id: id.to_string(),
+        restored_revision,
+        revision,
+    })
+}
+
+/// Replay the history up to `target`; returns the revision reached and the state
+pub(crate) fn state_at(
+    entries: &[ProviderHistoryEntry],
+    target: RestoreTarget,
+) -> Option<(i64, Value)> {
+    let mut state = json!({});
+    let mut reached = None;
+    for entry in entries {
+        let past = match target {
+            RestoreTarget::Revision(revision) => entry.revision > revision,
+            RestoreTarget::Time(time) => entry.changed_at > time,
+        };
+        if past {
+            break;
+        }
+        apply_changes(&mut state, &entry.changes);
+        reached = Some(entry.revision);
+    }
+    match target {
+        RestoreTarget::Revision(revision) if reached != Some(revision) => None,
+        _ => reached.map(|revision| (revision, state)),
+    }
+}
+
+pub(crate) fn history(
+    state: &AppState,
+    app_type: &AppType,
+    id: &str,
+) -> Result<Vec<ProviderHistoryEntry>, AppError> {
+    let mut entries = state.db.get_provider_history(app_type.as_str(), id)?;
+    if entries.is_empty()
+        && state
+            .db
+            .get_provider_by_id(id, app_type.as_str())?
+            .is_none()
+    {
+        return Err(AppError::provider_not_found(id, app_type.as_str()));
+    }
+    for change in entries
+        .iter_mut()
+        .flat_map(|entry| entry.changes.iter_mut())
+    {
+        redact_change(change);
+    }
+    Ok(entries)
+}
+
+fn redact_change(change: &mut JsonChange) {
+    let path: Vec<String> = change
+        .path
+        .split('/')
+        .skip(1)
+        .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+        .collect();
+    for value in [change.before.as_mut(), change.after.as_mut()]
+        .into_iter()
+        .flatten()
+    {
+        redact_value(&mut path.clone(), value);
+    }
+}
+
+fn redact_value(path: &mut Vec<String>, value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                path.push(key.clone());
+                redact_value(path, child);
+                path.pop();
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                redact_value(path, item);
+            }
+        }
+        Value::String(text) => {
+            if *path == ["settingsConfig", "config"] {
+                *text = mask_toml_secrets(text);
+            } else if is_secret_path(path) && !text.is_empty() {
+                *text = mask_secret(text);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Same fields as [`crate::provider::Provider::map_secrets`], for every app
+fn is_secret_path(path: &[String]) -> bool {
+    let segment = |index: usize| path.get(index).map(String::as_str);
+    match (segment(0), segment(1)) {
+        (Some("settingsConfig"), Some("auth")) => true,
+        (Some("settingsConfig"), Some("env")) => path.len() == 3 && is_secret_env_name(&path[2]),
+        (Some("settingsConfig"), Some("apiKey" | "api_key")) => path.len() == 2,
+        (Some("meta"), Some("usageScript")) => {
+            path.len() == 3 && matches!(path[2].as_str(), "apiKey" | "accessToken")
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entry(revision: i64, changed_at: i64, changes: Vec<JsonChange>) -> ProviderHistoryEntry {
+        ProviderHistoryEntry {
+            id: revision,
+            app_type: "claude".to_string(),
+            provider_id: "p1".to_string(),
+            revision,
+            changed_at,
+            source: "cli".to_string(),
+            changes,
+        }
+    }
+
+    fn set(path: &str, before: Option<Value>, after: Value) -> JsonChange {
+        JsonChange {
+            path: path.to_string(),
+            before,
+            after: Some(after),
+        }
+    }
+
+    #[test]
+    fn parses_revisions_and_times() {
+        assert_eq!(
+            "3".parse::<RestoreTarget>().unwrap(),
+            RestoreTarget::Revision(3)
+        );
+        assert_eq!(
+            "r12".parse::<RestoreTarget>().unwrap(),
+            RestoreTarget::Revision(12)
+        );
+        assert_eq!(
+            "1760000000".parse::<RestoreTarget>().unwrap(),
+            RestoreTarget::Time(1_760_000_000_000)
+        );
+        assert_eq!(
+            "1760000000123".parse::<RestoreTarget>().unwrap(),
+            RestoreTarget::Time(1_760_000_000_123)
+        );
+        assert_eq!(
+            "2025-10-09T08:53:20Z".parse::<RestoreTarget>().unwrap(),
+            RestoreTarget::Time(1_760_000_000_000)
+        );
+        assert!(matches!(
+            "2025-10-09 08:00".parse::<RestoreTarget>().unwrap(),
+            RestoreTarget::Time(_)
+        ));
+        assert!("yesterday".parse::<RestoreTarget>().is_err());
+    }
+
+    #[test]
+    fn replays_history_up_to_a_revision_or_time() {
+        let entries = vec![
+            entry(
+                1,
+                1_000,
+                vec![set(
+                    "",
+                    None,
+                    json!({ "name": "A", "settingsConfig": { "env": { "ANTHROPIC_BASE_URL": "https://a" } } }),
+                )],
+            ),
+            entry(
+                2,
+                2_000,
+                vec![set(
+                    "/settingsConfig/env/ANTHROPIC_BASE_URL",
+                    Some(json!("https://a")),
+                    json!("https://b"),
+                )],
+            ),
+            entry(3, 3_000, vec![set("/name", Some(json!("A")), json!("B"))]),
+        ];
+        let url = |target| {
+            let (revision, state) = state_at(&entries, target).unwrap();
+            (
+                revision,
+                state["settingsConfig"]["env"]["ANTHROPIC_BASE_URL"].clone(),
+            )
+        };
+
+        assert_eq!(url(RestoreTarget::Revision(1)), (1, json!("https://a")));
+        assert_eq!(url(RestoreTarget::Revision(3)), (3, json!("https://b")));
+        assert_eq!(url(RestoreTarget::Time(2_500)), (2, json!("https://b")));
+        assert!(state_at(&entries, RestoreTarget::Revision(7)).is_none());
+        assert!(state_at(&entries, RestoreTarget::Time(500)).is_none());
+    }
+
+    #[test]
+    fn masks_secrets_in_changes_and_nested_values() {
+        let mut token = JsonChange {
+            path: "/settingsConfig/env/ANTHROPIC_AUTH_TOKEN".to_string(),
+            before: Some(json!("sk-old-0123456789")),
+            after: Some(json!("sk-new-0123456789")),
+        };
+        redact_change(&mut token);
+        assert_eq!(token.before, Some(json!("****6789")));
+        assert_eq!(token.after, Some(json!("****6789")));
+
+        let mut created = JsonChange {
+            path: "/settingsConfig".to_string(),
+            before: None,
+            after: Some(json!({
+                "env": {
+                    "ANTHROPIC_BASE_URL": "https://relay.example.com",
+                    "ANTHROPIC_AUTH_TOKEN": "sk-abcdefghijkl"
+                },
+                "config": "model = \"gpt-5\"\napi_key = \"sk-abcdefghijkl\"\n"
+            })),
+        };
+        redact_change(&mut created);
+        let after = created.after.unwrap();
+        assert_eq!(
+            after["env"]["ANTHROPIC_BASE_URL"],
+            "https://relay.example.com"
+        );
+        assert_eq!(after["env"]["ANTHROPIC_AUTH_TOKEN"], "****ijkl");
+        assert!(!after["config"].as_str().unwrap().contains("sk-abc"));
+    }
+}
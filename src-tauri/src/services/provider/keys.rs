@@ -0,0 +1,183 @@
+//! Per-provider API key pools
+//!
+//! Some vendors hand out several keys, each with its own rate limit. The keys
+//! of a provider live in the `provider_keys` table; when the pool is not empty
+//! one of them replaces the key in `settings_config`, chosen by the provider's
+//! [`KeyStrategy`]. Depending on [`KeyRotation`] the choice is made each time
+//! the provider is switched to (and written to the live config), or by the
+//! proxy for every request.
+
+use super::ProviderService;
+use crate::app_config::AppType;
+use crate::database::{Database, ProviderKey};
+use crate::error::AppError;
+use crate::provider::{KeyRotation, KeyStrategy, Provider};
+use crate::proxy::providers::get_adapter;
+use crate::store::AppState;
+
+/// Pick the next key of a pool
+pub(crate) fn pick(strategy: KeyStrategy, keys: &[ProviderKey]) -> Option<&ProviderKey> {
+    match strategy {
+        // The key after the one used last, in the order they were added
+        KeyStrategy::RoundRobin => {
+            let last = keys
+                .iter()
+                .enumerate()
+                .filter_map(|(index, key)| Some((key.last_used?, index)))
+                .max()
+                .map(|(_, index)| index);
+            keys.get(last.map_or(0, |index| (index + 1) % keys.len()))
+        }
+        KeyStrategy::LeastRecentlyUsed => keys.iter().min_by_key(|key| (key.last_used, key.id)),
+        // Keep each key's share of uses close to its share of the total weight
+        KeyStrategy::Weighted => keys.iter().min_by(|a, b| {
+            let load = |key: &ProviderKey| (key.use_count + 1) as f64 / f64::from(key.weight);
+            load(a).total_cmp(&load(b)).then(a.id.cmp(&b.id))
+        }),
+    }
+}
+
+/// The provider with a key from its pool, if it rotates at `rotation` time
+///
+/// The chosen key is marked as used. Providers without a pool, or rotating at
+/// the other time, are returned unchanged; failures only log, so a broken pool
+/// never blocks a switch or a request.
+pub(crate) fn apply_pool(
+    db: &Database,
+    app_type: &AppType,
+    provider: &Provider,
+    rotation: KeyRotation,
+) -> Provider {
+    let current = provider.meta.as_ref().and_then(|m| m.key_rotation);
+    if current.unwrap_or_default() != rotation {
+        return provider.clone();
+    }
+    next_key(db, app_type, provider)
+}
+
+/// The provider with the next key from its pool, whatever its rotation time
+///
+/// Used when starting a `cc-switch run` session: the child talks to the vendor
+/// directly, so each session takes its own key. Providers without a pool are
+/// returned unchanged.
+pub(crate) fn next_key(db: &Database, app_type: &AppType, provider: &Provider) -> Provider {
+    let meta = provider.meta.as_ref();
+    let keys = match db.get_provider_keys(app_type.as_str(), &provider.id) {
+        Ok(keys) => keys,
+        Err(e) => {
+            log::warn!("读取供应商 {} 的 Key 池失败: {e}", provider.id);
+            return provider.clone();
+        }
+    };
+    let strategy = meta.and_then(|m| m.key_strategy).unwrap_or_default();
+    let Some(key) = pick(strategy, &keys) else {
+        return provider.clone();
+    };
+
+    let pooled = with_key(app_type, provider, &key.api_key);
+    if let Err(e) = db.mark_provider_key_used(key.id) {
+        log::warn!("记录 Key 使用失败: {e}");
+    }
+    pooled
+}
+
+/// The provider with `api_key` in place of its own key
+///
+/// Returned unchanged (with a warning) when the key cannot be set.
+pub(crate) fn with_key(app_type: &AppType, provider: &Provider, api_key: &str) -> Provider {
+    let mut pooled = provider.clone();
+    if let Err(e) = ProviderService::set_api_key(app_type, &mut pooled.settings_config, api_key) {
+        log::warn!("应用供应商 {} 的 Key 池失败: {e}", provider.id);
+        return provider.clone();
+    }
+    pooled
+}
+
+/// Add a key to a provider's pool; returns whether it was new
+///
+/// The first key added also puts the provider's own key into the pool, so that
+/// it keeps taking part in the rotation. Gemini pools without a rotation time
+/// rotate in the proxy, since free-tier keys mostly run into 429s mid-session.
+pub(crate) fn add_key(
+    state: &AppState,
+    app_type: &AppType,
+    provider_id: &str,
+    api_key: &str,
+    label: Option<&str>,
+    weight: u32,
+) -> Result<bool, AppError> {
+    let api_key = api_key.trim();
+    if api_key.is_empty() {
+        return Err(AppError::InvalidInput("API Key 不能为空".to_string()));
+    }
+    let app = app_type.as_str();
+    let mut provider = state
+        .db
+        .get_provider_by_id(provider_id, app)?
+        .ok_or_else(|| AppError::provider_not_found(provider_id, app))?;
+
+    if state.db.get_provider_keys(app, provider_id)?.is_empty() {
+        let own = get_adapter(app_type)
+            .extract_auth(&provider)
+            .map(|auth| auth.api_key.trim().to_string())
+            .filter(|key| !key.is_empty() && key != api_key);
+        if let Some(own) = own {
+            state.db.add_provider_key(app, provider_id, &own, None, 1)?;
+        }
+
+        if matches!(app_type, AppType::Gemini) {
+            let meta = provider.meta.get_or_insert_with(Default::default);
+            if meta.key_rotation.is_none() {
+                meta.key_rotation = Some(KeyRotation::Proxy);
+                state.db.save_provider(app, &provider)?;
+            }
+        }
+    }
+    state
+        .db
+        .add_provider_key(app, provider_id, api_key, label, weight)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(id: i64, weight: u32, use_count: i64, last_used: Option<i64>) -> ProviderKey {
+        ProviderKey {
+            id,
+            provider_id: "p".to_string(),
+            app_type: "claude".to_string(),
+            api_key: format!("sk-{id}"),
+            label: None,
+            weight,
+            use_count,
+            last_used,
+            added_at: 0,
+        }
+    }
+
+    #[test]
+    fn pick_follows_strategy() {
+        let fresh = [key(1, 1, 0, None), key(2, 1, 0, None)];
+        assert_eq!(pick(KeyStrategy::RoundRobin, &fresh).map(|k| k.id), Some(1));
+
+        let used = [
+            key(1, 1, 3, Some(30)),
+            key(2, 1, 2, Some(10)),
+            key(3, 1, 1, Some(20)),
+        ];
+        assert_eq!(pick(KeyStrategy::RoundRobin, &used).map(|k| k.id), Some(2));
+        assert_eq!(
+            pick(KeyStrategy::LeastRecentlyUsed, &used).map(|k| k.id),
+            Some(2)
+        );
+
+        let weighted = [key(1, 3, 2, Some(1)), key(2, 1, 1, Some(2))];
+        assert_eq!(
+            pick(KeyStrategy::Weighted, &weighted).map(|k| k.id),
+            Some(1)
+        );
+
+        assert!(pick(KeyStrategy::Weighted, &[]).is_none());
+    }
+}
@@ -0,0 +1,294 @@
+//! Spending limits
+//!
+//! `limitDailyUsd` / `limitMonthlyUsd` in a provider's meta are compared with
+//! the estimated spend recorded in the proxy request log (see
+//! [`Database::check_provider_limits`]). The `spendingLimitAction` setting
+//! decides what happens once a limit is reached:
+//!
+//! - `warn` (default): switching and proxying continue, a warning is logged
+//!   and the GUI is notified once per provider and day
+//! - `block`: switching to the provider is refused and the proxy skips it
+//! - `off`: limits are ignored
+
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::{Mutex, OnceLock};
+
+use serde::Serialize;
+use tauri::Emitter;
+
+use crate::database::Database;
+use crate::error::AppError;
+use crate::notifications::{self, Notification, NotificationKind};
+use crate::provider::Provider;
+use crate::services::usage_stats::ProviderLimitStatus;
+
+/// Frontend event emitted when a provider goes over its limit
+pub const LIMIT_EXCEEDED_EVENT: &str = "spending-limit-exceeded";
+
+/// What to do with a provider that is over its limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LimitAction {
+    Off,
+    #[default]
+    Warn,
+    Block,
+}
+
+impl FromStr for LimitAction {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "off" | "none" => Ok(Self::Off),
+            "warn" => Ok(Self::Warn),
+            "block" => Ok(Self::Block),
+            other => Err(AppError::InvalidInput(format!(
+                "未知的限额策略: {other}（可选: off, warn, block）"
+            ))),
+        }
+    }
+}
+
+impl LimitAction {
+    /// The configured action; unset or unknown values fall back to `warn`
+    pub fn current() -> Self {
+        crate::settings::get_settings()
+            .spending_limit_action
+            .as_deref()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Limit status of one provider, as shown by `cc-switch limits status`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LimitReport {
+    pub app: String,
+    pub provider_name: String,
+    pub is_current: bool,
+    #[serde(flatten)]
+    pub status: ProviderLimitStatus,
+}
+
+impl LimitReport {
+    pub fn exceeded(&self) -> bool {
+        self.status.daily_exceeded || self.status.monthly_exceeded
+    }
+}
+
+/// Payload of [`LIMIT_EXCEEDED_EVENT`]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LimitExceeded<'a> {
+    app: &'a str,
+    provider_id: &'a str,
+    provider_name: &'a str,
+    message: &'a str,
+    blocked: bool,
+}
+
+/// Whether the provider has any limit configured
+pub(crate) fn has_limits(provider: &Provider) -> bool {
+    let parses = |value: &Option<String>| {
+        value
+            .as_deref()
+            .is_some_and(|v| v.trim().parse::<f64>().is_ok())
+    };
+    provider
+        .meta
+        .as_ref()
+        .is_some_and(|meta| parses(&meta.limit_daily_usd) || parses(&meta.limit_monthly_usd))
+}
+
+/// Current status, or None when the provider has no limit configured
+pub(crate) fn status(
+    db: &Database,
+    app: &str,
+    provider: &Provider,
+) -> Result<Option<ProviderLimitStatus>, AppError> {
+    if !has_limits(provider) {
+        return Ok(None);
+    }
+    db.check_provider_limits(&provider.id, app).map(Some)
+}
+
+/// Human-readable description of an exceeded limit
+pub(crate) fn describe(provider: &Provider, status: &ProviderLimitStatus) -> Option<String> {
+    let (period, usage, limit) = if status.daily_exceeded {
+        ("每日", &status.daily_usage, &status.daily_limit)
+    } else if status.monthly_exceeded {
+        ("每月", &status.monthly_usage, &status.monthly_limit)
+    } else {
+        return None;
+    };
+    let usage = usage.parse::<f64>().unwrap_or_default();
+    Some(format!(
+        "供应商 {} 已超出{period}限额（已用 ${usage:.2} / 限额 ${}）",
+        provider.name,
+        limit.as_deref().unwrap_or("-")
+    ))
+}
+
+/// Check a provider before switching to it
+///
+/// Returns the warning to show when the provider is over its limit and the
+/// action is `warn`; fails when the action is `block`.
+pub(crate) fn check_switch(
+    db: &Database,
+    app: &str,
+    provider: &Provider,
+) -> Result<Option<String>, AppError> {
+    let action = LimitAction::current();
+    if action == LimitAction::Off {
+        return Ok(None);
+    }
+    let Some(message) = status(db, app, provider)?
+        .as_ref()
+        .and_then(|status| describe(provider, status))
+    else {
+        return Ok(None);
+    };
+    if action == LimitAction::Block {
+        return Err(AppError::localized(
+            "provider.limit.exceeded",
+            message,
+            format!("Provider {} has exceeded its spending limit", provider.name),
+        ));
+    }
+    log::warn!("[Limits] {message}");
+    Ok(Some(message))
+}
+
+/// Apply limits to the proxy's provider chain
+///
+/// Over-limit providers are reported (once per provider and day) and, when
+/// the action is `block`, dropped from the chain. Status lookups that fail
+/// never take a provider out of rotation.
+pub(crate) fn filter_chain(
+    db: &Database,
+    app: &str,
+    providers: Vec<Provider>,
+    app_handle: Option<&tauri::AppHandle>,
+) -> Vec<Provider> {
+    let action = LimitAction::current();
+    if action == LimitAction::Off {
+        return providers;
+    }
+    providers
+        .into_iter()
+        .filter(|provider| {
+            let message = match status(db, app, provider) {
+                Ok(Some(status)) => describe(provider, &status),
+                Ok(None) => None,
+                Err(e) => {
+                    log::warn!("[Limits] 检查供应商 {} 限额失败: {e}", provider.id);
+                    None
+                }
+            };
+            let Some(message) = message else {
+                return true;
+            };
+            let blocked = action == LimitAction::Block;
+            notify(app_handle, app, provider, &message, blocked);
+            !blocked
+        })
+        .collect()
+}
+
+/// Log, notify and emit the exceeded event, at most once per provider and day
+fn notify(
+    app_handle: Option<&tauri::AppHandle>,
+    app: &str,
+    provider: &Provider,
+    message: &str,
+    blocked: bool,
+) {
+    static NOTIFIED: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+    let key = format!(
+        "{app}:{}:{}",
+        provider.id,
+        chrono::Local::now().format("%Y-%m-%d")
+    );
+    let first = NOTIFIED
+        .get_or_init(Default::default)
+        .lock()
+        .map(|mut seen| seen.insert(key))
+        .unwrap_or(true);
+    if !first {
+        return;
+    }
+
+    log::warn!("[Limits] {message}");
+    notifications::notify(Notification::new(
+        NotificationKind::LimitExceeded,
+        app,
+        &provider.id,
+        &provider.name,
+        message,
+    ));
+    if let Some(handle) = app_handle {
+        let payload = LimitExceeded {
+            app,
+            provider_id: &provider.id,
+            provider_name: &provider.name,
+            message,
+            blocked,
+        };
+        if let Err(e) = handle.emit(LIMIT_EXCEEDED_EVENT, payload) {
+            log::error!("[Limits] 发射限额事件失败: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn limit_status(daily_exceeded: bool, monthly_exceeded: bool) -> ProviderLimitStatus {
+        ProviderLimitStatus {
+            provider_id: "p1".to_string(),
+            daily_usage: "12.500000".to_string(),
+            daily_limit: Some("10.00".to_string()),
+            daily_exceeded,
+            monthly_usage: "40.000000".to_string(),
+            monthly_limit: Some("100.00".to_string()),
+            monthly_exceeded,
+        }
+    }
+
+    #[test]
+    fn only_parseable_limits_count() {
+        let mut provider = Provider::with_id("p1".into(), "Relay".into(), json!({}), None);
+        assert!(!has_limits(&provider));
+
+        provider.meta = Some(crate::provider::ProviderMeta {
+            limit_daily_usd: Some("abc".to_string()),
+            ..Default::default()
+        });
+        assert!(!has_limits(&provider));
+
+        provider.meta.as_mut().unwrap().limit_monthly_usd = Some("50".to_string());
+        assert!(has_limits(&provider));
+    }
+
+    #[test]
+    fn describes_the_exceeded_period() {
+        let provider = Provider::with_id("p1".into(), "Relay".into(), json!({}), None);
+        assert!(describe(&provider, &limit_status(false, false)).is_none());
+
+        let message = describe(&provider, &limit_status(true, false)).unwrap();
+        assert!(message.contains("每日"));
+        assert!(message.contains("$12.50"));
+        assert!(message.contains("$10.00"));
+
+        assert!(describe(&provider, &limit_status(false, true))
+            .unwrap()
+            .contains("每月"));
+        assert_eq!("BLOCK".parse::<LimitAction>().unwrap(), LimitAction::Block);
+        assert!("deny".parse::<LimitAction>().is_err());
+    }
+}
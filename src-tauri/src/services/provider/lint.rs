@@ -0,0 +1,151 @@
+//! Live config linting
+//!
+//! `cc-switch lint` reads the files an app uses straight from disk, reports
+//! files that do not parse (with the location the parser gives), and runs
+//! [`validate_live`] on the rest. The same checks run on what cc-switch is
+//! about to write, see [`check_before_write`].
+
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::app_config::AppType;
+use crate::codex_config::{get_codex_auth_path, get_codex_config_path};
+use crate::config::get_claude_settings_path;
+use crate::error::AppError;
+use crate::gemini_config::{
+    env_to_json, get_gemini_env_path, get_gemini_settings_path, parse_env_file_strict,
+};
+
+use super::validator::{validate_live, ValidationIssue};
+
+/// A problem found in one live config file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LintIssue {
+    pub file: String,
+    #[serde(flatten)]
+    pub issue: ValidationIssue,
+}
+
+/// Lint every live config file of `app_type`
+pub(crate) fn lint(app_type: &AppType) -> Result<Vec<LintIssue>, AppError> {
+    let mut issues = Vec::new();
+    match app_type {
+        AppType::Claude => {
+            let path = get_claude_settings_path();
+            let settings = read_json(&path, &mut issues)?;
+            if !settings.is_null() {
+                report(&mut issues, &path, validate_live(app_type, &settings));
+            }
+        }
+        AppType::Codex => {
+            let auth_path = get_codex_auth_path();
+            let config_path = get_codex_config_path();
+            let auth = read_json(&auth_path, &mut issues)?;
+            let config = read_text(&config_path)?.unwrap_or_default();
+            let live = json!({ "auth": auth, "config": config });
+            let (auth_issues, config_issues): (Vec<_>, Vec<_>) = validate_live(app_type, &live)
+                .into_iter()
+                .partition(|issue| issue.field.starts_with("auth"));
+            report(&mut issues, &auth_path, auth_issues);
+            report(&mut issues, &config_path, config_issues);
+        }
+        AppType::Gemini => {
+            let env_path = get_gemini_env_path();
+            let settings_path = get_gemini_settings_path();
+            let env = match read_text(&env_path)? {
+                Some(text) => match parse_env_file_strict(&text) {
+                    Ok(map) => env_to_json(&map)["env"].clone(),
+                    Err(e) => {
+                        report(
+                            &mut issues,
+                            &env_path,
+                            vec![ValidationIssue::error("", e.to_string())],
+                        );
+                        Value::Null
+                    }
+                },
+                None => Value::Null,
+            };
+            let settings = read_json(&settings_path, &mut issues)?;
+            let live = json!({ "env": env, "config": settings });
+            let (env_issues, settings_issues): (Vec<_>, Vec<_>) = validate_live(app_type, &live)
+                .into_iter()
+                .partition(|issue| issue.field.starts_with("env"));
+            report(&mut issues, &env_path, env_issues);
+            report(&mut issues, &settings_path, settings_issues);
+        }
+    }
+    Ok(issues)
+}
+
+/// Check a live config about to be written: warnings are logged, errors stop the write
+pub(crate) fn check_before_write(app_type: &AppType, live: &Value) -> Result<(), AppError> {
+    let (errors, warnings): (Vec<_>, Vec<_>) = validate_live(app_type, live)
+        .into_iter()
+        .partition(ValidationIssue::is_error);
+    for issue in &warnings {
+        log::warn!(
+            "{} live 配置存在问题: {}: {}",
+            app_type.as_str(),
+            issue.field,
+            issue.message
+        );
+    }
+    if errors.is_empty() {
+        return Ok(());
+    }
+    let details: Vec<String> = errors
+        .iter()
+        .map(|issue| format!("{}: {}", issue.field, issue.message))
+        .collect();
+    Err(AppError::localized(
+        "live.lint.failed",
+        format!(
+            "{} 配置存在错误，未写入: {}",
+            app_type.as_str(),
+            details.join("; ")
+        ),
+        format!(
+            "{} config has errors and was not written: {}",
+            app_type.as_str(),
+            details.join("; ")
+        ),
+    ))
+}
+
+fn report(issues: &mut Vec<LintIssue>, path: &Path, found: Vec<ValidationIssue>) {
+    let file = path.display().to_string();
+    issues.extend(found.into_iter().map(|issue| LintIssue {
+        file: file.clone(),
+        issue,
+    }));
+}
+
+fn read_text(path: &Path) -> Result<Option<String>, AppError> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => Ok(Some(text)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(AppError::io(path, e)),
+    }
+}
+
+/// Parse a JSON file; missing files and parse errors yield Null (the latter reported)
+fn read_json(path: &Path, issues: &mut Vec<LintIssue>) -> Result<Value, AppError> {
+    let Some(text) = read_text(path)? else {
+        return Ok(Value::Null);
+    };
+    match serde_json::from_str(&text) {
+        Ok(value) => Ok(value),
+        Err(e) => {
+            report(
+                issues,
+                path,
+                vec![ValidationIssue::error("", format!("不是有效的 JSON: {e}"))],
+            );
+            Ok(Value::Null)
+        }
+    }
+}
@@ -7,17 +7,30 @@ use std::collections::HashMap;
 use serde_json::{json, Value};
 
 use crate::app_config::AppType;
-use crate::codex_config::{get_codex_auth_path, get_codex_config_path};
-use crate::config::{delete_file, get_claude_settings_path, read_json_file, write_json_file};
+use crate::codex_config::{
+    get_codex_auth_path, get_codex_config_path, merge_provider_config, read_codex_config_text,
+    write_codex_env,
+};
+use crate::config::{
+    delete_file, get_claude_settings_path, read_json_file, write_json_file, FileTransaction,
+    FileWriteResult, WriteOptions,
+};
+use crate::database::ChangeSource;
 use crate::error::AppError;
-use crate::provider::Provider;
+use crate::provider::{LiveWriteMode, Provider};
 use crate::services::mcp::McpService;
 use crate::store::AppState;
 
+use super::codex_login;
+use super::endpoints::apply_active_endpoint;
+use super::env::{apply_proxy_env, provider_env};
 use super::gemini_auth::{
     detect_gemini_auth_type, ensure_google_oauth_security_flag, GeminiAuthType,
 };
+use super::lint::check_before_write;
+use super::model::with_model_preference;
 use super::normalize_claude_models_in_value;
+use super::ownership::{self, preserve_unowned};
 
 /// Live configuration snapshot for backup/restore
 #[derive(Clone)]
@@ -92,12 +105,65 @@ impl LiveSnapshot {
     }
 }
 
+/// 一并写入 live 配置文件（两阶段，见 [`FileTransaction`]）；backup.liveBackup 开启时把
+/// 上一版保留为 `.bak`
+fn commit_live(tx: FileTransaction) -> Result<Vec<FileWriteResult>, AppError> {
+    let keep_backup = crate::settings::get_settings()
+        .backup
+        .and_then(|backup| backup.live_backup)
+        .unwrap_or(false);
+    tx.commit(WriteOptions { keep_backup })
+}
+
 /// Write live configuration snapshot for a provider
-pub(crate) fn write_live_snapshot(app_type: &AppType, provider: &Provider) -> Result<(), AppError> {
+///
+/// 返回一并写入的各文件结果（Gemini 的文件仍逐个写入，不在其中）。文件读写失败
+/// 包装为 [`AppError::LiveWrite`]，配置校验错误原样返回。
+pub(crate) fn write_live_snapshot(
+    app_type: &AppType,
+    provider: &Provider,
+) -> Result<Vec<FileWriteResult>, AppError> {
+    write_live_files(app_type, provider).map_err(|e| match e {
+        AppError::Io { .. } | AppError::IoContext { .. } | AppError::ApplyFailed { .. } => {
+            AppError::LiveWrite {
+                app: app_type.as_str().to_string(),
+                source: Box::new(e),
+            }
+        }
+        e => e,
+    })
+}
+
+fn write_live_files(
+    app_type: &AppType,
+    provider: &Provider,
+) -> Result<Vec<FileWriteResult>, AppError> {
+    let provider = &prepare_live_provider(app_type, provider)?;
     match app_type {
         AppType::Claude => {
             let path = get_claude_settings_path();
-            write_json_file(&path, &provider.settings_config)?;
+            // 现有文件缺失或无法解析时等同于覆盖
+            let live = if path.exists() {
+                read_json_file::<Value>(&path).unwrap_or_else(|e| {
+                    log::warn!("读取 Claude settings.json 失败，将整体覆盖: {e}");
+                    Value::Null
+                })
+            } else {
+                Value::Null
+            };
+            let settings = claude_live_settings(provider, &live);
+            check_before_write(app_type, &settings)?;
+            let mut tx = FileTransaction::new();
+            tx.write_json(&path, &settings)?;
+            let results = commit_live(tx)?;
+
+            // 免去 Claude Code 启动时“是否使用该 API Key”的确认
+            if let Some(key) = settings["env"]["ANTHROPIC_API_KEY"].as_str() {
+                if let Err(e) = crate::claude_mcp::approve_api_key_in_claude_json(key) {
+                    log::warn!("写入 ~/.claude.json 的 API Key 确认失败: {e}");
+                }
+            }
+            Ok(results)
         }
         AppType::Codex => {
             let obj = provider
@@ -111,17 +177,126 @@ pub(crate) fn write_live_snapshot(app_type: &AppType, provider: &Provider) -> Re
                 AppError::Config("Codex 供应商配置缺少 'config' 字段或不是字符串".to_string())
             })?;
 
+            // auth.json 中凭据以外的字段保留现有值；官方登录写回保存的快照
             let auth_path = get_codex_auth_path();
-            write_json_file(&auth_path, auth)?;
+            let auth = match codex_login::login_auth(provider)? {
+                Some(snapshot) => snapshot,
+                None => {
+                    let live_auth = if auth_path.exists() {
+                        read_json_file::<Value>(&auth_path).unwrap_or(Value::Null)
+                    } else {
+                        Value::Null
+                    };
+                    preserve_unowned(auth.clone(), &live_auth, ownership::CODEX_AUTH)
+                }
+            };
+
+            // 合并进现有 config.toml，保留用户的其他设置；现有文件无法解析时整体覆盖
             let config_path = get_codex_config_path();
-            std::fs::write(&config_path, config_str).map_err(|e| AppError::io(&config_path, e))?;
+            let existing = read_codex_config_text().unwrap_or_default();
+            let merged = merge_provider_config(&existing, config_str).unwrap_or_else(|e| {
+                log::warn!("合并 Codex config.toml 失败，将整体覆盖: {e}");
+                config_str.to_string()
+            });
+            check_before_write(app_type, &json!({ "auth": auth, "config": merged }))?;
+            // auth.json 与 config.toml 要么都换成新版本，要么都保持原样
+            let mut tx = FileTransaction::new();
+            tx.write_json(&auth_path, &auth)?
+                .write(&config_path, merged.into_bytes());
+            let results = commit_live(tx)?;
+            write_codex_env(&provider_env(provider))?;
+            Ok(results)
         }
         AppType::Gemini => {
+            let env = crate::gemini_config::json_to_env(&provider.settings_config)?;
+            check_before_write(app_type, &crate::gemini_config::env_to_json(&env))?;
             // Delegate to write_gemini_live which handles env file writing correctly
             write_gemini_live(provider)?;
+            Ok(Vec::new())
         }
     }
-    Ok(())
+}
+
+/// The provider as written to the live config: meta settings (default model,
+/// proxy) applied on top of `settings_config`
+pub(crate) fn prepare_live_provider(
+    app_type: &AppType,
+    provider: &Provider,
+) -> Result<Provider, AppError> {
+    let mut provider = with_model_preference(app_type, provider)?.into_owned();
+    apply_active_endpoint(app_type, &mut provider);
+    apply_proxy_env(&mut provider)?;
+    Ok(provider)
+}
+
+/// Top-level keys of Claude settings.json that belong to the machine rather than
+/// to a provider (e.g. the statusline installed by `cc-switch integrate
+/// claude-statusline`); the live value is kept in both write modes and is not
+/// backfilled into providers
+pub(crate) const CLAUDE_DEVICE_KEYS: &[&str] = &["statusLine"];
+
+/// The Claude settings.json written for a provider, given the current live file
+///
+/// Everything outside the paths cc-switch owns is kept from the live file in
+/// both write modes (see [`ownership`]).
+fn claude_live_settings(provider: &Provider, live: &Value) -> Value {
+    let mode = provider
+        .meta
+        .as_ref()
+        .and_then(|meta| meta.live_write_mode)
+        .unwrap_or_default();
+    let settings = match mode {
+        LiveWriteMode::Overwrite => provider.settings_config.clone(),
+        LiveWriteMode::Merge => merge_claude_settings(live, &provider.settings_config),
+    };
+    let mut settings = preserve_unowned(settings, live, ownership::CLAUDE_SETTINGS);
+    if let Some(obj) = settings.as_object_mut() {
+        for key in CLAUDE_DEVICE_KEYS {
+            if let Some(value) = live.get(*key) {
+                obj.insert(key.to_string(), value.clone());
+            }
+        }
+    }
+    settings
+}
+
+/// Merge a provider's Claude settings into the live settings.json
+///
+/// Top-level keys of the provider override the live ones; in `env`, the keys
+/// owned by cc-switch ([`ownership::CLAUDE_SETTINGS`]) are replaced and all other
+/// variables are kept. Everything else in the live file (permissions, hooks,
+/// statusLine, ...) is preserved.
+pub(crate) fn merge_claude_settings(live: &Value, provider: &Value) -> Value {
+    let (Some(live_obj), Some(provider_obj)) = (live.as_object(), provider.as_object()) else {
+        return provider.clone();
+    };
+
+    let mut merged = live_obj.clone();
+    merged.retain(|key, _| !ownership::is_owned(&format!("/{key}"), ownership::CLAUDE_SETTINGS));
+    for (key, value) in provider_obj {
+        if key != "env" {
+            merged.insert(key.clone(), value.clone());
+        }
+    }
+
+    let mut env = live_obj
+        .get("env")
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_default();
+    env.retain(|key, _| !ownership::is_owned(&format!("/env/{key}"), ownership::CLAUDE_SETTINGS));
+    if let Some(provider_env) = provider_obj.get("env").and_then(Value::as_object) {
+        for (key, value) in provider_env {
+            env.insert(key.clone(), value.clone());
+        }
+    }
+    if env.is_empty() && !provider_obj.contains_key("env") {
+        merged.remove("env");
+    } else {
+        merged.insert("env".to_string(), Value::Object(env));
+    }
+
+    Value::Object(merged)
 }
 
 /// Sync current provider to live configuration
@@ -151,6 +326,67 @@ pub fn sync_current_to_live(state: &AppState) -> Result<(), AppError> {
     Ok(())
 }
 
+/// The live settings as they would be after writing `provider`, in the shape
+/// returned by [`read_live_settings`]
+///
+/// `live` is the current result of [`read_live_settings`] (Null when missing);
+/// merging follows [`write_live_snapshot`], nothing is written.
+pub(crate) fn projected_live_settings(
+    app_type: &AppType,
+    provider: &Provider,
+    live: &Value,
+) -> Result<Value, AppError> {
+    let provider = prepare_live_provider(app_type, provider)?;
+    let settings = &provider.settings_config;
+    match app_type {
+        AppType::Claude => Ok(claude_live_settings(&provider, live)),
+        AppType::Codex => {
+            let config = settings.get("config").and_then(Value::as_str).unwrap_or("");
+            let existing = live.get("config").and_then(Value::as_str).unwrap_or("");
+            let merged =
+                merge_provider_config(existing, config).unwrap_or_else(|_| config.to_string());
+            let auth = match codex_login::login_auth(&provider)? {
+                Some(snapshot) => snapshot,
+                None => {
+                    let auth = settings.get("auth").cloned().unwrap_or_else(|| json!({}));
+                    preserve_unowned(auth, &live["auth"], ownership::CODEX_AUTH)
+                }
+            };
+            Ok(json!({
+                "auth": auth,
+                "config": merged,
+            }))
+        }
+        AppType::Gemini => {
+            use crate::gemini_config::{env_to_json, json_to_env};
+
+            let mut env_map = json_to_env(settings)?;
+            match detect_gemini_auth_type(&provider) {
+                GeminiAuthType::GoogleOfficial => env_map.clear(),
+                GeminiAuthType::Vertex => {
+                    env_map.remove("GEMINI_API_KEY");
+                    env_map.insert("GOOGLE_GENAI_USE_VERTEXAI".to_string(), "true".to_string());
+                }
+                GeminiAuthType::Packycode | GeminiAuthType::Generic => {}
+            }
+            let mut config = live.get("config").cloned().unwrap_or_else(|| json!({}));
+            if let (Some(merged), Some(provider_config)) = (
+                config.as_object_mut(),
+                settings.get("config").and_then(Value::as_object),
+            ) {
+                for (key, value) in provider_config {
+                    merged.insert(key.clone(), value.clone());
+                }
+            }
+            let env_map = with_unowned_gemini_env(env_map, &json_to_env(live)?);
+            Ok(json!({
+                "env": env_to_json(&env_map)["env"].clone(),
+                "config": config,
+            }))
+        }
+    }
+}
+
 /// Read current live settings for an app type
 pub fn read_live_settings(app_type: AppType) -> Result<Value, AppError> {
     match app_type {
@@ -296,7 +532,19 @@ pub fn import_default_config(state: &AppState, app_type: AppType) -> Result<bool
     );
     provider.category = Some("custom".to_string());
 
-    state.db.save_provider(app_type.as_str(), &provider)?;
+    // 导入的是用户现有配置，问题只记录日志，不阻止导入
+    for issue in super::validate_provider(&provider, &app_type) {
+        log::warn!(
+            "导入的 {} 默认配置存在问题: {}: {}",
+            app_type.as_str(),
+            issue.field,
+            issue.message
+        );
+    }
+
+    state
+        .db
+        .save_provider_as(app_type.as_str(), &provider, ChangeSource::Import)?;
     state
         .db
         .set_current_provider(app_type.as_str(), &provider.id)?;
@@ -304,11 +552,32 @@ pub fn import_default_config(state: &AppState, app_type: AppType) -> Result<bool
     Ok(true) // 真正导入了
 }
 
+/// Write the Gemini `.env`, keeping the variables cc-switch does not own
+fn write_gemini_env(env_map: HashMap<String, String>) -> Result<(), AppError> {
+    use crate::gemini_config::{read_gemini_env, write_gemini_env_atomic};
+
+    let live = read_gemini_env().unwrap_or_default();
+    write_gemini_env_atomic(&with_unowned_gemini_env(env_map, &live))
+}
+
+/// Add the live `.env` variables outside [`ownership::GEMINI_ENV`]
+fn with_unowned_gemini_env(
+    mut env_map: HashMap<String, String>,
+    live: &HashMap<String, String>,
+) -> HashMap<String, String> {
+    for (key, value) in live {
+        if !ownership::is_owned(&format!("/{key}"), ownership::GEMINI_ENV) {
+            env_map.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+    }
+    env_map
+}
+
 /// Write Gemini live configuration with authentication handling
 pub(crate) fn write_gemini_live(provider: &Provider) -> Result<(), AppError> {
     use crate::gemini_config::{
-        get_gemini_settings_path, json_to_env, validate_gemini_settings_strict,
-        write_gemini_env_atomic,
+        get_gemini_oauth_creds_path, get_gemini_settings_path, json_to_env,
+        validate_gemini_settings_strict, validate_gemini_vertex_settings,
     };
 
     // One-time auth type detection to avoid repeated detection
@@ -360,17 +629,27 @@ pub(crate) fn write_gemini_live(provider: &Provider) -> Result<(), AppError> {
         GeminiAuthType::GoogleOfficial => {
             // Google official uses OAuth, clear env
             env_map.clear();
-            write_gemini_env_atomic(&env_map)?;
+            write_gemini_env(env_map)?;
+            if !get_gemini_oauth_creds_path().exists() {
+                log::info!("未找到 Gemini OAuth 凭据，首次运行 Gemini CLI 时需登录 Google 账号");
+            }
+        }
+        GeminiAuthType::Vertex => {
+            // Vertex AI: project/location (or express-mode API key) plus the Vertex switch
+            validate_gemini_vertex_settings(&provider.settings_config)?;
+            env_map.remove("GEMINI_API_KEY");
+            env_map.insert("GOOGLE_GENAI_USE_VERTEXAI".to_string(), "true".to_string());
+            write_gemini_env(env_map)?;
         }
         GeminiAuthType::Packycode => {
             // PackyCode provider, uses API Key (strict validation on switch)
             validate_gemini_settings_strict(&provider.settings_config)?;
-            write_gemini_env_atomic(&env_map)?;
+            write_gemini_env(env_map)?;
         }
         GeminiAuthType::Generic => {
             // Generic provider, uses API Key (strict validation on switch)
             validate_gemini_settings_strict(&provider.settings_config)?;
-            write_gemini_env_atomic(&env_map)?;
+            write_gemini_env(env_map)?;
         }
     }
 
@@ -380,9 +659,11 @@ pub(crate) fn write_gemini_live(provider: &Provider) -> Result<(), AppError> {
 
     // Set security.auth.selectedType based on auth type
     // - Google Official: OAuth mode
+    // - Vertex: Vertex AI mode
     // - All others: API Key mode
     match auth_type {
         GeminiAuthType::GoogleOfficial => ensure_google_oauth_security_flag(provider)?,
+        GeminiAuthType::Vertex => crate::gemini_config::write_vertex_ai_settings()?,
         GeminiAuthType::Packycode | GeminiAuthType::Generic => {
             crate::gemini_config::write_packycode_settings()?;
         }
@@ -390,3 +671,132 @@ pub(crate) fn write_gemini_live(provider: &Provider) -> Result<(), AppError> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::ProviderMeta;
+
+    #[test]
+    fn merge_claude_settings_keeps_user_settings() {
+        let live = json!({
+            "env": {
+                "ANTHROPIC_BASE_URL": "https://old.example.com",
+                "ANTHROPIC_MODEL": "old-model",
+                "MY_TOOL_FLAG": "1"
+            },
+            "permissions": { "allow": ["Bash(ls:*)"] },
+            "statusLine": { "type": "command", "command": "status.sh" }
+        });
+        let provider = json!({
+            "env": {
+                "ANTHROPIC_BASE_URL": "https://new.example.com",
+                "ANTHROPIC_AUTH_TOKEN": "sk-new"
+            },
+            "model": "opus"
+        });
+
+        let merged = merge_claude_settings(&live, &provider);
+        assert_eq!(
+            merged["env"],
+            json!({
+                "ANTHROPIC_BASE_URL": "https://new.example.com",
+                "ANTHROPIC_AUTH_TOKEN": "sk-new",
+                "MY_TOOL_FLAG": "1"
+            })
+        );
+        assert_eq!(merged["model"], "opus");
+        assert_eq!(merged["permissions"], live["permissions"]);
+        assert_eq!(merged["statusLine"], live["statusLine"]);
+
+        assert_eq!(merge_claude_settings(&Value::Null, &provider), provider);
+    }
+
+    #[test]
+    fn claude_live_settings_keeps_live_status_line_when_overwriting() {
+        let live = json!({
+            "env": { "ANTHROPIC_BASE_URL": "https://old.example.com" },
+            "statusLine": { "type": "command", "command": "cc-switch-statusline.sh" }
+        });
+        let mut provider = Provider::with_id(
+            "p".to_string(),
+            "P".to_string(),
+            json!({
+                "env": { "ANTHROPIC_BASE_URL": "https://new.example.com" },
+                "statusLine": { "type": "command", "command": "stale.sh" }
+            }),
+            None,
+        );
+        provider.meta = Some(ProviderMeta {
+            live_write_mode: Some(LiveWriteMode::Overwrite),
+            ..Default::default()
+        });
+
+        let settings = claude_live_settings(&provider, &live);
+        assert_eq!(settings["statusLine"], live["statusLine"]);
+        assert_eq!(
+            settings["env"]["ANTHROPIC_BASE_URL"],
+            "https://new.example.com"
+        );
+
+        let settings = claude_live_settings(&provider, &Value::Null);
+        assert_eq!(settings["statusLine"]["command"], "stale.sh");
+    }
+
+    #[test]
+    fn projected_live_settings_keep_unowned_paths_for_each_app() {
+        let provider = |settings: Value| {
+            let mut provider = Provider::with_id("p".to_string(), "P".to_string(), settings, None);
+            provider.meta = Some(ProviderMeta {
+                live_write_mode: Some(LiveWriteMode::Overwrite),
+                ..Default::default()
+            });
+            provider
+        };
+
+        let claude = projected_live_settings(
+            &AppType::Claude,
+            &provider(json!({ "env": { "ANTHROPIC_BASE_URL": "https://new.example.com" } })),
+            &json!({
+                "env": { "ANTHROPIC_AUTH_TOKEN": "sk-old", "MY_FLAG": "1" },
+                "model": "old-model",
+                "outputStyle": "Explanatory",
+                "permissions": { "allow": ["Bash(ls:*)"] }
+            }),
+        )
+        .expect("claude");
+        assert_eq!(
+            claude,
+            json!({
+                "env": { "ANTHROPIC_BASE_URL": "https://new.example.com", "MY_FLAG": "1" },
+                "outputStyle": "Explanatory",
+                "permissions": { "allow": ["Bash(ls:*)"] }
+            })
+        );
+
+        let codex = projected_live_settings(
+            &AppType::Codex,
+            &provider(json!({ "auth": { "OPENAI_API_KEY": "sk-new" }, "config": "" })),
+            &json!({
+                "auth": { "tokens": { "access_token": "t" }, "note": "keep" },
+                "config": ""
+            }),
+        )
+        .expect("codex");
+        assert_eq!(
+            codex["auth"],
+            json!({ "OPENAI_API_KEY": "sk-new", "note": "keep" })
+        );
+
+        let gemini = projected_live_settings(
+            &AppType::Gemini,
+            &provider(json!({ "env": { "GEMINI_API_KEY": "new" } })),
+            &json!({ "env": { "GEMINI_API_KEY": "old", "DEBUG": "1" }, "config": {} }),
+        )
+        .expect("gemini");
+        assert_eq!(
+            gemini["env"],
+            json!({ "GEMINI_API_KEY": "new", "DEBUG": "1" })
+        );
+    }
+}
@@ -0,0 +1,236 @@
+//! Cross-app lookup of a credential or endpoint
+//!
+//! Answers "which providers still use this key / this relay?" across every
+//! app, e.g. when a leaked key has to be revoked everywhere. Matches are
+//! reported as field paths only; the matched value itself is never echoed.
+//!
+//! [`strip_key`] is the revocation counterpart: it blanks a key wherever the
+//! lookup would have found it.
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::AppError;
+use crate::provider::Provider;
+
+/// What to look for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LookupTarget {
+    /// An API key or token (exact substring, case-sensitive)
+    Key(String),
+    /// An endpoint or host (substring, case-insensitive)
+    Url(String),
+}
+
+impl LookupTarget {
+    /// Build from the optional `key` / `url` arguments; exactly one must be given
+    pub fn from_args(key: Option<String>, url: Option<String>) -> Result<Self, AppError> {
+        let key = key.filter(|k| !k.trim().is_empty());
+        let url = url.filter(|u| !u.trim().is_empty());
+        match (key, url) {
+            (Some(key), None) => Ok(LookupTarget::Key(key.trim().to_string())),
+            (None, Some(url)) => Ok(LookupTarget::Url(url.trim().to_lowercase())),
+            _ => Err(AppError::InvalidInput(
+                "需要且只能指定 key 或 url 其中之一".to_string(),
+            )),
+        }
+    }
+
+    fn matches(&self, text: &str) -> bool {
+        match self {
+            LookupTarget::Key(key) => text.contains(key.as_str()),
+            LookupTarget::Url(url) => text.to_lowercase().contains(url.as_str()),
+        }
+    }
+}
+
+/// A provider that references the looked-up value
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderReference {
+    pub app: String,
+    pub id: String,
+    pub name: String,
+    pub is_current: bool,
+    /// Where the value was found (e.g. `settingsConfig.env.ANTHROPIC_AUTH_TOKEN`)
+    pub fields: Vec<String>,
+}
+
+/// Field paths of a provider that contain the target
+///
+/// Covers the whole serialized provider: settings, website URL and meta
+/// (custom endpoints, usage script credentials).
+pub(crate) fn find_fields(provider: &Provider, target: &LookupTarget) -> Vec<String> {
+    let mut fields = Vec::new();
+    if let Ok(value) = serde_json::to_value(provider) {
+        collect(&value, String::new(), target, &mut fields);
+    }
+    fields
+}
+
+/// Remove a key from a provider's settings and meta
+///
+/// Strings equal to the key are emptied, strings embedding it (e.g. Codex
+/// TOML) have it cut out, and array entries equal to it are dropped. Returns
+/// whether anything changed. Keys in the `provider_keys` pool are not touched.
+pub(crate) fn strip_key(provider: &mut Provider, key: &str) -> Result<bool, AppError> {
+    if key.is_empty() {
+        return Ok(false);
+    }
+    let mut changed = strip_value(&mut provider.settings_config, key);
+    if let Some(meta) = provider.meta.as_mut() {
+        let mut value = serde_json::to_value(&*meta)
+            .map_err(|e| AppError::Message(format!("序列化供应商元数据失败: {e}")))?;
+        if strip_value(&mut value, key) {
+            *meta = serde_json::from_value(value)
+                .map_err(|e| AppError::Message(format!("解析供应商元数据失败: {e}")))?;
+            changed = true;
+        }
+    }
+    Ok(changed)
+}
+
+/// API keys in a settings object without a dedicated adapter: non-empty
+/// strings under fields named like `apiKey`, `OPENAI_API_KEY` or `AUTH_TOKEN`
+pub(crate) fn api_keys(settings: &Value) -> Vec<String> {
+    let mut keys = Vec::new();
+    collect_api_keys(settings, &mut keys);
+    keys
+}
+
+fn collect_api_keys(value: &Value, out: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            for (name, child) in map {
+                let name = name.to_lowercase().replace(['_', '-'], "");
+                match child.as_str() {
+                    Some(text)
+                        if (name.ends_with("apikey") || name.ends_with("authtoken"))
+                            && !text.trim().is_empty() =>
+                    {
+                        out.push(text.trim().to_string())
+                    }
+                    _ => collect_api_keys(child, out),
+                }
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|item| collect_api_keys(item, out)),
+        _ => {}
+    }
+}
+
+fn strip_value(value: &mut Value, key: &str) -> bool {
+    match value {
+        Value::String(text) if text.contains(key) => {
+            *text = text.replace(key, "");
+            true
+        }
+        Value::Object(map) => map
+            .values_mut()
+            .fold(false, |changed, child| strip_value(child, key) || changed),
+        Value::Array(items) => {
+            let before = items.len();
+            items.retain(|item| item.as_str() != Some(key));
+            let removed = items.len() != before;
+            items
+                .iter_mut()
+                .fold(removed, |changed, child| strip_value(child, key) || changed)
+        }
+        _ => false,
+    }
+}
+
+fn collect(value: &Value, path: String, target: &LookupTarget, out: &mut Vec<String>) {
+    match value {
+        Value::String(text) => {
+            if target.matches(text) {
+                out.push(path);
+            }
+        }
+        Value::Object(map) => {
+            for (key, child) in map {
+                let child_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                // 自定义端点以 URL 为键
+                if matches!(target, LookupTarget::Url(_)) && target.matches(key) {
+                    out.push(child_path.clone());
+                    continue;
+                }
+                collect(child, child_path, target, out);
+            }
+        }
+        Value::Array(items) => {
+            for (index, child) in items.iter().enumerate() {
+                collect(child, format!("{path}[{index}]"), target, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn finds_key_and_url_by_field_path() {
+        let provider = Provider::with_id(
+            "codex".to_string(),
+            "Codex Relay".to_string(),
+            json!({
+                "auth": { "OPENAI_API_KEY": "sk-leaked-0001" },
+                "config": "[model_providers.relay]\nbase_url = \"https://OpenRouter.ai/api/v1\"\n"
+            }),
+            Some("https://openrouter.ai".to_string()),
+        );
+
+        let key = LookupTarget::from_args(Some("sk-leaked-0001".to_string()), None).unwrap();
+        assert_eq!(
+            find_fields(&provider, &key),
+            vec!["settingsConfig.auth.OPENAI_API_KEY"]
+        );
+
+        let url = LookupTarget::from_args(None, Some("openrouter.ai".to_string())).unwrap();
+        assert_eq!(
+            find_fields(&provider, &url),
+            vec!["settingsConfig.config", "websiteUrl"]
+        );
+
+        let other = LookupTarget::from_args(Some("sk-other".to_string()), None).unwrap();
+        assert!(find_fields(&provider, &other).is_empty());
+        assert!(LookupTarget::from_args(None, None).is_err());
+    }
+
+    #[test]
+    fn strip_key_blanks_values() {
+        let mut provider = Provider::with_id(
+            "g".to_string(),
+            "Gemini".to_string(),
+            json!({ "env": { "GEMINI_API_KEY": "AIza-leaked", "GEMINI_MODEL": "gemini-2.5-pro" } }),
+            None,
+        );
+        assert!(strip_key(&mut provider, "AIza-leaked").unwrap());
+        assert_eq!(provider.settings_config["env"]["GEMINI_API_KEY"], "");
+        assert_eq!(
+            provider.settings_config["env"]["GEMINI_MODEL"],
+            "gemini-2.5-pro"
+        );
+        assert!(!strip_key(&mut provider, "AIza-leaked").unwrap());
+    }
+
+    #[test]
+    fn api_keys_are_found_by_field_name() {
+        let opencode = json!({
+            "provider": { "relay": { "options": { "baseURL": "https://relay.example.com", "apiKey": "sk-open" } } },
+            "model": "relay/model"
+        });
+        assert_eq!(api_keys(&opencode), vec!["sk-open"]);
+        let qwen = json!({ "OPENAI_API_KEY": "sk-qwen", "OPENAI_BASE_URL": "https://x", "OPENAI_MODEL": "m" });
+        assert_eq!(api_keys(&qwen), vec!["sk-qwen"]);
+        assert!(api_keys(&json!({ "OPENAI_API_KEY": "" })).is_empty());
+    }
+}
@@ -2,28 +2,92 @@
 //!
 //! Handles provider CRUD operations, switching, and configuration management.
 
+mod adopt;
+mod bundle;
+mod codex_login;
+mod compat;
+mod dedupe;
+mod diff;
 mod endpoints;
+mod env;
+mod export;
 mod gemini_auth;
+mod history;
+mod hooks;
+mod journal;
+mod keys;
+mod limits;
+mod lint;
 mod live;
+mod lookup;
+mod model;
+mod ownership;
+mod policy;
+mod registry;
+mod rotation;
+mod segment;
+mod session;
+mod snippet;
+mod status;
+mod table;
 mod usage;
+mod usage_probe;
+mod validator;
 
 use indexmap::IndexMap;
 use regex::Regex;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
 
 use crate::app_config::AppType;
+use crate::app_registry::{AppDefinition, AppRegistry};
+use crate::config::FileWriteResult;
+use crate::database::{
+    BenchmarkResult, ProviderHistoryEntry, ProviderKey, ProviderPage, QueryOptions,
+};
 use crate::error::AppError;
-use crate::provider::{Provider, UsageResult};
+use crate::operation_lock::OperationLock;
+use crate::provider::{mask_secret, KeyRotation, KeyStrategy, Provider, UsageResult};
+use crate::proxy::providers::get_adapter;
+use crate::services::bench::{BenchService, RECENT_BENCHMARK_MS};
 use crate::services::mcp::McpService;
-use crate::settings::CustomEndpoint;
+use crate::services::speedtest::EndpointTiming;
+use crate::settings::{CustomEndpoint, SwitchHooks};
 use crate::store::AppState;
 
 // Re-export sub-module functions for external access
+pub use adopt::{auto_adopt, set_auto_adopt, UnmanagedLive};
+pub use bundle::{BundleImport, ExportBundle, SchemaKind};
+pub use codex_login::OFFICIAL_LOGIN_ID;
+pub use compat::app_version_warning;
+pub use dedupe::{DuplicateGroup, ProviderLabel};
+pub use diff::{DiffTarget, ProviderDiff, LIVE_LABEL};
+pub use env::{parse_env_assignment, ProviderProxy};
+pub use export::{ExportFormat, ShellKind};
+pub use history::SwitchRecord;
+pub(crate) use hooks::drop_untrusted_hooks;
+pub use hooks::{HookStage, SwitchEvent};
+pub use journal::{RestoreResult, RestoreTarget};
+pub(crate) use keys::{pick as pick_pool_key, with_key as with_pool_key};
+pub(crate) use limits::filter_chain as apply_spending_limits;
+pub use limits::{LimitAction, LimitReport, LIMIT_EXCEEDED_EVENT};
+pub use lint::LintIssue;
 pub use live::{import_default_config, read_live_settings, sync_current_to_live};
+pub use lookup::{LookupTarget, ProviderReference};
+pub use policy::{ProviderPolicy, RequiredField};
+pub use registry::EnrichResult;
+pub use rotation::KeyPolicy;
+pub use segment::{PromptSegment, SegmentFormat};
+pub use snippet::SnippetLang;
+pub use status::{CurrentProvider, CurrentStatus};
+pub use table::{parse_columns, render_grid, TableColumn, TableStyle, DEFAULT_COLUMNS};
+pub use usage::ProviderUsageSummary;
+pub use validator::{validate_live, validate_provider, Severity, ValidationIssue};
 
 // Internal re-exports (pub(crate))
 pub(crate) use live::write_live_snapshot;
+pub(crate) use usage::disable_untrusted_script;
 
 // Internal re-exports
 use live::write_gemini_live;
@@ -32,6 +96,19 @@ use usage::validate_usage_script;
 /// Provider business logic service
 pub struct ProviderService;
 
+/// Tag added to providers archived when their key is revoked
+pub const ARCHIVED_TAG: &str = "archived";
+
+/// Validation result of one stored provider
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderValidationReport {
+    pub app: String,
+    pub id: String,
+    pub name: String,
+    pub issues: Vec<ValidationIssue>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -71,6 +148,20 @@ mod tests {
         assert_eq!(api_key, "token");
         assert_eq!(base_url, "https://claude.example");
     }
+
+    #[test]
+    fn set_api_key_targets_app_specific_field() {
+        let mut claude = json!({ "env": { "ANTHROPIC_API_KEY": "old" } });
+        ProviderService::set_api_key(&AppType::Claude, &mut claude, "new").unwrap();
+        assert_eq!(claude, json!({ "env": { "ANTHROPIC_API_KEY": "new" } }));
+
+        let mut codex = json!({ "config": "" });
+        ProviderService::set_api_key(&AppType::Codex, &mut codex, "sk").unwrap();
+        assert_eq!(codex["auth"]["OPENAI_API_KEY"], "sk");
+
+        let mut gemini = json!({ "env": [] });
+        assert!(ProviderService::set_api_key(&AppType::Gemini, &mut gemini, "k").is_err());
+    }
 }
 
 impl ProviderService {
@@ -107,6 +198,18 @@ impl ProviderService {
         // Normalize Claude model keys
         Self::normalize_provider_if_claude(&app_type, &mut provider);
         Self::validate_provider_settings(&app_type, &provider)?;
+        ProviderPolicy::load()?.enforce(&provider)?;
+
+        if state
+            .db
+            .get_provider_by_id(&provider.id, app_type.as_str())?
+            .is_some()
+        {
+            return Err(AppError::DuplicateProvider {
+                id: provider.id,
+                app: app_type.as_str().to_string(),
+            });
+        }
 
         // Save to database
         state.db.save_provider(app_type.as_str(), &provider)?;
@@ -134,6 +237,7 @@ impl ProviderService {
         // Normalize Claude model keys
         Self::normalize_provider_if_claude(&app_type, &mut provider);
         Self::validate_provider_settings(&app_type, &provider)?;
+        ProviderPolicy::load()?.enforce(&provider)?;
 
         // Check if this is current provider (use effective current, not just DB)
         let effective_current =
@@ -144,34 +248,44 @@ impl ProviderService {
         state.db.save_provider(app_type.as_str(), &provider)?;
 
         if is_current {
-            // 如果代理接管模式处于激活状态，并且代理服务正在运行：
-            // - 不写 Live 配置（否则会破坏接管）
-            // - 仅更新 Live 备份（保证关闭代理时能恢复到最新配置）
-            let is_app_taken_over =
-                futures::executor::block_on(state.db.get_live_backup(app_type.as_str()))
-                    .ok()
-                    .flatten()
-                    .is_some();
-            let is_proxy_running = futures::executor::block_on(state.proxy_service.is_running());
-            let should_skip_live_write = is_app_taken_over && is_proxy_running;
-
-            if should_skip_live_write {
-                futures::executor::block_on(
-                    state
-                        .proxy_service
-                        .update_live_backup_from_provider(app_type.as_str(), &provider),
-                )
-                .map_err(|e| AppError::Message(format!("更新 Live 备份失败: {e}")))?;
-            } else {
-                write_live_snapshot(&app_type, &provider)?;
-                // Sync MCP
-                McpService::sync_all_enabled(state)?;
-            }
+            Self::refresh_current_live(state, &app_type, &provider)?;
         }
 
         Ok(true)
     }
 
+    /// Re-apply the (already saved) current provider to the live config
+    fn refresh_current_live(
+        state: &AppState,
+        app_type: &AppType,
+        provider: &Provider,
+    ) -> Result<(), AppError> {
+        // 如果代理接管模式处于激活状态，并且代理服务正在运行：
+        // - 不写 Live 配置（否则会破坏接管）
+        // - 仅更新 Live 备份（保证关闭代理时能恢复到最新配置）
+        let is_app_taken_over =
+            futures::executor::block_on(state.db.get_live_backup(app_type.as_str()))
+                .ok()
+                .flatten()
+                .is_some();
+        let is_proxy_running = futures::executor::block_on(state.proxy_service.is_running());
+        let should_skip_live_write = is_app_taken_over && is_proxy_running;
+
+        if should_skip_live_write {
+            futures::executor::block_on(
+                state
+                    .proxy_service
+                    .update_live_backup_from_provider(app_type.as_str(), provider),
+            )
+            .map_err(|e| AppError::Message(format!("更新 Live 备份失败: {e}")))?;
+        } else {
+            write_live_snapshot(app_type, provider)?;
+            // Sync MCP
+            McpService::sync_all_enabled(state)?;
+        }
+        Ok(())
+    }
+
     /// Delete a provider
     ///
     /// 同时检查本地 settings 和数据库的当前供应商，防止删除任一端正在使用的供应商。
@@ -189,6 +303,29 @@ impl ProviderService {
         state.db.delete_provider(app_type.as_str(), id)
     }
 
+    /// Delete several providers at once (single transaction)
+    ///
+    /// Fails without deleting anything if any of them is the current provider.
+    pub fn delete_many(
+        state: &AppState,
+        app_type: AppType,
+        ids: &[String],
+    ) -> Result<usize, AppError> {
+        let local_current = crate::settings::get_current_provider(&app_type);
+        let db_current = state.db.get_current_provider(app_type.as_str())?;
+
+        if let Some(current) = ids
+            .iter()
+            .find(|id| local_current.as_ref() == Some(*id) || db_current.as_ref() == Some(*id))
+        {
+            return Err(AppError::Message(format!(
+                "无法删除当前正在使用的供应商: {current}"
+            )));
+        }
+
+        state.db.delete_providers(app_type.as_str(), ids)
+    }
+
     /// Switch to a provider
     ///
     /// Switch flow:
@@ -202,11 +339,44 @@ impl ProviderService {
     ///    d. Write target provider config to live files
     ///    e. Sync MCP configuration
     pub fn switch(state: &AppState, app_type: AppType, id: &str) -> Result<(), AppError> {
+        Self::switch_from(state, app_type, id, None).map(|_| ())
+    }
+
+    /// Switch to a provider, recording the working directory it was requested from
+    ///
+    /// `cwd` is kept in the switch history so spend can later be attributed to
+    /// a project (see [`ProviderService::switch_history`]). Returns what happened
+    /// to each live config file; a hot-switch under proxy takeover writes none.
+    pub fn switch_from(
+        state: &AppState,
+        app_type: AppType,
+        id: &str,
+        cwd: Option<&str>,
+    ) -> Result<Vec<FileWriteResult>, AppError> {
+        crate::settings::ensure_writable()?;
+        // Serialize with other cc-switch processes switching, importing or restoring
+        let _lock = OperationLock::acquire()?;
+        // A database written by a newer build opens read-only; switching only touches known columns
+        let _compat_writes = state.db.compat_writes()?;
+
         // Check if provider exists
         let providers = state.db.get_all_providers(app_type.as_str())?;
-        let _provider = providers
+        let target = providers
             .get(id)
-            .ok_or_else(|| AppError::Message(format!("供应商 {id} 不存在")))?;
+            .ok_or_else(|| AppError::provider_not_found(id, app_type.as_str()))?;
+
+        // Warn (without blocking) when the installed app is outside the pinned range
+        if let Some(warning) = compat::app_version_warning(&app_type, target) {
+            log::warn!("[Switch] {warning}");
+        }
+
+        // Refuse (or warn about) providers over their spending limit
+        limits::check_switch(&state.db, app_type.as_str(), target)?;
+
+        // A failing pre-switch command cancels the switch
+        let previous = crate::settings::get_effective_current_provider(&state.db, &app_type)?;
+        let event = |stage| SwitchEvent::new(stage, app_type.as_str(), previous.as_deref(), target);
+        hooks::run(target, &event(HookStage::PreSwitch))?;
 
         // Check if proxy takeover mode is active AND proxy server is actually running
         // Both conditions must be true to use hot-switch mode
@@ -232,7 +402,7 @@ impl ProviderService {
             // 获取新供应商的完整配置（用于更新备份）
             let provider = providers
                 .get(id)
-                .ok_or_else(|| AppError::Message(format!("供应商 {id} 不存在")))?;
+                .ok_or_else(|| AppError::provider_not_found(id, app_type.as_str()))?;
 
             // Update database is_current
             state.db.set_current_provider(app_type.as_str(), id)?;
@@ -250,11 +420,129 @@ impl ProviderService {
 
             // Note: No Live config write, no MCP sync
             // The proxy server will route requests to the new provider via is_current
-            return Ok(());
+            history::record(state, app_type.as_str(), provider, cwd);
+            hooks::run(target, &event(HookStage::PostSwitch))?;
+            return Ok(Vec::new());
         }
 
         // Normal mode: full switch with Live config write
-        Self::switch_normal(state, app_type, id, &providers)
+        let files = Self::switch_normal(state, app_type.clone(), id, &providers)?;
+        history::record(state, app_type.as_str(), target, cwd);
+        hooks::run(target, &event(HookStage::PostSwitch))?;
+        Ok(files)
+    }
+
+    /// Switch history, newest first
+    ///
+    /// With `cwd`, only switches made from that directory or below it are returned.
+    pub fn switch_history(
+        state: &AppState,
+        app: Option<&str>,
+        cwd: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<SwitchRecord>, AppError> {
+        let entries = state.db.get_switch_history(app)?;
+        Ok(history::build(entries, cwd, limit))
+    }
+
+    /// Change journal of a provider, oldest first, with secrets masked
+    pub fn change_history(
+        state: &AppState,
+        app_type: AppType,
+        id: &str,
+    ) -> Result<Vec<ProviderHistoryEntry>, AppError> {
+        journal::history(state, &app_type, id)
+    }
+
+    /// Live configs whose credentials match no stored provider, one per app
+    pub fn unmanaged_live(state: &AppState) -> Result<Vec<UnmanagedLive>, AppError> {
+        let mut found = Vec::new();
+        for app_type in [AppType::Claude, AppType::Codex, AppType::Gemini] {
+            found.extend(adopt::unmanaged_live(state, &app_type)?);
+        }
+        Ok(found)
+    }
+
+    /// Adopt the live config of an app as a new current provider
+    ///
+    /// Returns None when the live config already belongs to a stored provider.
+    pub fn adopt_live(state: &AppState, app_type: AppType) -> Result<Option<Provider>, AppError> {
+        match adopt::unmanaged_live(state, &app_type)? {
+            Some(live) => adopt::adopt(state, app_type, live).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Groups of providers with identical settings (see [`Provider::fingerprint`])
+    pub fn find_duplicates(
+        state: &AppState,
+        app_type: AppType,
+    ) -> Result<Vec<DuplicateGroup>, AppError> {
+        dedupe::find(state, &app_type)
+    }
+
+    /// Merge the duplicates of each group into the provider it keeps
+    pub fn merge_duplicates(
+        state: &AppState,
+        app_type: AppType,
+        groups: &[DuplicateGroup],
+    ) -> Result<(), AppError> {
+        for group in groups {
+            dedupe::merge(state, &app_type, group)?;
+        }
+        Ok(())
+    }
+
+    /// Structural diff of a provider's settings against another provider or the
+    /// live config, with secrets masked unless `show_secrets` is set
+    pub fn diff(
+        state: &AppState,
+        app_type: AppType,
+        id: &str,
+        target: &DiffTarget,
+        show_secrets: bool,
+    ) -> Result<ProviderDiff, AppError> {
+        diff::diff(state, app_type, id, target, show_secrets)
+    }
+
+    /// Summary of the current provider of an app and whether live config matches it
+    pub fn current_status(state: &AppState, app_type: AppType) -> Result<CurrentStatus, AppError> {
+        status::status(state, app_type)
+    }
+
+    /// Prompt segment of the current provider, taken from the cache when it is still valid
+    pub fn cached_prompt_segment(app_type: &AppType) -> Option<PromptSegment> {
+        segment::cached(app_type)
+    }
+
+    /// Prompt segment of the current provider, read from the database
+    pub fn prompt_segment(state: &AppState, app_type: AppType) -> Result<PromptSegment, AppError> {
+        segment::build(state, app_type)
+    }
+
+    /// Cache a prompt segment for [`ProviderService::cached_prompt_segment`]
+    pub fn cache_prompt_segment(segment: &PromptSegment) {
+        segment::store(segment)
+    }
+
+    /// Restore the settings of a provider to an earlier revision or point in time
+    pub fn restore_revision(
+        state: &AppState,
+        app_type: AppType,
+        id: &str,
+        target: RestoreTarget,
+    ) -> Result<RestoreResult, AppError> {
+        journal::restore(state, app_type, id, target)
+    }
+
+    /// Record a successful switch made outside [`ProviderService::switch_from`]
+    pub(crate) fn record_switch(
+        state: &AppState,
+        app: &str,
+        provider: &Provider,
+        cwd: Option<&str>,
+    ) {
+        history::record(state, app, provider, cwd);
     }
 
     /// Normal switch flow (non-proxy mode)
@@ -263,10 +551,10 @@ impl ProviderService {
         app_type: AppType,
         id: &str,
         providers: &indexmap::IndexMap<String, Provider>,
-    ) -> Result<(), AppError> {
+    ) -> Result<Vec<FileWriteResult>, AppError> {
         let provider = providers
             .get(id)
-            .ok_or_else(|| AppError::Message(format!("供应商 {id} 不存在")))?;
+            .ok_or_else(|| AppError::provider_not_found(id, app_type.as_str()))?;
 
         // Backfill: Backfill current live config to current provider
         // Use effective current provider (validated existence) to ensure backfill targets valid provider
@@ -278,6 +566,18 @@ impl ProviderService {
                 if let Ok(live_config) = read_live_settings(app_type.clone()) {
                     if let Some(mut current_provider) = providers.get(&current_id).cloned() {
                         current_provider.settings_config = live_config;
+                        // Device-level keys such as statusLine stay with the live file
+                        if matches!(app_type, AppType::Claude) {
+                            if let Some(obj) = current_provider.settings_config.as_object_mut() {
+                                for key in live::CLAUDE_DEVICE_KEYS {
+                                    obj.remove(*key);
+                                }
+                            }
+                        }
+                        // The ChatGPT login lives in its snapshot file, not in the database
+                        if matches!(app_type, AppType::Codex) && current_id == OFFICIAL_LOGIN_ID {
+                            current_provider.settings_config["auth"] = serde_json::json!({});
+                        }
                         // Ignore backfill failure, don't affect switch flow
                         let _ = state.db.save_provider(app_type.as_str(), &current_provider);
                     }
@@ -291,13 +591,21 @@ impl ProviderService {
         // Update database is_current (as default for new devices)
         state.db.set_current_provider(app_type.as_str(), id)?;
 
+        // Providers with a key pool get their next key at switch time
+        let provider = keys::apply_pool(&state.db, &app_type, provider, KeyRotation::Switch);
+
+        // Keep a ChatGPT login that the new auth.json would replace
+        if matches!(app_type, AppType::Codex) {
+            codex_login::preserve_login(state, &provider)?;
+        }
+
         // Sync to live (write_gemini_live handles security flag internally for Gemini)
-        write_live_snapshot(&app_type, provider)?;
+        let files = write_live_snapshot(&app_type, &provider)?;
 
         // Sync MCP
         McpService::sync_all_enabled(state)?;
 
-        Ok(())
+        Ok(files)
     }
 
     /// Sync current provider to live configuration (re-export)
@@ -309,6 +617,7 @@ impl ProviderService {
     ///
     /// Returns `Ok(true)` if imported, `Ok(false)` if skipped.
     pub fn import_default_config(state: &AppState, app_type: AppType) -> Result<bool, AppError> {
+        let _lock = OperationLock::acquire()?;
         import_default_config(state, app_type)
     }
 
@@ -356,6 +665,25 @@ impl ProviderService {
         endpoints::update_endpoint_last_used(state, app_type, provider_id, url)
     }
 
+    /// Race the base URL and custom endpoints of a provider, fastest first
+    pub async fn test_endpoints(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+    ) -> Result<Vec<EndpointTiming>, AppError> {
+        endpoints::test_endpoints(state, app_type, provider_id).await
+    }
+
+    /// Select the endpoint written to the live config (`None` = base URL)
+    pub fn set_active_endpoint(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+        url: Option<String>,
+    ) -> Result<Provider, AppError> {
+        endpoints::set_active_endpoint(state, app_type, provider_id, url)
+    }
+
     /// Update provider sort order
     pub fn update_sort_order(
         state: &AppState,
@@ -374,6 +702,952 @@ impl ProviderService {
         Ok(true)
     }
 
+    /// Rewrite the provider order (providers not listed keep their relative order after)
+    pub fn reorder(
+        state: &AppState,
+        app_type: AppType,
+        ordered_ids: &[String],
+    ) -> Result<(), AppError> {
+        state.db.reorder_providers(app_type.as_str(), ordered_ids)
+    }
+
+    /// Move one provider within the list; returns its new (0-based) position
+    pub fn move_provider(
+        state: &AppState,
+        app_type: AppType,
+        id: &str,
+        movement: ProviderMove,
+    ) -> Result<usize, AppError> {
+        let mut order: Vec<String> = state
+            .db
+            .get_all_providers(app_type.as_str())?
+            .into_keys()
+            .collect();
+        let from = order
+            .iter()
+            .position(|p| p == id)
+            .ok_or_else(|| AppError::provider_not_found(id, app_type.as_str()))?;
+
+        let last = order.len() - 1;
+        let to = match movement {
+            ProviderMove::To(position) => position.min(last),
+            ProviderMove::Up => from.saturating_sub(1),
+            ProviderMove::Down => (from + 1).min(last),
+        };
+
+        let moved = order.remove(from);
+        order.insert(to, moved);
+        state.db.reorder_providers(app_type.as_str(), &order)?;
+        Ok(to)
+    }
+
+    /// Fill missing provider metadata from the known vendor registry (re-export)
+    ///
+    /// With `apply = false` only a preview is returned.
+    pub fn enrich(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+        apply: bool,
+    ) -> Result<EnrichResult, AppError> {
+        registry::enrich_provider(state, app_type, provider_id, apply)
+    }
+
+    /// Duplicate a provider under a fresh id, optionally with a different API key
+    pub fn clone_provider(
+        state: &AppState,
+        app_type: AppType,
+        source_id: &str,
+        name: Option<&str>,
+        api_key: Option<&str>,
+    ) -> Result<Provider, AppError> {
+        let mut provider = state
+            .db
+            .clone_provider(app_type.as_str(), source_id, name)?;
+
+        if let Some(api_key) = api_key.map(str::trim).filter(|k| !k.is_empty()) {
+            Self::set_api_key(&app_type, &mut provider.settings_config, api_key)?;
+            state.db.save_provider(app_type.as_str(), &provider)?;
+        }
+
+        Ok(provider)
+    }
+
+    /// Add a tag to a provider
+    pub fn add_tag(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+        tag: &str,
+    ) -> Result<(), AppError> {
+        state.db.add_tag(app_type.as_str(), provider_id, tag)
+    }
+
+    /// Remove a tag from a provider
+    pub fn remove_tag(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+        tag: &str,
+    ) -> Result<(), AppError> {
+        state.db.remove_tag(app_type.as_str(), provider_id, tag)
+    }
+
+    /// Add the same tag to several providers (single transaction)
+    /// Pin or unpin a provider; pinned providers are listed first by the
+    /// interactive pickers and take the quick-switch slots `cc-switch 1`..`9`
+    pub fn set_pinned(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+        pinned: bool,
+    ) -> Result<(), AppError> {
+        if !state
+            .db
+            .set_provider_pinned(app_type.as_str(), provider_id, pinned)?
+        {
+            return Err(AppError::provider_not_found(provider_id, app_type.as_str()));
+        }
+        Ok(())
+    }
+
+    /// Set or clear a provider's alias (`work`, `cheap`, ...)
+    ///
+    /// Aliases are unique per app and are accepted wherever a provider ID is
+    /// (see [`ProviderService::resolve_id`]).
+    pub fn set_alias(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+        alias: Option<&str>,
+    ) -> Result<(), AppError> {
+        let alias = alias.map(str::trim).filter(|alias| !alias.is_empty());
+        if alias.is_some_and(|alias| alias.chars().any(char::is_whitespace)) {
+            return Err(AppError::localized(
+                "provider.alias.invalid",
+                "别名不能包含空白字符",
+                "Aliases cannot contain whitespace",
+            ));
+        }
+        if !state
+            .db
+            .set_provider_alias(app_type.as_str(), provider_id, alias)?
+        {
+            return Err(AppError::provider_not_found(provider_id, app_type.as_str()));
+        }
+        Ok(())
+    }
+
+    /// The provider ID for an ID or alias; unknown values are returned as-is so
+    /// the caller reports the usual "not found" error
+    pub fn resolve_id(
+        state: &AppState,
+        app_type: &AppType,
+        id_or_alias: &str,
+    ) -> Result<String, AppError> {
+        Ok(state
+            .db
+            .resolve_provider_id(app_type.as_str(), id_or_alias)?
+            .unwrap_or_else(|| id_or_alias.to_string()))
+    }
+
+    /// Pinned providers in slot order (the list's sort order)
+    pub fn pinned(state: &AppState, app_type: AppType) -> Result<Vec<Provider>, AppError> {
+        let (mut providers, pinned) = Self::list_pinned_first(state, app_type)?;
+        providers.truncate(pinned);
+        Ok(providers)
+    }
+
+    /// All providers with the pinned ones moved to the front, and how many are pinned
+    pub fn list_pinned_first(
+        state: &AppState,
+        app_type: AppType,
+    ) -> Result<(Vec<Provider>, usize), AppError> {
+        let mut providers = state.db.get_all_providers(app_type.as_str())?;
+        let mut ordered: Vec<Provider> = state
+            .db
+            .get_pinned_provider_ids(app_type.as_str())?
+            .iter()
+            .filter_map(|id| providers.shift_remove(id))
+            .collect();
+        let pinned = ordered.len();
+        ordered.extend(providers.into_values());
+        Ok((ordered, pinned))
+    }
+
+    pub fn add_tag_to_many(
+        state: &AppState,
+        app_type: AppType,
+        ids: &[String],
+        tag: &str,
+    ) -> Result<usize, AppError> {
+        state.db.add_tag_to_providers(app_type.as_str(), ids, tag)
+    }
+
+    /// Collect the selected providers (in sort order) for export
+    pub fn export_selected(
+        state: &AppState,
+        app_type: AppType,
+        ids: &[String],
+    ) -> Result<Vec<Provider>, AppError> {
+        let providers = state.db.get_all_providers(app_type.as_str())?;
+        if let Some(missing) = ids.iter().find(|id| !providers.contains_key(*id)) {
+            return Err(AppError::provider_not_found(missing, app_type.as_str()));
+        }
+
+        Ok(providers
+            .into_values()
+            .filter(|p| ids.contains(&p.id))
+            .collect())
+    }
+
+    /// Providers of an app as an export bundle (empty `ids` exports all of them)
+    pub fn export_bundle(
+        state: &AppState,
+        app_type: AppType,
+        ids: &[String],
+    ) -> Result<ExportBundle, AppError> {
+        bundle::export(state, app_type, ids)
+    }
+
+    /// Validate an export bundle against its JSON Schema and add its providers,
+    /// skipping ids that already exist
+    ///
+    /// Older bundle formats are upgraded first; `app` is only used for bare
+    /// provider maps, which don't record the app they came from.
+    pub fn import_bundle(
+        state: &AppState,
+        value: &Value,
+        app: Option<AppType>,
+    ) -> Result<BundleImport, AppError> {
+        bundle::import(state, bundle::parse(value, app.as_ref())?)
+    }
+
+    /// Convert providers to another tool's config format
+    ///
+    /// Empty `ids` exports every provider of the app.
+    pub fn export_as(
+        state: &AppState,
+        app_type: AppType,
+        ids: &[String],
+        format: ExportFormat,
+    ) -> Result<String, AppError> {
+        let providers: Vec<Provider> = if ids.is_empty() {
+            state
+                .db
+                .get_all_providers(app_type.as_str())?
+                .into_values()
+                .collect()
+        } else {
+            Self::export_selected(state, app_type.clone(), ids)?
+        };
+        Ok(Self::render_export(&app_type, &providers, format))
+    }
+
+    /// Shell statements that apply a provider to the current shell session only
+    pub fn shell_env(
+        state: &AppState,
+        app_type: AppType,
+        id: &str,
+        shell: ShellKind,
+    ) -> Result<String, AppError> {
+        let provider = state
+            .db
+            .get_provider_by_id(id, app_type.as_str())?
+            .ok_or_else(|| AppError::provider_not_found(id, app_type.as_str()))?;
+        let provider = live::prepare_live_provider(&app_type, &provider)?;
+        Ok(export::shell_exports(&app_type, &provider, shell))
+    }
+
+    /// Variables for running a command with a provider (default: the current one)
+    /// without touching the live config
+    ///
+    /// A provider with a key pool gets the next key from it for each session.
+    pub fn session_env(
+        state: &AppState,
+        app_type: AppType,
+        id: Option<&str>,
+    ) -> Result<Vec<(String, String)>, AppError> {
+        let id = match id {
+            Some(id) => id.to_string(),
+            None => crate::settings::get_effective_current_provider(&state.db, &app_type)?
+                .ok_or_else(|| {
+                    AppError::InvalidInput(format!(
+                        "{} 没有当前供应商，请用 --provider 指定",
+                        app_type.as_str()
+                    ))
+                })?,
+        };
+        let provider = state
+            .db
+            .get_provider_by_id(&id, app_type.as_str())?
+            .ok_or_else(|| AppError::provider_not_found(&id, app_type.as_str()))?;
+        let provider = keys::next_key(&state.db, &app_type, &provider);
+        session::session_env(&app_type, &provider)
+    }
+
+    /// Run a command with extra variables, forwarding signals; returns its exit code
+    pub fn run_session(command: &[String], env: &[(String, String)]) -> Result<i32, AppError> {
+        session::run(command, env)
+    }
+
+    /// Render already collected providers in another tool's config format
+    pub fn render_export(
+        app_type: &AppType,
+        providers: &[Provider],
+        format: ExportFormat,
+    ) -> String {
+        export::render(app_type, providers, format)
+    }
+
+    /// List providers carrying the given tag (in sort order)
+    pub fn list_by_tag(
+        state: &AppState,
+        app_type: AppType,
+        tag: &str,
+    ) -> Result<Vec<Provider>, AppError> {
+        state.db.find_by_tag(app_type.as_str(), tag)
+    }
+
+    /// Validate stored providers, returning only those with issues
+    ///
+    /// Checks every app when `app_type` is `None`.
+    pub fn validate_all(
+        state: &AppState,
+        app_type: Option<AppType>,
+    ) -> Result<Vec<ProviderValidationReport>, AppError> {
+        let apps = match app_type {
+            Some(app_type) => vec![app_type],
+            None => vec![AppType::Claude, AppType::Codex, AppType::Gemini],
+        };
+
+        let mut reports = Vec::new();
+        for app_type in apps {
+            for provider in state.db.get_all_providers(app_type.as_str())?.into_values() {
+                let issues = validate_provider(&provider, &app_type);
+                if !issues.is_empty() {
+                    reports.push(ProviderValidationReport {
+                        app: app_type.as_str().to_string(),
+                        id: provider.id,
+                        name: provider.name,
+                        issues,
+                    });
+                }
+            }
+        }
+        Ok(reports)
+    }
+
+    /// Spending limit status of every provider that has a limit configured
+    ///
+    /// Checks every app when `app_type` is `None`.
+    pub fn limits_status(
+        state: &AppState,
+        app_type: Option<AppType>,
+    ) -> Result<Vec<LimitReport>, AppError> {
+        let apps = match app_type {
+            Some(app_type) => vec![app_type],
+            None => vec![AppType::Claude, AppType::Codex, AppType::Gemini],
+        };
+
+        let mut reports = Vec::new();
+        for app_type in apps {
+            let current = Self::current(state, app_type.clone())?;
+            for provider in state.db.get_all_providers(app_type.as_str())?.into_values() {
+                if let Some(status) = limits::status(&state.db, app_type.as_str(), &provider)? {
+                    reports.push(LimitReport {
+                        app: app_type.as_str().to_string(),
+                        is_current: provider.id == current,
+                        provider_name: provider.name,
+                        status,
+                    });
+                }
+            }
+        }
+        Ok(reports)
+    }
+
+    /// Warning for a Codex provider whose auth.json would replace a ChatGPT login
+    pub fn codex_login_warning(app_type: &AppType, provider: &Provider) -> Option<String> {
+        match app_type {
+            AppType::Codex => codex_login::warning(provider),
+            _ => None,
+        }
+    }
+
+    /// Problems found in the app's live config files (`cc-switch lint`)
+    pub fn lint_live(app_type: &AppType) -> Result<Vec<LintIssue>, AppError> {
+        lint::lint(app_type)
+    }
+
+    /// Warning for a provider that is over its spending limit
+    pub fn limit_warning(
+        state: &AppState,
+        app_type: &AppType,
+        provider: &Provider,
+    ) -> Option<String> {
+        limits::status(&state.db, app_type.as_str(), provider)
+            .ok()
+            .flatten()
+            .and_then(|status| limits::describe(provider, &status))
+    }
+
+    /// Find providers of every app that contain a key or endpoint
+    pub fn find_references(
+        state: &AppState,
+        target: &LookupTarget,
+    ) -> Result<Vec<ProviderReference>, AppError> {
+        let mut references = Vec::new();
+        for app in AppRegistry::load().all() {
+            let current = Self::registry_current(state, app)?;
+            for provider in state.db.get_all_providers(&app.id)?.into_values() {
+                let fields = lookup::find_fields(&provider, target);
+                if !fields.is_empty() {
+                    references.push(ProviderReference {
+                        app: app.id.clone(),
+                        is_current: provider.id == current,
+                        id: provider.id,
+                        name: provider.name,
+                        fields,
+                    });
+                }
+            }
+        }
+        Ok(references)
+    }
+
+    /// Revoke an API key everywhere it is used
+    ///
+    /// `target` is either the key itself or a provider id, in which case that
+    /// provider's key is revoked. The key is blanked in every provider of every
+    /// registered app and dropped from their key pools, affected providers are
+    /// optionally tagged `archived`, current ones are re-applied to the live config,
+    /// the key is scrubbed from the provider history (so a restore cannot bring it
+    /// back), and the action is written to the audit log.
+    pub fn revoke_key(
+        state: &AppState,
+        target: &str,
+        archive: bool,
+    ) -> Result<Vec<ProviderReference>, AppError> {
+        let apps = AppRegistry::load().all().to_vec();
+        let target = target.trim();
+
+        // 参数是供应商 ID 时，吊销该供应商当前使用的密钥
+        let mut keys = Vec::new();
+        for app in &apps {
+            let Some(provider) = state.db.get_provider_by_id(target, &app.id)? else {
+                continue;
+            };
+            let found = match app.app_type() {
+                Some(app_type) => get_adapter(&app_type)
+                    .extract_auth(&provider)
+                    .map(|auth| vec![auth.api_key])
+                    .unwrap_or_default(),
+                None => lookup::api_keys(&provider.settings_config),
+            };
+            for key in found {
+                if !key.is_empty() && !keys.contains(&key) {
+                    keys.push(key);
+                }
+            }
+        }
+        if keys.is_empty() {
+            // 过短的片段可能误伤大量无关配置
+            if target.chars().count() < 8 {
+                return Err(AppError::InvalidInput(
+                    "未找到该供应商，且作为密钥过短（至少 8 个字符）".to_string(),
+                ));
+            }
+            keys.push(target.to_string());
+        }
+
+        let mut revoked = Vec::new();
+        for app in &apps {
+            let current = Self::registry_current(state, app)?;
+            for mut provider in state.db.get_all_providers(&app.id)?.into_values() {
+                let mut fields = Vec::new();
+                for key in &keys {
+                    fields.extend(lookup::find_fields(
+                        &provider,
+                        &LookupTarget::Key(key.clone()),
+                    ));
+                    lookup::strip_key(&mut provider, key)?;
+                    if state.db.remove_provider_key(&app.id, &provider.id, key)? {
+                        fields.push("keyPool".to_string());
+                    }
+                }
+                if fields.is_empty() {
+                    continue;
+                }
+
+                state.db.save_provider(&app.id, &provider)?;
+                if archive {
+                    state.db.add_tag(&app.id, &provider.id, ARCHIVED_TAG)?;
+                }
+                let is_current = provider.id == current;
+                if is_current {
+                    match app.app_type() {
+                        Some(app_type) => Self::refresh_current_live(state, &app_type, &provider)?,
+                        None => app.write_live(&provider.settings_config)?,
+                    }
+                }
+
+                let hints: Vec<String> = keys.iter().map(|key| mask_secret(key)).collect();
+                let detail = format!(
+                    "revoked {}{}",
+                    hints.join(", "),
+                    if archive { "; archived" } else { "" }
+                );
+                state.db.record_audit(
+                    "key.revoke",
+                    Some(&app.id),
+                    Some(&provider.id),
+                    Some(&detail),
+                )?;
+                log::info!(
+                    "已吊销 {} 供应商 {} 中的密钥 {}",
+                    app.id,
+                    provider.id,
+                    hints.join(", ")
+                );
+
+                revoked.push(ProviderReference {
+                    app: app.id.clone(),
+                    id: provider.id,
+                    name: provider.name,
+                    is_current,
+                    fields,
+                });
+            }
+        }
+
+        // 上面的保存也会把旧值记入变更日志，因此最后统一抹去
+        for key in &keys {
+            state.db.scrub_provider_history(key)?;
+        }
+        Ok(revoked)
+    }
+
+    /// Current provider id of a registered app (empty when unset)
+    fn registry_current(state: &AppState, app: &AppDefinition) -> Result<String, AppError> {
+        match app.app_type() {
+            Some(app_type) => Self::current(state, app_type),
+            None => Ok(state.db.get_current_provider(&app.id)?.unwrap_or_default()),
+        }
+    }
+
+    /// Render the provider list as a plain-text table (in sort order)
+    pub fn render_table(
+        state: &AppState,
+        app_type: AppType,
+        columns: &[TableColumn],
+        style: TableStyle,
+    ) -> Result<String, AppError> {
+        let providers = state.db.get_all_providers(app_type.as_str())?;
+        Self::render_table_of(state, &app_type, &providers, columns, style)
+    }
+
+    /// Render a subset of the providers as a table (e.g. one vendor group)
+    pub fn render_table_of(
+        state: &AppState,
+        app_type: &AppType,
+        providers: &IndexMap<String, Provider>,
+        columns: &[TableColumn],
+        style: TableStyle,
+    ) -> Result<String, AppError> {
+        let current = Self::current(state, app_type.clone())?;
+        let health = state.db.get_provider_health_map(app_type.as_str())?;
+        let latency = Self::recent_benchmarks(state, app_type)?
+            .into_iter()
+            .filter_map(|(id, result)| result.ttfb_ms.map(|ms| (id, ms)))
+            .collect();
+        Ok(table::render(
+            app_type, providers, &current, &health, &latency, columns, style,
+        ))
+    }
+
+    /// The provider with the lowest latency in recent benchmarks
+    ///
+    /// Only successful runs from the last 24 hours count, and providers over
+    /// their spending limit are skipped when limits are enforced.
+    pub fn fastest(state: &AppState, app_type: AppType) -> Result<Option<Provider>, AppError> {
+        let mut results: Vec<BenchmarkResult> = Self::recent_benchmarks(state, &app_type)?
+            .into_values()
+            .collect();
+        BenchService::rank(&mut results);
+
+        let providers = state.db.get_all_providers(app_type.as_str())?;
+        let blocking = LimitAction::current() == LimitAction::Block;
+        for result in results {
+            let Some(provider) = providers.get(&result.provider_id) else {
+                continue;
+            };
+            if blocking && Self::limit_warning(state, &app_type, provider).is_some() {
+                continue;
+            }
+            return Ok(Some(provider.clone()));
+        }
+        Ok(None)
+    }
+
+    fn recent_benchmarks(
+        state: &AppState,
+        app_type: &AppType,
+    ) -> Result<HashMap<String, BenchmarkResult>, AppError> {
+        let since = chrono::Utc::now().timestamp_millis() - RECENT_BENCHMARK_MS;
+        state.db.get_recent_benchmarks(app_type.as_str(), since)
+    }
+
+    /// Render an SDK client setup snippet for a provider
+    pub fn snippet(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+        lang: SnippetLang,
+    ) -> Result<String, AppError> {
+        let provider = state
+            .db
+            .get_provider_by_id(provider_id, app_type.as_str())?
+            .ok_or_else(|| AppError::provider_not_found(provider_id, app_type.as_str()))?;
+        Ok(snippet::render(&app_type, &provider, lang))
+    }
+
+    /// Full-text search over provider name, notes, category and endpoint
+    pub fn search(
+        state: &AppState,
+        app_type: AppType,
+        query: &str,
+    ) -> Result<Vec<Provider>, AppError> {
+        state.db.search_providers(app_type.as_str(), query)
+    }
+
+    /// One page of providers, sorted and optionally filtered
+    pub fn query(
+        state: &AppState,
+        app_type: AppType,
+        options: &QueryOptions,
+    ) -> Result<ProviderPage, AppError> {
+        state.db.query_providers(app_type.as_str(), options)
+    }
+
+    /// Mark a provider as the default of its category
+    ///
+    /// Returns the category name.
+    pub fn set_category_default(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+    ) -> Result<String, AppError> {
+        state
+            .db
+            .set_category_default(app_type.as_str(), provider_id)
+    }
+
+    /// Remove the default provider of a category
+    pub fn clear_category_default(
+        state: &AppState,
+        app_type: AppType,
+        category: &str,
+    ) -> Result<(), AppError> {
+        state.db.clear_category_default(app_type.as_str(), category)
+    }
+
+    /// Category defaults of an app (category -> provider id)
+    pub fn category_defaults(
+        state: &AppState,
+        app_type: AppType,
+    ) -> Result<BTreeMap<String, String>, AppError> {
+        state.db.get_category_defaults(app_type.as_str())
+    }
+
+    /// Default provider of a category
+    pub fn category_default_provider(
+        state: &AppState,
+        app_type: &AppType,
+        category: &str,
+    ) -> Result<Provider, AppError> {
+        let no_default = || {
+            AppError::localized(
+                "provider.category.no_default",
+                format!("分类 {category} 没有默认供应商"),
+                format!("Category {category} has no default provider"),
+            )
+        };
+        let id = state
+            .db
+            .get_category_default(app_type.as_str(), category)?
+            .ok_or_else(no_default)?;
+
+        // 默认供应商之后可能被改到其他分类，此时视为没有默认值
+        state
+            .db
+            .get_provider_by_id(&id, app_type.as_str())?
+            .filter(|p| p.category.as_deref().map(str::trim) == Some(category.trim()))
+            .ok_or_else(no_default)
+    }
+
+    /// Switch to the default provider of a category
+    ///
+    /// Returns the id of the provider switched to.
+    pub fn switch_by_category(
+        state: &AppState,
+        app_type: AppType,
+        category: &str,
+    ) -> Result<String, AppError> {
+        let provider = Self::category_default_provider(state, &app_type, category)?;
+        Self::switch(state, app_type, &provider.id)?;
+        Ok(provider.id)
+    }
+
+    /// Set the provider's default model (an empty string clears it)
+    ///
+    /// `small_fast_model` is Claude-only. If the provider is current, the live
+    /// config is rewritten with the new model.
+    pub fn set_model(
+        state: &AppState,
+        app_type: AppType,
+        id: &str,
+        model: &str,
+        small_fast_model: Option<&str>,
+    ) -> Result<Provider, AppError> {
+        if small_fast_model.is_some() && !matches!(app_type, AppType::Claude) {
+            return Err(AppError::InvalidInput(
+                "小型快速模型仅适用于 Claude".to_string(),
+            ));
+        }
+        let mut provider = state
+            .db
+            .get_provider_by_id(id, app_type.as_str())?
+            .ok_or_else(|| AppError::provider_not_found(id, app_type.as_str()))?;
+
+        let non_empty = |value: &str| Some(value.trim().to_string()).filter(|v| !v.is_empty());
+        let meta = provider.meta.get_or_insert_with(Default::default);
+        meta.default_model = non_empty(model);
+        if let Some(small_fast) = small_fast_model {
+            meta.small_fast_model = non_empty(small_fast);
+        }
+
+        Self::update(state, app_type, provider.clone())?;
+        Ok(provider)
+    }
+
+    /// Environment variables stored on a provider
+    pub fn env(
+        state: &AppState,
+        app_type: AppType,
+        id: &str,
+    ) -> Result<BTreeMap<String, String>, AppError> {
+        let provider = state
+            .db
+            .get_provider_by_id(id, app_type.as_str())?
+            .ok_or_else(|| AppError::provider_not_found(id, app_type.as_str()))?;
+        Ok(env::provider_env(&provider))
+    }
+
+    /// Set and remove extra environment variables on a provider
+    ///
+    /// If the provider is current, the live config is rewritten. Returns the
+    /// provider's variables after the change.
+    pub fn set_env(
+        state: &AppState,
+        app_type: AppType,
+        id: &str,
+        set: &BTreeMap<String, String>,
+        unset: &[String],
+    ) -> Result<BTreeMap<String, String>, AppError> {
+        let mut provider = state
+            .db
+            .get_provider_by_id(id, app_type.as_str())?
+            .ok_or_else(|| AppError::provider_not_found(id, app_type.as_str()))?;
+        if env::apply_env_changes(&mut provider, set, unset)? {
+            let vars = env::provider_env(&provider);
+            Self::update(state, app_type, provider)?;
+            return Ok(vars);
+        }
+        Ok(env::provider_env(&provider))
+    }
+
+    /// Change a provider's HTTP/SOCKS proxy settings
+    ///
+    /// The proxy is exported into the app's env on every live write; if the
+    /// provider is current, the live config is rewritten right away.
+    pub fn set_proxy(
+        state: &AppState,
+        app_type: AppType,
+        id: &str,
+        proxy: &ProviderProxy,
+    ) -> Result<Provider, AppError> {
+        let mut provider = state
+            .db
+            .get_provider_by_id(id, app_type.as_str())?
+            .ok_or_else(|| AppError::provider_not_found(id, app_type.as_str()))?;
+        proxy.apply(provider.meta.get_or_insert_with(Default::default))?;
+        Self::update(state, app_type, provider.clone())?;
+        Ok(provider)
+    }
+
+    /// Switch hooks of a provider, or the global ones when `provider_id` is None
+    pub fn get_hooks(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: Option<&str>,
+    ) -> Result<SwitchHooks, AppError> {
+        let Some(id) = provider_id else {
+            return Ok(crate::settings::get_settings().hooks.unwrap_or_default());
+        };
+        let provider = state
+            .db
+            .get_provider_by_id(id, app_type.as_str())?
+            .ok_or_else(|| AppError::provider_not_found(id, app_type.as_str()))?;
+        Ok(provider.meta.and_then(|m| m.hooks).unwrap_or_default())
+    }
+
+    /// Replace the switch hooks of a provider, or the global ones when `provider_id` is None
+    pub fn set_hooks(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: Option<&str>,
+        hooks: SwitchHooks,
+    ) -> Result<(), AppError> {
+        let hooks = Some(hooks).filter(|h| !h.is_empty());
+        let Some(id) = provider_id else {
+            let mut settings = crate::settings::get_settings();
+            settings.hooks = hooks;
+            return crate::settings::update_settings(settings);
+        };
+        let mut provider = state
+            .db
+            .get_provider_by_id(id, app_type.as_str())?
+            .ok_or_else(|| AppError::provider_not_found(id, app_type.as_str()))?;
+        provider.meta.get_or_insert_with(Default::default).hooks = hooks;
+        state.db.save_provider(app_type.as_str(), &provider)
+    }
+
+    /// Keys in a provider's pool, in the order they were added
+    pub fn list_keys(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+    ) -> Result<Vec<ProviderKey>, AppError> {
+        let app = app_type.as_str();
+        if state.db.get_provider_by_id(provider_id, app)?.is_none() {
+            return Err(AppError::provider_not_found(provider_id, app));
+        }
+        state.db.get_provider_keys(app, provider_id)
+    }
+
+    /// Add a key to a provider's pool (the first one also adds the provider's own key)
+    ///
+    /// Returns whether the key was new; for a known key only label and weight change.
+    pub fn add_key(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+        api_key: &str,
+        label: Option<&str>,
+        weight: u32,
+    ) -> Result<bool, AppError> {
+        keys::add_key(state, &app_type, provider_id, api_key, label, weight)
+    }
+
+    /// Remove a key from a provider's pool, by value or by its id in the pool
+    pub fn remove_key(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+        key_or_id: &str,
+    ) -> Result<bool, AppError> {
+        state
+            .db
+            .remove_provider_key(app_type.as_str(), provider_id, key_or_id.trim())
+    }
+
+    /// Set how a provider's key pool is rotated; `None` leaves a setting as is
+    pub fn set_key_rotation(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+        strategy: Option<KeyStrategy>,
+        rotation: Option<KeyRotation>,
+    ) -> Result<Provider, AppError> {
+        let mut provider = state
+            .db
+            .get_provider_by_id(provider_id, app_type.as_str())?
+            .ok_or_else(|| AppError::provider_not_found(provider_id, app_type.as_str()))?;
+        let meta = provider.meta.get_or_insert_with(Default::default);
+        if strategy.is_some() {
+            meta.key_strategy = strategy;
+        }
+        if rotation.is_some() {
+            meta.key_rotation = rotation;
+        }
+        state.db.save_provider(app_type.as_str(), &provider)?;
+        Ok(provider)
+    }
+
+    /// Set or clear the key expiry date and rotation interval
+    pub fn set_key_policy(
+        state: &AppState,
+        app_type: AppType,
+        id: &str,
+        policy: &KeyPolicy,
+    ) -> Result<Provider, AppError> {
+        let mut provider = state
+            .db
+            .get_provider_by_id(id, app_type.as_str())?
+            .ok_or_else(|| AppError::provider_not_found(id, app_type.as_str()))?;
+        policy.apply(provider.meta.get_or_insert_with(Default::default))?;
+        Self::update(state, app_type, provider.clone())?;
+        Ok(provider)
+    }
+
+    /// Replace a provider's API key, keeping the old key's fingerprint in its meta
+    ///
+    /// The live config is rewritten when the provider is current. `expires_at`
+    /// sets the expiry date of the new key.
+    pub fn rotate_key(
+        state: &AppState,
+        app_type: AppType,
+        id: &str,
+        api_key: &str,
+        expires_at: Option<&str>,
+    ) -> Result<Provider, AppError> {
+        rotation::rotate_key(state, &app_type, id, api_key, expires_at)
+    }
+
+    /// First provider (in sort order) carrying the given tag
+    pub fn first_by_tag(
+        state: &AppState,
+        app_type: &AppType,
+        tag: &str,
+    ) -> Result<Provider, AppError> {
+        state
+            .db
+            .find_by_tag(app_type.as_str(), tag)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                AppError::localized(
+                    "provider.tag.no_match",
+                    format!("没有带标签 {tag} 的供应商"),
+                    format!("No provider is tagged {tag}"),
+                )
+            })
+    }
+
+    /// Switch to the first provider (in sort order) carrying the given tag
+    ///
+    /// Returns the id of the provider switched to.
+    pub fn switch_by_tag(
+        state: &AppState,
+        app_type: AppType,
+        tag: &str,
+    ) -> Result<String, AppError> {
+        let provider = Self::first_by_tag(state, &app_type, tag)?;
+        Self::switch(state, app_type, &provider.id)?;
+        Ok(provider.id)
+    }
+
     /// Query provider usage (re-export)
     pub async fn query_usage(
         state: &AppState,
@@ -383,12 +1657,21 @@ impl ProviderService {
         usage::query_usage(state, app_type, provider_id).await
     }
 
+    /// Query usage of all providers with usage configured (re-export)
+    pub async fn query_all_usage(
+        state: &AppState,
+        app_type: AppType,
+    ) -> Result<Vec<ProviderUsageSummary>, AppError> {
+        usage::query_all_usage(state, app_type).await
+    }
+
     /// Test usage script (re-export)
     #[allow(clippy::too_many_arguments)]
     pub async fn test_usage_script(
         state: &AppState,
         app_type: AppType,
         provider_id: &str,
+        language: &str,
         script_code: &str,
         timeout: u64,
         api_key: Option<&str>,
@@ -400,6 +1683,7 @@ impl ProviderService {
             state,
             app_type,
             provider_id,
+            language,
             script_code,
             timeout,
             api_key,
@@ -411,7 +1695,7 @@ impl ProviderService {
     }
 
     pub(crate) fn write_gemini_live(provider: &Provider) -> Result<(), AppError> {
-        write_gemini_live(provider)
+        write_gemini_live(&live::prepare_live_provider(&AppType::Gemini, provider)?)
     }
 
     fn validate_provider_settings(app_type: &AppType, provider: &Provider) -> Result<(), AppError> {
@@ -471,6 +1755,25 @@ impl ProviderService {
             }
         }
 
+        let errors: Vec<String> = validate_provider(provider, app_type)
+            .into_iter()
+            .filter(ValidationIssue::is_error)
+            .map(|issue| format!("{}: {}", issue.field, issue.message))
+            .collect();
+        if !errors.is_empty() {
+            return Err(AppError::localized(
+                "provider.validation.failed",
+                format!("供应商 {} 配置无效: {}", provider.id, errors.join("; ")),
+                format!(
+                    "Provider {} configuration is invalid: {}",
+                    provider.id,
+                    errors.join("; ")
+                ),
+            ));
+        }
+
+        compat::validate_app_version_range(provider)?;
+
         // Validate and clean UsageScript configuration (common for all app types)
         if let Some(meta) = &provider.meta {
             if let Some(usage_script) = &meta.usage_script {
@@ -481,6 +1784,51 @@ impl ProviderService {
         Ok(())
     }
 
+    /// Write the API key into the app-specific location of `settings_config`
+    ///
+    /// Claude keeps whichever of `ANTHROPIC_AUTH_TOKEN` / `ANTHROPIC_API_KEY`
+    /// the provider already uses (defaulting to the former).
+    pub(crate) fn set_api_key(
+        app_type: &AppType,
+        settings: &mut Value,
+        api_key: &str,
+    ) -> Result<(), AppError> {
+        let (section, field) = match app_type {
+            AppType::Claude => {
+                let uses_api_key = settings.pointer("/env/ANTHROPIC_AUTH_TOKEN").is_none()
+                    && settings.pointer("/env/ANTHROPIC_API_KEY").is_some();
+                let field = if uses_api_key {
+                    "ANTHROPIC_API_KEY"
+                } else {
+                    "ANTHROPIC_AUTH_TOKEN"
+                };
+                ("env", field)
+            }
+            AppType::Codex => ("auth", "OPENAI_API_KEY"),
+            AppType::Gemini => ("env", "GEMINI_API_KEY"),
+        };
+
+        let root = settings.as_object_mut().ok_or_else(|| {
+            AppError::localized(
+                "provider.settings.not_object",
+                "配置格式错误: settings_config 必须是对象",
+                "Invalid configuration: settings_config must be an object",
+            )
+        })?;
+        let section = root
+            .entry(section)
+            .or_insert_with(|| Value::Object(Default::default()));
+        let section = section.as_object_mut().ok_or_else(|| {
+            AppError::localized(
+                "provider.settings.section_not_object",
+                format!("配置格式错误: {field} 所在的配置段必须是对象"),
+                format!("Invalid configuration: the section holding {field} must be an object"),
+            )
+        })?;
+        section.insert(field.to_string(), Value::String(api_key.to_string()));
+        Ok(())
+    }
+
     #[allow(dead_code)]
     fn extract_credentials(
         provider: &Provider,
@@ -684,6 +2032,17 @@ pub(crate) fn normalize_claude_models_in_value(settings: &mut Value) -> bool {
     changed
 }
 
+/// How to move a provider within the list
+///
+/// Serialized as `"up"`, `"down"` or `{ "to": <0-based position> }`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProviderMove {
+    To(usize),
+    Up,
+    Down,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct ProviderSortUpdate {
     pub id: String,
@@ -0,0 +1,151 @@
+//! Per-provider model preference
+//!
+//! A provider can carry a default model (and, for Claude, a small/fast model)
+//! in its meta. The preference is injected into the live config on every
+//! write, at the place each app reads it from:
+//!
+//! - Claude: `env.ANTHROPIC_MODEL` / `env.ANTHROPIC_DEFAULT_HAIKU_MODEL`
+//! - Codex: top-level `model` in `config.toml`
+//! - Gemini: `env.GEMINI_MODEL`
+
+use std::borrow::Cow;
+
+use serde_json::Value;
+use toml_edit::DocumentMut;
+
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::provider::Provider;
+
+/// The provider with its model preference applied to `settings_config`
+///
+/// Borrows the provider unchanged when no preference is set.
+pub(crate) fn with_model_preference<'a>(
+    app_type: &AppType,
+    provider: &'a Provider,
+) -> Result<Cow<'a, Provider>, AppError> {
+    let Some(meta) = provider.meta.as_ref() else {
+        return Ok(Cow::Borrowed(provider));
+    };
+    let model = meta.default_model.as_deref().filter(|m| !m.is_empty());
+    let small_fast = meta.small_fast_model.as_deref().filter(|m| !m.is_empty());
+    if model.is_none() && small_fast.is_none() {
+        return Ok(Cow::Borrowed(provider));
+    }
+
+    let mut provider = provider.clone();
+    let settings = &mut provider.settings_config;
+    match app_type {
+        AppType::Claude => {
+            if let Some(model) = model {
+                set_env(settings, "ANTHROPIC_MODEL", model)?;
+            }
+            if let Some(small_fast) = small_fast {
+                set_env(settings, "ANTHROPIC_DEFAULT_HAIKU_MODEL", small_fast)?;
+            }
+        }
+        AppType::Codex => {
+            if let Some(model) = model {
+                set_codex_model(settings, model)?;
+            }
+        }
+        AppType::Gemini => {
+            if let Some(model) = model {
+                set_env(settings, "GEMINI_MODEL", model)?;
+            }
+        }
+    }
+    Ok(Cow::Owned(provider))
+}
+
+fn set_env(settings: &mut Value, key: &str, model: &str) -> Result<(), AppError> {
+    let obj = settings
+        .as_object_mut()
+        .ok_or_else(|| AppError::Config("供应商配置必须是 JSON 对象".to_string()))?;
+    let env = obj
+        .entry("env")
+        .or_insert_with(|| Value::Object(Default::default()));
+    let env = env
+        .as_object_mut()
+        .ok_or_else(|| AppError::Config("供应商配置中的 env 必须是对象".to_string()))?;
+    env.insert(key.to_string(), Value::String(model.to_string()));
+    Ok(())
+}
+
+fn set_codex_model(settings: &mut Value, model: &str) -> Result<(), AppError> {
+    let config = settings
+        .get("config")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let mut doc = config.parse::<DocumentMut>().map_err(|e| {
+        AppError::localized(
+            "codex.config.invalid_toml",
+            format!("供应商 config 不是有效的 TOML: {e}"),
+            format!("Provider config is not valid TOML: {e}"),
+        )
+    })?;
+    doc["model"] = toml_edit::value(model);
+    let obj = settings
+        .as_object_mut()
+        .ok_or_else(|| AppError::Config("Codex 供应商配置必须是 JSON 对象".to_string()))?;
+    obj.insert("config".to_string(), Value::String(doc.to_string()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::ProviderMeta;
+    use serde_json::json;
+
+    fn provider(settings: Value, model: &str, small_fast: Option<&str>) -> Provider {
+        let mut provider = Provider::with_id("p".to_string(), "P".to_string(), settings, None);
+        provider.meta = Some(ProviderMeta {
+            default_model: Some(model.to_string()),
+            small_fast_model: small_fast.map(str::to_string),
+            ..Default::default()
+        });
+        provider
+    }
+
+    #[test]
+    fn injects_model_where_each_app_reads_it() {
+        let claude = provider(
+            json!({ "env": { "ANTHROPIC_MODEL": "old" } }),
+            "claude-sonnet-4-5",
+            Some("claude-haiku-4-5"),
+        );
+        let applied = with_model_preference(&AppType::Claude, &claude).unwrap();
+        assert_eq!(
+            applied.settings_config["env"]["ANTHROPIC_MODEL"],
+            "claude-sonnet-4-5"
+        );
+        assert_eq!(
+            applied.settings_config["env"]["ANTHROPIC_DEFAULT_HAIKU_MODEL"],
+            "claude-haiku-4-5"
+        );
+
+        let codex = provider(
+            json!({ "auth": {}, "config": "model = \"gpt-5\"\nmodel_provider = \"relay\"\n" }),
+            "gpt-5-codex",
+            None,
+        );
+        let applied = with_model_preference(&AppType::Codex, &codex).unwrap();
+        let config = applied.settings_config["config"].as_str().unwrap();
+        assert!(config.contains("model = \"gpt-5-codex\""));
+        assert!(config.contains("model_provider = \"relay\""));
+
+        let gemini = provider(json!({}), "gemini-2.5-pro", None);
+        let applied = with_model_preference(&AppType::Gemini, &gemini).unwrap();
+        assert_eq!(
+            applied.settings_config["env"]["GEMINI_MODEL"],
+            "gemini-2.5-pro"
+        );
+
+        let plain = Provider::with_id("q".to_string(), "Q".to_string(), json!({}), None);
+        assert!(matches!(
+            with_model_preference(&AppType::Claude, &plain).unwrap(),
+            Cow::Borrowed(_)
+        ));
+    }
+}
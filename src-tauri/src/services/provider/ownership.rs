@@ -0,0 +1,175 @@
+//! Config ownership
+//!
+//! Each live config file has a fixed set of JSON pointer paths that cc-switch
+//! owns: the provider's credentials, endpoint and model. Owned paths are
+//! replaced (or cleared) on every switch; everything outside them belongs to
+//! the user (`outputStyle`, `permissions`, hooks, extra env variables, ...) and
+//! is read back from the existing live file and kept on every write, whichever
+//! [`LiveWriteMode`](crate::provider::LiveWriteMode) the provider uses.
+//!
+//! A trailing `*` in the last segment matches any key with that prefix
+//! (`/env/ANTHROPIC_*`), and the empty pointer owns the whole document. Codex
+//! `config.toml` is merged key by key in
+//! [`merge_provider_config`](crate::codex_config::merge_provider_config), which
+//! keeps its own list of provider keys.
+
+use serde_json::{Map, Value};
+
+/// Claude `settings.json`
+pub(crate) const CLAUDE_SETTINGS: &[&str] = &[
+    "/env/ANTHROPIC_*",
+    "/env/API_TIMEOUT_MS",
+    "/env/CLAUDE_CODE_MAX_OUTPUT_TOKENS",
+    "/env/CLAUDE_CODE_DISABLE_NONESSENTIAL_TRAFFIC",
+    "/env/DISABLE_NON_ESSENTIAL_MODEL_CALLS",
+    "/env/OPENROUTER_API_KEY",
+    "/env/HTTP_PROXY",
+    "/env/HTTPS_PROXY",
+    "/env/NO_PROXY",
+    "/apiKeyHelper",
+    "/model",
+];
+
+/// Codex `auth.json` (API key or ChatGPT login)
+pub(crate) const CODEX_AUTH: &[&str] = &["/OPENAI_API_KEY", "/tokens", "/last_refresh"];
+
+/// Gemini `.env`, as a flat object
+pub(crate) const GEMINI_ENV: &[&str] = &[
+    "/GEMINI_*",
+    "/GOOGLE_*",
+    "/HTTP_PROXY",
+    "/HTTPS_PROXY",
+    "/NO_PROXY",
+];
+
+/// Whether `pointer` is one of the `owned` paths or lies below one
+pub(crate) fn is_owned(pointer: &str, owned: &[&str]) -> bool {
+    owned.iter().any(|path| covers(path, pointer))
+}
+
+fn covers(path: &str, pointer: &str) -> bool {
+    if path.is_empty() {
+        return true;
+    }
+    let (parent, last) = path.rsplit_once('/').unwrap_or(("", path));
+    let Some(rest) = pointer.strip_prefix(parent) else {
+        return false;
+    };
+    let Some(rest) = rest.strip_prefix('/') else {
+        return false;
+    };
+    let segment = rest.split('/').next().unwrap_or_default();
+    match last.strip_suffix('*') {
+        Some(prefix) => segment.starts_with(prefix),
+        None => segment == last,
+    }
+}
+
+/// Copy everything from `live` that is outside the owned paths and not already
+/// set in `written`; values in `written` win, objects are merged recursively
+pub(crate) fn preserve_unowned(written: Value, live: &Value, owned: &[&str]) -> Value {
+    match (written, live.as_object()) {
+        (Value::Object(mut written), Some(live)) => {
+            merge_unowned(&mut written, live, "", owned);
+            Value::Object(written)
+        }
+        (written, _) => written,
+    }
+}
+
+fn merge_unowned(
+    written: &mut Map<String, Value>,
+    live: &Map<String, Value>,
+    pointer: &str,
+    owned: &[&str],
+) {
+    for (key, value) in live {
+        let path = format!("{pointer}/{}", key.replace('~', "~0").replace('/', "~1"));
+        if is_owned(&path, owned) {
+            continue;
+        }
+        match (written.get_mut(key), value) {
+            (None, _) => {
+                written.insert(key.clone(), value.clone());
+            }
+            (Some(Value::Object(target)), Value::Object(source)) => {
+                merge_unowned(target, source, &path, owned);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn claude_keeps_user_settings_and_drops_stale_provider_paths() {
+        let live = json!({
+            "env": {
+                "ANTHROPIC_BASE_URL": "https://old.example.com",
+                "ANTHROPIC_SMALL_FAST_MODEL": "old-haiku",
+                "MY_TOOL_FLAG": "1"
+            },
+            "model": "old-model",
+            "outputStyle": "Explanatory",
+            "permissions": { "allow": ["Bash(ls:*)"] },
+            "hooks": { "PostToolUse": [{ "matcher": "Edit", "hooks": [] }] }
+        });
+        let provider = json!({
+            "env": { "ANTHROPIC_BASE_URL": "https://new.example.com" },
+            "permissions": { "deny": ["WebFetch"] }
+        });
+
+        let written = preserve_unowned(provider, &live, CLAUDE_SETTINGS);
+        assert_eq!(
+            written["env"],
+            json!({ "ANTHROPIC_BASE_URL": "https://new.example.com", "MY_TOOL_FLAG": "1" })
+        );
+        assert_eq!(written.get("model"), None);
+        assert_eq!(written["outputStyle"], "Explanatory");
+        assert_eq!(written["hooks"], live["hooks"]);
+        assert_eq!(
+            written["permissions"],
+            json!({ "deny": ["WebFetch"], "allow": ["Bash(ls:*)"] })
+        );
+    }
+
+    #[test]
+    fn codex_auth_replaces_credentials_and_keeps_other_fields() {
+        let live = json!({
+            "OPENAI_API_KEY": null,
+            "tokens": { "access_token": "chatgpt" },
+            "last_refresh": "2025-01-01T00:00:00Z",
+            "workspace_note": "keep"
+        });
+        let written = preserve_unowned(json!({ "OPENAI_API_KEY": "sk-relay" }), &live, CODEX_AUTH);
+        assert_eq!(
+            written,
+            json!({ "OPENAI_API_KEY": "sk-relay", "workspace_note": "keep" })
+        );
+    }
+
+    #[test]
+    fn gemini_env_keeps_unrelated_variables() {
+        let live = json!({
+            "GEMINI_API_KEY": "old",
+            "GOOGLE_CLOUD_PROJECT": "old-project",
+            "DEBUG": "1"
+        });
+        let written = preserve_unowned(json!({ "GEMINI_API_KEY": "new" }), &live, GEMINI_ENV);
+        assert_eq!(written, json!({ "GEMINI_API_KEY": "new", "DEBUG": "1" }));
+    }
+
+    #[test]
+    fn pointer_matching() {
+        assert!(is_owned("/env/ANTHROPIC_MODEL", CLAUDE_SETTINGS));
+        assert!(is_owned("/model", CLAUDE_SETTINGS));
+        assert!(!is_owned("/env", CLAUDE_SETTINGS));
+        assert!(!is_owned("/models", CLAUDE_SETTINGS));
+        assert!(!is_owned("/hooks/model", CLAUDE_SETTINGS));
+        assert!(is_owned("/anything", &[""]));
+    }
+}
@@ -0,0 +1,144 @@
+//! Provider policy
+//!
+//! Teams sharing one database can drop a `policy.json` into the app config
+//! directory to require certain fields before a provider can be saved, and to
+//! supply a notes template the editor pre-fills:
+//!
+//! ```json
+//! {
+//!   "requiredFields": ["notes", "category", "websiteUrl"],
+//!   "notesTemplate": "Owner: \nBilling: \n"
+//! }
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::get_app_config_dir;
+use crate::error::AppError;
+use crate::provider::Provider;
+
+const POLICY_FILE: &str = "policy.json";
+
+/// Provider fields a policy can require
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RequiredField {
+    Notes,
+    Category,
+    WebsiteUrl,
+    Icon,
+}
+
+impl RequiredField {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Notes => "notes",
+            Self::Category => "category",
+            Self::WebsiteUrl => "websiteUrl",
+            Self::Icon => "icon",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderPolicy {
+    #[serde(default)]
+    pub required_fields: Vec<RequiredField>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes_template: Option<String>,
+}
+
+impl ProviderPolicy {
+    /// Load the policy file; a missing file means no policy
+    pub fn load() -> Result<Self, AppError> {
+        let path = get_app_config_dir().join(POLICY_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = std::fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+        serde_json::from_str(&content).map_err(|e| {
+            AppError::localized(
+                "provider.policy.invalid",
+                format!("策略文件 {} 格式错误: {e}", path.display()),
+                format!("Invalid policy file {}: {e}", path.display()),
+            )
+        })
+    }
+
+    /// Reject the provider if any required field is empty
+    ///
+    /// Notes left identical to the template count as empty.
+    pub fn enforce(&self, provider: &Provider) -> Result<(), AppError> {
+        let missing: Vec<&str> = self
+            .required_fields
+            .iter()
+            .filter(|field| !self.is_filled(provider, **field))
+            .map(RequiredField::as_str)
+            .collect();
+
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        let fields = missing.join(", ");
+        Err(AppError::localized(
+            "provider.policy.missing_fields",
+            format!("根据团队策略，供应商 {} 必须填写: {fields}", provider.name),
+            format!(
+                "Team policy requires provider {} to have: {fields}",
+                provider.name
+            ),
+        ))
+    }
+
+    fn is_filled(&self, provider: &Provider, field: RequiredField) -> bool {
+        let value = match field {
+            RequiredField::Notes => provider.notes.as_deref(),
+            RequiredField::Category => provider.category.as_deref(),
+            RequiredField::WebsiteUrl => provider.website_url.as_deref(),
+            RequiredField::Icon => provider.icon.as_deref(),
+        };
+        let Some(value) = value.map(str::trim).filter(|v| !v.is_empty()) else {
+            return false;
+        };
+
+        match (field, self.notes_template.as_deref()) {
+            (RequiredField::Notes, Some(template)) => value != template.trim(),
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn enforce_reports_missing_and_template_notes() {
+        let policy: ProviderPolicy = serde_json::from_value(json!({
+            "requiredFields": ["notes", "websiteUrl"],
+            "notesTemplate": "Owner: "
+        }))
+        .unwrap();
+
+        let mut provider = Provider::with_id("p".into(), "P".into(), json!({}), None);
+        provider.notes = Some("Owner: ".to_string());
+        let err = policy.enforce(&provider).unwrap_err().to_string();
+        assert!(err.contains("notes") && err.contains("websiteUrl"), "{err}");
+
+        provider.notes = Some("Owner: infra team".to_string());
+        provider.website_url = Some("https://example.com".to_string());
+        assert!(policy.enforce(&provider).is_ok());
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        assert!(serde_json::from_value::<ProviderPolicy>(json!({
+            "requiredFields": ["owner"]
+        }))
+        .is_err());
+    }
+}
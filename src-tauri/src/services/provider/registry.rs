@@ -0,0 +1,430 @@
+//! Known vendor registry
+//!
+//! Mirrors the vendor metadata of the frontend presets so providers created by
+//! hand (or imported) can be enriched from their base URL.
+
+use serde::Serialize;
+use url::Url;
+
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::provider::Provider;
+use crate::proxy::providers::get_adapter;
+use crate::store::AppState;
+
+/// Metadata of a known vendor
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct VendorPreset {
+    pub name: &'static str,
+    /// Host suffixes identifying the vendor's API endpoints
+    pub hosts: &'static [&'static str],
+    pub website_url: &'static str,
+    pub category: &'static str,
+    pub icon: Option<&'static str>,
+    pub icon_color: Option<&'static str>,
+    /// Built-in usage probe (see `usage_probe`)
+    pub usage_provider: Option<&'static str>,
+    pub pricing_note: Option<&'static str>,
+}
+
+const fn vendor(
+    name: &'static str,
+    hosts: &'static [&'static str],
+    website_url: &'static str,
+    category: &'static str,
+) -> VendorPreset {
+    VendorPreset {
+        name,
+        hosts,
+        website_url,
+        category,
+        icon: None,
+        icon_color: None,
+        usage_provider: None,
+        pricing_note: None,
+    }
+}
+
+const fn with_icon(
+    preset: VendorPreset,
+    icon: &'static str,
+    icon_color: Option<&'static str>,
+) -> VendorPreset {
+    VendorPreset {
+        icon: Some(icon),
+        icon_color,
+        ..preset
+    }
+}
+
+const fn with_usage(preset: VendorPreset, usage_provider: &'static str) -> VendorPreset {
+    VendorPreset {
+        usage_provider: Some(usage_provider),
+        ..preset
+    }
+}
+
+const fn with_pricing(preset: VendorPreset, pricing_note: &'static str) -> VendorPreset {
+    VendorPreset {
+        pricing_note: Some(pricing_note),
+        ..preset
+    }
+}
+
+pub(crate) const VENDORS: &[VendorPreset] = &[
+    with_usage(
+        with_icon(
+            vendor(
+                "Claude Official",
+                &["api.anthropic.com"],
+                "https://www.anthropic.com/claude-code",
+                "official",
+            ),
+            "anthropic",
+            Some("#D4915D"),
+        ),
+        "anthropic",
+    ),
+    with_icon(
+        vendor(
+            "OpenAI Official",
+            &["api.openai.com"],
+            "https://chatgpt.com/codex",
+            "official",
+        ),
+        "openai",
+        Some("#00A67E"),
+    ),
+    with_icon(
+        vendor(
+            "Google Official",
+            &["generativelanguage.googleapis.com"],
+            "https://ai.google.dev/",
+            "official",
+        ),
+        "gemini",
+        Some("#4285F4"),
+    ),
+    with_icon(
+        vendor(
+            "Azure OpenAI",
+            &["openai.azure.com"],
+            "https://learn.microsoft.com/azure/ai-services/openai/",
+            "third_party",
+        ),
+        "azure",
+        Some("#0078D4"),
+    ),
+    with_icon(
+        vendor(
+            "DeepSeek",
+            &["api.deepseek.com"],
+            "https://platform.deepseek.com",
+            "cn_official",
+        ),
+        "deepseek",
+        Some("#1E88E5"),
+    ),
+    with_icon(
+        vendor(
+            "Zhipu GLM",
+            &["open.bigmodel.cn"],
+            "https://open.bigmodel.cn",
+            "cn_official",
+        ),
+        "zhipu",
+        Some("#0F62FE"),
+    ),
+    with_icon(
+        vendor("Z.ai GLM", &["api.z.ai"], "https://z.ai", "cn_official"),
+        "zhipu",
+        Some("#0F62FE"),
+    ),
+    with_icon(
+        vendor(
+            "Qwen Coder",
+            &["dashscope.aliyuncs.com"],
+            "https://bailian.console.aliyun.com",
+            "cn_official",
+        ),
+        "qwen",
+        Some("#FF6A00"),
+    ),
+    with_icon(
+        vendor(
+            "Kimi",
+            &["api.moonshot.cn"],
+            "https://platform.moonshot.cn/console",
+            "cn_official",
+        ),
+        "kimi",
+        Some("#6366F1"),
+    ),
+    with_icon(
+        vendor(
+            "Kimi For Coding",
+            &["api.kimi.com"],
+            "https://www.kimi.com/coding/docs/",
+            "cn_official",
+        ),
+        "kimi",
+        Some("#6366F1"),
+    ),
+    with_icon(
+        vendor(
+            "ModelScope",
+            &["api-inference.modelscope.cn"],
+            "https://modelscope.cn",
+            "aggregator",
+        ),
+        "modelscope",
+        Some("#624AFF"),
+    ),
+    vendor(
+        "KAT-Coder",
+        &["vanchin.streamlake.ai"],
+        "https://console.streamlake.ai",
+        "cn_official",
+    ),
+    with_icon(
+        vendor(
+            "Longcat",
+            &["api.longcat.chat"],
+            "https://longcat.chat/platform",
+            "cn_official",
+        ),
+        "longcat",
+        Some("#29E154"),
+    ),
+    with_icon(
+        vendor(
+            "MiniMax",
+            &["api.minimaxi.com"],
+            "https://platform.minimaxi.com",
+            "cn_official",
+        ),
+        "minimax",
+        Some("#FF6B6B"),
+    ),
+    with_icon(
+        vendor(
+            "MiniMax en",
+            &["api.minimax.io"],
+            "https://platform.minimax.io",
+            "cn_official",
+        ),
+        "minimax",
+        Some("#FF6B6B"),
+    ),
+    with_icon(
+        vendor(
+            "DouBaoSeed",
+            &["volces.com"],
+            "https://www.volcengine.com/product/doubao",
+            "cn_official",
+        ),
+        "doubao",
+        Some("#3370FF"),
+    ),
+    vendor(
+        "BaiLing",
+        &["api.tbox.cn"],
+        "https://alipaytbox.yuque.com/sxs0ba/ling/get_started",
+        "cn_official",
+    ),
+    with_icon(
+        vendor(
+            "AiHubMix",
+            &["aihubmix.com"],
+            "https://aihubmix.com",
+            "aggregator",
+        ),
+        "aihubmix",
+        Some("#006FFB"),
+    ),
+    vendor(
+        "DMXAPI",
+        &["dmxapi.cn"],
+        "https://www.dmxapi.cn",
+        "aggregator",
+    ),
+    with_usage(
+        with_icon(
+            vendor(
+                "PackyCode",
+                &["packyapi.com"],
+                "https://www.packyapi.com",
+                "third_party",
+            ),
+            "packycode",
+            None,
+        ),
+        "newapi",
+    ),
+    with_pricing(
+        with_usage(
+            with_icon(
+                vendor(
+                    "OpenRouter",
+                    &["openrouter.ai"],
+                    "https://openrouter.ai",
+                    "aggregator",
+                ),
+                "openrouter",
+                Some("#6566F1"),
+            ),
+            "openrouter",
+        ),
+        "按模型计费，价格见 https://openrouter.ai/models",
+    ),
+];
+
+/// Find the vendor serving the given base URL (host or host suffix match)
+pub(crate) fn find_vendor(base_url: &str) -> Option<&'static VendorPreset> {
+    let host = Url::parse(base_url.trim())
+        .ok()?
+        .host_str()?
+        .to_ascii_lowercase();
+    VENDORS.iter().find(|vendor| {
+        vendor
+            .hosts
+            .iter()
+            .any(|h| host == *h || host.ends_with(&format!(".{h}")))
+    })
+}
+
+/// Result of an enrichment
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnrichResult {
+    /// Matched vendor name
+    pub vendor: String,
+    /// Names of the fields that were (or would be) filled in
+    pub filled_fields: Vec<String>,
+    pub provider: Provider,
+    pub applied: bool,
+}
+
+/// Fill the provider's missing metadata from a vendor preset
+///
+/// Only empty fields are filled; returns the names of the filled fields.
+pub(crate) fn enrich(provider: &mut Provider, vendor: &VendorPreset) -> Vec<String> {
+    let mut filled = Vec::new();
+
+    if provider.website_url.as_deref().is_none_or(str::is_empty) {
+        provider.website_url = Some(vendor.website_url.to_string());
+        filled.push("websiteUrl".to_string());
+    }
+    if provider.category.as_deref().is_none_or(str::is_empty) {
+        provider.category = Some(vendor.category.to_string());
+        filled.push("category".to_string());
+    }
+    if let Some(icon) = vendor.icon {
+        if provider.icon.as_deref().is_none_or(str::is_empty) {
+            provider.icon = Some(icon.to_string());
+            filled.push("icon".to_string());
+        }
+    }
+    if let Some(color) = vendor.icon_color {
+        if provider.icon_color.as_deref().is_none_or(str::is_empty) {
+            provider.icon_color = Some(color.to_string());
+            filled.push("iconColor".to_string());
+        }
+    }
+    if let Some(note) = vendor.pricing_note {
+        if provider.notes.as_deref().is_none_or(str::is_empty) {
+            provider.notes = Some(note.to_string());
+            filled.push("notes".to_string());
+        }
+    }
+    if let Some(usage_provider) = vendor.usage_provider {
+        let meta = provider.meta.get_or_insert_with(Default::default);
+        if meta.usage_script.is_none() && meta.usage_provider.is_none() {
+            meta.usage_provider = Some(usage_provider.to_string());
+            filled.push("usageProvider".to_string());
+        }
+    }
+
+    filled
+}
+
+/// Enrich a stored provider; persists the result when `apply` is true
+pub fn enrich_provider(
+    state: &AppState,
+    app_type: AppType,
+    provider_id: &str,
+    apply: bool,
+) -> Result<EnrichResult, AppError> {
+    let mut provider = state
+        .db
+        .get_provider_by_id(provider_id, app_type.as_str())?
+        .ok_or_else(|| AppError::provider_not_found(provider_id, app_type.as_str()))?;
+
+    let base_url = get_adapter(&app_type)
+        .extract_base_url(&provider)
+        .map_err(|e| AppError::Message(format!("提取 base_url 失败: {e}")))?;
+
+    let vendor = find_vendor(&base_url).ok_or_else(|| {
+        AppError::localized(
+            "provider.enrich.unknown_vendor",
+            format!("未找到与 {base_url} 匹配的已知供应商"),
+            format!("No known vendor matches {base_url}"),
+        )
+    })?;
+
+    let filled_fields = enrich(&mut provider, vendor);
+    let applied = apply && !filled_fields.is_empty();
+    if applied {
+        state.db.save_provider(app_type.as_str(), &provider)?;
+    }
+
+    Ok(EnrichResult {
+        vendor: vendor.name.to_string(),
+        filled_fields,
+        provider,
+        applied,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn find_vendor_matches_host_suffix() {
+        assert_eq!(
+            find_vendor("https://api-slb.packyapi.com/v1").map(|v| v.name),
+            Some("PackyCode")
+        );
+        assert_eq!(
+            find_vendor("https://ark.cn-beijing.volces.com/api/coding").map(|v| v.name),
+            Some("DouBaoSeed")
+        );
+        assert!(find_vendor("https://notpackyapi.com").is_none());
+        assert!(find_vendor("not a url").is_none());
+    }
+
+    #[test]
+    fn enrich_only_fills_missing_fields() {
+        let mut provider = Provider::with_id(
+            "or".to_string(),
+            "My OpenRouter".to_string(),
+            json!({ "env": { "ANTHROPIC_BASE_URL": "https://openrouter.ai/api" } }),
+            None,
+        );
+        provider.icon = Some("custom".to_string());
+
+        let vendor = find_vendor("https://openrouter.ai/api").unwrap();
+        let filled = enrich(&mut provider, vendor);
+
+        assert!(filled.contains(&"websiteUrl".to_string()));
+        assert!(!filled.contains(&"icon".to_string()));
+        assert_eq!(provider.icon.as_deref(), Some("custom"));
+        assert_eq!(provider.category.as_deref(), Some("aggregator"));
+        assert_eq!(
+            provider.meta.unwrap().usage_provider.as_deref(),
+            Some("openrouter")
+        );
+    }
+}
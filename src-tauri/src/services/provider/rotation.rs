@@ -0,0 +1,98 @@
+//! API key expiry and rotation
+//!
+//! A provider can carry a fixed expiry date for its key and/or a rotation
+//! interval (see [`Provider::key_expiry`]). Rotating replaces the key in
+//! `settings_config`, keeps only the fingerprint of the old key in
+//! `meta.retiredKeys` (so it also shows up in the provider history), and
+//! restarts the rotation interval.
+
+use super::ProviderService;
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::provider::{key_fingerprint, Provider, ProviderMeta, RetiredKey};
+use crate::proxy::providers::get_adapter;
+use crate::store::AppState;
+
+/// Key expiry settings to change; `Some("")` clears a field, `None` leaves it as is
+#[derive(Debug, Clone, Default)]
+pub struct KeyPolicy {
+    /// `YYYY-MM-DD`
+    pub expires_at: Option<String>,
+    /// Whole days
+    pub rotation_days: Option<String>,
+}
+
+impl KeyPolicy {
+    pub fn is_empty(&self) -> bool {
+        self.expires_at.is_none() && self.rotation_days.is_none()
+    }
+
+    /// Validate and write into the meta
+    pub(crate) fn apply(&self, meta: &mut ProviderMeta) -> Result<(), AppError> {
+        if let Some(date) = self.expires_at.as_deref().map(str::trim) {
+            if !date.is_empty() && chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_err() {
+                return Err(AppError::InvalidInput(format!(
+                    "无效的到期日: {date}（格式为 YYYY-MM-DD）"
+                )));
+            }
+            meta.key_expires_at = Some(date.to_string()).filter(|d| !d.is_empty());
+        }
+        if let Some(days) = self.rotation_days.as_deref().map(str::trim) {
+            meta.key_rotation_days = match days {
+                "" => None,
+                days => Some(days.parse::<u32>().ok().filter(|d| *d > 0).ok_or_else(|| {
+                    AppError::InvalidInput(format!("无效的轮换周期: {days}（应为正整数天数）"))
+                })?),
+            };
+        }
+        Ok(())
+    }
+}
+
+/// Replace the key and remember the fingerprint of the old one
+pub(crate) fn rotate_key(
+    state: &AppState,
+    app_type: &AppType,
+    id: &str,
+    api_key: &str,
+    expires_at: Option<&str>,
+) -> Result<Provider, AppError> {
+    let api_key = api_key.trim();
+    if api_key.is_empty() {
+        return Err(AppError::InvalidInput("API Key 不能为空".to_string()));
+    }
+    let mut provider = state
+        .db
+        .get_provider_by_id(id, app_type.as_str())?
+        .ok_or_else(|| AppError::provider_not_found(id, app_type.as_str()))?;
+    let old_key = get_adapter(app_type)
+        .extract_auth(&provider)
+        .map(|auth| auth.api_key)
+        .filter(|key| !key.trim().is_empty());
+    if old_key.as_deref().map(str::trim) == Some(api_key) {
+        return Err(AppError::InvalidInput(
+            "新的 API Key 与当前密钥相同".to_string(),
+        ));
+    }
+
+    ProviderService::set_api_key(app_type, &mut provider.settings_config, api_key)?;
+    let now = chrono::Utc::now().timestamp_millis();
+    let meta = provider.meta.get_or_insert_with(Default::default);
+    if let Some(old_key) = old_key {
+        meta.retired_keys.push(RetiredKey {
+            fingerprint: key_fingerprint(&old_key),
+            retired_at: now,
+        });
+    }
+    meta.key_rotated_at = Some(now);
+    // The old expiry date belonged to the old key
+    meta.key_expires_at = None;
+    KeyPolicy {
+        expires_at: expires_at.map(str::to_string),
+        rotation_days: None,
+    }
+    .apply(meta)?;
+
+    ProviderService::update(state, app_type.clone(), provider.clone())?;
+    Ok(provider)
+}
@@ -0,0 +1,172 @@
+//! Shell prompt segment
+//!
+//! Renders the current provider and model (e.g. `⚡ deepseek-r1 via openrouter`)
+//! for starship, powerlevel10k and similar prompts. Prompts render on every
+//! command, so the segment is cached in `~/.cc-switch/cache/prompt-<app>.json`
+//! together with the modification times of the database, its WAL file and
+//! settings.json, taken after the database is closed again; as long as those are
+//! unchanged the cache is used and SQLite is never opened.
+
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+
+use super::snippet::model_of;
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::settings::AppSettings;
+use crate::store::AppState;
+
+/// Output style of the segment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SegmentFormat {
+    /// Text only
+    Plain,
+    /// Bold yellow text for a starship `custom` module
+    Starship,
+    /// Black on yellow, closed with a powerline arrow
+    Powerline,
+}
+
+impl FromStr for SegmentFormat {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "plain" => Ok(Self::Plain),
+            "starship" => Ok(Self::Starship),
+            "powerline" | "p10k" => Ok(Self::Powerline),
+            other => Err(AppError::InvalidInput(format!(
+                "不支持的提示符格式: {other}（可选: powerline, starship, plain）"
+            ))),
+        }
+    }
+}
+
+/// Current provider of an app as shown in the prompt
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptSegment {
+    pub app: String,
+    /// `None` when no provider is current
+    pub provider: Option<String>,
+    pub model: Option<String>,
+}
+
+impl PromptSegment {
+    /// Rendered segment; empty when no provider is current so the prompt hides it
+    pub fn render(&self, format: SegmentFormat) -> String {
+        let Some(provider) = &self.provider else {
+            return String::new();
+        };
+        let text = match &self.model {
+            Some(model) => format!("⚡ {model} via {provider}"),
+            None => format!("⚡ {provider}"),
+        };
+        match format {
+            SegmentFormat::Plain => text,
+            SegmentFormat::Starship => format!("\x1b[1;33m{text}\x1b[0m"),
+            SegmentFormat::Powerline => format!("\x1b[30;43m {text} \x1b[0;33m\u{e0b0}\x1b[0m"),
+        }
+    }
+}
+
+/// Cache file content
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    stamp: Vec<Option<u64>>,
+    segment: PromptSegment,
+}
+
+/// Segment from the cache, if nothing it depends on changed since it was written
+pub(crate) fn cached(app_type: &AppType) -> Option<PromptSegment> {
+    let entry: CacheEntry = crate::config::read_json_file(&cache_path(app_type)).ok()?;
+    (entry.stamp == stamp()).then_some(entry.segment)
+}
+
+/// Build the segment from the database
+pub(crate) fn build(state: &AppState, app_type: AppType) -> Result<PromptSegment, AppError> {
+    let id = crate::settings::get_effective_current_provider(&state.db, &app_type)?;
+    let provider = match id {
+        Some(id) => state.db.get_provider_by_id(&id, app_type.as_str())?,
+        None => None,
+    };
+    Ok(PromptSegment {
+        app: app_type.as_str().to_string(),
+        model: provider.as_ref().and_then(|p| {
+            model_of(&app_type, p).or_else(|| p.meta.as_ref()?.default_model.clone())
+        }),
+        provider: provider.map(|p| p.name),
+    })
+}
+
+/// Cache the segment; call once the database is closed, since closing it may
+/// checkpoint the WAL and touch the files the stamp is taken from
+pub(crate) fn store(segment: &PromptSegment) {
+    let Ok(app_type) = AppType::from_str(&segment.app) else {
+        return;
+    };
+    let entry = CacheEntry {
+        stamp: stamp(),
+        segment: segment.clone(),
+    };
+    if let Err(e) = crate::config::write_json_file(&cache_path(&app_type), &entry) {
+        log::debug!("写入提示符缓存失败: {e}");
+    }
+}
+
+fn cache_path(app_type: &AppType) -> PathBuf {
+    crate::config::get_app_config_dir()
+        .join("cache")
+        .join(format!("prompt-{}.json", app_type.as_str()))
+}
+
+/// Modification times of everything the segment is derived from
+fn stamp() -> Vec<Option<u64>> {
+    let db = crate::config::get_database_path();
+    let mut wal = db.clone().into_os_string();
+    wal.push("-wal");
+    [db, PathBuf::from(wal), AppSettings::settings_path()]
+        .iter()
+        .map(PathBuf::as_path)
+        .map(modified)
+        .collect()
+}
+
+fn modified(path: &Path) -> Option<u64> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    let nanos = modified.duration_since(UNIX_EPOCH).ok()?.as_nanos();
+    u64::try_from(nanos).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_formats_and_hides_without_provider() {
+        let mut segment = PromptSegment {
+            app: "claude".to_string(),
+            provider: Some("openrouter".to_string()),
+            model: Some("deepseek-r1".to_string()),
+        };
+        assert_eq!(
+            segment.render(SegmentFormat::Plain),
+            "⚡ deepseek-r1 via openrouter"
+        );
+        assert_eq!(
+            segment.render(SegmentFormat::Starship),
+            "\x1b[1;33m⚡ deepseek-r1 via openrouter\x1b[0m"
+        );
+        assert!(segment
+            .render(SegmentFormat::Powerline)
+            .ends_with("\u{e0b0}\x1b[0m"));
+
+        segment.model = None;
+        assert_eq!(segment.render(SegmentFormat::Plain), "⚡ openrouter");
+        segment.provider = None;
+        assert_eq!(segment.render(SegmentFormat::Starship), "");
+    }
+}
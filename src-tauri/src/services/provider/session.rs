@@ -0,0 +1,130 @@
+//! Ephemeral provider sessions
+//!
+//! Runs a command with a provider's variables (`ANTHROPIC_BASE_URL`,
+//! `OPENAI_API_KEY`, ...) injected into the child process only; the live config
+//! files are not touched. Tools that also read their own config files may let
+//! those take precedence (e.g. `env` in Claude settings.json), so the live
+//! config is best left without provider variables when sessions are used.
+//!
+//! On Unix, SIGTERM and SIGHUP sent to cc-switch are forwarded to the child, and
+//! SIGINT / SIGQUIT are ignored while it runs since the terminal already delivers
+//! them to the whole foreground process group. The exit code of the child is
+//! returned, or `128 + signal` when it was killed by a signal.
+
+use std::process::Command;
+
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::provider::Provider;
+
+use super::export::env_vars;
+use super::live::prepare_live_provider;
+
+/// Variables for running a provider in a single process
+///
+/// Besides the tool's own variables, `CC_SWITCH_APP` / `CC_SWITCH_PROVIDER`
+/// tell the child which provider it was started with.
+pub(crate) fn session_env(
+    app_type: &AppType,
+    provider: &Provider,
+) -> Result<Vec<(String, String)>, AppError> {
+    let provider = prepare_live_provider(app_type, provider)?;
+    let mut vars = env_vars(app_type, &provider);
+    vars.push(("CC_SWITCH_APP".to_string(), app_type.as_str().to_string()));
+    vars.push(("CC_SWITCH_PROVIDER".to_string(), provider.id.clone()));
+    Ok(vars)
+}
+
+/// Run `command` with `env` added to the inherited environment and wait for it
+pub(crate) fn run(command: &[String], env: &[(String, String)]) -> Result<i32, AppError> {
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| AppError::InvalidInput("缺少要运行的命令".to_string()))?;
+    let mut child = Command::new(program)
+        .args(args)
+        .envs(env.iter().cloned())
+        .spawn()
+        .map_err(|e| AppError::Message(format!("无法启动 {program}: {e}")))?;
+
+    #[cfg(unix)]
+    signals::forward_to(child.id());
+    let status = child.wait();
+    #[cfg(unix)]
+    signals::restore();
+
+    let status = status.map_err(|e| AppError::Message(format!("等待 {program} 退出失败: {e}")))?;
+    if let Some(code) = status.code() {
+        return Ok(code);
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return Ok(128 + signal);
+        }
+    }
+    Ok(1)
+}
+
+#[cfg(unix)]
+mod signals {
+    use std::sync::atomic::{AtomicI32, Ordering};
+
+    /// Pid of the running child; 0 when there is none
+    static CHILD: AtomicI32 = AtomicI32::new(0);
+
+    const FORWARDED: [libc::c_int; 2] = [libc::SIGTERM, libc::SIGHUP];
+    const IGNORED: [libc::c_int; 2] = [libc::SIGINT, libc::SIGQUIT];
+
+    extern "C" fn forward(signal: libc::c_int) {
+        let pid = CHILD.load(Ordering::SeqCst);
+        if pid > 0 {
+            // kill(2) is async-signal-safe
+            unsafe {
+                libc::kill(pid, signal);
+            }
+        }
+    }
+
+    pub(super) fn forward_to(pid: u32) {
+        CHILD.store(pid as i32, Ordering::SeqCst);
+        let handler = forward as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        unsafe {
+            for signal in FORWARDED {
+                libc::signal(signal, handler);
+            }
+            for signal in IGNORED {
+                libc::signal(signal, libc::SIG_IGN);
+            }
+        }
+    }
+
+    pub(super) fn restore() {
+        CHILD.store(0, Ordering::SeqCst);
+        unsafe {
+            for signal in FORWARDED.into_iter().chain(IGNORED) {
+                libc::signal(signal, libc::SIG_DFL);
+            }
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn sh(script: &str) -> Vec<String> {
+        vec!["sh".to_string(), "-c".to_string(), script.to_string()]
+    }
+
+    #[test]
+    fn run_passes_env_and_exit_code() {
+        let env = vec![("CC_SWITCH_TEST_VALUE".to_string(), "42".to_string())];
+        assert_eq!(
+            run(&sh("exit $CC_SWITCH_TEST_VALUE"), &env).expect("run"),
+            42
+        );
+        assert_eq!(run(&sh("kill -TERM $$"), &[]).expect("run"), 128 + 15);
+        assert!(run(&[], &[]).is_err());
+    }
+}
@@ -0,0 +1,209 @@
+//! SDK code snippets for a provider
+//!
+//! Renders a ready-to-paste client setup for the official SDK of each app
+//! (Anthropic for Claude, OpenAI for Codex, Google GenAI for Gemini). The API
+//! key is never embedded; snippets read it from an environment variable.
+
+use std::str::FromStr;
+
+use serde_json::Value;
+
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::provider::Provider;
+use crate::proxy::providers::get_adapter;
+
+/// Target language of a snippet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnippetLang {
+    Python,
+    Node,
+}
+
+impl FromStr for SnippetLang {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "python" | "py" => Ok(SnippetLang::Python),
+            "node" | "js" | "javascript" | "ts" | "typescript" => Ok(SnippetLang::Node),
+            other => Err(AppError::InvalidInput(format!(
+                "不支持的语言: {other}（可选: python, node）"
+            ))),
+        }
+    }
+}
+
+/// Render the SDK client setup for a provider
+pub(crate) fn render(app_type: &AppType, provider: &Provider, lang: SnippetLang) -> String {
+    let base_url = get_adapter(app_type)
+        .extract_base_url(provider)
+        .ok()
+        .filter(|url| !url.is_empty());
+    let model = model_of(app_type, provider);
+
+    let comment = match lang {
+        SnippetLang::Python => "#",
+        SnippetLang::Node => "//",
+    };
+    let mut out = format!("{comment} {}\n", provider.name);
+    out.push_str(&match (app_type, lang) {
+        (AppType::Claude, SnippetLang::Python) => claude_python(base_url.as_deref(), model),
+        (AppType::Claude, SnippetLang::Node) => claude_node(base_url.as_deref(), model),
+        (AppType::Codex, SnippetLang::Python) => openai_python(base_url.as_deref(), model),
+        (AppType::Codex, SnippetLang::Node) => openai_node(base_url.as_deref(), model),
+        (AppType::Gemini, SnippetLang::Python) => gemini_python(base_url.as_deref(), model),
+        (AppType::Gemini, SnippetLang::Node) => gemini_node(base_url.as_deref(), model),
+    });
+    out
+}
+
+/// Model configured for the provider, if any
+pub(super) fn model_of(app_type: &AppType, provider: &Provider) -> Option<String> {
+    let settings = &provider.settings_config;
+    match app_type {
+        AppType::Claude => settings
+            .pointer("/env/ANTHROPIC_MODEL")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        AppType::Codex => settings
+            .get("config")
+            .and_then(Value::as_str)
+            .and_then(|text| toml::from_str::<toml::Table>(text).ok())
+            .and_then(|table| table.get("model")?.as_str().map(str::to_string)),
+        AppType::Gemini => settings
+            .pointer("/env/GEMINI_MODEL")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+    }
+    .filter(|model| !model.is_empty())
+}
+
+/// String literal valid in both Python and JavaScript
+fn quote(s: &str) -> String {
+    serde_json::to_string(s).unwrap_or_else(|_| format!("\"{s}\""))
+}
+
+fn claude_python(base_url: Option<&str>, model: Option<String>) -> String {
+    let mut out =
+        String::from("import os\n\nfrom anthropic import Anthropic\n\nclient = Anthropic(\n");
+    if let Some(url) = base_url {
+        out.push_str(&format!("    base_url={},\n", quote(url)));
+    }
+    out.push_str("    api_key=os.environ[\"ANTHROPIC_API_KEY\"],\n)\n");
+    let model = model.unwrap_or_else(|| "claude-sonnet-4-5".to_string());
+    out.push_str(&format!(
+        "\nmessage = client.messages.create(\n    model={},\n    max_tokens=1024,\n    messages=[{{\"role\": \"user\", \"content\": \"Hello\"}}],\n)\nprint(message.content)\n",
+        quote(&model)
+    ));
+    out
+}
+
+fn claude_node(base_url: Option<&str>, model: Option<String>) -> String {
+    let mut out = String::from(
+        "import Anthropic from \"@anthropic-ai/sdk\";\n\nconst client = new Anthropic({\n",
+    );
+    if let Some(url) = base_url {
+        out.push_str(&format!("  baseURL: {},\n", quote(url)));
+    }
+    out.push_str("  apiKey: process.env.ANTHROPIC_API_KEY,\n});\n");
+    let model = model.unwrap_or_else(|| "claude-sonnet-4-5".to_string());
+    out.push_str(&format!(
+        "\nconst message = await client.messages.create({{\n  model: {},\n  max_tokens: 1024,\n  messages: [{{ role: \"user\", content: \"Hello\" }}],\n}});\nconsole.log(message.content);\n",
+        quote(&model)
+    ));
+    out
+}
+
+fn openai_python(base_url: Option<&str>, model: Option<String>) -> String {
+    let mut out = String::from("import os\n\nfrom openai import OpenAI\n\nclient = OpenAI(\n");
+    if let Some(url) = base_url {
+        out.push_str(&format!("    base_url={},\n", quote(url)));
+    }
+    out.push_str("    api_key=os.environ[\"OPENAI_API_KEY\"],\n)\n");
+    let model = model.unwrap_or_else(|| "gpt-5-codex".to_string());
+    out.push_str(&format!(
+        "\nresponse = client.responses.create(model={}, input=\"Hello\")\nprint(response.output_text)\n",
+        quote(&model)
+    ));
+    out
+}
+
+fn openai_node(base_url: Option<&str>, model: Option<String>) -> String {
+    let mut out = String::from("import OpenAI from \"openai\";\n\nconst client = new OpenAI({\n");
+    if let Some(url) = base_url {
+        out.push_str(&format!("  baseURL: {},\n", quote(url)));
+    }
+    out.push_str("  apiKey: process.env.OPENAI_API_KEY,\n});\n");
+    let model = model.unwrap_or_else(|| "gpt-5-codex".to_string());
+    out.push_str(&format!(
+        "\nconst response = await client.responses.create({{ model: {}, input: \"Hello\" }});\nconsole.log(response.output_text);\n",
+        quote(&model)
+    ));
+    out
+}
+
+fn gemini_python(base_url: Option<&str>, model: Option<String>) -> String {
+    let mut out = String::from("import os\n\nfrom google import genai\n\nclient = genai.Client(\n");
+    out.push_str("    api_key=os.environ[\"GEMINI_API_KEY\"],\n");
+    if let Some(url) = base_url {
+        out.push_str(&format!(
+            "    http_options={{\"base_url\": {}}},\n",
+            quote(url)
+        ));
+    }
+    out.push_str(")\n");
+    let model = model.unwrap_or_else(|| "gemini-2.5-pro".to_string());
+    out.push_str(&format!(
+        "\nresponse = client.models.generate_content(model={}, contents=\"Hello\")\nprint(response.text)\n",
+        quote(&model)
+    ));
+    out
+}
+
+fn gemini_node(base_url: Option<&str>, model: Option<String>) -> String {
+    let mut out = String::from(
+        "import { GoogleGenAI } from \"@google/genai\";\n\nconst client = new GoogleGenAI({\n",
+    );
+    out.push_str("  apiKey: process.env.GEMINI_API_KEY,\n");
+    if let Some(url) = base_url {
+        out.push_str(&format!("  httpOptions: {{ baseUrl: {} }},\n", quote(url)));
+    }
+    out.push_str("});\n");
+    let model = model.unwrap_or_else(|| "gemini-2.5-pro".to_string());
+    out.push_str(&format!(
+        "\nconst response = await client.models.generateContent({{ model: {}, contents: \"Hello\" }});\nconsole.log(response.text);\n",
+        quote(&model)
+    ));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn snippet_uses_base_url_and_env_key_placeholder() {
+        let provider = Provider::with_id(
+            "relay".to_string(),
+            "Relay".to_string(),
+            json!({
+                "auth": { "OPENAI_API_KEY": "sk-secret" },
+                "config": "model = \"gpt-5\"\n\n[model_providers.relay]\nbase_url = \"https://relay.example.com/v1\"\n"
+            }),
+            None,
+        );
+
+        let python = render(&AppType::Codex, &provider, SnippetLang::Python);
+        assert!(python.contains("base_url=\"https://relay.example.com/v1\""));
+        assert!(python.contains("os.environ[\"OPENAI_API_KEY\"]"));
+        assert!(python.contains("model=\"gpt-5\""));
+        assert!(!python.contains("sk-secret"));
+
+        let node = render(&AppType::Codex, &provider, SnippetLang::Node);
+        assert!(node.starts_with("// Relay\n"));
+        assert!(node.contains("baseURL: \"https://relay.example.com/v1\""));
+        assert!(node.contains("process.env.OPENAI_API_KEY"));
+    }
+}
@@ -0,0 +1,121 @@
+//! Current provider status
+//!
+//! A compact summary of the active provider of an app — name, base URL, masked
+//! key and whether the live config still matches what cc-switch would write —
+//! for `cc-switch current` and other quick status displays.
+
+use serde::Serialize;
+
+use super::diff::{diff, DiffTarget};
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::provider::mask_secret;
+use crate::proxy::providers::get_adapter;
+use crate::store::AppState;
+
+/// Active provider of one app; `provider` is `None` when no provider is current
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrentStatus {
+    pub app: String,
+    pub provider: Option<CurrentProvider>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrentProvider {
+    pub id: String,
+    pub name: String,
+    pub base_url: Option<String>,
+    /// Masked API key
+    pub api_key: Option<String>,
+    /// Whether the live config matches the provider's settings
+    pub in_sync: bool,
+    /// Number of differing settings paths when out of sync
+    pub drift: usize,
+}
+
+pub(crate) fn status(state: &AppState, app_type: AppType) -> Result<CurrentStatus, AppError> {
+    let app = app_type.as_str().to_string();
+    let id = crate::settings::get_effective_current_provider(&state.db, &app_type)?;
+    let Some(provider) = id.and_then(|id| {
+        state
+            .db
+            .get_provider_by_id(&id, app_type.as_str())
+            .ok()
+            .flatten()
+    }) else {
+        return Ok(CurrentStatus {
+            app,
+            provider: None,
+        });
+    };
+
+    let adapter = get_adapter(&app_type);
+    let base_url = adapter
+        .extract_base_url(&provider)
+        .ok()
+        .filter(|url| !url.is_empty());
+    let api_key = adapter
+        .extract_auth(&provider)
+        .map(|auth| mask_secret(&auth.api_key));
+    let drift = diff(state, app_type, &provider.id, &DiffTarget::Live, false)?
+        .changes
+        .len();
+
+    Ok(CurrentStatus {
+        app,
+        provider: Some(CurrentProvider {
+            id: provider.id,
+            name: provider.name,
+            base_url,
+            api_key,
+            in_sync: drift == 0,
+            drift,
+        }),
+    })
+}
+
+impl CurrentStatus {
+    /// `app:name` for shell prompts, with a trailing `*` when the live config has drifted
+    /// and `-` as the name when no provider is current
+    pub fn porcelain(&self) -> String {
+        match &self.provider {
+            Some(p) if p.in_sync => format!("{}:{}", self.app, p.name),
+            Some(p) => format!("{}:{}*", self.app, p.name),
+            None => format!("{}:-", self.app),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(provider: Option<(&str, bool)>) -> CurrentStatus {
+        CurrentStatus {
+            app: "claude".to_string(),
+            provider: provider.map(|(name, in_sync)| CurrentProvider {
+                id: name.to_lowercase(),
+                name: name.to_string(),
+                base_url: None,
+                api_key: None,
+                in_sync,
+                drift: usize::from(!in_sync),
+            }),
+        }
+    }
+
+    #[test]
+    fn porcelain_marks_drift_and_missing_provider() {
+        assert_eq!(
+            sample(Some(("DeepSeek", true))).porcelain(),
+            "claude:DeepSeek"
+        );
+        assert_eq!(
+            sample(Some(("DeepSeek", false))).porcelain(),
+            "claude:DeepSeek*"
+        );
+        assert_eq!(sample(None).porcelain(), "claude:-");
+    }
+}
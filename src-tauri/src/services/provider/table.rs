@@ -0,0 +1,400 @@
+//! Plain-text table rendering of the provider list
+//!
+//! Used by non-interactive consumers (RPC mode, scripts) that want a
+//! human-readable overview instead of raw JSON. The plain style drops the
+//! borders so every provider is exactly one line, which keeps `grep`/`awk`
+//! pipelines predictable when stdout is not a terminal.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use chrono::{Local, TimeZone};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::provider::{mask_secret, Provider};
+use crate::proxy::providers::get_adapter;
+use crate::proxy::types::ProviderHealth;
+
+/// A column of the provider table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TableColumn {
+    Index,
+    Name,
+    Category,
+    BaseUrl,
+    Key,
+    Current,
+    /// Proxy health: `ok`, `down(<failures>)`, or `-` when never checked
+    Status,
+    Created,
+    /// Time to first byte of the latest benchmark from the last 24 hours
+    Latency,
+    /// Key expiry date, or `-` when neither an expiry date nor a rotation interval is set
+    Expires,
+}
+
+/// Columns shown when none are requested
+pub const DEFAULT_COLUMNS: &[TableColumn] = &[
+    TableColumn::Index,
+    TableColumn::Name,
+    TableColumn::Category,
+    TableColumn::BaseUrl,
+    TableColumn::Key,
+    TableColumn::Current,
+    TableColumn::Status,
+    TableColumn::Created,
+];
+
+/// Table layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TableStyle {
+    /// ASCII box borders, for humans
+    #[default]
+    Bordered,
+    /// Space-aligned columns, one line per provider, for scripts
+    Plain,
+}
+
+impl TableStyle {
+    /// Bordered on a terminal, plain when stdout is piped or redirected
+    pub fn for_stdout() -> Self {
+        use std::io::IsTerminal;
+        if std::io::stdout().is_terminal() {
+            TableStyle::Bordered
+        } else {
+            TableStyle::Plain
+        }
+    }
+}
+
+impl FromStr for TableStyle {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "bordered" | "box" => Ok(TableStyle::Bordered),
+            "plain" => Ok(TableStyle::Plain),
+            "auto" => Ok(TableStyle::for_stdout()),
+            other => Err(AppError::InvalidInput(format!(
+                "未知的表格样式: {other}（可选: bordered, plain, auto）"
+            ))),
+        }
+    }
+}
+
+impl TableColumn {
+    fn header(self) -> &'static str {
+        match self {
+            TableColumn::Index => "#",
+            TableColumn::Name => "Name",
+            TableColumn::Category => "Category",
+            TableColumn::BaseUrl => "Base URL",
+            TableColumn::Key => "Key",
+            TableColumn::Current => "Current",
+            TableColumn::Status => "Status",
+            TableColumn::Created => "Created",
+            TableColumn::Latency => "Latency",
+            TableColumn::Expires => "Expires",
+        }
+    }
+}
+
+impl FromStr for TableColumn {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+            "index" | "#" => Ok(TableColumn::Index),
+            "name" => Ok(TableColumn::Name),
+            "category" => Ok(TableColumn::Category),
+            "base_url" | "baseurl" | "url" => Ok(TableColumn::BaseUrl),
+            "key" => Ok(TableColumn::Key),
+            "current" => Ok(TableColumn::Current),
+            "status" | "health" => Ok(TableColumn::Status),
+            "created" | "created_at" => Ok(TableColumn::Created),
+            "latency" | "ttfb" => Ok(TableColumn::Latency),
+            "expires" | "expiry" => Ok(TableColumn::Expires),
+            other => Err(AppError::InvalidInput(format!(
+                "未知的列: {other}（可选: index, name, category, base_url, key, current, status, created, latency, expires）"
+            ))),
+        }
+    }
+}
+
+/// Parse a comma separated column list (e.g. `name,base_url,key`)
+pub fn parse_columns(spec: &str) -> Result<Vec<TableColumn>, AppError> {
+    let columns = spec
+        .split(',')
+        .filter(|s| !s.trim().is_empty())
+        .map(TableColumn::from_str)
+        .collect::<Result<Vec<_>, _>>()?;
+    if columns.is_empty() {
+        return Ok(DEFAULT_COLUMNS.to_vec());
+    }
+    Ok(columns)
+}
+
+/// Render providers (in their stored order) as a text table
+pub(crate) fn render(
+    app_type: &AppType,
+    providers: &IndexMap<String, Provider>,
+    current: &str,
+    health: &HashMap<String, ProviderHealth>,
+    latency: &HashMap<String, u64>,
+    columns: &[TableColumn],
+    style: TableStyle,
+) -> String {
+    let columns = if columns.is_empty() {
+        DEFAULT_COLUMNS
+    } else {
+        columns
+    };
+
+    let rows: Vec<Vec<String>> = providers
+        .values()
+        .enumerate()
+        .map(|(index, provider)| {
+            columns
+                .iter()
+                .map(|column| cell(app_type, provider, index, current, health, latency, *column))
+                .collect()
+        })
+        .collect();
+
+    let headers: Vec<&str> = columns.iter().map(|c| c.header()).collect();
+    render_grid(&headers, &rows, style)
+}
+
+/// Render arbitrary rows under the given headers
+pub fn render_grid(headers: &[&str], rows: &[Vec<String>], style: TableStyle) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| display_width(h)).collect();
+    for row in rows {
+        for (width, value) in widths.iter_mut().zip(row) {
+            *width = (*width).max(display_width(value));
+        }
+    }
+
+    if style == TableStyle::Plain {
+        let mut out = vec![format_plain_row(headers.iter().copied(), &widths)];
+        for row in rows {
+            out.push(format_plain_row(row.iter().map(String::as_str), &widths));
+        }
+        return out.join("\n");
+    }
+
+    let separator = {
+        let mut line = String::from("+");
+        for width in &widths {
+            line.push_str(&"-".repeat(width + 2));
+            line.push('+');
+        }
+        line
+    };
+
+    let mut out = vec![separator.clone()];
+    out.push(format_row(headers.iter().copied(), &widths));
+    out.push(separator.clone());
+    for row in rows {
+        out.push(format_row(row.iter().map(String::as_str), &widths));
+    }
+    if !rows.is_empty() {
+        out.push(separator);
+    }
+    out.join("\n")
+}
+
+fn format_row<'a>(values: impl Iterator<Item = &'a str>, widths: &[usize]) -> String {
+    let mut line = String::from("|");
+    for (value, width) in values.zip(widths) {
+        line.push(' ');
+        line.push_str(value);
+        line.push_str(&" ".repeat(width - display_width(value) + 1));
+        line.push('|');
+    }
+    line
+}
+
+/// Columns separated by two spaces; the last column is not padded
+fn format_plain_row<'a>(values: impl Iterator<Item = &'a str>, widths: &[usize]) -> String {
+    let mut line = String::new();
+    for (i, (value, width)) in values.zip(widths).enumerate() {
+        if i > 0 {
+            line.push_str("  ");
+        }
+        line.push_str(value);
+        if i + 1 < widths.len() {
+            line.push_str(&" ".repeat(width - display_width(value)));
+        }
+    }
+    line.trim_end().to_string()
+}
+
+fn cell(
+    app_type: &AppType,
+    provider: &Provider,
+    index: usize,
+    current: &str,
+    health: &HashMap<String, ProviderHealth>,
+    latency: &HashMap<String, u64>,
+    column: TableColumn,
+) -> String {
+    match column {
+        TableColumn::Index => (index + 1).to_string(),
+        TableColumn::Name => provider.name.clone(),
+        TableColumn::Category => provider.category.clone().unwrap_or_default(),
+        TableColumn::BaseUrl => get_adapter(app_type)
+            .extract_base_url(provider)
+            .unwrap_or_default(),
+        TableColumn::Key => {
+            let key = get_adapter(app_type)
+                .extract_auth(provider)
+                .map(|auth| mask_secret(&auth.api_key))
+                .unwrap_or_default();
+            // Keys that expire soon are flagged in the default layout as well
+            match provider.key_expiry(Local::now().date_naive()) {
+                Some(expiry) if expiry.needs_attention() => {
+                    format!("{key} ({})", expiry.describe())
+                }
+                _ => key,
+            }
+        }
+        TableColumn::Current => {
+            if provider.id == current {
+                "*".to_string()
+            } else {
+                String::new()
+            }
+        }
+        TableColumn::Status => match health.get(&provider.id) {
+            None => "-".to_string(),
+            Some(h) if h.is_healthy => "ok".to_string(),
+            Some(h) => format!("down({})", h.consecutive_failures),
+        },
+        TableColumn::Created => provider
+            .created_at
+            .and_then(|ms| Local.timestamp_millis_opt(ms).single())
+            .map(|dt| dt.format("%Y-%m-%d").to_string())
+            .unwrap_or_default(),
+        TableColumn::Latency => latency
+            .get(&provider.id)
+            .map(|ms| format!("{ms}ms"))
+            .unwrap_or_else(|| "-".to_string()),
+        TableColumn::Expires => provider
+            .key_expiry(Local::now().date_naive())
+            .map(|expiry| expiry.due.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|| "-".to_string()),
+    }
+}
+
+/// Terminal width of a string; CJK and full-width characters take two cells
+fn display_width(s: &str) -> usize {
+    s.chars()
+        .map(|c| match c as u32 {
+            0x1100..=0x115F
+            | 0x2E80..=0xA4CF
+            | 0xAC00..=0xD7A3
+            | 0xF900..=0xFAFF
+            | 0xFE30..=0xFE4F
+            | 0xFF00..=0xFF60
+            | 0xFFE0..=0xFFE6
+            | 0x1F300..=0x1FAFF => 2,
+            _ => 1,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn renders_selected_columns_with_masked_key() {
+        let mut providers = IndexMap::new();
+        let mut provider = Provider::with_id(
+            "p1".to_string(),
+            "中转".to_string(),
+            json!({ "env": {
+                "ANTHROPIC_BASE_URL": "https://relay.example.com",
+                "ANTHROPIC_AUTH_TOKEN": "sk-relay-secret-1234"
+            } }),
+            None,
+        );
+        provider.category = Some("third_party".to_string());
+        providers.insert(provider.id.clone(), provider);
+
+        let columns = parse_columns("index,name,base_url,key,current").unwrap();
+        let table = render(
+            &AppType::Claude,
+            &providers,
+            "p1",
+            &HashMap::new(),
+            &HashMap::new(),
+            &columns,
+            TableStyle::Bordered,
+        );
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert_eq!(
+            lines[1],
+            "| # | Name | Base URL                  | Key      | Current |"
+        );
+        assert_eq!(
+            lines[3],
+            "| 1 | 中转 | https://relay.example.com | ****1234 | *       |"
+        );
+        assert!(!table.contains("sk-relay-secret"));
+        assert!(parse_columns("name,bogus").is_err());
+    }
+
+    #[test]
+    fn plain_style_is_one_line_per_provider_with_status() {
+        let mut providers = IndexMap::new();
+        for (id, key) in [("a", "sk-aaaa-1111"), ("bb", "sk-bbbb-2222")] {
+            let provider = Provider::with_id(
+                id.to_string(),
+                id.to_uppercase(),
+                json!({ "env": { "ANTHROPIC_AUTH_TOKEN": key } }),
+                None,
+            );
+            providers.insert(provider.id.clone(), provider);
+        }
+        let mut health = HashMap::new();
+        health.insert(
+            "bb".to_string(),
+            ProviderHealth {
+                provider_id: "bb".to_string(),
+                app_type: "claude".to_string(),
+                is_healthy: false,
+                consecutive_failures: 3,
+                last_success_at: None,
+                last_failure_at: None,
+                last_error: None,
+                updated_at: String::new(),
+            },
+        );
+
+        let latency = HashMap::from([("a".to_string(), 320)]);
+
+        let columns = parse_columns("current,name,status,key,latency").unwrap();
+        let table = render(
+            &AppType::Claude,
+            &providers,
+            "a",
+            &health,
+            &latency,
+            &columns,
+            TableStyle::Plain,
+        );
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "Current  Name  Status   Key       Latency");
+        assert_eq!(lines[1], "*        A     -        ****1111  320ms");
+        assert_eq!(lines[2], "         BB    down(3)  ****2222  -");
+    }
+}
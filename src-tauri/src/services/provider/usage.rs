@@ -2,15 +2,84 @@
 //!
 //! Handles executing and formatting usage query results.
 
+use futures::future::join_all;
+use serde::Serialize;
+
+use super::usage_probe::{self, ProbeCredentials};
 use crate::app_config::AppType;
 use crate::error::AppError;
-use crate::provider::{UsageData, UsageResult, UsageScript};
+use crate::provider::{Provider, UsageData, UsageResult, UsageScript};
 use crate::settings;
 use crate::store::AppState;
-use crate::usage_script;
+use crate::usage_script::{self, ScriptLanguage};
+
+/// Run the script with the engine matching its language
+///
+/// JavaScript runs in the embedded QuickJS sandbox; shell/node/python run as
+/// an external process with credentials injected via environment variables,
+/// and only when allowed on this machine (see
+/// [`settings::external_usage_scripts_allowed`]).
+async fn run_usage_script(
+    language: &str,
+    script_code: &str,
+    api_key: &str,
+    base_url: &str,
+    timeout: u64,
+    access_token: Option<&str>,
+    user_id: Option<&str>,
+) -> Result<serde_json::Value, AppError> {
+    let language = ScriptLanguage::parse(language).ok_or_else(|| {
+        AppError::localized(
+            "usage_script.unsupported_language",
+            format!("不支持的脚本语言: {language}"),
+            format!("Unsupported script language: {language}"),
+        )
+    })?;
+
+    match language {
+        ScriptLanguage::JavaScript => {
+            usage_script::execute_usage_script(
+                script_code,
+                api_key,
+                base_url,
+                timeout,
+                access_token,
+                user_id,
+            )
+            .await
+        }
+        external => {
+            if !settings::external_usage_scripts_allowed() {
+                return Err(AppError::localized(
+                    "usage_script.external_not_allowed",
+                    format!(
+                        "未允许运行外部用量脚本：请在设置中开启 allowExternalUsageScripts 或设置 {}=1",
+                        settings::EXTERNAL_SCRIPTS_ENV
+                    ),
+                    format!(
+                        "External usage scripts are not allowed: enable allowExternalUsageScripts in the settings or set {}=1",
+                        settings::EXTERNAL_SCRIPTS_ENV
+                    ),
+                ));
+            }
+            usage_script::execute_external_script(
+                external,
+                script_code,
+                api_key,
+                base_url,
+                timeout,
+                access_token,
+                user_id,
+            )
+            .await
+        }
+    }
+}
 
 /// Execute usage script and format result (private helper method)
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn execute_and_format_usage_result(
+    language: &str,
     script_code: &str,
     api_key: &str,
     base_url: &str,
@@ -18,7 +87,8 @@ pub(crate) async fn execute_and_format_usage_result(
     access_token: Option<&str>,
     user_id: Option<&str>,
 ) -> Result<UsageResult, AppError> {
-    match usage_script::execute_usage_script(
+    match run_usage_script(
+        language,
         script_code,
         api_key,
         base_url,
@@ -54,28 +124,31 @@ pub(crate) async fn execute_and_format_usage_result(
                 error: None,
             })
         }
-        Err(err) => {
-            let lang = settings::get_settings()
-                .language
-                .unwrap_or_else(|| "zh".to_string());
-
-            let msg = match err {
-                AppError::Localized { zh, en, .. } => {
-                    if lang == "en" {
-                        en
-                    } else {
-                        zh
-                    }
-                }
-                other => other.to_string(),
-            };
+        Err(err) => Ok(failure_result(err)),
+    }
+}
 
-            Ok(UsageResult {
-                success: false,
-                data: None,
-                error: Some(msg),
-            })
+/// Convert an error into a failed usage result in the UI language
+fn failure_result(err: AppError) -> UsageResult {
+    let lang = settings::get_settings()
+        .language
+        .unwrap_or_else(|| "zh".to_string());
+
+    let msg = match err {
+        AppError::Localized { zh, en, .. } => {
+            if lang == "en" {
+                en
+            } else {
+                zh
+            }
         }
+        other => other.to_string(),
+    };
+
+    UsageResult {
+        success: false,
+        data: None,
+        error: Some(msg),
     }
 }
 
@@ -107,75 +180,124 @@ fn extract_base_url_from_provider(provider: &crate::provider::Provider) -> Optio
     }
 }
 
-/// Query provider usage (using saved script configuration)
+/// Query provider usage (using saved script or built-in probe configuration)
+///
+/// An enabled usage script takes precedence; otherwise the built-in probe
+/// selected by `meta.usageProvider` is used.
 pub async fn query_usage(
     state: &AppState,
     app_type: AppType,
     provider_id: &str,
 ) -> Result<UsageResult, AppError> {
-    let (script_code, timeout, api_key, base_url, access_token, user_id) = {
-        let providers = state.db.get_all_providers(app_type.as_str())?;
-        let provider = providers.get(provider_id).ok_or_else(|| {
-            AppError::localized(
-                "provider.not_found",
-                format!("供应商不存在: {provider_id}"),
-                format!("Provider not found: {provider_id}"),
-            )
-        })?;
+    let provider = state
+        .db
+        .get_provider_by_id(provider_id, app_type.as_str())?
+        .ok_or_else(|| AppError::provider_not_found(provider_id, app_type.as_str()))?;
 
-        let usage_script = provider
-            .meta
-            .as_ref()
-            .and_then(|m| m.usage_script.as_ref())
-            .ok_or_else(|| {
-                AppError::localized(
-                    "provider.usage.script.missing",
-                    "未配置用量查询脚本",
-                    "Usage script is not configured",
-                )
-            })?;
-        if !usage_script.enabled {
-            return Err(AppError::localized(
-                "provider.usage.disabled",
-                "用量查询未启用",
-                "Usage query is disabled",
-            ));
-        }
+    query_provider_usage(&provider).await
+}
+
+async fn query_provider_usage(provider: &Provider) -> Result<UsageResult, AppError> {
+    let meta = provider.meta.as_ref();
+    let usage_script = meta.and_then(|m| m.usage_script.as_ref());
+    let usage_provider = meta.and_then(|m| m.usage_provider.as_deref());
 
-        // Get credentials: prioritize UsageScript values, fallback to provider config
-        let api_key = usage_script
-            .api_key
-            .clone()
+    // Get credentials: prioritize UsageScript values, fallback to provider config
+    let creds = ProbeCredentials {
+        api_key: usage_script
+            .and_then(|s| s.api_key.clone())
             .filter(|k| !k.is_empty())
             .or_else(|| extract_api_key_from_provider(provider))
-            .unwrap_or_default();
-
-        let base_url = usage_script
-            .base_url
-            .clone()
+            .unwrap_or_default(),
+        base_url: usage_script
+            .and_then(|s| s.base_url.clone())
             .filter(|u| !u.is_empty())
             .or_else(|| extract_base_url_from_provider(provider))
-            .unwrap_or_default();
-
-        (
-            usage_script.code.clone(),
-            usage_script.timeout.unwrap_or(10),
-            api_key,
-            base_url,
-            usage_script.access_token.clone(),
-            usage_script.user_id.clone(),
-        )
+            .unwrap_or_default(),
+        access_token: usage_script.and_then(|s| s.access_token.clone()),
+        user_id: usage_script.and_then(|s| s.user_id.clone()),
     };
+    let timeout = usage_script.and_then(|s| s.timeout).unwrap_or(10);
 
-    execute_and_format_usage_result(
-        &script_code,
-        &api_key,
-        &base_url,
-        timeout,
-        access_token.as_deref(),
-        user_id.as_deref(),
-    )
-    .await
+    match (usage_script, usage_provider) {
+        (Some(script), _) if script.enabled => {
+            execute_and_format_usage_result(
+                &script.language,
+                &script.code,
+                &creds.api_key,
+                &creds.base_url,
+                timeout,
+                creds.access_token.as_deref(),
+                creds.user_id.as_deref(),
+            )
+            .await
+        }
+        (_, Some(probe_id)) => {
+            let probe = usage_probe::usage_provider_for(probe_id).ok_or_else(|| {
+                AppError::localized(
+                    "provider.usage.probe.unknown",
+                    format!("未知的内置用量查询: {probe_id}"),
+                    format!("Unknown built-in usage provider: {probe_id}"),
+                )
+            })?;
+            Ok(
+                match usage_probe::run_probe(probe.as_ref(), &creds, timeout).await {
+                    Ok(data) => UsageResult {
+                        success: true,
+                        data: Some(data),
+                        error: None,
+                    },
+                    Err(err) => failure_result(err),
+                },
+            )
+        }
+        (Some(_), None) => Err(AppError::localized(
+            "provider.usage.disabled",
+            "用量查询未启用",
+            "Usage query is disabled",
+        )),
+        (None, None) => Err(AppError::localized(
+            "provider.usage.script.missing",
+            "未配置用量查询脚本",
+            "Usage script is not configured",
+        )),
+    }
+}
+
+/// Usage summary entry for a single provider
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderUsageSummary {
+    pub provider_id: String,
+    pub provider_name: String,
+    pub result: UsageResult,
+}
+
+/// Query usage of every provider with an enabled script or built-in probe
+pub async fn query_all_usage(
+    state: &AppState,
+    app_type: AppType,
+) -> Result<Vec<ProviderUsageSummary>, AppError> {
+    let providers = state.db.get_all_providers(app_type.as_str())?;
+
+    let configured = providers.into_values().filter(|p| {
+        p.meta.as_ref().is_some_and(|m| {
+            m.usage_script.as_ref().is_some_and(|s| s.enabled) || m.usage_provider.is_some()
+        })
+    });
+
+    let tasks = configured.map(|provider| async move {
+        let result = query_provider_usage(&provider)
+            .await
+            .unwrap_or_else(failure_result);
+        ProviderUsageSummary {
+            provider_id: provider.id,
+            provider_name: provider.name,
+            result,
+        }
+    });
+
+    Ok(join_all(tasks).await)
 }
 
 /// Test usage script (using temporary script content, not saved)
@@ -184,6 +306,7 @@ pub async fn test_usage_script(
     _state: &AppState,
     _app_type: AppType,
     _provider_id: &str,
+    language: &str,
     script_code: &str,
     timeout: u64,
     api_key: Option<&str>,
@@ -193,6 +316,7 @@ pub async fn test_usage_script(
 ) -> Result<UsageResult, AppError> {
     // Use provided credential parameters directly for testing
     execute_and_format_usage_result(
+        language,
         script_code,
         api_key.unwrap_or(""),
         base_url.unwrap_or(""),
@@ -203,6 +327,42 @@ pub async fn test_usage_script(
     .await
 }
 
+/// Disable a usage script that would run as an external process
+///
+/// Providers from import files and sync remotes must not bring commands that
+/// run on this machine. A script identical to the one of `trusted` (the local
+/// copy of the provider) is kept. Returns whether the script was disabled.
+pub(crate) fn disable_untrusted_script(
+    provider: &mut Provider,
+    trusted: Option<&Provider>,
+) -> bool {
+    let Some(script) = provider
+        .meta
+        .as_mut()
+        .and_then(|meta| meta.usage_script.as_mut())
+    else {
+        return false;
+    };
+    if !script.enabled
+        || ScriptLanguage::parse(&script.language) == Some(ScriptLanguage::JavaScript)
+    {
+        return false;
+    }
+    let known = trusted
+        .and_then(|local| local.meta.as_ref()?.usage_script.as_ref())
+        .is_some_and(|local| local.language == script.language && local.code == script.code);
+    if known {
+        return false;
+    }
+    script.enabled = false;
+    log::warn!(
+        "已停用供应商 {} 导入的外部用量脚本（{}）",
+        provider.id,
+        script.language
+    );
+    true
+}
+
 /// Validate UsageScript configuration (boundary checks)
 pub(crate) fn validate_usage_script(script: &UsageScript) -> Result<(), AppError> {
     // Validate auto query interval (0-1440 minutes, max 24 hours)
@@ -0,0 +1,427 @@
+//! Built-in usage probes
+//!
+//! Native HTTP balance queries for common relay APIs, used when a provider
+//! selects a probe via `meta.usageProvider` instead of a custom script.
+
+use reqwest::header::HeaderMap;
+use reqwest::{Client, Method};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+use crate::error::AppError;
+use crate::provider::UsageData;
+
+/// new-api / one-api quota units per USD
+const NEW_API_QUOTA_PER_USD: f64 = 500_000.0;
+
+/// Credentials passed to a usage probe
+#[derive(Debug, Clone, Default)]
+pub struct ProbeCredentials {
+    pub api_key: String,
+    pub base_url: String,
+    pub access_token: Option<String>,
+    pub user_id: Option<String>,
+}
+
+/// HTTP request built by a usage probe
+#[derive(Debug, Clone)]
+pub struct ProbeRequest {
+    pub method: Method,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<Value>,
+}
+
+/// A vendor-specific usage query
+///
+/// Implementations only build the request and parse the response, so they
+/// stay free of I/O and can be tested without network access.
+pub trait UsageProvider: Send + Sync {
+    /// Identifier stored in `meta.usageProvider`
+    fn id(&self) -> &'static str;
+
+    /// Build the HTTP request for the given credentials
+    fn build_request(&self, creds: &ProbeCredentials) -> Result<ProbeRequest, AppError>;
+
+    /// Parse a successful response into usage data
+    fn parse_response(&self, headers: &HeaderMap, body: &str) -> Result<Vec<UsageData>, AppError>;
+}
+
+/// Look up a built-in probe by identifier
+pub fn usage_provider_for(id: &str) -> Option<Box<dyn UsageProvider>> {
+    match id.trim().to_ascii_lowercase().as_str() {
+        "newapi" | "new-api" | "oneapi" | "one-api" => Some(Box::new(NewApiUsage)),
+        "openrouter" => Some(Box::new(OpenRouterUsage)),
+        "anthropic" => Some(Box::new(AnthropicHeadersUsage)),
+        _ => None,
+    }
+}
+
+/// Execute a probe and return the parsed usage data
+pub async fn run_probe(
+    probe: &dyn UsageProvider,
+    creds: &ProbeCredentials,
+    timeout_secs: u64,
+) -> Result<Vec<UsageData>, AppError> {
+    let request = probe.build_request(creds)?;
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(timeout_secs.clamp(2, 30)))
+        .build()
+        .map_err(|e| {
+            AppError::localized(
+                "usage_probe.client_create_failed",
+                format!("创建客户端失败: {e}"),
+                format!("Failed to create client: {e}"),
+            )
+        })?;
+
+    let mut req = client.request(request.method, &request.url);
+    for (k, v) in &request.headers {
+        req = req.header(k, v);
+    }
+    if let Some(body) = &request.body {
+        req = req.json(body);
+    }
+
+    let resp = req.send().await.map_err(|e| {
+        AppError::localized(
+            "usage_probe.request_failed",
+            format!("请求失败: {e}"),
+            format!("Request failed: {e}"),
+        )
+    })?;
+
+    let status = resp.status();
+    let headers = resp.headers().clone();
+    let text = resp.text().await.map_err(|e| {
+        AppError::localized(
+            "usage_probe.read_response_failed",
+            format!("读取响应失败: {e}"),
+            format!("Failed to read response: {e}"),
+        )
+    })?;
+
+    if !status.is_success() {
+        let preview: String = text.chars().take(200).collect();
+        return Err(AppError::localized(
+            "usage_probe.http_error",
+            format!("HTTP {status} : {preview}"),
+            format!("HTTP {status} : {preview}"),
+        ));
+    }
+
+    probe.parse_response(&headers, &text)
+}
+
+fn require_base_url(creds: &ProbeCredentials) -> Result<&str, AppError> {
+    let base = creds.base_url.trim().trim_end_matches('/');
+    if base.is_empty() {
+        return Err(AppError::localized(
+            "usage_probe.base_url_required",
+            "缺少 Base URL",
+            "Base URL is required",
+        ));
+    }
+    Ok(base)
+}
+
+fn parse_json(body: &str) -> Result<Value, AppError> {
+    serde_json::from_str(body).map_err(|e| {
+        AppError::localized(
+            "usage_probe.response_parse_failed",
+            format!("解析响应 JSON 失败: {e}"),
+            format!("Failed to parse response JSON: {e}"),
+        )
+    })
+}
+
+fn unexpected_format(field: &str) -> AppError {
+    AppError::localized(
+        "usage_probe.unexpected_format",
+        format!("响应缺少字段: {field}"),
+        format!("Response is missing field: {field}"),
+    )
+}
+
+/// one-api / new-api style `/api/user/self`
+struct NewApiUsage;
+
+impl UsageProvider for NewApiUsage {
+    fn id(&self) -> &'static str {
+        "newapi"
+    }
+
+    fn build_request(&self, creds: &ProbeCredentials) -> Result<ProbeRequest, AppError> {
+        let base = require_base_url(creds)?;
+        let token = creds
+            .access_token
+            .as_deref()
+            .filter(|t| !t.is_empty())
+            .unwrap_or(&creds.api_key);
+
+        let mut headers = vec![("Authorization".to_string(), format!("Bearer {token}"))];
+        if let Some(uid) = creds.user_id.as_deref().filter(|u| !u.is_empty()) {
+            headers.push(("New-Api-User".to_string(), uid.to_string()));
+        }
+
+        Ok(ProbeRequest {
+            method: Method::GET,
+            url: format!("{base}/api/user/self"),
+            headers,
+            body: None,
+        })
+    }
+
+    fn parse_response(&self, _headers: &HeaderMap, body: &str) -> Result<Vec<UsageData>, AppError> {
+        let value = parse_json(body)?;
+
+        if value.get("success").and_then(Value::as_bool) == Some(false) {
+            let message = value
+                .get("message")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            return Ok(vec![UsageData {
+                plan_name: None,
+                extra: None,
+                is_valid: Some(false),
+                invalid_message: Some(message),
+                total: None,
+                used: None,
+                remaining: None,
+                unit: None,
+            }]);
+        }
+
+        let data = value.get("data").ok_or_else(|| unexpected_format("data"))?;
+        let quota = data
+            .get("quota")
+            .and_then(Value::as_f64)
+            .ok_or_else(|| unexpected_format("data.quota"))?;
+        let used = data
+            .get("used_quota")
+            .and_then(Value::as_f64)
+            .unwrap_or(0.0);
+
+        let remaining = quota / NEW_API_QUOTA_PER_USD;
+        let used = used / NEW_API_QUOTA_PER_USD;
+
+        Ok(vec![UsageData {
+            plan_name: data
+                .get("group")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            extra: None,
+            is_valid: Some(true),
+            invalid_message: None,
+            total: Some(remaining + used),
+            used: Some(used),
+            remaining: Some(remaining),
+            unit: Some("USD".to_string()),
+        }])
+    }
+}
+
+/// OpenRouter `/api/v1/credits`
+struct OpenRouterUsage;
+
+impl UsageProvider for OpenRouterUsage {
+    fn id(&self) -> &'static str {
+        "openrouter"
+    }
+
+    fn build_request(&self, creds: &ProbeCredentials) -> Result<ProbeRequest, AppError> {
+        let base = creds.base_url.trim().trim_end_matches('/');
+        let base = if base.is_empty() {
+            "https://openrouter.ai/api"
+        } else {
+            base.trim_end_matches("/v1")
+        };
+
+        Ok(ProbeRequest {
+            method: Method::GET,
+            url: format!("{base}/v1/credits"),
+            headers: vec![(
+                "Authorization".to_string(),
+                format!("Bearer {}", creds.api_key),
+            )],
+            body: None,
+        })
+    }
+
+    fn parse_response(&self, _headers: &HeaderMap, body: &str) -> Result<Vec<UsageData>, AppError> {
+        let value = parse_json(body)?;
+        let data = value.get("data").ok_or_else(|| unexpected_format("data"))?;
+        let total = data
+            .get("total_credits")
+            .and_then(Value::as_f64)
+            .ok_or_else(|| unexpected_format("data.total_credits"))?;
+        let used = data
+            .get("total_usage")
+            .and_then(Value::as_f64)
+            .unwrap_or(0.0);
+
+        Ok(vec![UsageData {
+            plan_name: Some("OpenRouter".to_string()),
+            extra: None,
+            is_valid: Some(true),
+            invalid_message: None,
+            total: Some(total),
+            used: Some(used),
+            remaining: Some(total - used),
+            unit: Some("USD".to_string()),
+        }])
+    }
+}
+
+/// Anthropic rate-limit headers
+///
+/// Sends a token-count request (not billed) and reports the
+/// `anthropic-ratelimit-*` headers of the response.
+struct AnthropicHeadersUsage;
+
+impl AnthropicHeadersUsage {
+    const LIMITS: [(&'static str, &'static str); 3] = [
+        ("requests", "requests"),
+        ("tokens", "tokens"),
+        ("input-tokens", "input tokens"),
+    ];
+}
+
+impl UsageProvider for AnthropicHeadersUsage {
+    fn id(&self) -> &'static str {
+        "anthropic"
+    }
+
+    fn build_request(&self, creds: &ProbeCredentials) -> Result<ProbeRequest, AppError> {
+        let base = creds.base_url.trim().trim_end_matches('/');
+        let base = if base.is_empty() {
+            "https://api.anthropic.com"
+        } else {
+            base
+        };
+
+        Ok(ProbeRequest {
+            method: Method::POST,
+            url: format!("{base}/v1/messages/count_tokens"),
+            headers: vec![
+                ("x-api-key".to_string(), creds.api_key.clone()),
+                ("anthropic-version".to_string(), "2023-06-01".to_string()),
+            ],
+            body: Some(json!({
+                "model": "claude-3-5-haiku-latest",
+                "messages": [{ "role": "user", "content": "ping" }]
+            })),
+        })
+    }
+
+    fn parse_response(&self, headers: &HeaderMap, _body: &str) -> Result<Vec<UsageData>, AppError> {
+        let header_f64 = |name: String| {
+            headers
+                .get(name.as_str())
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<f64>().ok())
+        };
+
+        let usage: Vec<UsageData> = Self::LIMITS
+            .iter()
+            .filter_map(|(key, unit)| {
+                let limit = header_f64(format!("anthropic-ratelimit-{key}-limit"))?;
+                let remaining = header_f64(format!("anthropic-ratelimit-{key}-remaining"))?;
+                let reset = headers
+                    .get(format!("anthropic-ratelimit-{key}-reset").as_str())
+                    .and_then(|v| v.to_str().ok())
+                    .map(|r| format!("reset: {r}"));
+                Some(UsageData {
+                    plan_name: Some(format!("Rate limit ({unit})")),
+                    extra: reset,
+                    is_valid: Some(true),
+                    invalid_message: None,
+                    total: Some(limit),
+                    used: Some(limit - remaining),
+                    remaining: Some(remaining),
+                    unit: Some(unit.to_string()),
+                })
+            })
+            .collect();
+
+        if usage.is_empty() {
+            return Err(unexpected_format("anthropic-ratelimit-*"));
+        }
+        Ok(usage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn creds(base_url: &str) -> ProbeCredentials {
+        ProbeCredentials {
+            api_key: "sk-test".to_string(),
+            base_url: base_url.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn lookup_resolves_aliases() {
+        assert_eq!(usage_provider_for("one-api").unwrap().id(), "newapi");
+        assert_eq!(usage_provider_for("OpenRouter").unwrap().id(), "openrouter");
+        assert!(usage_provider_for("unknown").is_none());
+    }
+
+    #[test]
+    fn new_api_converts_quota_to_usd() {
+        let probe = NewApiUsage;
+        let req = probe
+            .build_request(&creds("https://relay.example/"))
+            .unwrap();
+        assert_eq!(req.url, "https://relay.example/api/user/self");
+
+        let body =
+            r#"{"success":true,"data":{"quota":5000000,"used_quota":2500000,"group":"vip"}}"#;
+        let usage = probe.parse_response(&HeaderMap::new(), body).unwrap();
+        assert_eq!(usage[0].remaining, Some(10.0));
+        assert_eq!(usage[0].used, Some(5.0));
+        assert_eq!(usage[0].total, Some(15.0));
+        assert_eq!(usage[0].plan_name.as_deref(), Some("vip"));
+    }
+
+    #[test]
+    fn openrouter_reports_remaining_credits() {
+        let probe = OpenRouterUsage;
+        let req = probe.build_request(&creds("")).unwrap();
+        assert_eq!(req.url, "https://openrouter.ai/api/v1/credits");
+
+        let body = r#"{"data":{"total_credits":20.0,"total_usage":7.5}}"#;
+        let usage = probe.parse_response(&HeaderMap::new(), body).unwrap();
+        assert_eq!(usage[0].remaining, Some(12.5));
+    }
+
+    #[test]
+    fn anthropic_reads_ratelimit_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "anthropic-ratelimit-requests-limit",
+            HeaderValue::from_static("50"),
+        );
+        headers.insert(
+            "anthropic-ratelimit-requests-remaining",
+            HeaderValue::from_static("49"),
+        );
+
+        let usage = AnthropicHeadersUsage
+            .parse_response(&headers, "{}")
+            .unwrap();
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage[0].remaining, Some(49.0));
+        assert_eq!(usage[0].used, Some(1.0));
+
+        assert!(AnthropicHeadersUsage
+            .parse_response(&HeaderMap::new(), "{}")
+            .is_err());
+    }
+}
@@ -0,0 +1,566 @@
+//! Per-app provider validation
+//!
+//! Checks a provider's `settings_config` against what each app needs to
+//! actually work, returning every problem found instead of stopping at the
+//! first one:
+//!
+//! - Claude: `env.ANTHROPIC_BASE_URL` plus `ANTHROPIC_API_KEY` or `ANTHROPIC_AUTH_TOKEN`
+//!   (official providers, and providers configuring neither, rely on the app's own
+//!   login and only get a warning)
+//! - Codex: an `auth` object and valid TOML in `config`
+//! - Gemini: an `env` object or a top-level `apiKey`; with `meta.geminiAuthMode`
+//!   set, OAuth needs no key and Vertex AI needs `GOOGLE_CLOUD_PROJECT` and
+//!   `GOOGLE_CLOUD_LOCATION` (or an express-mode `GOOGLE_API_KEY`)
+//!
+//! [`validate_live`] checks the live config an app actually reads (in the shape
+//! returned by [`read_live_settings`](super::read_live_settings)): unknown
+//! top-level keys, malformed base URLs, and credential variables that are set
+//! but empty or that conflict. It backs `cc-switch lint` and also runs on every
+//! write, where errors stop the write.
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::app_config::AppType;
+use crate::provider::{GeminiAuthMode, Provider};
+
+/// Severity of a validation issue; errors block saving
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A single problem found in a provider
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    /// Path of the offending field inside `settingsConfig` (e.g. `env.ANTHROPIC_BASE_URL`)
+    pub field: String,
+    pub message: String,
+}
+
+impl ValidationIssue {
+    pub(crate) fn error(field: &str, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            field: field.to_string(),
+            message: message.into(),
+        }
+    }
+
+    pub(crate) fn warning(field: &str, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            field: field.to_string(),
+            message: message.into(),
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+/// Validate a provider for the given app
+pub fn validate_provider(provider: &Provider, app_type: &AppType) -> Vec<ValidationIssue> {
+    let settings = &provider.settings_config;
+    if !settings.is_object() {
+        return vec![ValidationIssue::error(
+            "",
+            "settingsConfig 必须是 JSON 对象",
+        )];
+    }
+
+    match app_type {
+        AppType::Claude => validate_claude(provider),
+        AppType::Codex => validate_codex(settings),
+        AppType::Gemini => validate_gemini(provider),
+    }
+}
+
+fn validate_claude(provider: &Provider) -> Vec<ValidationIssue> {
+    let env = match provider.settings_config.get("env") {
+        Some(Value::Object(env)) => Some(env),
+        Some(_) => return vec![ValidationIssue::error("env", "env 必须是 JSON 对象")],
+        None => None,
+    };
+    // 官方供应商通过应用内登录认证，不要求地址和密钥
+    if is_official(provider) {
+        return Vec::new();
+    }
+    let Some(env) = env else {
+        return vec![ValidationIssue::error(
+            "env",
+            "缺少 env（需要 ANTHROPIC_BASE_URL 与 API Key）",
+        )];
+    };
+
+    let key_names = ["ANTHROPIC_API_KEY", "ANTHROPIC_AUTH_TOKEN"];
+    if !env.contains_key("ANTHROPIC_BASE_URL") && !key_names.iter().any(|k| env.contains_key(*k)) {
+        return vec![ValidationIssue::warning(
+            "env.ANTHROPIC_BASE_URL",
+            "未配置 ANTHROPIC_BASE_URL 与 API Key，将使用 Claude Code 的登录态",
+        )];
+    }
+
+    let mut issues = Vec::new();
+
+    match env.get("ANTHROPIC_BASE_URL").and_then(Value::as_str) {
+        Some(url) if !url.trim().is_empty() => {
+            if url::Url::parse(url.trim()).is_err() {
+                issues.push(ValidationIssue::error(
+                    "env.ANTHROPIC_BASE_URL",
+                    format!("不是有效的 URL: {url}"),
+                ));
+            }
+        }
+        _ => issues.push(ValidationIssue::error(
+            "env.ANTHROPIC_BASE_URL",
+            "缺少 ANTHROPIC_BASE_URL",
+        )),
+    }
+
+    let keys: Vec<&str> = key_names
+        .into_iter()
+        .filter_map(|name| env.get(name).and_then(Value::as_str))
+        .collect();
+    if keys.is_empty() {
+        issues.push(ValidationIssue::error(
+            "env.ANTHROPIC_AUTH_TOKEN",
+            "缺少 ANTHROPIC_API_KEY 或 ANTHROPIC_AUTH_TOKEN",
+        ));
+    } else if keys.iter().all(|key| key.trim().is_empty()) {
+        issues.push(ValidationIssue::warning(
+            "env.ANTHROPIC_AUTH_TOKEN",
+            "API Key 为空",
+        ));
+    }
+
+    issues
+}
+
+fn validate_codex(settings: &Value) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+
+    match settings.get("auth") {
+        Some(Value::Object(_)) => {}
+        Some(_) => issues.push(ValidationIssue::error("auth", "auth 必须是 JSON 对象")),
+        None => issues.push(ValidationIssue::error("auth", "缺少 auth 配置")),
+    }
+
+    match settings.get("config") {
+        None | Some(Value::Null) => {}
+        Some(Value::String(text)) => {
+            if let Err(e) = toml::from_str::<toml::Table>(text) {
+                issues.push(ValidationIssue::error(
+                    "config",
+                    format!("config 不是有效的 TOML: {}", e.message()),
+                ));
+            }
+        }
+        Some(_) => issues.push(ValidationIssue::error("config", "config 必须是字符串")),
+    }
+
+    issues
+}
+
+fn validate_gemini(provider: &Provider) -> Vec<ValidationIssue> {
+    let settings = &provider.settings_config;
+    let mode = provider
+        .meta
+        .as_ref()
+        .and_then(|meta| meta.gemini_auth_mode);
+    let mut issues = Vec::new();
+
+    match (settings.get("env"), settings.get("apiKey")) {
+        (Some(Value::Object(_)), _) => {}
+        (Some(_), _) => issues.push(ValidationIssue::error("env", "env 必须是 JSON 对象")),
+        (None, Some(Value::String(_))) => {}
+        // OAuth 模式通过 Google 账号登录，不需要密钥
+        (None, _) if mode == Some(GeminiAuthMode::Oauth) => {}
+        (None, _) => issues.push(ValidationIssue::error("env", "需要 env 或 apiKey")),
+    }
+
+    if mode == Some(GeminiAuthMode::Vertex) {
+        let env = settings.get("env").and_then(Value::as_object);
+        let has = |key: &str| {
+            env.and_then(|env| env.get(key))
+                .and_then(Value::as_str)
+                .is_some_and(|v| !v.trim().is_empty())
+        };
+        if !has("GOOGLE_API_KEY") {
+            for key in ["GOOGLE_CLOUD_PROJECT", "GOOGLE_CLOUD_LOCATION"] {
+                if !has(key) {
+                    issues.push(ValidationIssue::error(
+                        &format!("env.{key}"),
+                        format!("Vertex AI 模式缺少 {key}"),
+                    ));
+                }
+            }
+        }
+    }
+
+    if let Some(config) = settings.get("config") {
+        if !(config.is_object() || config.is_null()) {
+            issues.push(ValidationIssue::error("config", "config 必须是 JSON 对象"));
+        }
+    }
+
+    issues
+}
+
+fn is_official(provider: &Provider) -> bool {
+    provider.category.as_deref() == Some("official")
+}
+
+/// Top-level keys Claude Code reads from settings.json
+const CLAUDE_SETTINGS_KEYS: &[&str] = &[
+    "$schema",
+    "apiKeyHelper",
+    "alwaysThinkingEnabled",
+    "awsAuthRefresh",
+    "awsCredentialExport",
+    "cleanupPeriodDays",
+    "companyAnnouncements",
+    "disableAllHooks",
+    "disabledMcpjsonServers",
+    "enableAllProjectMcpServers",
+    "enabledMcpjsonServers",
+    "enabledPlugins",
+    "env",
+    "extraKnownMarketplaces",
+    "forceLoginMethod",
+    "forceLoginOrgUUID",
+    "hooks",
+    "includeCoAuthoredBy",
+    "model",
+    "otelHeadersHelper",
+    "outputStyle",
+    "permissions",
+    "sandbox",
+    "spinnerTipsEnabled",
+    "statusLine",
+    "subagentStatusLine",
+];
+
+/// Top-level keys Codex reads from config.toml
+const CODEX_CONFIG_KEYS: &[&str] = &[
+    "approval_policy",
+    "disable_response_storage",
+    "experimental_use_exec_command_tool",
+    "features",
+    "file_opener",
+    "hide_agent_reasoning",
+    "history",
+    "mcp_servers",
+    "model",
+    "model_auto_compact_token_limit",
+    "model_context_window",
+    "model_max_output_tokens",
+    "model_provider",
+    "model_providers",
+    "model_reasoning_effort",
+    "model_reasoning_summary",
+    "model_supports_reasoning_summaries",
+    "model_verbosity",
+    "notice",
+    "notify",
+    "preferred_auth_method",
+    "profile",
+    "profiles",
+    "project_doc_max_bytes",
+    "projects",
+    "sandbox_mode",
+    "sandbox_workspace_write",
+    "shell_environment_policy",
+    "show_raw_agent_reasoning",
+    "tools",
+    "tui",
+    "windows_wsl_setup_acknowledged",
+];
+
+/// Providers built into Codex that need no `[model_providers.*]` entry
+const CODEX_BUILTIN_PROVIDERS: &[&str] = &["openai", "oss", "ollama", "lmstudio"];
+
+/// Check a live config for the given app
+pub fn validate_live(app_type: &AppType, live: &Value) -> Vec<ValidationIssue> {
+    match app_type {
+        AppType::Claude => match live.as_object() {
+            Some(settings) => lint_claude(settings),
+            None => vec![ValidationIssue::error("", "settings.json 必须是 JSON 对象")],
+        },
+        AppType::Codex => lint_codex(live),
+        AppType::Gemini => lint_gemini(live),
+    }
+}
+
+fn lint_claude(settings: &Map<String, Value>) -> Vec<ValidationIssue> {
+    let mut issues = unknown_keys(settings.keys(), CLAUDE_SETTINGS_KEYS, "");
+    let env = match settings.get("env") {
+        Some(Value::Object(env)) => env,
+        Some(_) => {
+            issues.push(ValidationIssue::error("env", "env 必须是 JSON 对象"));
+            return issues;
+        }
+        None => return issues,
+    };
+
+    for (key, value) in env {
+        if !value.is_string() {
+            issues.push(ValidationIssue::warning(
+                &format!("env.{key}"),
+                "环境变量的值应为字符串",
+            ));
+        }
+    }
+    if let Some(url) = env.get("ANTHROPIC_BASE_URL").and_then(Value::as_str) {
+        check_base_url(&mut issues, "env.ANTHROPIC_BASE_URL", url);
+    }
+    let api_key = env.get("ANTHROPIC_API_KEY").and_then(Value::as_str);
+    let token = env.get("ANTHROPIC_AUTH_TOKEN").and_then(Value::as_str);
+    match (api_key.map(str::trim), token.map(str::trim)) {
+        (Some(""), Some("")) => issues.push(ValidationIssue::warning(
+            "env.ANTHROPIC_AUTH_TOKEN",
+            "ANTHROPIC_API_KEY 与 ANTHROPIC_AUTH_TOKEN 都为空",
+        )),
+        (Some(key), Some(token)) if !key.is_empty() && !token.is_empty() => {
+            issues.push(ValidationIssue::warning(
+                "env.ANTHROPIC_API_KEY",
+                "同时设置了 ANTHROPIC_API_KEY 与 ANTHROPIC_AUTH_TOKEN，Claude Code 会提示认证冲突",
+            ))
+        }
+        _ => {}
+    }
+    issues
+}
+
+fn lint_codex(live: &Value) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+
+    match live.get("auth") {
+        Some(Value::Object(auth)) => match auth.get("OPENAI_API_KEY") {
+            None | Some(Value::Null) | Some(Value::String(_)) => {}
+            Some(_) => issues.push(ValidationIssue::error(
+                "auth.OPENAI_API_KEY",
+                "OPENAI_API_KEY 必须是字符串或 null",
+            )),
+        },
+        Some(Value::Null) | None => {}
+        Some(_) => issues.push(ValidationIssue::error("auth", "auth.json 必须是 JSON 对象")),
+    }
+
+    let text = live.get("config").and_then(Value::as_str).unwrap_or("");
+    let config = match toml::from_str::<toml::Table>(text) {
+        Ok(config) => config,
+        Err(e) => {
+            issues.push(ValidationIssue::error(
+                "config",
+                format!("config.toml 不是有效的 TOML: {}", e.message()),
+            ));
+            return issues;
+        }
+    };
+    issues.extend(unknown_keys(config.keys(), CODEX_CONFIG_KEYS, "config."));
+
+    let providers = config
+        .get("model_providers")
+        .and_then(toml::Value::as_table);
+    for (name, entry) in providers.into_iter().flatten() {
+        if let Some(url) = entry.get("base_url").and_then(toml::Value::as_str) {
+            check_base_url(
+                &mut issues,
+                &format!("config.model_providers.{name}.base_url"),
+                url,
+            );
+        }
+    }
+    if let Some(name) = config.get("model_provider").and_then(toml::Value::as_str) {
+        let defined = providers.is_some_and(|providers| providers.contains_key(name));
+        if !defined && !CODEX_BUILTIN_PROVIDERS.contains(&name) {
+            issues.push(ValidationIssue::warning(
+                "config.model_provider",
+                format!("model_provider = \"{name}\" 没有对应的 [model_providers.{name}]"),
+            ));
+        }
+    }
+    issues
+}
+
+fn lint_gemini(live: &Value) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    let env = live.get("env").and_then(Value::as_object);
+    let var = |key: &str| env.and_then(|env| env.get(key)).and_then(Value::as_str);
+
+    if let Some(url) = var("GOOGLE_GEMINI_BASE_URL") {
+        check_base_url(&mut issues, "env.GOOGLE_GEMINI_BASE_URL", url);
+    }
+    match (
+        var("GEMINI_API_KEY").map(str::trim),
+        var("GOOGLE_API_KEY").map(str::trim),
+    ) {
+        (Some(""), None | Some("")) | (None, Some("")) => issues.push(ValidationIssue::warning(
+            "env.GEMINI_API_KEY",
+            "GEMINI_API_KEY / GOOGLE_API_KEY 已设置但为空",
+        )),
+        (Some(gemini), Some(google)) if !gemini.is_empty() && !google.is_empty() => {
+            issues.push(ValidationIssue::warning(
+                "env.GOOGLE_API_KEY",
+                "同时设置了 GEMINI_API_KEY 与 GOOGLE_API_KEY，Gemini CLI 优先使用 GOOGLE_API_KEY",
+            ))
+        }
+        _ => {}
+    }
+
+    if let Some(config) = live.get("config") {
+        if !(config.is_object() || config.is_null()) {
+            issues.push(ValidationIssue::error(
+                "config",
+                "settings.json 必须是 JSON 对象",
+            ));
+        }
+    }
+    issues
+}
+
+fn unknown_keys<'a>(
+    keys: impl Iterator<Item = &'a String>,
+    known: &[&str],
+    prefix: &str,
+) -> Vec<ValidationIssue> {
+    keys.filter(|key| !known.contains(&key.as_str()))
+        .map(|key| ValidationIssue::warning(&format!("{prefix}{key}"), "未知字段"))
+        .collect()
+}
+
+fn check_base_url(issues: &mut Vec<ValidationIssue>, field: &str, url: &str) {
+    let url = url.trim();
+    if url.is_empty() {
+        issues.push(ValidationIssue::warning(field, "Base URL 为空"));
+        return;
+    }
+    match url::Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") && parsed.host().is_some() => {}
+        _ => issues.push(ValidationIssue::error(
+            field,
+            format!("不是有效的 http(s) URL: {url}"),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn provider(settings: Value) -> Provider {
+        Provider::with_id("p".to_string(), "P".to_string(), settings, None)
+    }
+
+    #[test]
+    fn claude_requires_base_url_and_key() {
+        let issues = validate_provider(
+            &provider(json!({ "env": { "ANTHROPIC_BASE_URL": "not a url" } })),
+            &AppType::Claude,
+        );
+        let fields: Vec<&str> = issues.iter().map(|i| i.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            vec!["env.ANTHROPIC_BASE_URL", "env.ANTHROPIC_AUTH_TOKEN"]
+        );
+        assert!(issues.iter().all(ValidationIssue::is_error));
+
+        let login = validate_provider(&provider(json!({ "env": {} })), &AppType::Claude);
+        assert_eq!(login.len(), 1);
+        assert!(!login[0].is_error());
+
+        let ok = provider(json!({ "env": {
+            "ANTHROPIC_BASE_URL": "https://relay.example.com",
+            "ANTHROPIC_API_KEY": "sk"
+        } }));
+        assert!(validate_provider(&ok, &AppType::Claude).is_empty());
+
+        let mut official = provider(json!({ "env": {} }));
+        official.category = Some("official".to_string());
+        assert!(validate_provider(&official, &AppType::Claude).is_empty());
+    }
+
+    #[test]
+    fn codex_and_gemini_shapes() {
+        let issues = validate_provider(
+            &provider(json!({ "auth": {}, "config": "model = " })),
+            &AppType::Codex,
+        );
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].field, "config");
+
+        assert!(
+            validate_provider(&provider(json!({ "apiKey": "k" })), &AppType::Gemini).is_empty()
+        );
+        assert!(validate_provider(&provider(json!({})), &AppType::Gemini)[0].is_error());
+
+        let mut vertex = provider(json!({ "env": { "GOOGLE_CLOUD_PROJECT": "demo" } }));
+        vertex.meta = Some(crate::provider::ProviderMeta {
+            gemini_auth_mode: Some(GeminiAuthMode::Vertex),
+            ..Default::default()
+        });
+        let issues = validate_provider(&vertex, &AppType::Gemini);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].field, "env.GOOGLE_CLOUD_LOCATION");
+
+        let mut oauth = provider(json!({}));
+        oauth.meta = Some(crate::provider::ProviderMeta {
+            gemini_auth_mode: Some(GeminiAuthMode::Oauth),
+            ..Default::default()
+        });
+        assert!(validate_provider(&oauth, &AppType::Gemini).is_empty());
+    }
+
+    #[test]
+    fn live_configs_report_unknown_keys_urls_and_key_conflicts() {
+        let claude = json!({
+            "env": {
+                "ANTHROPIC_BASE_URL": "relay.example.com",
+                "ANTHROPIC_API_KEY": "",
+                "ANTHROPIC_AUTH_TOKEN": ""
+            },
+            "permisions": {}
+        });
+        let issues = validate_live(&AppType::Claude, &claude);
+        let fields: Vec<(&str, bool)> = issues
+            .iter()
+            .map(|i| (i.field.as_str(), i.is_error()))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("permisions", false),
+                ("env.ANTHROPIC_BASE_URL", true),
+                ("env.ANTHROPIC_AUTH_TOKEN", false),
+            ]
+        );
+
+        let codex = json!({
+            "auth": { "OPENAI_API_KEY": "sk" },
+            "config": "model_provider = \"relay\"\n[model_providers.other]\nbase_url = \"ftp://x\"\n"
+        });
+        let fields: Vec<String> = validate_live(&AppType::Codex, &codex)
+            .into_iter()
+            .map(|i| i.field)
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                "config.model_providers.other.base_url",
+                "config.model_provider"
+            ]
+        );
+        let broken = json!({ "auth": {}, "config": "model = " });
+        assert!(validate_live(&AppType::Codex, &broken)[0].is_error());
+
+        let gemini = json!({ "env": { "GEMINI_API_KEY": "k", "GOOGLE_GEMINI_BASE_URL": "https://g.example.com" } });
+        assert!(validate_live(&AppType::Gemini, &gemini).is_empty());
+    }
+}
@@ -76,6 +76,37 @@ impl ProxyService {
         Ok(info)
     }
 
+    /// 以指定地址/端口启动代理服务器（命令行前台运行，不修改已保存的代理配置）
+    ///
+    /// 不接管 Live 配置：客户端需自行把 base URL 指向本地代理。每个请求都按数据库中
+    /// 的当前供应商路由，因此在界面或命令行中切换供应商会立即生效，无需重启客户端会话。
+    pub async fn start_detached(
+        &self,
+        address: Option<String>,
+        port: Option<u16>,
+    ) -> Result<ProxyServerInfo, String> {
+        let mut config = self
+            .db
+            .get_proxy_config()
+            .await
+            .map_err(|e| format!("获取代理配置失败: {e}"))?;
+        if let Some(address) = address {
+            config.listen_address = address;
+        }
+        if let Some(port) = port {
+            config.listen_port = port;
+        }
+        config.live_takeover_active = false;
+
+        let server = ProxyServer::new(config, self.db.clone(), None);
+        let info = server
+            .start()
+            .await
+            .map_err(|e| format!("启动代理服务器失败: {e}"))?;
+        *self.server.write().await = Some(server);
+        Ok(info)
+    }
+
     /// 启动代理服务器（带 Live 配置接管）
     pub async fn start_with_takeover(&self) -> Result<ProxyServerInfo, String> {
         // 1. 备份各应用的 Live 配置
@@ -1369,7 +1400,7 @@ impl ProxyService {
     // ==================== Live 配置读写辅助方法 ====================
 
     /// 更新 TOML 字符串中的 base_url
-    fn update_toml_base_url(toml_str: &str, new_url: &str) -> String {
+    pub(crate) fn update_toml_base_url(toml_str: &str, new_url: &str) -> String {
         use toml_edit::DocumentMut;
 
         let mut doc = match toml_str.parse::<DocumentMut>() {
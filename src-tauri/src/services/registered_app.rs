@@ -0,0 +1,138 @@
+//! 注册表应用的供应商管理
+//!
+//! 面向 [`AppRegistry`] 中的任意应用 id：内置应用（Claude / Codex / Gemini）转交
+//! [`ProviderService`]，其他应用按 [`AppDefinition`] 校验必填字段并写入 live 配置。
+
+use indexmap::IndexMap;
+
+use crate::app_registry::{AppDefinition, AppRegistry};
+use crate::error::AppError;
+use crate::provider::Provider;
+use crate::services::provider::ProviderService;
+use crate::store::AppState;
+
+/// 注册表应用业务
+pub struct RegisteredAppService;
+
+impl RegisteredAppService {
+    /// 所有已注册的应用
+    pub fn definitions() -> Vec<AppDefinition> {
+        AppRegistry::load().all().to_vec()
+    }
+
+    /// 查找已注册应用
+    pub fn definition(app_id: &str) -> Result<AppDefinition, AppError> {
+        AppRegistry::load().require(app_id).cloned()
+    }
+
+    /// 列出应用的供应商
+    pub fn list(state: &AppState, app_id: &str) -> Result<IndexMap<String, Provider>, AppError> {
+        let app = Self::definition(app_id)?;
+        state.db.get_all_providers(&app.id)
+    }
+
+    /// 获取供应商
+    pub fn get(state: &AppState, app_id: &str, provider_id: &str) -> Result<Provider, AppError> {
+        let app = Self::definition(app_id)?;
+        state
+            .db
+            .get_provider_by_id(provider_id, &app.id)?
+            .ok_or_else(|| AppError::provider_not_found(provider_id, app.id.as_str()))
+    }
+
+    /// 按 ID 或别名解析供应商 ID；未找到时原样返回，由调用方报告"未找到"
+    pub fn resolve_id(
+        state: &AppState,
+        app_id: &str,
+        id_or_alias: &str,
+    ) -> Result<String, AppError> {
+        let app = Self::definition(app_id)?;
+        Ok(state
+            .db
+            .resolve_provider_id(&app.id, id_or_alias)?
+            .unwrap_or_else(|| id_or_alias.to_string()))
+    }
+
+    /// 当前供应商 ID（未设置时为空字符串）
+    pub fn current(state: &AppState, app_id: &str) -> Result<String, AppError> {
+        let app = Self::definition(app_id)?;
+        match app.app_type() {
+            Some(app_type) => ProviderService::current(state, app_type),
+            None => Ok(state.db.get_current_provider(&app.id)?.unwrap_or_default()),
+        }
+    }
+
+    /// 新增或更新供应商；若为当前供应商则同步写入 live 配置
+    pub fn save(state: &AppState, app_id: &str, provider: Provider) -> Result<(), AppError> {
+        let app = Self::definition(app_id)?;
+        if let Some(app_type) = app.app_type() {
+            let exists = state
+                .db
+                .get_provider_by_id(&provider.id, app_type.as_str())?
+                .is_some();
+            return if exists {
+                ProviderService::update(state, app_type, provider).map(|_| ())
+            } else {
+                ProviderService::add(state, app_type, provider).map(|_| ())
+            };
+        }
+
+        Self::ensure_required_fields(&app, &provider)?;
+        let is_current = state.db.get_current_provider(&app.id)?.as_deref() == Some(&provider.id);
+        state.db.save_provider(&app.id, &provider)?;
+        if is_current {
+            app.write_live(&provider.settings_config)?;
+        }
+        Ok(())
+    }
+
+    /// 切换供应商
+    pub fn switch(state: &AppState, app_id: &str, provider_id: &str) -> Result<(), AppError> {
+        let app = Self::definition(app_id)?;
+        if let Some(app_type) = app.app_type() {
+            return ProviderService::switch(state, app_type, provider_id);
+        }
+
+        let provider = Self::get(state, &app.id, provider_id)?;
+        Self::ensure_required_fields(&app, &provider)?;
+        app.write_live(&provider.settings_config)?;
+        state.db.set_current_provider(&app.id, provider_id)?;
+        ProviderService::record_switch(state, &app.id, &provider, None);
+        Ok(())
+    }
+
+    /// 删除供应商（不能删除当前供应商）
+    pub fn delete(state: &AppState, app_id: &str, provider_id: &str) -> Result<(), AppError> {
+        let app = Self::definition(app_id)?;
+        if let Some(app_type) = app.app_type() {
+            return ProviderService::delete(state, app_type, provider_id);
+        }
+        Self::get(state, &app.id, provider_id)?;
+        if state.db.get_current_provider(&app.id)?.as_deref() == Some(provider_id) {
+            return Err(AppError::Message(
+                "无法删除当前正在使用的供应商".to_string(),
+            ));
+        }
+        state.db.delete_provider(&app.id, provider_id)
+    }
+
+    fn ensure_required_fields(app: &AppDefinition, provider: &Provider) -> Result<(), AppError> {
+        let missing = app.missing_fields(&provider.settings_config);
+        if missing.is_empty() {
+            return Ok(());
+        }
+        Err(AppError::localized(
+            "provider.validation.failed",
+            format!(
+                "{} 供应商配置缺少必填字段: {}",
+                app.name,
+                missing.join(", ")
+            ),
+            format!(
+                "{} provider config is missing required fields: {}",
+                app.name,
+                missing.join(", ")
+            ),
+        ))
+    }
+}
@@ -133,7 +133,7 @@ impl SkillService {
     }
 
     fn get_install_dir_for_app(app_type: &AppType) -> Result<PathBuf> {
-        let home = dirs::home_dir().context(format_skill_error(
+        let home = crate::config::home_dir().context(format_skill_error(
             "GET_HOME_DIR_FAILED",
             &[],
             Some("checkPermission"),
@@ -0,0 +1,319 @@
+//! 应用配置目录快照
+//!
+//! 把 Claude Code / Codex / Gemini CLI 的整个配置目录（`~/.claude`、`~/.codex`、`~/.gemini`，
+//! 不含会话记录、日志等体积大的缓存）打包为 `~/.cc-switch/snapshots/<id>.tar.zst`，试用新的
+//! 供应商或 MCP 配置之后可以整体恢复。每个应用按 backup.snapshotRetainCount（默认 10）保留
+//! 最近的快照；恢复前会先为当前目录生成一份快照。
+
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::Local;
+use serde::Serialize;
+use tempfile::NamedTempFile;
+
+use crate::app_config::AppType;
+use crate::codex_config::get_codex_config_dir;
+use crate::config::{get_app_config_dir, get_claude_config_dir};
+use crate::error::AppError;
+use crate::gemini_config::get_gemini_dir;
+use crate::operation_lock::OperationLock;
+
+/// 每个应用默认保留的快照数量
+const SNAPSHOT_RETAIN: usize = 10;
+
+const SNAPSHOT_EXT: &str = ".tar.zst";
+
+/// 恢复前自动生成的快照标签
+const PRE_RESTORE_LABEL: &str = "pre-restore";
+
+/// 配置目录快照
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotInfo {
+    /// 文件名（不含扩展名），形如 `codex-20250101-120000[-label]`
+    pub id: String,
+    pub app: String,
+    pub path: PathBuf,
+    /// 字节数
+    pub size: u64,
+    /// 文件修改时间（Unix 毫秒）
+    pub created_at: i64,
+}
+
+/// 配置目录快照业务
+pub struct SnapshotService;
+
+impl SnapshotService {
+    pub fn snapshot_dir() -> PathBuf {
+        get_app_config_dir().join("snapshots")
+    }
+
+    /// 应用的配置目录
+    pub fn app_dir(app_type: &AppType) -> PathBuf {
+        match app_type {
+            AppType::Claude => get_claude_config_dir(),
+            AppType::Codex => get_codex_config_dir(),
+            AppType::Gemini => get_gemini_dir(),
+        }
+    }
+
+    /// 打包应用的配置目录，并按保留策略删除该应用的旧快照
+    pub fn create(app_type: &AppType, label: Option<&str>) -> Result<SnapshotInfo, AppError> {
+        let _lock = OperationLock::acquire()?;
+        let source = Self::app_dir(app_type);
+        if !source.is_dir() {
+            return Err(AppError::Message(format!(
+                "{} 的配置目录不存在: {}",
+                app_type.as_str(),
+                source.display()
+            )));
+        }
+
+        let dir = Self::snapshot_dir();
+        fs::create_dir_all(&dir).map_err(|e| AppError::io(&dir, e))?;
+        let mut id = format!(
+            "{}-{}",
+            app_type.as_str(),
+            Local::now().format("%Y%m%d-%H%M%S")
+        );
+        if let Some(label) = label.map(sanitize_label).filter(|l| !l.is_empty()) {
+            id.push('-');
+            id.push_str(&label);
+        }
+        let path = dir.join(format!("{id}{SNAPSHOT_EXT}"));
+
+        let tmp = NamedTempFile::new_in(&dir).map_err(|e| AppError::io(&dir, e))?;
+        write_archive(&source, excluded(app_type), tmp.as_file())
+            .map_err(|e| AppError::io(&source, e))?;
+        tmp.persist(&path)
+            .map_err(|e| AppError::io(&path, e.error))?;
+        log::info!(
+            "已生成 {} 配置目录快照: {}",
+            app_type.as_str(),
+            path.display()
+        );
+
+        Self::prune(app_type)?;
+        Self::list(Some(app_type))?
+            .into_iter()
+            .find(|snapshot| snapshot.path == path)
+            .ok_or_else(|| AppError::Message(format!("快照已被保留策略删除: {id}")))
+    }
+
+    /// 列出快照（最新的在前）；`app_type` 为空时包含所有应用
+    pub fn list(app_type: Option<&AppType>) -> Result<Vec<SnapshotInfo>, AppError> {
+        let dir = Self::snapshot_dir();
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(AppError::io(&dir, e)),
+        };
+        let mut snapshots: Vec<SnapshotInfo> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let path = entry.path();
+                let id = path.file_name()?.to_str()?.strip_suffix(SNAPSHOT_EXT)?;
+                let app = id.split_once('-')?.0.to_string();
+                if app_type.is_some_and(|app_type| app_type.as_str() != app) {
+                    return None;
+                }
+                let metadata = entry.metadata().ok()?;
+                let created_at = metadata
+                    .modified()
+                    .ok()
+                    .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+                    .map(|age| age.as_millis() as i64)
+                    .unwrap_or_default();
+                Some(SnapshotInfo {
+                    id: id.to_string(),
+                    app,
+                    size: metadata.len(),
+                    created_at,
+                    path,
+                })
+            })
+            .collect();
+        snapshots.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(b.id.cmp(&a.id)));
+        Ok(snapshots)
+    }
+
+    /// 用快照替换应用的配置目录（排除的缓存保持不变），返回恢复前自动生成的快照 ID
+    ///
+    /// 快照先解压到临时目录，解压失败时配置目录不受影响。
+    pub fn restore(id: &str) -> Result<(SnapshotInfo, Option<String>), AppError> {
+        let _lock = OperationLock::acquire()?;
+        let snapshot = Self::list(None)?
+            .into_iter()
+            .find(|snapshot| snapshot.id == id)
+            .ok_or_else(|| AppError::InvalidInput(format!("快照不存在: {id}")))?;
+        let app_type = snapshot.app.parse::<AppType>()?;
+        let target = Self::app_dir(&app_type);
+        let parent = target
+            .parent()
+            .ok_or_else(|| AppError::Config(format!("无效的配置目录: {}", target.display())))?;
+        fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
+
+        let staging = tempfile::tempdir_in(parent).map_err(|e| AppError::io(parent, e))?;
+        let file = File::open(&snapshot.path).map_err(|e| AppError::io(&snapshot.path, e))?;
+        let decoder = zstd::Decoder::new(file).map_err(|e| AppError::io(&snapshot.path, e))?;
+        tar::Archive::new(decoder)
+            .unpack(staging.path())
+            .map_err(|e| AppError::io(&snapshot.path, e))?;
+
+        let safety = if target.is_dir() {
+            Some(Self::create(&app_type, Some(PRE_RESTORE_LABEL))?.id)
+        } else {
+            None
+        };
+
+        let excludes = excluded(&app_type);
+        fs::create_dir_all(&target).map_err(|e| AppError::io(&target, e))?;
+        for entry in fs::read_dir(&target).map_err(|e| AppError::io(&target, e))? {
+            let entry = entry.map_err(|e| AppError::io(&target, e))?;
+            if is_excluded(&entry.file_name(), excludes) {
+                continue;
+            }
+            remove_entry(&entry.path())?;
+        }
+        for entry in fs::read_dir(staging.path()).map_err(|e| AppError::io(staging.path(), e))? {
+            let entry = entry.map_err(|e| AppError::io(staging.path(), e))?;
+            let dest = target.join(entry.file_name());
+            fs::rename(entry.path(), &dest).map_err(|e| AppError::io(&dest, e))?;
+        }
+        log::info!("已从快照 {id} 恢复 {}", target.display());
+        Ok((snapshot, safety))
+    }
+
+    /// 按保留策略删除应用的旧快照，返回被删除的快照
+    pub fn prune(app_type: &AppType) -> Result<Vec<SnapshotInfo>, AppError> {
+        let retain = crate::settings::get_settings()
+            .backup
+            .and_then(|backup| backup.snapshot_retain_count)
+            .unwrap_or(SNAPSHOT_RETAIN)
+            .max(1);
+        let expired: Vec<SnapshotInfo> = Self::list(Some(app_type))?
+            .into_iter()
+            .skip(retain)
+            .collect();
+        for snapshot in &expired {
+            fs::remove_file(&snapshot.path).map_err(|e| AppError::io(&snapshot.path, e))?;
+        }
+        Ok(expired)
+    }
+}
+
+/// 不打包的顶层目录：会话记录、日志与缓存
+fn excluded(app_type: &AppType) -> &'static [&'static str] {
+    match app_type {
+        AppType::Claude => &[
+            "projects",
+            "todos",
+            "shell-snapshots",
+            "statsig",
+            "file-history",
+            "debug",
+            "ide",
+            "logs",
+        ],
+        AppType::Codex => &["sessions", "archived_sessions", "log", "cache", "tmp"],
+        AppType::Gemini => &["tmp", "history", "logs"],
+    }
+}
+
+fn is_excluded(name: &std::ffi::OsStr, excludes: &[&str]) -> bool {
+    name.to_str().is_some_and(|name| excludes.contains(&name))
+}
+
+fn sanitize_label(label: &str) -> String {
+    label
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+fn remove_entry(path: &Path) -> Result<(), AppError> {
+    let metadata = fs::symlink_metadata(path).map_err(|e| AppError::io(path, e))?;
+    if metadata.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+    .map_err(|e| AppError::io(path, e))
+}
+
+fn write_archive(source: &Path, excludes: &[&str], out: &File) -> io::Result<()> {
+    let encoder = zstd::Encoder::new(out, 0)?;
+    let mut builder = tar::Builder::new(encoder);
+    builder.follow_symlinks(false);
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        if is_excluded(&entry.file_name(), excludes) {
+            continue;
+        }
+        append_entry(&mut builder, &entry.path(), Path::new(&entry.file_name()))?;
+    }
+    builder.into_inner()?.finish()?;
+    Ok(())
+}
+
+fn append_entry<W: io::Write>(
+    builder: &mut tar::Builder<W>,
+    path: &Path,
+    name: &Path,
+) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        builder.append_dir(name, path)?;
+        for entry in fs::read_dir(path)? {
+            let entry = entry?;
+            append_entry(builder, &entry.path(), &name.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        builder.append_path_with_name(path, name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn archive_skips_excluded_directories() {
+        let source = tempfile::tempdir().unwrap();
+        fs::write(source.path().join("config.toml"), "model = \"o3\"\n").unwrap();
+        fs::create_dir_all(source.path().join("prompts/nested")).unwrap();
+        fs::write(source.path().join("prompts/nested/a.md"), "hi").unwrap();
+        fs::create_dir_all(source.path().join("sessions")).unwrap();
+        fs::write(source.path().join("sessions/big.jsonl"), "{}").unwrap();
+
+        let archive = NamedTempFile::new().unwrap();
+        write_archive(source.path(), excluded(&AppType::Codex), archive.as_file()).unwrap();
+
+        let target = tempfile::tempdir().unwrap();
+        let decoder = zstd::Decoder::new(File::open(archive.path()).unwrap()).unwrap();
+        tar::Archive::new(decoder).unpack(target.path()).unwrap();
+        assert_eq!(
+            fs::read_to_string(target.path().join("config.toml")).unwrap(),
+            "model = \"o3\"\n"
+        );
+        assert_eq!(
+            fs::read_to_string(target.path().join("prompts/nested/a.md")).unwrap(),
+            "hi"
+        );
+        assert!(!target.path().join("sessions").exists());
+    }
+
+    #[test]
+    fn labels_are_safe_for_file_names() {
+        assert_eq!(sanitize_label(" before mcp/test "), "before-mcp-test");
+    }
+}
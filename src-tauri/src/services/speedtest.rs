@@ -18,6 +18,36 @@ pub struct EndpointLatency {
     pub error: Option<String>,
 }
 
+/// 端点竞速结果：分别记录 TCP 建连与完整 HEAD 请求的耗时
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EndpointTiming {
+    pub url: String,
+    /// TCP 建连耗时（毫秒）
+    pub connect_ms: Option<u64>,
+    /// 在新连接上完成 TCP + TLS + HTTP HEAD 的耗时（毫秒）
+    pub head_ms: Option<u64>,
+    pub status: Option<u16>,
+    pub error: Option<String>,
+}
+
+impl EndpointTiming {
+    fn failed(url: String, error: String) -> Self {
+        Self {
+            url,
+            connect_ms: None,
+            head_ms: None,
+            status: None,
+            error: Some(error),
+        }
+    }
+
+    /// 只要收到 HTTP 响应即视为可达（API 根路径返回 404/405 很常见）
+    pub fn reachable(&self) -> bool {
+        self.head_ms.is_some()
+    }
+}
+
 /// 网络测速相关业务
 pub struct SpeedtestService;
 
@@ -112,6 +142,78 @@ impl SpeedtestService {
         Ok(results.into_iter().flatten().collect::<Vec<_>>())
     }
 
+    /// 并发测试一组端点，返回按速度排序的结果（可达的在前，HEAD 耗时升序）
+    ///
+    /// 与 [`Self::test_endpoints`] 不同，这里不做热身请求：每个端点使用独立的客户端，
+    /// 测得的是冷启动时建立连接的真实代价。
+    pub async fn race_endpoints(
+        urls: Vec<String>,
+        timeout_secs: Option<u64>,
+    ) -> Result<Vec<EndpointTiming>, AppError> {
+        let timeout = Duration::from_secs(Self::sanitize_timeout(timeout_secs));
+        let tasks = urls
+            .into_iter()
+            .map(|url| Self::time_endpoint(url, timeout));
+        let mut results = join_all(tasks).await;
+        Self::rank_timings(&mut results);
+        Ok(results)
+    }
+
+    async fn time_endpoint(raw_url: String, timeout: Duration) -> EndpointTiming {
+        let trimmed = raw_url.trim().to_string();
+        let parsed = match Url::parse(&trimmed) {
+            Ok(parsed) => parsed,
+            Err(err) => return EndpointTiming::failed(trimmed, format!("URL 无效: {err}")),
+        };
+        let (Some(host), Some(port)) = (parsed.host_str(), parsed.port_or_known_default()) else {
+            return EndpointTiming::failed(trimmed, "URL 缺少主机名".to_string());
+        };
+
+        let start = Instant::now();
+        let connect_ms =
+            match tokio::time::timeout(timeout, tokio::net::TcpStream::connect((host, port))).await
+            {
+                Ok(Ok(_)) => start.elapsed().as_millis() as u64,
+                Ok(Err(err)) => return EndpointTiming::failed(trimmed, format!("连接失败: {err}")),
+                Err(_) => return EndpointTiming::failed(trimmed, "连接超时".to_string()),
+            };
+
+        let client = match Client::builder()
+            .timeout(timeout)
+            .pool_max_idle_per_host(0)
+            .user_agent("cc-switch-speedtest/1.0")
+            .build()
+        {
+            Ok(client) => client,
+            Err(err) => return EndpointTiming::failed(trimmed, err.to_string()),
+        };
+        let start = Instant::now();
+        match client.head(parsed).send().await {
+            Ok(resp) => EndpointTiming {
+                url: trimmed,
+                connect_ms: Some(connect_ms),
+                head_ms: Some(start.elapsed().as_millis() as u64),
+                status: Some(resp.status().as_u16()),
+                error: None,
+            },
+            Err(err) => {
+                let message = if err.is_timeout() {
+                    "请求超时".to_string()
+                } else {
+                    err.to_string()
+                };
+                EndpointTiming {
+                    connect_ms: Some(connect_ms),
+                    ..EndpointTiming::failed(trimmed, message)
+                }
+            }
+        }
+    }
+
+    fn rank_timings(results: &mut [EndpointTiming]) {
+        results.sort_by_key(|timing| (!timing.reachable(), timing.head_ms.unwrap_or(u64::MAX)));
+    }
+
     fn build_client(timeout_secs: u64) -> Result<Client, AppError> {
         Client::builder()
             .timeout(Duration::from_secs(timeout_secs))
@@ -157,6 +259,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn race_ranks_reachable_endpoints_first() {
+        let timing = |url: &str, head_ms: Option<u64>| EndpointTiming {
+            url: url.to_string(),
+            connect_ms: head_ms.map(|ms| ms / 2),
+            head_ms,
+            status: head_ms.map(|_| 404),
+            error: None,
+        };
+        let mut results = vec![
+            timing("https://down.example.com", None),
+            timing("https://slow.example.com", Some(480)),
+            timing("https://fast.example.com", Some(120)),
+        ];
+        SpeedtestService::rank_timings(&mut results);
+        let urls: Vec<_> = results.iter().map(|t| t.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            vec![
+                "https://fast.example.com",
+                "https://slow.example.com",
+                "https://down.example.com"
+            ]
+        );
+
+        let invalid = tauri::async_runtime::block_on(SpeedtestService::race_endpoints(
+            vec!["not a url".into()],
+            None,
+        ))
+        .expect("invalid inputs should still succeed");
+        assert!(!invalid[0].reachable());
+    }
+
     #[test]
     fn test_endpoints_handles_empty_list() {
         let result =
@@ -0,0 +1,188 @@
+//! Snapshot encryption
+//!
+//! Layout: `MAGIC | salt (16 bytes) | nonce (12 bytes) | AES-256-GCM ciphertext`.
+//! The key is derived from the passphrase with PBKDF2-HMAC-SHA256 and a fresh
+//! random salt per snapshot, so the same passphrase never reuses a key/nonce pair.
+//!
+//! The git backend encrypts single secret values instead, see [`SecretBox`].
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::prelude::*;
+use hmac::{Hmac, Mac};
+use pbkdf2::pbkdf2_hmac;
+use sha2::Sha256;
+
+use crate::error::AppError;
+
+const MAGIC: &[u8] = b"CCSWSYNC1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const PBKDF2_ROUNDS: u32 = 100_000;
+
+/// Encrypt a snapshot with the passphrase
+pub(crate) fn encrypt(plaintext: &[u8], passphrase: &str) -> Result<Vec<u8>, AppError> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher(passphrase, &salt)
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| AppError::Message("加密快照失败".to_string()))?;
+
+    let mut out = Vec::with_capacity(MAGIC.len() + SALT_LEN + NONCE_LEN + ciphertext.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&salt);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+/// Decrypt a snapshot produced by [`encrypt`]
+pub(crate) fn decrypt(data: &[u8], passphrase: &str) -> Result<Vec<u8>, AppError> {
+    let body = data
+        .strip_prefix(MAGIC)
+        .filter(|body| body.len() > SALT_LEN + NONCE_LEN)
+        .ok_or_else(|| {
+            AppError::localized(
+                "sync.snapshot_invalid",
+                "远端文件不是 CC Switch 同步快照",
+                "The remote file is not a CC Switch sync snapshot",
+            )
+        })?;
+    let (salt, rest) = body.split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    cipher(passphrase, salt)
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| {
+            AppError::localized(
+                "sync.decrypt_failed",
+                "解密快照失败：口令错误或快照已损坏",
+                "Failed to decrypt the snapshot: wrong passphrase or corrupted data",
+            )
+        })
+}
+
+/// Random salt for [`SecretBox::new`]
+pub(crate) fn random_salt() -> [u8; SALT_LEN] {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    salt
+}
+
+/// Deterministic encryption of single secret values
+///
+/// Sealed values look like `enc:v1:<base64(nonce | ciphertext)>`. The nonce is
+/// an HMAC of the field path and the plaintext, so an unchanged secret always
+/// seals to the same text and does not show up as a change in the git history.
+/// This reveals whether two fields hold the same secret, nothing more.
+pub(crate) struct SecretBox {
+    cipher: Aes256Gcm,
+    mac_key: [u8; 32],
+}
+
+impl SecretBox {
+    pub(crate) const PREFIX: &'static str = "enc:v1:";
+
+    pub(crate) fn new(passphrase: &str, salt: &[u8]) -> Self {
+        let mut key = [0u8; 64];
+        pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, PBKDF2_ROUNDS, &mut key);
+        let mut mac_key = [0u8; 32];
+        mac_key.copy_from_slice(&key[32..]);
+        Self {
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key[..32])),
+            mac_key,
+        }
+    }
+
+    pub(crate) fn is_sealed(value: &str) -> bool {
+        value.starts_with(Self::PREFIX)
+    }
+
+    pub(crate) fn seal(&self, path: &str, plaintext: &str) -> Result<String, AppError> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.mac_key).expect("HMAC accepts any key length");
+        mac.update(path.as_bytes());
+        mac.update(&[0]);
+        mac.update(plaintext.as_bytes());
+        let digest = mac.finalize().into_bytes();
+        let nonce = Nonce::from_slice(&digest[..NONCE_LEN]);
+
+        let ciphertext = self
+            .cipher
+            .encrypt(nonce, plaintext.as_bytes())
+            .map_err(|_| AppError::Message("加密密钥字段失败".to_string()))?;
+        let mut out = nonce.to_vec();
+        out.extend_from_slice(&ciphertext);
+        Ok(format!("{}{}", Self::PREFIX, BASE64_STANDARD.encode(out)))
+    }
+
+    pub(crate) fn open(&self, sealed: &str) -> Result<String, AppError> {
+        let failed = || {
+            AppError::localized(
+                "sync.decrypt_failed",
+                "解密密钥字段失败：口令错误或文件已损坏",
+                "Failed to decrypt a secret field: wrong passphrase or corrupted data",
+            )
+        };
+        let data = sealed
+            .strip_prefix(Self::PREFIX)
+            .and_then(|body| BASE64_STANDARD.decode(body).ok())
+            .filter(|data| data.len() > NONCE_LEN)
+            .ok_or_else(failed)?;
+        let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+        let plaintext = self
+            .cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| failed())?;
+        String::from_utf8(plaintext).map_err(|_| failed())
+    }
+}
+
+fn cipher(passphrase: &str, salt: &[u8]) -> Aes256Gcm {
+    let mut key = [0u8; 32];
+    pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, PBKDF2_ROUNDS, &mut key);
+    Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_and_rejects_wrong_passphrase() {
+        let sealed = encrypt(b"-- CC Switch SQLite", "correct horse").unwrap();
+        assert!(sealed.starts_with(MAGIC));
+        assert_eq!(
+            decrypt(&sealed, "correct horse").unwrap(),
+            b"-- CC Switch SQLite"
+        );
+        assert!(decrypt(&sealed, "battery staple").is_err());
+        assert!(decrypt(b"not a snapshot", "correct horse").is_err());
+
+        // A fresh salt and nonce every time
+        assert_ne!(
+            sealed,
+            encrypt(b"-- CC Switch SQLite", "correct horse").unwrap()
+        );
+    }
+
+    #[test]
+    fn secret_box_is_deterministic_per_path() {
+        let salt = random_salt();
+        let secrets = SecretBox::new("correct horse", &salt);
+        let sealed = secrets.seal("env.ANTHROPIC_AUTH_TOKEN", "sk-123").unwrap();
+        assert!(SecretBox::is_sealed(&sealed));
+        assert_eq!(
+            sealed,
+            secrets.seal("env.ANTHROPIC_AUTH_TOKEN", "sk-123").unwrap()
+        );
+        assert_ne!(sealed, secrets.seal("apiKey", "sk-123").unwrap());
+        assert_eq!(secrets.open(&sealed).unwrap(), "sk-123");
+
+        assert!(SecretBox::new("battery staple", &salt)
+            .open(&sealed)
+            .is_err());
+        assert!(secrets.open("enc:v1:bm9wZQ==").is_err());
+    }
+}
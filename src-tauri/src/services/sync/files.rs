@@ -0,0 +1,324 @@
+//! Per-provider file serialization for the git backend
+//!
+//! Every provider becomes `providers/<app>/<id>.json`: pretty JSON with sorted
+//! keys, so a file only changes when the provider does and diffs stay readable.
+//! Volatile per-device data (endpoint speed test results, last use) is left out.
+//!
+//! Secret fields (see [`Provider::map_secrets`]) are sealed with [`SecretBox`]
+//! when a passphrase is configured, and replaced by [`REDACTED`] otherwise. A
+//! redacted field keeps the local value on pull (and stays empty for providers
+//! this device does not have), so a redacted repository only carries the
+//! non-secret parts of the configuration between devices.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use base64::prelude::*;
+use serde_json::{json, Value};
+
+use super::crypto::{random_salt, SecretBox};
+use crate::app_config::AppType;
+use crate::config::atomic_write;
+use crate::database::{sort_json_keys, Database};
+use crate::error::AppError;
+use crate::provider::Provider;
+
+/// Repository manifest: format version and the key derivation salt
+pub(crate) const MANIFEST: &str = "cc-switch-sync.json";
+/// Directory holding the provider files
+pub(crate) const PROVIDERS_DIR: &str = "providers";
+/// Placeholder written instead of a secret when no passphrase is configured
+pub(crate) const REDACTED: &str = "<redacted>";
+
+const FORMAT_VERSION: u64 = 1;
+const VOLATILE_ENDPOINT_FIELDS: [&str; 3] = ["lastUsed", "latencyMs", "testedAt"];
+
+/// How secret fields are written and read
+pub(crate) enum Secrets {
+    Sealed(SecretBox),
+    Redacted,
+}
+
+impl Secrets {
+    /// Open the secrets of the repository in `dir`, creating its manifest on first use
+    pub(crate) fn for_repo(dir: &Path, passphrase: Option<&str>) -> Result<Self, AppError> {
+        let path = dir.join(MANIFEST);
+        let manifest: Value = match fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str(&text).map_err(|e| AppError::json(&path, e))?,
+            Err(_) => Value::Null,
+        };
+        let salt = manifest["salt"]
+            .as_str()
+            .and_then(|salt| BASE64_STANDARD.decode(salt).ok());
+
+        match (passphrase, salt) {
+            (Some(passphrase), Some(salt)) => {
+                Ok(Secrets::Sealed(SecretBox::new(passphrase, &salt)))
+            }
+            (Some(passphrase), None) => {
+                let salt = random_salt();
+                write_file(
+                    &path,
+                    &json!({ "version": FORMAT_VERSION, "salt": BASE64_STANDARD.encode(salt) }),
+                )?;
+                Ok(Secrets::Sealed(SecretBox::new(passphrase, &salt)))
+            }
+            (None, Some(_)) => Err(AppError::localized(
+                "sync.passphrase_missing",
+                format!(
+                    "同步仓库中的密钥已加密：请设置 {} 或 sync.passphrase",
+                    super::PASSPHRASE_ENV
+                ),
+                format!(
+                    "The secrets in the sync repository are encrypted: set {} or sync.passphrase",
+                    super::PASSPHRASE_ENV
+                ),
+            )),
+            (None, None) => {
+                if manifest.is_null() {
+                    write_file(&path, &json!({ "version": FORMAT_VERSION }))?;
+                }
+                Ok(Secrets::Redacted)
+            }
+        }
+    }
+}
+
+/// Relative path of a provider file
+pub(crate) fn provider_path(app_type: &AppType, id: &str) -> PathBuf {
+    let name: String = id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    Path::new(PROVIDERS_DIR)
+        .join(app_type.as_str())
+        .join(format!("{}.json", name.trim_start_matches('.')))
+}
+
+/// Deterministic file content of one provider
+pub(crate) fn serialize_provider(
+    app_type: &AppType,
+    provider: &Provider,
+    updated_at: Option<i64>,
+    secrets: &Secrets,
+) -> Result<String, AppError> {
+    let mut error = None;
+    let provider = provider.map_secrets(app_type, &mut |path, secret| match secrets {
+        Secrets::Sealed(secret_box) => secret_box.seal(path, secret).unwrap_or_else(|e| {
+            error.get_or_insert(e);
+            String::new()
+        }),
+        Secrets::Redacted => REDACTED.to_string(),
+    });
+    if let Some(e) = error {
+        return Err(e);
+    }
+
+    let mut value = serde_json::to_value(&provider)
+        .map_err(|e| AppError::Message(format!("序列化供应商失败: {e}")))?;
+    if let Some(endpoints) = value
+        .pointer_mut("/meta/customEndpoints")
+        .and_then(Value::as_object_mut)
+    {
+        for endpoint in endpoints.values_mut().filter_map(Value::as_object_mut) {
+            for field in VOLATILE_ENDPOINT_FIELDS {
+                endpoint.remove(field);
+            }
+        }
+    }
+    if let (Some(object), Some(updated_at)) = (value.as_object_mut(), updated_at) {
+        object.insert("updatedAt".to_string(), json!(updated_at));
+    }
+
+    let mut text = serde_json::to_string_pretty(&sort_json_keys(&value))
+        .map_err(|e| AppError::Message(format!("序列化供应商失败: {e}")))?;
+    text.push('\n');
+    Ok(text)
+}
+
+/// Parse a provider file; sealed secrets are opened, redacted ones are taken
+/// from `local` (the same provider on this device) when it has them
+pub(crate) fn deserialize_provider(
+    app_type: &AppType,
+    text: &str,
+    secrets: &Secrets,
+    local: Option<&Provider>,
+) -> Result<(Provider, Option<i64>), AppError> {
+    let value: Value = serde_json::from_str(text)
+        .map_err(|e| AppError::Message(format!("解析供应商文件失败: {e}")))?;
+    let updated_at = value["updatedAt"].as_i64();
+    let provider: Provider = serde_json::from_value(value)
+        .map_err(|e| AppError::Message(format!("解析供应商文件失败: {e}")))?;
+
+    let mut local_secrets = HashMap::new();
+    if let Some(local) = local {
+        local.map_secrets(app_type, &mut |path, secret| {
+            local_secrets.insert(path.to_string(), secret.to_string());
+            secret.to_string()
+        });
+    }
+
+    let mut error = None;
+    let provider = provider.map_secrets(app_type, &mut |path, value| {
+        if value == REDACTED {
+            return local_secrets.get(path).cloned().unwrap_or_default();
+        }
+        match secrets {
+            Secrets::Sealed(secret_box) if SecretBox::is_sealed(value) => {
+                secret_box.open(value).unwrap_or_else(|e| {
+                    error.get_or_insert(e);
+                    String::new()
+                })
+            }
+            _ => value.to_string(),
+        }
+    });
+    match error {
+        Some(e) => Err(e),
+        None => Ok((provider, updated_at)),
+    }
+}
+
+/// Rewrite the provider files in `dir` from the database
+pub(crate) fn write_tree(dir: &Path, db: &Database, secrets: &Secrets) -> Result<(), AppError> {
+    let root = dir.join(PROVIDERS_DIR);
+    if root.exists() {
+        fs::remove_dir_all(&root).map_err(|e| AppError::io(&root, e))?;
+    }
+    for app_type in [AppType::Claude, AppType::Codex, AppType::Gemini] {
+        let app = app_type.as_str();
+        let timestamps = db.get_provider_timestamps(app)?;
+        for (id, provider) in db.get_all_providers(app)? {
+            let text =
+                serialize_provider(&app_type, &provider, timestamps.get(&id).copied(), secrets)?;
+            let path = dir.join(provider_path(&app_type, &id));
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
+            }
+            atomic_write(&path, text.as_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+/// Load the provider files in `dir` into an in-memory database
+///
+/// `local` supplies the secrets of redacted fields.
+pub(crate) fn read_tree(
+    dir: &Path,
+    secrets: &Secrets,
+    local: &Database,
+) -> Result<Database, AppError> {
+    let db = Database::memory()?;
+    for app_type in [AppType::Claude, AppType::Codex, AppType::Gemini] {
+        let app = app_type.as_str();
+        let app_dir = dir.join(PROVIDERS_DIR).join(app);
+        let Ok(entries) = fs::read_dir(&app_dir) else {
+            continue;
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+
+        let local_providers = local.get_all_providers(app)?;
+        for path in paths {
+            let text = fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+            let id = serde_json::from_str::<Value>(&text)
+                .ok()
+                .and_then(|value| value["id"].as_str().map(str::to_string))
+                .unwrap_or_default();
+            let (provider, updated_at) =
+                deserialize_provider(&app_type, &text, secrets, local_providers.get(&id))
+                    .map_err(|e| AppError::Message(format!("{}: {e}", path.display())))?;
+            db.save_provider(app, &provider)?;
+            if let Some(updated_at) = updated_at {
+                db.set_provider_updated_at(app, &provider.id, updated_at)?;
+            }
+        }
+    }
+    Ok(db)
+}
+
+fn write_file(path: &Path, value: &Value) -> Result<(), AppError> {
+    let mut text = serde_json::to_string_pretty(value)
+        .map_err(|e| AppError::Message(format!("序列化失败: {e}")))?;
+    text.push('\n');
+    atomic_write(path, text.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider(token: &str) -> Provider {
+        Provider::with_id(
+            "my relay".to_string(),
+            "My Relay".to_string(),
+            json!({
+                "env": {
+                    "ANTHROPIC_BASE_URL": "https://relay.example.com",
+                    "ANTHROPIC_AUTH_TOKEN": token
+                }
+            }),
+            None,
+        )
+    }
+
+    #[test]
+    fn sealed_files_are_deterministic_and_round_trip() {
+        let secrets = Secrets::Sealed(SecretBox::new("pass", b"0123456789abcdef"));
+        let text = serialize_provider(&AppType::Claude, &provider("sk-secret"), Some(42), &secrets)
+            .unwrap();
+        assert!(!text.contains("sk-secret"));
+        assert!(text.contains("https://relay.example.com"));
+        assert_eq!(
+            text,
+            serialize_provider(&AppType::Claude, &provider("sk-secret"), Some(42), &secrets)
+                .unwrap()
+        );
+
+        let (restored, updated_at) =
+            deserialize_provider(&AppType::Claude, &text, &secrets, None).unwrap();
+        assert_eq!(updated_at, Some(42));
+        assert_eq!(
+            restored.settings_config["env"]["ANTHROPIC_AUTH_TOKEN"],
+            "sk-secret"
+        );
+        assert_eq!(
+            provider_path(&AppType::Claude, &restored.id),
+            Path::new("providers/claude/my_relay.json")
+        );
+    }
+
+    #[test]
+    fn redacted_fields_keep_the_local_secret() {
+        let text = serialize_provider(
+            &AppType::Claude,
+            &provider("sk-secret"),
+            None,
+            &Secrets::Redacted,
+        )
+        .unwrap();
+        assert!(text.contains(REDACTED));
+        assert!(!text.contains("sk-secret"));
+
+        let local = provider("sk-local");
+        let (restored, _) =
+            deserialize_provider(&AppType::Claude, &text, &Secrets::Redacted, Some(&local))
+                .unwrap();
+        assert_eq!(
+            restored.settings_config["env"]["ANTHROPIC_AUTH_TOKEN"],
+            "sk-local"
+        );
+    }
+}
@@ -0,0 +1,157 @@
+//! Git repository backend
+//!
+//! Shells out to the `git` executable, so SSH keys, credential helpers and the
+//! user's identity work as they do on the command line. The working copy lives
+//! in `~/.cc-switch/sync-repo` and only ever holds files generated from the
+//! database, so it is reset to the remote branch before every sync.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::config::get_app_config_dir;
+use crate::error::AppError;
+use crate::settings::SyncSettings;
+
+const DEFAULT_BRANCH: &str = "main";
+
+pub(super) struct GitRepo {
+    dir: PathBuf,
+    remote: String,
+    branch: String,
+}
+
+impl GitRepo {
+    pub(super) fn from_settings(settings: &SyncSettings) -> Result<Self, AppError> {
+        let remote = settings
+            .remote
+            .clone()
+            .or_else(|| settings.url.clone())
+            .filter(|remote| !remote.trim().is_empty())
+            .ok_or_else(|| {
+                AppError::localized(
+                    "sync.git_remote_missing",
+                    "git 后端需要仓库地址（--remote）",
+                    "The git backend needs a repository (--remote)",
+                )
+            })?;
+        let branch = settings
+            .branch
+            .clone()
+            .filter(|branch| !branch.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_BRANCH.to_string());
+        Ok(Self {
+            dir: get_app_config_dir().join("sync-repo"),
+            remote: remote.trim().to_string(),
+            branch,
+        })
+    }
+
+    pub(super) fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub(super) fn target(&self) -> String {
+        format!("{} ({})", self.remote, self.branch)
+    }
+
+    /// Bring the working copy to the state of the remote branch
+    pub(super) fn checkout(&self) -> Result<(), AppError> {
+        if !self.dir.join(".git").exists() {
+            std::fs::create_dir_all(&self.dir).map_err(|e| AppError::io(&self.dir, e))?;
+            self.git(&["init", "--quiet"])?;
+            self.git(&["remote", "add", "origin", &self.remote])?;
+        } else {
+            self.git(&["remote", "set-url", "origin", &self.remote])?;
+        }
+
+        if self.remote_head()?.is_some() {
+            self.git(&["fetch", "--quiet", "origin", &self.branch])?;
+            self.git(&[
+                "checkout",
+                "--quiet",
+                "-f",
+                "-B",
+                &self.branch,
+                "FETCH_HEAD",
+            ])?;
+            self.git(&["clean", "--quiet", "-fd"])?;
+        } else {
+            // Empty remote: start the branch with the first push
+            self.git(&[
+                "symbolic-ref",
+                "HEAD",
+                &format!("refs/heads/{}", self.branch),
+            ])?;
+        }
+        Ok(())
+    }
+
+    /// Commit of the working copy, `None` before the first commit
+    pub(super) fn head(&self) -> Option<String> {
+        self.git(&["rev-parse", "--verify", "--quiet", "HEAD"]).ok()
+    }
+
+    /// Commit of the remote branch, `None` when it does not exist yet
+    pub(super) fn remote_head(&self) -> Result<Option<String>, AppError> {
+        let output = run(Command::new("git").args([
+            "ls-remote",
+            self.remote.as_str(),
+            &format!("refs/heads/{}", self.branch),
+        ]))?;
+        Ok(output.split_whitespace().next().map(str::to_string))
+    }
+
+    /// Commit all changes and push them; returns the new commit, or `None`
+    /// when the working copy already matches the last commit
+    pub(super) fn commit_and_push(&self, message: &str) -> Result<Option<String>, AppError> {
+        self.git(&["add", "-A"])?;
+        if self.git(&["status", "--porcelain"])?.is_empty() {
+            return Ok(None);
+        }
+
+        let mut commit = Vec::new();
+        // Fall back to a neutral identity on machines without a git identity
+        if self.git(&["config", "user.email"]).is_err() {
+            commit.extend([
+                "-c",
+                "user.name=CC Switch",
+                "-c",
+                "user.email=cc-switch@localhost",
+            ]);
+        }
+        commit.extend(["commit", "--quiet", "-m", message]);
+        self.git(&commit)?;
+        self.git(&[
+            "push",
+            "--quiet",
+            "origin",
+            &format!("HEAD:refs/heads/{}", self.branch),
+        ])?;
+        Ok(self.head())
+    }
+
+    fn git(&self, args: &[&str]) -> Result<String, AppError> {
+        run(Command::new("git").arg("-C").arg(&self.dir).args(args))
+    }
+}
+
+fn run(command: &mut Command) -> Result<String, AppError> {
+    let output = command
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .map_err(|e| {
+            AppError::localized(
+                "sync.git_unavailable",
+                format!("无法运行 git: {e}"),
+                format!("Failed to run git: {e}"),
+            )
+        })?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(AppError::Message(format!(
+            "git 执行失败: {}",
+            stderr.trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
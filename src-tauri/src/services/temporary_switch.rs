@@ -0,0 +1,263 @@
+//! 限时切换
+//!
+//! 临时切换到某个供应商（例如试用新的中转），到期或包装的命令退出后自动切回之前的供应商。
+//! 待恢复记录保存在 `pending_reverts` 表中（见 [`PendingRevert`]），因此计时可以由常驻的
+//! 应用进程负责，也可以由 CLI 启动的后台计时进程（`switch --revert --wait`）负责；两者同时
+//! 处理同一条记录时只有持有当前计时凭据的一方会生效。期间若用户手动切换到其他供应商，
+//! 则到期时不再恢复。
+
+use std::time::Duration;
+
+use crate::app_config::AppType;
+use crate::database::PendingRevert;
+use crate::error::AppError;
+use crate::services::provider::ProviderService;
+use crate::store::AppState;
+
+/// 一次限时切换
+pub type TemporarySwitch = PendingRevert;
+
+/// 限时切换业务
+pub struct TemporarySwitchService;
+
+impl TemporarySwitchService {
+    /// 解析时长，如 `90s`、`30m`、`2h`、`1h30m`、`1d`
+    pub fn parse_duration(text: &str) -> Result<Duration, AppError> {
+        let invalid = || {
+            AppError::localized(
+                "provider.temporary.invalid_duration",
+                format!("无效的时长: {text}（示例: 30m、2h、1h30m）"),
+                format!("Invalid duration: {text} (e.g. 30m, 2h, 1h30m)"),
+            )
+        };
+
+        let mut total = 0u64;
+        let mut number = String::new();
+        for c in text.trim().chars() {
+            if c.is_ascii_digit() {
+                number.push(c);
+                continue;
+            }
+            let unit = match c.to_ascii_lowercase() {
+                's' => 1,
+                'm' => 60,
+                'h' => 3600,
+                'd' => 86400,
+                _ => return Err(invalid()),
+            };
+            let value: u64 = number.parse().map_err(|_| invalid())?;
+            total = value
+                .checked_mul(unit)
+                .and_then(|secs| total.checked_add(secs))
+                .ok_or_else(invalid)?;
+            number.clear();
+        }
+        if !number.is_empty() || total == 0 {
+            return Err(invalid());
+        }
+        Ok(Duration::from_secs(total))
+    }
+
+    /// 立即切换到目标供应商，并登记恢复记录
+    ///
+    /// `duration` 为空时不按时间恢复，只在 `pid` 对应的进程（包装命令的 cc-switch）退出后恢复。
+    /// 若该应用已有未恢复的限时切换，保留最初的恢复目标并重新计时。
+    pub fn start(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+        duration: Option<Duration>,
+        pid: Option<u32>,
+    ) -> Result<TemporarySwitch, AppError> {
+        let current = ProviderService::current(state, app_type.clone())?;
+        let from_id = state
+            .db
+            .get_pending_revert(app_type.as_str())?
+            .map(|existing| existing.from_id)
+            .unwrap_or(current);
+        if from_id.is_empty() {
+            return Err(AppError::Message(
+                "当前没有选中的供应商，无法限时切换".to_string(),
+            ));
+        }
+        if from_id == provider_id {
+            return Err(AppError::InvalidInput(format!(
+                "供应商 {provider_id} 已是恢复目标，无需限时切换"
+            )));
+        }
+
+        ProviderService::switch(state, app_type.clone(), provider_id)?;
+
+        // 计时凭据取纳秒时间戳，保证已取消或被替代的旧计时器（可能在其他进程中）不会误触发
+        let now = chrono::Utc::now();
+        let record = TemporarySwitch {
+            app_type: app_type.as_str().to_string(),
+            from_id,
+            to_id: provider_id.to_string(),
+            revert_at: duration.map(|d| now.timestamp_millis() + d.as_millis() as i64),
+            token: now
+                .timestamp_nanos_opt()
+                .unwrap_or_else(|| now.timestamp_millis()),
+            pid,
+            created_at: now.timestamp_millis(),
+        };
+        state.db.save_pending_revert(&record)?;
+        Ok(record)
+    }
+
+    /// 到期处理：取出记录，返回需要切回的供应商 ID
+    ///
+    /// 计时凭据已失效（被新的限时切换替代或已取消）、或用户已手动切换到其他供应商时返回 None。
+    pub fn take_due(
+        state: &AppState,
+        app_type: &AppType,
+        token: i64,
+    ) -> Result<Option<String>, AppError> {
+        let Some(record) = state
+            .db
+            .take_pending_revert(app_type.as_str(), Some(token))?
+        else {
+            return Ok(None);
+        };
+
+        let current = ProviderService::current(state, app_type.clone())?;
+        if current != record.to_id {
+            log::info!(
+                "{} 已手动切换到 {current}，跳过限时切换的自动恢复",
+                app_type.as_str()
+            );
+            return Ok(None);
+        }
+        if state
+            .db
+            .get_provider_by_id(&record.from_id, app_type.as_str())?
+            .is_none()
+        {
+            log::warn!("恢复目标供应商 {} 已不存在，跳过自动恢复", record.from_id);
+            return Ok(None);
+        }
+        Ok(Some(record.from_id))
+    }
+
+    /// 取出记录并切回之前的供应商（不经过托盘，供 CLI 与后台计时进程使用）
+    pub fn revert(
+        state: &AppState,
+        app_type: &AppType,
+        token: i64,
+    ) -> Result<Option<String>, AppError> {
+        let Some(from_id) = Self::take_due(state, app_type, token)? else {
+            return Ok(None);
+        };
+        log::info!("限时切换结束，{} 切回供应商 {from_id}", app_type.as_str());
+        ProviderService::switch(state, app_type.clone(), &from_id)?;
+        Ok(Some(from_id))
+    }
+
+    /// 已到期的记录：到期时间已过，或包装命令的进程已不存在
+    pub fn due(state: &AppState) -> Result<Vec<TemporarySwitch>, AppError> {
+        let now = chrono::Utc::now().timestamp_millis();
+        Ok(state
+            .db
+            .list_pending_reverts()?
+            .into_iter()
+            .filter(|record| {
+                record.revert_at.is_some_and(|at| at <= now)
+                    || record.pid.is_some_and(|pid| !process_alive(pid))
+            })
+            .collect())
+    }
+
+    /// 当前未恢复的限时切换
+    pub fn get(state: &AppState, app_type: &AppType) -> Result<Option<TemporarySwitch>, AppError> {
+        state.db.get_pending_revert(app_type.as_str())
+    }
+
+    /// 所有未恢复的限时切换
+    pub fn list(state: &AppState) -> Result<Vec<TemporarySwitch>, AppError> {
+        state.db.list_pending_reverts()
+    }
+
+    /// 启动后台计时进程（`cc-switch switch --revert --app <app> --wait <token>`），到期后切回
+    ///
+    /// 计时进程脱离当前终端的进程组，关闭终端不会中断它；当前进程的 `--config-dir` /
+    /// `--db-path` 会一并传递。
+    pub fn spawn_revert_timer(app_type: &AppType, token: i64) -> Result<(), AppError> {
+        let binary = std::env::current_exe()
+            .map_err(|e| AppError::Message(format!("无法获取 cc-switch 可执行文件路径: {e}")))?;
+        let mut command = std::process::Command::new(binary);
+        if let Some(dir) = crate::config::get_home_override() {
+            command.arg("--config-dir").arg(dir);
+        }
+        if let Some(path) = crate::config::get_db_path_override() {
+            command.arg("--db-path").arg(path);
+        }
+        command
+            .args(["switch", "--revert", "--app", app_type.as_str(), "--wait"])
+            .arg(token.to_string())
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null());
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            command.process_group(0);
+        }
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::CommandExt;
+            const DETACHED_PROCESS: u32 = 0x0000_0008;
+            const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+            command.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+        }
+        command
+            .spawn()
+            .map_err(|e| AppError::Message(format!("启动限时切换计时进程失败: {e}")))?;
+        Ok(())
+    }
+
+    /// 取消限时切换（保持当前供应商，不再自动恢复）
+    pub fn cancel(
+        state: &AppState,
+        app_type: &AppType,
+    ) -> Result<Option<TemporarySwitch>, AppError> {
+        state.db.take_pending_revert(app_type.as_str(), None)
+    }
+}
+
+/// 进程是否仍在运行；无法判断的平台上视为仍在运行
+fn process_alive(pid: u32) -> bool {
+    #[cfg(unix)]
+    {
+        // 信号 0 只做存在性与权限检查；EPERM 表示进程存在但属于其他用户
+        let result = unsafe { libc::kill(pid as libc::pid_t, 0) };
+        result == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = pid;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_duration_accepts_compound_units() {
+        let parse = TemporarySwitchService::parse_duration;
+        assert_eq!(parse("90s").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse("2h").unwrap(), Duration::from_secs(7200));
+        assert_eq!(parse("1h30m").unwrap(), Duration::from_secs(5400));
+        assert!(parse("2").is_err());
+        assert!(parse("0m").is_err());
+        assert!(parse("1w").is_err());
+        assert!(parse("").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn process_alive_detects_running_process() {
+        assert!(process_alive(std::process::id()));
+    }
+}
@@ -119,7 +119,7 @@ impl Database {
         start_date: Option<i64>,
         end_date: Option<i64>,
     ) -> Result<UsageSummary, AppError> {
-        let conn = lock_conn!(self.conn);
+        self.with_read(|conn| {
 
         let (where_clause, params_vec) = if start_date.is_some() || end_date.is_some() {
             let mut conditions = Vec::new();
@@ -179,14 +179,14 @@ impl Database {
         })?;
 
         Ok(result)
+        })
     }
 
     /// 获取每日趋势
     pub fn get_daily_trends(&self, days: u32) -> Result<Vec<DailyStats>, AppError> {
-        let conn = lock_conn!(self.conn);
-
-        if days <= 1 {
-            let sql = "SELECT 
+        self.with_read(|conn| {
+            if days <= 1 {
+                let sql = "SELECT 
                     strftime('%Y-%m-%dT%H:00:00Z', datetime(created_at, 'unixepoch')) as bucket,
                     COUNT(*) as request_count,
                     COALESCE(SUM(CAST(total_cost_usd AS REAL)), 0) as total_cost,
@@ -200,53 +200,53 @@ impl Database {
                  GROUP BY bucket
                  ORDER BY bucket ASC";
 
-            let mut stmt = conn.prepare(sql)?;
-            let rows = stmt.query_map([], |row| {
-                Ok(DailyStats {
-                    date: row.get(0)?,
-                    request_count: row.get::<_, i64>(1)? as u64,
-                    total_cost: format!("{:.6}", row.get::<_, f64>(2)?),
-                    total_tokens: row.get::<_, i64>(3)? as u64,
-                    total_input_tokens: row.get::<_, i64>(4)? as u64,
-                    total_output_tokens: row.get::<_, i64>(5)? as u64,
-                    total_cache_creation_tokens: row.get::<_, i64>(6)? as u64,
-                    total_cache_read_tokens: row.get::<_, i64>(7)? as u64,
-                })
-            })?;
-
-            let mut buckets: HashMap<String, DailyStats> = HashMap::new();
-            for row in rows {
-                let stat = row?;
-                buckets.insert(stat.date.clone(), stat);
-            }
+                let mut stmt = conn.prepare_cached(sql)?;
+                let rows = stmt.query_map([], |row| {
+                    Ok(DailyStats {
+                        date: row.get(0)?,
+                        request_count: row.get::<_, i64>(1)? as u64,
+                        total_cost: format!("{:.6}", row.get::<_, f64>(2)?),
+                        total_tokens: row.get::<_, i64>(3)? as u64,
+                        total_input_tokens: row.get::<_, i64>(4)? as u64,
+                        total_output_tokens: row.get::<_, i64>(5)? as u64,
+                        total_cache_creation_tokens: row.get::<_, i64>(6)? as u64,
+                        total_cache_read_tokens: row.get::<_, i64>(7)? as u64,
+                    })
+                })?;
+
+                let mut buckets: HashMap<String, DailyStats> = HashMap::new();
+                for row in rows {
+                    let stat = row?;
+                    buckets.insert(stat.date.clone(), stat);
+                }
 
-            let mut stats = Vec::new();
-            let today = Utc::now().date_naive();
-            for hour in 0..24 {
-                let bucket = today
-                    .and_hms_opt(hour, 0, 0)
-                    .unwrap()
-                    .format("%Y-%m-%dT%H:00:00Z")
-                    .to_string();
-
-                if let Some(stat) = buckets.remove(&bucket) {
-                    stats.push(stat);
-                } else {
-                    stats.push(DailyStats {
-                        date: bucket,
-                        request_count: 0,
-                        total_cost: "0.000000".to_string(),
-                        total_tokens: 0,
-                        total_input_tokens: 0,
-                        total_output_tokens: 0,
-                        total_cache_creation_tokens: 0,
-                        total_cache_read_tokens: 0,
-                    });
+                let mut stats = Vec::new();
+                let today = Utc::now().date_naive();
+                for hour in 0..24 {
+                    let bucket = today
+                        .and_hms_opt(hour, 0, 0)
+                        .unwrap()
+                        .format("%Y-%m-%dT%H:00:00Z")
+                        .to_string();
+
+                    if let Some(stat) = buckets.remove(&bucket) {
+                        stats.push(stat);
+                    } else {
+                        stats.push(DailyStats {
+                            date: bucket,
+                            request_count: 0,
+                            total_cost: "0.000000".to_string(),
+                            total_tokens: 0,
+                            total_input_tokens: 0,
+                            total_output_tokens: 0,
+                            total_cache_creation_tokens: 0,
+                            total_cache_read_tokens: 0,
+                        });
+                    }
                 }
-            }
-            Ok(stats)
-        } else {
-            let sql = "SELECT 
+                Ok(stats)
+            } else {
+                let sql = "SELECT 
                     date(created_at, 'unixepoch') as bucket,
                     COUNT(*) as request_count,
                     COALESCE(SUM(CAST(total_cost_usd AS REAL)), 0) as total_cost,
@@ -260,55 +260,56 @@ impl Database {
                  GROUP BY bucket
                  ORDER BY bucket ASC";
 
-            let mut stmt = conn.prepare(sql)?;
-            let rows = stmt.query_map([format!("-{days} days")], |row| {
-                Ok(DailyStats {
-                    date: row.get(0)?,
-                    request_count: row.get::<_, i64>(1)? as u64,
-                    total_cost: format!("{:.6}", row.get::<_, f64>(2)?),
-                    total_tokens: row.get::<_, i64>(3)? as u64,
-                    total_input_tokens: row.get::<_, i64>(4)? as u64,
-                    total_output_tokens: row.get::<_, i64>(5)? as u64,
-                    total_cache_creation_tokens: row.get::<_, i64>(6)? as u64,
-                    total_cache_read_tokens: row.get::<_, i64>(7)? as u64,
-                })
-            })?;
-
-            let mut map = HashMap::new();
-            for row in rows {
-                let stat = row?;
-                map.insert(stat.date.clone(), stat);
-            }
+                let mut stmt = conn.prepare_cached(sql)?;
+                let rows = stmt.query_map([format!("-{days} days")], |row| {
+                    Ok(DailyStats {
+                        date: row.get(0)?,
+                        request_count: row.get::<_, i64>(1)? as u64,
+                        total_cost: format!("{:.6}", row.get::<_, f64>(2)?),
+                        total_tokens: row.get::<_, i64>(3)? as u64,
+                        total_input_tokens: row.get::<_, i64>(4)? as u64,
+                        total_output_tokens: row.get::<_, i64>(5)? as u64,
+                        total_cache_creation_tokens: row.get::<_, i64>(6)? as u64,
+                        total_cache_read_tokens: row.get::<_, i64>(7)? as u64,
+                    })
+                })?;
+
+                let mut map = HashMap::new();
+                for row in rows {
+                    let stat = row?;
+                    map.insert(stat.date.clone(), stat);
+                }
 
-            let mut stats = Vec::new();
-            let start_day =
-                Utc::now().date_naive() - Duration::days((days.saturating_sub(1)) as i64);
-
-            for i in 0..days {
-                let day = start_day + Duration::days(i as i64);
-                let key = day.format("%Y-%m-%d").to_string();
-                if let Some(stat) = map.remove(&key) {
-                    stats.push(stat);
-                } else {
-                    stats.push(DailyStats {
-                        date: key,
-                        request_count: 0,
-                        total_cost: "0.000000".to_string(),
-                        total_tokens: 0,
-                        total_input_tokens: 0,
-                        total_output_tokens: 0,
-                        total_cache_creation_tokens: 0,
-                        total_cache_read_tokens: 0,
-                    });
+                let mut stats = Vec::new();
+                let start_day =
+                    Utc::now().date_naive() - Duration::days((days.saturating_sub(1)) as i64);
+
+                for i in 0..days {
+                    let day = start_day + Duration::days(i as i64);
+                    let key = day.format("%Y-%m-%d").to_string();
+                    if let Some(stat) = map.remove(&key) {
+                        stats.push(stat);
+                    } else {
+                        stats.push(DailyStats {
+                            date: key,
+                            request_count: 0,
+                            total_cost: "0.000000".to_string(),
+                            total_tokens: 0,
+                            total_input_tokens: 0,
+                            total_output_tokens: 0,
+                            total_cache_creation_tokens: 0,
+                            total_cache_read_tokens: 0,
+                        });
+                    }
                 }
+                Ok(stats)
             }
-            Ok(stats)
-        }
+        })
     }
 
     /// 获取 Provider 统计
     pub fn get_provider_stats(&self) -> Result<Vec<ProviderStats>, AppError> {
-        let conn = lock_conn!(self.conn);
+        self.with_read(|conn| {
 
         let sql = "SELECT 
                 l.provider_id,
@@ -323,7 +324,7 @@ impl Database {
              GROUP BY l.provider_id, l.app_type
              ORDER BY total_cost DESC";
 
-        let mut stmt = conn.prepare(sql)?;
+        let mut stmt = conn.prepare_cached(sql)?;
         let rows = stmt.query_map([], |row| {
             let request_count: i64 = row.get(2)?;
             let success_count: i64 = row.get(5)?;
@@ -352,13 +353,13 @@ impl Database {
         }
 
         Ok(stats)
+        })
     }
 
     /// 获取模型统计
     pub fn get_model_stats(&self) -> Result<Vec<ModelStats>, AppError> {
-        let conn = lock_conn!(self.conn);
-
-        let sql = "SELECT 
+        self.with_read(|conn| {
+            let sql = "SELECT 
                 model,
                 COUNT(*) as request_count,
                 COALESCE(SUM(input_tokens + output_tokens), 0) as total_tokens,
@@ -367,31 +368,32 @@ impl Database {
              GROUP BY model
              ORDER BY total_cost DESC";
 
-        let mut stmt = conn.prepare(sql)?;
-        let rows = stmt.query_map([], |row| {
-            let request_count: i64 = row.get(1)?;
-            let total_cost: f64 = row.get(3)?;
-            let avg_cost = if request_count > 0 {
-                total_cost / request_count as f64
-            } else {
-                0.0
-            };
-
-            Ok(ModelStats {
-                model: row.get(0)?,
-                request_count: request_count as u64,
-                total_tokens: row.get::<_, i64>(2)? as u64,
-                total_cost: format!("{total_cost:.6}"),
-                avg_cost_per_request: format!("{avg_cost:.6}"),
-            })
-        })?;
+            let mut stmt = conn.prepare_cached(sql)?;
+            let rows = stmt.query_map([], |row| {
+                let request_count: i64 = row.get(1)?;
+                let total_cost: f64 = row.get(3)?;
+                let avg_cost = if request_count > 0 {
+                    total_cost / request_count as f64
+                } else {
+                    0.0
+                };
+
+                Ok(ModelStats {
+                    model: row.get(0)?,
+                    request_count: request_count as u64,
+                    total_tokens: row.get::<_, i64>(2)? as u64,
+                    total_cost: format!("{total_cost:.6}"),
+                    avg_cost_per_request: format!("{avg_cost:.6}"),
+                })
+            })?;
 
-        let mut stats = Vec::new();
-        for row in rows {
-            stats.push(row?);
-        }
+            let mut stats = Vec::new();
+            for row in rows {
+                stats.push(row?);
+            }
 
-        Ok(stats)
+            Ok(stats)
+        })
     }
 
     /// 获取请求日志列表（分页）
@@ -401,7 +403,7 @@ impl Database {
         page: u32,
         page_size: u32,
     ) -> Result<PaginatedLogs, AppError> {
-        let conn = lock_conn!(self.conn);
+        self.with_read(|conn| {
 
         let mut conditions = Vec::new();
         let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
@@ -466,7 +468,7 @@ impl Database {
              LIMIT ? OFFSET ?"
         );
 
-        let mut stmt = conn.prepare(&sql)?;
+        let mut stmt = conn.prepare_cached(&sql)?;
         let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
         let rows = stmt.query_map(params_refs.as_slice(), |row| {
             Ok(RequestLogDetail {
@@ -501,7 +503,7 @@ impl Database {
         for row in rows {
             let mut log = row?;
             Self::maybe_backfill_log_costs(
-                &conn,
+                conn,
                 &mut log,
                 &mut provider_cache,
                 &mut pricing_cache,
@@ -515,6 +517,7 @@ impl Database {
             page,
             page_size,
         })
+        })
     }
 
     /// 获取单个请求详情
@@ -522,7 +525,7 @@ impl Database {
         &self,
         request_id: &str,
     ) -> Result<Option<RequestLogDetail>, AppError> {
-        let conn = lock_conn!(self.conn);
+        self.with_read(|conn| {
 
         let result = conn.query_row(
             "SELECT l.request_id, l.provider_id, p.name as provider_name, l.app_type, l.model,
@@ -566,7 +569,7 @@ impl Database {
                 let mut provider_cache = HashMap::new();
                 let mut pricing_cache = HashMap::new();
                 Self::maybe_backfill_log_costs(
-                    &conn,
+                    conn,
                     &mut detail,
                     &mut provider_cache,
                     &mut pricing_cache,
@@ -576,6 +579,7 @@ impl Database {
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
             Err(e) => Err(AppError::Database(e.to_string())),
         }
+        })
     }
 
     /// 检查 Provider 使用限额
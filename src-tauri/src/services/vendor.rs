@@ -0,0 +1,306 @@
+//! 服务商账号（vendor）
+//!
+//! 同一服务商下的多个供应商（例如 OpenRouter 的多个 Key、同一中转的不同线路）可以归到一个
+//! vendor 下：列表按 vendor 分组显示，用量按 vendor 汇总。删除 vendor 时，其供应商默认只解除
+//! 关联，也可以一并归档或删除。
+
+use std::collections::HashMap;
+
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use crate::app_config::AppType;
+use crate::database::Vendor;
+use crate::error::AppError;
+use crate::provider::Provider;
+use crate::services::provider::{ProviderService, TableColumn, TableStyle, ARCHIVED_TAG};
+use crate::store::AppState;
+
+/// 删除 vendor 时如何处理其供应商
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VendorRemoval {
+    /// 只解除关联
+    Detach,
+    /// 打上归档标签
+    Archive,
+    /// 一并删除（正在使用的供应商会使整个操作失败）
+    Cascade,
+}
+
+/// vendor 下的供应商
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VendorProvider {
+    pub app: String,
+    pub id: String,
+    pub name: String,
+}
+
+/// vendor 及其供应商
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VendorSummary {
+    #[serde(flatten)]
+    pub vendor: Vendor,
+    pub providers: Vec<VendorProvider>,
+}
+
+/// 按 vendor 汇总的用量；`vendor_id` 为空表示未归组的供应商
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VendorUsage {
+    pub vendor_id: Option<String>,
+    pub name: String,
+    pub providers: usize,
+    pub requests: u64,
+    pub tokens: u64,
+}
+
+/// 服务商账号业务
+pub struct VendorService;
+
+impl VendorService {
+    /// 所有 vendor 及其在各应用下的供应商
+    pub fn list(state: &AppState) -> Result<Vec<VendorSummary>, AppError> {
+        let mut members = Self::members(state)?;
+        Ok(state
+            .db
+            .get_vendors()?
+            .into_iter()
+            .map(|vendor| VendorSummary {
+                providers: members.remove(&vendor.id).unwrap_or_default(),
+                vendor,
+            })
+            .collect())
+    }
+
+    pub fn add(
+        state: &AppState,
+        id: &str,
+        name: Option<&str>,
+        website_url: Option<&str>,
+        notes: Option<&str>,
+    ) -> Result<Vendor, AppError> {
+        let id = id.trim();
+        if id.is_empty() {
+            return Err(AppError::InvalidInput("vendor ID 不能为空".to_string()));
+        }
+        if state.db.get_vendor(id)?.is_some() {
+            return Err(AppError::InvalidInput(format!("vendor {id} 已存在")));
+        }
+        let vendor = Vendor {
+            id: id.to_string(),
+            name: name.unwrap_or(id).to_string(),
+            website_url: website_url.map(str::to_string),
+            notes: notes.map(str::to_string),
+            created_at: chrono::Utc::now().timestamp_millis(),
+        };
+        state.db.save_vendor(&vendor)?;
+        Ok(vendor)
+    }
+
+    /// 修改 vendor；为 None 的字段保持不变，空字符串清除网址或备注
+    pub fn update(
+        state: &AppState,
+        id: &str,
+        name: Option<&str>,
+        website_url: Option<&str>,
+        notes: Option<&str>,
+    ) -> Result<Vendor, AppError> {
+        let mut vendor = Self::get(state, id)?;
+        if let Some(name) = name {
+            vendor.name = name.to_string();
+        }
+        if let Some(url) = website_url {
+            vendor.website_url = Some(url.to_string()).filter(|url| !url.is_empty());
+        }
+        if let Some(notes) = notes {
+            vendor.notes = Some(notes.to_string()).filter(|notes| !notes.is_empty());
+        }
+        state.db.save_vendor(&vendor)?;
+        Ok(vendor)
+    }
+
+    /// 把供应商归到 vendor 下；`vendor_id` 为 None 时解除关联
+    pub fn assign(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+        vendor_id: Option<&str>,
+    ) -> Result<(), AppError> {
+        if let Some(vendor_id) = vendor_id {
+            Self::get(state, vendor_id)?;
+        }
+        if !state
+            .db
+            .set_provider_vendor(app_type.as_str(), provider_id, vendor_id)?
+        {
+            return Err(AppError::provider_not_found(provider_id, app_type.as_str()));
+        }
+        Ok(())
+    }
+
+    /// 删除 vendor，返回受影响的供应商数
+    pub fn remove(state: &AppState, id: &str, removal: VendorRemoval) -> Result<usize, AppError> {
+        Self::get(state, id)?;
+        let mut by_app: IndexMap<String, Vec<String>> = IndexMap::new();
+        for provider in Self::members(state)?.remove(id).unwrap_or_default() {
+            by_app.entry(provider.app).or_default().push(provider.id);
+        }
+        let affected = by_app.values().map(Vec::len).sum();
+
+        match removal {
+            VendorRemoval::Detach => {}
+            VendorRemoval::Archive => {
+                for (app, ids) in &by_app {
+                    state.db.add_tag_to_providers(app, ids, ARCHIVED_TAG)?;
+                }
+            }
+            VendorRemoval::Cascade => {
+                // 先检查所有应用，避免删除一部分后才发现某个供应商正在使用
+                for (app, ids) in &by_app {
+                    let app_type = app.parse::<AppType>()?;
+                    let current = ProviderService::current(state, app_type)?;
+                    if ids.contains(&current) {
+                        return Err(AppError::Message(format!(
+                            "无法删除当前正在使用的供应商: {current}"
+                        )));
+                    }
+                }
+                for (app, ids) in &by_app {
+                    ProviderService::delete_many(state, app.parse::<AppType>()?, ids)?;
+                }
+            }
+        }
+
+        state.db.delete_vendor(id)?;
+        Ok(affected)
+    }
+
+    /// 按 vendor 汇总代理记录到的用量（包含所有应用）
+    pub fn usage(state: &AppState) -> Result<Vec<VendorUsage>, AppError> {
+        let assigned = state.db.get_provider_vendors(None)?;
+        let mut usage: IndexMap<Option<String>, VendorUsage> = state
+            .db
+            .get_vendors()?
+            .into_iter()
+            .map(|vendor| {
+                let entry = VendorUsage {
+                    vendor_id: Some(vendor.id.clone()),
+                    name: vendor.name,
+                    providers: 0,
+                    requests: 0,
+                    tokens: 0,
+                };
+                (Some(vendor.id), entry)
+            })
+            .collect();
+        for vendor_id in assigned.values() {
+            if let Some(entry) = usage.get_mut(&Some(vendor_id.clone())) {
+                entry.providers += 1;
+            }
+        }
+
+        for counters in state.db.get_usage_counters(None)? {
+            let vendor_id = assigned
+                .get(&(counters.app_type, counters.provider_id))
+                .filter(|id| usage.contains_key(&Some((*id).clone())))
+                .cloned();
+            let entry = usage.entry(vendor_id).or_insert_with(|| VendorUsage {
+                vendor_id: None,
+                name: "(未归组)".to_string(),
+                providers: 0,
+                requests: 0,
+                tokens: 0,
+            });
+            if entry.vendor_id.is_none() {
+                entry.providers += 1;
+            }
+            entry.requests += counters.requests;
+            entry.tokens += counters.tokens;
+        }
+        Ok(usage.into_values().collect())
+    }
+
+    /// 按 vendor 分组渲染供应商表格；未归组的供应商排在最后
+    pub fn render_grouped(
+        state: &AppState,
+        app_type: AppType,
+        providers: &IndexMap<String, Provider>,
+        columns: &[TableColumn],
+        style: TableStyle,
+    ) -> Result<String, AppError> {
+        let assigned = state.db.get_provider_vendors(Some(app_type.as_str()))?;
+        let vendor_of = |provider_id: &str| {
+            assigned
+                .get(&(app_type.as_str().to_string(), provider_id.to_string()))
+                .cloned()
+        };
+
+        let vendors = state.db.get_vendors()?;
+        let members_of = |vendor_id: Option<&str>| -> IndexMap<String, Provider> {
+            providers
+                .iter()
+                .filter(|(id, _)| match vendor_of(id) {
+                    Some(id) if vendors.iter().any(|v| v.id == id) => {
+                        vendor_id == Some(id.as_str())
+                    }
+                    _ => vendor_id.is_none(),
+                })
+                .map(|(id, p)| (id.clone(), p.clone()))
+                .collect()
+        };
+
+        let mut groups: Vec<(String, IndexMap<String, Provider>)> = Vec::new();
+        for vendor in &vendors {
+            let members = members_of(Some(vendor.id.as_str()));
+            if !members.is_empty() {
+                groups.push((format!("{} ({})", vendor.name, vendor.id), members));
+            }
+        }
+        let ungrouped = members_of(None);
+        if !ungrouped.is_empty() {
+            groups.push(("(未归组)".to_string(), ungrouped));
+        }
+
+        let mut out = Vec::with_capacity(groups.len());
+        for (title, members) in groups {
+            let table =
+                ProviderService::render_table_of(state, &app_type, &members, columns, style)?;
+            out.push(format!("{title}\n{table}"));
+        }
+        Ok(out.join("\n"))
+    }
+
+    fn get(state: &AppState, id: &str) -> Result<Vendor, AppError> {
+        state
+            .db
+            .get_vendor(id)?
+            .ok_or_else(|| AppError::InvalidInput(format!("vendor 不存在: {id}")))
+    }
+
+    /// vendor_id -> 其供应商（按应用、供应商顺序）
+    fn members(state: &AppState) -> Result<HashMap<String, Vec<VendorProvider>>, AppError> {
+        let assigned = state.db.get_provider_vendors(None)?;
+        let mut members: HashMap<String, Vec<VendorProvider>> = HashMap::new();
+        if assigned.is_empty() {
+            return Ok(members);
+        }
+        for app_type in [AppType::Claude, AppType::Codex, AppType::Gemini] {
+            let app = app_type.as_str().to_string();
+            for provider in state.db.get_all_providers(&app)?.into_values() {
+                if let Some(vendor_id) = assigned.get(&(app.clone(), provider.id.clone())) {
+                    members
+                        .entry(vendor_id.clone())
+                        .or_default()
+                        .push(VendorProvider {
+                            app: app.clone(),
+                            id: provider.id,
+                            name: provider.name,
+                        });
+                }
+            }
+        }
+        Ok(members)
+    }
+}
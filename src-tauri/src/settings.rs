@@ -1,3 +1,4 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
@@ -7,13 +8,139 @@ use crate::app_config::AppType;
 use crate::error::AppError;
 
 /// 自定义端点配置（历史兼容，实际存储在 provider.meta.custom_endpoints）
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CustomEndpoint {
     pub url: String,
     pub added_at: i64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_used: Option<i64>,
+    /// 最近一次测速的耗时（毫秒），测速失败时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    /// 最近一次测速的时间
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tested_at: Option<i64>,
+}
+
+/// 云同步配置（设备级，包含凭据，不写入数据库）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncSettings {
+    /// `webdav`、`s3` 或 `git`
+    pub backend: String,
+    /// WebDAV 目录地址，或 S3 兼容服务的 endpoint（如 `https://s3.us-east-1.amazonaws.com`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bucket: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_key_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_access_key: Option<String>,
+    /// 远端对象名前缀（S3），默认 `cc-switch/`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    /// Git 仓库地址（git 后端），如 `git@github.com:me/cc-switch-config`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote: Option<String>,
+    /// Git 分支（git 后端），默认 `main`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// 快照加密口令；环境变量 `CC_SWITCH_SYNC_PASSPHRASE` 优先
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub passphrase: Option<String>,
+}
+
+/// 数据库自动备份策略（备份位于 `~/.cc-switch/backups`）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupSettings {
+    /// 启动时距离上次备份超过该小时数则自动备份，0 表示关闭，默认 24
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval_hours: Option<u64>,
+    /// 最多保留的备份数量，默认 10
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retain_count: Option<usize>,
+    /// 备份总大小上限（MB），超出时从最旧的开始删除，默认不限
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_total_mb: Option<u64>,
+    /// 切换供应商写入 settings.json / auth.json / config.toml 时把上一版保留为 `<文件名>.bak`，
+    /// 默认关闭
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub live_backup: Option<bool>,
+    /// 每个应用最多保留的配置目录快照数量（`cc-switch app snapshot`），默认 10
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot_retain_count: Option<usize>,
+}
+
+/// 切换供应商前后执行的钩子，见 `cc-switch hook`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SwitchHooks {
+    /// 切换前执行；命令以非零状态退出时取消切换
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pre_switch: Vec<SwitchHook>,
+    /// 切换成功后执行
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_switch: Vec<SwitchHook>,
+}
+
+impl SwitchHooks {
+    pub fn is_empty(&self) -> bool {
+        self.pre_switch.is_empty() && self.post_switch.is_empty()
+    }
+}
+
+/// 一个钩子：执行 shell 命令，或向 webhook POST JSON（app、from、to、timestamp）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SwitchHook {
+    /// 通过 `sh -c`（Windows 为 `cmd /C`）执行，事件信息见 `CC_SWITCH_*` 环境变量
+    Command {
+        command: String,
+    },
+    Webhook {
+        url: String,
+    },
+}
+
+/// 故障转移、超出消费限额等事件的通知方式（见 `crate::notifications`）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationSettings {
+    /// 为空时不发送通知（仅写日志）
+    #[serde(default)]
+    pub channels: Vec<NotificationChannel>,
+}
+
+impl Default for NotificationSettings {
+    /// 未配置时只发送桌面通知
+    fn default() -> Self {
+        Self {
+            channels: vec![NotificationChannel::Desktop],
+        }
+    }
+}
+
+/// 一个通知渠道
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum NotificationChannel {
+    /// 打印到标准输出（前台运行代理或脚本时使用）
+    Stdout,
+    /// 系统桌面通知
+    Desktop,
+    /// POST JSON 到指定地址
+    Webhook { url: String },
+    /// 执行 shell 命令，事件信息见 `CC_SWITCH_*` 环境变量
+    Command { command: String },
 }
 
 /// 应用设置结构
@@ -39,6 +166,39 @@ pub struct AppSettings {
     pub launch_on_startup: bool,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub language: Option<String>,
+    /// 数字格式的区域设置（如 `en-US`、`de-DE`），未设置时跟随界面语言
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub number_locale: Option<String>,
+    /// 用量金额统一显示的货币（`USD` / `CNY`），未设置时保持原货币
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_currency: Option<String>,
+    /// USD → CNY 换算汇率
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usd_to_cny_rate: Option<f64>,
+    /// 供应商超出消费限额时的处理（`off` / `warn` / `block`），未设置时为 warn
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spending_limit_action: Option<String>,
+    /// 云同步配置，见 `cc-switch sync`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sync: Option<SyncSettings>,
+    /// 数据库自动备份与保留策略，见 `cc-switch backup`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backup: Option<BackupSettings>,
+    /// 对所有供应商生效的切换钩子（供应商自己的钩子在其后执行）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hooks: Option<SwitchHooks>,
+    /// 通知渠道，未设置时为桌面通知
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notifications: Option<NotificationSettings>,
+    /// 只读模式：可以查看配置，但不能切换或修改供应商，见 [`is_read_only`]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub read_only: bool,
+    /// 数据库存放在当前用户的数据目录而不是应用配置目录，见 [`crate::config::get_data_dir`]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub per_user_database: bool,
+    /// 允许以外部进程运行 shell / node / python 用量脚本，见 [`external_usage_scripts_allowed`]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub allow_external_usage_scripts: bool,
 
     // ===== 设备级目录覆盖 =====
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -81,6 +241,17 @@ impl Default for AppSettings {
             skip_claude_onboarding: true,
             launch_on_startup: false,
             language: None,
+            number_locale: None,
+            display_currency: None,
+            usd_to_cny_rate: None,
+            spending_limit_action: None,
+            sync: None,
+            backup: None,
+            hooks: None,
+            notifications: None,
+            read_only: false,
+            per_user_database: false,
+            allow_external_usage_scripts: false,
             claude_config_dir: None,
             codex_config_dir: None,
             gemini_config_dir: None,
@@ -92,11 +263,14 @@ impl Default for AppSettings {
 }
 
 impl AppSettings {
-    fn settings_path() -> PathBuf {
+    pub(crate) fn settings_path() -> PathBuf {
         // settings.json 保留用于旧版本迁移和无数据库场景
-        dirs::home_dir()
-            .expect("无法获取用户主目录")
-            .join(".cc-switch")
+        crate::config::get_home_override()
+            .unwrap_or_else(|| {
+                crate::config::home_dir()
+                    .expect("无法获取用户主目录")
+                    .join(".cc-switch")
+            })
             .join("settings.json")
     }
 
@@ -176,15 +350,15 @@ fn settings_store() -> &'static RwLock<AppSettings> {
 
 fn resolve_override_path(raw: &str) -> PathBuf {
     if raw == "~" {
-        if let Some(home) = dirs::home_dir() {
+        if let Some(home) = crate::config::home_dir() {
             return home;
         }
     } else if let Some(stripped) = raw.strip_prefix("~/") {
-        if let Some(home) = dirs::home_dir() {
+        if let Some(home) = crate::config::home_dir() {
             return home.join(stripped);
         }
     } else if let Some(stripped) = raw.strip_prefix("~\\") {
-        if let Some(home) = dirs::home_dir() {
+        if let Some(home) = crate::config::home_dir() {
             return home.join(stripped);
         }
     }
@@ -238,6 +412,61 @@ pub fn get_gemini_override_dir() -> Option<PathBuf> {
         .map(|p| resolve_override_path(p))
 }
 
+// ===== 只读模式 =====
+
+/// 设为 `1` / `true` 时以只读模式运行（与设置中的 readOnly 相同）
+pub const READ_ONLY_ENV: &str = "CC_SWITCH_READ_ONLY";
+
+/// 设为 `1` / `true` 时忽略只读模式（管理员覆盖）
+pub const ADMIN_ENV: &str = "CC_SWITCH_ADMIN";
+
+pub(crate) fn env_flag(name: &str) -> bool {
+    std::env::var(name).is_ok_and(|value| {
+        matches!(
+            value.trim().to_ascii_lowercase().as_str(),
+            "1" | "true" | "yes" | "on"
+        )
+    })
+}
+
+/// 是否处于只读模式
+///
+/// 共享机器上可以让普通用户只查看配置：数据库拒绝所有写入，命令行拒绝切换、添加、
+/// 删除等子命令。由设置 readOnly 或环境变量 `CC_SWITCH_READ_ONLY` 开启，
+/// `CC_SWITCH_ADMIN` 覆盖两者。
+pub fn is_read_only() -> bool {
+    if env_flag(ADMIN_ENV) {
+        return false;
+    }
+    env_flag(READ_ONLY_ENV) || settings_store().read().is_ok_and(|s| s.read_only)
+}
+
+/// 只读模式下返回 [`AppError::ReadOnly`]
+pub fn ensure_writable() -> Result<(), AppError> {
+    if is_read_only() {
+        Err(AppError::ReadOnly)
+    } else {
+        Ok(())
+    }
+}
+
+// ===== 外部用量脚本 =====
+
+/// 设为 `1` / `true` 时允许运行外部用量脚本（与设置中的 allowExternalUsageScripts 相同）
+pub const EXTERNAL_SCRIPTS_ENV: &str = "CC_SWITCH_ALLOW_EXTERNAL_SCRIPTS";
+
+/// 是否允许以外部进程运行 shell / node / python 用量脚本
+///
+/// 这类脚本能以当前用户的身份执行任意命令，而供应商可能来自导入的文件或云同步，因此只有在
+/// 本机开启设置 allowExternalUsageScripts 或环境变量 `CC_SWITCH_ALLOW_EXTERNAL_SCRIPTS` 后才运行。
+/// 两者都不随数据库同步或导出。
+pub fn external_usage_scripts_allowed() -> bool {
+    env_flag(EXTERNAL_SCRIPTS_ENV)
+        || settings_store()
+            .read()
+            .is_ok_and(|s| s.allow_external_usage_scripts)
+}
+
 // ===== 当前供应商管理函数 =====
 
 /// 获取指定应用类型的当前供应商 ID（从本地 settings 读取）
@@ -1,11 +1,12 @@
-use crate::database::Database;
+use crate::database::{AsyncDatabase, Database};
 use crate::services::ProxyService;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
 /// 全局应用状态
 pub struct AppState {
     pub db: Arc<Database>,
     pub proxy_service: ProxyService,
+    async_db: OnceLock<AsyncDatabase>,
 }
 
 impl AppState {
@@ -13,6 +14,16 @@ impl AppState {
     pub fn new(db: Arc<Database>) -> Self {
         let proxy_service = ProxyService::new(db.clone());
 
-        Self { db, proxy_service }
+        Self {
+            db,
+            proxy_service,
+            async_db: OnceLock::new(),
+        }
+    }
+
+    /// 异步命令使用的数据库句柄（首次调用时启动数据库线程）
+    pub fn async_db(&self) -> &AsyncDatabase {
+        self.async_db
+            .get_or_init(|| AsyncDatabase::new(self.db.clone()))
     }
 }
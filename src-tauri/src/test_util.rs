@@ -0,0 +1,178 @@
+//! 测试辅助模块（需启用 `test-util` feature）
+//!
+//! 为 live 配置读写提供可复用的测试脚手架：
+//! - 隔离的 HOME 目录与全局测试锁
+//! - 各应用的供应商样例（fixtures）
+//! - live 配置的往返校验（写入 → 读取 → 比对）
+//! - golden 文件比对（设置 `CC_SWITCH_UPDATE_GOLDEN=1` 时重写 golden 文件）
+//!
+//! 新增 AppType 时，只需补充 [`fixture_provider`] 的样例即可复用往返校验。
+
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+use serde_json::{json, Value};
+
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::provider::Provider;
+use crate::services::provider::{read_live_settings, write_live_snapshot};
+use crate::settings::{update_settings, AppSettings};
+
+/// 设置后重写 golden 文件而不是比对
+pub const UPDATE_GOLDEN_ENV: &str = "CC_SWITCH_UPDATE_GOLDEN";
+
+/// 隔离的测试 HOME 目录
+///
+/// 持有期间占用全局测试锁，避免多个测试并发写入同一 HOME。
+pub struct TestHome {
+    path: PathBuf,
+    _guard: MutexGuard<'static, ()>,
+}
+
+impl TestHome {
+    /// 创建（或清空）隔离 HOME，并将 `HOME`/`USERPROFILE` 指向它
+    pub fn new() -> Self {
+        static LOCK: OnceLock<Mutex<()>> = OnceLock::new();
+        let guard = LOCK
+            .get_or_init(|| Mutex::new(()))
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+
+        let path = std::env::temp_dir().join("cc-switch-test-util-home");
+        if path.exists() {
+            let _ = std::fs::remove_dir_all(&path);
+        }
+        std::fs::create_dir_all(&path).expect("create test home");
+        std::env::set_var("HOME", &path);
+        #[cfg(windows)]
+        std::env::set_var("USERPROFILE", &path);
+
+        // 清除设置缓存中的目录覆盖，确保路径解析到隔离 HOME
+        let _ = update_settings(AppSettings::default());
+
+        Self {
+            path,
+            _guard: guard,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Default for TestHome {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 各应用的供应商样例
+pub fn fixture_provider(app_type: &AppType) -> Provider {
+    let (id, settings) = match app_type {
+        AppType::Claude => (
+            "fixture-claude",
+            json!({
+                "env": {
+                    "ANTHROPIC_AUTH_TOKEN": "sk-fixture",
+                    "ANTHROPIC_BASE_URL": "https://claude.example.com"
+                },
+                "permissions": { "allow": [] }
+            }),
+        ),
+        AppType::Codex => (
+            "fixture-codex",
+            json!({
+                "auth": { "OPENAI_API_KEY": "sk-fixture" },
+                "config": "model_provider = \"fixture\"\nmodel = \"gpt-5-codex\"\n\n[model_providers.fixture]\nname = \"fixture\"\nbase_url = \"https://codex.example.com/v1\"\nwire_api = \"responses\"\n"
+            }),
+        ),
+        AppType::Gemini => (
+            "fixture-gemini",
+            json!({
+                "env": {
+                    "GEMINI_API_KEY": "fixture-key",
+                    "GOOGLE_GEMINI_BASE_URL": "https://gemini.example.com"
+                },
+                "config": {}
+            }),
+        ),
+    };
+
+    Provider::with_id(id.to_string(), format!("Fixture {id}"), settings, None)
+}
+
+/// 写入 live 配置后重新读取，返回读取结果
+pub fn live_round_trip(app_type: &AppType, provider: &Provider) -> Result<Value, AppError> {
+    write_live_snapshot(app_type, provider)?;
+    read_live_settings(app_type.clone())
+}
+
+/// 断言供应商配置经 live 文件往返后保持不变
+///
+/// 采用包含语义：期望值中的每个字段都必须出现在读取结果中且相等，
+/// 读取结果可以包含写入器补充的额外字段。
+pub fn assert_live_round_trip(app_type: &AppType, provider: &Provider) {
+    let actual = live_round_trip(app_type, provider)
+        .unwrap_or_else(|e| panic!("{} live round trip failed: {e}", app_type.as_str()));
+
+    if let Some(path) = first_mismatch(&provider.settings_config, &actual, "") {
+        panic!(
+            "{} live round trip mismatch at `{path}`\nexpected: {}\nactual: {}",
+            app_type.as_str(),
+            serde_json::to_string_pretty(&provider.settings_config).unwrap_or_default(),
+            serde_json::to_string_pretty(&actual).unwrap_or_default(),
+        );
+    }
+}
+
+/// 返回期望值中第一个与实际值不一致的路径（包含语义）
+pub fn first_mismatch(expected: &Value, actual: &Value, path: &str) -> Option<String> {
+    match (expected, actual) {
+        (Value::Object(exp), Value::Object(act)) => exp.iter().find_map(|(key, value)| {
+            let child = format!("{path}/{key}");
+            match act.get(key) {
+                Some(actual_value) => first_mismatch(value, actual_value, &child),
+                None => Some(child),
+            }
+        }),
+        _ if expected == actual => None,
+        _ => Some(if path.is_empty() {
+            "/".to_string()
+        } else {
+            path.to_string()
+        }),
+    }
+}
+
+/// 将 JSON 与 golden 文件比对（键排序、格式化后按文本比较）
+pub fn assert_golden_json(golden: &Path, actual: &Value) {
+    let text = serde_json::to_string_pretty(&sort_keys(actual)).expect("serialize golden json");
+    assert_golden_text(golden, &format!("{text}\n"));
+}
+
+/// 将文本与 golden 文件比对
+pub fn assert_golden_text(golden: &Path, actual: &str) {
+    if std::env::var_os(UPDATE_GOLDEN_ENV).is_some() || !golden.exists() {
+        if let Some(parent) = golden.parent() {
+            std::fs::create_dir_all(parent).expect("create golden dir");
+        }
+        std::fs::write(golden, actual).expect("write golden file");
+        return;
+    }
+
+    let expected = std::fs::read_to_string(golden).expect("read golden file");
+    let normalize = |s: &str| s.replace("\r\n", "\n");
+    assert_eq!(
+        normalize(&expected),
+        normalize(actual),
+        "golden mismatch: {} (set {UPDATE_GOLDEN_ENV}=1 to update)",
+        golden.display()
+    );
+}
+
+/// 递归排序对象键，保证输出稳定
+pub fn sort_keys(value: &Value) -> Value {
+    crate::database::sort_json_keys(value)
+}
@@ -0,0 +1,209 @@
+//! 用量与金额的本地化格式
+//!
+//! 按区域设置格式化数字（千位分隔符、小数点）与货币，并支持 USD ↔ CNY 换算：
+//! 中转站余额多以人民币显示，而预算通常以美元设置。换算汇率可在设置中配置。
+
+use crate::settings::AppSettings;
+
+/// 未配置时使用的 USD → CNY 汇率
+pub const DEFAULT_USD_TO_CNY_RATE: f64 = 7.2;
+
+/// 支持换算的货币
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Currency {
+    Usd,
+    Cny,
+}
+
+impl Currency {
+    /// 从用量单位或设置值识别货币（如 `USD`、`$`、`CNY`、`元`）
+    pub fn from_unit(unit: &str) -> Option<Self> {
+        match unit.trim().to_ascii_lowercase().as_str() {
+            "usd" | "$" | "us$" | "dollar" | "dollars" | "美元" => Some(Self::Usd),
+            "cny" | "rmb" | "¥" | "￥" | "元" | "人民币" => Some(Self::Cny),
+            _ => None,
+        }
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Self::Usd => "$",
+            Self::Cny => "¥",
+        }
+    }
+}
+
+/// 区域设置相关的数字格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberLocale {
+    group_separator: char,
+    decimal_separator: char,
+    /// 货币符号在数字之前（`$1.00`）还是之后（`1,00 $`）
+    symbol_first: bool,
+}
+
+impl NumberLocale {
+    /// 根据语言标签（如 `en-US`、`zh`、`de-DE`）选择格式，未知时按英文格式
+    pub fn from_tag(tag: &str) -> Self {
+        let language = tag
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match language.as_str() {
+            "de" | "es" | "it" | "nl" | "pt" | "id" | "tr" => Self {
+                group_separator: '.',
+                decimal_separator: ',',
+                symbol_first: false,
+            },
+            "fr" | "ru" | "pl" | "cs" | "sv" | "fi" | "uk" => Self {
+                group_separator: '\u{a0}',
+                decimal_separator: ',',
+                symbol_first: false,
+            },
+            _ => Self {
+                group_separator: ',',
+                decimal_separator: '.',
+                symbol_first: true,
+            },
+        }
+    }
+
+    /// 按固定小数位格式化
+    pub fn format_number(&self, value: f64, decimals: usize) -> String {
+        let text = format!("{:.*}", decimals, value.abs());
+        let (integer, fraction) = text.split_once('.').unwrap_or((text.as_str(), ""));
+
+        let mut grouped = String::new();
+        for (index, digit) in integer.chars().enumerate() {
+            if index > 0 && (integer.len() - index) % 3 == 0 {
+                grouped.push(self.group_separator);
+            }
+            grouped.push(digit);
+        }
+        if !fraction.is_empty() {
+            grouped.push(self.decimal_separator);
+            grouped.push_str(fraction);
+        }
+        if value < 0.0 && text.chars().any(|c| c.is_ascii_digit() && c != '0') {
+            grouped.insert(0, '-');
+        }
+        grouped
+    }
+
+    /// 格式化金额（两位小数）
+    pub fn format_money(&self, value: f64, currency: Currency) -> String {
+        let number = self.format_number(value, 2);
+        if self.symbol_first {
+            match number.strip_prefix('-') {
+                Some(abs) => format!("-{}{abs}", currency.symbol()),
+                None => format!("{}{number}", currency.symbol()),
+            }
+        } else {
+            format!("{number} {}", currency.symbol())
+        }
+    }
+}
+
+/// 用量输出的格式化器
+#[derive(Debug, Clone, Copy)]
+pub struct UsageFormatter {
+    locale: NumberLocale,
+    /// 金额统一换算到的货币；None 表示保持原货币
+    display_currency: Option<Currency>,
+    usd_to_cny_rate: f64,
+}
+
+impl UsageFormatter {
+    pub fn new(
+        locale: NumberLocale,
+        display_currency: Option<Currency>,
+        usd_to_cny_rate: f64,
+    ) -> Self {
+        Self {
+            locale,
+            display_currency,
+            usd_to_cny_rate,
+        }
+    }
+
+    /// 根据设置构建（numberLocale → language → 英文格式）
+    pub fn from_settings(settings: &AppSettings) -> Self {
+        let tag = settings
+            .number_locale
+            .as_deref()
+            .or(settings.language.as_deref())
+            .unwrap_or("en");
+        Self::new(
+            NumberLocale::from_tag(tag),
+            settings
+                .display_currency
+                .as_deref()
+                .and_then(Currency::from_unit),
+            settings
+                .usd_to_cny_rate
+                .filter(|rate| *rate > 0.0)
+                .unwrap_or(DEFAULT_USD_TO_CNY_RATE),
+        )
+    }
+
+    /// 货币换算
+    pub fn convert(&self, value: f64, from: Currency, to: Currency) -> f64 {
+        match (from, to) {
+            (Currency::Usd, Currency::Cny) => value * self.usd_to_cny_rate,
+            (Currency::Cny, Currency::Usd) => value / self.usd_to_cny_rate,
+            _ => value,
+        }
+    }
+
+    /// 格式化金额，必要时换算到显示货币
+    pub fn money(&self, value: f64, currency: Currency) -> String {
+        let target = self.display_currency.unwrap_or(currency);
+        self.locale
+            .format_money(self.convert(value, currency, target), target)
+    }
+
+    /// 格式化带单位的用量：货币单位按金额显示，其他单位（如 `tokens`、`次`）保留在数字后
+    pub fn amount(&self, value: f64, unit: Option<&str>) -> String {
+        match unit {
+            Some(unit) => match Currency::from_unit(unit) {
+                Some(currency) => self.money(value, currency),
+                None => format!("{} {unit}", self.count(value)),
+            },
+            None => self.count(value),
+        }
+    }
+
+    /// 格式化计数（整数不带小数，否则保留两位）
+    pub fn count(&self, value: f64) -> String {
+        let decimals = if value.fract() == 0.0 { 0 } else { 2 };
+        self.locale.format_number(value, decimals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_numbers_per_locale() {
+        let en = NumberLocale::from_tag("en-US");
+        let de = NumberLocale::from_tag("de_DE");
+        assert_eq!(en.format_number(1234567.891, 2), "1,234,567.89");
+        assert_eq!(de.format_number(1234567.891, 2), "1.234.567,89");
+        assert_eq!(en.format_number(-0.001, 2), "0.00");
+        assert_eq!(en.format_money(-12.5, Currency::Usd), "-$12.50");
+        assert_eq!(de.format_money(12.5, Currency::Cny), "12,50 ¥");
+    }
+
+    #[test]
+    fn converts_between_usd_and_cny() {
+        let formatter = UsageFormatter::new(NumberLocale::from_tag("zh"), Some(Currency::Usd), 7.0);
+        assert_eq!(formatter.amount(70.0, Some("CNY")), "$10.00");
+        assert_eq!(formatter.amount(3.0, Some("USD")), "$3.00");
+        assert_eq!(formatter.amount(12000.0, Some("tokens")), "12,000 tokens");
+
+        let keep = UsageFormatter::new(NumberLocale::from_tag("en"), None, 7.0);
+        assert_eq!(keep.amount(70.0, Some("元")), "¥70.00");
+    }
+}
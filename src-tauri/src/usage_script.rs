@@ -2,6 +2,7 @@ use reqwest::Client;
 use rquickjs::{Context, Function, Runtime};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::process::Stdio;
 use std::time::Duration;
 use url::{Host, Url};
 
@@ -690,6 +691,131 @@ fn is_loopback_host(url: &Url) -> bool {
     }
 }
 
+/// 用量脚本语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptLanguage {
+    /// 内置 QuickJS 执行（request + extractor 模板）
+    JavaScript,
+    Shell,
+    Node,
+    Python,
+}
+
+impl ScriptLanguage {
+    /// 解析 `UsageScript.language`，未知值返回 `None`
+    pub fn parse(language: &str) -> Option<Self> {
+        match language.trim().to_ascii_lowercase().as_str() {
+            "" | "javascript" | "js" => Some(Self::JavaScript),
+            "shell" | "sh" | "bash" => Some(Self::Shell),
+            "node" | "nodejs" => Some(Self::Node),
+            "python" | "python3" | "py" => Some(Self::Python),
+            _ => None,
+        }
+    }
+
+    /// 返回解释器程序及执行内联代码所需的参数
+    fn interpreter(self) -> Option<(&'static str, &'static str)> {
+        match self {
+            Self::JavaScript => None,
+            #[cfg(windows)]
+            Self::Shell => Some(("cmd", "/C")),
+            #[cfg(not(windows))]
+            Self::Shell => Some(("sh", "-c")),
+            Self::Node => Some(("node", "-e")),
+            #[cfg(windows)]
+            Self::Python => Some(("python", "-c")),
+            #[cfg(not(windows))]
+            Self::Python => Some(("python3", "-c")),
+        }
+    }
+}
+
+/// 以外部进程执行用量查询脚本
+///
+/// 凭据通过环境变量注入（`CC_SWITCH_API_KEY`、`CC_SWITCH_BASE_URL`、
+/// `CC_SWITCH_ACCESS_TOKEN`、`CC_SWITCH_USER_ID`），脚本需向 stdout 输出
+/// 与 extractor 返回值相同格式的 JSON（单对象或数组）。
+pub async fn execute_external_script(
+    language: ScriptLanguage,
+    script_code: &str,
+    api_key: &str,
+    base_url: &str,
+    timeout_secs: u64,
+    access_token: Option<&str>,
+    user_id: Option<&str>,
+) -> Result<Value, AppError> {
+    let (program, flag) = language.interpreter().ok_or_else(|| {
+        AppError::localized(
+            "usage_script.not_external",
+            "JavaScript 脚本不能以外部进程执行",
+            "JavaScript scripts cannot run as an external process",
+        )
+    })?;
+
+    let mut command = tokio::process::Command::new(program);
+    command
+        .arg(flag)
+        .arg(script_code)
+        .env("CC_SWITCH_API_KEY", api_key)
+        .env("CC_SWITCH_BASE_URL", base_url)
+        .env("CC_SWITCH_ACCESS_TOKEN", access_token.unwrap_or(""))
+        .env("CC_SWITCH_USER_ID", user_id.unwrap_or(""))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    let child = command.spawn().map_err(|e| {
+        AppError::localized(
+            "usage_script.spawn_failed",
+            format!("启动解释器 {program} 失败: {e}"),
+            format!("Failed to start interpreter {program}: {e}"),
+        )
+    })?;
+
+    // 与 HTTP 请求一致地约束超时范围；超时后 child 被 drop 并终止
+    let timeout = timeout_secs.clamp(2, 30);
+    let output = tokio::time::timeout(Duration::from_secs(timeout), child.wait_with_output())
+        .await
+        .map_err(|_| {
+            AppError::localized(
+                "usage_script.timeout",
+                format!("脚本执行超时（{timeout} 秒）"),
+                format!("Script timed out after {timeout}s"),
+            )
+        })?
+        .map_err(|e| {
+            AppError::localized(
+                "usage_script.wait_failed",
+                format!("等待脚本结束失败: {e}"),
+                format!("Failed to wait for script: {e}"),
+            )
+        })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let preview: String = stderr.trim().chars().take(200).collect();
+        return Err(AppError::localized(
+            "usage_script.exit_failed",
+            format!("脚本退出异常 ({}): {preview}", output.status),
+            format!("Script exited with {}: {preview}", output.status),
+        ));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let result: Value = serde_json::from_str(stdout.trim()).map_err(|e| {
+        AppError::localized(
+            "usage_script.stdout_parse_failed",
+            format!("解析脚本输出 JSON 失败: {e}"),
+            format!("Failed to parse script output as JSON: {e}"),
+        )
+    })?;
+
+    validate_result(&result)?;
+
+    Ok(result)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -867,4 +993,57 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_script_language_parse() {
+        assert_eq!(
+            ScriptLanguage::parse("javascript"),
+            Some(ScriptLanguage::JavaScript)
+        );
+        assert_eq!(ScriptLanguage::parse(""), Some(ScriptLanguage::JavaScript));
+        assert_eq!(ScriptLanguage::parse("Bash"), Some(ScriptLanguage::Shell));
+        assert_eq!(ScriptLanguage::parse("node"), Some(ScriptLanguage::Node));
+        assert_eq!(
+            ScriptLanguage::parse("python3"),
+            Some(ScriptLanguage::Python)
+        );
+        assert_eq!(ScriptLanguage::parse("ruby"), None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_external_shell_script_reads_env_and_parses_stdout() {
+        let script =
+            r#"printf '{"remaining": 12.5, "unit": "USD", "planName": "%s"}' "$CC_SWITCH_API_KEY""#;
+        let result = execute_external_script(
+            ScriptLanguage::Shell,
+            script,
+            "sk-plan",
+            "https://api.example.com",
+            5,
+            None,
+            None,
+        )
+        .await
+        .expect("shell script should succeed");
+
+        assert_eq!(result["remaining"], 12.5);
+        assert_eq!(result["planName"], "sk-plan");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_external_script_non_zero_exit_is_error() {
+        let result = execute_external_script(
+            ScriptLanguage::Shell,
+            "echo boom >&2; exit 3",
+            "",
+            "",
+            5,
+            None,
+            None,
+        )
+        .await;
+        assert!(result.is_err());
+    }
 }
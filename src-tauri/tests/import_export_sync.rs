@@ -4,7 +4,7 @@ use std::path::PathBuf;
 
 use cc_switch_lib::{
     get_claude_settings_path, read_json_file, AppError, AppType, ConfigService, MultiAppConfig,
-    Provider, ProviderMeta,
+    Provider, ProviderMeta, SqlExportOptions,
 };
 
 #[path = "support.rs"]
@@ -1076,3 +1076,63 @@ fn import_sql_accepts_cc_switch_exported_backup() {
         "imported providers should contain test-provider"
     );
 }
+
+#[test]
+fn export_sql_is_deterministic_across_insert_order() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+
+    let export_providers = |ids: &[&str]| -> String {
+        reset_test_fs();
+        let home = ensure_test_home();
+
+        let mut config = MultiAppConfig::default();
+        {
+            let manager = config
+                .get_manager_mut(&AppType::Claude)
+                .expect("claude manager");
+            manager.current = "b".to_string();
+            for id in ids {
+                manager.providers.insert(
+                    id.to_string(),
+                    Provider::with_id(
+                        id.to_string(),
+                        id.to_uppercase(),
+                        json!({"env": {"ANTHROPIC_BASE_URL": "https://api.test", "ANTHROPIC_AUTH_TOKEN": id}}),
+                        None,
+                    ),
+                );
+            }
+        }
+
+        let state = create_test_state_with_config(&config).expect("create test state");
+        let export_path = home.join("deterministic.sql");
+        state
+            .db
+            .export_sql_with_options(
+                &export_path,
+                SqlExportOptions {
+                    normalize_timestamps: true,
+                },
+            )
+            .expect("export should succeed");
+
+        let content = fs::read_to_string(&export_path).expect("read exported file");
+        assert!(
+            !content.contains("生成时间"),
+            "normalized export should omit the generation time"
+        );
+        content
+            .lines()
+            .filter(|line| line.starts_with("INSERT INTO \"providers\""))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let first = export_providers(&["c", "a", "b"]);
+    let second = export_providers(&["b", "c", "a"]);
+    assert!(!first.is_empty(), "export should contain provider rows");
+    assert_eq!(
+        first, second,
+        "provider rows should be exported in a stable order"
+    );
+}
@@ -0,0 +1,35 @@
+#![cfg(feature = "test-util")]
+
+use cc_switch_lib::test_util::{
+    assert_live_round_trip, first_mismatch, fixture_provider, TestHome,
+};
+use cc_switch_lib::AppType;
+use serde_json::json;
+
+#[test]
+fn live_config_round_trips_for_every_app() {
+    let _home = TestHome::new();
+
+    for app_type in [AppType::Claude, AppType::Codex, AppType::Gemini] {
+        let provider = fixture_provider(&app_type);
+        assert_live_round_trip(&app_type, &provider);
+    }
+}
+
+#[test]
+fn first_mismatch_uses_containment_semantics() {
+    let expected = json!({ "env": { "KEY": "a" } });
+
+    assert_eq!(
+        first_mismatch(&expected, &json!({ "env": { "KEY": "a", "EXTRA": 1 } }), ""),
+        None
+    );
+    assert_eq!(
+        first_mismatch(&expected, &json!({ "env": { "KEY": "b" } }), ""),
+        Some("/env/KEY".to_string())
+    );
+    assert_eq!(
+        first_mismatch(&expected, &json!({}), ""),
+        Some("/env".to_string())
+    );
+}
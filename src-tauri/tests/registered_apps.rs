@@ -0,0 +1,191 @@
+use serde_json::{json, Value};
+
+use cc_switch_lib::{read_json_file, run_cli, Provider};
+
+#[path = "support.rs"]
+mod support;
+use support::{create_test_state, ensure_test_home, reset_test_fs, test_mutex};
+
+fn cli(args: &[&str]) -> Option<i32> {
+    run_cli(args.iter().map(|arg| arg.to_string()).collect())
+}
+
+#[test]
+fn cli_lists_and_switches_opencode_providers() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let home = ensure_test_home();
+
+    let state = create_test_state().expect("create test state");
+    for (id, url, model) in [
+        ("relay", "https://relay.example/v1", "cc-switch/gpt-5"),
+        ("local", "http://localhost:8080/v1", "cc-switch/qwen3-coder"),
+    ] {
+        let provider = Provider::with_id(
+            id.to_string(),
+            id.to_string(),
+            json!({
+                "provider": { "cc-switch": { "options": { "baseURL": url } } },
+                "model": model
+            }),
+            None,
+        );
+        state
+            .db
+            .save_provider("opencode", &provider)
+            .expect("save opencode provider");
+    }
+
+    assert_eq!(cli(&["list", "--app", "opencode", "--json"]), Some(0));
+    assert_eq!(
+        cli(&["switch", "local", "--app", "opencode", "--json"]),
+        Some(0)
+    );
+
+    let live: Value = read_json_file(&home.join(".config/opencode/opencode.json"))
+        .expect("read opencode live config");
+    assert_eq!(live["model"], json!("cc-switch/qwen3-coder"));
+    assert_eq!(
+        live["provider"]["cc-switch"]["options"]["baseURL"],
+        json!("http://localhost:8080/v1")
+    );
+    assert_eq!(
+        state
+            .db
+            .get_current_provider("opencode")
+            .expect("read current provider")
+            .as_deref(),
+        Some("local")
+    );
+
+    // 只对内置应用有意义的选项以用法错误拒绝
+    assert_eq!(
+        cli(&[
+            "list",
+            "--app",
+            "opencode",
+            "--group-by",
+            "vendor",
+            "--json"
+        ]),
+        Some(2)
+    );
+}
+
+#[test]
+fn cli_switches_qwen_env_and_patches_settings() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let home = ensure_test_home();
+
+    let qwen_dir = home.join(".qwen");
+    std::fs::create_dir_all(&qwen_dir).expect("create qwen dir");
+    std::fs::write(qwen_dir.join("settings.json"), r#"{"ui":{"theme":"dark"}}"#)
+        .expect("seed qwen settings");
+
+    let state = create_test_state().expect("create test state");
+    let provider = Provider::with_id(
+        "dashscope".to_string(),
+        "DashScope".to_string(),
+        json!({
+            "OPENAI_API_KEY": "sk-qwen",
+            "OPENAI_BASE_URL": "https://dashscope.aliyuncs.com/compatible-mode/v1",
+            "OPENAI_MODEL": "qwen3-coder-plus"
+        }),
+        None,
+    );
+    state
+        .db
+        .save_provider("qwen", &provider)
+        .expect("save qwen provider");
+
+    assert_eq!(cli(&["list", "--app", "qwen", "--json"]), Some(0));
+    assert_eq!(
+        cli(&["switch", "dashscope", "--app", "qwen", "--json"]),
+        Some(0)
+    );
+    assert_eq!(
+        cli(&["show", "dashscope", "--app", "qwen", "--json"]),
+        Some(0)
+    );
+    assert_eq!(cli(&["current", "--app", "qwen", "--json"]), Some(0));
+
+    let env = std::fs::read_to_string(qwen_dir.join(".env")).expect("read qwen .env");
+    assert!(env.contains("OPENAI_API_KEY=sk-qwen"), "{env}");
+    assert!(
+        env.contains("OPENAI_BASE_URL=https://dashscope.aliyuncs.com/compatible-mode/v1"),
+        "{env}"
+    );
+    let settings: Value =
+        read_json_file(&qwen_dir.join("settings.json")).expect("read qwen settings");
+    assert_eq!(
+        settings["security"]["auth"]["selectedType"],
+        json!("openai")
+    );
+    assert_eq!(settings["ui"]["theme"], json!("dark"));
+    assert_eq!(
+        state
+            .db
+            .get_current_provider("qwen")
+            .expect("read current provider")
+            .as_deref(),
+        Some("dashscope")
+    );
+}
+
+#[test]
+fn cli_registry_app_commands_and_explicit_errors_for_the_rest() {
+    let _guard = test_mutex().lock().expect("acquire test mutex");
+    reset_test_fs();
+    let _home = ensure_test_home();
+
+    let state = create_test_state().expect("create test state");
+    for id in ["dashscope", "spare"] {
+        let provider = Provider::with_id(
+            id.to_string(),
+            id.to_string(),
+            json!({
+                "OPENAI_API_KEY": "sk-qwen",
+                "OPENAI_BASE_URL": "https://dashscope.aliyuncs.com/compatible-mode/v1"
+            }),
+            None,
+        );
+        state
+            .db
+            .save_provider("qwen", &provider)
+            .expect("save qwen provider");
+    }
+
+    assert_eq!(
+        cli(&["provider", "list", "--app", "qwen", "--json"]),
+        Some(0)
+    );
+    assert_eq!(
+        cli(&["provider", "show", "dashscope", "--app", "qwen", "--json"]),
+        Some(0)
+    );
+    assert_eq!(
+        cli(&["provider", "delete", "spare", "--app", "qwen", "--json"]),
+        Some(0)
+    );
+
+    // 只支持内置应用的子命令在执行前以用法错误拒绝，不会改动数据
+    let unsupported: [&[&str]; 5] = [
+        &["provider", "history", "dashscope"],
+        &["provider", "pin", "dashscope"],
+        &["provider", "clone", "dashscope"],
+        &["key", "list"],
+        &["provider", "export"],
+    ];
+    for command in unsupported {
+        let mut args = command.to_vec();
+        args.extend(["--app", "qwen", "--json"]);
+        assert_eq!(cli(&args), Some(2), "{command:?}");
+    }
+
+    let providers = state
+        .db
+        .get_all_providers("qwen")
+        .expect("list qwen providers");
+    assert_eq!(providers.keys().collect::<Vec<_>>(), ["dashscope"]);
+}
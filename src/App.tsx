@@ -129,6 +129,28 @@ function App() {
     };
   }, [activeApp, refetch]);
 
+  // 监听命令行等其他进程对供应商的修改
+  useEffect(() => {
+    let unsubscribe: (() => void) | undefined;
+
+    const setupListener = async () => {
+      try {
+        unsubscribe = await providersApi.onDataChanged(async (event) => {
+          if (event.appType === activeApp) {
+            await refetch();
+          }
+        });
+      } catch (error) {
+        console.error("[App] Failed to subscribe data change event", error);
+      }
+    };
+
+    setupListener();
+    return () => {
+      unsubscribe?.();
+    };
+  }, [activeApp, refetch]);
+
   // 应用启动时检测所有应用的环境变量冲突
   useEffect(() => {
     const checkEnvOnStartup = async () => {
@@ -154,6 +176,46 @@ function App() {
     checkEnvOnStartup();
   }, []);
 
+  // 应用启动时检查 live 配置是否被外部改成了未保存的凭据
+  useEffect(() => {
+    const checkUnmanagedLive = async () => {
+      try {
+        const unmanaged = await providersApi.getUnmanagedLive();
+        for (const live of unmanaged) {
+          toast.info(
+            t("provider.unmanagedLive", {
+              defaultValue: "{{app}} 的当前配置（{{url}}）不属于任何供应商",
+              app: live.app,
+              url: live.baseUrl || "official",
+            }),
+            {
+              closeButton: true,
+              duration: Infinity,
+              action: {
+                label: t("provider.adoptLive", {
+                  defaultValue: "保存为供应商",
+                }),
+                onClick: async () => {
+                  try {
+                    await providersApi.adoptLive(live.app);
+                    await providersApi.updateTrayMenu();
+                    await refetch();
+                  } catch (error) {
+                    toast.error(String(error));
+                  }
+                },
+              },
+            },
+          );
+        }
+      } catch (error) {
+        console.error("[App] Failed to check unmanaged live configs:", error);
+      }
+    };
+
+    checkUnmanagedLive();
+  }, []);
+
   // 应用启动时检查是否刚完成了配置迁移
   useEffect(() => {
     const checkMigration = async () => {
@@ -13,11 +13,49 @@ export interface ProviderSwitchEvent {
   providerId: string;
 }
 
+export interface ProviderQueryOptions {
+  offset?: number;
+  limit?: number;
+  sortBy?: "sortIndex" | "name" | "createdAt" | "updatedAt";
+  descending?: boolean;
+  filter?: string;
+}
+
+export interface ProviderPage {
+  total: number;
+  providers: Provider[];
+}
+
+export interface DataChangeEvent {
+  id: number;
+  kind:
+    | "providerAdded"
+    | "providerUpdated"
+    | "providerDeleted"
+    | "providerSwitched";
+  appType: AppId;
+  providerId: string;
+  changedAt: number;
+}
+
+export interface UnmanagedLiveConfig {
+  app: AppId;
+  baseUrl: string;
+  keyFingerprint: string;
+}
+
 export const providersApi = {
   async getAll(appId: AppId): Promise<Record<string, Provider>> {
     return await invoke("get_providers", { app: appId });
   },
 
+  async query(
+    appId: AppId,
+    options: ProviderQueryOptions = {},
+  ): Promise<ProviderPage> {
+    return await invoke("query_providers", { app: appId, options });
+  },
+
   async getCurrent(appId: AppId): Promise<string> {
     return await invoke("get_current_provider", { app: appId });
   },
@@ -42,6 +80,14 @@ export const providersApi = {
     return await invoke("import_default_config", { app: appId });
   },
 
+  async getUnmanagedLive(): Promise<UnmanagedLiveConfig[]> {
+    return await invoke("get_unmanaged_live_configs");
+  },
+
+  async adoptLive(appId: AppId): Promise<Provider | null> {
+    return await invoke("adopt_live_provider", { app: appId });
+  },
+
   async updateTrayMenu(): Promise<boolean> {
     return await invoke("update_tray_menu");
   },
@@ -61,4 +107,13 @@ export const providersApi = {
       handler(payload);
     });
   },
+
+  // 任意进程（例如命令行）修改供应商后由后端推送
+  async onDataChanged(
+    handler: (event: DataChangeEvent) => void,
+  ): Promise<UnlistenFn> {
+    return await listen("data-changed", (event) => {
+      handler(event.payload as DataChangeEvent);
+    });
+  },
 };